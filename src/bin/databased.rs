@@ -38,8 +38,13 @@ fn main() {
     debug!("CTL RPC socket {}", &service_config.ctl_endpoint);
 
     debug!("Starting runtime ...");
-    databased::run(service_config, opts.absolute_data_dir_path())
-        .expect("Error running databased runtime");
+    databased::run(
+        service_config,
+        opts.absolute_data_dir_path(),
+        opts.checkpoint_passphrase.clone(),
+        opts.checkpoint_retention(),
+    )
+    .expect("Error running databased runtime");
 
     unreachable!()
 }
@@ -46,6 +46,12 @@ fn main() -> Result<(), Error> {
     debug!("MSG RPC socket {}", &service_config.msg_endpoint);
     debug!("CTL RPC socket {}", &service_config.ctl_endpoint);
 
+    if opts.check_config {
+        let checks = farcasterd::check_config(&opts);
+        let passed = farcasterd::print_report(&checks);
+        std::process::exit(if passed { 0 } else { 1 });
+    }
+
     debug!("Config file path: {}", &opts.config);
     let config = parse_config(&opts.config)?;
     debug!("Configuration: {:#?}", &config);
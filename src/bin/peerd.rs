@@ -211,8 +211,15 @@ fn main() {
         PeerSocket::Connect(remote_node) => {
             debug!("Peerd running in CONNECT mode");
             debug!("Connecting to {}", &remote_node.addr());
-            peerd::run_from_connect(service_config, remote_node, local_socket, local_node)
-                .expect("Error running peerd runtime");
+            peerd::run_from_connect(
+                service_config,
+                remote_node,
+                local_socket,
+                local_node,
+                opts.rate_limit(),
+                opts.ping(),
+            )
+            .expect("Error running peerd runtime");
             unreachable!()
         }
     };
@@ -234,6 +241,8 @@ fn main() {
         remote_node_addr,
         local_socket,
         local_node,
+        opts.rate_limit(),
+        opts.ping(),
     )
     .expect("Error running peerd runtime");
 
@@ -12,9 +12,9 @@ extern crate log;
 use clap::Parser;
 
 use farcaster_node::cli::Opts;
-use farcaster_node::client::Client;
-use farcaster_node::LogStyle;
+use farcaster_node::client::{Client, REQUEST_TIMEOUT_EXIT_CODE, WAIT_TIMEOUT_EXIT_CODE};
 use farcaster_node::ServiceConfig;
+use farcaster_node::{Error, LogStyle};
 use microservices::shell::Exec;
 
 fn main() {
@@ -28,12 +28,18 @@ fn main() {
     debug!("MSG RPC socket {}", &service_config.msg_endpoint);
     debug!("CTL RPC socket {}", &service_config.ctl_endpoint);
 
-    let mut client = Client::with(service_config).expect("Error initializing client");
+    let mut client = Client::with(service_config)
+        .expect("Error initializing client")
+        .with_timeout(opts.request_timeout());
 
     trace!("Executing command: {:?}", opts.command);
 
     if let Err(err) = opts.command.exec(&mut client) {
         eprintln!("{} {}", "error:".err(), err.err());
-        std::process::exit(1);
+        std::process::exit(match err {
+            Error::RequestTimeout(_) => REQUEST_TIMEOUT_EXIT_CODE,
+            Error::WaitTimeout(..) => WAIT_TIMEOUT_EXIT_CODE,
+            _ => 1,
+        });
     }
 }
@@ -42,10 +42,16 @@ fn main() {
 
     let wallet_token = Token(opts.wallet_token.token);
 
-    let node_secrets = NodeSecrets::new(opts.key_opts.key_file.clone());
+    let restored_seed = opts.restore_mnemonic.as_deref().map(|mnemonic| {
+        NodeSecrets::seed_from_mnemonic(mnemonic).unwrap_or_else(|err| {
+            error!("Unable to restore the wallet seed: {}", err);
+            std::process::exit(2);
+        })
+    });
+    let node_secrets = NodeSecrets::new(opts.key_opts.key_file.clone(), restored_seed);
 
     debug!("Starting runtime ...");
-    walletd::run(service_config, wallet_token, node_secrets)
+    walletd::run(service_config, wallet_token, node_secrets, opts.account)
         .expect("Error running walletd runtime");
 
     unreachable!()
@@ -37,4 +37,8 @@ pub enum BridgeMsg {
     },
     #[display("Grpc Server Terminated")]
     GrpcServerTerminated,
+    /// Wakes up a client whose pending response, identified by its sequence number, timed
+    /// out
+    #[display("Response Timeout {0}")]
+    ResponseTimeout(u64),
 }
@@ -16,19 +16,21 @@ use farcaster_core::{
     blockchain::Blockchain,
     swap::btcxmr::{Deal, DealParameters, Parameters},
     swap::SwapId,
+    trade::DealId,
 };
 
-use bitcoin::secp256k1::SecretKey;
+use bitcoin::secp256k1::{PublicKey, SecretKey};
 use bitcoin::Transaction;
 use internet2::addr::{InetSocketAddr, NodeAddr};
 use strict_encoding::{NetworkDecode, NetworkEncode};
 
 use crate::bus::p2p::{PeerMsg, TakerCommit};
 use crate::bus::{
-    AddressSecretKey, CheckpointEntry, DealInfo, Failure, OptionDetails, Outcome, Progress,
+    AddressSecretKey, CheckpointEntry, DealBookEntry, DealInfo, Failure, OptionDetails, Outcome,
+    PeerRateLimited, Progress, SwapStateChanged,
 };
 use crate::swapd::CheckpointSwapd;
-use crate::syncerd::{Health, SweepAddressAddendum};
+use crate::syncerd::{Health, SetPollIntervals, SweepAddressAddendum};
 use crate::{Error, ServiceId};
 
 use super::p2p::Commit;
@@ -39,9 +41,22 @@ pub enum CtlMsg {
     #[display("hello()")]
     Hello,
 
+    #[display("heartbeat()")]
+    Heartbeat,
+
+    /// Sent by every service to farcasterd along with its Hello, carries the version of the
+    /// service
+    #[display("service_version({0})")]
+    ServiceVersion(String),
+
     #[display("terminate()")]
     Terminate,
 
+    /// A message sent from farcaster to syncers to drop the tasks of an ended swap, without
+    /// terminating the syncer serving the other swaps
+    #[display("terminate_swap({0})")]
+    TerminateSwap(SwapId),
+
     #[display(inner)]
     Success(OptionDetails),
 
@@ -77,8 +92,26 @@ pub enum CtlMsg {
     #[display("peerd_unreachable({0})")]
     PeerdUnreachable(ServiceId),
 
-    #[display("peerd_reconnected({0})")]
-    PeerdReconnected(ServiceId),
+    /// Sent by farcasterd to a swap once a new peerd serves the connection with its
+    /// counterparty, e.g. after a reconnection. The swap sends its peer messages to the new peerd.
+    #[display("update_peerd({0})")]
+    UpdatePeerd(UpdatePeerd),
+
+    /// Sent by farcasterd to a swap once its syncer of a blockchain became unhealthy and a
+    /// fallback syncer of the same network takes over. The swap moves its tasks of the
+    /// blockchain to the new syncer.
+    #[display("update_syncer({0})")]
+    UpdateSyncer(UpdateSyncer),
+
+    /// Sent by farcasterd to a swap once its syncer became unhealthy without a healthy fallback
+    /// syncer to take over. The swap ignores the events of the syncer until it is resumed.
+    #[display("pause_syncer({0})")]
+    PauseSyncer(SwapSyncer),
+
+    /// Sent by farcasterd to a swap paused on a syncer once the syncer is healthy again. The swap
+    /// issues its tasks of the blockchain to the syncer again to catch up.
+    #[display("resume_syncer({0})")]
+    ResumeSyncer(SwapSyncer),
 
     #[display("peerd_terminated()")]
     PeerdTerminated,
@@ -89,6 +122,10 @@ pub enum CtlMsg {
     #[display("re-connected")]
     Reconnected,
 
+    /// Sent by peerd to farcasterd when the remote peer exceeds the inbound message rate limit
+    #[display("peer_rate_limited({0})")]
+    PeerRateLimited(PeerRateLimited),
+
     #[display("connect({0})")]
     Connect(SwapId),
 
@@ -115,12 +152,86 @@ pub enum CtlMsg {
     #[display("get_keys({0})")]
     GetKeys(GetKeys),
 
+    /// Request a rotation of the node keys. Sent without a token by the client to farcasterd,
+    /// which forwards it with the wallet token to walletd.
+    #[display("rotate_node_keys()")]
+    RotateNodeKeys(Option<Token>),
+
+    /// Request the wallet seed mnemonic. Sent without a token by the client to farcasterd, which
+    /// forwards it with the wallet token to walletd, which answers the client directly.
+    #[display("export_seed()")]
+    ExportSeed(Option<Token>),
+
+    #[display("seed_mnemonic(..)")]
+    SeedMnemonic(String),
+
+    /// Request a self-check of the swap key derivations. Sent without a token by the client to
+    /// farcasterd, which forwards it with the wallet token to walletd, which answers the client
+    /// directly.
+    #[display("verify_key_manager()")]
+    VerifyKeyManager(Option<Token>),
+
+    /// Read the configuration file again and apply the settings that can change at runtime.
+    /// Sent by the client to farcasterd, which answers with the applied and rejected settings.
+    #[display("reload_config()")]
+    ReloadConfig,
+
+    /// Set the bounds of the poll interval of a running syncer. Sent by farcasterd to the
+    /// syncers when the configuration is reloaded.
+    #[display("set_poll_intervals({0})")]
+    SetPollIntervals(SetPollIntervals),
+
+    /// Filter the log records of a running service with the given filter, in the `RUST_LOG`
+    /// syntax, none falls back to the `RUST_LOG` environment variable. Sent by farcasterd to its
+    /// services when the configuration is reloaded.
+    #[display("set_log_filter({0:?})")]
+    SetLogFilter(Option<String>),
+
     #[display("revoke_deal({0})")]
     RevokeDeal(Deal),
 
+    /// Start an additional listener for incoming peer connections on the socket address. Sent
+    /// by the client to farcasterd, fails if a listener is already bound to it.
+    #[display("listen({0})")]
+    Listen(InetSocketAddr),
+
+    /// Stop the listener bound to the socket address, other listeners are not affected.
+    #[display("stop_listen({0})")]
+    StopListen(StopListen),
+
+    /// Close the connection with a remote peer. Sent by the client to farcasterd, which
+    /// terminates the corresponding peerd.
+    #[display("disconnect_peer({0})")]
+    DisconnectPeer(DisconnectPeer),
+
+    /// Ban a remote node from connecting or being connected to. Sent by the client to
+    /// farcasterd, which forwards it to databased to persist the ban.
+    #[display("ban_peer({0})")]
+    BanPeer(BanPeer),
+
+    /// Lift the ban of a remote node. Sent by the client to farcasterd, which forwards it to
+    /// databased.
+    #[display("unban_peer({0})")]
+    UnbanPeer(PublicKey),
+
+    /// Stop the node. Sent by the client to farcasterd, which terminates its services in order,
+    /// waits for them to exit unless forced, replies with the stopped services and exits.
+    #[display("shutdown(force: {0})")]
+    Shutdown(bool),
+
     #[display("abort_swap()")]
     AbortSwap,
 
+    /// Broadcast the refund transaction of a canceled swap once the cancel transaction is
+    /// final. Sent by the client to Bob's swap, which fails if the refund is not valid yet.
+    #[display("execute_refund({0})")]
+    ExecuteRefund(SwapId),
+
+    /// Broadcast the punish transaction of a canceled swap once the punish timelock expired.
+    /// Sent by the client to Alice's swap, which fails if the punish is not valid yet.
+    #[display("execute_punish({0})")]
+    ExecutePunish(SwapId),
+
     #[display("get_sweep_bitcoin_address({0})")]
     GetSweepBitcoinAddress(bitcoin::Address),
 
@@ -141,6 +252,10 @@ pub enum CtlMsg {
     #[display("swap_outcome({0})")]
     SwapOutcome(Outcome),
 
+    /// A message sent from swapd to farcasterd on every swap state transition
+    #[display("swap_state_changed({0})")]
+    SwapStateChanged(SwapStateChanged),
+
     #[display("checkpoint({0})", alt = "{0:#}")]
     #[from]
     Checkpoint(Checkpoint),
@@ -148,9 +263,19 @@ pub enum CtlMsg {
     #[display("remove_checkpoint")]
     RemoveCheckpoint(SwapId),
 
+    /// Remove the checkpoints of ended swaps and dangling checkpoint data
+    #[display("prune_checkpoints({0})")]
+    PruneCheckpoints(PruneCheckpoints),
+
     #[display("set_deal_history({0})")]
     SetDealInfo(DealInfo),
 
+    #[display("set_deal_book_entry({0})")]
+    SetDealBookEntry(DealBookEntry),
+
+    #[display("remove_deal_book_entry({0})")]
+    RemoveDealBookEntry(DealId),
+
     #[display("keys({0})")]
     Keys(Keys),
 
@@ -164,6 +289,11 @@ pub enum CtlMsg {
     #[display("monero_address({0})")]
     MoneroAddress(MoneroAddress),
 
+    /// Request the Monero address a swap funds, or sweeps the bought Monero to, answered with
+    /// `MoneroAddress`
+    #[display("get_xmr_address({0})")]
+    GetXmrAddress(SwapId),
+
     #[display("funding_completed({0})")]
     FundingCompleted(Blockchain),
 
@@ -181,6 +311,11 @@ pub enum CtlMsg {
 
     #[display("health_result({0})")]
     HealthResult(Health),
+
+    /// Retrieve the block at a height from the default syncer of a network, answered with the
+    /// block info
+    #[display("get_block({0})")]
+    GetBlock(GetBlock),
 }
 
 #[derive(Clone, Debug, Display, NetworkEncode, NetworkDecode)]
@@ -198,6 +333,13 @@ pub struct ProtoDeal {
     pub public_addr: InetSocketAddr,
     pub arbitrating_addr: bitcoin::Address,
     pub accordant_addr: monero::Address,
+    /// Confirmations required on the funding transactions, the network default when none
+    pub funding_confirmations: Option<u32>,
+    /// Syncer instance of the syncers configuration running the swap, the default syncers when
+    /// none
+    pub syncer_instance: Option<String>,
+    /// Unix timestamp after which the deal is not honored anymore, never expires when none
+    pub expiry: Option<u64>,
 }
 
 #[derive(Clone, Debug, Display, NetworkEncode, NetworkDecode)]
@@ -206,6 +348,16 @@ pub struct PubDeal {
     pub deal: Deal,
     pub bitcoin_address: bitcoin::Address,
     pub monero_address: monero::Address,
+    /// Confirmations required on the funding transactions, the network default when none
+    pub funding_confirmations: Option<u32>,
+    /// Syncer instance of the syncers configuration running the swap, the default syncers when
+    /// none
+    pub syncer_instance: Option<String>,
+    /// Only run the preflight checks of taking the deal, without starting the swap
+    pub dry_run: bool,
+    /// Take the deal again even though it was already taken, as long as no swap of it is in
+    /// progress
+    pub force: bool,
 }
 
 #[derive(Clone, Debug, Display, NetworkEncode, NetworkDecode)]
@@ -220,11 +372,96 @@ pub struct Token(pub String);
 #[display("token({0})")]
 pub struct GetKeys(pub Token);
 
+#[derive(Clone, Debug, Display, NetworkEncode, NetworkDecode)]
+#[display("{bind_addr}, keep_connections: {keep_connections}")]
+pub struct StopListen {
+    pub bind_addr: InetSocketAddr,
+    /// Keep the connections accepted by the listener alive instead of closing them
+    pub keep_connections: bool,
+}
+
+#[derive(Clone, Debug, Display, NetworkEncode, NetworkDecode)]
+#[display("{swap_id}, new_peerd: {new_peerd}")]
+pub struct UpdatePeerd {
+    pub swap_id: SwapId,
+    pub new_peerd: ServiceId,
+}
+
+#[derive(Clone, Debug, Display, NetworkEncode, NetworkDecode)]
+#[display("{swap_id}, new_syncer: {new_syncer}")]
+pub struct UpdateSyncer {
+    pub swap_id: SwapId,
+    pub new_syncer: ServiceId,
+}
+
+#[derive(Clone, Debug, Display, NetworkEncode, NetworkDecode)]
+#[display("{swap_id}, syncer: {syncer}")]
+pub struct SwapSyncer {
+    pub swap_id: SwapId,
+    pub syncer: ServiceId,
+}
+
+#[derive(Clone, Debug, Display, NetworkEncode, NetworkDecode)]
+#[display("{node_addr}, force: {force}")]
+pub struct DisconnectPeer {
+    pub node_addr: NodeAddr,
+    /// Disconnect even if swaps are running over the connection
+    pub force: bool,
+}
+
+#[derive(Clone, Debug, Display, NetworkEncode, NetworkDecode)]
+#[display("{node_id}, abort_swaps: {abort_swaps}")]
+pub struct BanPeer {
+    pub node_id: PublicKey,
+    /// Also abort the running swaps with the banned node that have not locked yet
+    pub abort_swaps: bool,
+}
+
+#[derive(Clone, Debug, Display, NetworkEncode, NetworkDecode)]
+#[display("{blockchain} {network}, height: {height}, include_txs: {include_txs}")]
+pub struct GetBlock {
+    pub blockchain: Blockchain,
+    pub network: Network,
+    pub height: u64,
+    /// Include the transactions of the block, only served by bitcoin rpc backends
+    pub include_txs: bool,
+}
+
+#[derive(Clone, Debug, Display, NetworkEncode, NetworkDecode)]
+#[display("keep_terminal: {keep_terminal}")]
+pub struct PruneCheckpoints {
+    /// Keep the checkpoints of swaps in a terminal state, only remove dangling checkpoint data
+    pub keep_terminal: bool,
+}
+
 #[derive(Clone, Debug, Display, NetworkEncode, NetworkDecode)]
 #[display("{deal}, ..")]
 pub struct SwapKeys {
     pub key_manager: WrappedKeyManager,
     pub deal: Deal,
+    pub monero_deposit: MoneroDeposit,
+}
+
+/// Subaddress of the node Monero wallet dedicated to a swap, with the view key of the wallet
+#[derive(Clone, Debug, Display, NetworkEncode, NetworkDecode, Eq, PartialEq)]
+#[display("{address}")]
+pub struct MoneroDeposit {
+    pub address: monero::Address,
+    pub view_key: monero::PrivateKey,
+}
+
+impl MoneroDeposit {
+    /// Standard address made of the spend key of the subaddress and the view key of the wallet.
+    /// Outputs paying to the subaddress are found scanning with the view key as if they paid to
+    /// this address, so a view-only wallet of it watches the subaddress. Wallet-rpc cannot open
+    /// a view-only wallet from the subaddress itself.
+    pub fn watch_address(&self) -> monero::Address {
+        monero::Address::standard(
+            self.address.network,
+            self.address.public_spend,
+            monero::PublicKey::from_private_key(&self.view_key),
+        )
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -258,6 +495,7 @@ pub struct InitTakerSwap {
     pub key_manager: WrappedKeyManager,
     pub target_bitcoin_address: bitcoin::Address,
     pub target_monero_address: monero::Address,
+    pub monero_deposit: MoneroDeposit,
 }
 
 #[derive(Clone, Debug, Display, NetworkEncode, NetworkDecode)]
@@ -269,6 +507,7 @@ pub struct InitMakerSwap {
     pub key_manager: WrappedKeyManager,
     pub target_bitcoin_address: bitcoin::Address,
     pub target_monero_address: monero::Address,
+    pub monero_deposit: MoneroDeposit,
     pub commit: Commit,
 }
 
@@ -4,20 +4,24 @@
 // license that can be found in the LICENSE file or at
 // https://opensource.org/licenses/MIT.
 
+use std::collections::BTreeMap;
 use std::str::FromStr;
 use std::time::Duration;
 
 use amplify::ToYamlString;
+use bitcoin::secp256k1::PublicKey;
+use farcaster_core::blockchain::{Blockchain, Network};
 use farcaster_core::role::{SwapRole, TradeRole};
 use farcaster_core::trade::DealId;
-use farcaster_core::{blockchain::Blockchain, swap::btcxmr::Deal, swap::SwapId};
+use farcaster_core::{swap::btcxmr::Deal, swap::SwapId};
 use internet2::addr::{InetSocketAddr, NodeAddr, NodeId};
 #[cfg(feature = "serde")]
-use serde_with::{DisplayFromStr, DurationSeconds};
+use serde_with::{DisplayFromStr, DurationMilliSeconds, DurationSeconds};
 use strict_encoding::{NetworkDecode, NetworkEncode};
 
 use crate::bus::{
-    AddressSecretKey, CheckpointEntry, DealInfo, Failure, List, OptionDetails, Progress,
+    AddressSecretKey, CheckpointEntry, CheckpointStateInfo, DealBookEntry, DealInfo, Failure, List,
+    OptionDetails, Progress, SwapStateChanged,
 };
 use crate::cli::DealSelector;
 use crate::farcasterd::stats::Stats;
@@ -37,21 +41,46 @@ pub enum InfoMsg {
     #[display("get_info()")]
     GetInfo,
 
+    /// Request the info of a running swap, forwarded by farcasterd to the swap
+    #[display("get_swap_info({0})")]
+    GetSwapInfo(SwapId),
+
+    /// Request the info of a connected peer, forwarded by farcasterd to its peerd
+    #[display("get_peer_info({0})")]
+    GetPeerInfo(NodeAddr),
+
     #[display("list_peers()")]
     ListPeers,
 
     #[display("list_swaps()")]
     ListSwaps,
 
+    /// List the running swaps with their lifecycle, role, counterparty and amounts
+    #[display("list_swap_summaries()")]
+    ListSwapSummaries,
+
     #[display("list_tasks()")]
     ListTasks,
 
+    #[display("get_syncer_health()")]
+    GetSyncerHealth,
+
+    /// List the syncers launched by farcasterd, running or not registered yet
+    #[display("list_syncers()")]
+    ListSyncers,
+
     #[display("list_deals({0})")]
     ListDeals(DealStatusSelector),
 
+    #[display("list_deal_book()")]
+    ListDealBook,
+
     #[display("list_listens()")]
     ListListens,
 
+    #[display("list_banned_peers()")]
+    ListBannedPeers,
+
     #[display("retrieve_all_checkpoint_info")]
     RetrieveAllCheckpointInfo,
 
@@ -61,18 +90,26 @@ pub enum InfoMsg {
     #[display("get_addresses({0})")]
     GetAddresses(Blockchain),
 
-    #[display("needs_funding({0})")]
-    NeedsFunding(Blockchain),
+    /// Swaps awaiting funding on the blockchain, on both blockchains when none
+    #[display("needs_funding(..)")]
+    NeedsFunding(Option<Blockchain>),
 
     #[display("get_checkpoint_entry({0})")]
     GetCheckpointEntry(SwapId),
 
+    #[display("list_checkpoints()")]
+    ListCheckpoints,
+
     // Progress functionalities
     // ----------------
     // Returns a SwapProgress message
     #[display("read_progress({0})")]
     ReadProgress(SwapId),
 
+    /// Returns a SwapProgress message with the last entries of the progress only
+    #[display(inner)]
+    ReadLastProgress(ReadLastProgress),
+
     #[display(inner)]
     SwapProgress(SwapProgress),
 
@@ -85,6 +122,9 @@ pub enum InfoMsg {
     #[display(inner)]
     Progress(Progress),
 
+    #[display(inner)]
+    SwapStateChanged(SwapStateChanged),
+
     #[display(inner)]
     Success(OptionDetails),
 
@@ -121,6 +161,9 @@ pub enum InfoMsg {
     #[from]
     SwapInfo(SwapInfo),
     // - End GetInfo section
+    #[display("syncer_health(..)")]
+    #[from]
+    SyncerHealth(SyncerHealth),
 
     // - ListPeers section
     #[display(inner)]
@@ -134,12 +177,24 @@ pub enum InfoMsg {
     SwapList(List<SwapId>),
     // - End ListSwap section
 
+    // - ListSwapSummaries section
+    #[display(inner)]
+    #[from]
+    SwapSummaryList(List<SwapSummary>),
+    // - End ListSwapSummaries section
+
     // - ListTasks section
     #[display(inner)]
     #[from]
     TaskList(List<SyncerdTask>),
     // - End ListTasks section
 
+    // - ListSyncers section
+    #[display(inner)]
+    #[from]
+    SyncerList(List<SyncerEntry>),
+    // - End ListSyncers section
+
     // - ListDeals section
     #[display(inner)]
     #[from]
@@ -149,14 +204,30 @@ pub enum InfoMsg {
     DealInfoList(List<DealInfo>),
     // - End ListDeals section
 
+    // - ListDealBook section
+    #[display(inner)]
+    #[from]
+    DealBook(List<DealBookEntry>),
+    // - End ListDealBook section
+
     // - ListListen section
     #[display(inner)]
     #[from]
     ListenList(List<String>),
     // - End ListListen section
+
+    // - ListBannedPeers section
+    #[display(inner)]
+    BannedPeerList(List<PublicKey>),
+    // - End ListBannedPeers section
     #[display(inner)]
     CheckpointList(List<CheckpointEntry>),
 
+    // - ListCheckpoints section
+    #[display(inner)]
+    CheckpointStateList(List<CheckpointStateInfo>),
+    // - End ListCheckpoints section
+
     // - GetAddressSecretKey section
     #[display("address_secret_key")]
     AddressSecretKey(AddressSecretKey),
@@ -179,6 +250,9 @@ pub enum InfoMsg {
 
     #[display("{0}")]
     AddressBalance(AddressBalance),
+
+    #[display("{0}")]
+    BlockInfo(BlockInfo),
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, Display, NetworkEncode, NetworkDecode)]
@@ -228,6 +302,8 @@ pub struct MadeDeal {
 #[display(TookDeal::to_yaml_string)]
 pub struct TookDeal {
     pub deal_id: DealId,
+    /// The id of the swap of the deal, to query and follow the swap with
+    pub swap_id: SwapId,
     pub message: String,
 }
 
@@ -247,6 +323,72 @@ pub struct SyncerInfo {
     pub tasks: Vec<SyncerdTask>,
 }
 
+#[derive(Clone, PartialEq, Eq, Debug, Display, NetworkEncode, NetworkDecode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display(SyncerHealth::to_yaml_string)]
+pub struct SyncerHealth {
+    pub syncer: String,
+    /// Whether the syncer backend answered the tip request
+    pub connected: bool,
+    pub synced_height: u64,
+    /// Height of the backend chain tip, none if the backend is unreachable
+    pub tip_height: Option<u64>,
+    /// Number of blocks the syncer is behind the backend tip
+    pub lag: Option<u64>,
+    /// Reason the backend is unreachable
+    pub error: Option<String>,
+}
+
+/// Block retrieved by a syncer at a height, with its data hex encoded
+#[derive(Clone, PartialEq, Eq, Debug, Display, NetworkEncode, NetworkDecode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display(BlockInfo::to_yaml_string)]
+pub struct BlockInfo {
+    pub height: u64,
+    pub hash: String,
+    /// Consensus serialized block header
+    pub header: String,
+    /// Number of transactions in the block, none if the backend does not report it
+    pub tx_count: Option<u64>,
+    /// Consensus serialized transactions of the block, only if requested
+    pub txs: Option<Vec<String>>,
+}
+
+/// Syncer launched by farcasterd, the client completes the health of the registered ones
+#[cfg_attr(feature = "serde", serde_as)]
+#[derive(Clone, PartialEq, Eq, Debug, Display, NetworkEncode, NetworkDecode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display(SyncerEntry::to_yaml_string)]
+pub struct SyncerEntry {
+    #[serde_as(as = "DisplayFromStr")]
+    pub blockchain: Blockchain,
+    #[serde_as(as = "DisplayFromStr")]
+    pub network: Network,
+    /// Name of the syncer instance in the syncers configuration, none for the default syncer
+    pub instance: Option<String>,
+    /// Whether the syncer announced itself with Hello, a syncer still launching did not yet
+    pub registered: bool,
+    /// Time elapsed since the last heartbeat of the syncer
+    #[serde_as(as = "Option<DurationSeconds>")]
+    pub last_heartbeat: Option<Duration>,
+    /// Whether the syncer missed too many heartbeats
+    pub degraded: bool,
+    /// Connection and synchronization of the syncer with its backend, none if it did not answer
+    pub health: Option<SyncerHealth>,
+}
+
 #[cfg_attr(feature = "serde", serde_as)]
 #[derive(Clone, PartialEq, Eq, Debug, Display, NetworkEncode, NetworkDecode)]
 #[cfg_attr(
@@ -256,6 +398,9 @@ pub struct SyncerInfo {
 )]
 #[display(NodeInfo::to_yaml_string)]
 pub struct NodeInfo {
+    /// Public key identifying the node to its peers, known once walletd sent the node keys
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub node_id: Option<PublicKey>,
     pub listens: Vec<InetSocketAddr>,
     #[serde_as(as = "DurationSeconds")]
     pub uptime: Duration,
@@ -263,10 +408,59 @@ pub struct NodeInfo {
     #[serde_as(as = "Vec<DisplayFromStr>")]
     pub peers: Vec<NodeAddr>,
     pub swaps: Vec<SwapId>,
+    /// Number of running swaps at each stage
+    #[serde(default)]
+    pub swaps_by_state: BTreeMap<SwapLifecycle, usize>,
+    /// Swaps taken or accepted and not ended yet, including the ones being set up
+    pub active_swaps: usize,
+    /// Maximum number of swaps taken or accepted concurrently
+    pub max_concurrent_swaps: usize,
     #[serde_as(as = "Vec<DisplayFromStr>")]
     pub deals: Vec<Deal>,
     #[serde(alias = "statistics")]
     pub stats: Stats,
+    /// Time elapsed since the last heartbeat of each running service
+    #[serde_as(as = "BTreeMap<_, DurationSeconds>")]
+    pub heartbeats: BTreeMap<String, Duration>,
+    /// Services that missed too many heartbeats
+    pub degraded: Vec<String>,
+    /// Version of farcasterd, crate version and git commit
+    pub version: String,
+    /// Versions reported by the connected services
+    pub service_versions: BTreeMap<String, String>,
+}
+
+/// Summary of the node assembled by the client from the node, swaps and syncers info
+#[derive(Clone, PartialEq, Eq, Debug, Display)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display(NodeStatus::to_yaml_string)]
+pub struct NodeStatus {
+    pub node: NodeInfo,
+    /// Running swaps grouped by their current state
+    pub swaps: BTreeMap<String, Vec<SwapId>>,
+    /// Health of the running syncers
+    pub syncers: Vec<SyncerHealth>,
+}
+
+/// Side that established a peer connection
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Display, NetworkEncode, NetworkDecode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub enum ConnectionDirection {
+    /// The remote peer connected to our listener, it reconnects on its own when the connection
+    /// is lost
+    #[display("Inbound")]
+    Inbound,
+    /// We connected to the listener of the remote peer and reconnect when the connection is lost
+    #[display("Outbound")]
+    Outbound,
 }
 
 #[cfg_attr(feature = "serde", serde_as)]
@@ -289,8 +483,23 @@ pub struct PeerInfo {
     pub since: u64,
     pub messages_sent: usize,
     pub messages_received: usize,
-    pub forked_from_listener: bool,
+    /// Messages received from the remote peer and dropped for exceeding the rate limit
+    pub messages_dropped: usize,
+    /// Encoded size of the messages sent to the remote peer
+    pub bytes_sent: u64,
+    /// Encoded size of the messages received from the remote peer
+    pub bytes_received: u64,
+    /// Whether the remote peer dialed our listener or we dialed theirs
+    pub direction: ConnectionDirection,
     pub awaits_pong: bool,
+    /// Round-trip time of the last answered ping
+    #[serde_as(as = "Option<DurationMilliSeconds>")]
+    pub last_ping_rtt: Option<Duration>,
+    /// Round-trip times of the recent answered pings, oldest first
+    #[serde_as(as = "Vec<DurationMilliSeconds>")]
+    pub ping_rtts: Vec<Duration>,
+    /// Pings the remote peer never answered
+    pub ping_timeouts: usize,
 }
 
 #[cfg_attr(feature = "serde", serde_as)]
@@ -314,6 +523,61 @@ pub struct SwapInfo {
     pub local_swap_role: SwapRole,
     pub connected_counterparty_node_id: Option<NodeId>,
     pub state: StateReport,
+    /// Subaddress of the node Monero wallet dedicated to the swap
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub monero_deposit_address: Option<monero::Address>,
+}
+
+/// Request of the last progress entries of a swap
+#[derive(Clone, PartialEq, Eq, Debug, Display, NetworkEncode, NetworkDecode)]
+#[display("read_last_progress({swap_id}, {count})")]
+pub struct ReadLastProgress {
+    pub swap_id: SwapId,
+    pub count: u32,
+}
+
+/// Stage of a running swap as tracked by farcasterd
+#[derive(
+    Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Display, NetworkEncode, NetworkDecode,
+)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub enum SwapLifecycle {
+    /// Swapd and its syncers are launching
+    #[display("Launching")]
+    Launching,
+    /// Swapd and its syncers are launching to resume the swap from a checkpoint
+    #[display("Restoring")]
+    Restoring,
+    /// Swapd runs the swap
+    #[display("Running")]
+    Running,
+}
+
+/// Summary of a running swap, listed without querying each swapd
+#[cfg_attr(feature = "serde", serde_as)]
+#[derive(Clone, PartialEq, Eq, Debug, Display, NetworkEncode, NetworkDecode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display(SwapSummary::to_yaml_string)]
+pub struct SwapSummary {
+    pub swap_id: SwapId,
+    pub lifecycle: SwapLifecycle,
+    pub local_swap_role: SwapRole,
+    pub local_trade_role: TradeRole,
+    /// Node id of the counterparty, none for a restored maker swap not yet reconnected
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub counterparty_node_id: Option<NodeId>,
+    #[serde(with = "bitcoin::util::amount::serde::as_btc")]
+    pub arbitrating_amount: bitcoin::Amount,
+    #[serde(with = "monero::util::amount::serde::as_xmr")]
+    pub accordant_amount: monero::Amount,
 }
 
 #[cfg_attr(feature = "serde", serde_as)]
@@ -396,12 +660,41 @@ impl FromStr for Address {
     derive(Serialize, Deserialize),
     serde(crate = "serde_crate")
 )]
-#[display(AddressBalance::to_yaml_string)]
+#[display(AddressBalance::to_denominated_yaml_string)]
 pub struct AddressBalance {
     pub address: Address,
+    /// Balance in atomic units of the address blockchain, satoshi or piconero
     pub balance: u64,
 }
 
+/// Displayed form of an [`AddressBalance`], with the balance in BTC or XMR
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+#[serde(crate = "serde_crate")]
+struct DenominatedAddressBalance<'a> {
+    address: &'a Address,
+    balance: String,
+}
+
+impl AddressBalance {
+    /// The balance in the denomination of the address blockchain, converted without rounding
+    pub fn denominated_balance(&self) -> String {
+        match self.address {
+            Address::Bitcoin(_) => bitcoin::Amount::from_sat(self.balance).to_string(),
+            Address::Monero(_) => monero::Amount::from_pico(self.balance).to_string(),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_denominated_yaml_string(&self) -> String {
+        DenominatedAddressBalance {
+            address: &self.address,
+            balance: self.denominated_balance(),
+        }
+        .to_yaml_string()
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Display, NetworkEncode, NetworkDecode)]
 pub enum DealStatusSelector {
     #[display("Open")]
@@ -463,16 +756,28 @@ impl ToYamlString for TookDeal {}
 #[cfg(feature = "serde")]
 impl ToYamlString for CheckpointEntry {}
 #[cfg(feature = "serde")]
+impl ToYamlString for CheckpointStateInfo {}
+#[cfg(feature = "serde")]
 impl ToYamlString for SwapProgress {}
 #[cfg(feature = "serde")]
 impl ToYamlString for NodeInfo {}
 #[cfg(feature = "serde")]
+impl ToYamlString for NodeStatus {}
+#[cfg(feature = "serde")]
+impl ToYamlString for DenominatedAddressBalance<'_> {}
+#[cfg(feature = "serde")]
 impl ToYamlString for PeerInfo {}
 #[cfg(feature = "serde")]
 impl ToYamlString for SwapInfo {}
 #[cfg(feature = "serde")]
+impl ToYamlString for SwapSummary {}
+#[cfg(feature = "serde")]
 impl ToYamlString for SyncerInfo {}
 #[cfg(feature = "serde")]
+impl ToYamlString for BlockInfo {}
+#[cfg(feature = "serde")]
+impl ToYamlString for SyncerHealth {}
+#[cfg(feature = "serde")]
 impl ToYamlString for ProgressEvent {}
 #[cfg(feature = "serde")]
 impl ToYamlString for FundingInfos {}
@@ -98,7 +98,7 @@ impl microservices::rpc::Request for BusMsg {}
 impl From<crate::Error> for BusMsg {
     fn from(err: crate::Error) -> Self {
         BusMsg::Ctl(CtlMsg::Failure(Failure {
-            code: FailureCode::Unknown,
+            code: (&err).into(),
             info: err.to_string(),
         }))
     }
@@ -4,14 +4,18 @@
 // license that can be found in the LICENSE file or at
 // https://opensource.org/licenses/MIT.
 
+use bitcoin::hashes::{sha256, Hash, HashEngine};
 use farcaster_core::{
+    consensus::CanonicalBytes,
+    crypto::{ArbitratingKeyId, Commit as _, CommitmentEngine, GenerateKey, KeccakCommitment},
     protocol::message::Abort,
     swap::btcxmr::message::{
         BuyProcedureSignature, CommitAliceParameters, CommitBobParameters, CoreArbitratingSetup,
         RefundProcedureSignatures, RevealAliceParameters, RevealBobParameters,
     },
-    swap::btcxmr::Deal,
+    swap::btcxmr::{Deal, KeyManager},
     swap::SwapId,
+    Uuid,
 };
 use internet2::Api;
 use strict_encoding::{StrictDecode, StrictEncode};
@@ -162,6 +166,12 @@ impl TakerCommit {
     pub fn swap_id(&self) -> SwapId {
         self.commit.swap_id()
     }
+
+    /// The swap id derived from the deal and the commitment of the taker, the one the taker must
+    /// commit to
+    pub fn derived_swap_id(&self) -> SwapId {
+        derive_swap_id(&self.deal, &self.commit.buy_commitment())
+    }
 }
 
 #[derive(Clone, Debug, Display, StrictEncode, StrictDecode)]
@@ -179,6 +189,51 @@ impl Commit {
             Self::BobParameters(c) => c.swap_id,
         }
     }
+
+    /// Commitment to the buy key of the committing party
+    pub fn buy_commitment(&self) -> KeccakCommitment {
+        match self {
+            Self::AliceParameters(c) => c.buy,
+            Self::BobParameters(c) => c.buy,
+        }
+    }
+}
+
+/// Commitment to the buy key of the swap keys, the same the swap commits to in its parameters
+pub fn buy_key_commitment(
+    key_manager: &KeyManager,
+) -> Result<KeccakCommitment, farcaster_core::crypto::Error> {
+    let buy = key_manager.clone().get_pubkey(ArbitratingKeyId::Buy)?;
+    Ok(CommitmentEngine.commit_to(buy.as_canonical_bytes()))
+}
+
+/// Deterministic id of the swap executing a deal.
+///
+/// Both peers derive the id from the deal and from the commitments of the two parties before any
+/// funding. The maker is committed by the deal itself, which carries its node key and the uuid
+/// it drew for the deal. The taker is committed by its commitment to its buy key, the first of
+/// the commitments of its taker commit and a fresh one for every set of swap keys. The id is the
+/// first 16 bytes of the SHA-256 of the `farcaster-swap-id` tag, the strict encoding of the deal
+/// and the 32 bytes of the taker commitment, read as a uuid:
+///
+/// - the taker derives it once its swap keys are generated and commits to it in its taker
+///   commit;
+/// - the maker derives it again from the deal and the taker commit it receives, and turns down a
+///   taker commit to any other id.
+///
+/// A deal taken again with new keys thus runs under a new swap id.
+pub fn derive_swap_id(deal: &Deal, taker_commitment: &KeccakCommitment) -> SwapId {
+    let mut encoded_deal = vec![];
+    deal.strict_encode(&mut encoded_deal)
+        .expect("deal encodes in memory");
+    let mut engine = sha256::Hash::engine();
+    engine.input(b"farcaster-swap-id");
+    engine.input(&encoded_deal);
+    engine.input(taker_commitment.as_bytes());
+    let hash = sha256::Hash::from_engine(engine).into_inner();
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&hash[..16]);
+    SwapId(Uuid::from(uuid::Uuid::from_bytes(bytes)))
 }
 
 #[derive(Clone, Debug, Display, StrictEncode, StrictDecode)]
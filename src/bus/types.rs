@@ -6,6 +6,7 @@
 
 use std::{
     fmt::{self, Debug, Display, Formatter},
+    io::{self, Read},
     str::FromStr,
 };
 
@@ -19,12 +20,12 @@ use amplify::{ToYamlString, Wrapper};
 use internet2::addr::NodeId;
 use microservices::rpc;
 use serde_with::DisplayFromStr;
-use strict_encoding::{NetworkDecode, NetworkEncode};
+use strict_encoding::{NetworkDecode, NetworkEncode, StrictDecode};
 
 use crate::swapd::StateReport;
 use crate::syncerd::Health;
 
-#[derive(Clone, PartialEq, Eq, Debug, Display, NetworkEncode, NetworkDecode)]
+#[derive(Clone, PartialEq, Eq, Debug, Display, NetworkEncode)]
 #[display("{swap_id}, {deal}")]
 #[cfg_attr(
     feature = "serde",
@@ -37,6 +38,63 @@ pub struct CheckpointEntry {
     pub deal: Deal,
     pub trade_role: TradeRole,
     pub expected_counterparty_node_id: Option<NodeId>,
+    /// The syncer instance the swap watches the arbitrating chain on, updated on failover so
+    /// the swap is restored on the syncer it last used
+    pub arbitrating_syncer_instance: Option<String>,
+    /// The syncer instance the swap watches the accordant chain on, updated on failover
+    pub accordant_syncer_instance: Option<String>,
+}
+
+impl StrictDecode for CheckpointEntry {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, strict_encoding::Error> {
+        let swap_id = StrictDecode::strict_decode(&mut d)?;
+        let deal = StrictDecode::strict_decode(&mut d)?;
+        let trade_role = StrictDecode::strict_decode(&mut d)?;
+        let expected_counterparty_node_id = StrictDecode::strict_decode(&mut d)?;
+        // entries stored before syncer instances end here, their swaps run on the default
+        // syncers
+        let arbitrating_syncer_instance =
+            strict_decode_trailing::<Option<String>, _>(&mut d)?.flatten();
+        let accordant_syncer_instance =
+            strict_decode_trailing::<Option<String>, _>(&mut d)?.flatten();
+        Ok(Self {
+            swap_id,
+            deal,
+            trade_role,
+            expected_counterparty_node_id,
+            arbitrating_syncer_instance,
+            accordant_syncer_instance,
+        })
+    }
+}
+
+/// Decode a field appended to a stored structure, or `None` if the data ends before it because
+/// it was stored by an older version
+pub(crate) fn strict_decode_trailing<T: StrictDecode, D: io::Read>(
+    d: &mut D,
+) -> Result<Option<T>, strict_encoding::Error> {
+    let mut first = [0u8; 1];
+    if d.read(&mut first)? == 0 {
+        return Ok(None);
+    }
+    T::strict_decode((&first[..]).chain(d)).map(Some)
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Display, NetworkEncode, NetworkDecode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display(CheckpointStateInfo::to_yaml_string)]
+pub struct CheckpointStateInfo {
+    pub swap_id: SwapId,
+    /// The swap state at the time the checkpoint was captured
+    pub state: String,
+    /// Whether the swap reached an end state, either in the checkpoint or in the deal history
+    pub terminal: bool,
+    /// Unix timestamp of the capture, unknown for checkpoints stored by older versions
+    pub timestamp: Option<u64>,
 }
 
 #[derive(Clone, Debug, Display, Eq, PartialEq, Hash, NetworkDecode, NetworkEncode)]
@@ -102,6 +160,8 @@ pub enum DealStatus {
     Revoked,
     #[display("Ended({0})")]
     Ended(Outcome),
+    #[display("Expired")]
+    Expired,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Display, NetworkEncode, NetworkDecode)]
@@ -117,11 +177,37 @@ pub struct DealInfo {
     pub serialized_deal: String,
     pub status: DealStatus,
     pub local_trade_role: TradeRole,
+    /// Unix timestamp after which the deal is not honored anymore, only known for open deals
+    pub expiry: Option<u64>,
 }
 
 #[cfg(feature = "serde")]
 impl ToYamlString for DealInfo {}
 
+/// A deal persisted in the deal book. Deals made by the node are re-advertised after a restart
+/// while open, deals of other nodes are added by the client to be taken later. Entries are
+/// dropped once their deal is revoked, expires or its swap ends.
+#[derive(Clone, Debug, Eq, PartialEq, Display, NetworkEncode, NetworkDecode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display(DealBookEntry::to_yaml_string)]
+pub struct DealBookEntry {
+    pub deal: Deal,
+    pub arbitrating_addr: bitcoin::Address,
+    pub accordant_addr: monero::Address,
+    pub funding_confirmations: Option<u32>,
+    pub syncer_instance: Option<String>,
+    pub expiry: Option<u64>,
+    pub local_trade_role: TradeRole,
+    pub status: DealStatus,
+}
+
+#[cfg(feature = "serde")]
+impl ToYamlString for DealBookEntry {}
+
 #[derive(Clone, Debug, Eq, PartialEq, Hash, Display, NetworkEncode, NetworkDecode)]
 #[cfg_attr(
     feature = "serde",
@@ -139,6 +225,48 @@ pub enum Outcome {
     FailureAbort,
 }
 
+/// Action taken by peerd against a remote peer exceeding its inbound message rate limit
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Display, NetworkEncode, NetworkDecode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate", rename_all = "lowercase")
+)]
+pub enum RateLimitAction {
+    /// Drop the messages exceeding the limit and keep the connection open
+    #[display("throttle")]
+    Throttle,
+    /// Drop the connection with the remote peer
+    #[display("disconnect")]
+    Disconnect,
+}
+
+impl FromStr for RateLimitAction {
+    type Err = crate::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "throttle" => Ok(RateLimitAction::Throttle),
+            "disconnect" => Ok(RateLimitAction::Disconnect),
+            s => Err(crate::Error::Farcaster(format!(
+                "Unknown rate limit action {}, expected throttle or disconnect",
+                s
+            ))),
+        }
+    }
+}
+
+/// Sent by peerd to farcasterd when the remote peer exceeds the inbound message rate limit
+#[derive(Clone, Debug, Eq, PartialEq, Display, NetworkEncode, NetworkDecode)]
+#[display("{max_messages} messages per {window}s exceeded, {action}, {messages_dropped} dropped")]
+pub struct PeerRateLimited {
+    pub max_messages: u32,
+    /// Length of the rate limit window in seconds
+    pub window: u64,
+    pub action: RateLimitAction,
+    /// Total number of messages dropped on this connection
+    pub messages_dropped: u64,
+}
+
 #[derive(Clone, Debug, Display, NetworkEncode, NetworkDecode)]
 #[display(inner)]
 pub enum Progress {
@@ -147,6 +275,24 @@ pub enum Progress {
     StateTransition(StateTransition),
 }
 
+/// A transition of the swap state machine, named after the swap states
+#[derive(Clone, Debug, Eq, PartialEq, Display, NetworkEncode, NetworkDecode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display("{swap_id}: {from} -> {to}")]
+pub struct SwapStateChanged {
+    pub swap_id: SwapId,
+    pub from: String,
+    pub to: String,
+    /// Unix timestamp of the transition
+    pub at: u64,
+    /// Set when the swap transitions to its end state
+    pub outcome: Option<Outcome>,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Display, NetworkEncode, NetworkDecode)]
 #[cfg_attr(
     feature = "serde",
@@ -214,7 +360,52 @@ pub struct Failure {
 )]
 #[display(Debug)]
 pub enum FailureCode {
-    /// Catch-all: TODO: Expand
+    /// The walletd token provided with the request is invalid
+    InvalidToken = 0x001,
+
+    /// The swap is unknown or not running
+    SwapNotFound = 0x002,
+
+    /// The deal is unknown
+    DealNotFound = 0x003,
+
+    /// The remote peer could not be reached or stopped responding
+    PeerUnreachable = 0x004,
+
+    /// The available funds do not cover the request
+    InsufficientFunds = 0x005,
+
+    /// The checkpoint is not in the database
+    CheckpointNotFound = 0x006,
+
+    /// The address is not in the database
+    AddressNotFound = 0x007,
+
+    /// A syncer failed to complete the request
+    SyncerFailure = 0x008,
+
+    /// The request or one of its arguments is invalid
+    InvalidRequest = 0x009,
+
+    /// The node already runs its maximum number of concurrent swaps
+    SwapLimitReached = 0x00A,
+
+    /// A syncer could not connect to its blockchain backend
+    SyncerBackendUnreachable = 0x00B,
+
+    /// The blockchain backend of a syncer answered a call with an error
+    SyncerBackendRpc = 0x00C,
+
+    /// The syncer task targeted by the request does not exist
+    TaskNotFound = 0x00D,
+
+    /// The rate of the deal deviates from the reference price beyond the tolerance
+    RateOutOfTolerance = 0x00E,
+
+    /// A swap of the deal is already in progress
+    SwapInProgress = 0x00F,
+
+    /// Catch-all
     Unknown = 0xFFF,
 
     TargetServiceNotFound = 0xFFE,
@@ -223,12 +414,50 @@ pub enum FailureCode {
 impl From<u16> for FailureCode {
     fn from(value: u16) -> Self {
         match value {
+            0x001 => FailureCode::InvalidToken,
+            0x002 => FailureCode::SwapNotFound,
+            0x003 => FailureCode::DealNotFound,
+            0x004 => FailureCode::PeerUnreachable,
+            0x005 => FailureCode::InsufficientFunds,
+            0x006 => FailureCode::CheckpointNotFound,
+            0x007 => FailureCode::AddressNotFound,
+            0x008 => FailureCode::SyncerFailure,
+            0x009 => FailureCode::InvalidRequest,
+            0x00A => FailureCode::SwapLimitReached,
+            0x00B => FailureCode::SyncerBackendUnreachable,
+            0x00C => FailureCode::SyncerBackendRpc,
+            0x00D => FailureCode::TaskNotFound,
+            0x00E => FailureCode::RateOutOfTolerance,
+            0x00F => FailureCode::SwapInProgress,
             0xFFE => FailureCode::TargetServiceNotFound,
             _ => FailureCode::Unknown,
         }
     }
 }
 
+impl From<&crate::Error> for FailureCode {
+    fn from(err: &crate::Error) -> Self {
+        use crate::error::{Error, SyncerError};
+        match err {
+            Error::InvalidToken => FailureCode::InvalidToken,
+            Error::NotResponding | Error::Peer(_) => FailureCode::PeerUnreachable,
+            Error::Syncer(SyncerError::BackendConnectionFailed { .. }) => {
+                FailureCode::SyncerBackendUnreachable
+            }
+            Error::Syncer(SyncerError::BackendRpc { .. }) => FailureCode::SyncerBackendRpc,
+            Error::Syncer(SyncerError::TaskNotFound(_)) => FailureCode::TaskNotFound,
+            Error::Syncer(_) => FailureCode::SyncerFailure,
+            Error::BitcoinAddress(_)
+            | Error::BitcoinAmount(_)
+            | Error::MoneroAddress(_)
+            | Error::MoneroAmount(_)
+            | Error::Uuid(_)
+            | Error::Inet2AddrParseError(_) => FailureCode::InvalidRequest,
+            _ => FailureCode::Unknown,
+        }
+    }
+}
+
 impl From<FailureCode> for u16 {
     fn from(code: FailureCode) -> Self {
         code as u16
@@ -315,3 +544,45 @@ impl ToYamlString for DefaultHealthReport {}
 impl ToYamlString for CompleteHealthReport {}
 #[cfg(feature = "serde")]
 impl ToYamlString for ReducedHealthReport {}
+
+#[test]
+fn decode_checkpoint_entry_without_syncer_instances() {
+    use farcaster_core::Uuid;
+    use strict_encoding::StrictEncode;
+
+    let entry = CheckpointEntry {
+        swap_id: Uuid::new().into(),
+        deal: Deal::from_str("Deal:Cke4ftrP5A781Vq85dgBQJNwYgBS4nuUV1LQM2fvVdFMNR4h5TrWhRR11111uMFuZTAsNgpdK8DiK11111TB9zym113GTvtvqfD1111114A4TTfFfmZoWyvpcjDBtTZCdWFSUWcRKYfEC3Y17hqaXZ3dWz11111111111111111111111111111111111111111AfZ113SEBTEspU3a").unwrap(),
+        trade_role: TradeRole::Maker,
+        expected_counterparty_node_id: None,
+        arbitrating_syncer_instance: None,
+        accordant_syncer_instance: None,
+    };
+
+    // entry layout of the versions preceding syncer instances
+    let mut encoded = vec![];
+    entry.swap_id.strict_encode(&mut encoded).unwrap();
+    entry.deal.strict_encode(&mut encoded).unwrap();
+    entry.trade_role.strict_encode(&mut encoded).unwrap();
+    entry
+        .expected_counterparty_node_id
+        .strict_encode(&mut encoded)
+        .unwrap();
+    assert_eq!(
+        CheckpointEntry::strict_deserialize(&encoded).unwrap(),
+        entry
+    );
+
+    // entries with their syncer instances still decode one after the other in a list
+    let trusted = CheckpointEntry {
+        swap_id: Uuid::new().into(),
+        arbitrating_syncer_instance: Some("trusted".to_string()),
+        ..entry.clone()
+    };
+    let entries = vec![trusted, entry];
+    let encoded = entries.strict_serialize().unwrap();
+    assert_eq!(
+        Vec::<CheckpointEntry>::strict_deserialize(&encoded).unwrap(),
+        entries
+    );
+}
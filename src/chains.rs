@@ -6,11 +6,18 @@
 
 //! This module defines the currently supported blockchains in the node for the arbitrating and
 //! the accordant roles. The types defined here are convertible with core `Blockchain` type.
+//!
+//! It also maps the node [`Network`] to the network of each chain, so that both legs of a swap
+//! run on coherent networks: mainnet with mainnet, testnet Bitcoin with stagenet Monero and local
+//! regtest Bitcoin with a local Monero daemon.
 
 use std::convert::TryFrom;
 use std::str::FromStr;
 
-use farcaster_core::{blockchain::Blockchain, consensus};
+use farcaster_core::{
+    blockchain::{Blockchain, Network},
+    consensus,
+};
 use strict_encoding::{StrictDecode, StrictEncode};
 
 /// A list of supported arbitrating blockchain
@@ -117,3 +124,28 @@ impl TryFrom<Blockchain> for AccordantBlockchain {
         }
     }
 }
+
+/// The Monero network used for swaps on the given node network. Testnet swaps run on stagenet
+/// and local swaps on a regtest daemon, whose addresses use the mainnet prefixes.
+pub fn monero_network(network: Network) -> monero::Network {
+    match network {
+        Network::Mainnet | Network::Local => monero::Network::Mainnet,
+        Network::Testnet => monero::Network::Stagenet,
+    }
+}
+
+/// The node network a Monero address belongs to, none for Monero testnet addresses as no node
+/// network runs on Monero testnet. Mainnet address types resolve to [`Network::Mainnet`], callers
+/// fall back on [`Network::Local`] when only a local setup is available.
+pub fn monero_address_network(network: monero::Network) -> Option<Network> {
+    match network {
+        monero::Network::Mainnet => Some(Network::Mainnet),
+        monero::Network::Stagenet => Some(Network::Testnet),
+        monero::Network::Testnet => None,
+    }
+}
+
+/// Check that a Monero address uses the network parameters of the node network
+pub fn is_monero_address_on(address: &monero::Address, network: Network) -> bool {
+    address.network == monero_network(network)
+}
@@ -6,8 +6,11 @@
 
 use farcaster_core::swap::btcxmr::{Deal, DealParameters};
 use farcaster_core::Uuid;
+use std::collections::BTreeMap;
 use std::io::{self, Read};
+use std::net::IpAddr;
 use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use internet2::addr::{InetSocketAddr, NodeAddr};
 use microservices::shell::Exec;
@@ -17,24 +20,28 @@ use clap_complete::generate;
 use clap_complete::shells::*;
 use farcaster_core::{
     blockchain::{Blockchain, Network},
-    role::SwapRole,
+    role::{SwapRole, TradeRole},
     swap::SwapId,
 };
 
 use super::Command;
 use crate::bus::{
     ctl::{self, CtlMsg},
-    info::{Address, InfoMsg},
+    info::{Address, InfoMsg, NodeStatus, ReadLastProgress},
     AddressSecretKey,
 };
 use crate::bus::{
-    BusMsg, CompleteHealthReport, DefaultHealthReport, Failure, FailureCode, HealthCheckSelector,
-    ReducedHealthReport,
+    BusMsg, CompleteHealthReport, DealBookEntry, DealStatus, DefaultHealthReport, Failure,
+    FailureCode, HealthCheckSelector, List, ReducedHealthReport,
 };
-use crate::cli::opts::CheckpointSelector;
+use crate::cli::opts::{CheckpointSelector, CheckpointsCommand, OutputFormat};
+use crate::cli::watch::watch;
 use crate::client::Client;
 use crate::syncerd::{Health, SweepAddressAddendum, SweepBitcoinAddress, SweepMoneroAddress};
-use crate::{Error, LogStyle, ServiceId};
+use crate::{is_monero_address_on, monero_network, Error, LogStyle, ServiceId, SyncerInstance};
+
+/// Outputs of at most this many satoshis are considered dust
+const BITCOIN_DUST_LIMIT_SAT: u64 = 546;
 
 impl Exec for Command {
     type Client = Client;
@@ -57,25 +64,31 @@ impl Exec for Command {
                     1 => {
                         let subj = subject.get(0).expect("vec of lenght 1");
                         if let Ok(node_addr) = NodeAddr::from_str(subj) {
-                            runtime
-                                .request_info(ServiceId::Peer(0, node_addr), InfoMsg::GetInfo)?;
+                            runtime.request_info(
+                                ServiceId::Farcasterd,
+                                InfoMsg::GetPeerInfo(node_addr),
+                            )?;
                             ServiceId::Peer(0, node_addr)
                         } else if let Ok(swap_id) = Uuid::from_str(subj).map(SwapId) {
-                            runtime.request_info(ServiceId::Swap(swap_id), InfoMsg::GetInfo)?;
+                            runtime.request_info(
+                                ServiceId::Farcasterd,
+                                InfoMsg::GetSwapInfo(swap_id),
+                            )?;
                             ServiceId::Swap(swap_id)
                         } else {
                             return Err(Error::Other(err));
                         }
                     }
-                    2 => {
+                    2 | 3 => {
                         let blockchain =
                             Blockchain::from_str(subject.get(0).expect("vec of lenght 2"))?;
                         let network = Network::from_str(subject.get(1).expect("vec of lenght 2"))?;
+                        let instance = SyncerInstance(subject.get(2).cloned());
                         runtime.request_info(
-                            ServiceId::Syncer(blockchain, network),
+                            ServiceId::Syncer(blockchain, network, instance.clone()),
                             InfoMsg::GetInfo,
                         )?;
-                        ServiceId::Syncer(blockchain, network)
+                        ServiceId::Syncer(blockchain, network, instance)
                     }
                     _ => {
                         return Err(Error::Other(err));
@@ -86,6 +99,9 @@ impl Exec for Command {
                     BusMsg::Info(InfoMsg::PeerInfo(info)) => println!("{}", info),
                     BusMsg::Info(InfoMsg::SwapInfo(info)) => println!("{}", info),
                     BusMsg::Info(InfoMsg::SyncerInfo(info)) => println!("{}", info),
+                    BusMsg::Info(InfoMsg::Failure(Failure { info, .. })) => {
+                        return Err(Error::Farcaster(info));
+                    }
                     BusMsg::Ctl(CtlMsg::Failure(Failure { code, .. }))
                         if code == FailureCode::TargetServiceNotFound =>
                     {
@@ -102,10 +118,10 @@ impl Exec for Command {
                                     swap_id
                                 )));
                             }
-                            ServiceId::Syncer(blockchain, network) => {
+                            ServiceId::Syncer(blockchain, network, instance) => {
                                 return Err(Error::Farcaster(format!(
-                                    "No running syncer for {} {}",
-                                    blockchain, network
+                                    "No running syncer{} for {} {}",
+                                    instance, blockchain, network
                                 )));
                             }
                             _ => {
@@ -124,13 +140,71 @@ impl Exec for Command {
                 }
             }
 
+            Command::Status { output } => {
+                runtime.request_info(ServiceId::Farcasterd, InfoMsg::GetInfo)?;
+                let node = match runtime.report_failure()? {
+                    BusMsg::Info(InfoMsg::NodeInfo(info)) => info,
+                    _ => return Err(Error::Farcaster("Received unexpected response".to_string())),
+                };
+                let mut swaps: BTreeMap<String, Vec<SwapId>> = BTreeMap::new();
+                for swap_id in node.swaps.iter() {
+                    runtime.request_info(ServiceId::Farcasterd, InfoMsg::GetSwapInfo(*swap_id))?;
+                    // swaps not launched yet have no state to report
+                    let state = match runtime.response()? {
+                        BusMsg::Info(InfoMsg::SwapInfo(info)) => info.state.state,
+                        _ => "Not running".to_string(),
+                    };
+                    swaps.entry(state).or_default().push(*swap_id);
+                }
+                let mut syncers = vec![];
+                for blockchain in [Blockchain::Bitcoin, Blockchain::Monero] {
+                    for network in [Network::Mainnet, Network::Testnet, Network::Local] {
+                        runtime.request_info(
+                            ServiceId::Syncer(blockchain, network, SyncerInstance::DEFAULT),
+                            InfoMsg::GetSyncerHealth,
+                        )?;
+                        // syncers not running are reported as not found
+                        if let BusMsg::Info(InfoMsg::SyncerHealth(health)) = runtime.response()? {
+                            syncers.push(health);
+                        }
+                    }
+                }
+                let status = NodeStatus {
+                    node,
+                    swaps,
+                    syncers,
+                };
+                match output {
+                    OutputFormat::Yaml => println!("{}", status),
+                    OutputFormat::Json => println!(
+                        "{}",
+                        serde_json::to_string_pretty(&status)
+                            .map_err(|err| Error::Farcaster(err.to_string()))?
+                    ),
+                }
+            }
+
             Command::Peers => {
                 runtime.request_info(ServiceId::Farcasterd, InfoMsg::ListPeers)?;
                 runtime.report_response_or_fail()?;
             }
 
-            Command::ListSwaps => {
-                runtime.request_info(ServiceId::Farcasterd, InfoMsg::ListSwaps)?;
+            Command::Watch { interval } => {
+                if interval == 0 {
+                    return Err(Error::Farcaster(
+                        "--interval must be greater than zero".to_string(),
+                    ));
+                }
+                watch(runtime, Duration::from_secs(interval))?;
+            }
+
+            Command::ListSwaps { summary } => {
+                let request = if summary {
+                    InfoMsg::ListSwapSummaries
+                } else {
+                    InfoMsg::ListSwaps
+                };
+                runtime.request_info(ServiceId::Farcasterd, request)?;
                 runtime.report_response_or_fail()?;
             }
 
@@ -139,19 +213,114 @@ impl Exec for Command {
                 runtime.report_response_or_fail()?;
             }
 
+            Command::ListDealBook => {
+                runtime.request_info(ServiceId::Database, InfoMsg::ListDealBook)?;
+                runtime.report_response_or_fail()?;
+            }
+
+            Command::AddToDealBook {
+                bitcoin_address,
+                monero_address,
+                deal,
+            } => {
+                runtime.request_ctl(
+                    ServiceId::Farcasterd,
+                    CtlMsg::SetDealBookEntry(DealBookEntry {
+                        expiry: None,
+                        deal,
+                        arbitrating_addr: bitcoin_address,
+                        accordant_addr: monero_address,
+                        funding_confirmations: None,
+                        syncer_instance: None,
+                        local_trade_role: TradeRole::Taker,
+                        status: DealStatus::Open,
+                    }),
+                )?;
+                runtime.report_response_or_fail()?;
+            }
+
+            Command::RemoveFromDealBook { deal } => {
+                runtime.request_ctl(
+                    ServiceId::Farcasterd,
+                    CtlMsg::RemoveDealBookEntry(deal.id()),
+                )?;
+                runtime.report_response_or_fail()?;
+            }
+
             Command::ListTasks {
                 blockchain,
                 network,
+                instance,
+            } => {
+                runtime.request_info(
+                    ServiceId::Syncer(blockchain, network, SyncerInstance(instance)),
+                    InfoMsg::ListTasks,
+                )?;
+                runtime.report_response_or_fail()?;
+            }
+
+            Command::SyncerHealth {
+                blockchain,
+                network,
+                instance,
+            } => {
+                runtime.request_info(
+                    ServiceId::Syncer(blockchain, network, SyncerInstance(instance)),
+                    InfoMsg::GetSyncerHealth,
+                )?;
+                runtime.report_response_or_fail()?;
+            }
+
+            Command::GetBlock {
+                blockchain,
+                network,
+                height,
+                include_txs,
             } => {
-                runtime.request_info(ServiceId::Syncer(blockchain, network), InfoMsg::ListTasks)?;
+                runtime.request_ctl(
+                    ServiceId::Farcasterd,
+                    CtlMsg::GetBlock(ctl::GetBlock {
+                        blockchain,
+                        network,
+                        height,
+                        include_txs,
+                    }),
+                )?;
                 runtime.report_response_or_fail()?;
             }
 
+            Command::ListSyncers => {
+                runtime.request_info(ServiceId::Farcasterd, InfoMsg::ListSyncers)?;
+                let mut syncers = match runtime.report_failure()? {
+                    BusMsg::Info(InfoMsg::SyncerList(syncers)) => syncers.into_inner(),
+                    _ => return Err(Error::Farcaster("Received unexpected response".to_string())),
+                };
+                for syncer in syncers.iter_mut().filter(|syncer| syncer.registered) {
+                    runtime.request_info(
+                        ServiceId::Syncer(
+                            syncer.blockchain,
+                            syncer.network,
+                            syncer.instance.clone().into(),
+                        ),
+                        InfoMsg::GetSyncerHealth,
+                    )?;
+                    if let BusMsg::Info(InfoMsg::SyncerHealth(health)) = runtime.response()? {
+                        syncer.health = Some(health);
+                    }
+                }
+                println!("{}", List::from_iter(syncers));
+            }
+
             Command::ListListens => {
                 runtime.request_info(ServiceId::Farcasterd, InfoMsg::ListListens)?;
                 runtime.report_response_or_fail()?;
             }
 
+            Command::ListBans => {
+                runtime.request_info(ServiceId::Farcasterd, InfoMsg::ListBannedPeers)?;
+                runtime.report_response_or_fail()?;
+            }
+
             Command::ListCheckpoints { select } => {
                 match select {
                     CheckpointSelector::All => {
@@ -182,6 +351,21 @@ impl Exec for Command {
                 runtime.report_response_or_fail()?;
             }
 
+            Command::Checkpoints { subcommand } => {
+                match subcommand {
+                    CheckpointsCommand::Ls => {
+                        runtime.request_info(ServiceId::Database, InfoMsg::ListCheckpoints)?;
+                    }
+                    CheckpointsCommand::Prune { keep_terminal } => {
+                        runtime.request_ctl(
+                            ServiceId::Database,
+                            CtlMsg::PruneCheckpoints(ctl::PruneCheckpoints { keep_terminal }),
+                        )?;
+                    }
+                }
+                runtime.report_response_or_fail()?;
+            }
+
             Command::RestoreCheckpoint { swap_id } => {
                 runtime.request_info(ServiceId::Database, InfoMsg::GetCheckpointEntry(swap_id))?;
                 if let BusMsg::Info(InfoMsg::CheckpointEntry(entry)) = runtime.report_failure()? {
@@ -274,6 +458,11 @@ impl Exec for Command {
                 maker_role,
                 public_ip_addr,
                 public_port,
+                funding_confirmations,
+                syncer_instance,
+                expiry,
+                wait,
+                wait_timeout,
             } => {
                 let deal_parameters = DealParameters {
                     uuid: Uuid::new().into(),
@@ -288,15 +477,42 @@ impl Exec for Command {
                     maker_role,
                 };
                 let public_addr = InetSocketAddr::socket(public_ip_addr, public_port);
+                validate_make_args(
+                    &deal_parameters,
+                    &arbitrating_addr,
+                    &accordant_addr,
+                    public_ip_addr,
+                    public_port,
+                )?;
                 let proto_deal = ctl::ProtoDeal {
                     deal_parameters,
                     public_addr,
                     arbitrating_addr,
                     accordant_addr,
+                    funding_confirmations,
+                    syncer_instance,
+                    expiry: expiry.map(|expiry| {
+                        SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .unwrap_or_else(|_| Duration::from_secs(0))
+                            .as_secs()
+                            + expiry
+                    }),
                 };
                 runtime.request_ctl(ServiceId::Farcasterd, CtlMsg::MakeDeal(proto_deal))?;
-                // report success or failure of the request to cli
-                runtime.report_response_or_fail()?;
+                if wait {
+                    match runtime.report_failure()? {
+                        BusMsg::Info(InfoMsg::MadeDeal(made_deal)) => {
+                            println!("{}", made_deal);
+                            let swap_id = made_deal.viewable_deal.details.id().into();
+                            runtime.wait_swap(swap_id, Duration::from_secs(wait_timeout))?;
+                        }
+                        resp => println!("{}", resp),
+                    }
+                } else {
+                    // report success or failure of the request to cli
+                    runtime.report_response_or_fail()?;
+                }
             }
 
             Command::DealInfo { deal } => {
@@ -309,13 +525,21 @@ impl Exec for Command {
                 bitcoin_address,
                 monero_address,
                 without_validation,
+                funding_confirmations,
+                syncer_instance,
+                dry_run,
+                force,
+                wait,
+                wait_timeout,
             } => {
+                // fail locally on a deal this node cannot take, before contacting the maker
+                validate_take_args(&deal.parameters, &bitcoin_address, &monero_address)?;
                 let Deal {
                     node_id,
                     peer_address,
                     ..
                 } = deal;
-                if !without_validation {
+                if !without_validation && !dry_run {
                     println!(
                         "\nWant to buy {}?\n\nCarefully validate the deal!\n",
                         deal_buy_information(&deal.parameters)
@@ -323,7 +547,7 @@ impl Exec for Command {
                     println!("Trade counterparty: {}@{}\n", &node_id, peer_address);
                     println!("{}", serde_yaml::to_string(&deal).expect("already parsed"));
                 }
-                if without_validation || take_deal() {
+                if dry_run || without_validation || take_deal() {
                     // pass deal to farcasterd to initiate the swap
                     runtime.request_ctl(
                         ServiceId::Farcasterd,
@@ -331,10 +555,27 @@ impl Exec for Command {
                             deal,
                             bitcoin_address,
                             monero_address,
+                            funding_confirmations,
+                            syncer_instance,
+                            dry_run,
+                            force,
                         }),
                     )?;
-                    // report success of failure of the request to cli
-                    runtime.report_response_or_fail()?;
+                    if wait {
+                        match runtime.report_failure()? {
+                            BusMsg::Info(InfoMsg::TookDeal(took_deal)) => {
+                                println!("{}", took_deal);
+                                runtime.wait_swap(
+                                    took_deal.swap_id,
+                                    Duration::from_secs(wait_timeout),
+                                )?;
+                            }
+                            resp => println!("{}", resp),
+                        }
+                    } else {
+                        // report success of failure of the request to cli
+                        runtime.report_response_or_fail()?;
+                    }
                 }
             }
 
@@ -343,12 +584,112 @@ impl Exec for Command {
                 runtime.report_response_or_fail()?;
             }
 
+            Command::Shutdown { force } => {
+                runtime.request_ctl(ServiceId::Farcasterd, CtlMsg::Shutdown(force))?;
+                runtime.report_response_or_fail()?;
+            }
+
+            Command::RotateNodeKeys => {
+                runtime.request_ctl(ServiceId::Farcasterd, CtlMsg::RotateNodeKeys(None))?;
+                runtime.report_response_or_fail()?;
+            }
+
+            Command::ExportSeed { confirm } => {
+                if !confirm {
+                    return Err(Error::Farcaster(
+                        "The mnemonic gives access to the funds of the swaps, pass --confirm to \
+                         print it"
+                            .to_string(),
+                    ));
+                }
+                runtime.request_ctl(ServiceId::Farcasterd, CtlMsg::ExportSeed(None))?;
+                match runtime.report_failure()? {
+                    BusMsg::Ctl(CtlMsg::SeedMnemonic(mnemonic)) => println!("{}", mnemonic),
+                    resp => println!("{}", resp),
+                }
+            }
+
+            Command::VerifyKeyManager => {
+                runtime.request_ctl(ServiceId::Farcasterd, CtlMsg::VerifyKeyManager(None))?;
+                runtime.report_response_or_fail()?;
+            }
+
+            Command::ReloadConfig => {
+                runtime.request_ctl(ServiceId::Farcasterd, CtlMsg::ReloadConfig)?;
+                runtime.report_response_or_fail()?;
+            }
+
+            Command::Listen { bind_addr } => {
+                runtime.request_ctl(ServiceId::Farcasterd, CtlMsg::Listen(bind_addr))?;
+                runtime.report_response_or_fail()?;
+            }
+
+            Command::StopListen {
+                bind_addr,
+                keep_connections,
+            } => {
+                runtime.request_ctl(
+                    ServiceId::Farcasterd,
+                    CtlMsg::StopListen(ctl::StopListen {
+                        bind_addr,
+                        keep_connections,
+                    }),
+                )?;
+                runtime.report_response_or_fail()?;
+            }
+
+            Command::Disconnect { node_addr, force } => {
+                runtime.request_ctl(
+                    ServiceId::Farcasterd,
+                    CtlMsg::DisconnectPeer(ctl::DisconnectPeer { node_addr, force }),
+                )?;
+                runtime.report_response_or_fail()?;
+            }
+
+            Command::Ban {
+                node_id,
+                abort_swaps,
+            } => {
+                runtime.request_ctl(
+                    ServiceId::Farcasterd,
+                    CtlMsg::BanPeer(ctl::BanPeer {
+                        node_id,
+                        abort_swaps,
+                    }),
+                )?;
+                runtime.report_response_or_fail()?;
+            }
+
+            Command::Unban { node_id } => {
+                runtime.request_ctl(ServiceId::Farcasterd, CtlMsg::UnbanPeer(node_id))?;
+                runtime.report_response_or_fail()?;
+            }
+
+            Command::XmrAddress { swap_id } => {
+                runtime.request_ctl(ServiceId::Farcasterd, CtlMsg::GetXmrAddress(swap_id))?;
+                runtime.report_response_or_fail()?;
+            }
+
             Command::AbortSwap { swap_id } => {
                 runtime.request_ctl(ServiceId::Swap(swap_id), CtlMsg::AbortSwap)?;
                 runtime.report_response_or_fail()?;
             }
 
-            Command::Progress { swapid, follow } => {
+            Command::Refund { swap_id } => {
+                runtime.request_ctl(ServiceId::Swap(swap_id), CtlMsg::ExecuteRefund(swap_id))?;
+                runtime.report_response_or_fail()?;
+            }
+
+            Command::Punish { swap_id } => {
+                runtime.request_ctl(ServiceId::Swap(swap_id), CtlMsg::ExecutePunish(swap_id))?;
+                runtime.report_response_or_fail()?;
+            }
+
+            Command::Progress {
+                swapid,
+                follow,
+                last,
+            } => {
                 if follow {
                     // subscribe to progress event and loop until Finish event is received or user
                     // ctrl-c the cli. Expect to recieve a stream of event responses
@@ -366,11 +707,22 @@ impl Exec for Command {
                 } else {
                     // request a read progress response. Expect to recieve only one response and
                     // quit
-                    runtime.request_info(ServiceId::Farcasterd, InfoMsg::ReadProgress(swapid))?;
+                    let request = match last {
+                        Some(count) => InfoMsg::ReadLastProgress(ReadLastProgress {
+                            swap_id: swapid,
+                            count,
+                        }),
+                        None => InfoMsg::ReadProgress(swapid),
+                    };
+                    runtime.request_info(ServiceId::Farcasterd, request)?;
                     runtime.report_response_or_fail()?;
                 }
             }
 
+            Command::Follow { swap_id } => {
+                runtime.follow_swap(swap_id)?;
+            }
+
             Command::NeedsFunding { blockchain } => {
                 runtime.request_info(ServiceId::Farcasterd, InfoMsg::NeedsFunding(blockchain))?;
                 runtime.report_response_or_fail()?;
@@ -384,6 +736,7 @@ impl Exec for Command {
             Command::SweepBitcoinAddress {
                 source_address,
                 destination_address,
+                fee_sat_per_kvb,
             } => {
                 runtime.request_info(
                     ServiceId::Database,
@@ -400,6 +753,7 @@ impl Exec for Command {
                             source_address,
                             source_secret_key: secret_key_info.secret_key,
                             destination_address,
+                            fee_sat_per_kvb,
                         })),
                     )?;
                     runtime.report_response_or_fail()?;
@@ -531,3 +885,96 @@ fn deal_buy_information(deal_parameters: &DealParameters) -> String {
         ),
     }
 }
+
+/// Validate the arguments of the make command before sending the deal to farcasterd, naming the
+/// argument that failed
+fn validate_make_args(
+    deal_parameters: &DealParameters,
+    arbitrating_addr: &bitcoin::Address,
+    accordant_addr: &monero::Address,
+    public_ip_addr: IpAddr,
+    public_port: u16,
+) -> Result<(), Error> {
+    if deal_parameters.arbitrating_amount.as_sat() <= BITCOIN_DUST_LIMIT_SAT {
+        return Err(Error::Farcaster(format!(
+            "--btc-amount: {} is not above the dust limit of {} satoshi",
+            deal_parameters.arbitrating_amount, BITCOIN_DUST_LIMIT_SAT
+        )));
+    }
+    if deal_parameters.accordant_amount.as_pico() == 0 {
+        return Err(Error::Farcaster(format!(
+            "--xmr-amount: {} is not a positive amount",
+            deal_parameters.accordant_amount
+        )));
+    }
+    if deal_parameters.network != arbitrating_addr.network.into() {
+        return Err(Error::Farcaster(format!(
+            "--btc-addr: {} is not a {} address",
+            arbitrating_addr, deal_parameters.network
+        )));
+    }
+    // Testnet swaps use stagenet addresses, local swaps mainnet address types
+    if !is_monero_address_on(accordant_addr, deal_parameters.network) {
+        return Err(Error::Farcaster(format!(
+            "--xmr-addr: {} is not a {} address, {} swaps use {:?} addresses",
+            accordant_addr,
+            deal_parameters.network,
+            deal_parameters.network,
+            monero_network(deal_parameters.network)
+        )));
+    }
+    if public_ip_addr.is_unspecified() {
+        return Err(Error::Farcaster(format!(
+            "--public-ip-addr: {} cannot be advertised to takers",
+            public_ip_addr
+        )));
+    }
+    if public_port == 0 {
+        return Err(Error::Farcaster(
+            "--public-port: port 0 cannot be advertised to takers".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Validate the deal and the addresses of the take command before sending the deal to
+/// farcasterd, naming the argument that failed
+fn validate_take_args(
+    deal_parameters: &DealParameters,
+    bitcoin_addr: &bitcoin::Address,
+    monero_addr: &monero::Address,
+) -> Result<(), Error> {
+    if deal_parameters.arbitrating_blockchain != Blockchain::Bitcoin
+        || deal_parameters.accordant_blockchain != Blockchain::Monero
+    {
+        return Err(Error::Farcaster(format!(
+            "--deal: {} for {} swaps are not supported, only Bitcoin for Monero",
+            deal_parameters.arbitrating_blockchain, deal_parameters.accordant_blockchain
+        )));
+    }
+    if deal_parameters.arbitrating_amount.as_sat() <= BITCOIN_DUST_LIMIT_SAT
+        || deal_parameters.accordant_amount.as_pico() == 0
+    {
+        return Err(Error::Farcaster(format!(
+            "--deal: the amounts {} and {} cannot be swapped",
+            deal_parameters.arbitrating_amount, deal_parameters.accordant_amount
+        )));
+    }
+    if deal_parameters.network != bitcoin_addr.network.into() {
+        return Err(Error::Farcaster(format!(
+            "--btc-addr: {} is not a {} address, as required by the {} deal",
+            bitcoin_addr, deal_parameters.network, deal_parameters.network
+        )));
+    }
+    // Testnet swaps use stagenet addresses, local swaps mainnet address types
+    if !is_monero_address_on(monero_addr, deal_parameters.network) {
+        return Err(Error::Farcaster(format!(
+            "--xmr-addr: {} is not a {} address, {} deals use {:?} addresses",
+            monero_addr,
+            deal_parameters.network,
+            deal_parameters.network,
+            monero_network(deal_parameters.network)
+        )));
+    }
+    Ok(())
+}
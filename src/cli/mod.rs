@@ -6,5 +6,6 @@
 
 mod command;
 mod opts;
+mod watch;
 
 pub use opts::{Command, DealSelector, Opts};
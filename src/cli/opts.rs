@@ -4,11 +4,14 @@
 // license that can be found in the LICENSE file or at
 // https://opensource.org/licenses/MIT.
 
+use bitcoin::secp256k1::PublicKey;
 use bitcoin::Address as BtcAddress;
 use clap_complete::shells::Shell;
+use internet2::addr::{InetSocketAddr, NodeAddr};
 use monero::Address as XmrAddress;
 use std::net::IpAddr;
 use std::str::FromStr;
+use std::time::Duration;
 
 use farcaster_core::{
     bitcoin::{fee::SatPerKvB, timelock::CSVTimelock},
@@ -19,6 +22,10 @@ use farcaster_core::{
 
 use crate::bus::info::Address;
 use crate::bus::HealthCheckSelector;
+use crate::syncerd::types::validate_sweep_fee_rate;
+
+/// Prefix of the encoded deals
+const DEAL_PREFIX: &str = "Deal:";
 
 /// Command-line tool for working with Farcaster node
 #[derive(Parser, Clone, PartialEq, Eq, Debug)]
@@ -29,6 +36,11 @@ pub struct Opts {
     #[clap(flatten)]
     pub shared: crate::opts::Opts,
 
+    /// Seconds to wait for the daemon to answer a request before failing; 0 waits indefinitely.
+    /// Following the progress of a swap is never subject to the timeout
+    #[clap(long, default_value = "60")]
+    pub timeout: u64,
+
     /// Command to execute
     #[clap(subcommand)]
     pub command: Command,
@@ -38,6 +50,14 @@ impl Opts {
     pub fn process(&mut self) {
         self.shared.process();
     }
+
+    pub fn request_timeout(&self) -> Option<Duration> {
+        if self.timeout == 0 {
+            None
+        } else {
+            Some(Duration::from_secs(self.timeout))
+        }
+    }
 }
 
 /// Command-line commands:
@@ -46,17 +66,40 @@ pub enum Command {
     /// General information about the running node
     #[display("info<{subject:?}>")]
     Info {
-        /// Remote peer address, swap id, or blockchain and network. If absent, returns information
-        /// about the node itself
+        /// Remote peer address, swap id, or blockchain and network, optionally followed by the
+        /// name of a syncer instance. If absent, returns information about the node itself
         subject: Vec<String>,
     },
 
+    /// Summarizes the node: its info, peers, running swaps grouped by state and the health of
+    /// its syncers
+    #[clap(aliases = &["st"])]
+    Status {
+        /// Format of the summary
+        #[clap(short, long, default_value = "yaml", possible_values = &["yaml", "json"])]
+        output: OutputFormat,
+    },
+
     /// Lists existing peer connections
     Peers,
 
+    /// Redraws a live dashboard of the node, its peers and running swaps, highlighting the
+    /// changes since the previous refresh. Exit with Ctrl-C.
+    #[display("watch")]
+    Watch {
+        /// Seconds between two refreshes
+        #[clap(short, long, default_value = "5")]
+        interval: u64,
+    },
+
     /// Lists running swaps
     #[clap(aliases = &["ls"])]
-    ListSwaps,
+    ListSwaps {
+        /// List each swap with its lifecycle, role, counterparty and amounts instead of its id
+        /// only
+        #[clap(short, long)]
+        summary: bool,
+    },
 
     /// Lists deals created by daemon
     #[clap(aliases = &["ld"])]
@@ -70,11 +113,46 @@ pub enum Command {
         select: DealSelector,
     },
 
+    /// Lists the deals of the deal book, re-advertised after a restart until they are taken,
+    /// revoked or expire
+    #[clap(aliases = &["ldb"])]
+    #[display("list-deal-book")]
+    ListDealBook,
+
+    /// Adds a deal of another node to the deal book, to take it later. Deals made by this node
+    /// are added to the deal book when made.
+    #[clap(aliases = &["adb"])]
+    #[display("add-to-deal-book<{deal}>")]
+    AddToDealBook {
+        /// Bitcoin address used as destination or refund address when taking the deal.
+        #[clap(long = "btc-addr")]
+        bitcoin_address: BtcAddress,
+
+        /// Monero address used as destination or refund address when taking the deal.
+        #[clap(long = "xmr-addr")]
+        monero_address: XmrAddress,
+
+        /// An encoded deal, as shared by the maker.
+        #[clap(short = 'D', long = "deal", parse(try_from_str = parse_deal))]
+        deal: Deal,
+    },
+
+    /// Removes a deal from the deal book. Open deals made by this node are removed by revoking
+    /// them.
+    #[clap(aliases = &["rdb"])]
+    #[display("remove-from-deal-book<{deal}>")]
+    RemoveFromDealBook {
+        /// The deal to remove.
+        #[clap(parse(try_from_str = parse_deal))]
+        deal: Deal,
+    },
+
     /// Gives information on an open deal
     #[clap(aliases = &["di"])]
     #[display("deal-info<{deal}>")]
     DealInfo {
         /// The deal to be canceled.
+        #[clap(parse(try_from_str = parse_deal))]
         deal: Deal,
     },
 
@@ -82,6 +160,15 @@ pub enum Command {
     #[clap(aliases = &["ll"])]
     ListListens,
 
+    /// Lists the banned remote peers
+    #[display("list-bans")]
+    ListBans,
+
+    /// Lists the syncers launched by the node with their blockchain, network and health, e.g.
+    /// to check both blockchains of a swap are synced before trading
+    #[display("list-syncers")]
+    ListSyncers,
+
     /// Lists tasks currently treated by a syncer
     #[clap(aliases = &["lt"])]
     ListTasks {
@@ -90,6 +177,40 @@ pub enum Command {
 
         /// The network for which we want to list the tasks
         network: Network,
+
+        /// The name of the syncer instance, the default syncer if absent
+        #[clap(long)]
+        instance: Option<String>,
+    },
+
+    /// Reports whether a syncer's backend is reachable and how far the syncer is behind its tip
+    SyncerHealth {
+        /// The blockchain of the syncer
+        blockchain: Blockchain,
+
+        /// The network of the syncer
+        network: Network,
+
+        /// The name of the syncer instance, the default syncer if absent
+        #[clap(long)]
+        instance: Option<String>,
+    },
+
+    /// Retrieves the block at a height from the default syncer of a network, to audit what the
+    /// syncer saw, e.g. during a disputed swap
+    GetBlock {
+        /// The blockchain of the block
+        blockchain: Blockchain,
+
+        /// The network of the block
+        network: Network,
+
+        /// The height of the block
+        height: u64,
+
+        /// Include the transactions of the block, only served by bitcoin rpc backends
+        #[clap(long)]
+        include_txs: bool,
     },
 
     /// Lists saved checkpoints of the swaps
@@ -104,6 +225,13 @@ pub enum Command {
         select: CheckpointSelector,
     },
 
+    /// Inspect and clean up the saved checkpoints
+    #[display("checkpoints<{subcommand}>")]
+    Checkpoints {
+        #[clap(subcommand)]
+        subcommand: CheckpointsCommand,
+    },
+
     /// Checks the health of the syncers. By default 'mainnet' and 'testnet' are checked, use the
     /// selector to change this behavior.
     #[clap(aliases = &["hc"])]
@@ -211,6 +339,30 @@ pub enum Command {
         /// {farcasterd.bind_ip}:{farcasterd.bind_port}
         #[clap(short = 'p', long, default_value = "7067")]
         public_port: u16,
+
+        /// Number of confirmations required on the funding transactions before the swap
+        /// proceeds; defaults to the finality configured for the network.
+        #[clap(long)]
+        funding_confirmations: Option<u32>,
+
+        /// Name of the syncer instance, among the instances of the syncers configuration, the
+        /// swap runs on; defaults to the default syncers of the network.
+        #[clap(long)]
+        syncer_instance: Option<String>,
+
+        /// Number of seconds after which the deal expires and is not honored anymore; the deal
+        /// never expires by default.
+        #[clap(long)]
+        expiry: Option<u64>,
+
+        /// Block until the swap of the deal terminates, printing its progress. The process
+        /// exits with an error if the swap fails.
+        #[clap(long)]
+        wait: bool,
+
+        /// Maximum time to wait for the swap to terminate with --wait, in seconds.
+        #[clap(long, default_value = "86400")]
+        wait_timeout: u64,
     },
 
     /// Taker accepts deal and connects to maker's daemon to start the trade.
@@ -223,22 +375,146 @@ pub enum Command {
         #[clap(long = "xmr-addr")]
         monero_address: XmrAddress,
 
-        /// An encoded deal.
-        #[clap(short = 'D', long = "deal")]
+        /// An encoded deal, as shared by the maker.
+        #[clap(short = 'D', long = "deal", parse(try_from_str = parse_deal))]
         deal: Deal,
 
         /// Accept the deal without validation.
         #[clap(short, long)]
         without_validation: bool,
+
+        /// Number of confirmations required on the funding transactions before the swap
+        /// proceeds; defaults to the finality configured for the network.
+        #[clap(long)]
+        funding_confirmations: Option<u32>,
+
+        /// Name of the syncer instance, among the instances of the syncers configuration, the
+        /// swap runs on; defaults to the default syncers of the network.
+        #[clap(long)]
+        syncer_instance: Option<String>,
+
+        /// Only check that the deal can be taken, without starting the swap.
+        #[clap(long, conflicts_with = "wait")]
+        dry_run: bool,
+
+        /// Take the deal again even though it was already taken, e.g. after the maker turned
+        /// down a previous attempt. The new swap gets its own swap id. A deal with a swap in
+        /// progress is never taken twice.
+        #[clap(long)]
+        force: bool,
+
+        /// Block until the swap terminates, printing its progress. The process exits with an
+        /// error if the swap fails.
+        #[clap(long)]
+        wait: bool,
+
+        /// Maximum time to wait for the swap to terminate with --wait, in seconds.
+        #[clap(long, default_value = "86400")]
+        wait_timeout: u64,
     },
 
     /// Revoke deal accepts a deal and revokes it within the runtime.
     #[display("revoke-deal<{deal}>")]
     RevokeDeal {
         /// The deal to be canceled.
+        #[clap(parse(try_from_str = parse_deal))]
         deal: Deal,
     },
 
+    /// Stop the node: the running swaps, the peer connections, the syncers and the other
+    /// services are terminated in this order, and farcasterd exits once they did. Interrupted
+    /// swaps can be restored from their checkpoint on the next launch.
+    #[display("shutdown")]
+    Shutdown {
+        /// Kill the services right away instead of waiting for them to exit
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// Rotate the node keys used by peerd. Swap wallet derivations are not affected. Refused
+    /// while any peer connection or listener is active.
+    #[display("rotate-node-keys")]
+    RotateNodeKeys,
+
+    /// Print the BIP39 mnemonic of the wallet seed, which restores the wallet with walletd
+    /// `--restore-mnemonic`. Anyone knowing the mnemonic can spend the funds of the swaps.
+    #[display("export-seed")]
+    ExportSeed {
+        /// Confirm the mnemonic is to be printed
+        #[clap(long)]
+        confirm: bool,
+    },
+
+    /// Check the swap key derivations of the wallet are consistent, e.g. before starting a swap
+    /// to detect a corrupted seed or a wrong account configuration.
+    #[display("verify-key-manager")]
+    VerifyKeyManager,
+
+    /// Read the configuration file of farcasterd again and apply the settings that can change
+    /// without a restart, e.g. the log level, the swap limits, the peer limits and the syncer
+    /// servers. The log level and the poll intervals also apply to the running services. Changes
+    /// to the other settings, e.g. the bind address or the wallet account, are rejected.
+    #[display("reload-config")]
+    ReloadConfig,
+
+    /// Start an additional listener for incoming peer connections, e.g. to listen on both a
+    /// clearnet and an onion address. Listeners are listed by the list-listens command.
+    #[display("listen<{bind_addr}>")]
+    Listen {
+        /// The socket address to bind, e.g. 0.0.0.0:7067
+        bind_addr: InetSocketAddr,
+    },
+
+    /// Stop a listener, as listed by the list-listens command. The connections it accepted are
+    /// closed, unless kept alive.
+    #[display("stop-listen<{bind_addr}>")]
+    StopListen {
+        /// The socket address the listener is bound to
+        bind_addr: InetSocketAddr,
+
+        /// Keep the connections accepted by the listener alive.
+        #[clap(long)]
+        keep_connections: bool,
+    },
+
+    /// Close the connection with a remote peer, as listed by the peers command. Refused if swaps
+    /// are running over the connection, unless forced.
+    #[display("disconnect<{node_addr}>")]
+    Disconnect {
+        /// The address of the remote peer
+        node_addr: NodeAddr,
+
+        /// Close the connection even if swaps are running over it.
+        #[clap(long)]
+        force: bool,
+    },
+
+    /// Ban a remote peer: its connections are terminated and refused, whether incoming or
+    /// outgoing. The ban persists across restarts.
+    #[display("ban<{node_id}>")]
+    Ban {
+        /// The node id of the remote peer
+        node_id: PublicKey,
+
+        /// Also abort the running swaps with the banned peer that have not locked yet.
+        #[clap(long)]
+        abort_swaps: bool,
+    },
+
+    /// Lift the ban of a remote peer.
+    #[display("unban<{node_id}>")]
+    Unban {
+        /// The node id of the remote peer
+        node_id: PublicKey,
+    },
+
+    /// Show the Monero address a swap funds, or sweeps the bought Monero to.
+    #[display("xmr-address<{swap_id}>")]
+    XmrAddress {
+        /// The swap id
+        swap_id: SwapId,
+    },
+
     /// Abort a swap if it has not locked yet.
     #[display("abort-swap<{swap_id}>")]
     AbortSwap {
@@ -246,6 +522,22 @@ pub enum Command {
         swap_id: SwapId,
     },
 
+    /// Broadcast the refund transaction of a canceled swap as Bob, once the cancel transaction
+    /// is final. Overrides a stuck automated refund.
+    #[display("refund<{swap_id}>")]
+    Refund {
+        /// The swap to be refunded
+        swap_id: SwapId,
+    },
+
+    /// Broadcast the punish transaction of a canceled swap as Alice, once the punish timelock
+    /// expired. Overrides a stuck automated punish.
+    #[display("punish<{swap_id}>")]
+    Punish {
+        /// The swap to be punished
+        swap_id: SwapId,
+    },
+
     /// Request swap progress report.
     #[display("progress<{swapid}>")]
     Progress {
@@ -255,13 +547,27 @@ pub enum Command {
         /// Subscribe to progress and only return when progress is finished.
         #[clap(short, long)]
         follow: bool,
+
+        /// Only return the given number of latest progress entries, ignored when following the
+        /// progress.
+        #[clap(short = 'n', long, conflicts_with = "follow")]
+        last: Option<u32>,
+    },
+
+    /// Follow the progress of a running swap until it terminates. Returns with an error if the
+    /// swap fails.
+    #[display("follow<{swap_id}>")]
+    Follow {
+        /// The swap to follow.
+        swap_id: SwapId,
     },
 
-    /// Returns addresses and amounts that require funding for blockchain.
-    #[display("needs-funding<{blockchain}>")]
+    /// Returns the swaps awaiting funding with the addresses and amounts to fund them, on the
+    /// given blockchain or on both blockchains if omitted.
+    #[display("needs-funding")]
     NeedsFunding {
         /// The blockchain funding required needs to be checked against.
-        blockchain: Blockchain,
+        blockchain: Option<Blockchain>,
     },
 
     /// Returns previously created funding addresses for blockchain.
@@ -278,6 +584,9 @@ pub enum Command {
         source_address: BtcAddress,
         /// The destination address receiving the coins.
         destination_address: BtcAddress,
+        /// Fee rate in satoshi per kvB for the sweep transaction, estimated if not set.
+        #[clap(long, parse(try_from_str = parse_sweep_fee_rate))]
+        fee_sat_per_kvb: Option<u64>,
     },
 
     /// Attempts to sweep any funds on a given monero funding address
@@ -345,6 +654,22 @@ pub enum DealSelectorParseError {
     Invalid,
 }
 
+/// Checkpoints subcommands:
+#[derive(Subcommand, Clone, PartialEq, Eq, Debug, Display)]
+pub enum CheckpointsCommand {
+    /// Lists the saved checkpoints with the swap state they captured and their timestamp
+    #[display("ls")]
+    Ls,
+
+    /// Removes the checkpoints of swaps in a terminal state and dangling checkpoint data
+    #[display("prune")]
+    Prune {
+        /// Keep the checkpoints of swaps in a terminal state
+        #[clap(long)]
+        keep_terminal: bool,
+    },
+}
+
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display, From)]
 #[display(Debug)]
 pub enum CheckpointSelector {
@@ -372,6 +697,32 @@ pub enum CheckpointSelectorParseError {
     Invalid,
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Display)]
+pub enum OutputFormat {
+    #[display("yaml")]
+    Yaml,
+    #[display("json")]
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = OutputFormatParseError;
+    fn from_str(input: &str) -> Result<OutputFormat, Self::Err> {
+        match input {
+            "yaml" => Ok(OutputFormat::Yaml),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(OutputFormatParseError::Invalid),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum OutputFormatParseError {
+    /// The provided value can't be parsed as an output format, use yaml or json
+    Invalid,
+}
+
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display, Error, From)]
 #[display(doc_comments)]
 pub enum AmountOfAssetParseError {
@@ -432,3 +783,27 @@ impl FromStr for AmountOfAsset {
         Ok(AmountOfAsset { asset, amount })
     }
 }
+
+/// Parse a sweep fee rate, rejecting a null or implausibly high rate
+fn parse_sweep_fee_rate(s: &str) -> Result<u64, String> {
+    let fee_sat_per_kvb = u64::from_str(s).map_err(|err| err.to_string())?;
+    validate_sweep_fee_rate(fee_sat_per_kvb)
+}
+
+/// Parse a deal pasted from an out-of-band channel. The encoding carries a checksum, so
+/// corrupted or truncated deals are rejected with the reason instead of being misread.
+fn parse_deal(s: &str) -> Result<Deal, String> {
+    let s = s.trim();
+    if !s.starts_with(DEAL_PREFIX) {
+        return Err(format!(
+            "an encoded deal starts with `{}`, check the deal was copied entirely",
+            DEAL_PREFIX
+        ));
+    }
+    Deal::from_str(s).map_err(|err| {
+        format!(
+            "the deal is corrupted or truncated, check it was copied entirely: {}",
+            err
+        )
+    })
+}
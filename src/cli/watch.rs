@@ -0,0 +1,200 @@
+// Copyright 2020-2022 Farcaster Devs & LNP/BP Standards Association
+//
+// Use of this source code is governed by an MIT-style
+// license that can be found in the LICENSE file or at
+// https://opensource.org/licenses/MIT.
+
+//! Live dashboard of the node, redrawn in the terminal from the existing info requests. Swaps
+//! and peers that appeared, changed state or went away since the previous poll are highlighted.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use farcaster_core::swap::SwapId;
+use internet2::addr::NodeAddr;
+
+use crate::bus::info::{InfoMsg, NodeInfo, SwapSummary};
+use crate::bus::BusMsg;
+use crate::client::Client;
+use crate::{Error, LogStyle, ServiceId};
+
+/// Clear the terminal and move the cursor to its top left corner
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Granularity of the Ctrl-C checks while waiting for the next poll
+const INTERRUPT_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// State of the node at a poll
+struct Snapshot {
+    node: NodeInfo,
+    peers: Vec<NodeAddr>,
+    /// Running swaps with their current state
+    swaps: BTreeMap<SwapId, (SwapSummary, String)>,
+}
+
+impl Snapshot {
+    fn poll(runtime: &mut Client) -> Result<Self, Error> {
+        runtime.request_info(ServiceId::Farcasterd, InfoMsg::GetInfo)?;
+        let node = match runtime.report_failure()? {
+            BusMsg::Info(InfoMsg::NodeInfo(info)) => info,
+            _ => return Err(Error::Farcaster("Received unexpected response".to_string())),
+        };
+        runtime.request_info(ServiceId::Farcasterd, InfoMsg::ListPeers)?;
+        let peers = match runtime.report_failure()? {
+            BusMsg::Info(InfoMsg::PeerList(peers)) => peers.into_inner(),
+            _ => return Err(Error::Farcaster("Received unexpected response".to_string())),
+        };
+        runtime.request_info(ServiceId::Farcasterd, InfoMsg::ListSwapSummaries)?;
+        let summaries = match runtime.report_failure()? {
+            BusMsg::Info(InfoMsg::SwapSummaryList(summaries)) => summaries.into_inner(),
+            _ => return Err(Error::Farcaster("Received unexpected response".to_string())),
+        };
+        let mut swaps = BTreeMap::new();
+        for summary in summaries {
+            runtime.request_info(ServiceId::Farcasterd, InfoMsg::GetSwapInfo(summary.swap_id))?;
+            // swaps not launched yet have no state to report
+            let state = match runtime.response()? {
+                BusMsg::Info(InfoMsg::SwapInfo(info)) => info.state.state,
+                _ => "Not running".to_string(),
+            };
+            swaps.insert(summary.swap_id, (summary, state));
+        }
+        Ok(Snapshot { node, peers, swaps })
+    }
+
+    /// Render the snapshot, highlighting the changes since the previous one
+    fn render(&self, previous: Option<&Snapshot>, interval: Duration) -> String {
+        let mut out = String::from(CLEAR_SCREEN);
+        out.push_str(&format!(
+            "{} {} | refreshed every {}s, Ctrl-C to exit\n",
+            "farcaster node".bright_white_bold(),
+            self.node
+                .node_id
+                .map(|node_id| node_id.to_string())
+                .unwrap_or_else(|| "(keys not loaded)".to_string()),
+            interval.as_secs()
+        ));
+        out.push_str(&format!(
+            "version {} | uptime {}s | active swaps {}/{} | open deals {}\n",
+            self.node.version,
+            self.node.uptime.as_secs(),
+            self.node.active_swaps,
+            self.node.max_concurrent_swaps,
+            self.node.deals.len()
+        ));
+        if !self.node.swaps_by_state.is_empty() {
+            out.push_str(&format!(
+                "swaps: {}\n",
+                self.node
+                    .swaps_by_state
+                    .iter()
+                    .map(|(lifecycle, count)| format!("{} {}", count, lifecycle))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+        if !self.node.degraded.is_empty() {
+            out.push_str(&format!(
+                "{} {}\n",
+                "degraded services:".err(),
+                self.node.degraded.join(", ")
+            ));
+        }
+
+        out.push_str(&format!(
+            "\n{} ({})\n",
+            "Peers".bright_blue_bold(),
+            self.peers.len()
+        ));
+        for peer in self.peers.iter() {
+            match previous {
+                Some(previous) if !previous.peers.contains(peer) => {
+                    out.push_str(&format!("  + {}\n", peer.bright_green_bold()))
+                }
+                _ => out.push_str(&format!("  {}\n", peer)),
+            }
+        }
+        for peer in previous.iter().flat_map(|previous| previous.peers.iter()) {
+            if !self.peers.contains(peer) {
+                out.push_str(&format!("  - {} disconnected\n", peer.red_bold()));
+            }
+        }
+
+        out.push_str(&format!(
+            "\n{} ({})\n",
+            "Swaps".bright_blue_bold(),
+            self.swaps.len()
+        ));
+        for (swap_id, (summary, state)) in self.swaps.iter() {
+            let line = format!(
+                "{} {} {} {} {} for {}",
+                swap_id,
+                summary.lifecycle,
+                summary.local_trade_role,
+                summary.local_swap_role,
+                summary.arbitrating_amount,
+                summary.accordant_amount
+            );
+            match previous.map(|previous| previous.swaps.get(swap_id)) {
+                Some(None) => out.push_str(&format!(
+                    "  + {} {}\n",
+                    line.bright_green_bold(),
+                    state.bright_green_bold()
+                )),
+                Some(Some((_, previous_state))) if previous_state != state => {
+                    out.push_str(&format!(
+                        "  * {} {} -> {}\n",
+                        line,
+                        previous_state,
+                        state.bright_yellow_bold()
+                    ))
+                }
+                _ => out.push_str(&format!("  {} {}\n", line, state)),
+            }
+        }
+        for swap_id in previous.iter().flat_map(|previous| previous.swaps.keys()) {
+            if !self.swaps.contains_key(swap_id) {
+                out.push_str(&format!("  - {} ended\n", swap_id.red_bold()));
+            }
+        }
+        out
+    }
+}
+
+/// Poll the node and redraw the dashboard at every interval until interrupted with Ctrl-C
+pub fn watch(runtime: &mut Client, interval: Duration) -> Result<(), Error> {
+    let interrupted = Arc::new(AtomicBool::new(false));
+    spawn_interrupt_listener(Arc::clone(&interrupted))?;
+
+    let mut previous: Option<Snapshot> = None;
+    while !interrupted.load(Ordering::SeqCst) {
+        let polled = Instant::now();
+        let snapshot = Snapshot::poll(runtime)?;
+        print!("{}", snapshot.render(previous.as_ref(), interval));
+        previous = Some(snapshot);
+        while polled.elapsed() < interval && !interrupted.load(Ordering::SeqCst) {
+            sleep(INTERRUPT_CHECK_INTERVAL);
+        }
+    }
+    println!();
+    Ok(())
+}
+
+/// Flag the interruption on Ctrl-C instead of terminating the process, so the dashboard exits
+/// once the pending requests are answered
+fn spawn_interrupt_listener(interrupted: Arc<AtomicBool>) -> Result<(), Error> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    std::thread::spawn(move || {
+        rt.block_on(async {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                interrupted.store(true, Ordering::SeqCst);
+            }
+        })
+    });
+    Ok(())
+}
@@ -4,29 +4,57 @@
 // license that can be found in the LICENSE file or at
 // https://opensource.org/licenses/MIT.
 
+use std::sync::{Arc, Mutex};
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use internet2::ZmqSocketType;
+use farcaster_core::swap::SwapId;
+use internet2::{session::LocalSession, zeromq, SendRecvMessage, TypedEnum, ZmqSocketType};
 use microservices::esb;
+use microservices::ZMQ_CONTEXT;
 
-use crate::bus::{ctl::CtlMsg, info::InfoMsg, BusMsg, ServiceBus};
+use crate::bus::bridge::BridgeMsg;
+use crate::bus::ServiceBus;
+use crate::bus::{
+    ctl::CtlMsg, info::InfoMsg, BusMsg, Failure, FailureCode, Outcome, SwapStateChanged,
+};
 use crate::service::Endpoints;
 use crate::service::ServiceConfig;
 use crate::{Error, LogStyle, ServiceId};
 
+/// Interval between two progress subscription attempts while waiting for a swap to launch
+const WAIT_SWAP_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Process exit code used when a swap did not terminate before the wait timeout
+pub const WAIT_TIMEOUT_EXIT_CODE: i32 = 2;
+
+/// Default time to wait for a daemon to answer a request
+pub const CLIENT_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Process exit code used when a daemon did not answer a request before the request timeout
+pub const REQUEST_TIMEOUT_EXIT_CODE: i32 = 3;
+
+/// Interval at which the response watchdog checks the pending response deadline
+const RESPONSE_WATCHDOG_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Deadline of the pending response with the sequence number it was armed with
+type ResponseDeadline = Arc<Mutex<Option<(u64, Instant)>>>;
+
 #[repr(C)]
 pub struct Client {
     identity: ServiceId,
     response_queue: std::collections::VecDeque<BusMsg>,
     esb: esb::Controller<ServiceBus, BusMsg, Handler>,
+    timeout: Option<Duration>,
+    deadline: ResponseDeadline,
+    deadline_seq: u64,
 }
 
 impl Client {
     pub fn with(config: ServiceConfig) -> Result<Self, Error> {
         debug!("Setting up RPC client...");
         let identity = ServiceId::client();
-        let esb = esb::Controller::with(
+        let mut esb = esb::Controller::with(
             map! {
                 ServiceBus::Ctl => esb::BusConfig::with_addr(
                     config.ctl_endpoint,
@@ -44,6 +72,26 @@ impl Client {
             },
         )?;
 
+        // reads from the bus are blocking, the watchdog wakes them up through the bridge once
+        // the deadline of the pending response passes
+        let watchdog_endpoint = format!("inproc://clientwatchdog-{}", identity);
+        let rx_watchdog = ZMQ_CONTEXT.socket(zmq::PULL)?;
+        rx_watchdog.bind(&watchdog_endpoint)?;
+        esb.add_service_bus(
+            ServiceBus::Bridge,
+            esb::BusConfig {
+                api_type: ZmqSocketType::Push,
+                carrier: zeromq::Carrier::Socket(rx_watchdog),
+                router: None,
+                queued: true,
+                topic: None,
+            },
+        )?;
+        let tx_watchdog = ZMQ_CONTEXT.socket(zmq::PUSH)?;
+        tx_watchdog.connect(&watchdog_endpoint)?;
+        let deadline = ResponseDeadline::default();
+        spawn_response_watchdog(Arc::clone(&deadline), tx_watchdog, identity.clone());
+
         // We have to sleep in order for ZMQ to bootstrap
         sleep(Duration::from_secs_f32(0.1));
 
@@ -51,9 +99,19 @@ impl Client {
             identity,
             response_queue: empty!(),
             esb,
+            timeout: Some(CLIENT_REQUEST_TIMEOUT),
+            deadline,
+            deadline_seq: 0,
         })
     }
 
+    /// Set the time to wait for a daemon to answer a request, waiting indefinitely if none.
+    /// Streams of swap progress are never subject to the timeout.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
     pub fn identity(&self) -> ServiceId {
         self.identity.clone()
     }
@@ -72,16 +130,56 @@ impl Client {
         Ok(())
     }
 
+    /// Next response of the daemons. Fails with [`Error::RequestTimeout`] if no response is
+    /// received within the request timeout.
     pub fn response(&mut self) -> Result<BusMsg, Error> {
-        if self.response_queue.is_empty() {
-            for rep in self.esb.recv_poll()? {
-                self.response_queue.push_back(rep.request);
+        match self.timeout {
+            Some(timeout) => self
+                .response_until(Some(Instant::now() + timeout))?
+                .ok_or_else(|| Error::RequestTimeout(timeout.as_secs())),
+            None => Ok(self
+                .response_until(None)?
+                .expect("without deadline we always receive a response")),
+        }
+    }
+
+    /// Next response of the daemons, none if the deadline passes before one is received
+    fn response_until(&mut self, deadline: Option<Instant>) -> Result<Option<BusMsg>, Error> {
+        while self.response_queue.is_empty() {
+            self.deadline_seq += 1;
+            let seq = self.deadline_seq;
+            self.set_deadline(deadline.map(|deadline| (seq, deadline)));
+            let res = self.esb.recv_poll();
+            self.set_deadline(None);
+            let mut expired = false;
+            for rep in res? {
+                match rep.request {
+                    // wake ups armed for earlier responses are stale
+                    BusMsg::Bridge(BridgeMsg::ResponseTimeout(timeout_seq)) => {
+                        expired |= timeout_seq == seq
+                    }
+                    request => self.response_queue.push_back(request),
+                }
+            }
+            if expired && self.response_queue.is_empty() {
+                return Ok(None);
             }
         }
-        Ok(self
-            .response_queue
-            .pop_front()
-            .expect("We always have at least one element"))
+        Ok(self.response_queue.pop_front())
+    }
+
+    fn set_deadline(&self, deadline: Option<(u64, Instant)>) {
+        if let Ok(mut pending) = self.deadline.lock() {
+            *pending = deadline;
+        }
+    }
+
+    /// Run the closure without the request timeout, for reads waiting on a swap to progress
+    fn untimed<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let timeout = self.timeout.take();
+        let res = f(self);
+        self.timeout = timeout;
+        res
     }
 
     pub fn report_failure(&mut self) -> Result<BusMsg, Error> {
@@ -100,6 +198,10 @@ impl Client {
 
     /// Print the stream of received requests until progress fails or succeed
     pub fn report_progress(&mut self) -> Result<(), Error> {
+        self.untimed(|client| client.report_progress_untimed())
+    }
+
+    fn report_progress_untimed(&mut self) -> Result<(), Error> {
         // loop on all requests received until a progress termination condition is recieved
         // report failure transform BusMsg::Failure in error already, terminate on error or on
         // success
@@ -121,6 +223,123 @@ impl Client {
     }
 }
 
+impl Client {
+    /// Follow the progress of a swap until it terminates, waiting for the swap to be launched
+    /// first. Fails if the swap fails, and with [`Error::WaitTimeout`] if the swap did not
+    /// terminate before the timeout.
+    pub fn wait_swap(&mut self, swap_id: SwapId, timeout: Duration) -> Result<(), Error> {
+        let deadline = Instant::now() + timeout;
+        let timed_out = || Error::WaitTimeout(swap_id, timeout.as_secs());
+
+        // farcasterd only knows the swap once it is launched, retry the subscription until then
+        loop {
+            self.request_info(ServiceId::Farcasterd, InfoMsg::SubscribeProgress(swap_id))?;
+            match self.response_until(Some(deadline))?.ok_or_else(timed_out)? {
+                BusMsg::Info(InfoMsg::Failure(Failure {
+                    code: FailureCode::SwapNotFound,
+                    ..
+                })) if Instant::now() + WAIT_SWAP_RETRY_INTERVAL < deadline => {
+                    sleep(WAIT_SWAP_RETRY_INTERVAL)
+                }
+                BusMsg::Info(InfoMsg::Failure(Failure {
+                    code: FailureCode::SwapNotFound,
+                    ..
+                })) => return Err(timed_out()),
+                resp => {
+                    self.response_queue.push_front(resp);
+                    break;
+                }
+            }
+        }
+        self.report_swap_progress(swap_id, Some(deadline))?
+            .ok_or_else(timed_out)
+    }
+
+    /// Subscribe to the progress of a running swap and print it until the swap terminates.
+    /// Fails if the swap fails or is unknown.
+    pub fn follow_swap(&mut self, swap_id: SwapId) -> Result<(), Error> {
+        self.request_info(ServiceId::Farcasterd, InfoMsg::SubscribeProgress(swap_id))?;
+        self.report_swap_progress(swap_id, None)?
+            .expect("without deadline the swap progress is followed until its outcome");
+        Ok(())
+    }
+
+    /// Print the progress stream of a subscribed swap until its outcome, then unsubscribe.
+    /// Returns none if the deadline passes first.
+    fn report_swap_progress(
+        &mut self,
+        swap_id: SwapId,
+        deadline: Option<Instant>,
+    ) -> Result<Option<()>, Error> {
+        let res = loop {
+            let resp = match self.response_until(deadline)? {
+                Some(resp) => resp,
+                None => break None,
+            };
+            match resp {
+                BusMsg::Info(InfoMsg::Success(s)) | BusMsg::Ctl(CtlMsg::Success(s)) => {
+                    println!("{}", s.bright_green_bold());
+                    break Some(Ok(()));
+                }
+                BusMsg::Info(InfoMsg::Failure(f)) | BusMsg::Ctl(CtlMsg::Failure(f)) => {
+                    break Some(Err(Error::Farcaster(f.info)));
+                }
+                BusMsg::Info(InfoMsg::SwapStateChanged(SwapStateChanged {
+                    to,
+                    outcome: Some(outcome),
+                    ..
+                })) => {
+                    println!("{}", to);
+                    if outcome == Outcome::SuccessSwap {
+                        break Some(Ok(()));
+                    } else {
+                        break Some(Err(Error::Farcaster(format!(
+                            "Swap ended with {}",
+                            outcome
+                        ))));
+                    }
+                }
+                resp => println!("{}", resp),
+            }
+        };
+        self.request_info(ServiceId::Farcasterd, InfoMsg::UnsubscribeProgress(swap_id))?;
+        res.transpose()
+    }
+}
+
+/// Wake up the pending read of the client through the bridge once the deadline of the
+/// pending response passes
+fn spawn_response_watchdog(deadline: ResponseDeadline, socket: zmq::Socket, identity: ServiceId) {
+    std::thread::spawn(move || {
+        let mut session = LocalSession::with_zmq_socket(ZmqSocketType::Push, socket);
+        let address: Vec<u8> = identity.into();
+        loop {
+            sleep(RESPONSE_WATCHDOG_INTERVAL);
+            let expired = match deadline.lock() {
+                Ok(mut pending) => match *pending {
+                    Some((seq, deadline)) if Instant::now() > deadline => {
+                        *pending = None;
+                        Some(seq)
+                    }
+                    _ => None,
+                },
+                Err(_) => None,
+            };
+            if let Some(seq) = expired {
+                let request = BusMsg::Bridge(BridgeMsg::ResponseTimeout(seq));
+                if let Err(err) =
+                    session.send_routed_message(&address, &address, &address, &request.serialize())
+                {
+                    error!(
+                        "Failed to wake up the client on a response timeout: {}",
+                        err
+                    );
+                }
+            }
+        }
+    });
+}
+
 pub struct Handler {
     identity: ServiceId,
 }
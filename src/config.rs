@@ -12,6 +12,7 @@ use internet2::addr::InetSocketAddr;
 use serde::{Deserialize, Serialize};
 use serde_with::DisplayFromStr;
 
+use std::collections::HashMap;
 use std::convert::TryInto;
 use std::fmt::Display;
 use std::fs::File;
@@ -19,7 +20,13 @@ use std::io::prelude::*;
 use std::path::Path;
 use std::str::FromStr;
 
-use crate::{AccordantBlockchain, ArbitratingBlockchain, Error};
+use crate::bus::RateLimitAction;
+use crate::logger::LogFormat;
+use crate::opts::{BridgeSocket, BusTransport};
+use crate::{
+    is_monero_address_on, monero_network, AccordantBlockchain, ArbitratingBlockchain, Error,
+    SyncerInstance,
+};
 
 pub const FARCASTER_MAINNET_ELECTRUM_SERVER: &str = "ssl://blockstream.info:700";
 pub const FARCASTER_MAINNET_MONERO_DAEMON: &str = "http://node.community.rino.io:18081";
@@ -34,6 +41,8 @@ pub const FARCASTER_BIND_IP: &str = "0.0.0.0";
 
 pub const GRPC_BIND_IP_ADDRESS: &str = "127.0.0.1";
 
+pub const METRICS_BIND_IP_ADDRESS: &str = "127.0.0.1";
+
 pub const SWAP_MAINNET_BITCOIN_SAFETY: u8 = 7;
 pub const SWAP_MAINNET_BITCOIN_FINALITY: u8 = 6;
 pub const SWAP_MAINNET_BITCOIN_MIN_BTC_AMOUNT: f64 = 0.00001;
@@ -46,6 +55,12 @@ pub const SWAP_TESTNET_BITCOIN_SAFETY: u8 = 3;
 pub const SWAP_TESTNET_BITCOIN_FINALITY: u8 = 1;
 pub const SWAP_TESTNET_MONERO_FINALITY: u8 = 1;
 
+/// Upper bound of the per swap funding confirmations
+pub const SWAP_MAX_FUNDING_CONFIRMATIONS: u32 = 100;
+
+/// Default maximum number of swaps taken or accepted concurrently
+pub const FARCASTER_MAX_CONCURRENT_SWAPS: usize = 10;
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(crate = "serde_crate")]
 pub struct Config {
@@ -55,6 +70,8 @@ pub struct Config {
     pub swap: Option<SwapConfig>,
     /// Sets the grpc server port, if none is given, no grpc server is run
     pub grpc: Option<GrpcConfig>,
+    /// Sets the Prometheus metrics endpoint, if none is given, no metrics are served
+    pub metrics: Option<MetricsConfig>,
     /// Syncer configuration
     pub syncers: Option<Networked<Option<SyncerServers>>>,
 }
@@ -90,6 +107,28 @@ impl Config {
         }
     }
 
+    /// Returns if the metrics endpoint is enabled
+    pub fn is_metrics_enable(&self) -> bool {
+        match &self.metrics {
+            Some(MetricsConfig { enable, .. }) => *enable,
+            _ => false,
+        }
+    }
+
+    /// Returns the metrics endpoint bind address, if the ip is not set use the default value
+    pub fn metrics_bind_addr(&self) -> Option<String> {
+        match &self.metrics {
+            Some(MetricsConfig {
+                bind_port, bind_ip, ..
+            }) => Some(format!(
+                "{}:{}",
+                bind_ip.as_deref().unwrap_or(METRICS_BIND_IP_ADDRESS),
+                bind_port
+            )),
+            _ => None,
+        }
+    }
+
     /// Returns if auto restore is enabled. Default to true
     pub fn auto_restore_enable(&self) -> bool {
         match &self.farcasterd {
@@ -101,6 +140,180 @@ impl Config {
         }
     }
 
+    /// Returns the inbound message rate limit applied to peer connections, if None no limit is
+    /// applied
+    pub fn get_peer_rate_limit(&self) -> Option<PeerRateLimitConfig> {
+        match &self.farcasterd {
+            Some(FarcasterdConfig {
+                peer_rate_limit: Some(rate_limit),
+                ..
+            }) => Some(rate_limit.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the pings detecting stale peer connections, if None peerd defaults apply
+    pub fn get_peer_ping(&self) -> Option<PeerPingConfig> {
+        match &self.farcasterd {
+            Some(FarcasterdConfig {
+                peer_ping: Some(ping),
+                ..
+            }) => Some(*ping),
+            _ => None,
+        }
+    }
+
+    /// Returns the wallet account the swap keys are derived in
+    pub fn get_wallet_account(&self) -> u32 {
+        match &self.farcasterd {
+            Some(FarcasterdConfig {
+                wallet_account: Some(account),
+                ..
+            }) => *account,
+            _ => 0,
+        }
+    }
+
+    /// Returns the webhook notified on swap ends, if None no notification is sent
+    pub fn get_webhook(&self) -> Option<WebhookConfig> {
+        match &self.farcasterd {
+            Some(FarcasterdConfig {
+                webhook: Some(webhook),
+                ..
+            }) => Some(webhook.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the price reference the rate of the deals taken is checked against, if None the
+    /// rates are not checked
+    pub fn get_price_oracle(&self) -> Option<PriceOracleConfig> {
+        match &self.farcasterd {
+            Some(FarcasterdConfig {
+                price_oracle: Some(price_oracle),
+                ..
+            }) => Some(price_oracle.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the retention of the checkpoints of ended swaps, if None databased defaults apply
+    pub fn get_checkpoint_retention(&self) -> Option<CheckpointRetentionConfig> {
+        match &self.farcasterd {
+            Some(FarcasterdConfig {
+                checkpoint_retention: Some(retention),
+                ..
+            }) => Some(retention.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the deadline of the swaps in seconds, if None the swaps wait passively
+    pub fn get_swap_deadline(&self) -> Option<u64> {
+        match &self.farcasterd {
+            Some(FarcasterdConfig {
+                swap_deadline: Some(deadline),
+                ..
+            }) => Some(*deadline),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of blocks between two re-broadcasts of the swap transactions not final
+    /// yet, if None swapd defaults apply
+    pub fn get_rebroadcast_interval(&self) -> Option<u32> {
+        match &self.farcasterd {
+            Some(FarcasterdConfig {
+                rebroadcast_interval: Some(interval),
+                ..
+            }) => Some(*interval),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the swaps count the unconfirmed funds of their funding address when
+    /// checking they are funded, default to true
+    pub fn count_unconfirmed_funding(&self) -> bool {
+        match &self.farcasterd {
+            Some(FarcasterdConfig {
+                count_unconfirmed_funding: Some(count),
+                ..
+            }) => *count,
+            _ => true,
+        }
+    }
+
+    /// Returns the format of the logs, if None the command line or the default applies
+    pub fn get_log_format(&self) -> Option<LogFormat> {
+        match &self.farcasterd {
+            Some(FarcasterdConfig {
+                log_format: Some(format),
+                ..
+            }) => Some(*format),
+            _ => None,
+        }
+    }
+
+    /// Returns the filter of the log records, if None the `RUST_LOG` environment variable applies
+    pub fn get_log_level(&self) -> Option<String> {
+        match &self.farcasterd {
+            Some(FarcasterdConfig {
+                log_level: Some(filter),
+                ..
+            }) => Some(filter.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the syncers not running are launched on demand, default to true
+    pub fn auto_spawn_syncers_enable(&self) -> bool {
+        match &self.farcasterd {
+            Some(FarcasterdConfig {
+                auto_spawn_syncers: Some(enable),
+                ..
+            }) => *enable,
+            _ => true,
+        }
+    }
+
+    /// Returns the transport of the service buses, if None the command line applies
+    pub fn get_bus_transport(&self) -> Option<BusTransport> {
+        match &self.farcasterd {
+            Some(FarcasterdConfig {
+                bus_transport: Some(transport),
+                ..
+            }) => Some(*transport),
+            _ => None,
+        }
+    }
+
+    /// Returns the maximum number of swaps taken or accepted concurrently, default to
+    /// [`FARCASTER_MAX_CONCURRENT_SWAPS`]
+    pub fn get_max_concurrent_swaps(&self) -> usize {
+        match &self.farcasterd {
+            Some(FarcasterdConfig {
+                max_concurrent_swaps: Some(max),
+                ..
+            }) => *max,
+            _ => FARCASTER_MAX_CONCURRENT_SWAPS,
+        }
+    }
+
+    /// Returns if the unconfirmed funds of the auto-funding wallets count toward the funds
+    /// available to fund a swap
+    pub fn auto_funding_counts_unconfirmed(&self) -> bool {
+        matches!(
+            &self.farcasterd,
+            Some(FarcasterdConfig {
+                auto_funding: Some(AutoFundingConfig {
+                    count_unconfirmed: Some(true),
+                    ..
+                }),
+                ..
+            })
+        )
+    }
+
     /// Returns the auto-funding configuration for a given network if enable, if None no
     /// configuration is found
     pub fn get_auto_funding_config(&self, network: Network) -> Option<AutoFundingServers> {
@@ -150,6 +363,45 @@ impl Config {
         }
     }
 
+    /// Returns the configuration of a syncer instance, if found in config, for the specified
+    /// network: the network's section for the default instance, one of its instances otherwise
+    pub fn get_syncer_instance_servers(
+        &self,
+        network: Network,
+        instance: &SyncerInstance,
+    ) -> Option<SyncerServers> {
+        let servers = self.get_syncer_servers(network)?;
+        match instance.name() {
+            Some(name) => servers.instances?.remove(name),
+            None => Some(servers),
+        }
+    }
+
+    /// Returns the number of confirmations before a received Monero output can be spent on the
+    /// syncer instance, if None the syncerd and swapd defaults apply
+    pub fn get_monero_unlock_depth(
+        &self,
+        network: Network,
+        instance: &SyncerInstance,
+    ) -> Option<u32> {
+        self.get_syncer_instance_servers(network, instance)?
+            .monero_unlock_depth
+    }
+
+    /// Returns the syncer instances configured for the specified network: the default instance
+    /// followed by the named instances in alphabetical order
+    pub fn get_syncer_instances(&self, network: Network) -> Vec<SyncerInstance> {
+        let servers = match self.get_syncer_servers(network) {
+            Some(servers) => servers,
+            None => return vec![],
+        };
+        let mut names: Vec<String> = servers.instances.unwrap_or_default().into_keys().collect();
+        names.sort();
+        std::iter::once(SyncerInstance::DEFAULT)
+            .chain(names.into_iter().map(|name| SyncerInstance(Some(name))))
+            .collect()
+    }
+
     /// Returns the swap config for the specified network and arbitrating/accordant blockchains
     pub fn get_swap_config(
         &self,
@@ -197,6 +449,26 @@ impl Config {
         }
     }
 
+    /// Returns the swap config for the deal, requiring the given number of confirmations on the
+    /// funding transactions when set
+    pub fn get_deal_swap_config(
+        &self,
+        deal: &DealParameters,
+        funding_confirmations: Option<u32>,
+    ) -> Result<ParsedSwapConfig, Error> {
+        let swap_config = self.get_swap_config(
+            deal.arbitrating_blockchain.try_into()?,
+            deal.accordant_blockchain.try_into()?,
+            deal.network,
+        )?;
+        match funding_confirmations {
+            Some(confirmations) => {
+                swap_config.with_funding_confirmations(confirmations, deal.cancel_timelock.as_u32())
+            }
+            None => Ok(swap_config),
+        }
+    }
+
     /// Validate a deal against user configuration (farcasterd.toml) and user provided addresses
     pub fn validate_deal_parameters(
         &self,
@@ -272,11 +544,14 @@ impl Config {
         // validate accordant address
         match deal.accordant_blockchain.try_into()? {
             AccordantBlockchain::Monero => {
-                // Monero local address types are mainnet address types
-                if deal.network != acc_addr.network.into() && deal.network != Network::Local {
+                // Testnet swaps use stagenet addresses, local swaps mainnet address types
+                if !is_monero_address_on(acc_addr, deal.network) {
                     Err(Message(format!(
-                        "{} address is not a {} address",
-                        deal.accordant_blockchain, deal.network
+                        "{} address is not a {} address, {} swaps use {:?} addresses",
+                        deal.accordant_blockchain,
+                        deal.network,
+                        deal.network,
+                        monero_network(deal.network)
                     )))
                 } else {
                     Ok(())
@@ -295,6 +570,33 @@ impl Config {
         }
     }
 
+    /// Apply the settings of a configuration read again at runtime, see [`RELOADABLE_SETTINGS`].
+    /// The changes to the [`FIXED_SETTINGS`] are not applied, they are reported as rejected.
+    pub fn reload(&mut self, new: Config) -> Result<ConfigReload, Error> {
+        let to_value = |config: &Config| {
+            toml::Value::try_from(config).map_err(|err| Error::Farcaster(err.to_string()))
+        };
+        let mut current = to_value(self)?;
+        let new = to_value(&new)?;
+        let mut reload = ConfigReload::default();
+        for key in RELOADABLE_SETTINGS {
+            let value = setting(&new, key);
+            if setting(&current, key) != value {
+                set_setting(&mut current, key, value.cloned());
+                reload.applied.push(key.to_string());
+            }
+        }
+        for key in FIXED_SETTINGS {
+            if setting(&current, key) != setting(&new, key) {
+                reload.rejected.push(key.to_string());
+            }
+        }
+        *self = current
+            .try_into()
+            .map_err(|err| Error::Farcaster(err.to_string()))?;
+        Ok(reload)
+    }
+
     // Helper function to return default mainnet btc tradeable amounts
     fn mainnet_btc_default_tradeable() -> TradeableAmounts<bitcoin::Amount> {
         use bitcoin::Amount;
@@ -329,6 +631,7 @@ impl Default for Config {
             farcasterd: Some(FarcasterdConfig::default()),
             swap: Some(SwapConfig::default()),
             grpc: None,
+            metrics: None,
             syncers: Some(Networked {
                 mainnet: Some(SyncerServers {
                     electrum_server: FARCASTER_MAINNET_ELECTRUM_SERVER.into(),
@@ -336,6 +639,21 @@ impl Default for Config {
                     monero_rpc_wallet: FARCASTER_MAINNET_MONERO_RPC_WALLET.into(),
                     monero_lws: None,
                     monero_wallet_dir: None,
+                    bitcoin_rpc: None,
+                    bitcoin_cookie_path: None,
+                    bitcoin_rpc_user: None,
+                    bitcoin_rpc_pass: None,
+                    poll_interval: None,
+                    max_poll_interval: None,
+                    monero_unlock_depth: None,
+                    retry_max_attempts: None,
+                    retry_backoff: None,
+                    retry_max_backoff: None,
+                    fee_stale_ratio: None,
+                    bridge_transport: None,
+                    bridge_socket: None,
+                    mock_scenario: None,
+                    instances: None,
                 }),
                 testnet: Some(SyncerServers {
                     electrum_server: FARCASTER_TESTNET_ELECTRUM_SERVER.into(),
@@ -343,6 +661,21 @@ impl Default for Config {
                     monero_rpc_wallet: FARCASTER_TESTNET_MONERO_RPC_WALLET.into(),
                     monero_lws: None,
                     monero_wallet_dir: None,
+                    bitcoin_rpc: None,
+                    bitcoin_cookie_path: None,
+                    bitcoin_rpc_user: None,
+                    bitcoin_rpc_pass: None,
+                    poll_interval: None,
+                    max_poll_interval: None,
+                    monero_unlock_depth: None,
+                    retry_max_attempts: None,
+                    retry_backoff: None,
+                    retry_max_backoff: None,
+                    fee_stale_ratio: None,
+                    bridge_transport: None,
+                    bridge_socket: None,
+                    mock_scenario: None,
+                    instances: None,
                 }),
                 local: None,
             }),
@@ -361,6 +694,105 @@ pub struct FarcasterdConfig {
     pub bind_ip: Option<String>,
     /// Whether checkpoints should be auto restored at start-up, or not
     pub auto_restore: Option<bool>,
+    /// Sets the inbound message rate limit applied to each peer connection, default to no limit
+    pub peer_rate_limit: Option<PeerRateLimitConfig>,
+    /// Sets the pings detecting stale peer connections, default to a ping every 30 seconds and
+    /// dropping the connection after 3 missed pongs
+    pub peer_ping: Option<PeerPingConfig>,
+    /// Sets the wallet account the swap keys are derived in, default to account 0. Nodes sharing
+    /// a wallet seed must use distinct accounts
+    pub wallet_account: Option<u32>,
+    /// Sets the webhook notified when a swap ends, default to no notification
+    pub webhook: Option<WebhookConfig>,
+    /// Sets the price reference the rate of the deals taken is checked against, default to no
+    /// check
+    pub price_oracle: Option<PriceOracleConfig>,
+    /// Sets the retention of the checkpoints of ended swaps, default to keep them all
+    pub checkpoint_retention: Option<CheckpointRetentionConfig>,
+    /// Sets the wall-clock deadline of the swaps in seconds from their start, after which a swap
+    /// not locked yet is aborted and a locked swap refunds instead of buying. Default to no
+    /// deadline
+    pub swap_deadline: Option<u64>,
+    /// Sets the maximum number of swaps taken or accepted concurrently, further deals are
+    /// refused until a swap ends. Restored swaps are not limited. Default to 10
+    pub max_concurrent_swaps: Option<usize>,
+    /// Sets the number of blocks between two re-broadcasts of the swap transactions not final
+    /// yet, in case they were dropped from the mempool. 0 disables the re-broadcasts. Default to
+    /// 3
+    pub rebroadcast_interval: Option<u32>,
+    /// Whether the unconfirmed funds of the funding address count when a swap checks it is
+    /// funded, the swap waits for the funding to confirm otherwise. Default to true
+    pub count_unconfirmed_funding: Option<bool>,
+    /// Sets the format of the logs of farcasterd and the services it launches, `text` or
+    /// `json`, unless set on the command line. Default to text
+    pub log_format: Option<LogFormat>,
+    /// Sets the level of the logs of farcasterd and its services, or a filter in the `RUST_LOG`
+    /// syntax, e.g. `error,farcaster_node=debug`. Default to the `RUST_LOG` environment variable
+    pub log_level: Option<String>,
+    /// Sets the transport of the buses connecting farcasterd and the services it launches, `ipc`
+    /// or `tcp`, unless set on the command line. Default to no check of the transport
+    pub bus_transport: Option<BusTransport>,
+    /// Whether the syncers needed by a swap and not running are launched on demand, with the
+    /// backends of the syncers configuration. Default to true
+    pub auto_spawn_syncers: Option<bool>,
+}
+
+/// Retention of the checkpoints of swaps in a terminal state, enforced by databased
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(crate = "serde_crate")]
+pub struct CheckpointRetentionConfig {
+    /// Maximum time a checkpoint is kept after its swap ended, in seconds, default to no limit
+    pub max_age: Option<u64>,
+    /// Maximum number of checkpoints kept, the most recently ended ones, default to no limit
+    pub max_terminal: Option<usize>,
+}
+
+/// Webhook receiving a JSON notification on every swap end
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(crate = "serde_crate")]
+pub struct WebhookConfig {
+    /// The URL the notifications are posted to
+    pub url: String,
+    /// Maximum number of attempts to deliver a notification, default to 3
+    pub max_attempts: Option<u8>,
+}
+
+/// Price reference the BTC/XMR rate of the deals taken is checked against
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(crate = "serde_crate")]
+pub struct PriceOracleConfig {
+    /// The URL returning the price of one XMR in BTC, as a plain number or in a JSON document
+    pub url: String,
+    /// JSON pointer to the price in the document returned, e.g. `/monero/btc`, default to the
+    /// whole document
+    pub json_pointer: Option<String>,
+    /// Maximum relative deviation of the deal rate from the reference price, default to 0.05
+    pub tolerance: Option<f64>,
+    /// Whether deals deviating beyond the tolerance are refused, or only reported with a
+    /// warning. Default to true
+    pub reject: Option<bool>,
+}
+
+/// Inbound message rate limit applied by peerd to the remote peer
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(crate = "serde_crate")]
+pub struct PeerRateLimitConfig {
+    /// Maximum number of messages accepted from the remote peer per window
+    pub max_messages: u32,
+    /// Length of the window in seconds
+    pub window: u64,
+    /// Action taken against a remote peer exceeding the limit
+    pub action: RateLimitAction,
+}
+
+/// Pings sent by peerd to detect stale connections with the remote peer
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(crate = "serde_crate")]
+pub struct PeerPingConfig {
+    /// Interval between two pings in seconds
+    pub interval: u64,
+    /// Consecutive missed pongs after which the connection is dropped
+    pub max_missed: u8,
 }
 
 /// This struct holds all swap config, for all chains and all networks
@@ -401,6 +833,35 @@ pub struct ParsedSwapConfig {
     pub accordant: AccConfig,
 }
 
+impl ParsedSwapConfig {
+    /// Require the given number of confirmations on the arbitrating and accordant locks before
+    /// the swap proceeds, raising the arbitrating safety margin if needed. Fails if the value is
+    /// out of range or does not leave room before the cancel timelock.
+    pub fn with_funding_confirmations(
+        mut self,
+        confirmations: u32,
+        cancel_timelock: u32,
+    ) -> Result<Self, Error> {
+        if confirmations == 0 || confirmations > SWAP_MAX_FUNDING_CONFIRMATIONS {
+            return Err(Error::Farcaster(format!(
+                "Invalid funding confirmations {}, must be between 1 and {}",
+                confirmations, SWAP_MAX_FUNDING_CONFIRMATIONS
+            )));
+        }
+        let finality = confirmations as u8;
+        let safety = self.arbitrating.safety.max(finality + 1);
+        if safety as u32 >= cancel_timelock {
+            return Err(Error::Farcaster(format!(
+                "Invalid funding confirmations {}, must leave a safety margin before the cancel timelock of {} blocks",
+                confirmations, cancel_timelock
+            )));
+        }
+        self.arbitrating = ArbConfig { safety, finality };
+        self.accordant = AccConfig { finality };
+        Ok(self)
+    }
+}
+
 /// Holds the parameters needed for an arbitrating asset in a swap, e.g. Bitcoin
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(crate = "serde_crate")]
@@ -557,6 +1018,17 @@ pub struct GrpcConfig {
     pub bind_ip: Option<String>,
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(crate = "serde_crate")]
+pub struct MetricsConfig {
+    /// Serve the Prometheus metrics endpoint, requires the `metrics` feature
+    pub enable: bool,
+    /// Metrics endpoint port configuration
+    pub bind_port: u16,
+    /// Metrics endpoint listening ip address
+    pub bind_ip: Option<String>,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 #[serde(crate = "serde_crate")]
 pub struct AutoFundingConfig {
@@ -568,6 +1040,9 @@ pub struct AutoFundingConfig {
     pub testnet: Option<AutoFundingServers>,
     /// Local auto-funding configuration
     pub local: Option<AutoFundingServers>,
+    /// Count the unconfirmed funds of the auto-funding wallets when checking they cover a swap,
+    /// default to false
+    pub count_unconfirmed: Option<bool>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -598,6 +1073,40 @@ pub struct SyncerServers {
     pub monero_lws: Option<String>,
     /// Monero wallet directory
     pub monero_wallet_dir: Option<String>,
+    /// Bitcoin Core node RPC to use instead of the Electrum server, the node must run with
+    /// `-txindex`
+    pub bitcoin_rpc: Option<String>,
+    /// Path to the cookie file to connect to the Bitcoin Core node
+    pub bitcoin_cookie_path: Option<String>,
+    /// RPC user to connect to the Bitcoin Core node
+    pub bitcoin_rpc_user: Option<String>,
+    /// RPC pass to connect to the Bitcoin Core node
+    pub bitcoin_rpc_pass: Option<String>,
+    /// Interval, in milliseconds, between two polls of the backends while a watched item is
+    /// about to change state
+    pub poll_interval: Option<u64>,
+    /// Upper bound, in milliseconds, of the interval between two polls of the backends while idle
+    pub max_poll_interval: Option<u64>,
+    /// Number of confirmations before a received Monero output can be spent
+    pub monero_unlock_depth: Option<u32>,
+    /// Number of attempts of a failing backend call before reporting the failure
+    pub retry_max_attempts: Option<u32>,
+    /// Delay, in milliseconds, before retrying a failed backend call, doubled after every attempt
+    pub retry_backoff: Option<u64>,
+    /// Upper bound, in milliseconds, of the delay between two attempts of a backend call
+    pub retry_max_backoff: Option<u64>,
+    /// Ratio of the estimated fee rate below which an unconfirmed Bitcoin transaction is stale
+    pub fee_stale_ratio: Option<f64>,
+    /// Transport of the bridge between the syncer runtime and its synclet, default to inproc
+    pub bridge_transport: Option<BusTransport>,
+    /// ZMQ socket types of the bridge between the syncer runtime and its synclet, default to
+    /// pair
+    pub bridge_socket: Option<BridgeSocket>,
+    /// Scenario file replayed by the syncers instead of querying the backends, for tests only
+    pub mock_scenario: Option<String>,
+    /// Named syncers of the network, each configured by a complete section of its own, that a
+    /// swap can select instead of the syncers configured above
+    pub instances: Option<HashMap<String, SyncerServers>>,
 }
 
 #[derive(Deserialize, Serialize, Default, Debug, Clone)]
@@ -664,36 +1173,347 @@ impl Default for FarcasterdConfig {
             // write the default port and ip in the generated config
             bind_port: Some(FARCASTER_BIND_PORT),
             bind_ip: Some(FARCASTER_BIND_IP.to_string()),
+            peer_rate_limit: None,
+            peer_ping: None,
+            wallet_account: None,
+            webhook: None,
+            price_oracle: None,
+            checkpoint_retention: None,
+            swap_deadline: None,
+            max_concurrent_swaps: None,
+            rebroadcast_interval: None,
+            count_unconfirmed_funding: None,
+            log_format: None,
+            log_level: None,
+            bus_transport: None,
+            auto_spawn_syncers: None,
         }
     }
 }
 
-/// Parse a configuration file and return a [`Config`]
+/// Environment variables overriding a key of the configuration file
+const ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("FARCASTER_BIND_IP", "farcasterd.bind_ip"),
+    ("FARCASTER_BIND_PORT", "farcasterd.bind_port"),
+    ("FARCASTER_AUTO_RESTORE", "farcasterd.auto_restore"),
+    ("FARCASTER_WALLET_ACCOUNT", "farcasterd.wallet_account"),
+    (
+        "FARCASTER_MAX_CONCURRENT_SWAPS",
+        "farcasterd.max_concurrent_swaps",
+    ),
+    ("FARCASTER_SWAP_DEADLINE", "farcasterd.swap_deadline"),
+    ("FARCASTER_LOG_FORMAT", "farcasterd.log_format"),
+    ("FARCASTER_LOG_LEVEL", "farcasterd.log_level"),
+    ("FARCASTER_BUS_TRANSPORT", "farcasterd.bus_transport"),
+    ("FARCASTER_GRPC_ENABLE", "grpc.enable"),
+    ("FARCASTER_GRPC_BIND_PORT", "grpc.bind_port"),
+    ("FARCASTER_GRPC_BIND_IP", "grpc.bind_ip"),
+];
+
+/// Environment variables overriding a key of the syncers configuration of a network, prefixed
+/// with `FARCASTER_<NETWORK>_`, e.g. `FARCASTER_MAINNET_BITCOIN_RPC_URL`
+const SYNCER_ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("ELECTRUM_SERVER", "electrum_server"),
+    ("BITCOIN_RPC_URL", "bitcoin_rpc"),
+    ("BITCOIN_COOKIE_PATH", "bitcoin_cookie_path"),
+    ("BITCOIN_RPC_USER", "bitcoin_rpc_user"),
+    ("BITCOIN_RPC_PASS", "bitcoin_rpc_pass"),
+    ("MONERO_DAEMON", "monero_daemon"),
+    ("MONERO_RPC_WALLET", "monero_rpc_wallet"),
+    ("MONERO_LWS", "monero_lws"),
+];
+
+/// Parse a configuration file and return a [`Config`]. Values set in the environment, see
+/// [`ENV_OVERRIDES`] and [`SYNCER_ENV_OVERRIDES`], take precedence over the file, and the file
+/// over the defaults. Overridden values are validated as if they were set in the file.
 pub fn parse_config(path: &str) -> Result<Config, Error> {
     if Path::new(path).exists() {
-        let config_file = path;
-        info!("Loading config file at: {}", &config_file);
-        let mut settings = config::Config::default();
-        settings.merge(config::File::with_name(config_file).required(true))?;
-        let conf = settings.try_into::<Config>().map_err(Into::into);
-        trace!("{:#?}", conf);
-        conf
+        info!("Loading config file at: {}", path);
     } else {
         info!("No configuration file found, generating default config");
         let config = Config::default();
         let mut file = File::create(path)?;
         file.write_all(toml::to_vec(&config).unwrap().as_ref())?;
-        Ok(config)
     }
+    let mut settings = config::Config::default();
+    settings.merge(config::File::with_name(path).required(true))?;
+    apply_env_overrides(&mut settings, |var| std::env::var(var).ok())?;
+    let conf = settings.try_into::<Config>().map_err(Into::into);
+    trace!("{:#?}", conf);
+    conf
+}
+
+/// Set the configuration keys overridden by the variables found with the lookup
+fn apply_env_overrides(
+    settings: &mut config::Config,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Result<(), Error> {
+    let mut overrides: Vec<(String, String)> = ENV_OVERRIDES
+        .iter()
+        .map(|(var, key)| (var.to_string(), key.to_string()))
+        .collect();
+    for network in ["mainnet", "testnet", "local"] {
+        overrides.extend(SYNCER_ENV_OVERRIDES.iter().map(|(var, key)| {
+            (
+                format!("FARCASTER_{}_{}", network.to_uppercase(), var),
+                format!("syncers.{}.{}", network, key),
+            )
+        }));
+    }
+    for (var, key) in overrides {
+        if let Some(value) = lookup(&var) {
+            debug!("Overriding {} of the config file with {}", key, var);
+            settings.set(&key, value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Settings applied when farcasterd reloads its configuration file. They are read each time they
+/// are used, the swaps, peer connections and syncers started after the reload follow the new
+/// values. The log level and the poll intervals of the syncers are also pushed to the running
+/// services.
+pub const RELOADABLE_SETTINGS: &[&str] = &[
+    "farcasterd.log_level",
+    "farcasterd.auto_funding",
+    "farcasterd.peer_rate_limit",
+    "farcasterd.peer_ping",
+    "farcasterd.webhook",
+    "farcasterd.price_oracle",
+    "farcasterd.swap_deadline",
+    "farcasterd.max_concurrent_swaps",
+    "farcasterd.rebroadcast_interval",
+    "farcasterd.count_unconfirmed_funding",
+    "farcasterd.auto_spawn_syncers",
+    "swap",
+    "syncers",
+];
+
+/// Settings only read when farcasterd starts, or forwarded to the services it launches at
+/// start-up. Changing them requires a restart.
+pub const FIXED_SETTINGS: &[&str] = &[
+    "farcasterd.bind_port",
+    "farcasterd.bind_ip",
+    "farcasterd.auto_restore",
+    "farcasterd.wallet_account",
+    "farcasterd.checkpoint_retention",
+    "farcasterd.log_format",
+    "farcasterd.bus_transport",
+    "grpc",
+    "metrics",
+];
+
+/// Changed settings of a configuration reload
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct ConfigReload {
+    /// Settings whose new value applies from now on
+    pub applied: Vec<String>,
+    /// Settings whose new value requires a restart, the current value is kept
+    pub rejected: Vec<String>,
+}
+
+/// Value of a dotted setting, e.g. `farcasterd.bind_port`, none if it is not set
+fn setting<'a>(config: &'a toml::Value, key: &str) -> Option<&'a toml::Value> {
+    key.split('.')
+        .try_fold(config, |value, name| value.as_table()?.get(name))
+}
+
+/// Set or unset the value of a dotted setting, creating its missing parent tables
+fn set_setting(config: &mut toml::Value, key: &str, value: Option<toml::Value>) {
+    let mut names: Vec<&str> = key.split('.').collect();
+    let name = names.pop().expect("split yields at least one name");
+    let mut table = config;
+    for parent in names {
+        let parent_table = match table.as_table_mut() {
+            Some(parent_table) => parent_table,
+            None => return,
+        };
+        table = parent_table
+            .entry(parent)
+            .or_insert_with(|| toml::Value::Table(toml::value::Table::new()));
+    }
+    if let Some(table) = table.as_table_mut() {
+        match value {
+            Some(value) => table.insert(name.to_string(), value),
+            None => table.remove(name),
+        };
+    }
+}
+
+/// Read a configuration file before the logger is initialized. Errors are ignored here, they are
+/// reported when the file is parsed with [`parse_config`].
+fn peek_config(path: &str) -> Option<Config> {
+    if !Path::new(path).exists() {
+        return None;
+    }
+    let mut settings = config::Config::default();
+    settings
+        .merge(config::File::with_name(path).required(true))
+        .ok()?;
+    apply_env_overrides(&mut settings, |var| std::env::var(var).ok()).ok()?;
+    settings.try_into::<Config>().ok()
+}
+
+/// Read the log format of a configuration file before the logger is initialized
+pub fn peek_log_format(path: &str) -> Option<LogFormat> {
+    peek_config(path)?.get_log_format()
+}
+
+/// Read the bus transport of a configuration file before the service buses are set up
+pub fn peek_bus_transport(path: &str) -> Option<BusTransport> {
+    peek_config(path)?.get_bus_transport()
 }
 
 #[cfg(test)]
 mod tests {
-    use super::parse_config;
+    use super::{apply_env_overrides, parse_config, Config};
+    use crate::SyncerInstance;
+    use farcaster_core::blockchain::Network;
 
     #[test]
     fn config_example_parse() {
         let config = parse_config("./farcasterd.toml").expect("correct config example");
         dbg!(config);
     }
+
+    fn settings_with_overrides(vars: &[(&str, &str)]) -> Result<Config, crate::Error> {
+        let mut settings = config::Config::default();
+        settings.merge(config::File::with_name("./farcasterd.toml").required(true))?;
+        apply_env_overrides(&mut settings, |var| {
+            vars.iter()
+                .find(|(name, _)| *name == var)
+                .map(|(_, value)| value.to_string())
+        })?;
+        settings.try_into::<Config>().map_err(Into::into)
+    }
+
+    #[test]
+    fn env_overrides_take_precedence() {
+        let config = settings_with_overrides(&[
+            ("FARCASTER_BIND_PORT", "9735"),
+            ("FARCASTER_MAINNET_BITCOIN_RPC_URL", "http://localhost:8332"),
+        ])
+        .expect("valid overrides");
+        assert_eq!(config.farcasterd.unwrap().bind_port, Some(9735));
+        let servers = config.get_syncer_servers(Network::Mainnet).unwrap();
+        assert_eq!(
+            servers.bitcoin_rpc.as_deref(),
+            Some("http://localhost:8332")
+        );
+        // keys not overridden keep the value of the file
+        assert_eq!(servers.electrum_server, "ssl://blockstream.info:700");
+    }
+
+    #[test]
+    fn env_overrides_are_validated() {
+        assert!(settings_with_overrides(&[("FARCASTER_BIND_PORT", "not a port")]).is_err());
+        assert!(settings_with_overrides(&[("FARCASTER_LOG_FORMAT", "yaml")]).is_err());
+        assert!(settings_with_overrides(&[("FARCASTER_BUS_TRANSPORT", "udp")]).is_err());
+    }
+
+    #[test]
+    fn reload_applies_only_reloadable_settings() {
+        let mut config = parse_config("./farcasterd.toml").expect("correct config example");
+        let mut new = config.clone();
+        let farcasterd = new.farcasterd.as_mut().unwrap();
+        farcasterd.max_concurrent_swaps = Some(3);
+        farcasterd.bind_port = Some(9735);
+        let reload = config.reload(new).expect("valid reload");
+        assert_eq!(reload.applied, vec!["farcasterd.max_concurrent_swaps"]);
+        assert_eq!(reload.rejected, vec!["farcasterd.bind_port"]);
+        assert_eq!(config.get_max_concurrent_swaps(), 3);
+        assert_eq!(config.farcasterd.unwrap().bind_port, Some(7067));
+    }
+
+    #[test]
+    fn reload_applies_log_level_and_poll_intervals() {
+        let mut config = parse_config("./farcasterd.toml").expect("correct config example");
+        let mut new = config.clone();
+        new.farcasterd.as_mut().unwrap().log_level = Some("debug".to_string());
+        new.syncers
+            .as_mut()
+            .unwrap()
+            .mainnet
+            .as_mut()
+            .unwrap()
+            .poll_interval = Some(2000);
+        let reload = config.reload(new).expect("valid reload");
+        assert_eq!(reload.applied, vec!["farcasterd.log_level", "syncers"]);
+        assert!(reload.rejected.is_empty());
+        assert_eq!(config.get_log_level(), Some("debug".to_string()));
+        assert_eq!(
+            config
+                .get_syncer_servers(Network::Mainnet)
+                .and_then(|servers| servers.poll_interval),
+            Some(2000)
+        );
+    }
+
+    #[test]
+    fn syncer_instances_are_listed_after_the_default() {
+        let mut config = parse_config("./farcasterd.toml").expect("correct config example");
+        let servers = config.get_syncer_servers(Network::Local).unwrap();
+        let instances = ["fallback", "backup"]
+            .iter()
+            .map(|name| (name.to_string(), servers.clone()))
+            .collect();
+        config
+            .syncers
+            .as_mut()
+            .unwrap()
+            .local
+            .as_mut()
+            .unwrap()
+            .instances = Some(instances);
+        assert_eq!(
+            config.get_syncer_instances(Network::Local),
+            vec![
+                SyncerInstance::DEFAULT,
+                SyncerInstance(Some("backup".to_string())),
+                SyncerInstance(Some("fallback".to_string())),
+            ]
+        );
+        assert_eq!(
+            config
+                .get_syncer_instance_servers(
+                    Network::Local,
+                    &SyncerInstance(Some("backup".to_string()))
+                )
+                .map(|servers| servers.electrum_server),
+            Some(servers.electrum_server)
+        );
+        assert!(config
+            .get_syncer_instance_servers(Network::Local, &SyncerInstance(Some("none".to_string())))
+            .is_none());
+    }
+
+    #[test]
+    fn monero_unlock_depth_follows_the_syncer_instance() {
+        let mut config = parse_config("./farcasterd.toml").expect("correct config example");
+        let local = config.syncers.as_mut().unwrap().local.as_mut().unwrap();
+        let mut backup = local.clone();
+        backup.monero_unlock_depth = Some(20);
+        local.instances = Some(std::iter::once(("backup".to_string(), backup)).collect());
+        assert_eq!(
+            config.get_monero_unlock_depth(Network::Local, &SyncerInstance::DEFAULT),
+            None
+        );
+        assert_eq!(
+            config.get_monero_unlock_depth(
+                Network::Local,
+                &SyncerInstance(Some("backup".to_string()))
+            ),
+            Some(20)
+        );
+    }
+
+    #[test]
+    fn bus_transport_is_configurable() {
+        let config = settings_with_overrides(&[("FARCASTER_BUS_TRANSPORT", "tcp")])
+            .expect("valid overrides");
+        assert_eq!(
+            config.get_bus_transport(),
+            Some(crate::opts::BusTransport::Tcp)
+        );
+    }
 }
@@ -0,0 +1,153 @@
+// Copyright 2020-2022 Farcaster Devs & LNP/BP Standards Association
+//
+// Use of this source code is governed by an MIT-style
+// license that can be found in the LICENSE file or at
+// https://opensource.org/licenses/MIT.
+
+//! Versioned on-disk format of the swap checkpoints.
+//!
+//! A stored checkpoint starts with the `FCKP` magic bytes followed by a version byte:
+//!  - `0x00`: the strict encoded checkpoint, in plaintext
+//!  - `0x02`: a 12 bytes nonce followed by the strict encoded checkpoint encrypted with
+//!    ChaCha20-Poly1305 under a key derived from the passphrase with PBKDF2-HMAC-SHA256 and
+//!    the random salt stored in the database
+//!
+//! Checkpoints written before the format was versioned do not start with the magic bytes and
+//! are loaded as plaintext.
+
+use bitcoin::secp256k1::rand;
+use chacha20poly1305::aead::{Aead, NewAead};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hmac::Hmac;
+use sha2::Sha256;
+
+use crate::Error;
+
+const MAGIC: &[u8; 4] = b"FCKP";
+const VERSION_PLAINTEXT: u8 = 0x00;
+const VERSION_CHACHA20_POLY1305_PBKDF2: u8 = 0x02;
+const NONCE_LEN: usize = 12;
+/// Length of the random salt the checkpoint key is derived with.
+pub const SALT_LEN: usize = 16;
+/// PBKDF2-HMAC-SHA256 iteration count, as recommended by OWASP.
+const PBKDF2_ROUNDS: u32 = 600_000;
+
+/// Encrypts and decrypts the checkpoints stored in the database. Without a passphrase
+/// checkpoints are written in plaintext and encrypted checkpoints cannot be read.
+pub struct CheckpointCipher {
+    key: Option<Key>,
+}
+
+impl CheckpointCipher {
+    /// Derives the checkpoint key from the passphrase and the database salt, see
+    /// [`SALT_LEN`].
+    pub fn new(passphrase: Option<&str>, salt: &[u8]) -> Self {
+        Self::with_rounds(passphrase, salt, PBKDF2_ROUNDS)
+    }
+
+    fn with_rounds(passphrase: Option<&str>, salt: &[u8], rounds: u32) -> Self {
+        Self {
+            key: passphrase.map(|passphrase| {
+                let mut key = Key::default();
+                pbkdf2::pbkdf2::<Hmac<Sha256>>(passphrase.as_bytes(), salt, rounds, &mut key);
+                key
+            }),
+        }
+    }
+
+    pub fn is_encrypting(&self) -> bool {
+        self.key.is_some()
+    }
+
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut sealed = MAGIC.to_vec();
+        match &self.key {
+            Some(key) => {
+                let nonce: [u8; NONCE_LEN] = rand::random();
+                let ciphertext = ChaCha20Poly1305::new(key)
+                    .encrypt(Nonce::from_slice(&nonce), plaintext)
+                    .map_err(|_| {
+                        Error::Farcaster("Failed to encrypt the checkpoint".to_string())
+                    })?;
+                sealed.push(VERSION_CHACHA20_POLY1305_PBKDF2);
+                sealed.extend_from_slice(&nonce);
+                sealed.extend(ciphertext);
+            }
+            None => {
+                sealed.push(VERSION_PLAINTEXT);
+                sealed.extend_from_slice(plaintext);
+            }
+        }
+        Ok(sealed)
+    }
+
+    pub fn open(&self, sealed: &[u8]) -> Result<Vec<u8>, Error> {
+        let data = match sealed.strip_prefix(&MAGIC[..]) {
+            Some(data) => data,
+            // legacy checkpoint, written before the format was versioned
+            None => return Ok(sealed.to_vec()),
+        };
+        match data.split_first() {
+            Some((&VERSION_PLAINTEXT, plaintext)) => Ok(plaintext.to_vec()),
+            Some((&VERSION_CHACHA20_POLY1305_PBKDF2, payload)) if payload.len() >= NONCE_LEN => {
+                let key = self.key.as_ref().ok_or_else(|| {
+                    Error::Farcaster(
+                        "The checkpoint is encrypted, but no checkpoint passphrase is set"
+                            .to_string(),
+                    )
+                })?;
+                let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+                ChaCha20Poly1305::new(key)
+                    .decrypt(Nonce::from_slice(nonce), ciphertext)
+                    .map_err(|_| {
+                        Error::Farcaster(
+                            "Failed to decrypt the checkpoint, is the passphrase correct?"
+                                .to_string(),
+                        )
+                    })
+            }
+            _ => Err(Error::Farcaster(
+                "Unknown checkpoint format version".to_string(),
+            )),
+        }
+    }
+}
+
+#[test]
+fn test_checkpoint_cipher() {
+    let checkpoint = vec![0, 1, 2, 3, 4, 5];
+    let salt = [7u8; SALT_LEN];
+
+    let plain = CheckpointCipher::new(None, &salt);
+    let sealed = plain.seal(&checkpoint).unwrap();
+    assert_eq!(checkpoint, plain.open(&sealed).unwrap());
+    // legacy checkpoints are loaded as is
+    assert_eq!(checkpoint, plain.open(&checkpoint).unwrap());
+
+    let cipher = CheckpointCipher::with_rounds(Some("passphrase"), &salt, 10);
+    let sealed = cipher.seal(&checkpoint).unwrap();
+    assert!(!sealed.ends_with(&checkpoint));
+    assert_eq!(sealed[MAGIC.len()], VERSION_CHACHA20_POLY1305_PBKDF2);
+    assert_eq!(checkpoint, cipher.open(&sealed).unwrap());
+    // plaintext and legacy checkpoints remain loadable when a passphrase is set
+    assert_eq!(
+        checkpoint,
+        cipher.open(&plain.seal(&checkpoint).unwrap()).unwrap()
+    );
+    assert_eq!(checkpoint, cipher.open(&checkpoint).unwrap());
+
+    assert!(plain.open(&sealed).is_err());
+    // unknown format versions are refused
+    let mut unknown = sealed.clone();
+    unknown[MAGIC.len()] = 0x01;
+    assert!(cipher.open(&unknown).is_err());
+    assert!(CheckpointCipher::with_rounds(Some("wrong"), &salt, 10)
+        .open(&sealed)
+        .is_err());
+    // the same passphrase under another database salt derives another key
+    assert!(
+        CheckpointCipher::with_rounds(Some("passphrase"), &[8u8; SALT_LEN], 10)
+            .open(&sealed)
+            .is_err()
+    );
+}
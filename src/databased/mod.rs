@@ -4,6 +4,7 @@
 // license that can be found in the LICENSE file or at
 // https://opensource.org/licenses/MIT.
 
+mod checkpoint_cipher;
 #[cfg(feature = "shell")]
 mod opts;
 mod runtime;
@@ -12,3 +13,4 @@ mod runtime;
 pub use opts::Opts;
 pub use runtime::checkpoint_send;
 pub use runtime::run;
+pub use runtime::CheckpointRetention;
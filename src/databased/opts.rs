@@ -6,6 +6,8 @@
 
 use std::path::PathBuf;
 
+use super::CheckpointRetention;
+
 /// database daemon; part of Farcaster Node
 #[derive(Parser, Clone, PartialEq, Eq, Debug)]
 #[clap(name = "databased", bin_name = "databased", author, version)]
@@ -14,6 +16,23 @@ pub struct Opts {
     /// command-line args or environment variables
     #[clap(flatten)]
     pub shared: crate::opts::Opts,
+
+    /// Passphrase used to encrypt the swap checkpoints with ChaCha20-Poly1305
+    ///
+    /// If unset, checkpoints are stored in plaintext. Checkpoints stored before the passphrase
+    /// was set remain loadable.
+    #[clap(long, env = "FARCASTER_CHECKPOINT_PASSPHRASE", hide_env_values = true)]
+    pub checkpoint_passphrase: Option<String>,
+
+    /// Maximum age, in seconds, of the checkpoints of swaps in a terminal state; older ones are
+    /// deleted. Unset by default, ended swap checkpoints are kept
+    #[clap(long)]
+    pub checkpoint_max_age: Option<u64>,
+
+    /// Maximum number of checkpoints of swaps in a terminal state kept, the most recent ones.
+    /// Unset by default, ended swap checkpoints are kept
+    #[clap(long)]
+    pub checkpoint_max_terminal: Option<usize>,
 }
 
 impl Opts {
@@ -21,6 +40,17 @@ impl Opts {
         self.shared.process();
     }
 
+    /// The retention policy of ended swap checkpoints, none unless a limit is set
+    pub fn checkpoint_retention(&self) -> Option<CheckpointRetention> {
+        if self.checkpoint_max_age.is_none() && self.checkpoint_max_terminal.is_none() {
+            return None;
+        }
+        Some(CheckpointRetention {
+            max_age: self.checkpoint_max_age,
+            max_terminal: self.checkpoint_max_terminal,
+        })
+    }
+
     pub fn absolute_data_dir_path(&self) -> PathBuf {
         PathBuf::from(shellexpand::tilde(&self.shared.data_dir.to_string_lossy()).to_string())
     }
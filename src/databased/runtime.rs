@@ -4,29 +4,51 @@
 // license that can be found in the LICENSE file or at
 // https://opensource.org/licenses/MIT.
 
+use bitcoin::secp256k1::{rand, PublicKey};
 use farcaster_core::swap::btcxmr::Deal;
 use farcaster_core::swap::SwapId;
+use farcaster_core::trade::DealId;
 use farcaster_core::{blockchain::Blockchain, role::TradeRole};
 use lmdb::{Cursor, Transaction as LMDBTransaction};
 use std::io::Cursor as IoCursor;
 use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
 use strict_encoding::{StrictDecode, StrictEncode};
 
 use crate::bus::{
-    ctl::{Checkpoint, CtlMsg},
+    ctl::{BanPeer, Checkpoint, CtlMsg, PruneCheckpoints, UpdateSyncer},
     info::{Address, InfoMsg},
     info::{BitcoinAddressSwapIdPair, DealStatusSelector, MoneroAddressSwapIdPair},
-    AddressSecretKey, BitcoinSecretKeyInfo, BusMsg, CheckpointEntry, DealInfo, DealStatus, Failure,
-    FailureCode, MoneroSecretKeyInfo, Outcome, ServiceBus,
+    AddressSecretKey, BitcoinSecretKeyInfo, BusMsg, CheckpointEntry, CheckpointStateInfo,
+    DealBookEntry, DealInfo, DealStatus, Failure, FailureCode, MoneroSecretKeyInfo, OptionDetails,
+    Outcome, ServiceBus,
 };
-use crate::{swapd::CheckpointSwapd, Endpoints};
+use crate::farcasterd::remove_progress_log;
+use crate::swapd::{CheckpointSwapd, SwapStateMachine};
+use crate::Endpoints;
+
+use super::checkpoint_cipher::{CheckpointCipher, SALT_LEN};
 use crate::{CtlServer, Error, LogStyle, Service, ServiceConfig, ServiceId};
 use microservices::esb::{self, Handler};
 
-pub fn run(config: ServiceConfig, data_dir: PathBuf) -> Result<(), Error> {
+pub fn run(
+    config: ServiceConfig,
+    data_dir: PathBuf,
+    checkpoint_passphrase: Option<String>,
+    retention: Option<CheckpointRetention>,
+) -> Result<(), Error> {
+    let mut database = Database::new(data_dir.clone()).unwrap();
+    let salt = database.checkpoint_salt()?;
+    let cipher = CheckpointCipher::new(checkpoint_passphrase.as_deref(), &salt);
+    if cipher.is_encrypting() {
+        info!("{} will encrypt swap checkpoints", "databased".label());
+    }
     let runtime = Runtime {
         identity: ServiceId::Database,
-        database: Database::new(data_dir).unwrap(),
+        database,
+        data_dir,
+        cipher,
+        retention,
     };
 
     Service::run(config, runtime, false)
@@ -35,6 +57,40 @@ pub fn run(config: ServiceConfig, data_dir: PathBuf) -> Result<(), Error> {
 pub struct Runtime {
     identity: ServiceId,
     database: Database,
+    /// Data directory holding the swap progress logs, deleted with their checkpoints
+    data_dir: PathBuf,
+    cipher: CheckpointCipher,
+    /// Retention of ended swap checkpoints, none keeps them all
+    retention: Option<CheckpointRetention>,
+}
+
+/// Retention policy applied to the checkpoints of swaps in a terminal state on every new
+/// checkpoint, each limit applies only if set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckpointRetention {
+    /// Maximum time, in seconds, a checkpoint is kept after its swap ended
+    pub max_age: Option<u64>,
+    /// Maximum number of terminal checkpoints kept, the most recently ended ones
+    pub max_terminal: Option<usize>,
+}
+
+impl CheckpointRetention {
+    /// Select among the ended swaps, given with their end timestamp, those ended for longer
+    /// than the maximum age or in excess of the most recently ended ones kept
+    fn expired(&self, ends: &[(SwapId, u64)], now: u64) -> Vec<SwapId> {
+        let mut ends: Vec<&(SwapId, u64)> = ends.iter().collect();
+        ends.sort_by(|a, b| b.1.cmp(&a.1));
+        ends.iter()
+            .enumerate()
+            .filter(|(rank, (_, ended_at))| {
+                self.max_terminal.map_or(false, |max| *rank >= max)
+                    || self
+                        .max_age
+                        .map_or(false, |max| now.saturating_sub(*ended_at) > max)
+            })
+            .map(|(_, (swap_id, _))| *swap_id)
+            .collect()
+    }
 }
 
 impl Runtime {}
@@ -86,12 +142,32 @@ impl Runtime {
                 debug!("Received Hello from {}", source);
             }
 
+            CtlMsg::Heartbeat if source == ServiceId::Farcasterd => {
+                endpoints.send_to(
+                    ServiceBus::Ctl,
+                    self.identity(),
+                    ServiceId::Farcasterd,
+                    BusMsg::Ctl(CtlMsg::Heartbeat),
+                )?;
+            }
+
+            CtlMsg::Terminate if source == ServiceId::Farcasterd => {
+                info!("Terminating {}", self.identity());
+                std::process::exit(0);
+            }
+
+            CtlMsg::SetLogFilter(filter) if source == ServiceId::Farcasterd => {
+                crate::logger::set_filter(filter.as_deref());
+            }
+
             CtlMsg::Checkpoint(Checkpoint { swap_id, state }) => {
                 let info = CheckpointEntry {
                     swap_id,
                     deal: state.deal.clone(),
                     trade_role: state.local_trade_role,
                     expected_counterparty_node_id: state.connected_counterparty_node_id,
+                    arbitrating_syncer_instance: state.bitcoin_syncer_instance.0.clone(),
+                    accordant_syncer_instance: state.monero_syncer_instance.0.clone(),
                 };
                 debug!("{} | setting checkpoint info entry", swap_id.swap_id());
                 self.database.set_checkpoint_info(&swap_id, &info)?;
@@ -101,10 +177,47 @@ impl Runtime {
                     swap_id,
                     service_id: source,
                 };
+                let ended = matches!(state.state, SwapStateMachine::SwapEnd(_));
                 let mut state_encoded = vec![];
                 state.strict_encode(&mut state_encoded)?;
-                self.database.set_checkpoint_state(&key, &state_encoded)?;
+                let state_sealed = self.cipher.seal(&state_encoded)?;
+                self.database.set_checkpoint_state(&key, &state_sealed)?;
+                let timestamp = SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap_or_else(|_| Duration::from_secs(0))
+                    .as_secs();
+                self.database
+                    .set_checkpoint_timestamp(&swap_id, timestamp)?;
+                if ended && self.database.get_checkpoint_end(&swap_id).is_err() {
+                    self.database.set_checkpoint_end(&swap_id, timestamp)?;
+                }
                 debug!("{} | checkpoint set", swap_id.swap_id());
+                self.enforce_checkpoint_retention(timestamp);
+            }
+
+            // Record the syncer a swap failed over to, so the swap is restored on it
+            CtlMsg::UpdateSyncer(UpdateSyncer {
+                swap_id,
+                new_syncer: ServiceId::Syncer(blockchain, _, instance),
+            }) if source == ServiceId::Farcasterd => {
+                match self.database.get_checkpoint_info(&swap_id) {
+                    Ok(mut info) => {
+                        if blockchain == info.deal.parameters.arbitrating_blockchain {
+                            info.arbitrating_syncer_instance = instance.0;
+                        } else {
+                            info.accordant_syncer_instance = instance.0;
+                        }
+                        self.database.set_checkpoint_info(&swap_id, &info)?;
+                        debug!("{} | updated the checkpoint syncers", swap_id.swap_id());
+                    }
+                    Err(err) => {
+                        debug!(
+                            "{} | No checkpoint to update the syncer of: {}",
+                            swap_id.swap_id(),
+                            err
+                        );
+                    }
+                }
             }
 
             CtlMsg::RestoreCheckpoint(CheckpointEntry { swap_id, .. }) => {
@@ -112,8 +225,10 @@ impl Runtime {
                     swap_id,
                     service_id: ServiceId::Swap(swap_id),
                 }) {
-                    Ok(raw_state) => {
-                        match CheckpointSwapd::strict_decode(IoCursor::new(raw_state)) {
+                    Ok(sealed_state) => {
+                        match self.cipher.open(&sealed_state).and_then(|raw_state| {
+                            Ok(CheckpointSwapd::strict_decode(IoCursor::new(raw_state))?)
+                        }) {
                             Ok(state) => {
                                 endpoints.send_to(
                                     ServiceBus::Ctl,
@@ -137,18 +252,38 @@ impl Runtime {
             }
 
             CtlMsg::RemoveCheckpoint(swap_id) => {
-                if let Err(err) = self.database.delete_checkpoint_state(CheckpointKey {
-                    swap_id,
-                    service_id: ServiceId::Swap(swap_id),
-                }) {
-                    debug!(
-                        "{} | Did not delete checkpoint swap entry: {}",
-                        swap_id, err
-                    );
-                }
-                if let Err(err) = self.database.delete_checkpoint_info(swap_id) {
-                    debug!("{} | Did not delete checkpoint info: {}", swap_id, err);
+                self.remove_checkpoint(swap_id);
+            }
+
+            CtlMsg::PruneCheckpoints(PruneCheckpoints { keep_terminal }) => {
+                let infos = self.database.get_all_checkpoint_info()?;
+                let states = self.checkpoint_states()?;
+                let mut pruned: Vec<SwapId> = infos
+                    .iter()
+                    .filter(|info| !states.iter().any(|state| state.swap_id == info.swap_id))
+                    .map(|info| info.swap_id)
+                    .collect();
+                pruned.extend(
+                    states
+                        .iter()
+                        .filter(|state| {
+                            (state.terminal && !keep_terminal)
+                                || !infos.iter().any(|info| info.swap_id == state.swap_id)
+                        })
+                        .map(|state| state.swap_id),
+                );
+                for swap_id in pruned.iter() {
+                    info!("{} | Pruning checkpoint", swap_id.swap_id());
+                    self.remove_checkpoint(*swap_id);
                 }
+                self.send_client_ctl(
+                    endpoints,
+                    source,
+                    CtlMsg::Success(OptionDetails::with(format!(
+                        "Pruned {} checkpoints",
+                        pruned.len()
+                    ))),
+                )?;
             }
 
             CtlMsg::SetAddressSecretKey(AddressSecretKey::Bitcoin {
@@ -173,6 +308,10 @@ impl Runtime {
                 local_trade_role,
                 ..
             }) => {
+                self.database.set_deal_book_status(&deal.id(), &status)?;
+                if let DealStatus::Ended(_) = status {
+                    self.mark_deal_checkpoints_ended(&deal)?;
+                }
                 self.database.set_deal(
                     &deal,
                     &DealValue {
@@ -182,12 +321,35 @@ impl Runtime {
                 )?;
             }
 
+            CtlMsg::BanPeer(BanPeer { node_id, .. }) => {
+                self.database.set_banned_peer(&node_id)?;
+            }
+
+            CtlMsg::UnbanPeer(node_id) => {
+                self.database.delete_banned_peer(&node_id)?;
+            }
+
+            CtlMsg::SetDealBookEntry(entry) => {
+                self.database.set_deal_book_entry(&entry)?;
+            }
+
+            CtlMsg::RemoveDealBookEntry(deal_id) => {
+                self.database.delete_deal_book_entry(&deal_id)?;
+            }
+
             CtlMsg::CleanDanglingDeals => {
+                // deals in the deal book are re-advertised by farcasterd, they are not dangling
                 let checkpointed_pub_deals: Vec<Deal> = self
                     .database
                     .get_all_checkpoint_info()?
                     .drain(..)
                     .map(|info| info.deal)
+                    .chain(
+                        self.database
+                            .get_deal_book()?
+                            .drain(..)
+                            .map(|entry| entry.deal),
+                    )
                     .collect();
                 let mut in_progress = self.database.get_deals(DealStatusSelector::InProgress)?;
                 self.database
@@ -232,6 +394,20 @@ impl Runtime {
                 self.send_client_info(endpoints, source, InfoMsg::DealInfoList(deal_infos.into()))?;
             }
 
+            InfoMsg::ListDealBook => {
+                let deal_book = self.database.get_deal_book()?;
+                self.send_client_info(endpoints, source, InfoMsg::DealBook(deal_book.into()))?;
+            }
+
+            InfoMsg::ListBannedPeers => {
+                let banned_peers = self.database.get_all_banned_peers()?;
+                self.send_client_info(
+                    endpoints,
+                    source,
+                    InfoMsg::BannedPeerList(banned_peers.into()),
+                )?;
+            }
+
             InfoMsg::RetrieveAllCheckpointInfo => {
                 match self.database.get_all_checkpoint_info() {
                     Ok(list) => {
@@ -247,7 +423,7 @@ impl Runtime {
                             endpoints,
                             source,
                             CtlMsg::Failure(Failure {
-                                code: FailureCode::Unknown,
+                                code: FailureCode::CheckpointNotFound,
                                 info: "Failed to retrieve checkpoint list".to_string(),
                             }),
                         )?;
@@ -255,6 +431,15 @@ impl Runtime {
                 };
             }
 
+            InfoMsg::ListCheckpoints => {
+                let states = self.checkpoint_states()?;
+                self.send_client_info(
+                    endpoints,
+                    source,
+                    InfoMsg::CheckpointStateList(states.into()),
+                )?;
+            }
+
             InfoMsg::GetCheckpointEntry(swap_id) => {
                 match self.database.get_checkpoint_info(&swap_id) {
                     Ok(entry) => {
@@ -266,7 +451,7 @@ impl Runtime {
                             endpoints,
                             source,
                             CtlMsg::Failure(Failure {
-                                code: FailureCode::Unknown,
+                                code: FailureCode::CheckpointNotFound,
                                 info: format!(
                                     "Could not retrieve checkpoint entry for {}",
                                     swap_id
@@ -284,7 +469,7 @@ impl Runtime {
                             endpoints,
                             source,
                             CtlMsg::Failure(Failure {
-                                code: FailureCode::Unknown,
+                                code: FailureCode::AddressNotFound,
                                 info: format!(
                                     "Could not retrieve secret key for address {}",
                                     address
@@ -311,7 +496,7 @@ impl Runtime {
                         endpoints,
                         source,
                         CtlMsg::Failure(Failure {
-                            code: FailureCode::Unknown,
+                            code: FailureCode::AddressNotFound,
                             info: format!("Could not retrieve secret key for address {}", address),
                         }),
                     )?,
@@ -363,6 +548,113 @@ impl Runtime {
 
         Ok(())
     }
+
+    fn remove_checkpoint(&mut self, swap_id: SwapId) {
+        if let Err(err) = self.database.delete_checkpoint_state(CheckpointKey {
+            swap_id,
+            service_id: ServiceId::Swap(swap_id),
+        }) {
+            debug!(
+                "{} | Did not delete checkpoint swap entry: {}",
+                swap_id, err
+            );
+        }
+        if let Err(err) = self.database.delete_checkpoint_info(swap_id) {
+            debug!("{} | Did not delete checkpoint info: {}", swap_id, err);
+        }
+        if let Err(err) = self.database.delete_checkpoint_timestamp(swap_id) {
+            debug!("{} | Did not delete checkpoint timestamp: {}", swap_id, err);
+        }
+        if let Err(err) = self.database.delete_checkpoint_end(swap_id) {
+            debug!("{} | Did not delete checkpoint end: {}", swap_id, err);
+        }
+        if let Err(err) = remove_progress_log(&self.data_dir, swap_id) {
+            warn!("{} | Failed to delete the progress log: {}", swap_id, err);
+        }
+    }
+
+    /// Records the end of the checkpointed swaps of an ended deal, the time retention ages
+    /// their checkpoints from
+    fn mark_deal_checkpoints_ended(&mut self, deal: &Deal) -> Result<(), Error> {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_else(|_| Duration::from_secs(0))
+            .as_secs();
+        for info in self.database.get_all_checkpoint_info()? {
+            if info.deal == *deal && self.database.get_checkpoint_end(&info.swap_id).is_err() {
+                self.database.set_checkpoint_end(&info.swap_id, now)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Deletes the terminal checkpoints expired under the retention policy, all at once. Only
+    /// the stored end timestamps are read, checkpoints are not decoded.
+    fn enforce_checkpoint_retention(&mut self, now: u64) {
+        let retention = match self.retention {
+            Some(retention) => retention,
+            None => return,
+        };
+        let expired = match self.database.get_all_checkpoint_ends() {
+            Ok(ends) => retention.expired(&ends, now),
+            Err(err) => {
+                warn!("Failed to apply the checkpoint retention policy: {}", err);
+                return;
+            }
+        };
+        if expired.is_empty() {
+            return;
+        }
+        match self.database.delete_checkpoints(&expired) {
+            Ok(()) => {
+                for swap_id in expired.iter() {
+                    info!(
+                        "{} | Deleted checkpoint under the retention policy",
+                        swap_id.swap_id()
+                    );
+                    if let Err(err) = remove_progress_log(&self.data_dir, *swap_id) {
+                        warn!("{} | Failed to delete the progress log: {}", swap_id, err);
+                    }
+                }
+            }
+            Err(err) => error!("Failed to delete the expired checkpoints: {}", err),
+        }
+    }
+
+    /// Decodes every stored checkpoint to report the swap state it captured. A swap is terminal
+    /// if the checkpointed state is an end state or if its deal ended in the deal history.
+    fn checkpoint_states(&mut self) -> Result<Vec<CheckpointStateInfo>, Error> {
+        let ended_deals: Vec<Deal> = self
+            .database
+            .get_deals(DealStatusSelector::Ended)?
+            .drain(..)
+            .map(|info| info.deal)
+            .collect();
+        let mut states = vec![];
+        for (key, sealed_state) in self.database.get_all_checkpoint_states()? {
+            let checkpoint = self.cipher.open(&sealed_state).and_then(|raw_state| {
+                Ok(CheckpointSwapd::strict_decode(IoCursor::new(raw_state))?)
+            });
+            let deal_ended = self
+                .database
+                .get_checkpoint_info(&key.swap_id)
+                .map_or(false, |info| ended_deals.contains(&info.deal));
+            let (state, terminal) = match checkpoint {
+                Ok(checkpoint) => (
+                    checkpoint.state.to_string(),
+                    deal_ended || matches!(checkpoint.state, SwapStateMachine::SwapEnd(_)),
+                ),
+                Err(err) => (format!("Unreadable checkpoint: {}", err), deal_ended),
+            };
+            states.push(CheckpointStateInfo {
+                swap_id: key.swap_id,
+                state,
+                terminal,
+                timestamp: self.database.get_checkpoint_timestamp(&key.swap_id).ok(),
+            });
+        }
+        Ok(states)
+    }
 }
 
 pub fn checkpoint_send(
@@ -397,9 +689,16 @@ struct Database(lmdb::Environment);
 
 const LMDB_CHECKPOINTS: &str = "checkpoints";
 const LMDB_CHECKPOINT_INFOS: &str = "checkpoint_infos";
+const LMDB_CHECKPOINT_TIMESTAMPS: &str = "checkpoint_timestamps";
+const LMDB_CHECKPOINT_ENDS: &str = "checkpoint_ends";
 const LMDB_BITCOIN_ADDRESSES: &str = "bitcoin_addresses";
 const LMDB_MONERO_ADDRESSES: &str = "monero_addresses";
 const LMDB_DEAL_HISTORY: &str = "deal_history";
+const LMDB_BANNED_PEERS: &str = "banned_peers";
+const LMDB_DEAL_BOOK: &str = "deal_book";
+const LMDB_METADATA: &str = "metadata";
+
+const CHECKPOINT_SALT_KEY: &[u8] = b"checkpoint_salt";
 
 impl Database {
     fn new(path: PathBuf) -> Result<Database, lmdb::Error> {
@@ -409,12 +708,43 @@ impl Database {
             .open(&path)?;
         env.create_db(Some(LMDB_CHECKPOINTS), lmdb::DatabaseFlags::empty())?;
         env.create_db(Some(LMDB_CHECKPOINT_INFOS), lmdb::DatabaseFlags::empty())?;
+        env.create_db(
+            Some(LMDB_CHECKPOINT_TIMESTAMPS),
+            lmdb::DatabaseFlags::empty(),
+        )?;
+        env.create_db(Some(LMDB_CHECKPOINT_ENDS), lmdb::DatabaseFlags::empty())?;
         env.create_db(Some(LMDB_BITCOIN_ADDRESSES), lmdb::DatabaseFlags::empty())?;
         env.create_db(Some(LMDB_DEAL_HISTORY), lmdb::DatabaseFlags::empty())?;
         env.create_db(Some(LMDB_MONERO_ADDRESSES), lmdb::DatabaseFlags::empty())?;
+        env.create_db(Some(LMDB_BANNED_PEERS), lmdb::DatabaseFlags::empty())?;
+        env.create_db(Some(LMDB_DEAL_BOOK), lmdb::DatabaseFlags::empty())?;
+        env.create_db(Some(LMDB_METADATA), lmdb::DatabaseFlags::empty())?;
         Ok(Database(env))
     }
 
+    /// Returns the salt the checkpoint key is derived with, generating and storing a random
+    /// one on first use.
+    fn checkpoint_salt(&mut self) -> Result<[u8; SALT_LEN], Error> {
+        let db = self.0.open_db(Some(LMDB_METADATA))?;
+        let mut tx = self.0.begin_rw_txn()?;
+        let mut salt = [0u8; SALT_LEN];
+        match tx.get(db, &CHECKPOINT_SALT_KEY) {
+            Ok(stored) if stored.len() == SALT_LEN => salt.copy_from_slice(stored),
+            Ok(_) => {
+                return Err(Error::Farcaster(
+                    "The stored checkpoint salt is malformed".to_string(),
+                ))
+            }
+            Err(lmdb::Error::NotFound) => {
+                salt = rand::random();
+                tx.put(db, &CHECKPOINT_SALT_KEY, &salt, lmdb::WriteFlags::empty())?;
+            }
+            Err(err) => return Err(err.into()),
+        }
+        tx.commit()?;
+        Ok(salt)
+    }
+
     fn set_deal(&mut self, deal: &Deal, value: &DealValue) -> Result<(), Error> {
         let db = self.0.open_db(Some(LMDB_DEAL_HISTORY))?;
         let mut tx = self.0.begin_rw_txn()?;
@@ -454,6 +784,7 @@ impl Database {
                     // match all ended and revoked deals on Ended selector
                     DealStatus::Ended(_) if selector == DealStatusSelector::Ended => Some(status),
                     DealStatus::Revoked if selector == DealStatusSelector::Ended => Some(status),
+                    DealStatus::Expired if selector == DealStatusSelector::Ended => Some(status),
                     _ if selector == DealStatusSelector::All => Some(status),
                     _ => None,
                 }?;
@@ -464,6 +795,7 @@ impl Database {
                             deal,
                             status: filtered_status,
                             local_trade_role,
+                            expiry: None,
                         })
                         .map_err(Error::from),
                 )
@@ -581,6 +913,104 @@ impl Database {
         res
     }
 
+    fn set_banned_peer(&mut self, node_id: &PublicKey) -> Result<(), Error> {
+        let db = self.0.open_db(Some(LMDB_BANNED_PEERS))?;
+        let mut tx = self.0.begin_rw_txn()?;
+        let key = node_id.serialize();
+        if tx.get(db, &key).is_err() {
+            tx.put(db, &key, &[], lmdb::WriteFlags::empty())?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn delete_banned_peer(&mut self, node_id: &PublicKey) -> Result<(), Error> {
+        let db = self.0.open_db(Some(LMDB_BANNED_PEERS))?;
+        let mut tx = self.0.begin_rw_txn()?;
+        let key = node_id.serialize();
+        if tx.get(db, &key).is_ok() {
+            tx.del(db, &key, None)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_all_banned_peers(&mut self) -> Result<Vec<PublicKey>, Error> {
+        let db = self.0.open_db(Some(LMDB_BANNED_PEERS))?;
+        let tx = self.0.begin_ro_txn()?;
+        let mut cursor = tx.open_ro_cursor(db)?;
+        let res = cursor
+            .iter()
+            .map(|(key, _)| Ok(PublicKey::from_slice(key)?))
+            .collect();
+        drop(cursor);
+        tx.abort();
+        res
+    }
+
+    fn set_deal_book_entry(&mut self, entry: &DealBookEntry) -> Result<(), Error> {
+        let db = self.0.open_db(Some(LMDB_DEAL_BOOK))?;
+        let mut tx = self.0.begin_rw_txn()?;
+        let mut key = vec![];
+        entry.deal.id().strict_encode(&mut key)?;
+        if tx.get(db, &key).is_ok() {
+            tx.del(db, &key, None)?;
+        }
+        let mut val = vec![];
+        entry.strict_encode(&mut val)?;
+        tx.put(db, &key, &val, lmdb::WriteFlags::empty())?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn delete_deal_book_entry(&mut self, deal_id: &DealId) -> Result<(), Error> {
+        let db = self.0.open_db(Some(LMDB_DEAL_BOOK))?;
+        let mut tx = self.0.begin_rw_txn()?;
+        let mut key = vec![];
+        deal_id.strict_encode(&mut key)?;
+        if tx.get(db, &key).is_ok() {
+            tx.del(db, &key, None)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Follow the status of a deal of the deal book, dropping its entry once the deal is
+    /// revoked, expires or its swap ends
+    fn set_deal_book_status(&mut self, deal_id: &DealId, status: &DealStatus) -> Result<(), Error> {
+        let db = self.0.open_db(Some(LMDB_DEAL_BOOK))?;
+        let mut tx = self.0.begin_rw_txn()?;
+        let mut key = vec![];
+        deal_id.strict_encode(&mut key)?;
+        let mut entry = match tx.get(db, &key) {
+            Ok(val) => DealBookEntry::strict_decode(IoCursor::new(val.to_vec()))?,
+            Err(lmdb::Error::NotFound) => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+        tx.del(db, &key, None)?;
+        if matches!(status, DealStatus::Open | DealStatus::InProgress) {
+            entry.status = status.clone();
+            let mut val = vec![];
+            entry.strict_encode(&mut val)?;
+            tx.put(db, &key, &val, lmdb::WriteFlags::empty())?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_deal_book(&mut self) -> Result<Vec<DealBookEntry>, Error> {
+        let db = self.0.open_db(Some(LMDB_DEAL_BOOK))?;
+        let tx = self.0.begin_ro_txn()?;
+        let mut cursor = tx.open_ro_cursor(db)?;
+        let res = cursor
+            .iter()
+            .map(|(_, val)| Ok(DealBookEntry::strict_decode(IoCursor::new(val.to_vec()))?))
+            .collect();
+        drop(cursor);
+        tx.abort();
+        res
+    }
+
     fn set_checkpoint_state(
         &mut self,
         checkpoint_key: &CheckpointKey,
@@ -673,6 +1103,142 @@ impl Database {
         tx.commit()?;
         Ok(())
     }
+
+    /// Delete the checkpoint state, info, timestamp and end of the swaps in a single
+    /// transaction
+    fn delete_checkpoints(&mut self, swap_ids: &[SwapId]) -> Result<(), Error> {
+        let states_db = self.0.open_db(Some(LMDB_CHECKPOINTS))?;
+        let infos_db = self.0.open_db(Some(LMDB_CHECKPOINT_INFOS))?;
+        let timestamps_db = self.0.open_db(Some(LMDB_CHECKPOINT_TIMESTAMPS))?;
+        let ends_db = self.0.open_db(Some(LMDB_CHECKPOINT_ENDS))?;
+        let mut tx = self.0.begin_rw_txn()?;
+        for swap_id in swap_ids {
+            let mut state_key = vec![];
+            CheckpointKey {
+                swap_id: *swap_id,
+                service_id: ServiceId::Swap(*swap_id),
+            }
+            .strict_encode(&mut state_key)?;
+            let mut swap_key = vec![];
+            swap_id.strict_encode(&mut swap_key)?;
+            for (db, key) in [
+                (states_db, &state_key),
+                (infos_db, &swap_key),
+                (timestamps_db, &swap_key),
+                (ends_db, &swap_key),
+            ] {
+                match tx.del(db, key, None) {
+                    Ok(()) | Err(lmdb::Error::NotFound) => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_all_checkpoint_states(&mut self) -> Result<Vec<(CheckpointKey, Vec<u8>)>, Error> {
+        let db = self.0.open_db(Some(LMDB_CHECKPOINTS))?;
+        let tx = self.0.begin_ro_txn()?;
+        let mut cursor = tx.open_ro_cursor(db)?;
+        let res = cursor
+            .iter()
+            .map(|(key, value)| {
+                Ok((
+                    CheckpointKey::strict_decode(IoCursor::new(key.to_vec()))?,
+                    value.to_vec(),
+                ))
+            })
+            .collect();
+        drop(cursor);
+        tx.abort();
+        res
+    }
+
+    fn set_checkpoint_timestamp(&mut self, swap_key: &SwapId, timestamp: u64) -> Result<(), Error> {
+        let db = self.0.open_db(Some(LMDB_CHECKPOINT_TIMESTAMPS))?;
+        let mut tx = self.0.begin_rw_txn()?;
+        let mut key = vec![];
+        swap_key.strict_encode(&mut key)?;
+        if tx.get(db, &key).is_ok() {
+            tx.del(db, &key, None)?;
+        }
+        let mut val = vec![];
+        timestamp.strict_encode(&mut val)?;
+        tx.put(db, &key, &val, lmdb::WriteFlags::empty())?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_checkpoint_timestamp(&mut self, swap_key: &SwapId) -> Result<u64, Error> {
+        let db = self.0.open_db(Some(LMDB_CHECKPOINT_TIMESTAMPS))?;
+        let tx = self.0.begin_ro_txn()?;
+        let mut key = vec![];
+        swap_key.strict_encode(&mut key)?;
+        let val = tx.get(db, &key)?.to_vec();
+        tx.abort();
+        Ok(u64::strict_decode(IoCursor::new(val))?)
+    }
+
+    fn delete_checkpoint_timestamp(&mut self, swap_key: SwapId) -> Result<(), Error> {
+        let db = self.0.open_db(Some(LMDB_CHECKPOINT_TIMESTAMPS))?;
+        let mut tx = self.0.begin_rw_txn()?;
+        let mut key = vec![];
+        swap_key.strict_encode(&mut key)?;
+        tx.del(db, &key, None)?;
+        tx.commit()?;
+        Ok(())
+    }
+    fn set_checkpoint_end(&mut self, swap_key: &SwapId, timestamp: u64) -> Result<(), Error> {
+        let db = self.0.open_db(Some(LMDB_CHECKPOINT_ENDS))?;
+        let mut tx = self.0.begin_rw_txn()?;
+        let mut key = vec![];
+        swap_key.strict_encode(&mut key)?;
+        let mut val = vec![];
+        timestamp.strict_encode(&mut val)?;
+        tx.put(db, &key, &val, lmdb::WriteFlags::empty())?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_checkpoint_end(&mut self, swap_key: &SwapId) -> Result<u64, Error> {
+        let db = self.0.open_db(Some(LMDB_CHECKPOINT_ENDS))?;
+        let tx = self.0.begin_ro_txn()?;
+        let mut key = vec![];
+        swap_key.strict_encode(&mut key)?;
+        let val = tx.get(db, &key)?.to_vec();
+        tx.abort();
+        Ok(u64::strict_decode(IoCursor::new(val))?)
+    }
+
+    /// The ended swaps with a stored checkpoint and the time they ended at
+    fn get_all_checkpoint_ends(&mut self) -> Result<Vec<(SwapId, u64)>, Error> {
+        let db = self.0.open_db(Some(LMDB_CHECKPOINT_ENDS))?;
+        let tx = self.0.begin_ro_txn()?;
+        let mut cursor = tx.open_ro_cursor(db)?;
+        let res = cursor
+            .iter()
+            .map(|(key, value)| {
+                Ok((
+                    SwapId::strict_decode(IoCursor::new(key.to_vec()))?,
+                    u64::strict_decode(IoCursor::new(value.to_vec()))?,
+                ))
+            })
+            .collect();
+        drop(cursor);
+        tx.abort();
+        res
+    }
+
+    fn delete_checkpoint_end(&mut self, swap_key: SwapId) -> Result<(), Error> {
+        let db = self.0.open_db(Some(LMDB_CHECKPOINT_ENDS))?;
+        let mut tx = self.0.begin_rw_txn()?;
+        let mut key = vec![];
+        swap_key.strict_encode(&mut key)?;
+        tx.del(db, &key, None)?;
+        tx.commit()?;
+        Ok(())
+    }
 }
 
 #[test]
@@ -717,12 +1283,29 @@ fn test_lmdb_state() {
         trade_role: TradeRole::Maker,
         deal: Deal::from_str("Deal:Cke4ftrP5A781Vq85dgBQJNwYgBS4nuUV1LQM2fvVdFMNR4h5TrWhRR11111uMFuZTAsNgpdK8DiK11111TB9zym113GTvtvqfD1111114A4TTfFfmZoWyvpcjDBtTZCdWFSUWcRKYfEC3Y17hqaXZ3dWz11111111111111111111111111111111111111111AfZ113SEBTEspU3a").unwrap(),
         expected_counterparty_node_id: None,
+        arbitrating_syncer_instance: Some("trusted".to_string()),
+        accordant_syncer_instance: None,
     };
     database.set_checkpoint_info(&key_info, &val_info).unwrap();
     let res = database.get_checkpoint_info(&key_info).unwrap();
     assert_eq!(val_info, res);
     database.delete_checkpoint_info(key_info).unwrap();
 
+    database.set_checkpoint_timestamp(&key_info, 1000).unwrap();
+    let res = database.get_checkpoint_timestamp(&key_info).unwrap();
+    assert_eq!(1000, res);
+    database.delete_checkpoint_timestamp(key_info).unwrap();
+    assert!(database.get_checkpoint_timestamp(&key_info).is_err());
+
+    database.set_checkpoint_end(&key_info, 1200).unwrap();
+    assert_eq!(1200, database.get_checkpoint_end(&key_info).unwrap());
+    assert!(database
+        .get_all_checkpoint_ends()
+        .unwrap()
+        .contains(&(key_info, 1200)));
+    database.delete_checkpoints(&[key_info]).unwrap();
+    assert!(database.get_checkpoint_end(&key_info).is_err());
+
     let sk = SecretKey::new(&mut bitcoin::secp256k1::rand::thread_rng());
     let private_key =
         bitcoin::PrivateKey::from_slice(&sk.secret_bytes(), bitcoin::Network::Testnet).unwrap();
@@ -761,6 +1344,13 @@ fn test_lmdb_state() {
     let addrs = database.get_all_monero_addresses().unwrap();
     assert!(addrs.iter().any(|(a, _)| *a == addr));
 
+    let node_id = PublicKey::from_secret_key(bitcoin::secp256k1::SECP256K1, &sk);
+    database.set_banned_peer(&node_id).unwrap();
+    database.set_banned_peer(&node_id).unwrap();
+    assert!(database.get_all_banned_peers().unwrap().contains(&node_id));
+    database.delete_banned_peer(&node_id).unwrap();
+    assert!(!database.get_all_banned_peers().unwrap().contains(&node_id));
+
     let deal_1 = Deal::from_str("Deal:Cke4ftrP5A7MgLMaQZLZUMTC6TfkqUKBu1LQM2fvVdFMNR4gmBqNCsR11111uMFuZTAsNgpdK8DiK11111TB9zym113GTvtvqfD1111114A4TTF4h53Tv4MR6eS9sdDxV5JCH9xZcKejCqKShnphqndeeD11111111111111111111111111111111111111111AfZ113XRBtrLeA3t").unwrap();
     let deal_2 = Deal::from_str("Deal:Cke4ftrP5A7Km9Kmc2UDBePio1p7wM56P1LQM2fvVdFMNR4gmBqNCsR11111uMFuZTAsNgpdK8DiK11111TB9zym113GTvtvqfD1111114A4TTF4h53Tv4MR6eS9sdDxV5JCH9xZcKejCqKShnphqndeeD11111111111111111111111111111111111111111AfZ113XRBuLWyw3M").unwrap();
 
@@ -818,14 +1408,95 @@ fn test_lmdb_state() {
         deal: deal_1,
         status: DealStatus::Ended(Outcome::SuccessSwap),
         local_trade_role: TradeRole::Maker,
+        expiry: None,
     };
     let status_2 = DealInfo {
         serialized_deal: deal_2.to_string(),
         deal: deal_2,
         status: DealStatus::Open,
         local_trade_role: TradeRole::Maker,
+        expiry: None,
     };
     assert!(deals_retrieved.len() == 2);
     assert!(deals_retrieved.contains(&status_1));
     assert!(deals_retrieved.contains(&status_2));
+
+    let entry = DealBookEntry {
+        deal: status_2.deal.clone(),
+        arbitrating_addr: bitcoin::Address::p2wpkh(&pk, bitcoin::Network::Testnet).unwrap(),
+        accordant_addr: addr,
+        funding_confirmations: Some(3),
+        syncer_instance: None,
+        expiry: None,
+        local_trade_role: TradeRole::Taker,
+        status: DealStatus::Open,
+    };
+    database.set_deal_book_entry(&entry).unwrap();
+    database.set_deal_book_entry(&entry).unwrap();
+    assert_eq!(database.get_deal_book().unwrap(), vec![entry.clone()]);
+    database
+        .set_deal_book_status(&status_2.deal.id(), &DealStatus::InProgress)
+        .unwrap();
+    assert_eq!(
+        database.get_deal_book().unwrap(),
+        vec![DealBookEntry {
+            status: DealStatus::InProgress,
+            ..entry.clone()
+        }]
+    );
+    database
+        .set_deal_book_status(
+            &status_2.deal.id(),
+            &DealStatus::Ended(Outcome::SuccessSwap),
+        )
+        .unwrap();
+    assert!(database.get_deal_book().unwrap().is_empty());
+    database.set_deal_book_entry(&entry).unwrap();
+    database
+        .delete_deal_book_entry(&status_2.deal.id())
+        .unwrap();
+    assert!(database.get_deal_book().unwrap().is_empty());
+
+    // the checkpoint salt is generated once and then stays the same
+    let salt = database.checkpoint_salt().unwrap();
+    assert_eq!(salt, database.checkpoint_salt().unwrap());
+}
+
+#[test]
+fn test_checkpoint_retention() {
+    use farcaster_core::Uuid;
+
+    let now = 1000;
+    let recent: SwapId = Uuid::new().into();
+    let old: SwapId = Uuid::new().into();
+    let older: SwapId = Uuid::new().into();
+    let ends = vec![(older, 850), (recent, 990), (old, 950)];
+
+    // the oldest end is too old
+    let retention = CheckpointRetention {
+        max_age: Some(100),
+        max_terminal: None,
+    };
+    assert_eq!(retention.expired(&ends, now), vec![older]);
+
+    // only the two most recently ended swaps are kept
+    let retention = CheckpointRetention {
+        max_age: None,
+        max_terminal: Some(2),
+    };
+    assert_eq!(retention.expired(&ends, now), vec![older]);
+
+    // both limits apply
+    let retention = CheckpointRetention {
+        max_age: Some(20),
+        max_terminal: Some(2),
+    };
+    assert_eq!(retention.expired(&ends, now), vec![old, older]);
+
+    // no limit set keeps everything
+    let retention = CheckpointRetention {
+        max_age: None,
+        max_terminal: None,
+    };
+    assert!(retention.expired(&ends, now).is_empty());
 }
@@ -60,6 +60,12 @@ pub enum Error {
     /// Peer does not respond to ping messages
     NotResponding,
 
+    /// The daemon did not answer within {0} seconds
+    RequestTimeout(u64),
+
+    /// Swap {0} did not terminate within {1} seconds
+    WaitTimeout(farcaster_core::swap::SwapId, u64),
+
     /// Peer has misbehaved peer protocol rules
     Misbehaving,
 
@@ -154,6 +160,11 @@ pub enum SyncerError {
     #[display(inner)]
     Electrum(electrum_client::Error),
 
+    /// Generic Bitcoin Core RPC errors
+    #[from]
+    #[display(inner)]
+    BitcoinRpc(bitcoincore_rpc::Error),
+
     /// Generic Monero RPC errors
     #[from]
     #[display(inner)]
@@ -170,6 +181,32 @@ pub enum SyncerError {
 
     /// Transaction should be found in the history if we successfully queried `transaction_get`
     TxNotInHistory,
+
+    /// The syncer could not connect to its backend
+    #[display("Failed to connect to the {backend} backend: {reason}")]
+    BackendConnectionFailed { backend: String, reason: String },
+
+    /// The backend answered a call with an error
+    #[display("The backend failed the {method} call: {message}")]
+    BackendRpc { method: String, message: String },
+
+    /// The Bitcoin Core node does not maintain a synced transaction index
+    #[display("The Bitcoin Core node must run with -txindex and a synced index: {0}")]
+    TxIndexUnavailable(String),
+
+    /// The syncer runs no task of the source targeted by the request
+    #[display("task from source {0} not found")]
+    TaskNotFound(ServiceId),
+}
+
+impl SyncerError {
+    /// Error of a backend call, keeping the name of the method for the logs and the clients
+    pub fn rpc(method: impl ToString, err: impl std::fmt::Display) -> Self {
+        SyncerError::BackendRpc {
+            method: method.to_string(),
+            message: err.to_string(),
+        }
+    }
 }
 
 impl microservices::error::Error for Error {}
@@ -200,6 +237,12 @@ impl From<electrum_client::Error> for Error {
     }
 }
 
+impl From<bitcoincore_rpc::Error> for Error {
+    fn from(err: bitcoincore_rpc::Error) -> Self {
+        Error::Syncer(SyncerError::BitcoinRpc(err))
+    }
+}
+
 //
 // Custom Core error transformation
 //
@@ -0,0 +1,236 @@
+// Copyright 2020-2022 Farcaster Devs & LNP/BP Standards Association
+//
+// Use of this source code is governed by an MIT-style
+// license that can be found in the LICENSE file or at
+// https://opensource.org/licenses/MIT.
+
+//! Checks run by `farcasterd --check-config` instead of launching the runtime: the configuration
+//! parses, the syncer backends are reachable, the data directory is writable and the swap keys
+//! derive correctly from the key file.
+
+use std::fs;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use farcaster_core::blockchain::Network;
+use strict_encoding::StrictDecode;
+
+use super::Opts;
+use crate::config::{Config, SyncerServers};
+use crate::opts::FARCASTER_KEY_FILE;
+use crate::walletd::NodeSecrets;
+use crate::{AccordantBlockchain, ArbitratingBlockchain, LogStyle};
+
+/// Time given to a syncer backend to accept a connection
+const BACKEND_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of a single check, with a description of what was found or what is wrong
+pub struct Check {
+    pub name: String,
+    pub result: Result<String, String>,
+}
+
+impl Check {
+    fn new(name: impl ToString, result: Result<String, String>) -> Self {
+        Check {
+            name: name.to_string(),
+            result,
+        }
+    }
+}
+
+/// Run all checks on the configuration and environment farcasterd would start with. Nothing is
+/// left on disk: a missing configuration file is reported as the default one that would be
+/// generated, a missing key file as one that would be created.
+pub fn check_config(opts: &Opts) -> Vec<Check> {
+    let mut checks = vec![];
+
+    let config = if Path::new(&opts.config).exists() {
+        match crate::config::parse_config(&opts.config) {
+            Ok(config) => {
+                checks.push(Check::new(
+                    "configuration",
+                    Ok(format!("{} parsed", opts.config)),
+                ));
+                config
+            }
+            Err(err) => {
+                // the remaining checks depend on the configuration
+                checks.push(Check::new("configuration", Err(err.to_string())));
+                return checks;
+            }
+        }
+    } else {
+        checks.push(Check::new(
+            "configuration",
+            Ok(format!(
+                "{} not found, the default configuration would be generated",
+                opts.config
+            )),
+        ));
+        Config::default()
+    };
+
+    checks.push(Check::new(
+        "bind address",
+        config
+            .get_bind_addr()
+            .map(|addr| addr.to_string())
+            .map_err(|err| err.to_string()),
+    ));
+
+    for network in [Network::Mainnet, Network::Testnet, Network::Local] {
+        checks.push(Check::new(
+            format!("{:?} swap parameters", network),
+            config
+                .get_swap_config(
+                    ArbitratingBlockchain::Bitcoin,
+                    AccordantBlockchain::Monero,
+                    network,
+                )
+                .map(|swap_config| {
+                    format!(
+                        "bitcoin finality {}, safety {}, monero finality {}",
+                        swap_config.arbitrating.finality,
+                        swap_config.arbitrating.safety,
+                        swap_config.accordant.finality
+                    )
+                })
+                .map_err(|err| err.to_string()),
+        ));
+        if let Some(servers) = config.get_syncer_servers(network) {
+            checks.extend(check_syncer_servers(network, &servers));
+        }
+    }
+
+    let data_dir =
+        PathBuf::from(shellexpand::tilde(&opts.shared.data_dir.to_string_lossy()).to_string());
+    checks.push(Check::new("data directory", check_data_dir(&data_dir)));
+
+    // walletd inherits the environment of farcasterd
+    let mut key_file =
+        std::env::var("FARCASTER_KEY_FILE").unwrap_or_else(|_| FARCASTER_KEY_FILE.to_string());
+    opts.shared.process_dir(&mut key_file);
+    checks.push(Check::new(
+        "key derivation",
+        check_key_file(&key_file, config.get_wallet_account()),
+    ));
+
+    checks
+}
+
+/// Print the report of the checks, returns whether they all passed
+pub fn print_report(checks: &[Check]) -> bool {
+    let mut passed = true;
+    for check in checks {
+        match &check.result {
+            Ok(details) => println!("{} {}: {}", "ok".bright_green_bold(), check.name, details),
+            Err(err) => {
+                passed = false;
+                println!("{} {}: {}", "FAILED".err(), check.name, err);
+            }
+        }
+    }
+    if passed {
+        println!("{}", "Configuration is valid".bright_green_bold());
+    } else {
+        println!("{}", "Configuration is invalid".err());
+    }
+    passed
+}
+
+fn check_syncer_servers(network: Network, servers: &SyncerServers) -> Vec<Check> {
+    let mut backends = vec![];
+    match &servers.bitcoin_rpc {
+        Some(bitcoin_rpc) => backends.push(("bitcoin rpc", bitcoin_rpc)),
+        None => backends.push(("electrum server", &servers.electrum_server)),
+    }
+    backends.push(("monero daemon", &servers.monero_daemon));
+    backends.push(("monero rpc wallet", &servers.monero_rpc_wallet));
+    if let Some(monero_lws) = &servers.monero_lws {
+        backends.push(("monero lws", monero_lws));
+    }
+    backends
+        .into_iter()
+        .map(|(name, url)| {
+            Check::new(
+                format!("{:?} {}", network, name),
+                check_reachable(url).map(|addr| format!("{} reachable at {}", url, addr)),
+            )
+        })
+        .collect()
+}
+
+/// Connect to the host of a backend url, e.g. `ssl://blockstream.info:700` or
+/// `http://localhost:18083`, returning the address that accepted the connection
+fn check_reachable(url: &str) -> Result<String, String> {
+    let (scheme, rest) = match url.split_once("://") {
+        Some((scheme, rest)) => (Some(scheme), rest),
+        None => (None, url),
+    };
+    let authority = rest.split('/').next().unwrap_or(rest);
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = match (host.rsplit_once(':'), scheme) {
+        (Some((_, port)), _) if port.parse::<u16>().is_ok() => host.to_string(),
+        (_, Some("http")) => format!("{}:80", host),
+        (_, Some("https")) => format!("{}:443", host),
+        _ => return Err(format!("No port in backend url {}", url)),
+    };
+    let addrs = host
+        .to_socket_addrs()
+        .map_err(|err| format!("Cannot resolve {}: {}", host, err))?;
+    let mut last_err = format!("{} resolves to no address", host);
+    for addr in addrs {
+        match TcpStream::connect_timeout(&addr, BACKEND_CONNECT_TIMEOUT) {
+            Ok(_) => return Ok(addr.to_string()),
+            Err(err) => last_err = format!("Cannot connect to {} ({}): {}", host, addr, err),
+        }
+    }
+    Err(last_err)
+}
+
+/// Check that the data directory exists and that files can be created in it
+fn check_data_dir(data_dir: &Path) -> Result<String, String> {
+    if !data_dir.is_dir() {
+        return Err(format!("{} is not a directory", data_dir.display()));
+    }
+    let probe = data_dir.join(".farcasterd-check-config");
+    fs::write(&probe, b"").map_err(|err| {
+        format!(
+            "Cannot create files in {}, check its permissions: {}",
+            data_dir.display(),
+            err
+        )
+    })?;
+    let _ = fs::remove_file(&probe);
+    Ok(format!("{} is writable", data_dir.display()))
+}
+
+/// Load the node secrets from the key file and verify the swap keys of the wallet account derive
+/// correctly
+fn check_key_file(key_file: &str, account: u32) -> Result<String, String> {
+    if !Path::new(key_file).exists() {
+        return Ok(format!(
+            "{} not found, new node secrets would be generated",
+            key_file
+        ));
+    }
+    let file = fs::File::open(key_file).map_err(|err| {
+        format!(
+            "Cannot open key file {}, check its permissions: {}",
+            key_file, err
+        )
+    })?;
+    let node_secrets = NodeSecrets::strict_decode(file)
+        .map_err(|err| format!("Cannot decode key file {}: {}", key_file, err))?;
+    node_secrets.verify_key_manager(account)?;
+    Ok(format!(
+        "keys of account {} derived from {}, node id {}",
+        account,
+        key_file,
+        node_secrets.node_id()
+    ))
+}
@@ -0,0 +1,198 @@
+// Copyright 2020-2022 Farcaster Devs & LNP/BP Standards Association
+//
+// Use of this source code is governed by an MIT-style
+// license that can be found in the LICENSE file or at
+// https://opensource.org/licenses/MIT.
+
+//! Read-only Prometheus metrics endpoint of farcasterd. The metrics are collected by the
+//! farcasterd runtime while handling bus messages and served in the Prometheus text format
+//! on `GET /metrics` by a dedicated thread.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Write as FmtWrite};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread::spawn;
+use std::time::Duration;
+
+use farcaster_core::swap::SwapId;
+
+use crate::farcasterd::stats::Stats;
+use crate::Error;
+
+/// Metrics shared between the farcasterd runtime and the metrics server thread
+pub type SharedMetrics = Arc<Mutex<Metrics>>;
+
+#[derive(Default, Debug)]
+pub struct Metrics {
+    /// Latest state of each running swap, set on swap state transitions
+    pub swap_states: HashMap<SwapId, String>,
+    pub connected_peers: usize,
+    /// Total number of syncer tasks issued by farcasterd
+    pub syncer_tasks: u32,
+    /// Syncer tasks issued by farcasterd still awaiting their result
+    pub pending_syncer_tasks: usize,
+    /// Messages sent and received per peer connection, refreshed on heartbeat
+    pub peer_messages: HashMap<String, (usize, usize)>,
+    /// Bytes sent and received per peer connection, refreshed on heartbeat
+    pub peer_bytes: HashMap<String, (u64, u64)>,
+    pub stats: Stats,
+}
+
+impl Metrics {
+    /// Render the metrics in the Prometheus text exposition format
+    pub fn render(&self) -> Result<String, fmt::Error> {
+        let mut out = String::new();
+
+        let mut swaps_by_state: BTreeMap<&str, u64> = BTreeMap::new();
+        for state in self.swap_states.values() {
+            *swaps_by_state.entry(state.as_str()).or_default() += 1;
+        }
+        writeln!(out, "# HELP farcaster_swaps Running swaps by state")?;
+        writeln!(out, "# TYPE farcaster_swaps gauge")?;
+        for (state, count) in swaps_by_state {
+            writeln!(out, "farcaster_swaps{{state=\"{}\"}} {}", state, count)?;
+        }
+
+        writeln!(out, "# HELP farcaster_peers Connected peers")?;
+        writeln!(out, "# TYPE farcaster_peers gauge")?;
+        writeln!(out, "farcaster_peers {}", self.connected_peers)?;
+
+        writeln!(
+            out,
+            "# HELP farcaster_syncer_tasks_total Syncer tasks issued by farcasterd"
+        )?;
+        writeln!(out, "# TYPE farcaster_syncer_tasks_total counter")?;
+        writeln!(out, "farcaster_syncer_tasks_total {}", self.syncer_tasks)?;
+        writeln!(
+            out,
+            "# HELP farcaster_syncer_tasks_pending Syncer tasks awaiting their result"
+        )?;
+        writeln!(out, "# TYPE farcaster_syncer_tasks_pending gauge")?;
+        writeln!(
+            out,
+            "farcaster_syncer_tasks_pending {}",
+            self.pending_syncer_tasks
+        )?;
+
+        let mut peers: Vec<_> = self.peer_messages.iter().collect();
+        peers.sort();
+        writeln!(
+            out,
+            "# HELP farcaster_peer_messages_sent_total Messages sent to each peer"
+        )?;
+        writeln!(out, "# TYPE farcaster_peer_messages_sent_total counter")?;
+        for (peer, (sent, _)) in peers.iter() {
+            writeln!(
+                out,
+                "farcaster_peer_messages_sent_total{{peer=\"{}\"}} {}",
+                peer, sent
+            )?;
+        }
+        writeln!(
+            out,
+            "# HELP farcaster_peer_messages_received_total Messages received from each peer"
+        )?;
+        writeln!(out, "# TYPE farcaster_peer_messages_received_total counter")?;
+        for (peer, (_, received)) in peers.iter() {
+            writeln!(
+                out,
+                "farcaster_peer_messages_received_total{{peer=\"{}\"}} {}",
+                peer, received
+            )?;
+        }
+
+        let mut peers: Vec<_> = self.peer_bytes.iter().collect();
+        peers.sort();
+        writeln!(
+            out,
+            "# HELP farcaster_peer_bytes_sent_total Bytes sent to each peer"
+        )?;
+        writeln!(out, "# TYPE farcaster_peer_bytes_sent_total counter")?;
+        for (peer, (sent, _)) in peers.iter() {
+            writeln!(
+                out,
+                "farcaster_peer_bytes_sent_total{{peer=\"{}\"}} {}",
+                peer, sent
+            )?;
+        }
+        writeln!(
+            out,
+            "# HELP farcaster_peer_bytes_received_total Bytes received from each peer"
+        )?;
+        writeln!(out, "# TYPE farcaster_peer_bytes_received_total counter")?;
+        for (peer, (_, received)) in peers.iter() {
+            writeln!(
+                out,
+                "farcaster_peer_bytes_received_total{{peer=\"{}\"}} {}",
+                peer, received
+            )?;
+        }
+
+        writeln!(
+            out,
+            "# HELP farcaster_swap_outcomes_total Ended swaps by outcome"
+        )?;
+        writeln!(out, "# TYPE farcaster_swap_outcomes_total counter")?;
+        for (outcome, count) in [
+            ("success", self.stats.success),
+            ("refund", self.stats.refund),
+            ("punish", self.stats.punish),
+            ("abort", self.stats.abort),
+        ] {
+            writeln!(
+                out,
+                "farcaster_swap_outcomes_total{{outcome=\"{}\"}} {}",
+                outcome, count
+            )?;
+        }
+        writeln!(
+            out,
+            "# HELP farcaster_swaps_initiated_total Swaps initiated by the node"
+        )?;
+        writeln!(out, "# TYPE farcaster_swaps_initiated_total counter")?;
+        writeln!(
+            out,
+            "farcaster_swaps_initiated_total {}",
+            self.stats.initialized
+        )?;
+
+        Ok(out)
+    }
+}
+
+/// Bind the metrics endpoint and serve it from a dedicated thread
+pub fn serve(bind_addr: &str, metrics: SharedMetrics) -> Result<(), Error> {
+    let listener = TcpListener::bind(bind_addr)?;
+    spawn(move || {
+        for stream in listener.incoming() {
+            let res = stream.and_then(|stream| respond(stream, &metrics));
+            if let Err(err) = res {
+                debug!("Failed to serve a metrics request: {}", err);
+            }
+        }
+    });
+    Ok(())
+}
+
+fn respond(mut stream: TcpStream, metrics: &SharedMetrics) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let (status, body) = match request_line.split_whitespace().collect::<Vec<_>>()[..] {
+        ["GET", "/metrics", ..] => match metrics.lock().map(|metrics| metrics.render()) {
+            Ok(Ok(body)) => ("200 OK", body),
+            _ => ("500 Internal Server Error", String::new()),
+        },
+        ["GET", ..] => ("404 Not Found", String::new()),
+        _ => ("405 Method Not Allowed", String::new()),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
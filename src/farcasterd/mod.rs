@@ -4,13 +4,23 @@
 // license that can be found in the LICENSE file or at
 // https://opensource.org/licenses/MIT.
 
+#[cfg(feature = "shell")]
+mod check;
+#[cfg(feature = "metrics")]
+mod metrics;
 #[cfg(feature = "shell")]
 mod opts;
+mod price_oracle;
+mod progress_log;
 mod runtime;
 pub mod stats;
 mod syncer_state_machine;
 mod trade_state_machine;
+mod webhook;
 
+#[cfg(feature = "shell")]
+pub use check::{check_config, print_report, Check};
 #[cfg(feature = "shell")]
 pub use opts::Opts;
+pub use progress_log::remove_progress_log;
 pub use runtime::run;
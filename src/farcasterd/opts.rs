@@ -30,11 +30,27 @@ pub struct Opts {
         value_hint = ValueHint::FilePath
     )]
     pub config: String,
+
+    /// Check the configuration and exit without launching the node
+    ///
+    /// Parses the configuration file, connects to the syncer backends, checks the
+    /// data directory permissions and the key derivation from the key file, then
+    /// exits with a nonzero code if any check failed.
+    #[clap(long, alias = "dry-run")]
+    pub check_config: bool,
 }
 
 impl Opts {
     pub fn process(&mut self) {
-        self.shared.process();
         self.shared.process_dir(&mut self.config);
+        // the log format of the configuration file applies unless set on the command line, it
+        // must be known before the logger is initialized
+        if self.shared.log_format.is_none() {
+            self.shared.log_format = crate::config::peek_log_format(&self.config);
+        }
+        if self.shared.bus_transport.is_none() {
+            self.shared.bus_transport = crate::config::peek_bus_transport(&self.config);
+        }
+        self.shared.process();
     }
 }
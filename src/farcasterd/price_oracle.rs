@@ -0,0 +1,292 @@
+// Copyright 2020-2022 Farcaster Devs & LNP/BP Standards Association
+//
+// Use of this source code is governed by an MIT-style
+// license that can be found in the LICENSE file or at
+// https://opensource.org/licenses/MIT.
+
+//! Sanity check of the BTC/XMR rate of the deals taken against the price reference configured in
+//! farcasterd's config, protecting takers from fat-fingered or malicious deals. The check is
+//! opt-in and fails open: a reference price that cannot be fetched is reported with a warning
+//! and the deal is taken unchecked. The price is fetched on a thread of its own and cached, the
+//! bus handler only reads the cache.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::thread::spawn;
+use std::time::{Duration, Instant};
+
+use farcaster_core::swap::btcxmr::DealParameters;
+use serde_json::Value;
+
+use crate::config::PriceOracleConfig;
+
+/// Default maximum relative deviation of a deal rate from the reference price
+pub const PRICE_ORACLE_TOLERANCE: f64 = 0.05;
+/// Timeout of the query of the reference price
+const PRICE_ORACLE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Age of the cached reference price after which it is fetched again
+const PRICE_ORACLE_REFRESH: Duration = Duration::from_secs(60);
+/// Age of the cached reference price after which rates are no longer checked against it
+const PRICE_ORACLE_MAX_AGE: Duration = Duration::from_secs(600);
+
+/// Outcome of the check of a deal rate, in BTC per XMR, against the reference price
+#[derive(Clone, Debug, PartialEq)]
+pub enum RateCheck {
+    /// The rate is within the tolerance of the reference price
+    Within { rate: f64, price: f64 },
+    /// The rate deviates from the reference price beyond the tolerance
+    Deviates {
+        rate: f64,
+        price: f64,
+        deviation: f64,
+    },
+    /// The reference price could not be fetched, the rate is not checked
+    Unavailable(String),
+}
+
+impl fmt::Display for RateCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RateCheck::Within { rate, price } => write!(
+                f,
+                "deal rate of {:.8} BTC/XMR within tolerance of the reference price of {:.8} BTC/XMR",
+                rate, price
+            ),
+            RateCheck::Deviates {
+                rate,
+                price,
+                deviation,
+            } => write!(
+                f,
+                "deal rate of {:.8} BTC/XMR deviates by {:.1}% from the reference price of {:.8} BTC/XMR",
+                rate,
+                deviation * 100.0,
+                price
+            ),
+            RateCheck::Unavailable(err) => write!(
+                f,
+                "reference price unavailable, deal rate not checked: {}",
+                err
+            ),
+        }
+    }
+}
+
+/// Latest reference price fetched from the oracle, shared with the thread fetching it
+#[derive(Clone, Default)]
+pub struct PriceCache(Arc<Mutex<CachedPrice>>);
+
+#[derive(Default)]
+struct CachedPrice {
+    /// The outcome of the latest fetch with the oracle url and pointer it was fetched from
+    fetched: Option<(String, Option<String>, Instant, Result<f64, String>)>,
+    /// Whether a fetch is in progress
+    fetching: bool,
+}
+
+impl PriceCache {
+    /// Fetch the reference price on a thread of its own, unless a fetch is already in progress
+    pub fn refresh(&self, config: &PriceOracleConfig) {
+        {
+            let mut cache = self.0.lock().expect("price cache poisoned");
+            if cache.fetching {
+                return;
+            }
+            cache.fetching = true;
+        }
+        let cache = self.clone();
+        let url = config.url.clone();
+        let json_pointer = config.json_pointer.clone();
+        spawn(move || {
+            let price = fetch_price(&url, json_pointer.as_deref());
+            if let Err(err) = &price {
+                warn!("Failed to fetch the reference price from {}: {}", url, err);
+            }
+            let mut cache = cache.0.lock().expect("price cache poisoned");
+            cache.fetched = Some((url, json_pointer, Instant::now(), price));
+            cache.fetching = false;
+        });
+    }
+
+    /// Check the rate of the deal against the cached reference price, without waiting for the
+    /// oracle. A missing or aging price is fetched again for the next checks.
+    pub fn check_rate(&self, config: &PriceOracleConfig, parameters: &DealParameters) -> RateCheck {
+        let rate = parameters.arbitrating_amount.as_btc() / parameters.accordant_amount.as_xmr();
+        self.check_price(config, rate)
+    }
+
+    fn check_price(&self, config: &PriceOracleConfig, rate: f64) -> RateCheck {
+        let (price, refresh) = {
+            let cache = self.0.lock().expect("price cache poisoned");
+            match &cache.fetched {
+                Some((url, json_pointer, at, price))
+                    if *url == config.url
+                        && *json_pointer == config.json_pointer
+                        && at.elapsed() < PRICE_ORACLE_MAX_AGE =>
+                {
+                    (Some(price.clone()), at.elapsed() >= PRICE_ORACLE_REFRESH)
+                }
+                _ => (None, true),
+            }
+        };
+        if refresh {
+            self.refresh(config);
+        }
+        match price {
+            Some(Ok(price)) => compare_rate(
+                rate,
+                price,
+                config.tolerance.unwrap_or(PRICE_ORACLE_TOLERANCE),
+            ),
+            Some(Err(err)) => RateCheck::Unavailable(err),
+            None => {
+                RateCheck::Unavailable(format!("no recent price from {}, fetching it", config.url))
+            }
+        }
+    }
+}
+
+fn compare_rate(rate: f64, price: f64, tolerance: f64) -> RateCheck {
+    let deviation = (rate - price).abs() / price;
+    if deviation > tolerance {
+        RateCheck::Deviates {
+            rate,
+            price,
+            deviation,
+        }
+    } else {
+        RateCheck::Within { rate, price }
+    }
+}
+
+/// Query the reference price, blocks until the oracle answers or the query times out
+fn fetch_price(url: &str, json_pointer: Option<&str>) -> Result<f64, String> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|err| format!("failed to start the price oracle runtime: {}", err))?;
+    let body = rt.block_on(async {
+        reqwest::Client::new()
+            .get(url)
+            .timeout(PRICE_ORACLE_TIMEOUT)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())?
+            .text()
+            .await
+    });
+    let body = body.map_err(|err| format!("failed to reach the price oracle: {}", err))?;
+    parse_price(&body, json_pointer)
+}
+
+/// Read the price, in BTC per XMR, from the document returned by the oracle: a JSON document
+/// holding the price at the pointer if any, or a plain number
+fn parse_price(body: &str, json_pointer: Option<&str>) -> Result<f64, String> {
+    let document: Value = serde_json::from_str(body.trim())
+        .map_err(|err| format!("invalid price oracle response: {}", err))?;
+    let value = match json_pointer {
+        Some(pointer) => document
+            .pointer(pointer)
+            .ok_or_else(|| format!("no price at {} in the price oracle response", pointer))?,
+        None => &document,
+    };
+    let price = match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(string) => string.parse::<f64>().ok(),
+        _ => None,
+    };
+    match price {
+        Some(price) if price.is_finite() && price > 0.0 => Ok(price),
+        _ => Err(format!(
+            "invalid price {} in the price oracle response",
+            value
+        )),
+    }
+}
+
+#[test]
+fn price_is_parsed_from_the_oracle_response() {
+    assert_eq!(parse_price("0.0065\n", None), Ok(0.0065));
+    assert_eq!(
+        parse_price(r#"{"monero":{"btc":0.0065}}"#, Some("/monero/btc")),
+        Ok(0.0065)
+    );
+    assert_eq!(
+        parse_price(r#"{"price":"0.0065"}"#, Some("/price")),
+        Ok(0.0065)
+    );
+    assert!(parse_price(r#"{"monero":{"btc":0.0065}}"#, Some("/monero/usd")).is_err());
+    assert!(parse_price(r#"{"monero":{"btc":0.0065}}"#, None).is_err());
+    assert!(parse_price("0", None).is_err());
+    assert!(parse_price("-0.0065", None).is_err());
+    assert!(parse_price("<html>", None).is_err());
+}
+
+#[test]
+fn rate_is_compared_within_the_tolerance() {
+    assert_eq!(
+        compare_rate(0.0066, 0.0065, 0.05),
+        RateCheck::Within {
+            rate: 0.0066,
+            price: 0.0065
+        }
+    );
+    assert!(matches!(
+        compare_rate(0.0070, 0.0065, 0.05),
+        RateCheck::Deviates { deviation, .. } if deviation > 0.07 && deviation < 0.08
+    ));
+    // deviations both ways are refused
+    assert!(matches!(
+        compare_rate(0.0060, 0.0065, 0.05),
+        RateCheck::Deviates { .. }
+    ));
+    assert!(matches!(
+        compare_rate(0.0070, 0.0065, 0.1),
+        RateCheck::Within { .. }
+    ));
+}
+
+#[test]
+fn rate_is_checked_against_the_cached_price() {
+    let config = PriceOracleConfig {
+        // nothing listens there, the fetch fails
+        url: "http://127.0.0.1:9/price".to_string(),
+        json_pointer: None,
+        tolerance: None,
+        reject: None,
+    };
+    let cache = PriceCache::default();
+    // nothing cached yet, the check does not wait for the oracle
+    assert!(matches!(
+        cache.check_price(&config, 0.0065),
+        RateCheck::Unavailable(_)
+    ));
+    // the failure of the fetch is reported until the next refresh
+    while cache.0.lock().unwrap().fetching {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(matches!(
+        cache.check_price(&config, 0.0065),
+        RateCheck::Unavailable(err) if err.contains("failed to reach the price oracle")
+    ));
+
+    cache.0.lock().unwrap().fetched = Some((config.url.clone(), None, Instant::now(), Ok(0.0065)));
+    assert!(matches!(
+        cache.check_price(&config, 0.0066),
+        RateCheck::Within { .. }
+    ));
+    assert!(matches!(
+        cache.check_price(&config, 0.0080),
+        RateCheck::Deviates { .. }
+    ));
+
+    // a price fetched from another oracle is not used
+    let other = PriceOracleConfig {
+        url: "http://127.0.0.1:9/other".to_string(),
+        ..config
+    };
+    assert!(matches!(
+        cache.check_price(&other, 0.0066),
+        RateCheck::Unavailable(_)
+    ));
+}
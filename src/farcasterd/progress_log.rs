@@ -0,0 +1,216 @@
+// Copyright 2020-2022 Farcaster Devs & LNP/BP Standards Association
+//
+// Use of this source code is governed by an MIT-style
+// license that can be found in the LICENSE file or at
+// https://opensource.org/licenses/MIT.
+
+//! Progress of the swaps persisted in the data directory, one log file per swap, so clients
+//! reconnecting after a restart of farcasterd can read the full history of a swap. Entries are
+//! appended as they arrive, the log is rewritten from the retained entries once it grows past
+//! twice the number of entries kept per swap. The log of a swap is deleted with its checkpoint.
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, Seek, Write};
+use std::path::{Path, PathBuf};
+
+use farcaster_core::swap::SwapId;
+use strict_encoding::{StrictDecode, StrictEncode};
+
+use crate::bus::ctl::ProgressStack;
+use crate::bus::Progress;
+use crate::Error;
+
+/// Number of progress entries kept per swap, in memory and on disk
+pub const PROGRESS_LOG_MAX_ENTRIES: usize = 1000;
+
+/// Push a progress entry in the queue of a swap, replacing the previous state update with the new
+/// one and dropping the oldest entries past the bound
+pub fn push_progress(queue: &mut VecDeque<ProgressStack>, entry: ProgressStack) {
+    if let ProgressStack::Progress(Progress::StateUpdate(_)) = entry {
+        if let Some(ProgressStack::Progress(Progress::StateUpdate(_))) = queue.back() {
+            queue.pop_back();
+        }
+    }
+    queue.push_back(entry);
+    while queue.len() > PROGRESS_LOG_MAX_ENTRIES {
+        queue.pop_front();
+    }
+}
+
+fn progress_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("progress")
+}
+
+fn progress_path(dir: &Path, swap_id: SwapId) -> PathBuf {
+    dir.join(format!("{}.log", swap_id))
+}
+
+/// Delete the progress log of the swap in the data directory, if any
+pub fn remove_progress_log(data_dir: &Path, swap_id: SwapId) -> Result<(), Error> {
+    let path = progress_path(&progress_dir(data_dir), swap_id);
+    for path in [path.with_extension("log.tmp"), path] {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Progress log files of the swaps
+pub struct ProgressLog {
+    dir: PathBuf,
+    // number of entries in the log file of each swap appended since it was last written whole
+    appended: HashMap<SwapId, usize>,
+}
+
+impl ProgressLog {
+    pub fn new(data_dir: &Path) -> Result<Self, Error> {
+        let dir = progress_dir(data_dir);
+        fs::create_dir_all(&dir)?;
+        Ok(ProgressLog {
+            dir,
+            appended: none!(),
+        })
+    }
+
+    fn path(&self, swap_id: SwapId) -> PathBuf {
+        progress_path(&self.dir, swap_id)
+    }
+
+    /// Append the entry to the log of the swap, the queue already holding it is written whole
+    /// instead once the log grew too large
+    pub fn append(
+        &mut self,
+        swap_id: SwapId,
+        entry: &ProgressStack,
+        queue: &VecDeque<ProgressStack>,
+    ) -> Result<(), Error> {
+        let appended = self.appended.entry(swap_id).or_insert(0);
+        *appended += 1;
+        if *appended > 2 * PROGRESS_LOG_MAX_ENTRIES {
+            return self.rewrite(swap_id, queue);
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path(swap_id))?;
+        entry.strict_encode(&mut file)?;
+        Ok(())
+    }
+
+    /// Replace the log of the swap with the entries of the queue
+    fn rewrite(&mut self, swap_id: SwapId, queue: &VecDeque<ProgressStack>) -> Result<(), Error> {
+        let tmp_path = self.path(swap_id).with_extension("log.tmp");
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        for entry in queue.iter() {
+            entry.strict_encode(&mut writer)?;
+        }
+        writer.flush()?;
+        drop(writer);
+        fs::rename(tmp_path, self.path(swap_id))?;
+        self.appended.insert(swap_id, queue.len());
+        Ok(())
+    }
+
+    /// Read back the progress of the swap, none if nothing was logged for it. A truncated last
+    /// entry, e.g. after a crash, is cut off the log so the next entries are appended after the
+    /// last good one.
+    pub fn read(&mut self, swap_id: SwapId) -> Result<Option<VecDeque<ProgressStack>>, Error> {
+        let path = self.path(swap_id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let file = File::open(&path)?;
+        let len = file.metadata()?.len();
+        let mut reader = BufReader::new(file);
+        let mut queue = VecDeque::new();
+        let mut appended = 0;
+        let mut good_len = 0;
+        while let Ok(entry) = ProgressStack::strict_decode(&mut reader) {
+            push_progress(&mut queue, entry);
+            appended += 1;
+            good_len = reader.stream_position()?;
+        }
+        drop(reader);
+        if good_len < len {
+            warn!(
+                "Dropping the last {} bytes of the progress log of swap {}, not a complete entry",
+                len - good_len,
+                swap_id
+            );
+            OpenOptions::new()
+                .write(true)
+                .open(&path)?
+                .set_len(good_len)?;
+        }
+        self.appended.insert(swap_id, appended);
+        Ok(Some(queue))
+    }
+}
+
+#[cfg(test)]
+fn test_progress_log() -> (PathBuf, ProgressLog, SwapId) {
+    let swap_id: SwapId = farcaster_core::Uuid::new().into();
+    let data_dir = std::env::temp_dir().join(format!("farcaster-progress-{}", swap_id));
+    let progress_log = ProgressLog::new(&data_dir).unwrap();
+    (data_dir, progress_log, swap_id)
+}
+
+#[cfg(test)]
+fn message(msg: &str) -> ProgressStack {
+    ProgressStack::Progress(Progress::Message(msg.to_string()))
+}
+
+#[test]
+fn progress_log_drops_a_truncated_last_entry() {
+    let (data_dir, mut progress_log, swap_id) = test_progress_log();
+    let mut queue = VecDeque::new();
+    for msg in ["funding", "locked"] {
+        push_progress(&mut queue, message(msg));
+        progress_log
+            .append(swap_id, queue.back().unwrap(), &queue)
+            .unwrap();
+    }
+    let path = progress_log.path(swap_id);
+    let good_len = fs::metadata(&path).unwrap().len();
+    // half written entry of a crash
+    let mut partial = vec![];
+    message("redeemed").strict_encode(&mut partial).unwrap();
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(&partial[..partial.len() / 2]).unwrap();
+    drop(file);
+
+    let read = progress_log.read(swap_id).unwrap().unwrap();
+    assert_eq!(read.len(), 2);
+    assert_eq!(fs::metadata(&path).unwrap().len(), good_len);
+
+    // the next entry follows the last good one
+    push_progress(&mut queue, message("redeemed"));
+    progress_log
+        .append(swap_id, queue.back().unwrap(), &queue)
+        .unwrap();
+    let read = progress_log.read(swap_id).unwrap().unwrap();
+    assert_eq!(
+        read.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        queue.iter().map(ToString::to_string).collect::<Vec<_>>()
+    );
+    fs::remove_dir_all(data_dir).unwrap();
+}
+
+#[test]
+fn progress_log_is_removed_with_the_swap() {
+    let (data_dir, mut progress_log, swap_id) = test_progress_log();
+    let mut queue = VecDeque::new();
+    push_progress(&mut queue, message("funding"));
+    progress_log
+        .append(swap_id, queue.back().unwrap(), &queue)
+        .unwrap();
+    assert!(progress_log.read(swap_id).unwrap().is_some());
+
+    remove_progress_log(&data_dir, swap_id).unwrap();
+    assert!(progress_log.read(swap_id).unwrap().is_none());
+    // nothing left to remove is not an error
+    remove_progress_log(&data_dir, swap_id).unwrap();
+    fs::remove_dir_all(data_dir).unwrap();
+}
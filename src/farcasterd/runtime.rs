@@ -4,36 +4,51 @@
 // license that can be found in the LICENSE file or at
 // https://opensource.org/licenses/MIT.
 
-use crate::bus::ctl::{CtlMsg, FundingInfo, GetKeys, SwapKeys};
+use crate::bus::bridge::BridgeMsg;
+use crate::bus::ctl::{
+    CtlMsg, DisconnectPeer, FundingInfo, GetKeys, StopListen, SwapKeys, SwapSyncer, UpdateSyncer,
+};
 use crate::bus::info::FundingInfos;
 use crate::bus::p2p::{PeerMsg, TakerCommit};
 use crate::bus::sync::SyncMsg;
-use crate::bus::{BusMsg, DealInfo, DealStatus, List, ServiceBus};
+use crate::bus::{
+    BusMsg, DealBookEntry, DealInfo, DealStatus, List, Outcome, RateLimitAction, ServiceBus,
+};
 use crate::event::StateMachineExecutor;
+#[cfg(feature = "metrics")]
+use crate::farcasterd::metrics::{self, SharedMetrics};
+use crate::farcasterd::price_oracle::PriceCache;
+use crate::farcasterd::progress_log::{push_progress, ProgressLog};
 use crate::farcasterd::stats::Stats;
 use crate::farcasterd::syncer_state_machine::{SyncerStateMachine, SyncerStateMachineExecutor};
 use crate::farcasterd::trade_state_machine::{TradeStateMachine, TradeStateMachineExecutor};
 use crate::farcasterd::Opts;
-use crate::syncerd::{AddressBalance, TaskAborted};
-use crate::syncerd::{Event as SyncerEvent, HealthResult, SweepSuccess, TaskId};
+use crate::syncerd::opts::BITCOIN_RPC_PASS_ENV;
+use crate::syncerd::{AddressBalance, BlockData, SetPollIntervals, TaskAborted};
+use crate::syncerd::{Event as SyncerEvent, HealthResult, SweepLocked, SweepSuccess, TaskId};
 use crate::{
     bus::ctl::{Keys, ProgressStack, Token},
-    bus::info::{DealStatusSelector, InfoMsg, NodeInfo, ProgressEvent, SwapProgress},
-    bus::{Failure, FailureCode, Progress},
+    bus::info::{
+        DealStatusSelector, InfoMsg, NodeInfo, ProgressEvent, ReadLastProgress, SwapProgress,
+        SyncerEntry,
+    },
+    bus::{Failure, FailureCode, OptionDetails, Progress},
     clap::Parser,
     config::ParsedSwapConfig,
     error::SyncerError,
     service::Endpoints,
 };
-use crate::{Config, CtlServer, Error, LogStyle, Service, ServiceConfig, ServiceId};
+use crate::{
+    Config, CtlServer, Error, LogStyle, Service, ServiceConfig, ServiceId, SyncerInstance, VERSION,
+};
 
 use std::collections::VecDeque;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ffi::OsStr;
 use std::io;
 use std::iter::FromIterator;
 use std::process;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 
 use bitcoin::{secp256k1::PublicKey, secp256k1::SecretKey};
 use clap::IntoApp;
@@ -44,18 +59,56 @@ use farcaster_core::{
     swap::SwapId,
 };
 use internet2::addr::NodeId;
-use internet2::{addr::InetSocketAddr, addr::NodeAddr};
+use internet2::session::LocalSession;
+use internet2::zeromq::ZmqSocketType;
+use internet2::{addr::InetSocketAddr, addr::NodeAddr, SendRecvMessage, TypedEnum};
 use microservices::esb::{self, Handler};
+use microservices::ZMQ_CONTEXT;
+
+/// Endpoint of the bridge waking up the farcasterd runtime from its own threads
+const FARCASTERD_BRIDGE: &str = "inproc://farcasterdbridge";
+/// Interval at which farcasterd requests a heartbeat from the running services
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+/// Number of missed heartbeats after which a service is considered degraded
+const MAX_MISSED_HEARTBEATS: u32 = 3;
+/// Number of consecutive health checks, one per heartbeat, reporting the backend of a syncer
+/// unreachable after which its swaps fail over to a fallback syncer
+const MAX_FAILED_HEALTH_CHECKS: u32 = 3;
+/// Binaries of the services in the order they are terminated on shutdown: the swaps first so
+/// they stop using the connections and syncers, databased last so it stores their checkpoints
+const SHUTDOWN_ORDER: [&str; 6] = ["swapd", "peerd", "syncerd", "grpcd", "walletd", "databased"];
+/// Time given to the services of a shutdown stage to exit before they are killed, longer than
+/// the broadcast drain of the syncers
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(70);
+/// Interval between two checks of the exit of a terminated service
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Time given to the shutdown reply to reach the client before farcasterd exits
+const SHUTDOWN_REPLY_DELAY: Duration = Duration::from_millis(500);
 
 pub fn run(
     service_config: ServiceConfig,
     config: Config,
-    _opts: Opts,
+    opts: Opts,
     wallet_token: Token,
 ) -> Result<(), Error> {
-    let _walletd = launch("walletd", ["--token", &wallet_token.to_string()])?;
+    // filter the logs of farcasterd and of the services it launches with the configured level
+    if let Some(filter) = config.get_log_level() {
+        crate::logger::set_filter(Some(&filter));
+    }
+    let progress_log = ProgressLog::new(&opts.shared.data_dir)?;
+    let mut launched = vec![];
+    let walletd = launch(
+        "walletd",
+        [
+            "--token",
+            &wallet_token.to_string(),
+            "--account",
+            &config.get_wallet_account().to_string(),
+        ],
+    )?;
+    launched.push(("walletd".to_string(), walletd));
     if config.is_grpc_enable() {
-        let _grpcd = launch(
+        let grpcd = launch(
             "grpcd",
             [
                 "--grpc-port",
@@ -64,9 +117,40 @@ pub fn run(
                 &config.grpc_bind_ip(),
             ],
         )?;
+        launched.push(("grpcd".to_string(), grpcd));
+    }
+    let mut databased_args: Vec<String> = vec![];
+    if let Some(retention) = config.get_checkpoint_retention() {
+        databased_args.extend(retention.max_age.map_or(vec![], |v| {
+            vec!["--checkpoint-max-age".to_string(), v.to_string()]
+        }));
+        databased_args.extend(retention.max_terminal.map_or(vec![], |v| {
+            vec!["--checkpoint-max-terminal".to_string(), v.to_string()]
+        }));
+    }
+    let databased = launch("databased", databased_args)?;
+    launched.push(("databased".to_string(), databased));
+
+    #[cfg(feature = "metrics")]
+    let metrics = match config.metrics_bind_addr() {
+        Some(bind_addr) if config.is_metrics_enable() => {
+            let metrics = SharedMetrics::default();
+            metrics::serve(&bind_addr, metrics.clone())?;
+            info!(
+                "{} serves metrics on {}",
+                "farcasterd".label(),
+                bind_addr.label()
+            );
+            Some(metrics)
+        }
+        _ => None,
+    };
+    #[cfg(not(feature = "metrics"))]
+    if config.is_metrics_enable() {
+        warn!(
+            "The metrics endpoint is enabled, but farcasterd is built without the metrics feature"
+        );
     }
-    let empty: Vec<String> = vec![];
-    let _databased = launch("databased", empty)?;
 
     if config.is_auto_funding_enable() {
         info!(
@@ -76,48 +160,145 @@ pub fn run(
         );
     }
 
+    // fetch the reference price ahead of the first deal taken
+    let price_cache = PriceCache::default();
+    if let Some(oracle) = config.get_price_oracle() {
+        price_cache.refresh(&oracle);
+    }
+
     let runtime = Runtime {
         identity: ServiceId::Farcasterd,
         node_secret_key: None,
         node_public_key: None,
         listens: none!(),
+        launched,
         started: SystemTime::now(),
         auto_restored: false,
+        deal_book_restored: false,
         spawning_services: none!(),
+        spawning_syncers: none!(),
         registered_services: none!(),
+        last_heartbeats: none!(),
+        syncer_health_failures: none!(),
+        paused_syncers: none!(),
+        shutdown: None,
+        key_rotation_requester: None,
+        service_versions: none!(),
+        banned_peers: none!(),
         deals: none!(),
         wallet_token,
         progress: none!(),
+        progress_log,
         progress_subscriptions: none!(),
         stats: none!(),
         config,
+        price_cache,
+        config_path: opts.config.clone(),
         syncer_task_counter: 0,
         trade_state_machines: vec![],
         syncer_state_machines: none!(),
+        #[cfg(feature = "metrics")]
+        metrics,
     };
 
-    let broker = true;
-    Service::run(service_config, runtime, broker)
+    let tx_heartbeat = ZMQ_CONTEXT.socket(zmq::PUSH)?;
+    let rx_bridge = ZMQ_CONTEXT.socket(zmq::PULL)?;
+    rx_bridge.bind(FARCASTERD_BRIDGE)?;
+    tx_heartbeat.connect(FARCASTERD_BRIDGE)?;
+    heartbeat_timer(tx_heartbeat);
+
+    let mut service = Service::broker(service_config, runtime)?;
+    service.add_bridge_service_bus(rx_bridge)?;
+    service.run_loop()?;
+    unreachable!()
+}
+
+/// Periodically wakes up the farcasterd runtime over its bridge so that it can
+/// request a heartbeat from every running service.
+fn heartbeat_timer(tx: zmq::Socket) {
+    std::thread::spawn(move || {
+        let mut session = LocalSession::with_zmq_socket(ZmqSocketType::Push, tx);
+        let identity: Vec<u8> = ServiceId::Farcasterd.into();
+        loop {
+            std::thread::sleep(HEARTBEAT_INTERVAL);
+            let request = BusMsg::Ctl(CtlMsg::Heartbeat);
+            if let Err(err) =
+                session.send_routed_message(&identity, &identity, &identity, &request.serialize())
+            {
+                error!(
+                    "Failed to send heartbeat tick over farcasterd bridge: {}",
+                    err
+                );
+            }
+        }
+    });
+}
+
+/// Run a blocking check off the bus handler, the control message it returns is forwarded to the
+/// given service through the farcasterd bridge
+pub fn spawn_bridged<F>(task: F)
+where
+    F: FnOnce() -> (ServiceId, CtlMsg) + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let (service_id, request) = task();
+        let tx = match ZMQ_CONTEXT
+            .socket(zmq::PUSH)
+            .and_then(|tx| tx.connect(FARCASTERD_BRIDGE).map(|_| tx))
+        {
+            Ok(tx) => tx,
+            Err(err) => {
+                error!("Failed to connect to the farcasterd bridge: {}", err);
+                return;
+            }
+        };
+        let mut session = LocalSession::with_zmq_socket(ZmqSocketType::Push, tx);
+        let identity: Vec<u8> = ServiceId::Farcasterd.into();
+        let request = BusMsg::Bridge(BridgeMsg::Ctl {
+            request,
+            service_id,
+        });
+        if let Err(err) =
+            session.send_routed_message(&identity, &identity, &identity, &request.serialize())
+        {
+            error!("Failed to send over farcasterd bridge: {}", err);
+        }
+    });
 }
 
 pub struct Runtime {
-    identity: ServiceId,                         // Set on Runtime instantiation
-    pub wallet_token: Token,                     // Set on Runtime instantiation
-    started: SystemTime,                         // Set on Runtime instantiation
-    auto_restored: bool,                         // Set on Runtime instantiation
+    identity: ServiceId,                // Set on Runtime instantiation
+    pub wallet_token: Token,            // Set on Runtime instantiation
+    started: SystemTime,                // Set on Runtime instantiation
+    auto_restored: bool,                // Set on Runtime instantiation
+    deal_book_restored: bool,           // Set once the deal book is requested from databased
     node_secret_key: Option<SecretKey>, // Set by Keys request shortly after Hello from walletd
     node_public_key: Option<PublicKey>, // Set by Keys request shortly after Hello from walletd
-    pub listens: HashSet<InetSocketAddr>, // Set by MakeDeal, contains unique socket addresses of the binding peerd listeners.
+    pub listens: HashMap<InetSocketAddr, process::Child>, // Set by MakeDeal and Listen, the binding peerd listeners keyed by their socket address.
+    pub launched: Vec<(String, process::Child)>, // The other processes launched with their binary name, reaped on shutdown
     pub spawning_services: HashSet<ServiceId>, // Services that have been launched, but have not replied with Hello yet
+    spawning_syncers: HashMap<u32, ServiceId>, // Process ids of the launched syncers, removed on their Hello or their exit
     pub registered_services: HashSet<ServiceId>, // Services that have announced themselves with Hello
+    last_heartbeats: HashMap<ServiceId, SystemTime>, // Last time each running service replied with a heartbeat, set on Hello and Heartbeat
+    syncer_health_failures: HashMap<ServiceId, u32>, // Consecutive health checks of each syncer reporting its backend unreachable, reset by a healthy report
+    paused_syncers: HashSet<(SwapId, ServiceId)>, // Unhealthy syncers without a healthy fallback the running swaps are paused on, removed on failover or recovery of the syncer
+    shutdown: Option<Shutdown>, // Set by Shutdown, the stage of the node shutdown in progress
+    key_rotation_requester: Option<ServiceId>, // Set by RotateNodeKeys from a client, consumed once walletd replies with the new Keys
+    service_versions: HashMap<ServiceId, String>, // Versions of the running services, set by ServiceVersion after Hello
+    banned_peers: HashSet<PublicKey>, // Node ids refused as peers, loaded from databased after its Hello and updated by BanPeer and UnbanPeer
     pub deals: HashSet<Deal>, // The set of all known deals. Includes open, consumed and ended deals includes open, consumed and ended deals
     progress: HashMap<ServiceId, VecDeque<ProgressStack>>, // A mapping from Swap ServiceId to its sent and received progress messages (Progress, Success, Failure)
+    progress_log: ProgressLog, // The progress messages persisted per swap, loaded back in progress on request
     progress_subscriptions: HashMap<ServiceId, HashSet<ServiceId>>, // A mapping from a Client ServiceId to its subsribed swap progresses
     pub stats: Stats,             // Some stats about deals and swaps
     pub config: Config,           // The complete node configuration
+    pub price_cache: PriceCache, // The reference price of the configured price oracle, fetched off the handler on start, reload and use
+    config_path: String,         // The configuration file, read again on ReloadConfig
     pub syncer_task_counter: u32, // A strictly incrementing counter of issued syncer tasks
     pub trade_state_machines: Vec<TradeStateMachine>, // New trade state machines are inserted on creation and destroyed upon state machine end transitions
     syncer_state_machines: HashMap<TaskId, SyncerStateMachine>, // New syncer state machines are inserted by their syncer task id when sending a syncer request and destroyed upon matching syncer request receival
+    #[cfg(feature = "metrics")]
+    metrics: Option<SharedMetrics>, // Set on Runtime instantiation if the metrics endpoint is enabled, updated on every handled request
 }
 
 impl CtlServer for Runtime {}
@@ -137,6 +318,8 @@ impl esb::Handler<ServiceBus> for Runtime {
         source: ServiceId,
         request: BusMsg,
     ) -> Result<(), Self::Error> {
+        #[cfg(feature = "metrics")]
+        self.record_metrics(&source, &request);
         match (bus, request) {
             // Peer-to-peer message bus, only accept Peer message
             (ServiceBus::Msg, BusMsg::P2p(req)) => self.handle_msg(endpoints, source, req),
@@ -146,6 +329,44 @@ impl esb::Handler<ServiceBus> for Runtime {
             (ServiceBus::Info, BusMsg::Info(req)) => self.handle_info(endpoints, source, req),
             // Syncer event bus for blockchain tasks and events, only accept Sync message
             (ServiceBus::Sync, BusMsg::Sync(req)) => self.handle_sync(endpoints, source, req),
+            // The services are going away, leave them alone until farcasterd exits
+            (ServiceBus::Bridge, BusMsg::Ctl(CtlMsg::Heartbeat)) if self.shutdown.is_some() => {
+                Ok(())
+            }
+            // Internal farcasterd bridge for the heartbeat timer and the checks run off the bus
+            // handler
+            (ServiceBus::Bridge, BusMsg::Ctl(CtlMsg::Heartbeat)) => {
+                self.request_heartbeats(endpoints)?;
+                self.failover_syncers(endpoints)?;
+                self.request_syncers_health(endpoints);
+                let exited = self.reap_launched();
+                self.fail_exited_syncers(endpoints, &exited)?;
+                self.expire_deals(endpoints)?;
+                #[cfg(feature = "metrics")]
+                self.request_peer_metrics(endpoints);
+                self.retry_swap_connections(endpoints)
+            }
+            // The launched processes of the current shutdown stage exited
+            (
+                ServiceBus::Bridge,
+                BusMsg::Bridge(BridgeMsg::Ctl {
+                    request: CtlMsg::Shutdown(_),
+                    service_id: ServiceId::Farcasterd,
+                }),
+            ) => {
+                if let Some(shutdown) = self.shutdown.as_mut() {
+                    shutdown.stage += 1;
+                }
+                self.shutdown_stage(endpoints)
+            }
+            // Results of the checks run off the bus handler, forwarded to their client
+            (
+                ServiceBus::Bridge,
+                BusMsg::Bridge(BridgeMsg::Ctl {
+                    request,
+                    service_id,
+                }),
+            ) => self.send_client_ctl(endpoints, service_id, request),
             // All other pairs are not supported
             (_, request) => Err(Error::NotSupported(bus, request.to_string())),
         }
@@ -225,6 +446,8 @@ impl Runtime {
                     source.label(),
                     "connected".bright_green_bold()
                 );
+                self.last_heartbeats
+                    .insert(source.clone(), SystemTime::now());
 
                 match &source {
                     ServiceId::Farcasterd => {
@@ -241,7 +464,14 @@ impl Runtime {
                             ServiceId::Database,
                             BusMsg::Ctl(CtlMsg::CleanDanglingDeals),
                         )?;
+                        endpoints.send_to(
+                            ServiceBus::Info,
+                            self.identity(),
+                            ServiceId::Database,
+                            BusMsg::Info(InfoMsg::ListBannedPeers),
+                        )?;
                         self.handle_auto_restore(endpoints)?;
+                        self.handle_deal_book_restore(endpoints)?;
                     }
                     ServiceId::Wallet => {
                         self.registered_services.insert(source.clone());
@@ -253,6 +483,16 @@ impl Runtime {
                             BusMsg::Ctl(CtlMsg::GetKeys(wallet_token)),
                         )?;
                     }
+                    ServiceId::Peer(_, addr)
+                        if self.banned_peers.contains(&addr.id.public_key()) =>
+                    {
+                        warn!(
+                            "Refusing connection {} with the banned peer {}",
+                            source.label(),
+                            addr.id
+                        );
+                        self.handle_failed_connection(endpoints, source.clone())?;
+                    }
                     ServiceId::Peer(_, addr) => {
                         // If this is a connecting peerd, only process the
                         // connection once ConnectSuccess / ConnectFailure is
@@ -272,7 +512,8 @@ impl Runtime {
                     ServiceId::Swap(_) => {
                         // nothing to do, we register swapd instances on a by-swap basis
                     }
-                    ServiceId::Syncer(_, _) => {
+                    ServiceId::Syncer(..) => {
+                        self.spawning_syncers.retain(|_, syncer| *syncer != source);
                         if self.spawning_services.remove(&source) {
                             self.registered_services.insert(source.clone());
                             info!(
@@ -327,11 +568,105 @@ impl Runtime {
                 }
             }
 
+            CtlMsg::ServiceVersion(version) => {
+                if version != VERSION {
+                    warn!(
+                        "Service {} runs version {} while farcasterd runs version {}",
+                        source.label(),
+                        version,
+                        VERSION
+                    );
+                }
+                self.service_versions.insert(source, version);
+            }
+
+            CtlMsg::Heartbeat => {
+                trace!("Received heartbeat from {}", source);
+                self.last_heartbeats.insert(source, SystemTime::now());
+            }
+
             CtlMsg::Keys(Keys(sk, pk)) => {
                 debug!("received peerd keys {}", sk.display_secret());
                 self.node_secret_key = Some(sk);
                 self.node_public_key = Some(pk);
+                if let Some(requester) = self.key_rotation_requester.take() {
+                    self.send_client_ctl(
+                        endpoints,
+                        requester,
+                        CtlMsg::Success(OptionDetails::with(format!(
+                            "Rotated node keys, new node id is {}",
+                            pk
+                        ))),
+                    )?;
+                }
                 self.handle_auto_restore(endpoints)?;
+                self.handle_deal_book_restore(endpoints)?;
+            }
+
+            CtlMsg::RotateNodeKeys(None) => {
+                let peers = self.get_open_connections();
+                if !peers.is_empty() || !self.listens.is_empty() {
+                    self.send_client_ctl(
+                        endpoints,
+                        source,
+                        CtlMsg::Failure(Failure {
+                            code: FailureCode::InvalidRequest,
+                            info: format!(
+                                "Cannot rotate node keys while peer connections are active \
+                                 ({} peers, {} listeners)",
+                                peers.len(),
+                                self.listens.len()
+                            ),
+                        }),
+                    )?;
+                } else if self.key_rotation_requester.is_some() {
+                    self.send_client_ctl(
+                        endpoints,
+                        source,
+                        CtlMsg::Failure(Failure {
+                            code: FailureCode::InvalidRequest,
+                            info: "A node key rotation is already pending".to_string(),
+                        }),
+                    )?;
+                } else {
+                    self.key_rotation_requester = Some(source);
+                    endpoints.send_to(
+                        ServiceBus::Ctl,
+                        self.identity(),
+                        ServiceId::Wallet,
+                        BusMsg::Ctl(CtlMsg::RotateNodeKeys(Some(self.wallet_token.clone()))),
+                    )?;
+                }
+            }
+
+            CtlMsg::ExportSeed(None) => {
+                warn!("Exporting the wallet seed to {}", source);
+                endpoints.send_to(
+                    ServiceBus::Ctl,
+                    source,
+                    ServiceId::Wallet,
+                    BusMsg::Ctl(CtlMsg::ExportSeed(Some(self.wallet_token.clone()))),
+                )?;
+            }
+
+            CtlMsg::ReloadConfig => {
+                let resp = match self.reload_config(endpoints) {
+                    Ok(details) => CtlMsg::Success(OptionDetails::with(details)),
+                    Err(err) => CtlMsg::Failure(Failure {
+                        code: FailureCode::Unknown,
+                        info: format!("Failed to reload the configuration: {}", err),
+                    }),
+                };
+                self.send_client_ctl(endpoints, source, resp)?;
+            }
+
+            CtlMsg::VerifyKeyManager(None) => {
+                endpoints.send_to(
+                    ServiceBus::Ctl,
+                    source,
+                    ServiceId::Wallet,
+                    BusMsg::Ctl(CtlMsg::VerifyKeyManager(Some(self.wallet_token.clone()))),
+                )?;
             }
 
             CtlMsg::PeerdTerminated if matches!(source, ServiceId::Peer(..)) => {
@@ -344,6 +679,309 @@ impl Runtime {
                 }
             }
 
+            CtlMsg::BanPeer(ban) => {
+                self.banned_peers.insert(ban.node_id);
+                endpoints.send_to(
+                    ServiceBus::Ctl,
+                    self.identity(),
+                    ServiceId::Database,
+                    BusMsg::Ctl(CtlMsg::BanPeer(ban.clone())),
+                )?;
+                let connections: Vec<ServiceId> = self
+                    .registered_services
+                    .iter()
+                    .filter(|service| {
+                        matches!(service, ServiceId::Peer(_, addr) if addr.id.public_key() == ban.node_id)
+                    })
+                    .cloned()
+                    .collect();
+                for connection in connections {
+                    if ban.abort_swaps {
+                        for swap_id in self
+                            .trade_state_machines
+                            .iter()
+                            .filter_map(|tsm| tsm.get_swap_id_with_matching_connection(&connection))
+                        {
+                            info!(
+                                "{} | Aborting the swap with the banned peer",
+                                swap_id.swap_id()
+                            );
+                            endpoints.send_to(
+                                ServiceBus::Ctl,
+                                self.identity(),
+                                ServiceId::Swap(swap_id),
+                                BusMsg::Ctl(CtlMsg::AbortSwap),
+                            )?;
+                        }
+                    }
+                    self.handle_failed_connection(endpoints, connection)?;
+                }
+                info!("{} {}", "Banned peer".bright_yellow_bold(), ban.node_id);
+                self.send_client_ctl(
+                    endpoints,
+                    source,
+                    CtlMsg::Success(OptionDetails::with(format!("Banned peer {}", ban.node_id))),
+                )?;
+            }
+
+            // Forward the request to the swap, which answers the client directly
+            CtlMsg::GetXmrAddress(swap_id) => {
+                let service = ServiceId::Swap(swap_id);
+                if self.registered_services.contains(&service) {
+                    endpoints.send_to(
+                        ServiceBus::Ctl,
+                        source,
+                        service,
+                        BusMsg::Ctl(CtlMsg::GetXmrAddress(swap_id)),
+                    )?;
+                } else {
+                    self.send_client_ctl(
+                        endpoints,
+                        source,
+                        CtlMsg::Failure(Failure {
+                            code: FailureCode::SwapNotFound,
+                            info: format!("No running swap with id {}", swap_id),
+                        }),
+                    )?;
+                }
+            }
+
+            CtlMsg::Listen(bind_addr) => {
+                let report = if self.listens.contains_key(&bind_addr) {
+                    Err(Error::Farcaster(format!(
+                        "Already listening on {}",
+                        bind_addr
+                    )))
+                } else {
+                    self.listen(bind_addr)
+                };
+                let report = match report {
+                    Ok(_) => {
+                        CtlMsg::Success(OptionDetails::with(format!("Listening on {}", bind_addr)))
+                    }
+                    Err(err) => CtlMsg::Failure(Failure {
+                        code: FailureCode::Unknown,
+                        info: err.to_string(),
+                    }),
+                };
+                self.send_client_ctl(endpoints, source, report)?;
+            }
+
+            CtlMsg::StopListen(StopListen {
+                bind_addr,
+                keep_connections,
+            }) => {
+                let report = match self.stop_listen(&bind_addr) {
+                    Ok(()) if keep_connections => CtlMsg::Success(OptionDetails::with(format!(
+                        "Stopped listening on {}",
+                        bind_addr
+                    ))),
+                    Ok(()) => {
+                        // the connections accepted by a listener are identified by its address
+                        let connections: Vec<ServiceId> = self
+                            .registered_services
+                            .iter()
+                            .filter(|service| {
+                                matches!(service, ServiceId::Peer(_, addr) if addr.addr == bind_addr)
+                            })
+                            .cloned()
+                            .collect();
+                        for connection in connections.iter() {
+                            if self.connection_has_swap_client(connection) {
+                                warn!(
+                                    "Closing {}, the swaps running over it lose their connection",
+                                    connection.label()
+                                );
+                            }
+                            self.terminate_connection(endpoints, connection.clone())?;
+                        }
+                        CtlMsg::Success(OptionDetails::with(format!(
+                            "Stopped listening on {} and closed {} connections",
+                            bind_addr,
+                            connections.len()
+                        )))
+                    }
+                    Err(err) => CtlMsg::Failure(Failure {
+                        code: FailureCode::Unknown,
+                        info: err.to_string(),
+                    }),
+                };
+                self.send_client_ctl(endpoints, source, report)?;
+            }
+
+            CtlMsg::Shutdown(_) if self.shutdown.is_some() => {
+                self.send_client_ctl(
+                    endpoints,
+                    source,
+                    CtlMsg::Failure(Failure {
+                        code: FailureCode::Unknown,
+                        info: "The node is already shutting down".to_string(),
+                    }),
+                )?;
+            }
+
+            CtlMsg::Shutdown(force) => {
+                info!(
+                    "{} the node{}",
+                    "Shutting down".bright_blue_bold(),
+                    if force { ", forced" } else { "" }
+                );
+                self.shutdown = Some(Shutdown {
+                    client: source,
+                    force,
+                    stage: 0,
+                    stopped: vec![],
+                });
+                self.shutdown_stage(endpoints)?;
+            }
+
+            CtlMsg::DisconnectPeer(DisconnectPeer { node_addr, force }) => {
+                let connection = self
+                    .registered_services
+                    .iter()
+                    .find(
+                        |service| matches!(service, ServiceId::Peer(_, addr) if *addr == node_addr),
+                    )
+                    .cloned();
+                let report = match connection {
+                    None => CtlMsg::Failure(Failure {
+                        code: FailureCode::Unknown,
+                        info: format!("No connection with the peer {}", node_addr),
+                    }),
+                    Some(connection) if !force && self.connection_has_swap_client(&connection) => {
+                        CtlMsg::Failure(Failure {
+                            code: FailureCode::Unknown,
+                            info: format!(
+                                "Swaps are running over the connection with the peer {}, \
+                                force the disconnect to close it anyway",
+                                node_addr
+                            ),
+                        })
+                    }
+                    Some(connection) => {
+                        if self.connection_has_swap_client(&connection) {
+                            warn!(
+                                "Forcing the disconnect of {}, the swaps running over it lose their connection",
+                                connection.label()
+                            );
+                        }
+                        info!("{} {}", "Disconnecting from".bright_blue_bold(), node_addr);
+                        self.terminate_connection(endpoints, connection)?;
+                        CtlMsg::Success(OptionDetails::with(format!(
+                            "Disconnected from the peer {}",
+                            node_addr
+                        )))
+                    }
+                };
+                self.send_client_ctl(endpoints, source, report)?;
+            }
+
+            // From client: Add a deal of another node to the deal book
+            CtlMsg::SetDealBookEntry(entry) => {
+                let res = if entry.local_trade_role != TradeRole::Taker {
+                    Err(Error::Farcaster(
+                        "Deals made by this node are added to the deal book when made".to_string(),
+                    ))
+                } else if Some(entry.deal.node_id) == self.node_public_key {
+                    Err(Error::Farcaster(
+                        "Cannot take a deal made by this node".to_string(),
+                    ))
+                } else {
+                    self.config.validate_deal_parameters(
+                        &entry.deal.parameters,
+                        &entry.arbitrating_addr,
+                        &entry.accordant_addr,
+                        TradeRole::Taker,
+                    )
+                };
+                let report = match res {
+                    Ok(()) => {
+                        let deal_id = entry.deal.id();
+                        endpoints.send_to(
+                            ServiceBus::Ctl,
+                            self.identity(),
+                            ServiceId::Database,
+                            BusMsg::Ctl(CtlMsg::SetDealBookEntry(DealBookEntry {
+                                status: DealStatus::Open,
+                                ..entry
+                            })),
+                        )?;
+                        CtlMsg::Success(OptionDetails::with(format!(
+                            "Added deal {} to the deal book",
+                            deal_id
+                        )))
+                    }
+                    Err(err) => CtlMsg::Failure(Failure {
+                        code: FailureCode::Unknown,
+                        info: err.to_string(),
+                    }),
+                };
+                self.send_client_ctl(endpoints, source, report)?;
+            }
+
+            // From client: Remove a deal from the deal book
+            CtlMsg::RemoveDealBookEntry(deal_id) => {
+                let report = if self
+                    .trade_state_machines
+                    .iter()
+                    .any(|tsm| tsm.open_deal().map_or(false, |deal| deal.id() == deal_id))
+                {
+                    CtlMsg::Failure(Failure {
+                        code: FailureCode::Unknown,
+                        info: format!("Deal {} is open, revoke it instead", deal_id),
+                    })
+                } else {
+                    endpoints.send_to(
+                        ServiceBus::Ctl,
+                        self.identity(),
+                        ServiceId::Database,
+                        BusMsg::Ctl(CtlMsg::RemoveDealBookEntry(deal_id)),
+                    )?;
+                    CtlMsg::Success(OptionDetails::with(format!(
+                        "Removed deal {} from the deal book",
+                        deal_id
+                    )))
+                };
+                self.send_client_ctl(endpoints, source, report)?;
+            }
+
+            CtlMsg::UnbanPeer(node_id) => {
+                if self.banned_peers.remove(&node_id) {
+                    endpoints.send_to(
+                        ServiceBus::Ctl,
+                        self.identity(),
+                        ServiceId::Database,
+                        BusMsg::Ctl(CtlMsg::UnbanPeer(node_id)),
+                    )?;
+                    info!("{} {}", "Unbanned peer".bright_green_bold(), node_id);
+                    self.send_client_ctl(
+                        endpoints,
+                        source,
+                        CtlMsg::Success(OptionDetails::with(format!("Unbanned peer {}", node_id))),
+                    )?;
+                } else {
+                    self.send_client_ctl(
+                        endpoints,
+                        source,
+                        CtlMsg::Failure(Failure {
+                            code: FailureCode::Unknown,
+                            info: format!("Peer {} is not banned", node_id),
+                        }),
+                    )?;
+                }
+            }
+
+            CtlMsg::PeerRateLimited(report) if matches!(source, ServiceId::Peer(..)) => {
+                warn!(
+                    "Peer connection {} exceeded the inbound rate limit: {}",
+                    source.bright_blue_italic(),
+                    report
+                );
+                if report.action == RateLimitAction::Disconnect {
+                    self.handle_failed_connection(endpoints, source.clone())?;
+                }
+            }
+
             // Notify all swapds in case of disconnect
             req @ (CtlMsg::Disconnected | CtlMsg::Reconnected) => {
                 for swap_id in self
@@ -360,22 +998,19 @@ impl Runtime {
                 }
             }
 
+            // Forward swap state transitions to the clients following the swap
+            CtlMsg::SwapStateChanged(change) => {
+                self.notify_subscribed_clients(
+                    endpoints,
+                    &source,
+                    InfoMsg::SwapStateChanged(change),
+                );
+            }
+
             // Add progress in queues and forward to subscribed clients
             event @ (CtlMsg::Progress(..) | CtlMsg::Success(..) | CtlMsg::Failure(..)) => {
-                if !self.progress.contains_key(&source) {
-                    self.progress.insert(source.clone(), none!());
-                };
-                let queue = self.progress.get_mut(&source).expect("checked/added above");
                 let prog = match event {
                     CtlMsg::Progress(p) => {
-                        // Replace the latest state update message in the queue
-                        if let Progress::StateUpdate(_) = p {
-                            if let Some(ProgressStack::Progress(Progress::StateUpdate(_))) =
-                                queue.back()
-                            {
-                                queue.pop_back();
-                            }
-                        }
                         (ProgressStack::Progress(p.clone()), InfoMsg::Progress(p))
                     }
                     CtlMsg::Success(s) => (ProgressStack::Success(s.clone()), InfoMsg::Success(s)),
@@ -383,7 +1018,7 @@ impl Runtime {
                     // filtered at higher level
                     _ => unreachable!(),
                 };
-                queue.push_back(prog.0);
+                self.record_progress(&source, prog.0);
                 // forward the request to each subscribed clients
                 self.notify_subscribed_clients(endpoints, &source, prog.1);
             }
@@ -410,7 +1045,8 @@ impl Runtime {
                     endpoints,
                     source,
                     InfoMsg::NodeInfo(NodeInfo {
-                        listens: self.listens.iter().into_iter().cloned().collect(),
+                        node_id: self.node_public_key,
+                        listens: self.listens.keys().cloned().collect(),
                         uptime: SystemTime::now()
                             .duration_since(self.started)
                             .unwrap_or_else(|_| Duration::from_secs(0)),
@@ -425,16 +1061,90 @@ impl Runtime {
                             .iter()
                             .filter_map(|tsm| tsm.swap_id())
                             .collect(),
+                        swaps_by_state: self.trade_state_machines.iter().fold(
+                            BTreeMap::new(),
+                            |mut swaps_by_state, tsm| {
+                                if let Some(summary) = tsm.swap_summary() {
+                                    *swaps_by_state.entry(summary.lifecycle).or_insert(0) += 1;
+                                }
+                                swaps_by_state
+                            },
+                        ),
+                        active_swaps: self.active_swaps(),
+                        max_concurrent_swaps: self.config.get_max_concurrent_swaps(),
                         deals: self
                             .trade_state_machines
                             .iter()
                             .filter_map(|tsm| tsm.open_deal())
                             .collect(),
                         stats: self.stats.clone(),
+                        heartbeats: self
+                            .heartbeat_ages()
+                            .map(|(service, age)| (service.to_string(), age))
+                            .collect(),
+                        degraded: self
+                            .heartbeat_ages()
+                            .filter(|(_, age)| *age > HEARTBEAT_INTERVAL * MAX_MISSED_HEARTBEATS)
+                            .map(|(service, _)| service.to_string())
+                            .collect(),
+                        version: VERSION.to_string(),
+                        service_versions: self
+                            .service_versions
+                            .iter()
+                            .map(|(service, version)| (service.to_string(), version.clone()))
+                            .collect(),
                     }),
                 )?;
             }
 
+            // Forward the request to the swap, which answers the client directly
+            InfoMsg::GetSwapInfo(swap_id) => {
+                let service = ServiceId::Swap(swap_id);
+                if self.registered_services.contains(&service) {
+                    endpoints.send_to(
+                        ServiceBus::Info,
+                        source,
+                        service,
+                        BusMsg::Info(InfoMsg::GetInfo),
+                    )?;
+                } else {
+                    self.send_client_info(
+                        endpoints,
+                        source,
+                        InfoMsg::Failure(Failure {
+                            code: FailureCode::SwapNotFound,
+                            info: format!("No running swap with id {}", swap_id),
+                        }),
+                    )?;
+                }
+            }
+
+            // Forward the request to the peerd, which answers the client directly
+            InfoMsg::GetPeerInfo(node_addr) => {
+                let service = self
+                    .registered_services
+                    .iter()
+                    .find(|service| service.node_addr() == Some(node_addr))
+                    .cloned();
+                if let Some(service) = service {
+                    endpoints.send_to(
+                        ServiceBus::Info,
+                        source,
+                        service,
+                        BusMsg::Info(InfoMsg::GetInfo),
+                    )?;
+                } else {
+                    self.send_client_info(
+                        endpoints,
+                        source,
+                        InfoMsg::Failure(Failure {
+                            code: FailureCode::PeerUnreachable,
+                            info: format!("No connected peer with address {}", node_addr),
+                        }),
+                    )?;
+                }
+            }
+
             InfoMsg::ListPeers => {
                 self.send_client_info(
                     endpoints,
@@ -456,18 +1166,35 @@ impl Runtime {
                 )?;
             }
 
+            InfoMsg::ListSwapSummaries => {
+                self.send_client_info(
+                    endpoints,
+                    source,
+                    InfoMsg::SwapSummaryList(
+                        self.trade_state_machines
+                            .iter()
+                            .filter_map(|tsm| tsm.swap_summary())
+                            .collect(),
+                    ),
+                )?;
+            }
+
             InfoMsg::ListDeals(ref deal_status_selector) => {
                 match deal_status_selector {
                     DealStatusSelector::Open => {
                         let open_deals = self
                             .trade_state_machines
                             .iter()
-                            .filter_map(|tsm| tsm.open_deal())
-                            .map(|deal| DealInfo {
+                            .filter(|tsm| !tsm.open_deal_expired())
+                            .filter_map(|tsm| {
+                                tsm.open_deal().map(|deal| (deal, tsm.open_deal_expiry()))
+                            })
+                            .map(|(deal, expiry)| DealInfo {
                                 serialized_deal: deal.to_string(),
                                 deal,
                                 status: DealStatus::Open,
                                 local_trade_role: TradeRole::Maker,
+                                expiry,
                             })
                             .collect();
                         self.send_client_info(endpoints, source, InfoMsg::DealList(open_deals))?;
@@ -482,6 +1209,7 @@ impl Runtime {
                                 deal,
                                 status: DealStatus::InProgress,
                                 local_trade_role: trade_role,
+                                expiry: None,
                             })
                             .collect();
                         self.send_client_info(endpoints, source, InfoMsg::DealList(pub_deals))?;
@@ -498,17 +1226,85 @@ impl Runtime {
                 };
             }
 
-            InfoMsg::ListListens => {
-                let listen_url: List<String> =
-                    List::from_iter(self.listens.clone().iter().map(|listen| listen.to_string()));
-                self.send_client_info(endpoints, source, InfoMsg::ListenList(listen_url))?;
+            InfoMsg::ListBannedPeers => {
+                self.send_client_info(
+                    endpoints,
+                    source,
+                    InfoMsg::BannedPeerList(self.banned_peers.iter().cloned().collect()),
+                )?;
+            }
+
+            InfoMsg::BannedPeerList(list) if source == ServiceId::Database => {
+                debug!("Loaded {} banned peers", list.len());
+                self.banned_peers.extend(list.iter());
+            }
+
+            InfoMsg::ListSyncers => {
+                let heartbeat_ages: HashMap<&ServiceId, Duration> = self.heartbeat_ages().collect();
+                let mut syncers: Vec<SyncerEntry> = self
+                    .registered_services
+                    .iter()
+                    .chain(self.spawning_services.iter())
+                    .filter_map(|service| match service {
+                        ServiceId::Syncer(blockchain, network, instance) => {
+                            let last_heartbeat = heartbeat_ages.get(service).copied();
+                            Some(SyncerEntry {
+                                blockchain: *blockchain,
+                                network: *network,
+                                instance: instance.0.clone(),
+                                registered: self.registered_services.contains(service),
+                                last_heartbeat,
+                                degraded: last_heartbeat.map_or(false, |age| {
+                                    age > HEARTBEAT_INTERVAL * MAX_MISSED_HEARTBEATS
+                                }),
+                                health: None,
+                            })
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                // a syncer both registered and spawning is listed once, as registered
+                syncers.sort_by_key(|syncer| {
+                    (
+                        syncer.blockchain.to_string(),
+                        syncer.network.to_string(),
+                        syncer.instance.clone(),
+                    )
+                });
+                syncers.dedup_by(|a, b| {
+                    a.blockchain == b.blockchain
+                        && a.network == b.network
+                        && a.instance == b.instance
+                });
+                self.send_client_info(
+                    endpoints,
+                    source,
+                    InfoMsg::SyncerList(List::from_iter(syncers)),
+                )?;
+            }
+
+            InfoMsg::ListListens => {
+                let listen_url: List<String> =
+                    List::from_iter(self.listens.keys().map(|listen| listen.to_string()));
+                self.send_client_info(endpoints, source, InfoMsg::ListenList(listen_url))?;
             }
 
-            // Returns a unique response that contains the complete progress queue
-            InfoMsg::ReadProgress(swap_id) => {
+            // Returns a unique response that contains the complete progress queue, or its last
+            // entries only
+            request @ (InfoMsg::ReadProgress(_) | InfoMsg::ReadLastProgress(_)) => {
+                let (swap_id, count) = match request {
+                    InfoMsg::ReadProgress(swap_id) => (swap_id, None),
+                    InfoMsg::ReadLastProgress(ReadLastProgress { swap_id, count }) => {
+                        (swap_id, Some(count as usize))
+                    }
+                    // filtered above
+                    _ => unreachable!(),
+                };
+                self.load_progress(swap_id);
                 if let Some(queue) = self.progress.get_mut(&ServiceId::Swap(swap_id)) {
                     let mut swap_progress = SwapProgress { progress: vec![] };
-                    for req in queue.iter() {
+                    let skipped = count.map_or(0, |count| queue.len().saturating_sub(count));
+                    for req in queue.iter().skip(skipped) {
                         match req {
                             ProgressStack::Progress(Progress::Message(m)) => {
                                 swap_progress
@@ -547,13 +1343,23 @@ impl Runtime {
                     report_to.push((
                         Some(source),
                         InfoMsg::Failure(Failure {
-                            code: FailureCode::Unknown,
+                            code: FailureCode::SwapNotFound,
                             info,
                         }),
                     ));
                 }
             }
 
+            // From internal: Re-advertise the deals of the deal book after a restart
+            // Deals of other nodes and deals already taken are not advertised
+            InfoMsg::DealBook(mut list) if source == ServiceId::Database => {
+                for entry in list.drain(..).filter(|entry| {
+                    entry.local_trade_role == TradeRole::Maker && entry.status == DealStatus::Open
+                }) {
+                    self.restore_deal(endpoints, entry)?;
+                }
+            }
+
             // From client: Request a list of checkpoints available for restore.
             // From internal: Trigger restore on a list of checkpoints.
             //
@@ -584,6 +1390,7 @@ impl Runtime {
             // Add the request's source to the subscription list for later progress notifications
             // and send all notifications already in the queue
             InfoMsg::SubscribeProgress(swap_id) => {
+                self.load_progress(swap_id);
                 let service = ServiceId::Swap(swap_id);
                 // if the swap is known either in the tsm's or progress, attach the client
                 // otherwise terminate
@@ -623,7 +1430,7 @@ impl Runtime {
                     report_to.push((
                         Some(source),
                         InfoMsg::Failure(Failure {
-                            code: FailureCode::Unknown,
+                            code: FailureCode::SwapNotFound,
                             info: "Unknown swapd".to_string(),
                         }),
                     ));
@@ -649,12 +1456,20 @@ impl Runtime {
                 // if no swap service exists no subscription need to be removed
             }
 
-            // Filter tsm by funding needs by blockchain and return the funding infos
+            // Filter tsm by funding needs by blockchain, or on both blockchains if none, and return
+            // the funding infos
             InfoMsg::NeedsFunding(blockchain) => {
-                let swaps_need_funding: Vec<FundingInfo> = self
-                    .trade_state_machines
-                    .iter()
-                    .filter_map(|tsm| tsm.needs_funding(blockchain))
+                let blockchains = match blockchain {
+                    Some(blockchain) => vec![blockchain],
+                    None => vec![Blockchain::Bitcoin, Blockchain::Monero],
+                };
+                let swaps_need_funding: Vec<FundingInfo> = blockchains
+                    .into_iter()
+                    .flat_map(|blockchain| {
+                        self.trade_state_machines
+                            .iter()
+                            .filter_map(move |tsm| tsm.needs_funding(blockchain))
+                    })
                     .collect();
                 self.send_client_info(
                     endpoints,
@@ -663,6 +1478,23 @@ impl Runtime {
                 )?;
             }
 
+            // Reply to the peer statistics requested for the metrics, recorded before handling
+            InfoMsg::PeerInfo(_) if matches!(source, ServiceId::Peer(..)) => {}
+
+            // Reply to the health check of a syncer, requested on every heartbeat
+            InfoMsg::SyncerHealth(health) if matches!(source, ServiceId::Syncer(..)) => {
+                if health.connected {
+                    self.syncer_health_failures.remove(&source);
+                } else {
+                    debug!(
+                        "Syncer {} reported its backend unreachable: {}",
+                        source,
+                        health.error.unwrap_or_default()
+                    );
+                    *self.syncer_health_failures.entry(source).or_default() += 1;
+                }
+            }
+
             req => {
                 warn!("Ignoring request: {}", req.err());
             }
@@ -715,6 +1547,75 @@ impl Runtime {
         Ok(())
     }
 
+    fn handle_deal_book_restore(&mut self, endpoints: &mut Endpoints) -> Result<(), Error> {
+        if self.services_ready().is_ok()
+            && self.peer_keys_ready().is_ok()
+            && !self.deal_book_restored
+        {
+            // Retrieving the deal book from databased triggers re-advertising its deals
+            endpoints.send_to(
+                ServiceBus::Info,
+                self.identity(),
+                ServiceId::Database,
+                BusMsg::Info(InfoMsg::ListDealBook),
+            )?;
+            self.deal_book_restored = true;
+        }
+        Ok(())
+    }
+
+    /// Re-advertise a deal of the deal book, dropping it from the book if it expired or was
+    /// made with previous node keys
+    fn restore_deal(
+        &mut self,
+        endpoints: &mut Endpoints,
+        entry: DealBookEntry,
+    ) -> Result<(), Error> {
+        let deal = entry.deal.clone();
+        let tsm = TradeStateMachine::restored_make_deal(entry.clone());
+        let (_, node_public_key) = self.peer_keys_ready()?;
+        let status = if tsm.open_deal_expired() {
+            info!(
+                "Deal {} of the deal book expired, not re-advertising it",
+                deal.id()
+            );
+            DealStatus::Expired
+        } else if deal.node_id != node_public_key {
+            warn!(
+                "Deal {} of the deal book was made with previous node keys, revoking it",
+                deal.id()
+            );
+            DealStatus::Revoked
+        } else {
+            let bind_addr = self.config.get_bind_addr();
+            if let Err(err) = bind_addr.and_then(|bind_addr| self.listen(bind_addr)) {
+                warn!(
+                    "Failed to start peerd listen, cannot re-advertise deal {}: {}",
+                    deal.id(),
+                    err
+                );
+                return Ok(());
+            }
+            info!("Re-advertising deal {} of the deal book", deal.id());
+            self.deals.insert(deal.clone());
+            self.trade_state_machines.push(tsm);
+            DealStatus::Open
+        };
+        endpoints.send_to(
+            ServiceBus::Ctl,
+            self.identity(),
+            ServiceId::Database,
+            BusMsg::Ctl(CtlMsg::SetDealInfo(DealInfo {
+                serialized_deal: deal.to_string(),
+                deal,
+                status,
+                local_trade_role: TradeRole::Maker,
+                expiry: entry.expiry,
+            })),
+        )?;
+        Ok(())
+    }
+
     pub fn services_ready(&self) -> Result<(), Error> {
         if !self.registered_services.contains(&ServiceId::Wallet) {
             Err(Error::Farcaster(
@@ -765,6 +1666,15 @@ impl Runtime {
             "Connection {} failed. Removing it from our connection pool and terminating.",
             connection
         );
+        self.terminate_connection(endpoints, connection)
+    }
+
+    /// Remove a peer connection from the connection pool and terminate its peerd
+    fn terminate_connection(
+        &mut self,
+        endpoints: &mut Endpoints,
+        connection: ServiceId,
+    ) -> Result<(), Error> {
         if let Some(node_addr) = connection.node_addr() {
             self.spawning_services
                 .remove(&ServiceId::dummy_peer_service_id(node_addr));
@@ -828,6 +1738,18 @@ impl Runtime {
                             )
                             .is_err()
                     } else {
+                        // the syncer serves other swaps, only drop the tasks left by this one
+                        if let Err(err) = endpoints.send_to(
+                            ServiceBus::Ctl,
+                            self.identity(),
+                            service.clone(),
+                            BusMsg::Ctl(CtlMsg::TerminateSwap(*swap_id)),
+                        ) {
+                            warn!(
+                                "{} | Failed to drop the tasks on {}: {}",
+                                swap_id, service, err
+                            );
+                        }
                         true
                     }
                 } else {
@@ -873,6 +1795,120 @@ impl Runtime {
             .any(|(tsm_deal, _)| tsm_deal.id() == deal.id())
     }
 
+    /// Whether the deal is open on this node, i.e. made locally and not taken yet
+    pub fn open_deals_contains(&self, deal: &Deal) -> bool {
+        self.trade_state_machines
+            .iter()
+            .filter_map(|tsm| tsm.open_deal())
+            .any(|open_deal| open_deal.id() == deal.id())
+    }
+
+    /// Number of swaps taken or accepted and not ended yet, including the ones being set up
+    pub fn active_swaps(&self) -> usize {
+        self.trade_state_machines
+            .iter()
+            .filter(|tsm| tsm.consumed_deal().is_some())
+            .count()
+    }
+
+    /// Whether a new swap would exceed the configured maximum number of concurrent swaps
+    pub fn swap_limit_reached(&self) -> bool {
+        self.active_swaps() >= self.config.get_max_concurrent_swaps()
+    }
+
+    /// Read the configuration file again and apply its reloadable settings, returns a description
+    /// of the applied and rejected changes
+    fn reload_config(&mut self, endpoints: &mut Endpoints) -> Result<String, Error> {
+        let new = crate::config::parse_config(&self.config_path)?;
+        let reload = self.config.reload(new)?;
+        if reload
+            .applied
+            .iter()
+            .any(|setting| setting == "farcasterd.log_level")
+        {
+            self.push_log_level(endpoints);
+        }
+        if reload.applied.iter().any(|setting| setting == "syncers") {
+            self.push_poll_intervals(endpoints);
+        }
+        if reload
+            .applied
+            .iter()
+            .any(|setting| setting == "farcasterd.price_oracle")
+        {
+            if let Some(oracle) = self.config.get_price_oracle() {
+                self.price_cache.refresh(&oracle);
+            }
+        }
+        for setting in &reload.applied {
+            info!(
+                "Applied the new value of {} from {}",
+                setting.label(),
+                self.config_path
+            );
+        }
+        for setting in &reload.rejected {
+            warn!(
+                "Ignored the new value of {} from {}, it only applies after a restart",
+                setting.label(),
+                self.config_path
+            );
+        }
+        let list = |settings: &[String]| match settings {
+            [] => "none".to_string(),
+            settings => settings.join(", "),
+        };
+        Ok(format!(
+            "Reloaded {}: applied {}; rejected, restart required: {}",
+            self.config_path,
+            list(&reload.applied),
+            list(&reload.rejected)
+        ))
+    }
+
+    /// Filter the logs of farcasterd and of the running services with the configured log level,
+    /// the services launched later inherit it
+    fn push_log_level(&mut self, endpoints: &mut Endpoints) {
+        let filter = self.config.get_log_level();
+        crate::logger::set_filter(filter.as_deref());
+        for service in self.registered_services.clone() {
+            if let Err(err) = endpoints.send_to(
+                ServiceBus::Ctl,
+                self.identity(),
+                service.clone(),
+                BusMsg::Ctl(CtlMsg::SetLogFilter(filter.clone())),
+            ) {
+                warn!("Failed to set the log level of {}: {}", service, err);
+            }
+        }
+    }
+
+    /// Send the configured poll intervals to the running syncers, the syncers launched later
+    /// receive them as arguments
+    fn push_poll_intervals(&mut self, endpoints: &mut Endpoints) {
+        for service in self.registered_services.clone() {
+            let servers = match &service {
+                ServiceId::Syncer(_, network, instance) => {
+                    self.config.get_syncer_instance_servers(*network, instance)
+                }
+                _ => continue,
+            };
+            if let Some(servers) = servers {
+                if let Err(err) = endpoints.send_to(
+                    ServiceBus::Ctl,
+                    self.identity(),
+                    service.clone(),
+                    BusMsg::Ctl(CtlMsg::SetPollIntervals(SetPollIntervals {
+                        poll_interval: servers.poll_interval,
+                        max_poll_interval: servers.max_poll_interval,
+                    })),
+                ) {
+                    warn!("Failed to set the poll intervals of {}: {}", service, err);
+                }
+            }
+        }
+    }
+
     fn running_swaps_contain(&self, swap_id: &SwapId) -> bool {
         self.trade_state_machines
             .iter()
@@ -913,6 +1949,177 @@ impl Runtime {
             .count()
     }
 
+    fn running_services(&self) -> HashSet<ServiceId> {
+        self.registered_services
+            .iter()
+            .cloned()
+            .chain(
+                self.trade_state_machines
+                    .iter()
+                    .filter_map(|tsm| tsm.swap_id())
+                    .map(ServiceId::Swap),
+            )
+            .collect()
+    }
+
+    fn heartbeat_ages(&self) -> impl Iterator<Item = (&ServiceId, Duration)> {
+        self.last_heartbeats.iter().map(|(service, last_seen)| {
+            (
+                service,
+                SystemTime::now()
+                    .duration_since(*last_seen)
+                    .unwrap_or_else(|_| Duration::from_secs(0)),
+            )
+        })
+    }
+
+    /// Stop honoring the open deals past their expiry and record them as expired
+    fn expire_deals(&mut self, endpoints: &mut Endpoints) -> Result<(), Error> {
+        let (expired, open): (Vec<_>, Vec<_>) = self
+            .trade_state_machines
+            .drain(..)
+            .partition(|tsm| tsm.open_deal_expired());
+        self.trade_state_machines = open;
+        for (deal, expiry) in expired
+            .iter()
+            .filter_map(|tsm| tsm.open_deal().map(|deal| (deal, tsm.open_deal_expiry())))
+        {
+            info!("Deal {} expired, not honoring it anymore", deal.id());
+            endpoints.send_to(
+                ServiceBus::Ctl,
+                self.identity(),
+                ServiceId::Database,
+                BusMsg::Ctl(CtlMsg::SetDealInfo(DealInfo {
+                    serialized_deal: deal.to_string(),
+                    deal,
+                    status: DealStatus::Expired,
+                    local_trade_role: TradeRole::Maker,
+                    expiry,
+                })),
+            )?;
+        }
+        Ok(())
+    }
+
+    fn request_heartbeats(&mut self, endpoints: &mut Endpoints) -> Result<(), Error> {
+        let running_services = self.running_services();
+        // Forget about services that are not running anymore
+        self.last_heartbeats
+            .retain(|service, _| running_services.contains(service));
+        self.service_versions
+            .retain(|service, _| running_services.contains(service));
+        for (service, age) in self.heartbeat_ages() {
+            if age > HEARTBEAT_INTERVAL * MAX_MISSED_HEARTBEATS {
+                warn!(
+                    "Service {} missed more than {} heartbeats, last seen {} seconds ago; it is {}",
+                    service.label(),
+                    MAX_MISSED_HEARTBEATS,
+                    age.as_secs(),
+                    "degraded".err()
+                );
+            }
+        }
+        for service in running_services {
+            if let Err(err) = endpoints.send_to(
+                ServiceBus::Ctl,
+                self.identity(),
+                service.clone(),
+                BusMsg::Ctl(CtlMsg::Heartbeat),
+            ) {
+                debug!("Failed to request heartbeat from {}: {}", service, err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Update the served metrics with the request about to be handled and the current state of
+    /// the runtime
+    #[cfg(feature = "metrics")]
+    fn record_metrics(&self, source: &ServiceId, request: &BusMsg) {
+        let mut metrics = match self.metrics.as_ref().map(|metrics| metrics.lock()) {
+            Some(Ok(metrics)) => metrics,
+            _ => return,
+        };
+        match (source, request) {
+            (ServiceId::Swap(swap_id), BusMsg::Ctl(CtlMsg::SwapStateChanged(change))) => {
+                metrics.swap_states.insert(*swap_id, change.to.clone());
+            }
+            (ServiceId::Swap(swap_id), BusMsg::Ctl(CtlMsg::SwapOutcome(_))) => {
+                metrics.swap_states.remove(swap_id);
+            }
+            (ServiceId::Peer(_, addr), BusMsg::Info(InfoMsg::PeerInfo(info))) => {
+                metrics.peer_messages.insert(
+                    addr.to_string(),
+                    (info.messages_sent, info.messages_received),
+                );
+                metrics
+                    .peer_bytes
+                    .insert(addr.to_string(), (info.bytes_sent, info.bytes_received));
+            }
+            _ => {}
+        }
+        metrics
+            .swap_states
+            .retain(|swap_id, _| self.running_swaps_contain(swap_id));
+        let connections: Vec<String> = self
+            .get_open_connections()
+            .iter()
+            .map(|addr| addr.to_string())
+            .collect();
+        metrics
+            .peer_messages
+            .retain(|peer, _| connections.contains(peer));
+        metrics
+            .peer_bytes
+            .retain(|peer, _| connections.contains(peer));
+        metrics.connected_peers = connections.len();
+        metrics.syncer_tasks = self.syncer_task_counter;
+        metrics.pending_syncer_tasks = self.syncer_state_machines.len();
+        metrics.stats = self.stats.clone();
+    }
+
+    /// Request the message counters of every peer connection for the metrics
+    #[cfg(feature = "metrics")]
+    fn request_peer_metrics(&self, endpoints: &mut Endpoints) {
+        if self.metrics.is_none() {
+            return;
+        }
+        for peer in self
+            .registered_services
+            .iter()
+            .filter(|service| matches!(service, ServiceId::Peer(..)))
+        {
+            if let Err(err) = endpoints.send_to(
+                ServiceBus::Info,
+                self.identity(),
+                peer.clone(),
+                BusMsg::Info(InfoMsg::GetInfo),
+            ) {
+                debug!("Failed to request the info of {}: {}", peer, err);
+            }
+        }
+    }
+
+    fn retry_swap_connections(&mut self, endpoints: &mut Endpoints) -> Result<(), Error> {
+        let swap_ids: Vec<SwapId> = self
+            .trade_state_machines
+            .iter()
+            .filter_map(|tsm| tsm.awaiting_reconnect())
+            .collect();
+        for swap_id in swap_ids {
+            debug!(
+                "Retrying to connect to the counterparty of swap {}",
+                swap_id
+            );
+            self.process_request_with_state_machines(
+                BusMsg::Ctl(CtlMsg::Connect(swap_id)),
+                self.identity(),
+                endpoints,
+            )?;
+        }
+        Ok(())
+    }
+
     fn get_open_connections(&self) -> Vec<NodeAddr> {
         self.registered_services
             .iter()
@@ -928,13 +2135,15 @@ impl Runtime {
         match (req, source) {
             (BusMsg::Ctl(CtlMsg::SweepAddress(..)), _)
             | (BusMsg::Ctl(CtlMsg::HealthCheck(..)), _)
-            | (BusMsg::Ctl(CtlMsg::GetBalance(..)), _) => Ok(Some(SyncerStateMachine::Start)),
+            | (BusMsg::Ctl(CtlMsg::GetBalance(..)), _)
+            | (BusMsg::Ctl(CtlMsg::GetBlock(..)), _) => Ok(Some(SyncerStateMachine::Start)),
             (
                 BusMsg::Sync(SyncMsg::Event(SyncerEvent::SweepSuccess(SweepSuccess {
                     id, ..
                 }))),
                 _,
             )
+            | (BusMsg::Sync(SyncMsg::Event(SyncerEvent::SweepLocked(SweepLocked { id, .. }))), _)
             | (
                 BusMsg::Sync(SyncMsg::Event(SyncerEvent::AddressBalance(AddressBalance {
                     id,
@@ -947,7 +2156,10 @@ impl Runtime {
                     id, ..
                 }))),
                 _,
-            ) => Ok(self.syncer_state_machines.remove(id)),
+            )
+            | (BusMsg::Sync(SyncMsg::Event(SyncerEvent::BlockData(BlockData { id, .. }))), _) => {
+                Ok(self.syncer_state_machines.remove(id))
+            }
             (BusMsg::Sync(SyncMsg::Event(SyncerEvent::TaskAborted(TaskAborted { id, .. }))), _) => {
                 // can only match to a syncer state machine if `id` vec is singleton, i.e. a single ssm.
                 // note that this limitation of the syncer state machine handling is not a problem in the
@@ -1078,7 +2290,7 @@ impl Runtime {
                         self.identity(),
                         source,
                         BusMsg::Ctl(CtlMsg::Failure(Failure {
-                            code: FailureCode::Unknown,
+                            code: FailureCode::DealNotFound,
                             info: "Deal to revoke not found.".to_string(),
                         })),
                     )?;
@@ -1090,7 +2302,7 @@ impl Runtime {
                         self.identity(),
                         source,
                         BusMsg::Ctl(CtlMsg::Failure(Failure {
-                            code: FailureCode::Unknown,
+                            code: FailureCode::SwapNotFound,
                             info: "Swap to connect not found.".to_string(),
                         })),
                     )?;
@@ -1122,7 +2334,7 @@ impl Runtime {
         self.services_ready()?;
         let (peer_secret_key, peer_public_key) = self.peer_keys_ready()?;
         let node_id = NodeId::from(peer_public_key);
-        if self.listens.iter().any(|a| a == &bind_addr) {
+        if self.listens.contains_key(&bind_addr) {
             let msg = format!("Already listening on {}", &bind_addr);
             debug!("{}", &msg);
             return Ok(node_id);
@@ -1139,19 +2351,19 @@ impl Runtime {
             .ok_or_else(|| Error::Farcaster("listen requires the port to listen on".to_string()))?;
 
         debug!("Instantiating peerd...");
-        let child = launch(
-            "peerd",
-            [
-                "--listen",
-                &format!("{}", address),
-                "--port",
-                &port.to_string(),
-                "--peer-secret-key",
-                &format!("{}", peer_secret_key.display_secret()),
-                "--token",
-                &self.wallet_token.clone().to_string(),
-            ],
-        );
+        let mut args = vec![
+            "--listen".to_string(),
+            format!("{}", address),
+            "--port".to_string(),
+            port.to_string(),
+            "--peer-secret-key".to_string(),
+            format!("{}", peer_secret_key.display_secret()),
+            "--token".to_string(),
+            self.wallet_token.clone().to_string(),
+        ];
+        args.extend(self.peer_rate_limit_args());
+        args.extend(self.peer_ping_args());
+        let child = launch("peerd", args);
 
         // in case it can't connect wait for it to crash
         std::thread::sleep(Duration::from_secs_f32(0.1));
@@ -1162,8 +2374,8 @@ impl Runtime {
             return Err(Error::Peer(internet2::presentation::Error::InvalidEndpoint));
         }
 
-        self.listens.insert(bind_addr);
         debug!("New instance of peerd launched with PID {}", child.id());
+        self.listens.insert(bind_addr, child);
         info!(
             "Connection daemon {} for incoming peer connections on {}",
             "listens".bright_green_bold(),
@@ -1172,9 +2384,397 @@ impl Runtime {
         Ok(node_id)
     }
 
+    /// Terminate the services of the current shutdown stage and wait off the bus handler for
+    /// their launched processes to exit, unless forced, before the next stage. Once the last
+    /// stage is done, reply with the stopped services and exit.
+    fn shutdown_stage(&mut self, endpoints: &mut Endpoints) -> Result<(), Error> {
+        let (stage, force) = match &self.shutdown {
+            Some(shutdown) => (shutdown.stage, shutdown.force),
+            None => return Ok(()),
+        };
+        let name = match SHUTDOWN_ORDER.get(stage) {
+            Some(name) => *name,
+            None => {
+                let Shutdown {
+                    client, stopped, ..
+                } = self.shutdown.take().expect("shutdown in progress");
+                self.spawning_services.clear();
+                self.send_client_ctl(
+                    endpoints,
+                    client,
+                    CtlMsg::Success(OptionDetails::with(if stopped.is_empty() {
+                        "No service was running".to_string()
+                    } else {
+                        format!("Stopped {}", stopped.join(", "))
+                    })),
+                )?;
+                std::thread::sleep(SHUTDOWN_REPLY_DELAY);
+                info!("Terminating {}", self.identity());
+                std::process::exit(0);
+            }
+        };
+        let mut stopped = vec![];
+        let services: Vec<ServiceId> = self
+            .registered_services
+            .iter()
+            .filter(|service| service_binary(service) == Some(name))
+            .cloned()
+            .collect();
+        for service in services {
+            // the peerd serving incoming connections are not launched by farcasterd, they
+            // only exit on terminate, even when forced
+            if let Err(err) = endpoints.send_to(
+                ServiceBus::Ctl,
+                self.identity(),
+                service.clone(),
+                BusMsg::Ctl(CtlMsg::Terminate),
+            ) {
+                warn!("Failed to terminate {}: {}", service, err);
+            }
+            self.registered_services.remove(&service);
+            self.last_heartbeats.remove(&service);
+            stopped.push(service.to_string());
+        }
+        if name == "peerd" {
+            let bind_addrs: Vec<InetSocketAddr> = self.listens.keys().cloned().collect();
+            for bind_addr in bind_addrs {
+                match self.stop_listen(&bind_addr) {
+                    Ok(()) => stopped.push(format!("listener on {}", bind_addr)),
+                    Err(err) => warn!("Failed to stop the listener on {}: {}", bind_addr, err),
+                }
+            }
+        }
+        if let Some(shutdown) = self.shutdown.as_mut() {
+            shutdown.stopped.append(&mut stopped);
+        }
+        let (children, others): (Vec<_>, Vec<_>) = self
+            .launched
+            .drain(..)
+            .partition(|(binary, _)| binary == name);
+        self.launched = others;
+        let timeout = if force {
+            Duration::ZERO
+        } else {
+            SHUTDOWN_TIMEOUT
+        };
+        spawn_bridged(move || {
+            wait_for_exit(children, timeout);
+            (ServiceId::Farcasterd, CtlMsg::Shutdown(force))
+        });
+        Ok(())
+    }
+
+    /// Return the syncer instance of the blockchain network if it is registered. A syncer not
+    /// running is launched if the configuration allows it, it is returned once registered with
+    /// its Hello.
+    pub fn syncer_up(
+        &mut self,
+        blockchain: Blockchain,
+        network: Network,
+        instance: &SyncerInstance,
+    ) -> Result<Option<ServiceId>, Error> {
+        let syncer_service = ServiceId::Syncer(blockchain, network, instance.clone());
+        if !self.registered_services.contains(&syncer_service)
+            && !self.spawning_services.contains(&syncer_service)
+        {
+            if !self.config.auto_spawn_syncers_enable() {
+                return Err(Error::Farcaster(format!(
+                    "No {} syncer running and launching syncers on demand is disabled",
+                    syncer_service
+                )));
+            }
+            let mut args = syncer_args(blockchain, network, instance);
+            args.append(&mut syncer_servers_args(
+                &self.config,
+                blockchain,
+                network,
+                instance,
+            )?);
+            debug!("launching syncer with: {:?}", args);
+            // the rpc password is passed in the environment, the arguments are public
+            let envs = match self.config.get_syncer_instance_servers(network, instance) {
+                Some(servers) if blockchain == Blockchain::Bitcoin => servers
+                    .bitcoin_rpc_pass
+                    .map(|pass| vec![(BITCOIN_RPC_PASS_ENV, pass)])
+                    .unwrap_or_default(),
+                _ => vec![],
+            };
+            let child = launch_with_env("syncerd", args, envs)?;
+            self.spawning_syncers
+                .insert(child.id(), syncer_service.clone());
+            self.launched.push(("syncerd".to_string(), child));
+            self.spawning_services.insert(syncer_service.clone());
+        }
+        if self.registered_services.contains(&syncer_service) {
+            Ok(Some(syncer_service))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Forget the launched processes that exited, reaping them
+    fn reap_launched(&mut self) -> Vec<u32> {
+        let mut exited = vec![];
+        self.launched = self
+            .launched
+            .drain(..)
+            .filter_map(|(name, mut child)| match child.try_wait() {
+                Ok(None) => Some((name, child)),
+                _ => {
+                    exited.push(child.id());
+                    None
+                }
+            })
+            .collect();
+        exited
+    }
+
+    /// Fail the swaps and the requests waiting for the launched syncers that exited before
+    /// registering, e.g. on an unreachable or misconfigured backend, instead of waiting forever
+    fn fail_exited_syncers(
+        &mut self,
+        endpoints: &mut Endpoints,
+        exited: &[u32],
+    ) -> Result<(), Error> {
+        for pid in exited {
+            let syncer = match self.spawning_syncers.remove(pid) {
+                Some(syncer) => syncer,
+                None => continue,
+            };
+            if !self.spawning_services.remove(&syncer) {
+                continue;
+            }
+            let info = format!(
+                "The {} syncer exited before registering, check the configuration and the \
+                 reachability of its backend",
+                syncer
+            );
+            error!("{}", info);
+            let failure = Failure {
+                code: FailureCode::SyncerBackendUnreachable,
+                info: info.clone(),
+            };
+
+            let (waiting, others): (Vec<_>, Vec<_>) = self
+                .trade_state_machines
+                .drain(..)
+                .partition(|tsm| tsm.awaits_syncer(&syncer));
+            self.trade_state_machines = others;
+            for tsm in waiting {
+                let swap_id = match tsm.swap_id() {
+                    Some(swap_id) => swap_id,
+                    None => continue,
+                };
+                warn!("{} | Failing the swap: {}", swap_id.swap_id(), info);
+                self.report_swap_failure(endpoints, swap_id, failure.clone());
+                if let TradeStateMachine::RestoringSwapd(_) = tsm {
+                    // the checkpoint is kept, the swap can be restored once the syncer runs
+                    endpoints.send_to(
+                        ServiceBus::Ctl,
+                        self.identity(),
+                        ServiceId::Swap(swap_id),
+                        BusMsg::Ctl(CtlMsg::Terminate),
+                    )?;
+                } else {
+                    self.clean_up_after_swap(&swap_id, endpoints)?;
+                }
+            }
+
+            let waiting: Vec<(TaskId, ServiceId)> = self
+                .syncer_state_machines
+                .iter()
+                .filter_map(|(task_id, ssm)| Some((*task_id, ssm.awaiting_client(&syncer)?)))
+                .collect();
+            for (task_id, client) in waiting {
+                self.syncer_state_machines.remove(&task_id);
+                self.send_client_ctl(endpoints, client, CtlMsg::Failure(failure.clone()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Request the health of the registered syncers, their replies are counted in the health
+    /// failures of the syncers
+    fn request_syncers_health(&mut self, endpoints: &mut Endpoints) {
+        let syncers: Vec<ServiceId> = self
+            .registered_services
+            .iter()
+            .filter(|service| matches!(service, ServiceId::Syncer(..)))
+            .cloned()
+            .collect();
+        self.syncer_health_failures
+            .retain(|syncer, _| syncers.contains(syncer));
+        for syncer in syncers {
+            if let Err(err) = endpoints.send_to(
+                ServiceBus::Info,
+                self.identity(),
+                syncer.clone(),
+                BusMsg::Info(InfoMsg::GetSyncerHealth),
+            ) {
+                debug!("Failed to request the health of {}: {}", syncer, err);
+            }
+        }
+    }
+
+    /// Whether the syncer cannot serve its swaps: it is not registered anymore, it missed too
+    /// many heartbeats or its backend was reported unreachable by the last health checks
+    fn syncer_unhealthy(&self, syncer: &ServiceId) -> bool {
+        !self.registered_services.contains(syncer)
+            || self
+                .syncer_health_failures
+                .get(syncer)
+                .map_or(false, |failures| *failures >= MAX_FAILED_HEALTH_CHECKS)
+            || self.heartbeat_ages().any(|(service, age)| {
+                service == syncer && age > HEARTBEAT_INTERVAL * MAX_MISSED_HEARTBEATS
+            })
+    }
+
+    /// Healthy syncer of the same blockchain network to fail over to from the unhealthy one,
+    /// among the default syncer and the named instances of the network. The configured
+    /// instances not running are launched, they can take over once registered.
+    fn fallback_syncer(&mut self, unhealthy: &ServiceId) -> Option<ServiceId> {
+        let (blockchain, network, instance) = match unhealthy {
+            ServiceId::Syncer(blockchain, network, instance) => (*blockchain, *network, instance),
+            _ => return None,
+        };
+        let candidates: Vec<SyncerInstance> = self
+            .config
+            .get_syncer_instances(network)
+            .into_iter()
+            .filter(|candidate| candidate != instance)
+            .collect();
+        for candidate in candidates {
+            let syncer = ServiceId::Syncer(blockchain, network, candidate.clone());
+            if self.registered_services.contains(&syncer) {
+                if !self.syncer_unhealthy(&syncer) {
+                    return Some(syncer);
+                }
+            } else if let Err(err) = self.syncer_up(blockchain, network, &candidate) {
+                debug!("Cannot launch the fallback syncer {}: {}", syncer, err);
+            }
+        }
+        None
+    }
+
+    /// Fail the running swaps over from their unhealthy syncers to healthy fallback syncers of
+    /// the same network. The swap moves its live tasks to the fallback syncer and aborts them on
+    /// the unhealthy one. A swap without a healthy fallback is not failed, its funds may already
+    /// be locked: it is paused on the unhealthy syncer and ignores its events. The swap resumes
+    /// on failover, or once the syncer recovers by issuing its live tasks to the syncer again.
+    fn failover_syncers(&mut self, endpoints: &mut Endpoints) -> Result<(), Error> {
+        let swaps: Vec<(SwapId, Vec<ServiceId>)> = self
+            .trade_state_machines
+            .iter()
+            .filter(|tsm| matches!(tsm, TradeStateMachine::SwapdRunning(_)))
+            .filter_map(|tsm| Some((tsm.swap_id()?, tsm.syncers())))
+            .map(|(swap_id, syncers)| {
+                let unhealthy = syncers
+                    .into_iter()
+                    .filter(|syncer| self.syncer_unhealthy(syncer))
+                    .collect();
+                (swap_id, unhealthy)
+            })
+            .collect();
+        let mut paused_syncers = std::mem::take(&mut self.paused_syncers);
+        let actions = failover_actions(swaps, &mut paused_syncers, |syncer| {
+            self.fallback_syncer(syncer)
+        });
+        self.paused_syncers = paused_syncers;
+        for action in actions {
+            match action {
+                FailoverAction::Replace {
+                    swap_id,
+                    syncer,
+                    fallback,
+                } => {
+                    let msg = format!(
+                        "Syncer {} is unhealthy, failing the swap over to {}",
+                        syncer, fallback
+                    );
+                    warn!("{} | {}", swap_id.swap_id(), msg);
+                    // databased records the fallback so a restore runs the swap on it
+                    for destination in [ServiceId::Swap(swap_id), ServiceId::Database] {
+                        endpoints.send_to(
+                            ServiceBus::Ctl,
+                            self.identity(),
+                            destination,
+                            BusMsg::Ctl(CtlMsg::UpdateSyncer(UpdateSyncer {
+                                swap_id,
+                                new_syncer: fallback.clone(),
+                            })),
+                        )?;
+                    }
+                    for tsm in self
+                        .trade_state_machines
+                        .iter_mut()
+                        .filter(|tsm| tsm.swap_id() == Some(swap_id))
+                    {
+                        tsm.replace_syncer(&syncer, fallback.clone());
+                    }
+                    self.report_swap_progress(endpoints, swap_id, msg);
+                }
+                FailoverAction::Pause { swap_id, syncer } => {
+                    let msg = format!(
+                        "Syncer {} is unhealthy and no healthy fallback syncer is available, \
+                         pausing the swap until the syncer recovers or a fallback syncer becomes \
+                         healthy",
+                        syncer
+                    );
+                    error!("{} | {}", swap_id.swap_id(), msg.err());
+                    endpoints.send_to(
+                        ServiceBus::Ctl,
+                        self.identity(),
+                        ServiceId::Swap(swap_id),
+                        BusMsg::Ctl(CtlMsg::PauseSyncer(SwapSyncer { swap_id, syncer })),
+                    )?;
+                    self.report_swap_progress(endpoints, swap_id, msg);
+                }
+                FailoverAction::Resume { swap_id, syncer } => {
+                    let msg = format!("Syncer {} is healthy again, resuming the swap", syncer);
+                    info!("{} | {}", swap_id.swap_id(), msg);
+                    endpoints.send_to(
+                        ServiceBus::Ctl,
+                        self.identity(),
+                        ServiceId::Swap(swap_id),
+                        BusMsg::Ctl(CtlMsg::ResumeSyncer(SwapSyncer { swap_id, syncer })),
+                    )?;
+                    self.report_swap_progress(endpoints, swap_id, msg);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop the listener bound to the socket address. The connections it accepted are served
+    /// by their own peerd and are not affected.
+    pub fn stop_listen(&mut self, bind_addr: &InetSocketAddr) -> Result<(), Error> {
+        let mut child = self
+            .listens
+            .remove(bind_addr)
+            .ok_or_else(|| Error::Farcaster(format!("Not listening on {}", bind_addr)))?;
+        child.kill()?;
+        child.wait()?;
+        info!(
+            "Connection daemon {} listening on {}",
+            "stopped".bright_green_bold(),
+            bind_addr
+        );
+        Ok(())
+    }
+
+    pub fn is_peer_banned(&self, node_id: &NodeId) -> bool {
+        self.banned_peers.contains(&node_id.public_key())
+    }
+
     pub fn connect_peer(&mut self, node_addr: &NodeAddr) -> Result<(bool, ServiceId), Error> {
         self.services_ready()?;
         let (peer_secret_key, _) = self.peer_keys_ready()?;
+        if self.is_peer_banned(&node_addr.id) {
+            return Err(Error::Farcaster(format!(
+                "Refusing to connect to the banned peer {}",
+                node_addr.id
+            )));
+        }
         if let Some(spawning_peer) = self.spawning_services.iter().find(|service| {
             if let Some(registered_node_addr) = service.node_addr() {
                 registered_node_addr.id == node_addr.id
@@ -1205,17 +2805,17 @@ impl Runtime {
         debug!("{} to remote peer {}", "Connecting", node_addr);
 
         // Start peerd
-        let child = launch(
-            "peerd",
-            [
-                "--connect",
-                &node_addr.to_string(),
-                "--peer-secret-key",
-                &format!("{}", peer_secret_key.display_secret()),
-                "--token",
-                &self.wallet_token.clone().to_string(),
-            ],
-        );
+        let mut args = vec![
+            "--connect".to_string(),
+            node_addr.to_string(),
+            "--peer-secret-key".to_string(),
+            format!("{}", peer_secret_key.display_secret()),
+            "--token".to_string(),
+            self.wallet_token.clone().to_string(),
+        ];
+        args.extend(self.peer_rate_limit_args());
+        args.extend(self.peer_ping_args());
+        let child = launch("peerd", args);
 
         // status is Some if peerd returns because it crashed
         let (child, status) = child.and_then(|mut c| c.try_wait().map(|s| (c, s)))?;
@@ -1225,6 +2825,7 @@ impl Runtime {
         }
 
         debug!("New instance of peerd launched with PID {}", child.id());
+        self.launched.push(("peerd".to_string(), child));
 
         self.spawning_services
             .insert(ServiceId::dummy_peer_service_id(*node_addr));
@@ -1233,6 +2834,121 @@ impl Runtime {
         Ok((false, ServiceId::dummy_peer_service_id(*node_addr)))
     }
 
+    /// Return the arguments passed to peerd to apply the configured inbound message rate limit
+    fn peer_rate_limit_args(&self) -> Vec<String> {
+        match self.config.get_peer_rate_limit() {
+            Some(rate_limit) => vec![
+                "--rate-limit".to_string(),
+                rate_limit.max_messages.to_string(),
+                "--rate-limit-window".to_string(),
+                rate_limit.window.to_string(),
+                "--rate-limit-action".to_string(),
+                rate_limit.action.to_string(),
+            ],
+            None => vec![],
+        }
+    }
+
+    /// Return the arguments passed to peerd to apply the configured stale connection pings
+    fn peer_ping_args(&self) -> Vec<String> {
+        match self.config.get_peer_ping() {
+            Some(ping) => vec![
+                "--ping-interval".to_string(),
+                ping.interval.to_string(),
+                "--ping-max-missed".to_string(),
+                ping.max_missed.to_string(),
+            ],
+            None => vec![],
+        }
+    }
+
+    /// Queue the outcome of an ended swap in its progress, notify it to the subscribed clients
+    /// and drop their subscriptions, the swap will not report any further progress
+    pub fn end_swap_progress(
+        &mut self,
+        swap_id: &SwapId,
+        outcome: &Outcome,
+        endpoints: &mut Endpoints,
+    ) {
+        let service = ServiceId::Swap(*swap_id);
+        let info = format!("Swap ended: {}", outcome);
+        let (stacked, request) = if *outcome == Outcome::SuccessSwap {
+            let details = OptionDetails::with(info);
+            (
+                ProgressStack::Success(details.clone()),
+                InfoMsg::Success(details),
+            )
+        } else {
+            let failure = Failure {
+                code: FailureCode::Unknown,
+                info,
+            };
+            (
+                ProgressStack::Failure(failure.clone()),
+                InfoMsg::Failure(failure),
+            )
+        };
+        self.record_progress(&service, stacked);
+        self.notify_subscribed_clients(endpoints, &service, request);
+        self.progress_subscriptions.remove(&service);
+    }
+
+    /// Record a message in the progress of a swap and forward it to the clients following it
+    fn report_swap_progress(&mut self, endpoints: &mut Endpoints, swap_id: SwapId, msg: String) {
+        let source = ServiceId::Swap(swap_id);
+        let progress = Progress::Message(msg);
+        self.record_progress(&source, ProgressStack::Progress(progress.clone()));
+        self.notify_subscribed_clients(endpoints, &source, InfoMsg::Progress(progress));
+    }
+
+    /// Record a failure in the progress of a swap and forward it to the clients following it
+    pub fn report_swap_failure(
+        &mut self,
+        endpoints: &mut Endpoints,
+        swap_id: SwapId,
+        failure: Failure,
+    ) {
+        let source = ServiceId::Swap(swap_id);
+        self.record_progress(&source, ProgressStack::Failure(failure.clone()));
+        self.notify_subscribed_clients(endpoints, &source, InfoMsg::Failure(failure));
+    }
+
+    /// Queue a progress entry of the service, replacing the latest state update with a new one,
+    /// and persist it if the service is a swap
+    fn record_progress(&mut self, source: &ServiceId, entry: ProgressStack) {
+        if let ServiceId::Swap(swap_id) = source {
+            self.load_progress(*swap_id);
+        }
+        let queue = self.progress.entry(source.clone()).or_default();
+        push_progress(queue, entry.clone());
+        if let ServiceId::Swap(swap_id) = source {
+            if let Err(err) = self.progress_log.append(*swap_id, &entry, queue) {
+                warn!(
+                    "Failed to persist the progress of swap {}: {}",
+                    swap_id, err
+                );
+            }
+        }
+    }
+
+    /// Load the persisted progress of the swap if not queued yet, e.g. after a restart
+    fn load_progress(&mut self, swap_id: SwapId) {
+        let service = ServiceId::Swap(swap_id);
+        if self.progress.contains_key(&service) {
+            return;
+        }
+        match self.progress_log.read(swap_id) {
+            Ok(Some(queue)) => {
+                self.progress.insert(service, queue);
+            }
+            Ok(None) => {}
+            Err(err) => warn!(
+                "Failed to read the persisted progress of swap {}: {}",
+                swap_id, err
+            ),
+        }
+    }
+
     /// Notify(forward to) the subscribed clients still online with the given request
     fn notify_subscribed_clients(
         &mut self,
@@ -1258,99 +2974,345 @@ impl Runtime {
     }
 }
 
-pub fn syncer_up(
-    spawning_services: &mut HashSet<ServiceId>,
-    registered_services: &mut HashSet<ServiceId>,
-    blockchain: Blockchain,
+/// Binary of the service, none for the clients
+fn service_binary(service: &ServiceId) -> Option<&'static str> {
+    match service {
+        ServiceId::Swap(_) => Some("swapd"),
+        ServiceId::Peer(..) => Some("peerd"),
+        ServiceId::Syncer(..) => Some("syncerd"),
+        ServiceId::Grpcd => Some("grpcd"),
+        ServiceId::Wallet => Some("walletd"),
+        ServiceId::Database => Some("databased"),
+        _ => None,
+    }
+}
+
+/// Node shutdown in progress, its stages follow [`SHUTDOWN_ORDER`]
+struct Shutdown {
+    /// Client replied to with the stopped services once the node is down
+    client: ServiceId,
+    /// Kill the launched processes instead of waiting for them to exit
+    force: bool,
+    /// Index of the current stage in the shutdown order
+    stage: usize,
+    /// Services stopped so far
+    stopped: Vec<String>,
+}
+
+/// Wait for the processes to exit until the timeout, killing the ones still running after it
+fn wait_for_exit(children: Vec<(String, process::Child)>, timeout: Duration) {
+    let deadline = Instant::now() + timeout;
+    for (name, mut child) in children {
+        loop {
+            match child.try_wait() {
+                Ok(Some(_)) => break,
+                Ok(None) if Instant::now() < deadline => std::thread::sleep(SHUTDOWN_POLL_INTERVAL),
+                Ok(None) => {
+                    if !timeout.is_zero() {
+                        warn!(
+                            "{} with PID {} did not exit in time, killing it",
+                            name,
+                            child.id()
+                        );
+                    }
+                    if let Err(err) = child.kill().and_then(|_| child.wait()) {
+                        warn!("Failed to kill {} with PID {}: {}", name, child.id(), err);
+                    }
+                    break;
+                }
+                Err(err) => {
+                    warn!(
+                        "Failed to wait for {} with PID {}: {}",
+                        name,
+                        child.id(),
+                        err
+                    );
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Check that a syncer service for the deal's network and syncer instance is running, being
+/// spawned, or configured to be launched, for both legs of the swap. Fails with the networks
+/// the available syncers run on otherwise. This only looks at the syncer services, the network
+/// of the backend servers they connect to is not queried.
+pub fn check_syncers_available(
+    spawning_services: &HashSet<ServiceId>,
+    registered_services: &HashSet<ServiceId>,
+    blockchains: [Blockchain; 2],
     network: Network,
+    instance: &SyncerInstance,
     config: &Config,
-) -> Result<Option<ServiceId>, Error> {
-    let syncer_service = ServiceId::Syncer(blockchain, network);
-    if !registered_services.contains(&syncer_service)
-        && !spawning_services.contains(&syncer_service)
-    {
-        let mut args = vec![
-            "--blockchain".to_string(),
-            blockchain.to_string(),
-            "--network".to_string(),
-            network.to_string(),
-        ];
-        args.append(&mut syncer_servers_args(config, blockchain, network)?);
-        debug!("launching syncer with: {:?}", args);
-        launch("syncerd", args)?;
-        spawning_services.insert(syncer_service.clone());
+) -> Result<(), Error> {
+    for blockchain in blockchains {
+        let syncer_service = ServiceId::Syncer(blockchain, network, instance.clone());
+        if registered_services.contains(&syncer_service)
+            || spawning_services.contains(&syncer_service)
+            || (config.auto_spawn_syncers_enable()
+                && config
+                    .get_syncer_instance_servers(network, instance)
+                    .is_some())
+        {
+            continue;
+        }
+        if let Some(name) = instance.name() {
+            return Err(Error::Farcaster(format!(
+                "No {} syncer instance {} running or configured for the deal network {}",
+                blockchain, name, network
+            )));
+        }
+        let available: Vec<String> = registered_services
+            .iter()
+            .chain(spawning_services.iter())
+            .filter_map(|service| match service {
+                ServiceId::Syncer(b, n, _) if *b == blockchain => Some(n.to_string()),
+                _ => None,
+            })
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        return Err(Error::Farcaster(format!(
+            "No {} syncer available for the deal network {}, {} syncers are only running for: {}",
+            blockchain,
+            network,
+            blockchain,
+            if available.is_empty() {
+                "none".to_string()
+            } else {
+                available.join(", ")
+            }
+        )));
     }
-    if registered_services.contains(&syncer_service) {
-        Ok(Some(syncer_service))
-    } else {
-        Ok(None)
+    Ok(())
+}
+
+/// Action of the syncer failover on a running swap
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FailoverAction {
+    /// Move the swap from its unhealthy syncer to the healthy fallback
+    Replace {
+        swap_id: SwapId,
+        syncer: ServiceId,
+        fallback: ServiceId,
+    },
+    /// Pause the swap on its unhealthy syncer without a healthy fallback
+    Pause { swap_id: SwapId, syncer: ServiceId },
+    /// Resume the swap on its paused syncer, healthy again
+    Resume { swap_id: SwapId, syncer: ServiceId },
+}
+
+/// Return the failover actions of the running swaps, given with their unhealthy syncers, and
+/// update the syncers the swaps are paused on. A swap is paused and resumed on a syncer once, not
+/// on every check; a swap failing over from a paused syncer is resumed by the failover itself.
+fn failover_actions(
+    swaps: Vec<(SwapId, Vec<ServiceId>)>,
+    paused_syncers: &mut HashSet<(SwapId, ServiceId)>,
+    mut fallback_syncer: impl FnMut(&ServiceId) -> Option<ServiceId>,
+) -> Vec<FailoverAction> {
+    paused_syncers.retain(|(paused, _)| swaps.iter().any(|(swap_id, _)| swap_id == paused));
+    let mut actions = vec![];
+    for (swap_id, unhealthy) in swaps {
+        let mut recovered: Vec<ServiceId> = paused_syncers
+            .iter()
+            .filter(|(paused, syncer)| *paused == swap_id && !unhealthy.contains(syncer))
+            .map(|(_, syncer)| syncer.clone())
+            .collect();
+        recovered.sort_by_key(|syncer| syncer.to_string());
+        for syncer in recovered {
+            paused_syncers.remove(&(swap_id, syncer.clone()));
+            actions.push(FailoverAction::Resume { swap_id, syncer });
+        }
+        for syncer in unhealthy {
+            match fallback_syncer(&syncer) {
+                Some(fallback) => {
+                    paused_syncers.remove(&(swap_id, syncer.clone()));
+                    actions.push(FailoverAction::Replace {
+                        swap_id,
+                        syncer,
+                        fallback,
+                    })
+                }
+                None if paused_syncers.insert((swap_id, syncer.clone())) => {
+                    actions.push(FailoverAction::Pause { swap_id, syncer })
+                }
+                None => {}
+            }
+        }
     }
+    actions
 }
 
 /// Launch a swapd instance with all the necessary paramters for: swap id, deal to use, trade role
-/// to execute, temporal safety arguments.
+/// to execute, temporal safety arguments, the optional swap deadline as a unix timestamp, the
+/// Monero unlock depth of the syncers, whether the unconfirmed funding counts and the syncer
+/// instances of the arbitrating and accordant blockchains.
 pub fn launch_swapd(
     local_trade_role: TradeRole,
     deal: Deal,
     swap_id: SwapId,
     swap_config: ParsedSwapConfig,
-) -> Result<(), Error> {
+    deadline: Option<u64>,
+    rebroadcast_interval: Option<u32>,
+    monero_unlock_depth: Option<u32>,
+    count_unconfirmed_funding: bool,
+    arbitrating_syncer_instance: &SyncerInstance,
+    accordant_syncer_instance: &SyncerInstance,
+) -> Result<process::Child, Error> {
     debug!("Instantiating swapd...");
-    let child = launch(
-        "swapd",
-        [
-            "--arb-finality".to_string(),
-            swap_config.arbitrating.finality.to_string(),
-            "--arb-safety".to_string(),
-            swap_config.arbitrating.safety.to_string(),
-            "--acc-finality".to_string(),
-            swap_config.accordant.finality.to_string(),
-            "--id".to_string(),
-            swap_id.to_string(),
-            "--deal".to_string(),
-            deal.to_string(),
-            "--trade-role".to_string(),
-            local_trade_role.to_string(),
-        ],
-    )?;
+    let mut args = vec![
+        "--arb-finality".to_string(),
+        swap_config.arbitrating.finality.to_string(),
+        "--arb-safety".to_string(),
+        swap_config.arbitrating.safety.to_string(),
+        "--acc-finality".to_string(),
+        swap_config.accordant.finality.to_string(),
+        "--id".to_string(),
+        swap_id.to_string(),
+        "--deal".to_string(),
+        deal.to_string(),
+        "--trade-role".to_string(),
+        local_trade_role.to_string(),
+    ];
+    if let Some(deadline) = deadline {
+        args.extend(["--deadline".to_string(), deadline.to_string()]);
+    }
+    if let Some(interval) = rebroadcast_interval {
+        args.extend(["--rebroadcast-interval".to_string(), interval.to_string()]);
+    }
+    if let Some(depth) = monero_unlock_depth {
+        args.extend(["--monero-unlock-depth".to_string(), depth.to_string()]);
+    }
+    if count_unconfirmed_funding {
+        args.push("--count-unconfirmed-funding".to_string());
+    }
+    args.append(&mut swapd_syncer_instance_args(
+        arbitrating_syncer_instance,
+        accordant_syncer_instance,
+    ));
+    let child = launch("swapd", args)?;
     debug!("New instance of swapd launched with PID {}", child.id());
     debug!("Awaiting for swapd to connect...");
-    Ok(())
+    Ok(child)
+}
+
+/// Return the arguments selecting the syncer instances of a swapd, a single instance when both
+/// blockchains run on the same one. Only Bitcoin arbitrates and Monero accords for now.
+fn swapd_syncer_instance_args(
+    arbitrating: &SyncerInstance,
+    accordant: &SyncerInstance,
+) -> Vec<String> {
+    if arbitrating == accordant {
+        return arbitrating.name().map_or(vec![], |name| {
+            vec!["--syncer-instance".to_string(), name.to_string()]
+        });
+    }
+    let mut args = vec![];
+    for (flag, instance) in [
+        ("--bitcoin-syncer-instance", arbitrating),
+        ("--monero-syncer-instance", accordant),
+    ] {
+        if let Some(name) = instance.name() {
+            args.extend([flag.to_string(), name.to_string()]);
+        }
+    }
+    args
 }
 
-/// Return the list of needed arguments for a syncer given a config and a network.
-/// This function only register the minimal set of URLs needed for the blockchain to work.
+/// Return the arguments identifying the syncer of a blockchain network and instance
+fn syncer_args(blockchain: Blockchain, network: Network, instance: &SyncerInstance) -> Vec<String> {
+    let mut args = vec![
+        "--blockchain".to_string(),
+        blockchain.to_string(),
+        "--network".to_string(),
+        network.to_string(),
+    ];
+    if let Some(name) = instance.name() {
+        args.extend(["--instance".to_string(), name.to_string()]);
+    }
+    args
+}
+
+/// Return the list of needed arguments for a syncer given a config, a network and the syncer
+/// instance. This function only register the minimal set of URLs needed for the blockchain to
+/// work.
 fn syncer_servers_args(
     config: &Config,
     blockchain: Blockchain,
     net: Network,
+    instance: &SyncerInstance,
 ) -> Result<Vec<String>, Error> {
-    match config.get_syncer_servers(net) {
-        Some(servers) => match blockchain {
-            Blockchain::Bitcoin => Ok(vec![
-                "--electrum-server".to_string(),
-                servers.electrum_server,
-            ]),
-            Blockchain::Monero => {
-                let mut args: Vec<String> = vec![
-                    "--monero-daemon".to_string(),
-                    servers.monero_daemon,
-                    "--monero-rpc-wallet".to_string(),
-                    servers.monero_rpc_wallet,
-                ];
-                args.extend(
-                    servers
-                        .monero_lws
-                        .map_or(vec![], |v| vec!["--monero-lws".to_string(), v]),
-                );
-                args.extend(
-                    servers
-                        .monero_wallet_dir
-                        .map_or(vec![], |v| vec!["--monero-wallet-dir-path".to_string(), v]),
-                );
-                Ok(args)
+    match config.get_syncer_instance_servers(net, instance) {
+        Some(servers) => {
+            let mut args: Vec<String> = match blockchain {
+                Blockchain::Bitcoin => {
+                    let mut args: Vec<String> =
+                        vec!["--electrum-server".to_string(), servers.electrum_server];
+                    for (flag, value) in [
+                        ("--bitcoin-rpc", servers.bitcoin_rpc),
+                        ("--bitcoin-cookie-path", servers.bitcoin_cookie_path),
+                        ("--bitcoin-rpc-user", servers.bitcoin_rpc_user),
+                    ] {
+                        args.extend(value.map_or(vec![], |v| vec![flag.to_string(), v]));
+                    }
+                    args.extend(servers.fee_stale_ratio.map_or(vec![], |v| {
+                        vec!["--fee-stale-ratio".to_string(), v.to_string()]
+                    }));
+                    args
+                }
+                Blockchain::Monero => {
+                    let mut args: Vec<String> = vec![
+                        "--monero-daemon".to_string(),
+                        servers.monero_daemon,
+                        "--monero-rpc-wallet".to_string(),
+                        servers.monero_rpc_wallet,
+                    ];
+                    args.extend(
+                        servers
+                            .monero_lws
+                            .map_or(vec![], |v| vec!["--monero-lws".to_string(), v]),
+                    );
+                    args.extend(
+                        servers
+                            .monero_wallet_dir
+                            .map_or(vec![], |v| vec!["--monero-wallet-dir-path".to_string(), v]),
+                    );
+                    args.extend(servers.monero_unlock_depth.map_or(vec![], |v| {
+                        vec!["--monero-unlock-depth".to_string(), v.to_string()]
+                    }));
+                    args
+                }
+            };
+            args.extend(servers.poll_interval.map_or(vec![], |v| {
+                vec!["--poll-interval".to_string(), v.to_string()]
+            }));
+            args.extend(servers.max_poll_interval.map_or(vec![], |v| {
+                vec!["--max-poll-interval".to_string(), v.to_string()]
+            }));
+            for (flag, value) in [
+                (
+                    "--retry-max-attempts",
+                    servers.retry_max_attempts.map(u64::from),
+                ),
+                ("--retry-backoff", servers.retry_backoff),
+                ("--retry-max-backoff", servers.retry_max_backoff),
+            ] {
+                args.extend(value.map_or(vec![], |v| vec![flag.to_string(), v.to_string()]));
             }
-        },
+            args.extend(servers.bridge_transport.map_or(vec![], |v| {
+                vec!["--bridge-transport".to_string(), v.to_string()]
+            }));
+            args.extend(servers.bridge_socket.map_or(vec![], |v| {
+                vec!["--bridge-socket".to_string(), v.to_string()]
+            }));
+            args.extend(
+                servers
+                    .mock_scenario
+                    .map_or(vec![], |v| vec!["--mock-scenario".to_string(), v]),
+            );
+            Ok(args)
+        }
         None => Err(SyncerError::InvalidConfig.into()),
     }
 }
@@ -1358,6 +3320,16 @@ fn syncer_servers_args(
 pub fn launch(
     name: &str,
     args: impl IntoIterator<Item = impl AsRef<OsStr>>,
+) -> io::Result<process::Child> {
+    launch_with_env(name, args, Vec::<(&str, String)>::new())
+}
+
+/// Launch a service with secrets passed in its environment rather than in its arguments, which
+/// are visible to every user of the system
+pub fn launch_with_env(
+    name: &str,
+    args: impl IntoIterator<Item = impl AsRef<OsStr>>,
+    envs: Vec<(&str, String)>,
 ) -> io::Result<process::Child> {
     let app = Opts::command();
     let mut bin_path = std::env::current_exe().map_err(|err| {
@@ -1409,8 +3381,29 @@ pub fn launch(
         cmd.args(["-T", *t]);
     }
 
+    // Forward the log format, possibly set in the configuration file
+    cmd.args(["--log-format", &crate::logger::log_format().to_string()]);
+
+    // Forward the log level set in the configuration file, replacing the one of the environment
+    if let Some(filter) = crate::logger::log_filter() {
+        cmd.env("RUST_LOG", filter);
+    }
+
+    // Forward the bus transport, possibly set in the configuration file, so every service checks
+    // its sockets against the same transport
+    let mut config_path = parsed.config.clone();
+    parsed.shared.process_dir(&mut config_path);
+    if let Some(transport) = parsed
+        .shared
+        .bus_transport
+        .or_else(|| crate::config::peek_bus_transport(&config_path))
+    {
+        cmd.args(["--bus-transport", &transport.to_string()]);
+    }
+
     // Given specialized args in launch
     cmd.args(args);
+    cmd.envs(envs);
 
     debug!("Executing `{:?}`", cmd);
     cmd.spawn().map_err(|err| {
@@ -1418,3 +3411,199 @@ pub fn launch(
         err
     })
 }
+
+#[test]
+fn swapd_runs_on_the_syncer_instances_passed() {
+    use farcaster_core::Uuid;
+    use std::str::FromStr;
+
+    let deal = Deal::from_str("Deal:Cke4ftrP5A781Vq85dgBQJNwYgBS4nuUV1LQM2fvVdFMNR4h5TrWhRR11111uMFuZTAsNgpdK8DiK11111TB9zym113GTvtvqfD1111114A4TTfFfmZoWyvpcjDBtTZCdWFSUWcRKYfEC3Y17hqaXZ3dWz11111111111111111111111111111111111111111AfZ113SEBTEspU3a").unwrap();
+    let parse = |arbitrating: &SyncerInstance, accordant: &SyncerInstance| {
+        let mut args = vec![
+            "swapd".to_string(),
+            "--arb-finality".to_string(),
+            "1".to_string(),
+            "--arb-safety".to_string(),
+            "3".to_string(),
+            "--acc-finality".to_string(),
+            "1".to_string(),
+            "--id".to_string(),
+            SwapId(Uuid::new()).to_string(),
+            "--deal".to_string(),
+            deal.to_string(),
+            "--trade-role".to_string(),
+            TradeRole::Maker.to_string(),
+        ];
+        args.append(&mut swapd_syncer_instance_args(arbitrating, accordant));
+        crate::swapd::Opts::try_parse_from(args)
+            .unwrap()
+            .syncer_instances()
+    };
+    let trusted = SyncerInstance(Some("trusted".to_string()));
+    let fallback = SyncerInstance(Some("fallback".to_string()));
+
+    assert_eq!(
+        (SyncerInstance::DEFAULT, SyncerInstance::DEFAULT),
+        parse(&SyncerInstance::DEFAULT, &SyncerInstance::DEFAULT)
+    );
+    assert_eq!(
+        vec!["--syncer-instance", "trusted"],
+        swapd_syncer_instance_args(&trusted, &trusted)
+    );
+    assert_eq!(
+        (trusted.clone(), trusted.clone()),
+        parse(&trusted, &trusted)
+    );
+    // a swap restored after its Bitcoin syncer failed over runs on the fallback for Bitcoin only
+    assert_eq!(
+        (fallback.clone(), trusted.clone()),
+        parse(&fallback, &trusted)
+    );
+    assert_eq!(
+        (fallback.clone(), SyncerInstance::DEFAULT),
+        parse(&fallback, &SyncerInstance::DEFAULT)
+    );
+    assert_eq!(
+        (SyncerInstance::DEFAULT, fallback.clone()),
+        parse(&SyncerInstance::DEFAULT, &fallback)
+    );
+}
+
+#[test]
+fn syncerd_runs_as_the_syncer_instance_passed() {
+    let parse = |instance: &SyncerInstance| {
+        let args = syncer_args(Blockchain::Monero, Network::Local, instance);
+        crate::syncerd::opts::Opts::try_parse_from(
+            vec!["syncerd".to_string()].into_iter().chain(args),
+        )
+        .unwrap()
+    };
+
+    let opts = parse(&SyncerInstance(Some("trusted".to_string())));
+    assert_eq!(Blockchain::Monero, opts.blockchain);
+    assert_eq!(Network::Local, opts.network);
+    assert_eq!(Some("trusted".to_string()), opts.instance);
+    assert_eq!(None, parse(&SyncerInstance::DEFAULT).instance);
+}
+
+#[test]
+fn unhealthy_syncers_fail_over_or_pause_the_swap() {
+    use farcaster_core::Uuid;
+
+    let syncer = |blockchain, name: &str| {
+        ServiceId::Syncer(
+            blockchain,
+            Network::Local,
+            SyncerInstance(Some(name.to_string())),
+        )
+    };
+    let swap_a = SwapId(Uuid::new());
+    let swap_b = SwapId(Uuid::new());
+    let mut paused_syncers = HashSet::new();
+
+    // healthy swaps are left alone
+    assert!(failover_actions(
+        vec![(swap_a, vec![]), (swap_b, vec![])],
+        &mut paused_syncers,
+        |_| panic!("no fallback needed")
+    )
+    .is_empty());
+
+    // an unhealthy syncer with a fallback is replaced, the other syncer of the swap is kept
+    let actions = failover_actions(
+        vec![(swap_a, vec![syncer(Blockchain::Bitcoin, "primary")])],
+        &mut paused_syncers,
+        |unhealthy| {
+            assert_eq!(syncer(Blockchain::Bitcoin, "primary"), *unhealthy);
+            Some(syncer(Blockchain::Bitcoin, "fallback"))
+        },
+    );
+    assert_eq!(
+        vec![FailoverAction::Replace {
+            swap_id: swap_a,
+            syncer: syncer(Blockchain::Bitcoin, "primary"),
+            fallback: syncer(Blockchain::Bitcoin, "fallback"),
+        }],
+        actions
+    );
+    assert!(paused_syncers.is_empty());
+
+    // without a fallback the swap is paused once on the syncer, its unhealthy syncer of the
+    // other blockchain with a fallback still fails over
+    let unhealthy = vec![
+        syncer(Blockchain::Bitcoin, "primary"),
+        syncer(Blockchain::Monero, "primary"),
+    ];
+    let fallback = |unhealthy: &ServiceId| match unhealthy {
+        ServiceId::Syncer(Blockchain::Monero, ..) => Some(syncer(Blockchain::Monero, "fallback")),
+        _ => None,
+    };
+    let actions = failover_actions(
+        vec![(swap_a, unhealthy.clone()), (swap_b, vec![])],
+        &mut paused_syncers,
+        fallback,
+    );
+    assert_eq!(
+        vec![
+            FailoverAction::Pause {
+                swap_id: swap_a,
+                syncer: syncer(Blockchain::Bitcoin, "primary"),
+            },
+            FailoverAction::Replace {
+                swap_id: swap_a,
+                syncer: syncer(Blockchain::Monero, "primary"),
+                fallback: syncer(Blockchain::Monero, "fallback"),
+            },
+        ],
+        actions
+    );
+    let actions = failover_actions(
+        vec![(swap_a, vec![syncer(Blockchain::Bitcoin, "primary")])],
+        &mut paused_syncers,
+        |_| None,
+    );
+    assert!(actions.is_empty());
+    assert!(paused_syncers.contains(&(swap_a, syncer(Blockchain::Bitcoin, "primary"))));
+
+    // the recovered syncer resumes the swap once
+    let actions = failover_actions(vec![(swap_a, vec![])], &mut paused_syncers, |_| None);
+    assert_eq!(
+        vec![FailoverAction::Resume {
+            swap_id: swap_a,
+            syncer: syncer(Blockchain::Bitcoin, "primary"),
+        }],
+        actions
+    );
+    assert!(failover_actions(vec![(swap_a, vec![])], &mut paused_syncers, |_| None).is_empty());
+
+    // a fallback becoming healthy takes over the paused syncer, the failover resumes the swap
+    failover_actions(
+        vec![(swap_a, vec![syncer(Blockchain::Bitcoin, "primary")])],
+        &mut paused_syncers,
+        |_| None,
+    );
+    let actions = failover_actions(
+        vec![(swap_a, vec![syncer(Blockchain::Bitcoin, "primary")])],
+        &mut paused_syncers,
+        |_| Some(syncer(Blockchain::Bitcoin, "fallback")),
+    );
+    assert_eq!(
+        vec![FailoverAction::Replace {
+            swap_id: swap_a,
+            syncer: syncer(Blockchain::Bitcoin, "primary"),
+            fallback: syncer(Blockchain::Bitcoin, "fallback"),
+        }],
+        actions
+    );
+    assert!(paused_syncers.is_empty());
+
+    // a paused swap no longer running is forgotten
+    failover_actions(
+        vec![(swap_b, vec![syncer(Blockchain::Bitcoin, "primary")])],
+        &mut paused_syncers,
+        |_| None,
+    );
+    assert!(paused_syncers.contains(&(swap_b, syncer(Blockchain::Bitcoin, "primary"))));
+    failover_actions(vec![], &mut paused_syncers, |_| None);
+    assert!(paused_syncers.is_empty());
+}
@@ -7,20 +7,21 @@
 use farcaster_core::blockchain::{Blockchain, Network};
 
 use crate::{
-    bus::ctl::CtlMsg,
-    bus::info::InfoMsg,
+    bus::ctl::{CtlMsg, GetBlock},
+    bus::info::{BlockInfo, InfoMsg},
     bus::BusMsg,
     bus::{sync::SyncMsg, AddressSecretKey, Failure, FailureCode},
     error::Error,
     event::{Event, StateMachine, StateMachineExecutor},
+    monero_address_network,
     syncerd::{
-        Event as SyncerEvent, GetAddressBalance, Health, HealthCheck, SweepAddress,
-        SweepAddressAddendum, Task, TaskAborted, TaskId,
+        BlockData, Event as SyncerEvent, GetAddressBalance, GetBlockByHeight, Health, HealthCheck,
+        SweepAddress, SweepAddressAddendum, Task, TaskAborted, TaskId,
     },
-    ServiceId,
+    ServiceId, SyncerInstance,
 };
 
-use super::runtime::{syncer_up, Runtime};
+use super::runtime::Runtime;
 
 /// State machine for making a syncer request from and to a client.
 /// State machine automaton:
@@ -124,6 +125,19 @@ impl SyncerStateMachine {
             _ => None,
         }
     }
+
+    /// Client of the request waiting for the syncer to register, none if the request does not
+    /// wait for this syncer
+    pub fn awaiting_client(&self, awaited: &ServiceId) -> Option<ServiceId> {
+        match self {
+            SyncerStateMachine::AwaitingSyncer(AwaitingSyncer { source, syncer, .. })
+                if syncer == awaited =>
+            {
+                Some(source.clone())
+            }
+            _ => None,
+        }
+    }
 }
 
 fn attempt_transition_to_awaiting_syncer_or_awaiting_syncer_request(
@@ -136,7 +150,7 @@ fn attempt_transition_to_awaiting_syncer_or_awaiting_syncer_request(
             let (blockchain, network) = match sweep_address.clone() {
                 SweepAddressAddendum::Monero(addendum) => {
                     let blockchain = Blockchain::Monero;
-                    let mut network = addendum.destination_address.network.into();
+                    let mut network = monero_syncer_network(&addendum.destination_address)?;
 
                     // Switch the network to local if the mainnet configuration does
                     // not exist and the local network exists
@@ -165,25 +179,21 @@ fn attempt_transition_to_awaiting_syncer_or_awaiting_syncer_request(
             runtime.syncer_task_counter += 1;
 
             // check if a monero syncer is up
-            if let Some(service_id) = syncer_up(
-                &mut runtime.spawning_services,
-                &mut runtime.registered_services,
-                blockchain,
-                network,
-                &runtime.config,
-            )? {
+            if let Some(service_id) =
+                runtime.syncer_up(blockchain, network, &SyncerInstance::DEFAULT)?
+            {
                 event.complete_sync_service(service_id, SyncMsg::Task(syncer_task))?;
                 Ok(Some(SyncerStateMachine::AwaitingSyncerRequest(
                     AwaitingSyncerRequest {
                         source,
                         syncer_task_id,
-                        syncer: ServiceId::Syncer(blockchain, network),
+                        syncer: ServiceId::Syncer(blockchain, network, SyncerInstance::DEFAULT),
                     },
                 )))
             } else {
                 Ok(Some(SyncerStateMachine::AwaitingSyncer(AwaitingSyncer {
                     source,
-                    syncer: ServiceId::Syncer(blockchain, network),
+                    syncer: ServiceId::Syncer(blockchain, network, SyncerInstance::DEFAULT),
                     syncer_task,
                     syncer_task_id,
                 })))
@@ -198,7 +208,7 @@ fn attempt_transition_to_awaiting_syncer_or_awaiting_syncer_request(
                     (Blockchain::Bitcoin, address.network.into())
                 }
                 AddressSecretKey::Monero { address, .. } => {
-                    (Blockchain::Monero, address.network.into())
+                    (Blockchain::Monero, monero_syncer_network(address)?)
                 }
             };
             let syncer_task = Task::GetAddressBalance(GetAddressBalance {
@@ -206,25 +216,21 @@ fn attempt_transition_to_awaiting_syncer_or_awaiting_syncer_request(
                 address_secret_key,
             });
             // check if a monero syncer is up
-            if let Some(service_id) = syncer_up(
-                &mut runtime.spawning_services,
-                &mut runtime.registered_services,
-                blockchain,
-                network,
-                &runtime.config,
-            )? {
+            if let Some(service_id) =
+                runtime.syncer_up(blockchain, network, &SyncerInstance::DEFAULT)?
+            {
                 event.complete_sync_service(service_id, SyncMsg::Task(syncer_task))?;
                 Ok(Some(SyncerStateMachine::AwaitingSyncerRequest(
                     AwaitingSyncerRequest {
                         source,
                         syncer_task_id,
-                        syncer: ServiceId::Syncer(blockchain, network),
+                        syncer: ServiceId::Syncer(blockchain, network, SyncerInstance::DEFAULT),
                     },
                 )))
             } else {
                 Ok(Some(SyncerStateMachine::AwaitingSyncer(AwaitingSyncer {
                     source,
-                    syncer: ServiceId::Syncer(blockchain, network),
+                    syncer: ServiceId::Syncer(blockchain, network, SyncerInstance::DEFAULT),
                     syncer_task,
                     syncer_task_id,
                 })))
@@ -236,26 +242,20 @@ fn attempt_transition_to_awaiting_syncer_or_awaiting_syncer_request(
             runtime.syncer_task_counter += 1;
             let syncer_task = Task::HealthCheck(HealthCheck { id: syncer_task_id });
 
-            match syncer_up(
-                &mut runtime.spawning_services,
-                &mut runtime.registered_services,
-                blockchain,
-                network,
-                &runtime.config,
-            ) {
+            match runtime.syncer_up(blockchain, network, &SyncerInstance::DEFAULT) {
                 Ok(Some(service_id)) => {
                     event.complete_sync_service(service_id, SyncMsg::Task(syncer_task))?;
                     Ok(Some(SyncerStateMachine::AwaitingSyncerRequest(
                         AwaitingSyncerRequest {
                             source,
                             syncer_task_id,
-                            syncer: ServiceId::Syncer(blockchain, network),
+                            syncer: ServiceId::Syncer(blockchain, network, SyncerInstance::DEFAULT),
                         },
                     )))
                 }
                 Ok(None) => Ok(Some(SyncerStateMachine::AwaitingSyncer(AwaitingSyncer {
                     source,
-                    syncer: ServiceId::Syncer(blockchain, network),
+                    syncer: ServiceId::Syncer(blockchain, network, SyncerInstance::DEFAULT),
                     syncer_task,
                     syncer_task_id,
                 }))),
@@ -268,6 +268,47 @@ fn attempt_transition_to_awaiting_syncer_or_awaiting_syncer_request(
             }
         }
 
+        BusMsg::Ctl(CtlMsg::GetBlock(GetBlock {
+            blockchain,
+            network,
+            height,
+            include_txs,
+        })) => {
+            let syncer_task_id = TaskId(runtime.syncer_task_counter);
+            runtime.syncer_task_counter += 1;
+            let syncer_task = Task::GetBlockByHeight(GetBlockByHeight {
+                id: syncer_task_id,
+                height,
+                include_txs,
+            });
+
+            match runtime.syncer_up(blockchain, network, &SyncerInstance::DEFAULT) {
+                Ok(Some(service_id)) => {
+                    event.complete_sync_service(service_id, SyncMsg::Task(syncer_task))?;
+                    Ok(Some(SyncerStateMachine::AwaitingSyncerRequest(
+                        AwaitingSyncerRequest {
+                            source,
+                            syncer_task_id,
+                            syncer: ServiceId::Syncer(blockchain, network, SyncerInstance::DEFAULT),
+                        },
+                    )))
+                }
+                Ok(None) => Ok(Some(SyncerStateMachine::AwaitingSyncer(AwaitingSyncer {
+                    source,
+                    syncer: ServiceId::Syncer(blockchain, network, SyncerInstance::DEFAULT),
+                    syncer_task,
+                    syncer_task_id,
+                }))),
+                Err(err) => {
+                    event.complete_ctl(CtlMsg::Failure(Failure {
+                        code: FailureCode::SyncerFailure,
+                        info: err.to_string(),
+                    }))?;
+                    Ok(None)
+                }
+            }
+        }
+
         req => {
             warn!(
                 "Request {} from {} invalid for state start - invalidating.",
@@ -352,6 +393,20 @@ fn attempt_transition_to_end(
             Ok(None)
         }
 
+        (BusMsg::Sync(SyncMsg::Event(SyncerEvent::SweepLocked(locked))), syncer_id)
+            if syncer == syncer_id && locked.id == syncer_task_id =>
+        {
+            event.send_client_info(
+                source,
+                InfoMsg::String(format!(
+                    "Funds are not spendable yet, {} confirmations remaining. Try again once unlocked.",
+                    locked.confirmations_remaining
+                )),
+            )?;
+            runtime.clean_up_after_syncer_usage(event.endpoints)?;
+            Ok(None)
+        }
+
         (BusMsg::Sync(SyncMsg::Event(SyncerEvent::AddressBalance(res))), syncer_id)
             if syncer == syncer_id && res.id == syncer_task_id =>
         {
@@ -359,7 +414,7 @@ fn attempt_transition_to_end(
                 event.send_client_ctl(
                     source,
                     CtlMsg::Failure(Failure {
-                        code: FailureCode::Unknown,
+                        code: FailureCode::SyncerFailure,
                         info: format!("Failed to get adddress balance {}", err),
                     }),
                 )?;
@@ -385,7 +440,7 @@ fn attempt_transition_to_end(
             event.send_client_ctl(
                 source,
                 CtlMsg::Failure(Failure {
-                    code: FailureCode::Unknown,
+                    code: FailureCode::SyncerFailure,
                     info: format!(
                         "Failure in chain query{}",
                         error.map_or("".to_string(), |e| format!(": {}", e))
@@ -396,6 +451,31 @@ fn attempt_transition_to_end(
             Ok(None)
         }
 
+        (BusMsg::Sync(SyncMsg::Event(SyncerEvent::BlockData(block))), syncer_id)
+            if syncer == syncer_id && block.id == syncer_task_id =>
+        {
+            let BlockData {
+                height,
+                hash,
+                header,
+                tx_count,
+                txs,
+                ..
+            } = block;
+            event.send_client_info(
+                source,
+                InfoMsg::BlockInfo(BlockInfo {
+                    height,
+                    hash: hex::encode(hash),
+                    header: hex::encode(header),
+                    tx_count,
+                    txs: txs.map(|txs| txs.iter().map(hex::encode).collect()),
+                }),
+            )?;
+            runtime.clean_up_after_syncer_usage(event.endpoints)?;
+            Ok(None)
+        }
+
         (BusMsg::Sync(SyncMsg::Event(SyncerEvent::HealthResult(res))), syncer_id)
             if syncer == syncer_id && res.id == syncer_task_id =>
         {
@@ -426,3 +506,14 @@ fn attempt_transition_to_end(
         }
     }
 }
+
+/// The network of the Monero syncer serving the address, Monero testnet addresses have none as
+/// testnet swaps run on stagenet
+fn monero_syncer_network(address: &monero::Address) -> Result<Network, Error> {
+    monero_address_network(address.network).ok_or_else(|| {
+        Error::Farcaster(format!(
+            "No syncer serves the Monero {:?} address {}, testnet swaps use stagenet addresses",
+            address.network, address
+        ))
+    })
+}
@@ -5,15 +5,21 @@
 // https://opensource.org/licenses/MIT.
 
 use crate::bus::ctl::{
-    BitcoinFundingInfo, CtlMsg, FundingInfo, InitMakerSwap, InitTakerSwap, MoneroFundingInfo,
-    ProtoDeal, PubDeal, SwapKeys, WrappedKeyManager,
+    BitcoinFundingInfo, CtlMsg, FundingInfo, InitMakerSwap, InitTakerSwap, MoneroDeposit,
+    MoneroFundingInfo, ProtoDeal, PubDeal, SwapKeys, UpdatePeerd, WrappedKeyManager,
 };
-use crate::bus::info::{InfoMsg, MadeDeal, TookDeal, ViewableDeal};
-use crate::bus::p2p::{Commit, PeerMsg};
-use crate::bus::{CheckpointEntry, DealInfo, DealStatus, Failure, FailureCode};
-use crate::farcasterd::runtime::{launch_swapd, syncer_up, Runtime};
+use crate::bus::info::{InfoMsg, MadeDeal, SwapLifecycle, SwapSummary, TookDeal, ViewableDeal};
+use crate::bus::p2p::{buy_key_commitment, derive_swap_id, Commit, PeerMsg};
+use crate::bus::{
+    CheckpointEntry, DealBookEntry, DealInfo, DealStatus, Failure, FailureCode, OptionDetails,
+};
+use crate::config::AutoFundingServers;
+use crate::farcasterd::price_oracle::RateCheck;
+use crate::farcasterd::runtime::{check_syncers_available, launch_swapd, spawn_bridged, Runtime};
+use crate::farcasterd::webhook::{self, SwapEndNotification};
 use crate::service::{SwapDetails, SwapLogging};
 use crate::LogStyle;
+use crate::SyncerInstance;
 use crate::{
     bus::{BusMsg, Outcome},
     error::Error,
@@ -26,8 +32,16 @@ use farcaster_core::swap::{btcxmr::Deal, SwapId};
 use farcaster_core::Uuid;
 use internet2::addr::{NodeAddr, NodeId};
 use microservices::esb::Handler;
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
+use std::net::{SocketAddr, TcpStream};
 use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Timeout of the maker reachability check of a dry run take
+const DRY_RUN_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Grace window past a deal's expiry during which it is still honored, absorbing clock skew
+/// between the maker and the taker
+const DEAL_EXPIRY_GRACE: Duration = Duration::from_secs(60);
 
 /// State machine for launching a swap and cleaning up once done.
 ///
@@ -65,7 +79,7 @@ pub enum TradeStateMachine {
     /// StartTaker state - transitions to TakeDeal on cli request or None on
     /// failure, or TakerConnect if not yet connected. Transition to TakeDeal
     /// triggers a connect peerd launch (if required). If connected, sends
-    /// CreateSwapKeys to walletd, sends TookDeal back to cli.
+    /// CreateSwapKeys to walletd.
     #[display("Start Taker")]
     StartTaker,
 
@@ -77,8 +91,8 @@ pub enum TradeStateMachine {
 
     /// TakerConnect state - transitions to TakeDeal once ConnectSuccess is
     /// received from peerd or None if ConnectFailed is received. On
-    /// ConnectSuccess sends CreateSwapKeys to walletd and TookDeal back to
-    /// cli. On Connect Failed sends Failure back to cli.
+    /// ConnectSuccess sends CreateSwapKeys to walletd. On Connect Failed sends
+    /// Failure back to cli.
     #[display("Taker Connect")]
     TakerConnect(TakerConnect),
 
@@ -96,8 +110,8 @@ pub enum TradeStateMachine {
     TakerCommit(TakerCommit),
 
     /// TakeDeal state - transitions to SwapdLaunched once SwapKeys is
-    /// received from walletd. Transition to SwapdLaunched triggers launch
-    /// swapd.
+    /// received from walletd. Transition to SwapdLaunched sends TookDeal with
+    /// the swap id derived from the keys back to cli and triggers launch swapd.
     #[display("Take Deal")]
     TakeDeal(TakeDeal),
 
@@ -124,6 +138,9 @@ pub struct MakeDeal {
     deal: Deal,
     arb_addr: bitcoin::Address,
     acc_addr: monero::Address,
+    funding_confirmations: Option<u32>,
+    syncer_instance: SyncerInstance,
+    expiry: Option<u64>,
 }
 
 pub struct TakerCommit {
@@ -132,12 +149,16 @@ pub struct TakerCommit {
     commit: Commit,
     target_bitcoin_address: bitcoin::Address,
     target_monero_address: monero::Address,
+    funding_confirmations: Option<u32>,
+    syncer_instance: SyncerInstance,
 }
 
 pub struct TakerConnect {
     deal: Deal,
     arb_addr: bitcoin::Address,
     acc_addr: monero::Address,
+    funding_confirmations: Option<u32>,
+    syncer_instance: SyncerInstance,
     source: ServiceId,
 }
 
@@ -145,7 +166,10 @@ pub struct TakeDeal {
     deal: Deal,
     arb_addr: bitcoin::Address,
     acc_addr: monero::Address,
+    funding_confirmations: Option<u32>,
+    syncer_instance: SyncerInstance,
     peerd: ServiceId,
+    source: ServiceId,
 }
 
 pub struct SwapdLaunched {
@@ -160,6 +184,8 @@ pub struct SwapdLaunched {
     key_manager: WrappedKeyManager,
     target_bitcoin_address: bitcoin::Address,
     target_monero_address: monero::Address,
+    monero_deposit: MoneroDeposit,
+    syncer_instance: SyncerInstance,
 }
 
 pub struct RestoringSwapd {
@@ -169,6 +195,8 @@ pub struct RestoringSwapd {
     expected_counterparty_node_id: Option<NodeId>,
     arbitrating_syncer_up: Option<ServiceId>,
     accordant_syncer_up: Option<ServiceId>,
+    arbitrating_syncer_instance: SyncerInstance,
+    accordant_syncer_instance: SyncerInstance,
     swapd_up: bool,
     expect_connection: bool,
     peerd: Option<ServiceId>,
@@ -307,6 +335,31 @@ impl TradeStateMachine {
         }
     }
 
+    /// The MakeDeal state of a deal re-advertised from the deal book
+    pub fn restored_make_deal(entry: DealBookEntry) -> TradeStateMachine {
+        TradeStateMachine::MakeDeal(MakeDeal {
+            deal: entry.deal,
+            arb_addr: entry.arbitrating_addr,
+            acc_addr: entry.accordant_addr,
+            funding_confirmations: entry.funding_confirmations,
+            syncer_instance: SyncerInstance(entry.syncer_instance),
+            expiry: entry.expiry,
+        })
+    }
+
+    pub fn open_deal_expiry(&self) -> Option<u64> {
+        if let TradeStateMachine::MakeDeal(MakeDeal { expiry, .. }) = self {
+            *expiry
+        } else {
+            None
+        }
+    }
+
+    /// Whether the machine holds an open deal that is past its expiry, grace window included
+    pub fn open_deal_expired(&self) -> bool {
+        matches!(self, TradeStateMachine::MakeDeal(MakeDeal { expiry, .. }) if deal_expired(*expiry))
+    }
+
     pub fn deal(&self) -> Option<&Deal> {
         match self {
             TradeStateMachine::MakeDeal(MakeDeal { deal, .. }) => Some(deal),
@@ -383,6 +436,110 @@ impl TradeStateMachine {
         }
     }
 
+    /// Whether the swap waits for the syncer to register before it runs
+    pub fn awaits_syncer(&self, syncer: &ServiceId) -> bool {
+        let (
+            deal,
+            arbitrating_syncer_up,
+            accordant_syncer_up,
+            arbitrating_syncer_instance,
+            accordant_syncer_instance,
+        ) = match self {
+            TradeStateMachine::SwapdLaunched(SwapdLaunched {
+                deal,
+                arbitrating_syncer_up,
+                accordant_syncer_up,
+                syncer_instance,
+                ..
+            }) => (
+                deal,
+                arbitrating_syncer_up,
+                accordant_syncer_up,
+                syncer_instance,
+                syncer_instance,
+            ),
+            TradeStateMachine::RestoringSwapd(RestoringSwapd {
+                deal,
+                arbitrating_syncer_up,
+                accordant_syncer_up,
+                arbitrating_syncer_instance,
+                accordant_syncer_instance,
+                ..
+            }) => (
+                deal,
+                arbitrating_syncer_up,
+                accordant_syncer_up,
+                arbitrating_syncer_instance,
+                accordant_syncer_instance,
+            ),
+            _ => return false,
+        };
+        let network = deal.parameters.network;
+        (arbitrating_syncer_up.is_none()
+            && *syncer
+                == ServiceId::Syncer(
+                    deal.parameters.arbitrating_blockchain,
+                    network,
+                    arbitrating_syncer_instance.clone(),
+                ))
+            || (accordant_syncer_up.is_none()
+                && *syncer
+                    == ServiceId::Syncer(
+                        deal.parameters.accordant_blockchain,
+                        network,
+                        accordant_syncer_instance.clone(),
+                    ))
+    }
+
+    /// Summary of the swap once its swapd is launched, listing it without querying swapd
+    pub fn swap_summary(&self) -> Option<SwapSummary> {
+        let lifecycle = match self {
+            TradeStateMachine::SwapdLaunched(_) => SwapLifecycle::Launching,
+            TradeStateMachine::RestoringSwapd(_) => SwapLifecycle::Restoring,
+            TradeStateMachine::SwapdRunning(_) => SwapLifecycle::Running,
+            _ => return None,
+        };
+        let deal = self.deal()?;
+        Some(SwapSummary {
+            swap_id: self.swap_id()?,
+            lifecycle,
+            local_swap_role: self.swap_role()?,
+            local_trade_role: self.trade_role()?,
+            counterparty_node_id: self.counterparty_node_id(),
+            arbitrating_amount: deal.parameters.arbitrating_amount,
+            accordant_amount: deal.parameters.accordant_amount,
+        })
+    }
+
+    /// Node id of the swap counterparty: the connected peer, else the one expected to reconnect
+    pub fn counterparty_node_id(&self) -> Option<NodeId> {
+        match self {
+            TradeStateMachine::SwapdLaunched(SwapdLaunched { peerd, .. }) => peerd.node_id(),
+            TradeStateMachine::RestoringSwapd(RestoringSwapd {
+                peerd,
+                deal,
+                trade_role,
+                expected_counterparty_node_id,
+                ..
+            })
+            | TradeStateMachine::SwapdRunning(SwapdRunning {
+                peerd,
+                deal,
+                trade_role,
+                expected_counterparty_node_id,
+                ..
+            }) => peerd
+                .as_ref()
+                .and_then(|peerd| peerd.node_id())
+                .or(*expected_counterparty_node_id)
+                .or_else(|| {
+                    // a taker reconnects to the maker of the deal
+                    (*trade_role == TradeRole::Taker).then(|| node_addr_from_deal(deal).id)
+                }),
+            _ => None,
+        }
+    }
+
     pub fn syncers(&self) -> Vec<ServiceId> {
         match self {
             TradeStateMachine::SwapdLaunched(SwapdLaunched {
@@ -412,6 +569,22 @@ impl TradeStateMachine {
         }
     }
 
+    /// Replace a syncer of the running swap once the swap failed over to a fallback syncer
+    pub fn replace_syncer(&mut self, syncer: &ServiceId, new_syncer: ServiceId) {
+        if let TradeStateMachine::SwapdRunning(SwapdRunning {
+            arbitrating_syncer,
+            accordant_syncer,
+            ..
+        }) = self
+        {
+            for current in [arbitrating_syncer, accordant_syncer] {
+                if current == syncer {
+                    *current = new_syncer.clone();
+                }
+            }
+        }
+    }
+
     pub fn get_connection(&self) -> Option<ServiceId> {
         match self {
             TradeStateMachine::TakeDeal(TakeDeal { peerd, .. }) => Some(peerd.clone()),
@@ -434,6 +607,20 @@ impl TradeStateMachine {
         }
     }
 
+    /// Returns the swap id of a running taker swap without a connection to its counterparty,
+    /// i.e. a restored swap whose counterparty was unreachable.
+    pub fn awaiting_reconnect(&self) -> Option<SwapId> {
+        match self {
+            TradeStateMachine::SwapdRunning(SwapdRunning {
+                peerd: None,
+                trade_role: TradeRole::Taker,
+                swap_id,
+                ..
+            }) => Some(*swap_id),
+            _ => None,
+        }
+    }
+
     pub fn awaiting_connect_from(&self) -> Option<NodeAddr> {
         match self {
             TradeStateMachine::TakerConnect(taker_connect) => {
@@ -511,15 +698,25 @@ fn attempt_transition_to_make_deal(
             arbitrating_addr,
             accordant_addr,
             public_addr,
-            ..
+            funding_confirmations,
+            syncer_instance,
+            expiry,
         })) => {
             // validate deal parameters
-            if let Err(e) = runtime.config.validate_deal_parameters(
-                &deal_parameters,
-                &arbitrating_addr,
-                &accordant_addr,
-                TradeRole::Maker,
-            ) {
+            if let Err(e) = runtime
+                .config
+                .validate_deal_parameters(
+                    &deal_parameters,
+                    &arbitrating_addr,
+                    &accordant_addr,
+                    TradeRole::Maker,
+                )
+                .and_then(|_| {
+                    runtime
+                        .config
+                        .get_deal_swap_config(&deal_parameters, funding_confirmations)
+                })
+            {
                 warn!("Deal parameters validation error: {}", e);
                 event.complete_client_ctl(CtlMsg::Failure(Failure {
                     code: FailureCode::Unknown,
@@ -565,6 +762,20 @@ fn attempt_transition_to_make_deal(
                             serialized_deal: deal.to_string(),
                             status: DealStatus::Open,
                             local_trade_role: TradeRole::Maker,
+                            expiry,
+                        }),
+                    )?;
+                    event.send_ctl_service(
+                        ServiceId::Database,
+                        CtlMsg::SetDealBookEntry(DealBookEntry {
+                            deal: deal.clone(),
+                            arbitrating_addr: arbitrating_addr.clone(),
+                            accordant_addr,
+                            funding_confirmations,
+                            syncer_instance: syncer_instance.clone(),
+                            expiry,
+                            local_trade_role: TradeRole::Maker,
+                            status: DealStatus::Open,
                         }),
                     )?;
                     event.complete_client_info(InfoMsg::MadeDeal(MadeDeal {
@@ -579,6 +790,9 @@ fn attempt_transition_to_make_deal(
                         deal,
                         arb_addr: arbitrating_addr,
                         acc_addr: accordant_addr,
+                        funding_confirmations,
+                        syncer_instance: SyncerInstance(syncer_instance),
+                        expiry,
                     })))
                 }
             }
@@ -603,14 +817,22 @@ fn attempt_transition_to_taker_connect_or_take_deal(
             deal,
             bitcoin_address: arb_addr,
             monero_address: acc_addr,
+            funding_confirmations,
+            syncer_instance,
+            dry_run,
+            force,
         })) => {
+            let syncer_instance = SyncerInstance(syncer_instance);
             // validate deal parameters
-            if let Err(e) = runtime.config.validate_deal_parameters(
-                &deal.parameters,
-                &arb_addr,
-                &acc_addr,
-                TradeRole::Taker,
-            ) {
+            if let Err(e) = runtime
+                .config
+                .validate_deal_parameters(&deal.parameters, &arb_addr, &acc_addr, TradeRole::Taker)
+                .and_then(|_| {
+                    runtime
+                        .config
+                        .get_deal_swap_config(&deal.parameters, funding_confirmations)
+                })
+            {
                 warn!("Deal parameters validation error: {}", e);
                 event.complete_client_ctl(CtlMsg::Failure(Failure {
                     code: FailureCode::Unknown,
@@ -618,7 +840,22 @@ fn attempt_transition_to_taker_connect_or_take_deal(
                 }))?;
                 return Ok(None);
             }
-            if runtime.consumed_deals_contains(&deal) || runtime.deals.contains(&deal) {
+            // a second swap of the deal would race the first one for the same counterparty funds,
+            // even when forced
+            if runtime.consumed_deals_contains(&deal) {
+                let msg = format!(
+                    "A swap of deal {} is already in progress, ignoring request",
+                    deal.id()
+                );
+                log_helper.log_warn(format!("{}", msg.err()));
+                event.complete_client_ctl(CtlMsg::Failure(Failure {
+                    code: FailureCode::SwapInProgress,
+                    info: msg,
+                }))?;
+                return Ok(None);
+            }
+            // a forced retake runs under a new swap id, derived from its fresh swap keys
+            if runtime.open_deals_contains(&deal) || (runtime.deals.contains(&deal) && !force) {
                 let msg = format!(
                     "{} already exists or was already taken, ignoring request",
                     &deal.to_string()
@@ -630,6 +867,70 @@ fn attempt_transition_to_taker_connect_or_take_deal(
                 }))?;
                 return Ok(None);
             }
+            if runtime.swap_limit_reached() {
+                let msg = format!(
+                    "Already running the maximum of {} concurrent swaps, cannot take deal {}",
+                    runtime.config.get_max_concurrent_swaps(),
+                    deal.id()
+                );
+                log_helper.log_warn(&msg);
+                event.complete_client_ctl(CtlMsg::Failure(Failure {
+                    code: FailureCode::SwapLimitReached,
+                    info: msg,
+                }))?;
+                return Ok(None);
+            }
+
+            // opt-in sanity check of the rate, a reference price that cannot be fetched does not
+            // prevent taking the deal
+            if let Some(oracle) = runtime.config.get_price_oracle() {
+                match runtime.price_cache.check_rate(&oracle, &deal.parameters) {
+                    check @ RateCheck::Deviates { .. } if oracle.reject.unwrap_or(true) => {
+                        let msg = format!("Refusing to take deal {}, {}", deal.id(), check);
+                        log_helper.log_warn(&msg);
+                        event.complete_client_ctl(CtlMsg::Failure(Failure {
+                            code: FailureCode::RateOutOfTolerance,
+                            info: msg,
+                        }))?;
+                        return Ok(None);
+                    }
+                    check @ RateCheck::Within { .. } => log_helper.log_debug(check),
+                    check => log_helper.log_warn(format!("Taking deal {}, {}", deal.id(), check)),
+                }
+            }
+
+            if dry_run {
+                match take_deal_preflight(runtime, &deal, &syncer_instance) {
+                    Ok(probe) => {
+                        // the network checks block, the client is answered once they complete
+                        let client = event.source.clone();
+                        spawn_bridged(move || match probe.run() {
+                            Ok(report) => {
+                                info!("Dry run: {}", report);
+                                (client, CtlMsg::Success(OptionDetails::with(report)))
+                            }
+                            Err(err) => {
+                                warn!("Dry run failed: {}", err);
+                                (
+                                    client,
+                                    CtlMsg::Failure(Failure {
+                                        code: FailureCode::Unknown,
+                                        info: err,
+                                    }),
+                                )
+                            }
+                        });
+                    }
+                    Err(err) => {
+                        log_helper.log_warn(format!("Dry run failed: {}", err));
+                        event.complete_client_ctl(CtlMsg::Failure(Failure {
+                            code: FailureCode::Unknown,
+                            info: err.to_string(),
+                        }))?;
+                    }
+                }
+                return Ok(None);
+            }
 
             let peer_node_addr = node_addr_from_deal(&deal);
             // connect to the remote peer
@@ -657,22 +958,23 @@ fn attempt_transition_to_taker_connect_or_take_deal(
                             ServiceId::Wallet,
                             CtlMsg::CreateSwapKeys(deal.clone(), runtime.wallet_token.clone()),
                         )?;
-                        event.complete_client_info(InfoMsg::TookDeal(TookDeal {
-                            deal_id: deal.id(),
-                            message: deal_registered,
-                        }))?;
                         runtime.deals.insert(deal.clone());
                         Ok(Some(TradeStateMachine::TakeDeal(TakeDeal {
                             deal,
                             arb_addr,
                             acc_addr,
+                            funding_confirmations,
+                            syncer_instance,
                             peerd: peer_service_id,
+                            source: event.source,
                         })))
                     } else {
                         Ok(Some(TradeStateMachine::TakerConnect(TakerConnect {
                             deal,
                             arb_addr,
                             acc_addr,
+                            funding_confirmations,
+                            syncer_instance,
                             source: event.source,
                         })))
                     }
@@ -701,6 +1003,8 @@ fn attempt_transition_to_restoring_swapd(
             deal,
             trade_role,
             expected_counterparty_node_id,
+            arbitrating_syncer_instance,
+            accordant_syncer_instance,
         })) => {
             if let Err(err) = runtime.services_ready() {
                 event.complete_client_ctl(CtlMsg::Failure(Failure {
@@ -766,22 +1070,36 @@ fn attempt_transition_to_restoring_swapd(
                 false
             };
 
-            let arbitrating_syncer_up = syncer_up(
-                &mut runtime.spawning_services,
-                &mut runtime.registered_services,
+            // restored swaps run on the syncers they last used, including a failover syncer
+            let arbitrating_syncer_instance = SyncerInstance(arbitrating_syncer_instance);
+            let accordant_syncer_instance = SyncerInstance(accordant_syncer_instance);
+            let arbitrating_syncer_up = runtime.syncer_up(
                 deal.parameters.arbitrating_blockchain,
                 deal.parameters.network,
-                &runtime.config,
+                &arbitrating_syncer_instance,
             )?;
-            let accordant_syncer_up = syncer_up(
-                &mut runtime.spawning_services,
-                &mut runtime.registered_services,
+            let accordant_syncer_up = runtime.syncer_up(
                 deal.parameters.accordant_blockchain,
                 deal.parameters.network,
-                &runtime.config,
+                &accordant_syncer_instance,
             )?;
 
-            launch_swapd(trade_role, deal.clone(), swap_id, swap_config)?;
+            // the restored swap keeps the deadline of its checkpoint
+            let swapd = launch_swapd(
+                trade_role,
+                deal.clone(),
+                swap_id,
+                swap_config,
+                None,
+                runtime.config.get_rebroadcast_interval(),
+                runtime
+                    .config
+                    .get_monero_unlock_depth(deal.parameters.network, &accordant_syncer_instance),
+                runtime.config.count_unconfirmed_funding(),
+                &arbitrating_syncer_instance,
+                &accordant_syncer_instance,
+            )?;
+            runtime.launched.push(("swapd".to_string(), swapd));
             event.complete_client_info(InfoMsg::String("Restoring checkpoint.".to_string()))?;
 
             Ok(Some(TradeStateMachine::RestoringSwapd(RestoringSwapd {
@@ -791,6 +1109,8 @@ fn attempt_transition_to_restoring_swapd(
                 expected_counterparty_node_id,
                 arbitrating_syncer_up,
                 accordant_syncer_up,
+                arbitrating_syncer_instance,
+                accordant_syncer_instance,
                 swapd_up: false,
                 peerd,
                 expect_connection,
@@ -817,8 +1137,81 @@ fn attempt_transition_to_taker_committed(
         deal,
         arb_addr,
         acc_addr,
+        funding_confirmations,
+        syncer_instance,
+        expiry,
     } = make_deal;
     match (event.request.clone(), event.source.clone()) {
+        (BusMsg::P2p(PeerMsg::TakerCommit(taker_commit)), ServiceId::Peer(..))
+            if deal == taker_commit.deal && deal_expired(expiry) =>
+        {
+            log_helper.log_warn(format!(
+                "Received TakerCommit for expired deal {}, replying with deal not found.",
+                deal.id()
+            ));
+            event.send_msg_service(
+                event.source.clone(),
+                PeerMsg::DealNotFound(taker_commit.commit.swap_id()),
+            )?;
+            event.complete_ctl_service(
+                ServiceId::Database,
+                CtlMsg::SetDealInfo(DealInfo {
+                    deal: deal.clone(),
+                    serialized_deal: deal.to_string(),
+                    status: DealStatus::Expired,
+                    local_trade_role: TradeRole::Maker,
+                    expiry,
+                }),
+            )?;
+            Ok(None)
+        }
+        (BusMsg::P2p(PeerMsg::TakerCommit(taker_commit)), ServiceId::Peer(..))
+            if deal == taker_commit.deal && runtime.swap_limit_reached() =>
+        {
+            // the deal stays open and can be taken once a running swap ends
+            log_helper.log_warn(format!(
+                "Received TakerCommit for deal {} while running the maximum of {} concurrent swaps, replying with deal not found.",
+                deal.id(),
+                runtime.config.get_max_concurrent_swaps()
+            ));
+            event.send_msg_service(
+                event.source.clone(),
+                PeerMsg::DealNotFound(taker_commit.commit.swap_id()),
+            )?;
+            Ok(Some(TradeStateMachine::MakeDeal(MakeDeal {
+                deal,
+                arb_addr,
+                acc_addr,
+                funding_confirmations,
+                syncer_instance,
+                expiry,
+            })))
+        }
+        (BusMsg::P2p(PeerMsg::TakerCommit(taker_commit)), ServiceId::Peer(..))
+            if deal == taker_commit.deal
+                && taker_commit.commit.swap_id() != taker_commit.derived_swap_id() =>
+        {
+            // both peers derive the swap id from the deal and the taker commitment, a commitment
+            // to another id cannot be correlated with the swap of the taker
+            log_helper.log_warn(format!(
+                "Received TakerCommit for deal {} with swap id {} instead of {}, replying with deal not found.",
+                deal.id(),
+                taker_commit.commit.swap_id(),
+                taker_commit.derived_swap_id()
+            ));
+            event.send_msg_service(
+                event.source.clone(),
+                PeerMsg::DealNotFound(taker_commit.commit.swap_id()),
+            )?;
+            Ok(Some(TradeStateMachine::MakeDeal(MakeDeal {
+                deal,
+                arb_addr,
+                acc_addr,
+                funding_confirmations,
+                syncer_instance,
+                expiry,
+            })))
+        }
         (BusMsg::P2p(PeerMsg::TakerCommit(taker_commit)), ServiceId::Peer(..)) => {
             if deal == taker_commit.deal {
                 let source = event.source.clone();
@@ -836,6 +1229,7 @@ fn attempt_transition_to_taker_committed(
                         serialized_deal: deal.to_string(),
                         status: DealStatus::InProgress,
                         local_trade_role: TradeRole::Maker,
+                        expiry: None,
                     }),
                 )?;
                 Ok(Some(TradeStateMachine::TakerCommit(TakerCommit {
@@ -844,6 +1238,8 @@ fn attempt_transition_to_taker_committed(
                     commit: taker_commit.commit,
                     target_bitcoin_address: arb_addr,
                     target_monero_address: acc_addr,
+                    funding_confirmations,
+                    syncer_instance,
                 })))
             } else {
                 log_helper.log_error(format!(
@@ -854,6 +1250,9 @@ fn attempt_transition_to_taker_committed(
                     deal,
                     arb_addr,
                     acc_addr,
+                    funding_confirmations,
+                    syncer_instance,
+                    expiry,
                 })))
             }
         }
@@ -868,6 +1267,7 @@ fn attempt_transition_to_taker_committed(
                         serialized_deal: deal.to_string(),
                         status: DealStatus::Revoked,
                         local_trade_role: TradeRole::Maker,
+                        expiry: None,
                     }),
                 )?;
                 event.complete_client_info(InfoMsg::String(
@@ -882,6 +1282,9 @@ fn attempt_transition_to_taker_committed(
                     deal,
                     arb_addr,
                     acc_addr,
+                    funding_confirmations,
+                    syncer_instance,
+                    expiry,
                 })))
             }
         }
@@ -901,6 +1304,9 @@ fn attempt_transition_to_taker_committed(
                 deal,
                 arb_addr,
                 acc_addr,
+                funding_confirmations,
+                syncer_instance,
+                expiry,
             })))
         }
     }
@@ -918,11 +1324,13 @@ fn attempt_transition_from_taker_commit_to_swapd_launched(
         commit,
         target_bitcoin_address,
         target_monero_address,
+        funding_confirmations,
+        syncer_instance,
     } = taker_commit;
     match event.request {
         BusMsg::Ctl(CtlMsg::SwapKeys(swap_keys)) => {
             let swap_id = commit.swap_id();
-            log_helper.log_info("Creating new swap.");
+            log_helper.log_info(format!("Creating new swap {}.", swap_id));
             let tsm = transition_to_swapd_launched_tsm(
                 runtime,
                 ConsumedDealRole::Maker(commit),
@@ -931,6 +1339,8 @@ fn attempt_transition_from_taker_commit_to_swapd_launched(
                 deal,
                 target_bitcoin_address,
                 target_monero_address,
+                funding_confirmations,
+                syncer_instance,
                 swap_id,
                 log_helper,
             )?;
@@ -948,6 +1358,8 @@ fn attempt_transition_from_taker_commit_to_swapd_launched(
                 commit,
                 target_bitcoin_address,
                 target_monero_address,
+                funding_confirmations,
+                syncer_instance,
             })))
         }
     }
@@ -963,6 +1375,8 @@ fn attempt_transition_to_take_deal(
         deal,
         arb_addr,
         acc_addr,
+        funding_confirmations,
+        syncer_instance,
         source,
     } = taker_connect;
     match event.request {
@@ -980,19 +1394,15 @@ fn attempt_transition_to_take_deal(
                 ServiceId::Wallet,
                 CtlMsg::CreateSwapKeys(deal.clone(), runtime.wallet_token.clone()),
             )?;
-            event.send_client_info(
-                source,
-                InfoMsg::TookDeal(TookDeal {
-                    deal_id: deal.id(),
-                    message: deal_registered,
-                }),
-            )?;
             runtime.deals.insert(deal.clone());
             Ok(Some(TradeStateMachine::TakeDeal(TakeDeal {
                 deal,
                 arb_addr,
                 acc_addr,
+                funding_confirmations,
+                syncer_instance,
                 peerd: event.source,
+                source,
             })))
         }
         BusMsg::Ctl(CtlMsg::ConnectFailed)
@@ -1008,7 +1418,7 @@ fn attempt_transition_to_take_deal(
                 source,
                 CtlMsg::Failure(Failure {
                     info: format!("Could not connect to remote peer {}.", event.source),
-                    code: FailureCode::Unknown,
+                    code: FailureCode::PeerUnreachable,
                 }),
             )?;
             Ok(None)
@@ -1030,6 +1440,8 @@ fn attempt_transition_to_take_deal(
                 deal,
                 arb_addr,
                 acc_addr,
+                funding_confirmations,
+                syncer_instance,
                 source,
             })))
         }
@@ -1046,12 +1458,23 @@ fn attempt_transition_from_take_deal_to_swapd_launched(
         deal,
         arb_addr,
         acc_addr,
+        funding_confirmations,
+        syncer_instance,
         peerd,
+        source,
     } = take_deal;
     match &event.request {
         BusMsg::Ctl(CtlMsg::SwapKeys(swap_keys)) => {
-            let swap_id: SwapId = deal.id().into(); // The deal id is now used to track a swap
-            log_helper.log_info("Creating new swap.");
+            let swap_id = derive_swap_id(&deal, &buy_key_commitment(&swap_keys.key_manager.0)?);
+            log_helper.log_info(format!("Creating new swap {}.", swap_id));
+            event.send_client_info(
+                source,
+                InfoMsg::TookDeal(TookDeal {
+                    deal_id: deal.id(),
+                    swap_id,
+                    message: "Deal registered".to_string(),
+                }),
+            )?;
             let tsm = transition_to_swapd_launched_tsm(
                 runtime,
                 ConsumedDealRole::Taker,
@@ -1060,6 +1483,8 @@ fn attempt_transition_from_take_deal_to_swapd_launched(
                 deal.clone(),
                 arb_addr,
                 acc_addr,
+                funding_confirmations,
+                syncer_instance,
                 swap_id,
                 log_helper,
             )?;
@@ -1070,6 +1495,7 @@ fn attempt_transition_from_take_deal_to_swapd_launched(
                     deal,
                     status: DealStatus::InProgress,
                     local_trade_role: TradeRole::Taker,
+                    expiry: None,
                 }),
             )?;
             Ok(Some(tsm))
@@ -1090,7 +1516,10 @@ fn attempt_transition_from_take_deal_to_swapd_launched(
                 deal,
                 arb_addr,
                 acc_addr,
+                funding_confirmations,
+                syncer_instance,
                 peerd,
+                source,
             })))
         }
     }
@@ -1104,28 +1533,42 @@ fn transition_to_swapd_launched_tsm(
     deal: Deal,
     target_bitcoin_address: bitcoin::Address,
     target_monero_address: monero::Address,
+    funding_confirmations: Option<u32>,
+    syncer_instance: SyncerInstance,
     swap_id: SwapId,
     log_helper: LogHelper,
 ) -> Result<TradeStateMachine, Error> {
-    let swap_config = runtime.config.get_swap_config(
-        deal.parameters.arbitrating_blockchain.try_into()?,
-        deal.parameters.accordant_blockchain.try_into()?,
+    if let Err(err) = check_syncers_available(
+        &runtime.spawning_services,
+        &runtime.registered_services,
+        [
+            deal.parameters.arbitrating_blockchain,
+            deal.parameters.accordant_blockchain,
+        ],
         deal.parameters.network,
-    )?;
-    let SwapKeys { key_manager, .. } = swap_keys;
-    let arbitrating_syncer_up = syncer_up(
-        &mut runtime.spawning_services,
-        &mut runtime.registered_services,
+        &syncer_instance,
+        &runtime.config,
+    ) {
+        log_helper.log_warn(format!("Not launching the swap: {}", err));
+        return Err(err);
+    }
+    let swap_config = runtime
+        .config
+        .get_deal_swap_config(&deal.parameters, funding_confirmations)?;
+    let SwapKeys {
+        key_manager,
+        monero_deposit,
+        ..
+    } = swap_keys;
+    let arbitrating_syncer_up = runtime.syncer_up(
         deal.parameters.arbitrating_blockchain,
         deal.parameters.network,
-        &runtime.config,
+        &syncer_instance,
     )?;
-    let accordant_syncer_up = syncer_up(
-        &mut runtime.spawning_services,
-        &mut runtime.registered_services,
+    let accordant_syncer_up = runtime.syncer_up(
         deal.parameters.accordant_blockchain,
         deal.parameters.network,
-        &runtime.config,
+        &syncer_instance,
     )?;
     log_helper.log_trace(format!(
         "launching swapd with swap_id: {}",
@@ -1133,12 +1576,28 @@ fn transition_to_swapd_launched_tsm(
     ));
 
     runtime.stats.incr_initiated();
-    launch_swapd(
+    let deadline = runtime.config.get_swap_deadline().map(|secs| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_else(|_| Duration::from_secs(0))
+            .as_secs()
+            + secs
+    });
+    let swapd = launch_swapd(
         consumed_deal_role.clone().into(),
         deal.clone(),
         swap_id,
         swap_config,
+        deadline,
+        runtime.config.get_rebroadcast_interval(),
+        runtime
+            .config
+            .get_monero_unlock_depth(deal.parameters.network, &syncer_instance),
+        runtime.config.count_unconfirmed_funding(),
+        &syncer_instance,
+        &syncer_instance,
     )?;
+    runtime.launched.push(("swapd".to_string(), swapd));
 
     Ok(TradeStateMachine::SwapdLaunched(SwapdLaunched {
         peerd,
@@ -1150,6 +1609,8 @@ fn transition_to_swapd_launched_tsm(
         key_manager,
         target_bitcoin_address,
         target_monero_address,
+        monero_deposit,
+        syncer_instance,
         consumed_deal_role,
         peerd_reconnected: false,
     }))
@@ -1172,11 +1633,17 @@ fn attempt_transition_from_swapd_launched_to_swapd_running(
         mut peerd_reconnected,
         target_bitcoin_address,
         target_monero_address,
+        monero_deposit,
         key_manager,
+        syncer_instance,
     } = swapd_launched;
     match (event.request.clone(), event.source.clone()) {
         (BusMsg::Ctl(CtlMsg::Hello), source)
-            if ServiceId::Syncer(Blockchain::Monero, deal.parameters.network) == source =>
+            if ServiceId::Syncer(
+                Blockchain::Monero,
+                deal.parameters.network,
+                syncer_instance.clone(),
+            ) == source =>
         {
             accordant_syncer_up = Some(source);
         }
@@ -1184,7 +1651,11 @@ fn attempt_transition_from_swapd_launched_to_swapd_running(
             swapd_up = true;
         }
         (BusMsg::Ctl(CtlMsg::Hello), source)
-            if ServiceId::Syncer(Blockchain::Bitcoin, deal.parameters.network) == source =>
+            if ServiceId::Syncer(
+                Blockchain::Bitcoin,
+                deal.parameters.network,
+                syncer_instance.clone(),
+            ) == source =>
         {
             arbitrating_syncer_up = Some(source);
         }
@@ -1230,6 +1701,7 @@ fn attempt_transition_from_swapd_launched_to_swapd_running(
                 key_manager,
                 target_bitcoin_address,
                 target_monero_address,
+                monero_deposit,
                 commit: commit.clone(),
             }),
             ConsumedDealRole::Taker => CtlMsg::TakeSwap(InitTakerSwap {
@@ -1239,12 +1711,16 @@ fn attempt_transition_from_swapd_launched_to_swapd_running(
                 key_manager,
                 target_bitcoin_address,
                 target_monero_address,
+                monero_deposit,
             }),
         };
         if peerd_reconnected {
             event.send_client_ctl(
                 ServiceId::Swap(swap_id),
-                CtlMsg::PeerdReconnected(peerd.clone()),
+                CtlMsg::UpdatePeerd(UpdatePeerd {
+                    swap_id,
+                    new_peerd: peerd.clone(),
+                }),
             )?;
         }
         event.complete_ctl_service(ServiceId::Swap(swap_id), init_swap_req)?;
@@ -1277,8 +1753,10 @@ fn attempt_transition_from_swapd_launched_to_swapd_running(
             key_manager,
             target_bitcoin_address,
             target_monero_address,
+            monero_deposit,
             arbitrating_syncer_up,
             accordant_syncer_up,
+            syncer_instance,
             swapd_up,
             consumed_deal_role,
             peerd_reconnected,
@@ -1299,6 +1777,8 @@ fn attempt_transition_from_restoring_swapd_to_swapd_running(
         expected_counterparty_node_id,
         mut arbitrating_syncer_up,
         mut accordant_syncer_up,
+        arbitrating_syncer_instance,
+        accordant_syncer_instance,
         mut swapd_up,
         mut peerd,
         mut expect_connection,
@@ -1306,7 +1786,11 @@ fn attempt_transition_from_restoring_swapd_to_swapd_running(
     } = restoring_swapd;
     match (event.request.clone(), event.source.clone()) {
         (BusMsg::Ctl(CtlMsg::Hello), source)
-            if ServiceId::Syncer(Blockchain::Monero, deal.parameters.network) == source =>
+            if ServiceId::Syncer(
+                deal.parameters.accordant_blockchain,
+                deal.parameters.network,
+                accordant_syncer_instance.clone(),
+            ) == source =>
         {
             accordant_syncer_up = Some(source);
         }
@@ -1314,7 +1798,11 @@ fn attempt_transition_from_restoring_swapd_to_swapd_running(
             swapd_up = true;
         }
         (BusMsg::Ctl(CtlMsg::Hello), source)
-            if ServiceId::Syncer(Blockchain::Bitcoin, deal.parameters.network) == source =>
+            if ServiceId::Syncer(
+                deal.parameters.arbitrating_blockchain,
+                deal.parameters.network,
+                arbitrating_syncer_instance.clone(),
+            ) == source =>
         {
             arbitrating_syncer_up = Some(source);
         }
@@ -1330,6 +1818,9 @@ fn attempt_transition_from_restoring_swapd_to_swapd_running(
             if Some(node_addr_from_deal(&deal)) == source.node_addr()
                 && trade_role == TradeRole::Taker =>
         {
+            // Do not hold back the restore on an offline counterparty, the connection is
+            // retried periodically once the swap is running
+            log_helper.log_warn("Counterparty is unreachable, restoring the swap without a connection and retrying to connect later");
             runtime.handle_failed_connection(event.endpoints, source)?;
             expect_connection = false;
         }
@@ -1357,7 +1848,13 @@ fn attempt_transition_from_restoring_swapd_to_swapd_running(
         runtime.stats.incr_initiated();
 
         if let Some(peerd) = peerd.clone() {
-            event.send_ctl_service(ServiceId::Swap(swap_id), CtlMsg::PeerdReconnected(peerd))?;
+            event.send_ctl_service(
+                ServiceId::Swap(swap_id),
+                CtlMsg::UpdatePeerd(UpdatePeerd {
+                    swap_id,
+                    new_peerd: peerd,
+                }),
+            )?;
         }
 
         event.complete_ctl_service(
@@ -1367,6 +1864,8 @@ fn attempt_transition_from_restoring_swapd_to_swapd_running(
                 deal: deal.clone(),
                 trade_role,
                 expected_counterparty_node_id,
+                arbitrating_syncer_instance: arbitrating_syncer_instance.0,
+                accordant_syncer_instance: accordant_syncer_instance.0,
             }),
         )?;
 
@@ -1390,6 +1889,8 @@ fn attempt_transition_from_restoring_swapd_to_swapd_running(
             expected_counterparty_node_id,
             arbitrating_syncer_up,
             accordant_syncer_up,
+            arbitrating_syncer_instance,
+            accordant_syncer_instance,
             swapd_up,
             peerd,
             expect_connection,
@@ -1423,9 +1924,15 @@ fn attempt_transition_to_end(
         {
             let swap_service_id = ServiceId::Swap(swap_id);
             log_helper.log_debug("Letting swapd know of peer reconnection.");
-            event.complete_ctl_service(swap_service_id, CtlMsg::PeerdReconnected(source))?;
+            event.complete_ctl_service(
+                swap_service_id,
+                CtlMsg::UpdatePeerd(UpdatePeerd {
+                    swap_id,
+                    new_peerd: source.clone(),
+                }),
+            )?;
             Ok(Some(TradeStateMachine::SwapdRunning(SwapdRunning {
-                peerd,
+                peerd: Some(source),
                 deal,
                 swap_id,
                 arbitrating_syncer,
@@ -1444,9 +1951,15 @@ fn attempt_transition_to_end(
         {
             let swap_service_id = ServiceId::Swap(swap_id);
             log_helper.log_debug("Letting swapd know of peer reconnection.");
-            event.complete_ctl_service(swap_service_id, CtlMsg::PeerdReconnected(source))?;
+            event.complete_ctl_service(
+                swap_service_id,
+                CtlMsg::UpdatePeerd(UpdatePeerd {
+                    swap_id,
+                    new_peerd: source.clone(),
+                }),
+            )?;
             Ok(Some(TradeStateMachine::SwapdRunning(SwapdRunning {
-                peerd,
+                peerd: Some(source),
                 deal,
                 swap_id,
                 arbitrating_syncer,
@@ -1498,6 +2011,37 @@ fn attempt_transition_to_end(
                             }
                         }.unwrap();
 
+                    if let Err(msg) = check_bitcoin_auto_funding_balance(
+                        &bitcoin_rpc,
+                        amount,
+                        runtime.config.auto_funding_counts_unconfirmed(),
+                    ) {
+                        log_helper.log_error(format!(
+                            "Not auto-funding Bitcoin, use `swap-cli needs-funding Bitcoin` to retrieve address and amount: {}",
+                            msg
+                        ));
+                        runtime.report_swap_failure(
+                            event.endpoints,
+                            swap_id,
+                            Failure {
+                                code: FailureCode::InsufficientFunds,
+                                info: msg,
+                            },
+                        );
+                        return Ok(Some(TradeStateMachine::SwapdRunning(SwapdRunning {
+                            peerd,
+                            deal,
+                            swap_id,
+                            arbitrating_syncer,
+                            accordant_syncer,
+                            funding_info: Some(info),
+                            auto_funded: false,
+                            clients_awaiting_connect_result,
+                            trade_role,
+                            expected_counterparty_node_id,
+                        })));
+                    }
+
                     match bitcoin_rpc
                         .send_to_address(address, amount, None, None, None, None, None, None)
                     {
@@ -1571,12 +2115,42 @@ fn attempt_transition_to_end(
                         .enable_all()
                         .build()
                         .unwrap();
+                    let host = auto_fund_config.monero_rpc_wallet;
+                    let wallet = monero_rpc::RpcClientBuilder::new()
+                        .build(host)
+                        .expect("client builder failed, cannot recover from bad configuration")
+                        .wallet();
+                    if let Err(msg) = rt.block_on(check_monero_auto_funding_balance(
+                        &wallet,
+                        amount,
+                        runtime.config.auto_funding_counts_unconfirmed(),
+                    )) {
+                        log_helper.log_error(format!(
+                            "Not auto-funding Monero, use `swap-cli needs-funding Monero` to retrieve address and amount: {}",
+                            msg
+                        ));
+                        runtime.report_swap_failure(
+                            event.endpoints,
+                            swap_id,
+                            Failure {
+                                code: FailureCode::InsufficientFunds,
+                                info: msg,
+                            },
+                        );
+                        return Ok(Some(TradeStateMachine::SwapdRunning(SwapdRunning {
+                            peerd,
+                            deal,
+                            swap_id,
+                            arbitrating_syncer,
+                            accordant_syncer,
+                            funding_info: Some(info),
+                            auto_funded: false,
+                            clients_awaiting_connect_result,
+                            trade_role,
+                            expected_counterparty_node_id,
+                        })));
+                    }
                     rt.block_on(async {
-                        let host = auto_fund_config.monero_rpc_wallet;
-                        let wallet = monero_rpc::RpcClientBuilder::new()
-                            .build(host)
-                            .expect("client builder failed, cannot recover from bad configuration")
-                            .wallet();
                         let options = monero_rpc::TransferOptions::default();
 
                         let mut auto_funded = false;
@@ -1672,19 +2246,30 @@ fn attempt_transition_to_end(
             })))
         }
 
-        (BusMsg::Ctl(CtlMsg::Connect(connect_swap_id)), _) if connect_swap_id == swap_id => {
+        (BusMsg::Ctl(CtlMsg::Connect(connect_swap_id)), source) if connect_swap_id == swap_id => {
+            // Farcasterd periodically issues a connect for restored swaps whose counterparty
+            // is unreachable, there is no client to reply to in that case
+            let retrying = source == ServiceId::Farcasterd;
             let mut new_peerd = peerd.clone();
             if let Some(peerd) = peerd {
-                event.complete_client_ctl(CtlMsg::Failure(Failure {
-                    code: FailureCode::Unknown,
-                    info: format!(
-                        "The swap already has a dedicated connection daemon {}",
-                        peerd
-                    ),
-                }))?;
+                if !retrying {
+                    event.complete_client_ctl(CtlMsg::Failure(Failure {
+                        code: FailureCode::Unknown,
+                        info: format!(
+                            "The swap already has a dedicated connection daemon {}",
+                            peerd
+                        ),
+                    }))?;
+                }
             } else {
                 let peer_node_addr = node_addr_from_deal(&deal);
                 match runtime.connect_peer(&peer_node_addr) {
+                    Err(err) if retrying => {
+                        log_helper.log_warn(format!(
+                            "Failed to reconnect to the counterparty, will retry: {}",
+                            err
+                        ));
+                    }
                     Err(err) => {
                         event.complete_client_ctl(CtlMsg::Failure(Failure {
                             code: FailureCode::Unknown,
@@ -1693,7 +2278,7 @@ fn attempt_transition_to_end(
                     }
                     Ok((connected, peerd)) => {
                         new_peerd = Some(peerd);
-                        if connected {
+                        if connected && !retrying {
                             event.complete_client_ctl(CtlMsg::ConnectSuccess)?;
                         }
                     }
@@ -1721,6 +2306,13 @@ fn attempt_transition_to_end(
                 event.send_client_ctl(client, CtlMsg::ConnectSuccess)?;
             }
             runtime.handle_new_connection(source.clone());
+            event.send_ctl_service(
+                ServiceId::Swap(swap_id),
+                CtlMsg::UpdatePeerd(UpdatePeerd {
+                    swap_id,
+                    new_peerd: source.clone(),
+                }),
+            )?;
             Ok(Some(TradeStateMachine::SwapdRunning(SwapdRunning {
                 peerd: Some(source),
                 deal,
@@ -1743,14 +2335,17 @@ fn attempt_transition_to_end(
                 event.send_client_ctl(
                     client,
                     CtlMsg::Failure(Failure {
-                        code: FailureCode::Unknown,
+                        code: FailureCode::PeerUnreachable,
                         info: format!("Failed to connect to remote peer: {}", source),
                     }),
                 )?;
             }
-            runtime.handle_failed_connection(event.endpoints, source.clone())?;
+            runtime.handle_failed_connection(event.endpoints, source)?;
+            if trade_role == TradeRole::Taker {
+                log_helper.log_warn("Counterparty is unreachable, will retry to connect");
+            }
             Ok(Some(TradeStateMachine::SwapdRunning(SwapdRunning {
-                peerd: Some(source),
+                peerd: None,
                 deal,
                 swap_id,
                 arbitrating_syncer,
@@ -1800,10 +2395,33 @@ fn attempt_transition_to_end(
                     deal,
                     status: DealStatus::Ended(outcome.clone()),
                     local_trade_role: trade_role,
+                    expiry: None,
                 }),
             )?;
             runtime.clean_up_after_swap(&swap_id, event.endpoints)?;
+            runtime.end_swap_progress(&swap_id, &outcome, event.endpoints);
             runtime.stats.incr_outcome(&outcome);
+            if let Some(webhook_config) = runtime.config.get_webhook() {
+                webhook::notify(
+                    webhook_config,
+                    SwapEndNotification {
+                        swap_id,
+                        outcome: outcome.clone(),
+                        trade_role,
+                        arbitrating_amount: deal.parameters.arbitrating_amount,
+                        accordant_amount: deal.parameters.accordant_amount,
+                        counterparty: peerd
+                            .as_ref()
+                            .and_then(|peerd| peerd.node_addr())
+                            .map(|addr| addr.id)
+                            .or(expected_counterparty_node_id),
+                        timestamp: SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .unwrap_or_default()
+                            .as_secs(),
+                    },
+                );
+            }
             match outcome {
                 Outcome::SuccessSwap => {
                     log_helper.log_debug(format!("Success on swap {}", swap_id));
@@ -1850,9 +2468,239 @@ fn attempt_transition_to_end(
     }
 }
 
+/// Virtual size assumed for the auto-funding Bitcoin transaction to estimate its fee, a segwit
+/// transaction spending two inputs to the funding and a change output
+const AUTO_FUNDING_TX_VSIZE: u64 = 208;
+
+/// Fee rate used when the Bitcoin node cannot estimate one, e.g. on regtest, in sat/kvB
+const AUTO_FUNDING_FALLBACK_FEE_RATE: u64 = 1000;
+
+/// Check that the auto-funding Bitcoin wallet holds the funding amount plus the estimated network
+/// fee of the funding transaction
+fn check_bitcoin_auto_funding_balance(
+    bitcoin_rpc: &bitcoincore_rpc::Client,
+    amount: bitcoin::Amount,
+    count_unconfirmed: bool,
+) -> Result<(), String> {
+    use bitcoincore_rpc::RpcApi;
+
+    let balances = bitcoin_rpc.get_balances().map_err(|err| {
+        format!(
+            "Failed to retrieve the auto-funding wallet balance: {}",
+            err
+        )
+    })?;
+    let mut available = balances.mine.trusted;
+    if count_unconfirmed {
+        available += balances.mine.untrusted_pending;
+    }
+    let fee_rate = bitcoin_rpc
+        .estimate_smart_fee(2, None)
+        .ok()
+        .and_then(|estimation| estimation.fee_rate)
+        .map_or(AUTO_FUNDING_FALLBACK_FEE_RATE, |fee_rate| fee_rate.as_sat());
+    let fee = bitcoin::Amount::from_sat(fee_rate * AUTO_FUNDING_TX_VSIZE / 1000);
+    if available < amount + fee {
+        return Err(format!(
+            "the auto-funding wallet holds {}{}, the swap requires {} plus about {} of network fees",
+            available,
+            if count_unconfirmed {
+                ""
+            } else {
+                " confirmed"
+            },
+            amount,
+            fee
+        ));
+    }
+    Ok(())
+}
+
+/// Check that the auto-funding Monero wallet holds more than the funding amount, the transfer
+/// fee is only known once the transaction is built
+async fn check_monero_auto_funding_balance(
+    wallet: &monero_rpc::WalletClient,
+    amount: monero::Amount,
+    count_unconfirmed: bool,
+) -> Result<(), String> {
+    let balance = wallet.get_balance(0, None).await.map_err(|err| {
+        format!(
+            "Failed to retrieve the auto-funding wallet balance: {}",
+            err
+        )
+    })?;
+    let available = if count_unconfirmed {
+        balance.balance
+    } else {
+        balance.unlocked_balance
+    };
+    if available <= amount {
+        return Err(format!(
+            "the auto-funding wallet holds {}{}, the swap requires {} plus the network fees",
+            available,
+            if count_unconfirmed { "" } else { " unlocked" },
+            amount
+        ));
+    }
+    Ok(())
+}
+
+/// Run the checks taking the deal would go through that need no network access: services and
+/// keys readiness, syncers and swap configuration for the deal network. Returns the probe of
+/// the maker reachability and of the auto-funding wallet balance, or the first blocker.
+fn take_deal_preflight(
+    runtime: &Runtime,
+    deal: &Deal,
+    syncer_instance: &SyncerInstance,
+) -> Result<TakeDealProbe, Error> {
+    runtime.services_ready()?;
+    runtime.peer_keys_ready()?;
+    let peer_node_addr = node_addr_from_deal(deal);
+    if runtime.is_peer_banned(&peer_node_addr.id) {
+        return Err(Error::Farcaster(format!(
+            "The maker {} is banned",
+            peer_node_addr.id
+        )));
+    }
+    check_syncers_available(
+        &runtime.spawning_services,
+        &runtime.registered_services,
+        [
+            deal.parameters.arbitrating_blockchain,
+            deal.parameters.accordant_blockchain,
+        ],
+        deal.parameters.network,
+        syncer_instance,
+        &runtime.config,
+    )?;
+    runtime.config.get_swap_config(
+        deal.parameters.arbitrating_blockchain.try_into()?,
+        deal.parameters.accordant_blockchain.try_into()?,
+        deal.parameters.network,
+    )?;
+
+    let connected = runtime
+        .registered_services
+        .iter()
+        .chain(runtime.spawning_services.iter())
+        .any(|service| service.node_addr().map(|addr| addr.id) == Some(peer_node_addr.id));
+    Ok(TakeDealProbe {
+        deal: deal.clone(),
+        peer_node_addr,
+        connected,
+        auto_funding: runtime
+            .config
+            .get_auto_funding_config(deal.parameters.network),
+        count_unconfirmed: runtime.config.auto_funding_counts_unconfirmed(),
+    })
+}
+
+/// Checks of a dry run take that need network access, run off the bus handler
+struct TakeDealProbe {
+    deal: Deal,
+    peer_node_addr: NodeAddr,
+    connected: bool,
+    auto_funding: Option<AutoFundingServers>,
+    count_unconfirmed: bool,
+}
+
+impl TakeDealProbe {
+    /// Check the reachability of the maker and the funding of the swap. Returns a description
+    /// of how the swap would be started, or the first blocker.
+    fn run(self) -> Result<String, String> {
+        let connection = if self.connected {
+            "the existing connection"
+        } else {
+            let socket_addr = SocketAddr::try_from(self.peer_node_addr.addr).map_err(|err| {
+                format!(
+                    "Cannot check the reachability of the maker at {}: {}",
+                    self.peer_node_addr.addr, err
+                )
+            })?;
+            TcpStream::connect_timeout(&socket_addr, DRY_RUN_CONNECT_TIMEOUT).map_err(|err| {
+                format!(
+                    "The maker is unreachable at {}: {}",
+                    self.peer_node_addr.addr, err
+                )
+            })?;
+            "a new connection"
+        };
+
+        // the taker funds the leg of its swap role
+        let parameters = &self.deal.parameters;
+        let funding = match (&self.auto_funding, parameters.maker_role.other()) {
+            (None, _) => "funded manually".to_string(),
+            (Some(servers), SwapRole::Bob) => {
+                let bitcoin_rpc = auto_funding_bitcoin_rpc(servers)?;
+                check_bitcoin_auto_funding_balance(
+                    &bitcoin_rpc,
+                    parameters.arbitrating_amount,
+                    self.count_unconfirmed,
+                )?;
+                format!("auto-funded with {}", parameters.arbitrating_amount)
+            }
+            (Some(servers), SwapRole::Alice) => {
+                let wallet = monero_rpc::RpcClientBuilder::new()
+                    .build(servers.monero_rpc_wallet.clone())
+                    .map_err(|err| format!("Failed to build the Monero wallet client: {}", err))?
+                    .wallet();
+                tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .map_err(|err| err.to_string())?
+                    .block_on(check_monero_auto_funding_balance(
+                        &wallet,
+                        parameters.accordant_amount,
+                        self.count_unconfirmed,
+                    ))?;
+                format!("auto-funded with {}", parameters.accordant_amount)
+            }
+        };
+
+        Ok(format!(
+            "deal {} can be taken, the swap would be run with {} through {} and {}",
+            self.deal.id(),
+            self.peer_node_addr,
+            connection,
+            funding
+        ))
+    }
+}
+
+/// Client of the Bitcoin node of the auto-funding wallet
+fn auto_funding_bitcoin_rpc(
+    servers: &AutoFundingServers,
+) -> Result<bitcoincore_rpc::Client, String> {
+    use bitcoincore_rpc::{Auth, Client};
+
+    let auth = match (
+        &servers.bitcoin_cookie_path,
+        &servers.bitcoin_rpc_user,
+        &servers.bitcoin_rpc_pass,
+    ) {
+        (Some(cookie), _, _) => Auth::CookieFile(shellexpand::tilde(cookie).to_string().into()),
+        (None, Some(user), Some(pass)) => Auth::UserPass(user.clone(), pass.clone()),
+        _ => {
+            return Err("Couldn't instantiate Bitcoin RPC - provide either `bitcoin_cookie_path` or `bitcoin_rpc_user` AND `bitcoin_rpc_pass` configuration parameters".to_string());
+        }
+    };
+    Client::new(&servers.bitcoin_rpc, auth).map_err(|err| err.to_string())
+}
+
 fn node_addr_from_deal(deal: &Deal) -> NodeAddr {
     NodeAddr {
         id: NodeId::from(deal.node_id), // node_id is bitcoin::Pubkey
         addr: deal.peer_address,        // peer_address is InetSocketAddr
     }
 }
+
+/// Whether a deal expiring at the given unix timestamp is past its expiry and grace window
+fn deal_expired(expiry: Option<u64>) -> bool {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_else(|_| Duration::from_secs(0))
+        .as_secs();
+    expiry.map_or(false, |expiry| {
+        now > expiry.saturating_add(DEAL_EXPIRY_GRACE.as_secs())
+    })
+}
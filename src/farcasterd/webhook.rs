@@ -0,0 +1,112 @@
+// Copyright 2020-2022 Farcaster Devs & LNP/BP Standards Association
+//
+// Use of this source code is governed by an MIT-style
+// license that can be found in the LICENSE file or at
+// https://opensource.org/licenses/MIT.
+
+//! Push notifications of ended swaps to the webhook configured in farcasterd's config. Each
+//! notification is posted as JSON from a dedicated thread, retrying with an exponential backoff
+//! on failures and non-2xx responses.
+
+use std::thread::spawn;
+use std::time::Duration;
+
+use farcaster_core::{role::TradeRole, swap::SwapId};
+use internet2::addr::NodeId;
+use serde_with::DisplayFromStr;
+
+use crate::bus::Outcome;
+use crate::config::WebhookConfig;
+
+/// Default number of attempts to deliver a notification
+pub const WEBHOOK_MAX_ATTEMPTS: u8 = 3;
+/// Delay before the first retry, doubled on every following attempt
+const WEBHOOK_RETRY_BACKOFF: Duration = Duration::from_secs(2);
+/// Timeout of a single delivery attempt
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The JSON payload posted to the webhook when a swap ends
+#[serde_as]
+#[derive(Clone, Debug, Serialize)]
+#[serde(crate = "serde_crate")]
+pub struct SwapEndNotification {
+    pub swap_id: SwapId,
+    pub outcome: Outcome,
+    pub trade_role: TradeRole,
+    #[serde_as(as = "DisplayFromStr")]
+    pub arbitrating_amount: bitcoin::Amount,
+    #[serde_as(as = "DisplayFromStr")]
+    pub accordant_amount: monero::Amount,
+    #[serde_as(as = "Option<DisplayFromStr>")]
+    pub counterparty: Option<NodeId>,
+    /// Unix timestamp of the swap end
+    pub timestamp: u64,
+}
+
+/// Post the notification to the webhook without blocking the caller
+pub fn notify(config: WebhookConfig, notification: SwapEndNotification) {
+    spawn(move || {
+        let swap_id = notification.swap_id;
+        let body = match serde_json::to_string(&notification) {
+            Ok(body) => body,
+            Err(err) => {
+                error!(
+                    "{} | Failed to encode the webhook notification: {}",
+                    swap_id, err
+                );
+                return;
+            }
+        };
+        let rt = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(rt) => rt,
+            Err(err) => {
+                error!("{} | Failed to start the webhook runtime: {}", swap_id, err);
+                return;
+            }
+        };
+        rt.block_on(post_with_retries(config, swap_id, body));
+    });
+}
+
+async fn post_with_retries(config: WebhookConfig, swap_id: SwapId, body: String) {
+    let client = reqwest::Client::new();
+    let max_attempts = config.max_attempts.unwrap_or(WEBHOOK_MAX_ATTEMPTS).max(1);
+    let mut backoff = WEBHOOK_RETRY_BACKOFF;
+    for attempt in 1..=max_attempts {
+        let res = client
+            .post(&config.url)
+            .header("Content-Type", "application/json")
+            .timeout(WEBHOOK_TIMEOUT)
+            .body(body.clone())
+            .send()
+            .await;
+        match res {
+            Ok(resp) if resp.status().is_success() => {
+                debug!("{} | Webhook notified of the swap end", swap_id);
+                return;
+            }
+            Ok(resp) => warn!(
+                "{} | Webhook responded with {} (attempt {}/{})",
+                swap_id,
+                resp.status(),
+                attempt,
+                max_attempts
+            ),
+            Err(err) => warn!(
+                "{} | Failed to reach the webhook: {} (attempt {}/{})",
+                swap_id, err, attempt, max_attempts
+            ),
+        }
+        if attempt < max_attempts {
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+    error!(
+        "{} | Giving up notifying the webhook of the swap end after {} attempts",
+        swap_id, max_attempts
+    );
+}
@@ -196,6 +196,7 @@ impl From<DealStatus> for farcaster::DealStatus {
             DealStatus::Open => farcaster::DealStatus::DealOpen,
             DealStatus::InProgress => farcaster::DealStatus::DealInProgress,
             DealStatus::Revoked => farcaster::DealStatus::DealRevoked,
+            DealStatus::Expired => farcaster::DealStatus::DealExpired,
             DealStatus::Ended(outcome) => match outcome {
                 Outcome::SuccessSwap => farcaster::DealStatus::DealEndedSuccessSwap,
                 Outcome::FailureAbort => farcaster::DealStatus::DealEndedFailureAbort,
@@ -433,8 +434,8 @@ impl Farcaster for FarcasterService {
             .map_err(|_| Status::invalid_argument("Invalid or malformed swap id".to_string()))?;
         let oneshot_rx = self
             .process_request(BusMsg::Bridge(BridgeMsg::Info {
-                request: InfoMsg::GetInfo,
-                service_id: ServiceId::Swap(swap_id),
+                request: InfoMsg::GetSwapInfo(swap_id),
+                service_id: ServiceId::Farcasterd,
             }))
             .await?;
         match oneshot_rx.await {
@@ -449,6 +450,7 @@ impl Farcaster for FarcasterService {
                 local_trade_role,
                 local_swap_role,
                 connected_counterparty_node_id,
+                monero_deposit_address,
             }))) => {
                 let reply = SwapInfoResponse {
                     id,
@@ -469,6 +471,9 @@ impl Farcaster for FarcasterService {
                         .map(|n| n.to_string())
                         .unwrap_or_else(|| "".to_string()),
                     state: state.to_string(),
+                    monero_deposit_address: monero_deposit_address
+                        .map(|address| address.to_string())
+                        .unwrap_or_else(|| "".to_string()),
                 };
                 Ok(GrpcResponse::new(reply))
             }
@@ -542,8 +547,8 @@ impl Farcaster for FarcasterService {
             id,
             deal: string_deal,
         } = request.into_inner();
-        let deal =
-            Deal::from_str(&string_deal).map_err(|_| Status::invalid_argument("deal malformed"))?;
+        let deal = Deal::from_str(string_deal.trim())
+            .map_err(|err| Status::invalid_argument(format!("deal malformed: {}", err)))?;
 
         let reply = DealInfoResponse {
             id,
@@ -820,6 +825,9 @@ impl Farcaster for FarcasterService {
             public_addr,
             arbitrating_addr,
             accordant_addr,
+            funding_confirmations: None,
+            syncer_instance: None,
+            expiry: None,
         };
 
         let oneshot_rx = self
@@ -848,7 +856,8 @@ impl Farcaster for FarcasterService {
         debug!("Received a grpc revoke deal request: {:?}", request);
         let RevokeDealRequest { id, deal: str_deal } = request.into_inner();
 
-        let deal = Deal::from_str(&str_deal).map_err(|_| Status::invalid_argument("deal"))?;
+        let deal = Deal::from_str(str_deal.trim())
+            .map_err(|err| Status::invalid_argument(format!("deal malformed: {}", err)))?;
 
         let oneshot_rx = self
             .process_request(BusMsg::Bridge(BridgeMsg::Ctl {
@@ -1005,7 +1014,7 @@ impl Farcaster for FarcasterService {
 
         let oneshot_rx = self
             .process_request(BusMsg::Bridge(BridgeMsg::Info {
-                request: InfoMsg::NeedsFunding(blockchain),
+                request: InfoMsg::NeedsFunding(Some(blockchain)),
                 service_id: ServiceId::Farcasterd,
             }))
             .await?;
@@ -1141,6 +1150,7 @@ impl Farcaster for FarcasterService {
                                     source_address,
                                     source_secret_key: secret_key_info.secret_key,
                                     destination_address,
+                                    fee_sat_per_kvb: None,
                                 },
                             )),
                             service_id: ServiceId::Farcasterd,
@@ -1216,7 +1226,8 @@ impl Farcaster for FarcasterService {
             .map_err(|_| Status::invalid_argument("arbitrating address"))?;
         let monero_address = monero::Address::from_str(&str_monero_address)
             .map_err(|_| Status::invalid_argument("accordant_address"))?;
-        let deal = Deal::from_str(&str_deal).map_err(|_| Status::invalid_argument("deal"))?;
+        let deal = Deal::from_str(str_deal.trim())
+            .map_err(|err| Status::invalid_argument(format!("deal malformed: {}", err)))?;
 
         let oneshot_rx = self
             .process_request(BusMsg::Bridge(BridgeMsg::Ctl {
@@ -1224,14 +1235,21 @@ impl Farcaster for FarcasterService {
                     deal,
                     bitcoin_address,
                     monero_address,
+                    funding_confirmations: None,
+                    syncer_instance: None,
+                    dry_run: false,
+                    force: false,
                 }),
                 service_id: ServiceId::Farcasterd,
             }))
             .await?;
 
         match oneshot_rx.await {
-            Ok(BusMsg::Info(InfoMsg::TookDeal(_))) => {
-                let reply = farcaster::TakeResponse { id };
+            Ok(BusMsg::Info(InfoMsg::TookDeal(took_deal))) => {
+                let reply = farcaster::TakeResponse {
+                    id,
+                    swap_id: took_deal.swap_id.to_string(),
+                };
                 Ok(GrpcResponse::new(reply))
             }
             res => process_error_response(res),
@@ -1518,7 +1536,7 @@ impl esb::Handler<ServiceBus> for Runtime {
 impl Runtime {
     fn handle_ctl(
         &mut self,
-        _endpoints: &mut Endpoints,
+        endpoints: &mut Endpoints,
         source: ServiceId,
         request: CtlMsg,
     ) -> Result<(), Error> {
@@ -1527,6 +1545,24 @@ impl Runtime {
                 debug!("Received Hello from {}", source);
             }
 
+            CtlMsg::Heartbeat if source == ServiceId::Farcasterd => {
+                endpoints.send_to(
+                    ServiceBus::Ctl,
+                    self.identity.clone(),
+                    ServiceId::Farcasterd,
+                    BusMsg::Ctl(CtlMsg::Heartbeat),
+                )?;
+            }
+
+            CtlMsg::Terminate if source == ServiceId::Farcasterd => {
+                info!("Terminating {}", self.identity);
+                std::process::exit(0);
+            }
+
+            CtlMsg::SetLogFilter(filter) if source == ServiceId::Farcasterd => {
+                crate::logger::set_filter(filter.as_deref());
+            }
+
             req => {
                 if let ServiceId::GrpcdClient(id) = source {
                     self.tx_response
@@ -46,6 +46,8 @@ pub mod config;
 pub mod error;
 pub mod event;
 #[cfg(feature = "shell")]
+pub mod logger;
+#[cfg(feature = "shell")]
 pub mod opts;
 
 #[cfg(feature = "node")]
@@ -69,7 +71,12 @@ pub mod walletd;
 pub use crate::config::Config;
 #[cfg(feature = "_rpc")]
 pub use crate::service::ServiceConfig;
-pub use chains::{AccordantBlockchain, ArbitratingBlockchain};
+pub use chains::{
+    is_monero_address_on, monero_address_network, monero_network, AccordantBlockchain,
+    ArbitratingBlockchain,
+};
 pub use error::Error;
 #[cfg(feature = "_rpc")]
-pub use service::{CtlServer, Endpoints, LogStyle, Service, ServiceId, TryToServiceId};
+pub use service::{
+    CtlServer, Endpoints, LogStyle, Service, ServiceId, SyncerInstance, TryToServiceId, VERSION,
+};
@@ -0,0 +1,247 @@
+// Copyright 2020-2022 Farcaster Devs & LNP/BP Standards Association
+//
+// Use of this source code is governed by an MIT-style
+// license that can be found in the LICENSE file or at
+// https://opensource.org/licenses/MIT.
+
+//! Log output of the binaries: the default human-readable colorized lines, or JSON lines for log
+//! aggregators carrying the level, the service and the swap the record belongs to.
+//!
+//! The records of a swap runtime are attributable to its swap: text lines not mentioning the swap
+//! id yet, e.g. those emitted by the service bus, are prefixed with it, and JSON lines carry it.
+//!
+//! Records are filtered with the `RUST_LOG` environment variable, or with the filter set at
+//! runtime with [`set_filter`], e.g. on a reload of the configuration.
+
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use colored::Colorize;
+use env_logger::fmt::Formatter;
+use farcaster_core::swap::SwapId;
+use lazy_static::lazy_static;
+use log::{Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+
+/// Format of the log output
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Display, Serialize, Deserialize)]
+#[serde(crate = "serde_crate", rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable colorized lines
+    #[display("text")]
+    Text,
+    /// One JSON object per line
+    #[display("json")]
+    Json,
+}
+
+impl Default for LogFormat {
+    fn default() -> Self {
+        LogFormat::Text
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("Unknown log format {}, expected text or json", s)),
+        }
+    }
+}
+
+/// Filter of the log records when `RUST_LOG` is not set
+const DEFAULT_FILTER: &str = "error,farcaster_node=info";
+
+/// Context attached to the JSON log records of the process
+#[derive(Default)]
+struct LogContext {
+    format: LogFormat,
+    is_test: bool,
+    // filter set at runtime, replacing the one of the environment
+    filter: Option<String>,
+    service: Option<String>,
+    swap_id: Option<SwapId>,
+}
+
+lazy_static! {
+    static ref LOG_CONTEXT: RwLock<LogContext> = RwLock::new(LogContext::default());
+    // filtering and formatting logger the records go through, rebuilt when the filter changes
+    static ref INNER_LOGGER: RwLock<Option<env_logger::Logger>> = RwLock::new(None);
+}
+
+/// Initialize the logger of the process with the given format, filtering records with the
+/// `RUST_LOG` environment variable
+pub fn init(format: LogFormat, is_test: bool) {
+    if format == LogFormat::Json {
+        // log messages are styled with terminal escape codes, useless in JSON
+        colored::control::set_override(false);
+    }
+    let inner = build_logger(format, is_test, None);
+    let max_level = inner.filter();
+    if let Ok(mut logger) = INNER_LOGGER.write() {
+        *logger = Some(inner);
+    }
+    log::set_boxed_logger(Box::new(ContextLogger))
+        .map(|()| log::set_max_level(max_level))
+        .expect("Failed to initialize loggger!");
+    if let Ok(mut context) = LOG_CONTEXT.write() {
+        context.format = format;
+        context.is_test = is_test;
+    }
+}
+
+fn build_logger(format: LogFormat, is_test: bool, filter: Option<&str>) -> env_logger::Logger {
+    let mut builder = match filter {
+        Some(filter) => {
+            let mut builder = env_logger::Builder::new();
+            builder.parse_filters(filter);
+            builder
+        }
+        None => {
+            env_logger::Builder::from_env(env_logger::Env::new().default_filter_or(DEFAULT_FILTER))
+        }
+    };
+    builder.is_test(is_test);
+    if format == LogFormat::Json {
+        builder.format(format_json);
+    }
+    builder.build()
+}
+
+/// Filter the log records of the process with the given filter, in the `RUST_LOG` syntax, e.g.
+/// `debug` or `error,farcaster_node=debug`. None falls back to the `RUST_LOG` environment
+/// variable.
+pub fn set_filter(filter: Option<&str>) {
+    let (format, is_test) = match LOG_CONTEXT.write() {
+        Ok(mut context) => {
+            context.filter = filter.map(ToString::to_string);
+            (context.format, context.is_test)
+        }
+        Err(_) => return,
+    };
+    let inner = build_logger(format, is_test, filter);
+    log::set_max_level(inner.filter());
+    if let Ok(mut logger) = INNER_LOGGER.write() {
+        *logger = Some(inner);
+    }
+}
+
+/// Filter set at runtime with [`set_filter`], forwarded to the launched services as `RUST_LOG`
+pub fn log_filter() -> Option<String> {
+    LOG_CONTEXT
+        .read()
+        .ok()
+        .and_then(|context| context.filter.clone())
+}
+
+/// Logger tagging the text records of a swap runtime with its swap id
+struct ContextLogger;
+
+impl Log for ContextLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        match INNER_LOGGER.read() {
+            Ok(inner) => inner
+                .as_ref()
+                .map_or(false, |inner| inner.enabled(metadata)),
+            Err(_) => false,
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        let inner = match INNER_LOGGER.read() {
+            Ok(inner) => inner,
+            Err(_) => return,
+        };
+        let inner = match inner.as_ref() {
+            Some(inner) if inner.matches(record) => inner,
+            _ => return,
+        };
+        match untagged_swap_id(record) {
+            Some(swap_id) => inner.log(
+                &record
+                    .to_builder()
+                    .args(format_args!(
+                        "{} | {}",
+                        swap_id.to_string().bright_blue().italic(),
+                        record.args()
+                    ))
+                    .build(),
+            ),
+            None => inner.log(record),
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(inner) = INNER_LOGGER.read() {
+            if let Some(inner) = inner.as_ref() {
+                inner.flush()
+            }
+        }
+    }
+}
+
+/// Swap id of the process if the text record does not mention it yet, swap logging helpers
+/// already prefix their messages with it
+fn untagged_swap_id(record: &Record) -> Option<SwapId> {
+    let swap_id = match LOG_CONTEXT.read() {
+        Ok(context) if context.format == LogFormat::Text => context.swap_id?,
+        _ => return None,
+    };
+    if record.args().to_string().contains(&swap_id.to_string()) {
+        None
+    } else {
+        Some(swap_id)
+    }
+}
+
+/// Format of the log output the process was initialized with, forwarded to the launched services
+pub fn log_format() -> LogFormat {
+    LOG_CONTEXT
+        .read()
+        .map(|context| context.format)
+        .unwrap_or_default()
+}
+
+/// Set the service, and the swap it runs if any, the log records of the process are attributed to
+pub fn set_context(service: impl ToString, swap_id: Option<SwapId>) {
+    if let Ok(mut context) = LOG_CONTEXT.write() {
+        context.service = Some(service.to_string());
+        context.swap_id = swap_id;
+    }
+}
+
+fn format_json(buf: &mut Formatter, record: &Record) -> io::Result<()> {
+    let (service, swap_id) = LOG_CONTEXT
+        .read()
+        .map(|context| (context.service.clone(), context.swap_id))
+        .unwrap_or_default();
+    let line = serde_json::json!({
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "level": record.level().to_string(),
+        "target": record.target(),
+        "service": service,
+        "swap_id": swap_id.map(|swap_id| swap_id.to_string()),
+        "message": record.args().to_string(),
+    });
+    writeln!(buf, "{}", line)
+}
+
+#[test]
+fn log_format_parses_case_insensitively() {
+    assert_eq!(LogFormat::from_str("json"), Ok(LogFormat::Json));
+    assert_eq!(LogFormat::from_str("TEXT"), Ok(LogFormat::Text));
+    assert!(LogFormat::from_str("yaml").is_err());
+}
+
+#[test]
+fn log_filter_sets_the_max_level() {
+    let inner = build_logger(LogFormat::Text, true, Some("debug"));
+    assert_eq!(inner.filter(), log::LevelFilter::Debug);
+    let inner = build_logger(LogFormat::Text, true, Some("error,farcaster_node=trace"));
+    assert_eq!(inner.filter(), log::LevelFilter::Trace);
+}
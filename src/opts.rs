@@ -11,6 +11,9 @@ use std::str::FromStr;
 use std::{fs, io};
 
 use internet2::addr::ServiceAddr;
+use serde::{Deserialize, Serialize};
+
+use crate::logger::{self, LogFormat};
 
 #[cfg(target_os = "linux")]
 pub const FARCASTER_DATA_DIR: &str = "~/.farcaster";
@@ -30,6 +33,11 @@ pub const FARCASTER_CTL_SOCKET_NAME: &str = "{data_dir}/ctl";
 pub const FARCASTER_INFO_SOCKET_NAME: &str = "{data_dir}/info";
 pub const FARCASTER_SYNC_SOCKET_NAME: &str = "{data_dir}/sync";
 
+pub const FARCASTER_MSG_SOCKET_PORT: u16 = 7081;
+pub const FARCASTER_CTL_SOCKET_PORT: u16 = 7082;
+pub const FARCASTER_INFO_SOCKET_PORT: u16 = 7083;
+pub const FARCASTER_SYNC_SOCKET_PORT: u16 = 7084;
+
 pub const FARCASTER_KEY_FILE: &str = "{data_dir}/key.dat";
 
 /// Shared options used by different binaries
@@ -119,6 +127,103 @@ pub struct Opts {
         default_value = FARCASTER_SYNC_SOCKET_NAME
     )]
     pub sync_socket: ServiceAddr,
+
+    /// Format of the log output, `text` or `json`
+    ///
+    /// JSON lines carry the level, the service and the swap of each record for
+    /// log aggregators. Defaults to the human-readable colorized text output.
+    #[clap(long, global = true, env = "FARCASTER_LOG_FORMAT")]
+    pub log_format: Option<LogFormat>,
+
+    /// Transport of the service buses, `ipc` or `tcp`
+    ///
+    /// When set, the msg, ctl, info and sync sockets must all use this transport,
+    /// the service fails at start-up otherwise. The sockets left to their defaults
+    /// follow the transport, `tcp` binds them to ports 7081 to 7084 of the loopback
+    /// interface. Use `tcp` to run services, e.g. the syncers, on another host than
+    /// farcasterd.
+    #[clap(long, global = true, env = "FARCASTER_BUS_TRANSPORT")]
+    pub bus_transport: Option<BusTransport>,
+}
+
+/// Transport of the ZMQ sockets connecting the services and the threads of a service
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Display, Serialize, Deserialize)]
+#[serde(crate = "serde_crate", rename_all = "lowercase")]
+pub enum BusTransport {
+    /// Between the threads of a single process
+    #[display("inproc")]
+    Inproc,
+    /// Between the processes of a single host, through socket files
+    #[display("ipc")]
+    Ipc,
+    /// Between hosts, through TCP connections
+    #[display("tcp")]
+    Tcp,
+}
+
+impl BusTransport {
+    /// Transport of a socket address
+    pub fn of(addr: &ServiceAddr) -> Self {
+        match addr {
+            ServiceAddr::Inproc(_) => BusTransport::Inproc,
+            ServiceAddr::Ipc(_) => BusTransport::Ipc,
+            ServiceAddr::Tcp(_) => BusTransport::Tcp,
+        }
+    }
+}
+
+impl FromStr for BusTransport {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "inproc" => Ok(BusTransport::Inproc),
+            "ipc" => Ok(BusTransport::Ipc),
+            "tcp" => Ok(BusTransport::Tcp),
+            _ => Err(format!(
+                "Unknown bus transport {}, expected inproc, ipc or tcp",
+                s
+            )),
+        }
+    }
+}
+
+/// ZMQ socket types of the bridge between the threads of a service
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Display, Serialize, Deserialize)]
+#[serde(crate = "serde_crate", rename_all = "lowercase")]
+pub enum BridgeSocket {
+    /// An exclusive pair of sockets, only reliable over inproc
+    #[display("pair")]
+    Pair,
+    /// The sending thread pushes the messages the receiving thread pulls, reconnecting over ipc
+    /// and tcp
+    #[display("push")]
+    Push,
+}
+
+impl BridgeSocket {
+    /// Socket types of the receiving and of the sending end of the bridge
+    pub fn socket_types(&self) -> (zmq::SocketType, zmq::SocketType) {
+        match self {
+            BridgeSocket::Pair => (zmq::PAIR, zmq::PAIR),
+            BridgeSocket::Push => (zmq::PULL, zmq::PUSH),
+        }
+    }
+}
+
+impl FromStr for BridgeSocket {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pair" => Ok(BridgeSocket::Pair),
+            "push" => Ok(BridgeSocket::Push),
+            _ => Err(format!(
+                "Unknown bridge socket {}, expected pair or push",
+                s
+            )),
+        }
+    }
 }
 
 /// Token used in services
@@ -141,13 +246,40 @@ impl FromStr for TokenString {
 
 impl Opts {
     pub fn process(&mut self) {
-        let env = env_logger::Env::new().default_filter_or("error,farcaster_node=info");
         // standard environment variable set to "true" when running in CI environments
         let is_test = matches!(std::env::var("CI"), Ok(v) if v == "true");
-        env_logger::from_env(env)
-            .is_test(is_test)
-            .try_init()
-            .expect("Failed to initialize loggger!");
+        logger::init(self.log_format.unwrap_or_default(), is_test);
+
+        // the sockets left to their defaults follow the bus transport
+        if self.bus_transport == Some(BusTransport::Tcp) {
+            for (s, name, port) in [
+                (
+                    &mut self.msg_socket,
+                    FARCASTER_MSG_SOCKET_NAME,
+                    FARCASTER_MSG_SOCKET_PORT,
+                ),
+                (
+                    &mut self.ctl_socket,
+                    FARCASTER_CTL_SOCKET_NAME,
+                    FARCASTER_CTL_SOCKET_PORT,
+                ),
+                (
+                    &mut self.info_socket,
+                    FARCASTER_INFO_SOCKET_NAME,
+                    FARCASTER_INFO_SOCKET_PORT,
+                ),
+                (
+                    &mut self.sync_socket,
+                    FARCASTER_SYNC_SOCKET_NAME,
+                    FARCASTER_SYNC_SOCKET_PORT,
+                ),
+            ] {
+                if matches!(s, ServiceAddr::Ipc(path) if *path == name) {
+                    *s = ServiceAddr::from_str(&format!("tcp://127.0.0.1:{}", port))
+                        .expect("Valid default tcp socket");
+                }
+            }
+        }
 
         let mut me = self.clone();
 
@@ -167,6 +299,41 @@ impl Opts {
                 _ => {}
             }
         }
+
+        // fail fast rather than waiting on services that can never connect
+        if let Err(err) = self.verify_bus_transport() {
+            error!("{}", err);
+            std::process::exit(1);
+        }
+    }
+
+    /// Check that the service buses all use the transport set with `--bus-transport`. The
+    /// services run in separate processes, the in-process transport cannot connect them.
+    pub fn verify_bus_transport(&self) -> Result<(), String> {
+        let transport =
+            match self.bus_transport {
+                Some(BusTransport::Inproc) => return Err(
+                    "The inproc transport cannot connect the service buses of separate processes, \
+                     use ipc or tcp"
+                        .to_string(),
+                ),
+                Some(transport) => transport,
+                None => return Ok(()),
+            };
+        for (name, addr) in [
+            ("msg", &self.msg_socket),
+            ("ctl", &self.ctl_socket),
+            ("info", &self.info_socket),
+            ("sync", &self.sync_socket),
+        ] {
+            if BusTransport::of(addr) != transport {
+                return Err(format!(
+                    "The {} socket {} does not use the {} bus transport shared by the services",
+                    name, addr, transport
+                ));
+            }
+        }
+        Ok(())
     }
 
     pub fn process_dir(&self, path: &mut String) {
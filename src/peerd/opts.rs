@@ -7,6 +7,8 @@
 use clap::{ArgGroup, ValueHint};
 use std::net::IpAddr;
 
+use crate::bus::RateLimitAction;
+use crate::config::{PeerPingConfig, PeerRateLimitConfig};
 use crate::opts::TokenString;
 use internet2::addr::{LocalNode, NodeAddr};
 
@@ -59,6 +61,32 @@ pub struct Opts {
     #[clap(short, long, default_value = "9735")]
     pub port: u16,
 
+    /// Maximum number of messages accepted from the remote peer per rate limit window
+    ///
+    /// If not set no rate limit is applied to the remote peer.
+    #[clap(long)]
+    pub rate_limit: Option<u32>,
+
+    /// Length of the rate limit window in seconds
+    #[clap(long, default_value = "60")]
+    pub rate_limit_window: u64,
+
+    /// Action taken against a remote peer exceeding the rate limit
+    ///
+    /// `throttle` drops the messages exceeding the limit, `disconnect` drops
+    /// the connection with the remote peer.
+    #[clap(long, default_value = "throttle", possible_values = &["throttle", "disconnect"])]
+    pub rate_limit_action: RateLimitAction,
+
+    /// Interval in seconds between two pings of the remote peer
+    #[clap(long, default_value = "30")]
+    pub ping_interval: u64,
+
+    /// Consecutive pings left unanswered by the remote peer after which the
+    /// connection is declared stale and dropped
+    #[clap(long, default_value = "3")]
+    pub ping_max_missed: u8,
+
     /// Node key configuration
     #[clap(flatten)]
     pub peer_key_opts: PeerKeyOpts,
@@ -77,6 +105,22 @@ impl Opts {
     pub fn process(&mut self) {
         self.shared.process();
     }
+
+    pub fn rate_limit(&self) -> Option<PeerRateLimitConfig> {
+        self.rate_limit.map(|max_messages| PeerRateLimitConfig {
+            max_messages,
+            window: self.rate_limit_window,
+            action: self.rate_limit_action,
+        })
+    }
+
+    pub fn ping(&self) -> PeerPingConfig {
+        PeerPingConfig {
+            interval: self.ping_interval,
+            // a connection is only stale after at least one missed pong
+            max_missed: self.ping_max_missed.max(1),
+        }
+    }
 }
 
 /// Node key configuration
@@ -7,9 +7,11 @@
 use farcaster_core::swap::SwapId;
 use internet2::addr::LocalNode;
 use microservices::peer::RecvMessage;
+use std::collections::VecDeque;
+use std::sync::mpsc::TryRecvError;
 use std::sync::Arc;
-use std::thread::spawn;
-use std::time::{Duration, SystemTime};
+use std::thread::{sleep, spawn};
+use std::time::{Duration, Instant, SystemTime};
 use uuid::Uuid;
 
 use amplify::Bipolar;
@@ -29,12 +31,16 @@ use microservices::ZMQ_CONTEXT;
 use crate::bus::p2p::Receipt;
 use crate::bus::{
     ctl::CtlMsg,
-    info::{InfoMsg, PeerInfo},
+    info::{ConnectionDirection, InfoMsg, PeerInfo},
     p2p::PeerMsg,
-    BusMsg, ServiceBus,
+    BusMsg, PeerRateLimited, RateLimitAction, ServiceBus,
 };
+use crate::config::{PeerPingConfig, PeerRateLimitConfig};
 use crate::{CtlServer, Endpoints, Error, LogStyle, Service, ServiceConfig, ServiceId};
 
+/// Number of recent ping round-trip times reported in the peer info
+const PING_RTT_HISTORY: usize = 10;
+
 pub fn start_connect_peer_listener_runtime(
     remote_node_addr: NodeAddr,
     local_node: LocalNode,
@@ -70,7 +76,7 @@ pub fn start_connect_peer_listener_runtime(
     let tx = ZMQ_CONTEXT.socket(zmq::PUSH)?;
     tx.connect("inproc://bridge")?;
 
-    let (thread_flag_tx, _thread_flag_rx) = std::sync::mpsc::channel();
+    let (thread_flag_tx, thread_flag_rx) = std::sync::mpsc::channel();
 
     debug!("Starting thread listening for messages from the remote peer");
     let bridge_handler = PeerReceiverRuntime {
@@ -87,8 +93,7 @@ pub fn start_connect_peer_listener_runtime(
             },
             BridgeHandler,
         )?,
-        _thread_flag_rx,
-        awaiting_pong: false,
+        thread_flag_rx,
     };
     let unmarshaller: Unmarshaller<PeerMsg> = PeerMsg::create_unmarshaller();
     let peer_receiver_runtime = peer::Listener::<PeerReceiverRuntime, PeerMsg>::with(
@@ -115,6 +120,8 @@ pub fn run_from_connect(
     remote_node_addr: NodeAddr,
     local_socket: Option<InetSocketAddr>,
     local_node: LocalNode,
+    rate_limit: Option<PeerRateLimitConfig>,
+    ping: PeerPingConfig,
 ) -> Result<(), Error> {
     debug!("Opening bridge between runtime and peer receiver threads");
     let rx = ZMQ_CONTEXT.socket(zmq::PULL)?;
@@ -122,6 +129,7 @@ pub fn run_from_connect(
 
     let (thread_flag_tx, _thread_flag_rx) = std::sync::mpsc::channel();
     let id = Uuid::new_v4().as_u128();
+    spawn_ping_ticker(ServiceId::Peer(id, remote_node_addr), ping.interval)?;
 
     debug!(
         "Starting main service runtime with identity: {}",
@@ -133,11 +141,20 @@ pub fn run_from_connect(
         local_socket,
         local_node,
         peer_sender: None, // As connector we create the sender on is_ready
-        forked_from_listener: false,
+        direction: ConnectionDirection::Outbound,
         started: SystemTime::now(),
         messages_sent: 0,
         messages_received: 0,
+        messages_dropped: 0,
+        bytes_sent: 0,
+        bytes_received: 0,
+        rate_limiter: rate_limit.map(RateLimiter::with),
         awaited_pong: None,
+        missed_pongs: 0,
+        max_missed_pongs: ping.max_missed,
+        last_ping_rtt: None,
+        ping_rtts: empty!(),
+        ping_timeouts: 0,
         thread_flag_tx,
         unchecked_msg_cache: empty!(),
     };
@@ -154,6 +171,8 @@ pub fn run_from_listener(
     remote_node_addr: Option<NodeAddr>,
     local_socket: Option<InetSocketAddr>,
     local_node: LocalNode,
+    rate_limit: Option<PeerRateLimitConfig>,
+    ping: PeerPingConfig,
 ) -> Result<(), Error> {
     debug!("Splitting connection into receiver and sender parts");
     let (mut peer_receiver, mut peer_sender) = connection.split();
@@ -192,7 +211,7 @@ pub fn run_from_listener(
     let tx = ZMQ_CONTEXT.socket(zmq::PUSH)?;
     tx.connect("inproc://bridge")?;
 
-    let (thread_flag_tx, _thread_flag_rx) = std::sync::mpsc::channel();
+    let (thread_flag_tx, thread_flag_rx) = std::sync::mpsc::channel();
 
     debug!("Starting thread listening for messages from the remote peer");
     let bridge_handler = PeerReceiverRuntime {
@@ -209,8 +228,7 @@ pub fn run_from_listener(
             },
             BridgeHandler,
         )?,
-        _thread_flag_rx,
-        awaiting_pong: false,
+        thread_flag_rx,
     };
     let unmarshaller: Unmarshaller<PeerMsg> = PeerMsg::create_unmarshaller();
     let peer_receiver_runtime = peer::Listener::<PeerReceiverRuntime, PeerMsg>::with(
@@ -233,25 +251,65 @@ pub fn run_from_listener(
         internal_identity
     );
     let runtime = Runtime {
-        identity: internal_identity,
+        identity: internal_identity.clone(),
         remote_node_addr,
         local_socket,
         local_node,
         peer_sender: Some(peer_sender),
-        forked_from_listener: true,
+        direction: ConnectionDirection::Inbound,
         started: SystemTime::now(),
         messages_sent: 0,
         messages_received: 0,
+        messages_dropped: 0,
+        bytes_sent: 0,
+        bytes_received: 0,
+        rate_limiter: rate_limit.map(RateLimiter::with),
         awaited_pong: None,
+        missed_pongs: 0,
+        max_missed_pongs: ping.max_missed,
+        last_ping_rtt: None,
+        ping_rtts: empty!(),
+        ping_timeouts: 0,
         thread_flag_tx,
         unchecked_msg_cache: empty!(),
     };
+    spawn_ping_ticker(internal_identity, ping.interval)?;
     let mut service = Service::service(config, runtime)?;
     service.add_bridge_service_bus(rx)?;
     service.run_loop()?;
     unreachable!()
 }
 
+/// Periodically emit `PingPeer` over the bridge so the runtime pings the remote peer and detects
+/// stale connections
+fn spawn_ping_ticker(identity: ServiceId, interval: u64) -> Result<(), Error> {
+    let tx = ZMQ_CONTEXT.socket(zmq::PUSH)?;
+    tx.connect("inproc://bridge")?;
+    let mut bridge = esb::Controller::with(
+        map! {
+            ServiceBus::Bridge => esb::BusConfig {
+                carrier: Carrier::Socket(tx),
+                router: None,
+                queued: true,
+                api_type: ZmqSocketType::Rep,
+                topic: None,
+            }
+        },
+        BridgeHandler,
+    )?;
+    spawn(move || loop {
+        sleep(Duration::from_secs(interval));
+        if let Err(err) = bridge.send_to(
+            ServiceBus::Bridge,
+            identity.clone(),
+            BusMsg::P2p(PeerMsg::PingPeer),
+        ) {
+            error!("Error sending ping request over bridge: {}", err);
+        }
+    });
+    Ok(())
+}
+
 pub struct BridgeHandler;
 
 impl esb::Handler<ServiceBus> for BridgeHandler {
@@ -284,8 +342,7 @@ impl esb::Handler<ServiceBus> for BridgeHandler {
 pub struct PeerReceiverRuntime {
     internal_identity: ServiceId,
     bridge: esb::Controller<ServiceBus, BusMsg, BridgeHandler>,
-    awaiting_pong: bool,
-    _thread_flag_rx: std::sync::mpsc::Receiver<()>,
+    thread_flag_rx: std::sync::mpsc::Receiver<()>,
 }
 
 impl PeerReceiverRuntime {
@@ -306,6 +363,17 @@ impl PeerReceiverRuntime {
             Ok(())
         }
     }
+
+    /// Whether the runtime dropped this receiver after declaring its connection stale
+    fn superseded(&self) -> bool {
+        loop {
+            match self.thread_flag_rx.try_recv() {
+                Ok(()) => continue,
+                Err(TryRecvError::Empty) => return false,
+                Err(TryRecvError::Disconnected) => return true,
+            }
+        }
+    }
 }
 
 impl peer::Handler<PeerMsg> for PeerReceiverRuntime {
@@ -316,13 +384,6 @@ impl peer::Handler<PeerMsg> for PeerReceiverRuntime {
         message: <Unmarshaller<PeerMsg> as Unmarshall>::Data,
     ) -> Result<(), Self::Error> {
         trace!("FWP message details: {:?}", message);
-        if let PeerMsg::Pong(_) = *Arc::clone(&message) {
-            if self.awaiting_pong {
-                self.awaiting_pong = false;
-            } else {
-                error!("Unexpected pong received in PeerReceiverRuntime.")
-            }
-        }
         if message.on_receiver_whitelist() {
             self.send_over_bridge(message)?;
         } else {
@@ -337,21 +398,16 @@ impl peer::Handler<PeerMsg> for PeerReceiverRuntime {
     fn handle_err(&mut self, err: Self::Error) -> Result<(), Self::Error> {
         debug!("Underlying peer interface requested to handle {}", err);
         match err {
+            // The liveness of the connection is checked by the runtime pinging the remote peer
             Error::Peer(presentation::Error::Transport(transport::Error::TimedOut)) => {
-                trace!("Time to ping the remote peer");
-                if self.awaiting_pong {
-                    error!(
-                        "The ping has failed, probably the connection is down. Will shutdown the receiver runtime."
-                    );
-                    self.send_over_bridge(Arc::new(PeerMsg::PeerReceiverRuntimeShutdown))?;
-                    return Err(Error::NotResponding);
-                }
-                // This means socket reading timeout and the fact that we need
-                // to send a ping message
-                self.send_over_bridge(Arc::new(PeerMsg::PingPeer))?;
-                self.awaiting_pong = true;
+                trace!("No message received from the remote peer before the read timeout");
                 Ok(())
             }
+            // The runtime already moved on from a connection it declared stale
+            _ if self.superseded() => {
+                debug!("Stale connection receiver runtime halting: {}", err);
+                Err(err)
+            }
             // for all other error types, indicating internal errors and broken
             // connections, we propagate error to the upper level (currently not
             // handled, will result in a broken peerd state)
@@ -367,6 +423,41 @@ impl peer::Handler<PeerMsg> for PeerReceiverRuntime {
     }
 }
 
+/// Counts the messages received from the remote peer over a fixed window
+struct RateLimiter {
+    config: PeerRateLimitConfig,
+    window_start: Instant,
+    window_count: u32,
+    // whether farcasterd was notified of the peer exceeding the limit in the current window
+    notified: bool,
+}
+
+impl RateLimiter {
+    fn with(config: PeerRateLimitConfig) -> Self {
+        RateLimiter {
+            config,
+            window_start: Instant::now(),
+            window_count: 0,
+            notified: false,
+        }
+    }
+
+    /// Count a new message, returns false if the message exceeds the limit
+    fn accept(&mut self) -> bool {
+        // once farcasterd is asked to disconnect the peer nothing else is accepted
+        if self.config.action == RateLimitAction::Disconnect && self.notified {
+            return false;
+        }
+        if self.window_start.elapsed() >= Duration::from_secs(self.config.window) {
+            self.window_start = Instant::now();
+            self.window_count = 0;
+            self.notified = false;
+        }
+        self.window_count = self.window_count.saturating_add(1);
+        self.window_count <= self.config.max_messages
+    }
+}
+
 pub struct Runtime {
     identity: ServiceId,
     remote_node_addr: Option<NodeAddr>,
@@ -374,13 +465,28 @@ pub struct Runtime {
     local_node: LocalNode,
 
     peer_sender: Option<PeerSender>,
-    // TODO: make this an enum instead with a descriptive distinction of listening and connecting to a listener
-    forked_from_listener: bool,
+    // Inbound for a peerd forked from the listener, outbound for a peerd connecting to a listener.
+    // Only outbound connections are re-established when lost.
+    direction: ConnectionDirection,
 
     started: SystemTime,
     messages_sent: usize,
     messages_received: usize,
-    awaited_pong: Option<u16>,
+    messages_dropped: usize,
+    /// Encoded size of the messages sent to the remote peer
+    bytes_sent: u64,
+    /// Encoded size of the messages received from the remote peer
+    bytes_received: u64,
+    rate_limiter: Option<RateLimiter>,
+    /// Size of the pong awaited from the remote peer with the time the ping was sent
+    awaited_pong: Option<(u16, Instant)>,
+    /// Consecutive pings the remote peer did not answer
+    missed_pongs: u8,
+    /// Consecutive missed pongs after which the connection is declared stale
+    max_missed_pongs: u8,
+    last_ping_rtt: Option<Duration>,
+    ping_rtts: VecDeque<Duration>,
+    ping_timeouts: usize,
 
     unchecked_msg_cache: Vec<((SwapId, internet2::TypeId), PeerMsg)>,
 
@@ -399,7 +505,7 @@ impl esb::Handler<ServiceBus> for Runtime {
 
     fn on_ready(&mut self, endpoints: &mut Endpoints) -> Result<(), Error> {
         // log iff taker
-        if !self.forked_from_listener {
+        if self.direction == ConnectionDirection::Outbound {
             let (peer_sender, thread_flag_tx) = match start_connect_peer_listener_runtime(
                 self.remote_node_addr.expect("Checked for connecter"),
                 self.local_node,
@@ -497,6 +603,7 @@ impl Runtime {
             &message.get_type()
         );
         self.messages_sent += 1;
+        self.record_sent_bytes(&message);
         while let Err(err) = self
             .peer_sender
             .as_mut()
@@ -515,7 +622,7 @@ impl Runtime {
                 err
             );
             // If this is the listener-forked peerd, i.e. the maker's peerd, terminate it.
-            if self.forked_from_listener {
+            if self.direction == ConnectionDirection::Inbound {
                 for (_, cached_msg) in self.unchecked_msg_cache.drain(..) {
                     // Draining cached messages to the various running swaps
                     endpoints.send_to(
@@ -577,6 +684,16 @@ impl Runtime {
         request: CtlMsg,
     ) -> Result<(), Error> {
         match request {
+            CtlMsg::Heartbeat if source == ServiceId::Farcasterd => {
+                endpoints.send_to(
+                    ServiceBus::Ctl,
+                    self.identity.clone(),
+                    ServiceId::Farcasterd,
+                    BusMsg::Ctl(CtlMsg::Heartbeat),
+                )?;
+                Ok(())
+            }
+
             CtlMsg::Terminate if source == ServiceId::Farcasterd => {
                 for (_, cached_msg) in self.unchecked_msg_cache.drain(..) {
                     // Draining cached messages to the various running swaps
@@ -597,6 +714,11 @@ impl Runtime {
                 std::process::exit(0);
             }
 
+            CtlMsg::SetLogFilter(filter) if source == ServiceId::Farcasterd => {
+                crate::logger::set_filter(filter.as_deref());
+                Ok(())
+            }
+
             _ => {
                 error!(
                     "{} | BusMsg is not supported by the CTL interface",
@@ -636,8 +758,14 @@ impl Runtime {
                         .as_secs(),
                     messages_sent: self.messages_sent,
                     messages_received: self.messages_received,
-                    forked_from_listener: self.forked_from_listener,
+                    messages_dropped: self.messages_dropped,
+                    bytes_sent: self.bytes_sent,
+                    bytes_received: self.bytes_received,
+                    direction: self.direction,
                     awaits_pong: self.awaited_pong.is_some(),
+                    last_ping_rtt: self.last_ping_rtt,
+                    ping_rtts: self.ping_rtts.iter().copied().collect(),
+                    ping_timeouts: self.ping_timeouts,
                 };
                 self.send_client_info(endpoints, source, InfoMsg::PeerInfo(info))?;
             }
@@ -695,6 +823,7 @@ impl Runtime {
                 cached_msg.swap_id(),
                 cached_msg
             );
+            self.bytes_sent += cached_msg.serialize().len() as u64;
             self.peer_sender
                 .as_mut()
                 .expect("should be connected")
@@ -720,8 +849,38 @@ impl Runtime {
 
         self.messages_received += 1;
 
+        // PingPeer and PeerReceiverRuntimeShutdown are emitted by the receiver runtime, not the
+        // remote peer
+        let from_remote = !matches!(
+            request,
+            PeerMsg::PingPeer | PeerMsg::PeerReceiverRuntimeShutdown
+        );
+        if from_remote {
+            self.bytes_received += request.serialize().len() as u64;
+        }
+        if from_remote && !self.check_rate_limit(endpoints)? {
+            debug!(
+                "{} | Dropping {}, the remote peer exceeded the rate limit",
+                self.identity(),
+                request
+            );
+            return Ok(());
+        }
+
         match &request {
-            PeerMsg::PingPeer => self.ping()?,
+            PeerMsg::PingPeer => {
+                if self.missed_pongs >= self.max_missed_pongs {
+                    warn!(
+                        "{} | The remote peer missed {} pongs in a row, dropping the stale connection",
+                        self.identity(),
+                        self.missed_pongs
+                    );
+                    self.drop_stale_connection();
+                    self.handle_connection_lost(endpoints)?;
+                } else {
+                    self.ping()?;
+                }
+            }
 
             PeerMsg::Ping(pong_size) => {
                 debug!("{} | receiving ping, ponging back", self.identity().label());
@@ -731,64 +890,32 @@ impl Runtime {
             PeerMsg::Pong(noise) => {
                 match self.awaited_pong {
                     None => error!("{} | Unexpected pong from the remote peer", self.identity()),
-                    Some(len) if len as usize != noise.len() => {
+                    Some((len, _)) if len as usize != noise.len() => {
                         warn!(
                             "{} | Pong data size does not match requested with ping",
                             self.identity()
                         );
                     }
-                    _ => trace!(
-                        "{} | Got pong reply, exiting pong await mode",
-                        self.identity()
-                    ),
+                    Some((_, sent)) => {
+                        let rtt = sent.elapsed();
+                        trace!(
+                            "{} | Got pong reply after {} ms, exiting pong await mode",
+                            self.identity(),
+                            rtt.as_millis()
+                        );
+                        self.record_ping_rtt(rtt);
+                    }
                 }
                 self.awaited_pong = None;
+                self.missed_pongs = 0;
             }
 
             PeerMsg::PeerReceiverRuntimeShutdown => {
                 warn!("{} | Exiting peerd receiver runtime", self.identity());
-                endpoints.send_to(
-                    ServiceBus::Ctl,
-                    self.identity(),
-                    ServiceId::Farcasterd,
-                    BusMsg::Ctl(CtlMsg::Disconnected),
-                )?;
-                // If this is the listener-forked peerd, i.e. the maker's peerd, terminate it.
-                if self.forked_from_listener {
-                    endpoints.send_to(
-                        ServiceBus::Ctl,
-                        self.identity(),
-                        ServiceId::Farcasterd,
-                        BusMsg::Ctl(CtlMsg::PeerdTerminated),
-                    )?;
-                    for ((swap_id, _), cached_msg) in self.unchecked_msg_cache.drain(..) {
-                        // Draining cached messages to the various running swaps
-                        debug!(
-                            "{} | Returning cache message {} back to swap",
-                            swap_id, cached_msg
-                        );
-                        endpoints.send_to(
-                            ServiceBus::Ctl,
-                            self.identity.clone(),
-                            ServiceId::Swap(swap_id),
-                            BusMsg::Ctl(CtlMsg::FailedPeerMessage(cached_msg)),
-                        )?;
-                    }
-                    warn!(
-                        "Waiting for farcaster to terminate peerd instance {}",
-                        self.identity().label()
-                    );
-                } else {
-                    // This blocks until reconnected successfully
-                    while let Err(err) = self.reconnect_peer(endpoints) {
-                        info!(
-                            "{} | Failed to reconnect: {}, retrying.",
-                            self.identity(),
-                            err
-                        );
-                        std::thread::sleep(std::time::Duration::from_secs(1));
-                    }
+                if self.awaited_pong.take().is_some() {
+                    self.record_ping_timeout();
                 }
+                self.handle_connection_lost(endpoints)?;
             }
 
             PeerMsg::MsgReceipt(receipt) => {
@@ -842,18 +969,58 @@ impl Runtime {
                 )?;
 
                 // send a receipt back to the remote peer
+                let receipt = PeerMsg::MsgReceipt(Receipt {
+                    swap_id: request.swap_id(),
+                    msg_type: request.get_type(),
+                });
+                self.record_sent_bytes(&receipt);
                 self.peer_sender
                     .as_mut()
                     .expect("should be connected")
-                    .send_message(PeerMsg::MsgReceipt(Receipt {
-                        swap_id: request.swap_id(),
-                        msg_type: request.get_type(),
-                    }))?;
+                    .send_message(receipt)?;
             }
         }
         Ok(())
     }
 
+    /// Apply the inbound rate limit to a message received from the remote peer, returns whether
+    /// the message can be processed. Farcasterd is notified once per window when the limit is
+    /// exceeded, and is responsible for terminating us if the action is to disconnect.
+    fn check_rate_limit(&mut self, endpoints: &mut Endpoints) -> Result<bool, Error> {
+        let rate_limiter = match self.rate_limiter.as_mut() {
+            Some(rate_limiter) => rate_limiter,
+            None => return Ok(true),
+        };
+        if rate_limiter.accept() {
+            return Ok(true);
+        }
+        self.messages_dropped += 1;
+        if rate_limiter.notified {
+            return Ok(false);
+        }
+        rate_limiter.notified = true;
+        let report = PeerRateLimited {
+            max_messages: rate_limiter.config.max_messages,
+            window: rate_limiter.config.window,
+            action: rate_limiter.config.action,
+            messages_dropped: self.messages_dropped as u64,
+        };
+        warn!(
+            "{} | The remote peer exceeded the rate limit of {} messages per {} seconds, applying {}",
+            self.identity,
+            report.max_messages,
+            report.window,
+            report.action.bright_yellow_bold()
+        );
+        endpoints.send_to(
+            ServiceBus::Ctl,
+            self.identity.clone(),
+            ServiceId::Farcasterd,
+            BusMsg::Ctl(CtlMsg::PeerRateLimited(report)),
+        )?;
+        Ok(false)
+    }
+
     fn ping(&mut self) -> Result<(), Error> {
         trace!("{} | Sending ping to the remote peer", self.identity());
         let mut rng = rand::thread_rng();
@@ -861,12 +1028,92 @@ impl Runtime {
         let mut noise = vec![0u8; len as usize];
         rng.fill_bytes(&mut noise);
         let pong_size = rng.gen_range(4, 32);
+        if self.awaited_pong.is_some() {
+            self.record_ping_timeout();
+        }
+        let ping = PeerMsg::Ping(pong_size);
         self.messages_sent += 1;
+        self.record_sent_bytes(&ping);
         self.peer_sender
             .as_mut()
             .expect("should be connected")
-            .send_message(PeerMsg::Ping(pong_size))?;
-        self.awaited_pong = Some(pong_size);
+            .send_message(ping)?;
+        self.awaited_pong = Some((pong_size, Instant::now()));
+        Ok(())
+    }
+
+    fn record_ping_rtt(&mut self, rtt: Duration) {
+        self.last_ping_rtt = Some(rtt);
+        if self.ping_rtts.len() == PING_RTT_HISTORY {
+            self.ping_rtts.pop_front();
+        }
+        self.ping_rtts.push_back(rtt);
+    }
+
+    fn record_ping_timeout(&mut self) {
+        warn!(
+            "{} | The remote peer did not answer the ping",
+            self.identity()
+        );
+        self.ping_timeouts += 1;
+        self.missed_pongs = self.missed_pongs.saturating_add(1);
+    }
+
+    /// Detach the receiver runtime of a connection that stopped answering pings, it may be
+    /// blocked reading from the remote peer forever and is not waited for before reconnecting
+    fn drop_stale_connection(&mut self) {
+        self.awaited_pong = None;
+        self.missed_pongs = 0;
+        // dropping the flag sender tells the receiver runtime it was superseded
+        let (thread_flag_tx, _) = std::sync::mpsc::channel();
+        self.thread_flag_tx = thread_flag_tx;
+    }
+
+    /// Notify farcasterd the connection is lost, then either wait for farcasterd to terminate
+    /// the listener-forked peerd or reconnect to the remote peer
+    fn handle_connection_lost(&mut self, endpoints: &mut Endpoints) -> Result<(), Error> {
+        endpoints.send_to(
+            ServiceBus::Ctl,
+            self.identity(),
+            ServiceId::Farcasterd,
+            BusMsg::Ctl(CtlMsg::Disconnected),
+        )?;
+        // If this is the listener-forked peerd, i.e. the maker's peerd, terminate it.
+        if self.direction == ConnectionDirection::Inbound {
+            endpoints.send_to(
+                ServiceBus::Ctl,
+                self.identity(),
+                ServiceId::Farcasterd,
+                BusMsg::Ctl(CtlMsg::PeerdTerminated),
+            )?;
+            for ((swap_id, _), cached_msg) in self.unchecked_msg_cache.drain(..) {
+                // Draining cached messages to the various running swaps
+                debug!(
+                    "{} | Returning cache message {} back to swap",
+                    swap_id, cached_msg
+                );
+                endpoints.send_to(
+                    ServiceBus::Ctl,
+                    self.identity.clone(),
+                    ServiceId::Swap(swap_id),
+                    BusMsg::Ctl(CtlMsg::FailedPeerMessage(cached_msg)),
+                )?;
+            }
+            warn!(
+                "Waiting for farcaster to terminate peerd instance {}",
+                self.identity().label()
+            );
+        } else {
+            // This blocks until reconnected successfully
+            while let Err(err) = self.reconnect_peer(endpoints) {
+                info!(
+                    "{} | Failed to reconnect: {}, retrying.",
+                    self.identity(),
+                    err
+                );
+                std::thread::sleep(std::time::Duration::from_secs(1));
+            }
+        }
         Ok(())
     }
 
@@ -880,11 +1127,18 @@ impl Runtime {
             .iter()
             .map(|_| rng.gen())
             .collect();
+        let pong = PeerMsg::Pong(noise);
         self.messages_sent += 1;
+        self.record_sent_bytes(&pong);
         self.peer_sender
             .as_mut()
             .expect("should be connected")
-            .send_message(PeerMsg::Pong(noise))?;
+            .send_message(pong)?;
         Ok(())
     }
+
+    /// Account the encoded size of a message sent to the remote peer
+    fn record_sent_bytes(&mut self, message: &PeerMsg) {
+        self.bytes_sent += message.serialize().len() as u64;
+    }
 }
@@ -40,6 +40,9 @@ lazy_static! {
     pub static ref ZMQ_CONTEXT: zmq::Context = zmq::Context::new();
 }
 
+/// Version of the running node, crate version and git commit it was built from
+pub const VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "+", env!("FARCASTER_GIT_HASH"));
+
 #[derive(
     Wrapper,
     Clone,
@@ -92,6 +95,42 @@ impl FromStr for ClientName {
     }
 }
 
+/// Instance of the syncer of a blockchain network: the default syncer configured by the network
+/// section of the syncers configuration, or a named syncer configured by one of its instances,
+/// e.g. to pin the swaps of high value to a trusted backend
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, StrictEncode, StrictDecode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub struct SyncerInstance(pub Option<String>);
+
+impl SyncerInstance {
+    /// The default syncer of a blockchain network
+    pub const DEFAULT: SyncerInstance = SyncerInstance(None);
+
+    /// The name of the instance in the syncers configuration, none for the default syncer
+    pub fn name(&self) -> Option<&str> {
+        self.0.as_deref()
+    }
+}
+
+impl From<Option<String>> for SyncerInstance {
+    fn from(name: Option<String>) -> Self {
+        SyncerInstance(name)
+    }
+}
+
+impl Display for SyncerInstance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(name) => write!(f, "<{}>", name),
+            None => Ok(()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Hash)]
 pub struct ServiceConfig {
     /// ZMQ socket for peer-to-peer network message bus
@@ -143,8 +182,8 @@ pub enum ServiceId {
     #[display("client<{0}>")]
     Client(u64),
 
-    #[display("{0} ({1}) syncer")]
-    Syncer(Blockchain, Network),
+    #[display("{0} ({1}) syncer{2}")]
+    Syncer(Blockchain, Network, SyncerInstance),
 
     #[display("walletd")]
     Wallet,
@@ -188,6 +227,14 @@ impl ServiceId {
         }
     }
 
+    pub fn syncer_instance(&self) -> Option<&SyncerInstance> {
+        if let ServiceId::Syncer(_, _, instance) = self {
+            Some(instance)
+        } else {
+            None
+        }
+    }
+
     pub fn dummy_peer_service_id(node_addr: NodeAddr) -> ServiceId {
         ServiceId::Peer(0, node_addr)
     }
@@ -271,6 +318,13 @@ where
             )
         };
 
+        let identity = esb::Handler::identity(&runtime);
+        let swap_id = match identity {
+            ServiceId::Swap(swap_id) => Some(swap_id),
+            _ => None,
+        };
+        crate::logger::set_context(identity, swap_id);
+
         let esb = esb::Controller::with(services, runtime)?;
         Ok(Self { esb, broker })
     }
@@ -315,6 +369,11 @@ where
                 ServiceId::Farcasterd,
                 BusMsg::Ctl(CtlMsg::Hello),
             )?;
+            self.esb.send_to(
+                ServiceBus::Ctl,
+                ServiceId::Farcasterd,
+                BusMsg::Ctl(CtlMsg::ServiceVersion(VERSION.to_string())),
+            )?;
         } else if identity != ServiceId::Farcasterd {
             warn!(
                 "Not saying hello to Farcasterd: service {} is broker",
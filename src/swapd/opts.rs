@@ -10,6 +10,8 @@ use farcaster_core::{
 };
 use std::str::FromStr;
 
+use crate::SyncerInstance;
+
 /// Swap executor daemon; part of Farcaster Node
 ///
 /// The daemon is controlled through ZMQ ctl socket (see `ctl-socket` argument
@@ -48,6 +50,41 @@ pub struct Opts {
     #[clap(long = "acc-finality")]
     pub accordant_finality: u8,
 
+    /// Wall-clock deadline of the swap as a unix timestamp in seconds; once passed the swap is
+    /// aborted if its funds are not locked yet, or stops short of the buy and refunds otherwise.
+    /// A restored swap keeps the deadline of its checkpoint
+    #[clap(long)]
+    pub deadline: Option<u64>,
+
+    /// Number of blocks between two re-broadcasts of the swap transactions not final yet, in case
+    /// they were dropped from the mempool; 0 disables the re-broadcasts
+    #[clap(long, default_value = "3")]
+    pub rebroadcast_interval: u32,
+
+    /// Number of confirmations before a received Monero output can be spent, must match the
+    /// unlock depth of the Monero syncer
+    #[clap(long, default_value = "10")]
+    pub monero_unlock_depth: u32,
+
+    /// Count the unconfirmed funds of the funding address when checking the swap is funded,
+    /// otherwise the swap waits for the funding to confirm
+    #[clap(long)]
+    pub count_unconfirmed_funding: bool,
+
+    /// Name of the syncer instance the swap runs on, the default syncers of the network when
+    /// none
+    #[clap(long)]
+    pub syncer_instance: Option<String>,
+
+    /// Name of the syncer instance the swap watches Bitcoin on, overrides `syncer-instance`,
+    /// e.g. to restore a swap after its Bitcoin syncer failed over
+    #[clap(long)]
+    pub bitcoin_syncer_instance: Option<String>,
+
+    /// Name of the syncer instance the swap watches Monero on, overrides `syncer-instance`
+    #[clap(long)]
+    pub monero_syncer_instance: Option<String>,
+
     /// These params can be read also from the configuration file, not just
     /// Command-line args or environment variables
     #[clap(flatten)]
@@ -58,4 +95,20 @@ impl Opts {
     pub fn process(&mut self) {
         self.shared.process();
     }
+
+    /// The syncer instances the swap watches Bitcoin and Monero on
+    pub fn syncer_instances(&self) -> (SyncerInstance, SyncerInstance) {
+        (
+            SyncerInstance(
+                self.bitcoin_syncer_instance
+                    .clone()
+                    .or_else(|| self.syncer_instance.clone()),
+            ),
+            SyncerInstance(
+                self.monero_syncer_instance
+                    .clone()
+                    .or_else(|| self.syncer_instance.clone()),
+            ),
+        )
+    }
 }
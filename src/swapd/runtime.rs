@@ -10,25 +10,31 @@ use super::{
     temporal_safety::TemporalSafety,
     StateReport,
 };
+use crate::service::SwapLogging;
 use crate::syncerd::types::{Event, TransactionConfirmations};
 use crate::syncerd::{Abort, Task, TaskTarget};
 use crate::{
-    bus::ctl::{Checkpoint, CtlMsg},
+    bus::ctl::{
+        Checkpoint, CtlMsg, MoneroAddress, MoneroDeposit, SwapSyncer, UpdatePeerd, UpdateSyncer,
+    },
     bus::info::{InfoMsg, SwapInfo},
     bus::p2p::PeerMsg,
     bus::sync::SyncMsg,
-    bus::{BusMsg, ServiceBus},
-    syncerd::{HeightChanged, TransactionRetrieved, XmrAddressAddendum},
+    bus::{strict_decode_trailing, BusMsg, Failure, FailureCode, ServiceBus, SwapStateChanged},
+    syncerd::{
+        HeightChanged, MoneroReceived, MoneroUnlocked, SweepLocked, TransactionRetrieved,
+        XmrAddressAddendum,
+    },
 };
 use crate::{service::SwapDetails, swapd::Opts};
-use crate::{service::SwapLogging, swapd::temporal_safety::SWEEP_MONERO_THRESHOLD};
 use crate::{
     service::{Endpoints, Reporter},
     syncerd::AddressTransaction,
 };
-use crate::{CtlServer, Error, LogStyle, Service, ServiceConfig, ServiceId};
+use crate::{CtlServer, Error, LogStyle, Service, ServiceConfig, ServiceId, SyncerInstance};
 
 use std::any::Any;
+use std::collections::HashSet;
 use std::time::{Duration, SystemTime};
 
 use bitcoin::Txid;
@@ -45,6 +51,7 @@ use microservices::esb::{self, Handler};
 use strict_encoding::{StrictDecode, StrictEncode};
 
 pub fn run(config: ServiceConfig, opts: Opts) -> Result<(), Error> {
+    let (bitcoin_syncer_instance, monero_syncer_instance) = opts.syncer_instances();
     let Opts {
         swap_id,
         deal,
@@ -52,6 +59,10 @@ pub fn run(config: ServiceConfig, opts: Opts) -> Result<(), Error> {
         arbitrating_finality,
         arbitrating_safety,
         accordant_finality,
+        deadline,
+        rebroadcast_interval,
+        monero_unlock_depth,
+        count_unconfirmed_funding,
         ..
     } = opts;
 
@@ -70,6 +81,8 @@ pub fn run(config: ServiceConfig, opts: Opts) -> Result<(), Error> {
         (SwapRole::Alice, TradeRole::Taker) => SwapStateMachine::StartTaker(SwapRole::Alice),
         (SwapRole::Bob, TradeRole::Taker) => SwapStateMachine::StartTaker(SwapRole::Bob),
     };
+    // tag all the log records of the process with the swap id from the start
+    crate::logger::set_context(ServiceId::Swap(swap_id), Some(swap_id));
     info!(
         "{}: {}",
         "Starting swap".to_string().bright_green_bold(),
@@ -91,7 +104,10 @@ pub fn run(config: ServiceConfig, opts: Opts) -> Result<(), Error> {
         watched_txs: none!(),
         retrieving_txs: none!(),
         sweeping_addr: none!(),
+        watched_deposit: none!(),
+        funding_balance: none!(),
         broadcasting_txs: none!(),
+        rebroadcasting_txs: none!(),
         txids: none!(),
         final_txs: none!(),
         tasks: none!(),
@@ -104,15 +120,22 @@ pub fn run(config: ServiceConfig, opts: Opts) -> Result<(), Error> {
         monero_height: 0,
         bitcoin_height: 0,
         confirmation_bound: 50000,
+        bitcoin_finality: temporal_safety.arb_finality,
+        monero_finality: temporal_safety.acc_finality,
         last_tx_event: none!(),
         network,
-        bitcoin_syncer: ServiceId::Syncer(Blockchain::Bitcoin, network),
-        monero_syncer: ServiceId::Syncer(Blockchain::Monero, network),
+        bitcoin_syncer: ServiceId::Syncer(Blockchain::Bitcoin, network, bitcoin_syncer_instance),
+        monero_syncer: ServiceId::Syncer(Blockchain::Monero, network, monero_syncer_instance),
         awaiting_funding: false,
         xmr_addr_addendum: None,
         confirmations: none!(),
         broadcasted_txs: none!(),
         failed_broadcasted_txs: none!(),
+        rebroadcast_interval,
+        last_rebroadcast_height: 0,
+        monero_unlock_depth,
+        rejected_txs: none!(),
+        monero_deposit_from_height: None,
     };
 
     let state_report = StateReport::new("Start".to_string(), &temporal_safety, &syncer_state);
@@ -126,6 +149,9 @@ pub fn run(config: ServiceConfig, opts: Opts) -> Result<(), Error> {
         }),
         connected: false,
         started: SystemTime::now(),
+        deadline: deadline.map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+        deadline_reached: false,
+        count_unconfirmed_funding,
         syncer_state,
         temporal_safety,
         enquirer: None,
@@ -136,6 +162,8 @@ pub fn run(config: ServiceConfig, opts: Opts) -> Result<(), Error> {
         latest_state_report: state_report,
         swap_state_machine,
         unhandled_peer_message: None, // The last message we received and was not handled by the state machine
+        monero_deposit: None,
+        paused_syncers: none!(),
     };
     let broker = false;
     Service::run(config, runtime, broker)
@@ -147,6 +175,13 @@ pub struct Runtime {
     pub peer_service: ServiceId,
     pub connected: bool,
     pub started: SystemTime,
+    /// Wall-clock time after which the swap is aborted if its funds are not locked yet, or
+    /// refunds otherwise
+    pub deadline: Option<SystemTime>,
+    pub deadline_reached: bool,
+    /// Whether the unconfirmed funds of the funding address count when checking the swap is
+    /// funded
+    pub count_unconfirmed_funding: bool,
     pub enquirer: Option<ServiceId>,
     pub syncer_state: SyncerState,
     pub temporal_safety: TemporalSafety,
@@ -157,9 +192,14 @@ pub struct Runtime {
     pub latest_state_report: StateReport,
     pub swap_state_machine: SwapStateMachine,
     pub unhandled_peer_message: Option<PeerMsg>,
+    /// Subaddress of the node Monero wallet dedicated to the swap
+    pub monero_deposit: Option<MoneroDeposit>,
+    /// Unhealthy syncers of the swap without a healthy fallback, their events are ignored until
+    /// farcasterd resumes the swap on them or fails it over
+    pub paused_syncers: HashSet<ServiceId>,
 }
 
-#[derive(Debug, Clone, Display, StrictEncode, StrictDecode)]
+#[derive(Debug, Clone, Display, StrictEncode)]
 #[display("checkpoint-swapd")]
 pub struct CheckpointSwapd {
     pub state: SwapStateMachine,
@@ -172,6 +212,58 @@ pub struct CheckpointSwapd {
     pub local_trade_role: TradeRole,
     pub connected_counterparty_node_id: Option<NodeId>,
     pub deal: Deal,
+    /// Wall-clock deadline of the swap as a unix timestamp in seconds, set once when the swap
+    /// starts
+    pub deadline: Option<u64>,
+    /// The syncer instances the swap runs on when checkpointed, restored on the same syncers
+    pub bitcoin_syncer_instance: SyncerInstance,
+    pub monero_syncer_instance: SyncerInstance,
+    /// Subaddress of the node Monero wallet dedicated to the swap and the height it is watched
+    /// from
+    pub monero_deposit: Option<MoneroDeposit>,
+    pub monero_deposit_from_height: Option<u64>,
+}
+
+impl StrictDecode for CheckpointSwapd {
+    fn strict_decode<D: std::io::Read>(mut d: D) -> Result<Self, strict_encoding::Error> {
+        let state = StrictDecode::strict_decode(&mut d)?;
+        let pending_msg = StrictDecode::strict_decode(&mut d)?;
+        let enquirer = StrictDecode::strict_decode(&mut d)?;
+        let xmr_addr_addendum = StrictDecode::strict_decode(&mut d)?;
+        let temporal_safety = StrictDecode::strict_decode(&mut d)?;
+        let txids = StrictDecode::strict_decode(&mut d)?;
+        let pending_broadcasts = StrictDecode::strict_decode(&mut d)?;
+        let local_trade_role = StrictDecode::strict_decode(&mut d)?;
+        let connected_counterparty_node_id = StrictDecode::strict_decode(&mut d)?;
+        let deal = StrictDecode::strict_decode(&mut d)?;
+        // checkpoints stored by older versions end before any of the following fields: their
+        // swaps have no deadline, run on the default syncers and have no deposit subaddress
+        let deadline = strict_decode_trailing::<Option<u64>, _>(&mut d)?.flatten();
+        let bitcoin_syncer_instance =
+            strict_decode_trailing::<SyncerInstance, _>(&mut d)?.unwrap_or_default();
+        let monero_syncer_instance =
+            strict_decode_trailing::<SyncerInstance, _>(&mut d)?.unwrap_or_default();
+        let monero_deposit = strict_decode_trailing::<Option<MoneroDeposit>, _>(&mut d)?.flatten();
+        let monero_deposit_from_height =
+            strict_decode_trailing::<Option<u64>, _>(&mut d)?.flatten();
+        Ok(Self {
+            state,
+            pending_msg,
+            enquirer,
+            xmr_addr_addendum,
+            temporal_safety,
+            txids,
+            pending_broadcasts,
+            local_trade_role,
+            connected_counterparty_node_id,
+            deal,
+            deadline,
+            bitcoin_syncer_instance,
+            monero_syncer_instance,
+            monero_deposit,
+            monero_deposit_from_height,
+        })
+    }
 }
 
 impl CtlServer for Runtime {}
@@ -361,10 +453,43 @@ impl Runtime {
                 std::process::exit(0);
             }
 
+            CtlMsg::SetLogFilter(filter) if source == ServiceId::Farcasterd => {
+                crate::logger::set_filter(filter.as_deref());
+            }
+
+            CtlMsg::Heartbeat if source == ServiceId::Farcasterd => {
+                endpoints.send_to(
+                    ServiceBus::Ctl,
+                    self.identity(),
+                    ServiceId::Farcasterd,
+                    BusMsg::Ctl(CtlMsg::Heartbeat),
+                )?;
+            }
+
             CtlMsg::Disconnected => {
                 self.connected = false;
             }
 
+            CtlMsg::GetXmrAddress(swap_id) => {
+                // Alice funds the Monero lock, Bob sweeps the bought Monero to his address
+                let address = match self.local_swap_role {
+                    SwapRole::Alice => self
+                        .syncer_state
+                        .xmr_addr_addendum
+                        .as_ref()
+                        .map(|addendum| addendum.address),
+                    SwapRole::Bob => self.swap_state_machine.target_monero_address(),
+                };
+                let reply = match address {
+                    Some(address) => CtlMsg::MoneroAddress(MoneroAddress(swap_id, address)),
+                    None => CtlMsg::Failure(Failure {
+                        code: FailureCode::Unknown,
+                        info: format!("The Monero address of swap {} is not known", swap_id),
+                    }),
+                };
+                self.send_client_ctl(endpoints, source, reply)?;
+            }
+
             CtlMsg::Reconnected => {
                 self.connected = true;
             }
@@ -373,9 +498,23 @@ impl Runtime {
             // maker launched swap after restoration and the taker reconnects,
             // after a manual connect call, or a new connection with the same
             // node address is established
-            CtlMsg::PeerdReconnected(service_id) => {
-                self.log_info(format!("Peer {} reconnected", service_id));
-                self.peer_service = service_id;
+            CtlMsg::UpdatePeerd(UpdatePeerd { swap_id, new_peerd })
+                if source == ServiceId::Farcasterd =>
+            {
+                if swap_id != self.swap_id() {
+                    self.log_error(format!(
+                        "Ignoring the peerd update of swap {}, not this swap",
+                        swap_id
+                    ));
+                    return Ok(());
+                }
+                if new_peerd != self.peer_service {
+                    self.log_info(format!(
+                        "Routing peer messages through {} instead of {}",
+                        new_peerd, self.peer_service
+                    ));
+                }
+                self.peer_service = new_peerd;
                 self.connected = true;
                 for msg in self.pending_peer_request.clone().iter() {
                     self.send_peer(endpoints, msg.clone())?;
@@ -383,6 +522,70 @@ impl Runtime {
                 self.pending_peer_request.clear();
             }
 
+            // Move the syncer tasks to the fallback syncer farcasterd failed over to
+            CtlMsg::UpdateSyncer(UpdateSyncer {
+                swap_id,
+                new_syncer,
+            }) if source == ServiceId::Farcasterd => {
+                if swap_id != self.swap_id() {
+                    self.log_error(format!(
+                        "Ignoring the syncer update of swap {}, not this swap",
+                        swap_id
+                    ));
+                    return Ok(());
+                }
+                self.syncer_state.switch_syncer(new_syncer, endpoints)?;
+                // a paused syncer replaced by the fallback no longer pauses the swap
+                let syncers = [
+                    self.syncer_state.bitcoin_syncer(),
+                    self.syncer_state.monero_syncer(),
+                ];
+                self.paused_syncers
+                    .retain(|syncer| syncers.contains(syncer));
+            }
+
+            CtlMsg::PauseSyncer(SwapSyncer { swap_id, syncer })
+                if source == ServiceId::Farcasterd =>
+            {
+                if swap_id != self.swap_id() {
+                    self.log_error(format!(
+                        "Ignoring the syncer pause of swap {}, not this swap",
+                        swap_id
+                    ));
+                    return Ok(());
+                }
+                if syncer == self.syncer_state.bitcoin_syncer()
+                    || syncer == self.syncer_state.monero_syncer()
+                {
+                    self.log_warn(format!(
+                        "Pausing the swap on the unhealthy syncer {}, its events are ignored until it recovers",
+                        syncer
+                    ));
+                    self.paused_syncers.insert(syncer);
+                }
+            }
+
+            CtlMsg::ResumeSyncer(SwapSyncer { swap_id, syncer })
+                if source == ServiceId::Farcasterd =>
+            {
+                if swap_id != self.swap_id() {
+                    self.log_error(format!(
+                        "Ignoring the syncer resume of swap {}, not this swap",
+                        swap_id
+                    ));
+                    return Ok(());
+                }
+                if self.paused_syncers.remove(&syncer) {
+                    self.log_info(format!(
+                        "Resuming the swap on the recovered syncer {}",
+                        syncer
+                    ));
+                    // the live tasks are issued to the syncer again, their events replay what
+                    // was ignored during the pause
+                    self.syncer_state.switch_syncer(syncer, endpoints)?;
+                }
+            }
+
             CtlMsg::FailedPeerMessage(msg) => {
                 self.log_warn(format!(
                     "Sending the peer message {} failed. Adding to pending peer requests",
@@ -401,12 +604,22 @@ impl Runtime {
                     xmr_addr_addendum,
                     local_trade_role,
                     state,
+                    deadline,
+                    monero_deposit,
+                    monero_deposit_from_height,
                     ..
                 } = state;
                 self.log_info("Restoring swap");
                 self.swap_state_machine = state;
                 self.enquirer = enquirer;
                 self.temporal_safety = temporal_safety;
+                // the deadline set when the swap started still holds after a restore
+                self.deadline =
+                    deadline.map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+                // the deposit subaddress is watched again from where it was, once the Monero
+                // height is known
+                self.monero_deposit = monero_deposit;
+                self.syncer_state.monero_deposit_from_height = monero_deposit_from_height;
                 // We need to update the peerd for the pending requests in case of reconnect
                 self.local_trade_role = local_trade_role;
                 self.syncer_state
@@ -459,6 +672,36 @@ impl Runtime {
                 if let Some(msg) = pending_msg {
                     self.send_peer(endpoints, msg)?;
                 }
+
+                let msg = format!(
+                    "Swap recovered from checkpoint in state {}",
+                    self.swap_state_machine
+                );
+                self.report_progress_message_log_fail(endpoints, msg);
+            }
+
+            CtlMsg::ExecuteRefund(_) | CtlMsg::ExecutePunish(_) => {
+                let tx_label = match request {
+                    CtlMsg::ExecuteRefund(_) => TxLabel::Refund,
+                    _ => TxLabel::Punish,
+                };
+                if self.swap_state_machine.recovery_tx_label() == Some(tx_label) {
+                    self.execute_state_machine(endpoints, BusMsg::Ctl(request), source)?;
+                } else {
+                    let msg = format!(
+                        "Cannot broadcast the {} transaction in state {}",
+                        tx_label, self.swap_state_machine
+                    );
+                    self.log_warn(&msg);
+                    self.send_client_ctl(
+                        endpoints,
+                        source,
+                        CtlMsg::Failure(Failure {
+                            code: FailureCode::Unknown,
+                            info: msg,
+                        }),
+                    )?;
+                }
             }
 
             req => {
@@ -495,6 +738,10 @@ impl Runtime {
                     local_trade_role: self.local_trade_role,
                     local_swap_role: self.deal.swap_role(&self.local_trade_role),
                     connected_counterparty_node_id: self.peer_service.node_id(),
+                    monero_deposit_address: self
+                        .monero_deposit
+                        .as_ref()
+                        .map(|deposit| deposit.address),
                 };
                 self.send_client_info(endpoints, source, InfoMsg::SwapInfo(info))?;
             }
@@ -516,6 +763,15 @@ impl Runtime {
         source: ServiceId,
         request: SyncMsg,
     ) -> Result<(), Error> {
+        if let SyncMsg::Event(ref event) = request {
+            if self.paused_syncers.contains(&source) {
+                self.log_debug(format!(
+                    "Ignoring the event {} of the paused syncer {}",
+                    event, source
+                ));
+                return Ok(());
+            }
+        }
         match request {
             SyncMsg::Event(ref event) if source == self.syncer_state.monero_syncer => {
                 match &event {
@@ -525,6 +781,7 @@ impl Runtime {
                             Blockchain::Monero,
                             endpoints,
                         );
+                        self.watch_monero_deposit(endpoints, *height)?;
                     }
 
                     Event::TransactionConfirmations(TransactionConfirmations {
@@ -548,6 +805,14 @@ impl Runtime {
                         }
                     }
 
+                    Event::OutpointCreationConfirmations(event) => {
+                        self.log_debug(event);
+                    }
+
+                    Event::OutpointSpendConfirmations(event) => {
+                        self.log_debug(event);
+                    }
+
                     Event::AddressTransaction(AddressTransaction { id, .. }) => {
                         // saving requests of interest for later replaying latest event
                         if let Some(txlabel) = self.syncer_state.tasks.watched_addrs.get(id) {
@@ -559,10 +824,44 @@ impl Runtime {
 
                     Event::SweepSuccess(_) => {}
 
+                    Event::SweepLocked(SweepLocked {
+                        confirmations_remaining,
+                        ..
+                    }) => self.log_info(format!(
+                        "Funds to sweep are locked for {} more confirmations",
+                        confirmations_remaining
+                    )),
+
                     Event::TaskAborted(_) => {}
 
                     Event::Empty(_) => {}
 
+                    Event::EstimatedTimeRemaining(event) => {
+                        self.log_debug(event);
+                    }
+
+                    Event::MoneroUnlocked(MoneroUnlocked { txid, .. }) => {
+                        self.log_info(format!("Monero transaction {} unlocked", txid))
+                    }
+
+                    Event::MoneroReceived(MoneroReceived {
+                        id,
+                        txid,
+                        amount,
+                        confirmations,
+                    }) if Some(*id) == self.syncer_state.tasks.watched_deposit => {
+                        if let Some(deposit) = &self.monero_deposit {
+                            let msg = format!(
+                                "Received {} on the Monero deposit subaddress {} in transaction {} ({} confirmations)",
+                                monero::Amount::from_pico(*amount),
+                                deposit.address,
+                                txid,
+                                confirmations.unwrap_or(0),
+                            );
+                            self.report_progress_message_log_fail(endpoints, msg);
+                        }
+                    }
+
                     event => {
                         self.log_error(format!("event not handled {}", event));
                     }
@@ -577,6 +876,7 @@ impl Runtime {
                             Blockchain::Bitcoin,
                             endpoints,
                         );
+                        self.check_deadline(endpoints)?;
                     }
 
                     // This re-triggers the tx fetch event in case the transaction was not detected yet
@@ -642,6 +942,14 @@ impl Runtime {
                         self.syncer_state.transaction_broadcasted(event);
                     }
 
+                    Event::BroadcastFailure(event) => {
+                        self.syncer_state.broadcast_failed(event);
+                    }
+
+                    Event::OutpointSpent(event) => {
+                        self.log_debug(event);
+                    }
+
                     Event::AddressTransaction(AddressTransaction { id, .. }) => {
                         // saving requests of interest for later replaying latest event
                         if let Some(txlabel) = self.syncer_state.tasks.watched_addrs.get(id) {
@@ -660,6 +968,10 @@ impl Runtime {
                         self.log_debug(event);
                     }
 
+                    Event::SweepLocked(event) => {
+                        self.log_debug(event);
+                    }
+
                     Event::TransactionRetrieved(event) => {
                         self.log_debug(event);
                     }
@@ -674,10 +986,64 @@ impl Runtime {
                     Event::Empty(_) => self.log_debug("empty event not handled for Bitcoin"),
 
                     Event::HealthResult(_) => self.log_debug("ignoring health result in swapd"),
+
+                    Event::FeeHistogram(event) => {
+                        self.log_debug(event);
+                    }
+
+                    Event::EstimatedTimeRemaining(event) => {
+                        self.log_debug(event);
+                    }
+
+                    Event::Unsupported(id) => {
+                        self.log_debug(format!("task {} unsupported by the bitcoin syncer", id))
+                    }
+
+                    Event::MoneroReceived(event) => self.log_error(format!(
+                        "unexpected event from the bitcoin syncer {}",
+                        event
+                    )),
+
+                    Event::MoneroUnlocked(event) => self.log_error(format!(
+                        "unexpected event from the bitcoin syncer {}",
+                        event
+                    )),
+
+                    Event::BackendStatus(_) => self.log_debug("ignoring backend status in swapd"),
+                    Event::BlockData(event) => self.log_debug(event),
+                    Event::Balance(event) => self.log_debug(event),
+                    Event::TransactionFeeStale(event) => self.log_warn(format!(
+                        "{} is likely stuck, its fee rate is below the estimation",
+                        event
+                    )),
+                    Event::FeeBumped(event) => self.log_info(event),
+                    Event::SeenInMempool(event) => {
+                        // the early sighting of a counterparty transaction is reported to the
+                        // client, the swap only moves on its confirmations
+                        match self.syncer_state.tasks.watched_txs.get(&event.id).copied() {
+                            Some(tx_label) => self.report_progress_message_log_fail(
+                                endpoints,
+                                format!(
+                                    "{} transaction ({}) seen in the mempool, not confirmed yet \
+                                    and may still be evicted or replaced",
+                                    tx_label.label(),
+                                    event.txid
+                                ),
+                            ),
+                            None => self.log_debug(format!("{} of an unknown task", event)),
+                        }
+                    }
                 };
             }
             _ => {}
         }
+        if let SyncMsg::Event(ref event) = request {
+            if source == self.syncer_state.bitcoin_syncer
+                || source == self.syncer_state.monero_syncer
+            {
+                self.syncer_state.forget_completed_task(event);
+            }
+        }
         self.execute_state_machine(endpoints, BusMsg::Sync(request), source)?;
 
         Ok(())
@@ -685,6 +1051,52 @@ impl Runtime {
 }
 
 impl Runtime {
+    /// Checked on every new bitcoin block. Once the deadline passed, aborts the swap if its funds
+    /// are not locked yet. Otherwise the swap stops short of the buy: Alice no longer requests
+    /// the Monero funding and Bob withholds the buy signature, the swap refunds once the cancel
+    /// timelock expires.
+    fn check_deadline(&mut self, endpoints: &mut Endpoints) -> Result<(), Error> {
+        match self.deadline {
+            Some(deadline) if !self.deadline_reached && SystemTime::now() >= deadline => {
+                self.deadline_reached = true;
+            }
+            _ => return Ok(()),
+        }
+        if self.swap_state_machine.abortable() {
+            let msg = "Swap deadline reached before the funds were locked, aborting the swap";
+            self.log_warn(msg);
+            self.report_progress_message_log_fail(endpoints, msg);
+            // the abort is requested on behalf of farcasterd, as on a ban
+            self.execute_state_machine(
+                endpoints,
+                BusMsg::Ctl(CtlMsg::AbortSwap),
+                ServiceId::Farcasterd,
+            )?;
+        } else if !matches!(self.swap_state_machine, SwapStateMachine::SwapEnd(_)) {
+            let msg = format!(
+                "Swap deadline reached with the funds locked, the swap no longer proceeds to the buy and refunds once the cancel timelock of {} blocks expires",
+                self.temporal_safety.cancel_timelock
+            );
+            self.log_warn(&msg);
+            self.report_progress_message_log_fail(endpoints, msg);
+            if self.syncer_state.awaiting_funding
+                && matches!(
+                    self.swap_state_machine,
+                    SwapStateMachine::AliceArbitratingLockFinal(_)
+                )
+            {
+                self.syncer_state.awaiting_funding = false;
+                endpoints.send_to(
+                    ServiceBus::Ctl,
+                    self.identity(),
+                    ServiceId::Farcasterd,
+                    BusMsg::Ctl(CtlMsg::FundingCanceled(Blockchain::Monero)),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
     fn execute_state_machine(
         &mut self,
         endpoints: &mut Endpoints,
@@ -698,7 +1110,9 @@ impl Runtime {
             msg.clone(),
             self.swap_state_machine.clone(),
         )? {
+            let from = self.swap_state_machine.to_string();
             self.swap_state_machine = ssm;
+            self.report_state_change(endpoints, from)?;
             // On SwapEnd, report immediately to ensure the progress message goes out before the swap is terminated, then let farcasterd know of the outcome.
             if let SwapStateMachine::SwapEnd(outcome) = &self.swap_state_machine {
                 let outcome = outcome.clone(); // so we don't borrow self anymore
@@ -736,6 +1150,36 @@ impl Runtime {
         Ok(())
     }
 
+    fn report_state_change(
+        &mut self,
+        endpoints: &mut Endpoints,
+        from: String,
+    ) -> Result<(), Error> {
+        let to = self.swap_state_machine.to_string();
+        if from == to {
+            return Ok(());
+        }
+        let outcome = match &self.swap_state_machine {
+            SwapStateMachine::SwapEnd(outcome) => Some(outcome.clone()),
+            _ => None,
+        };
+        let at = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.send_ctl(
+            endpoints,
+            ServiceId::Farcasterd,
+            BusMsg::Ctl(CtlMsg::SwapStateChanged(SwapStateChanged {
+                swap_id: self.swap_id,
+                from,
+                to,
+                at,
+                outcome,
+            })),
+        )
+    }
+
     fn report_potential_state_change(&mut self, endpoints: &mut Endpoints) -> Result<(), Error> {
         // Generate a new state report for the clients
         let new_state_report = StateReport::new(
@@ -753,6 +1197,33 @@ impl Runtime {
         Ok(())
     }
 
+    /// Watch the Monero deposit subaddress of the swap if not watched yet, from the height it
+    /// was watched from before a restore or from the current height
+    fn watch_monero_deposit(
+        &mut self,
+        endpoints: &mut Endpoints,
+        height: u64,
+    ) -> Result<(), Error> {
+        let deposit = match &self.monero_deposit {
+            Some(deposit) if self.syncer_state.tasks.watched_deposit.is_none() => deposit.clone(),
+            _ => return Ok(()),
+        };
+        let from_height = self
+            .syncer_state
+            .monero_deposit_from_height
+            .unwrap_or(height);
+        let task = self
+            .syncer_state
+            .watch_monero_deposit(&deposit, from_height);
+        endpoints.send_to(
+            ServiceBus::Sync,
+            self.identity(),
+            self.syncer_state.monero_syncer(),
+            BusMsg::Sync(SyncMsg::Task(task)),
+        )?;
+        Ok(())
+    }
+
     pub fn checkpoint_state(
         &mut self,
         endpoints: &mut Endpoints,
@@ -776,6 +1247,26 @@ impl Runtime {
                     local_trade_role: self.local_trade_role,
                     connected_counterparty_node_id: self.peer_service.node_id(),
                     deal: self.deal.clone(),
+                    deadline: self.deadline.and_then(|deadline| {
+                        deadline
+                            .duration_since(SystemTime::UNIX_EPOCH)
+                            .ok()
+                            .map(|since| since.as_secs())
+                    }),
+                    monero_deposit: self.monero_deposit.clone(),
+                    monero_deposit_from_height: self.syncer_state.monero_deposit_from_height,
+                    bitcoin_syncer_instance: self
+                        .syncer_state
+                        .bitcoin_syncer
+                        .syncer_instance()
+                        .cloned()
+                        .unwrap_or_default(),
+                    monero_syncer_instance: self
+                        .syncer_state
+                        .monero_syncer
+                        .syncer_instance()
+                        .cloned()
+                        .unwrap_or_default(),
                 },
             })),
         )?;
@@ -807,8 +1298,8 @@ impl Runtime {
         let acc_confs_needs = self
             .syncer_state
             .get_confs(TxLabel::AccLock)
-            .map(|confs| SWEEP_MONERO_THRESHOLD.saturating_sub(confs))
-            .unwrap_or(SWEEP_MONERO_THRESHOLD);
+            .map(|confs| self.syncer_state.monero_unlock_depth.saturating_sub(confs))
+            .unwrap_or(self.syncer_state.monero_unlock_depth);
         let sweep_block = self.syncer_state.height(Blockchain::Monero) + acc_confs_needs as u64;
         self.log_info(format!(
             "Tx {} needs {} more confirmations to spending maturity, and has {} confirmations.\n\
@@ -832,3 +1323,66 @@ impl Runtime {
         }
     }
 }
+
+#[test]
+fn decode_checkpoint_without_appended_fields() {
+    use std::str::FromStr;
+
+    let deal = Deal::from_str("Deal:Cke4ftrP5A781Vq85dgBQJNwYgBS4nuUV1LQM2fvVdFMNR4h5TrWhRR11111uMFuZTAsNgpdK8DiK11111TB9zym113GTvtvqfD1111114A4TTfFfmZoWyvpcjDBtTZCdWFSUWcRKYfEC3Y17hqaXZ3dWz11111111111111111111111111111111111111111AfZ113SEBTEspU3a").unwrap();
+    let temporal_safety = TemporalSafety {
+        cancel_timelock: 10,
+        punish_timelock: 20,
+        safety: 3,
+        arb_finality: 1,
+        acc_finality: 1,
+    };
+
+    // checkpoint layout of the versions preceding the swap deadline
+    let mut encoded = vec![];
+    SwapStateMachine::StartTaker(SwapRole::Alice)
+        .strict_encode(&mut encoded)
+        .unwrap();
+    None::<PeerMsg>.strict_encode(&mut encoded).unwrap();
+    None::<ServiceId>.strict_encode(&mut encoded).unwrap();
+    None::<XmrAddressAddendum>
+        .strict_encode(&mut encoded)
+        .unwrap();
+    temporal_safety.strict_encode(&mut encoded).unwrap();
+    Vec::<(TxLabel, Txid)>::new()
+        .strict_encode(&mut encoded)
+        .unwrap();
+    Vec::<(bitcoin::Transaction, TxLabel)>::new()
+        .strict_encode(&mut encoded)
+        .unwrap();
+    TradeRole::Taker.strict_encode(&mut encoded).unwrap();
+    None::<NodeId>.strict_encode(&mut encoded).unwrap();
+    deal.strict_encode(&mut encoded).unwrap();
+
+    let checkpoint = CheckpointSwapd::strict_deserialize(&encoded).unwrap();
+    assert_eq!(checkpoint.deal, deal);
+    assert_eq!(checkpoint.deadline, None);
+    assert_eq!(checkpoint.bitcoin_syncer_instance, SyncerInstance::DEFAULT);
+    assert_eq!(checkpoint.monero_syncer_instance, SyncerInstance::DEFAULT);
+    assert!(checkpoint.monero_deposit.is_none());
+    assert_eq!(checkpoint.monero_deposit_from_height, None);
+
+    // checkpoint layout with the deadline but without syncer instances
+    Some(1000u64).strict_encode(&mut encoded).unwrap();
+    let checkpoint = CheckpointSwapd::strict_deserialize(&encoded).unwrap();
+    assert_eq!(checkpoint.deadline, Some(1000));
+    assert_eq!(checkpoint.bitcoin_syncer_instance, SyncerInstance::DEFAULT);
+
+    // the current layout round trips
+    let mut checkpoint = checkpoint;
+    checkpoint.bitcoin_syncer_instance = SyncerInstance(Some("trusted".to_string()));
+    checkpoint.monero_deposit_from_height = Some(2000);
+    let decoded =
+        CheckpointSwapd::strict_deserialize(&checkpoint.strict_serialize().unwrap()).unwrap();
+    assert_eq!(decoded.deadline, Some(1000));
+    assert_eq!(
+        decoded.bitcoin_syncer_instance,
+        SyncerInstance(Some("trusted".to_string()))
+    );
+    assert_eq!(decoded.monero_syncer_instance, SyncerInstance::DEFAULT);
+    assert_eq!(decoded.monero_deposit_from_height, Some(2000));
+}
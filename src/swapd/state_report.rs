@@ -8,7 +8,6 @@ use farcaster_core::{blockchain::Blockchain, transaction::TxLabel};
 use strict_encoding::{NetworkDecode, NetworkEncode};
 
 use crate::bus::{Progress, StateTransition};
-use crate::swapd::temporal_safety::SWEEP_MONERO_THRESHOLD;
 
 use super::{syncer_client::SyncerState, temporal_safety::TemporalSafety};
 
@@ -77,7 +76,7 @@ impl StateReport {
                 .map(|confs| temp_safety.blocks_until_punish_after_cancel(confs)),
             blocks_until_safe_monero_buy_sweep: syncer_state
                 .get_confs(TxLabel::AccLock)
-                .map(|c| SWEEP_MONERO_THRESHOLD.saturating_sub(c)),
+                .map(|c| syncer_state.monero_unlock_depth.saturating_sub(c)),
         }
     }
 
@@ -39,6 +39,7 @@ use crate::{
         AddressSecretKey, BitcoinSecretKeyInfo, MoneroSecretKeyInfo,
     },
     event::Event,
+    monero_network,
     service::SwapLogging,
     syncerd::{SweepBitcoinAddress, SweepMoneroAddress},
     Error, LogStyle, ServiceId,
@@ -154,7 +155,14 @@ impl AliceSwapKeyManager {
             ..
         } = self;
         runtime.log_trace(format!("Verifying with Bob params: {}", parameters));
-        remote_commit.verify_with_reveal(&CommitmentEngine, parameters.clone())?;
+        remote_commit
+            .verify_with_reveal(&CommitmentEngine, parameters.clone())
+            .map_err(|err| {
+                Error::Farcaster(format!(
+                    "Revealed parameters from Bob do not match his commitment: {}.",
+                    err
+                ))
+            })?;
         let proof = parameters.proof.clone();
         let remote_params_candidate: Parameters = parameters.into_parameters();
         let proof_verification = key_manager.verify_proof(
@@ -163,9 +171,10 @@ impl AliceSwapKeyManager {
             proof,
         );
         if let Err(err) = proof_verification {
-            let msg = format!("DLEQ proof from Bob is invalid: {}.", err);
-            runtime.log_error(&msg);
-            return Err(Error::Farcaster(msg));
+            return Err(Error::Farcaster(format!(
+                "DLEQ proof from Bob is invalid: {}.",
+                err
+            )));
         }
         runtime.log_info("DLEQ proof from Bob successfully verified.");
 
@@ -247,7 +256,7 @@ impl AliceSwapKeyManager {
             "Full secret monero view key: {}",
             view.bright_green_bold()
         ));
-        let network = runtime.deal.parameters.network.into();
+        let network = monero_network(runtime.deal.parameters.network);
         let keypair = monero::KeyPair { view, spend };
         let corresponding_address = monero::Address::from_keypair(network, &keypair);
         runtime.log_info(format!(
@@ -443,6 +452,7 @@ impl BobSwapKeyManager {
             source_secret_key,
             source_address,
             destination_address,
+            fee_sat_per_kvb: None,
         })
     }
 
@@ -559,7 +569,7 @@ impl BobSwapKeyManager {
             "Full secret monero view key: {}",
             view.bright_green_bold()
         ));
-        let network = runtime.deal.parameters.network.into();
+        let network = monero_network(runtime.deal.parameters.network);
         let keypair = monero::KeyPair { view, spend };
         let corresponding_address = monero::Address::from_keypair(network, &keypair);
         runtime.log_info(format!(
@@ -602,7 +612,14 @@ impl BobSwapKeyManager {
         } = self;
         runtime.log_trace(format!("Verifying with Alice params: {}", parameters));
         let proof = parameters.proof.clone();
-        remote_commit.verify_with_reveal(&CommitmentEngine, parameters.clone())?;
+        remote_commit
+            .verify_with_reveal(&CommitmentEngine, parameters.clone())
+            .map_err(|err| {
+                Error::Farcaster(format!(
+                    "Revealed parameters from Alice do not match her commitment: {}.",
+                    err
+                ))
+            })?;
         let remote_params_candidate: Parameters = parameters.into_parameters();
         let proof_verification = key_manager.verify_proof(
             &remote_params_candidate.spend,
@@ -610,9 +627,10 @@ impl BobSwapKeyManager {
             proof,
         );
         if let Err(err) = proof_verification {
-            let msg = format!("DLEQ proof from Alice is invalid: {}.", err);
-            runtime.log_error(&msg);
-            return Err(Error::Farcaster(msg));
+            return Err(Error::Farcaster(format!(
+                "DLEQ proof from Alice is invalid: {}.",
+                err
+            )));
         }
         runtime.log_info("DLEQ proof from Alice successfully verified.");
 
@@ -27,7 +27,7 @@ use crate::{
     syncerd::{bitcoin_syncer::p2wpkh_signed_tx_fee, AddressTransaction},
 };
 use crate::{bus::ctl::MoneroFundingInfo, service::SwapLogging};
-use crate::{bus::p2p::Reveal, swapd::temporal_safety::SWEEP_MONERO_THRESHOLD};
+use crate::{bus::p2p::Reveal, syncerd::MoneroUnlocked};
 use crate::{
     bus::{
         ctl::{CtlMsg, InitMakerSwap, InitTakerSwap},
@@ -36,7 +36,10 @@ use crate::{
     },
     event::{Event, StateMachine},
     service::Reporter,
-    syncerd::{FeeEstimation, FeeEstimations, SweepAddress, TaskAborted, Txid},
+    syncerd::{
+        Balance, FeeEstimation, FeeEstimations, OutpointSpent, SweepAddress, SweepAddressAddendum,
+        TaskAborted, Txid,
+    },
     ServiceId,
 };
 use crate::{
@@ -45,12 +48,12 @@ use crate::{
         info::InfoMsg,
     },
     swapd::swap_key_manager::{HandleBuyProcedureSignatureRes, HandleRefundProcedureSignaturesRes},
-    syncerd::{SweepSuccess, Task, TransactionConfirmations},
+    syncerd::{BroadcastFailure, SweepSuccess, Task, TransactionConfirmations},
     Endpoints, Error,
 };
 use crate::{
     bus::{sync::SyncMsg, Outcome},
-    LogStyle,
+    monero_network, LogStyle,
 };
 use crate::{
     swapd::swap_key_manager::HandleCoreArbitratingSetupRes, syncerd::types::Event as SyncEvent,
@@ -209,7 +212,8 @@ pub enum SwapStateMachine {
     #[display("Bob Funded")]
     BobFunded(BobFunded),
     // BobRefundProcedureSignatures state - transitions to BobAccordantLock on event
-    // AddressTransaction, or BobCanceled on event TransactionConfirmations.
+    // AddressTransaction, BobCanceled on event TransactionConfirmations, or
+    // BobAbortAwaitingBitcoinSweep on event BroadcastFailure of the Lock.
     // Watches Monero transaction, aborts Monero AddressTransaction task.
     #[display("Bob Refund Procedure Signatures")]
     BobRefundProcedureSignatures(BobRefundProcedureSignatures),
@@ -225,7 +229,7 @@ pub enum SwapStateMachine {
     #[display("Bob Accordant Lock Final")]
     BobAccordantLockFinal(BobAccordantLockFinal),
     // BobBuySeen state - transitions to BobBuySweeping on event
-    // TransactionConfirmations. Sends sweep Monero to Monero syncer.
+    // MoneroUnlocked. Sends sweep Monero to Monero syncer.
     #[display("Bob Buy Seen")]
     BobBuySeen(SweepAddress),
     // BobBuySweeping state - transitions to SwapEnd on request SweepSuccess.
@@ -297,7 +301,7 @@ pub enum SwapStateMachine {
     #[display("Alice Cancel")]
     AliceCanceled(AliceCanceled),
     // AliceRefund state - transitions to AliceRefundSweeping on event
-    // TransactionConfirmations. Submits sweep Monero address task.
+    // MoneroUnlocked. Submits sweep Monero address task.
     #[display("Alice Refund")]
     AliceRefund(SweepAddress),
     // AliceRefundSweeping state - transitions to SwapEnd on event SweepSuccess.
@@ -361,6 +365,8 @@ pub struct BobFeeEstimated {
     required_funding_amount: bitcoin::Amount,
     remote_params: Parameters,
     swap_key_manager: BobSwapKeyManager,
+    // Funding transaction received, kept until the balance of the funding address is checked
+    funding_tx: Option<bitcoin::Transaction>,
 }
 
 #[derive(Clone, Debug, StrictEncode, StrictDecode)]
@@ -570,6 +576,78 @@ impl StateMachine<Runtime, Error> for SwapStateMachine {
     }
 }
 
+impl SwapStateMachine {
+    /// Whether the swap can still be aborted, i.e. no funds are locked yet
+    pub fn abortable(&self) -> bool {
+        matches!(
+            self,
+            SwapStateMachine::StartTaker(_)
+                | SwapStateMachine::StartMaker(_)
+                | SwapStateMachine::BobInitMaker(_)
+                | SwapStateMachine::AliceInitMaker(_)
+                | SwapStateMachine::BobInitTaker(_)
+                | SwapStateMachine::AliceInitTaker(_)
+                | SwapStateMachine::BobTakerMakerCommit(_)
+                | SwapStateMachine::AliceTakerMakerCommit(_)
+                | SwapStateMachine::BobReveal(_)
+                | SwapStateMachine::BobFeeEstimated(_)
+                | SwapStateMachine::BobFunded(_)
+                | SwapStateMachine::AliceReveal(_)
+        )
+    }
+
+    /// The timelock path transaction the operator can request to broadcast in this state
+    pub fn recovery_tx_label(&self) -> Option<TxLabel> {
+        match self {
+            SwapStateMachine::BobCanceled(_) | SwapStateMachine::BobCancelFinal => {
+                Some(TxLabel::Refund)
+            }
+            SwapStateMachine::AliceCanceled(_) => Some(TxLabel::Punish),
+            _ => None,
+        }
+    }
+
+    /// The address Bob sweeps the bought Monero to, until the sweep is underway
+    pub fn target_monero_address(&self) -> Option<monero::Address> {
+        match self {
+            SwapStateMachine::BobInitMaker(BobInitMaker {
+                swap_key_manager, ..
+            })
+            | SwapStateMachine::BobInitTaker(BobInitTaker {
+                swap_key_manager, ..
+            })
+            | SwapStateMachine::BobTakerMakerCommit(BobTakerMakerCommit {
+                swap_key_manager, ..
+            })
+            | SwapStateMachine::BobReveal(BobReveal {
+                swap_key_manager, ..
+            })
+            | SwapStateMachine::BobFeeEstimated(BobFeeEstimated {
+                swap_key_manager, ..
+            })
+            | SwapStateMachine::BobFunded(BobFunded {
+                swap_key_manager, ..
+            })
+            | SwapStateMachine::BobRefundProcedureSignatures(BobRefundProcedureSignatures {
+                swap_key_manager,
+                ..
+            })
+            | SwapStateMachine::BobAccordantLock(BobAccordantLock {
+                swap_key_manager, ..
+            })
+            | SwapStateMachine::BobAccordantLockFinal(BobAccordantLockFinal {
+                swap_key_manager,
+                ..
+            }) => Some(swap_key_manager.target_monero_address),
+            SwapStateMachine::BobBuySeen(SweepAddress {
+                addendum: SweepAddressAddendum::Monero(sweep),
+                ..
+            }) => Some(sweep.destination_address),
+            _ => None,
+        }
+    }
+}
+
 pub struct SwapStateMachineExecutor {}
 impl SwapStateMachineExecutor {
     pub fn execute(
@@ -624,6 +702,7 @@ fn attempt_transition_to_init_taker(
             ref key_manager,
             ref target_bitcoin_address,
             target_monero_address,
+            ref monero_deposit,
         })) => {
             if ServiceId::Swap(swap_id) != runtime.identity {
                 runtime.log_error(format!(
@@ -646,6 +725,7 @@ fn attempt_transition_to_init_taker(
                 runtime.connected = true;
             }
             runtime.enquirer = Some(report_to.clone());
+            runtime.monero_deposit = Some(monero_deposit.clone());
 
             match swap_role {
                 SwapRole::Bob => {
@@ -749,6 +829,7 @@ fn attempt_transition_to_init_maker(
             swap_id: _,
             target_bitcoin_address,
             target_monero_address,
+            monero_deposit,
             commit: remote_commit,
         })) => {
             // start watching block height changes
@@ -763,6 +844,7 @@ fn attempt_transition_to_init_maker(
                 runtime.connected = true;
             }
             runtime.enquirer = Some(report_to);
+            runtime.monero_deposit = Some(monero_deposit);
 
             match swap_role {
                 SwapRole::Bob => {
@@ -1048,6 +1130,7 @@ fn try_bob_reveal_to_bob_fee_estimated(
                 remote_params,
                 swap_key_manager,
                 required_funding_amount,
+                funding_tx: None,
             })))
         }
         BusMsg::Ctl(CtlMsg::AbortSwap) => handle_bob_abort_swap(event, runtime, swap_key_manager),
@@ -1062,8 +1145,9 @@ fn try_bob_fee_estimated_to_bob_funded(
 ) -> Result<Option<SwapStateMachine>, Error> {
     let BobFeeEstimated {
         remote_params,
-        mut swap_key_manager,
+        swap_key_manager,
         required_funding_amount,
+        funding_tx,
     } = bob_reveal;
     match &event.request {
         BusMsg::Sync(SyncMsg::Event(SyncEvent::AddressTransaction(AddressTransaction {
@@ -1091,74 +1175,164 @@ fn try_bob_fee_estimated_to_bob_funded(
                 runtime.log_error(&msg);
                 runtime.report_progress_message(event.endpoints, msg)?;
                 return handle_bob_abort_swap(event, runtime, swap_key_manager);
+            }
+
+            // the balance of the funding address must cover the swap before committing to it
+            let funding_address = swap_key_manager
+                .funding_address()
+                .expect("Am Bob, so have funding address");
+            let task = runtime.syncer_state.funding_balance_btc(funding_address);
+            event.send_sync_service(runtime.syncer_state.bitcoin_syncer(), SyncMsg::Task(task))?;
+            Ok(Some(SwapStateMachine::BobFeeEstimated(BobFeeEstimated {
+                remote_params,
+                swap_key_manager,
+                required_funding_amount,
+                funding_tx: Some(tx),
+            })))
+        }
+        BusMsg::Sync(SyncMsg::Event(SyncEvent::Balance(Balance {
+            id,
+            confirmed,
+            unconfirmed,
+        }))) if runtime.syncer_state.tasks.funding_balance == Some(*id) => {
+            runtime.syncer_state.tasks.funding_balance = None;
+            let tx = match funding_tx {
+                Some(tx) => tx,
+                None => return Ok(None),
+            };
+            let confirmed = bitcoin::Amount::from_sat(*confirmed);
+            let balance = confirmed + bitcoin::Amount::from_sat(*unconfirmed);
+            let available = if runtime.count_unconfirmed_funding {
+                balance
             } else {
-                // funding completed, amount is correct
+                confirmed
+            };
+            if available >= required_funding_amount {
                 event.send_ctl_service(
                     ServiceId::Farcasterd,
                     CtlMsg::FundingCompleted(Blockchain::Bitcoin),
                 )?;
+                return bob_commit_funding(event, runtime, remote_params, swap_key_manager, tx);
             }
-
-            // process tx with swap_key_manager
-            swap_key_manager.process_funding_tx(runtime, Tx::Funding(tx))?;
-            let core_arbitrating_setup =
-                swap_key_manager.create_core_arb(runtime, &remote_params)?;
-
-            // register a watch task for arb lock, cancel, and refund
-            for (&tx, tx_label) in [
-                &core_arbitrating_setup.lock,
-                &core_arbitrating_setup.cancel,
-                &core_arbitrating_setup.refund,
-            ]
-            .iter()
-            .zip([TxLabel::Lock, TxLabel::Cancel, TxLabel::Refund])
-            {
-                runtime.log_debug(format!("register watch {} tx", tx_label.label()));
-                let txid = tx.clone().extract_tx().txid();
-                let task = runtime.syncer_state.watch_tx_btc(txid, tx_label);
-                event.send_sync_service(
-                    runtime.syncer_state.bitcoin_syncer(),
-                    SyncMsg::Task(task),
+            if balance >= required_funding_amount {
+                // only the confirmation is missing, the balance is checked again on the next block
+                runtime.log_info(format!(
+                    "Funding of {} received, waiting for it to confirm",
+                    required_funding_amount.bright_green_bold()
+                ));
+                return Ok(Some(SwapStateMachine::BobFeeEstimated(BobFeeEstimated {
+                    remote_params,
+                    swap_key_manager,
+                    required_funding_amount,
+                    funding_tx: Some(tx),
+                })));
+            }
+            let msg = format!("Insufficient funding. The funding address holds {}, the swap requires {}. Do not fund this swap anymore, will abort and attempt to sweep the Bitcoin to the provided address.", balance, required_funding_amount);
+            runtime.log_error(&msg);
+            if let Some(dest) = runtime.report_to() {
+                event.send_ctl_service(
+                    dest,
+                    CtlMsg::Failure(Failure {
+                        code: FailureCode::InsufficientFunds,
+                        info: msg,
+                    }),
                 )?;
             }
-
-            // Set the monero address creation height for Bob before setting the first checkpoint
-            let acc_lock_height_lower_bound =
-                runtime.temporal_safety.block_height_reorg_lower_bound(
-                    Blockchain::Monero,
-                    runtime.syncer_state.height(Blockchain::Monero),
-                );
-
-            // checkpoint swap pre lock bob
-            runtime.log_debug("checkpointing bob pre lock state");
-            // transition to new state
-            let new_ssm = SwapStateMachine::BobFunded(BobFunded {
-                remote_params,
-                core_arbitrating_setup: core_arbitrating_setup.clone(),
-                swap_key_manager,
-                acc_lock_height_lower_bound,
-            });
-            runtime.checkpoint_state(
-                event.endpoints,
-                Some(PeerMsg::CoreArbitratingSetup(
-                    core_arbitrating_setup.clone(),
-                )),
-                new_ssm.clone(),
-            )?;
-
-            // send the message to counter-party
-            runtime.log_debug("sending core arb setup to peer");
-            runtime.send_peer(
-                event.endpoints,
-                PeerMsg::CoreArbitratingSetup(core_arbitrating_setup),
-            )?;
-            Ok(Some(new_ssm))
+            handle_bob_abort_swap(event, runtime, swap_key_manager)
+        }
+        // the balance is queried again on the next block if the syncer could not retrieve it or
+        // the funding was not confirmed yet
+        BusMsg::Sync(SyncMsg::Event(SyncEvent::TaskAborted(TaskAborted { id, error })))
+            if runtime
+                .syncer_state
+                .tasks
+                .funding_balance
+                .map_or(false, |task_id| id.contains(&task_id)) =>
+        {
+            runtime.log_warn(format!(
+                "Failed to retrieve the balance of the funding address: {}",
+                error.as_deref().unwrap_or("unknown error")
+            ));
+            runtime.syncer_state.tasks.funding_balance = None;
+            Ok(None)
+        }
+        BusMsg::Sync(SyncMsg::Event(SyncEvent::HeightChanged(_)))
+            if event.source == runtime.syncer_state.bitcoin_syncer()
+                && funding_tx.is_some()
+                && runtime.syncer_state.tasks.funding_balance.is_none() =>
+        {
+            let funding_address = swap_key_manager
+                .funding_address()
+                .expect("Am Bob, so have funding address");
+            let task = runtime.syncer_state.funding_balance_btc(funding_address);
+            event.send_sync_service(runtime.syncer_state.bitcoin_syncer(), SyncMsg::Task(task))?;
+            Ok(None)
         }
         BusMsg::Ctl(CtlMsg::AbortSwap) => handle_bob_abort_swap(event, runtime, swap_key_manager),
         _ => Ok(None),
     }
 }
 
+/// Commit to the swap once its funding is checked: build the core arbitrating setup from the
+/// funding transaction, checkpoint and send it to the counterparty
+fn bob_commit_funding(
+    mut event: Event,
+    runtime: &mut Runtime,
+    remote_params: Parameters,
+    mut swap_key_manager: BobSwapKeyManager,
+    tx: bitcoin::Transaction,
+) -> Result<Option<SwapStateMachine>, Error> {
+    // process tx with swap_key_manager
+    swap_key_manager.process_funding_tx(runtime, Tx::Funding(tx))?;
+    let core_arbitrating_setup = swap_key_manager.create_core_arb(runtime, &remote_params)?;
+
+    // register a watch task for arb lock, cancel, and refund
+    for (&tx, tx_label) in [
+        &core_arbitrating_setup.lock,
+        &core_arbitrating_setup.cancel,
+        &core_arbitrating_setup.refund,
+    ]
+    .iter()
+    .zip([TxLabel::Lock, TxLabel::Cancel, TxLabel::Refund])
+    {
+        runtime.log_debug(format!("register watch {} tx", tx_label.label()));
+        let txid = tx.clone().extract_tx().txid();
+        let task = runtime.syncer_state.watch_tx_btc(txid, tx_label);
+        event.send_sync_service(runtime.syncer_state.bitcoin_syncer(), SyncMsg::Task(task))?;
+    }
+
+    // Set the monero address creation height for Bob before setting the first checkpoint
+    let acc_lock_height_lower_bound = runtime.temporal_safety.block_height_reorg_lower_bound(
+        Blockchain::Monero,
+        runtime.syncer_state.height(Blockchain::Monero),
+    );
+
+    // checkpoint swap pre lock bob
+    runtime.log_debug("checkpointing bob pre lock state");
+    // transition to new state
+    let new_ssm = SwapStateMachine::BobFunded(BobFunded {
+        remote_params,
+        core_arbitrating_setup: core_arbitrating_setup.clone(),
+        swap_key_manager,
+        acc_lock_height_lower_bound,
+    });
+    runtime.checkpoint_state(
+        event.endpoints,
+        Some(PeerMsg::CoreArbitratingSetup(
+            core_arbitrating_setup.clone(),
+        )),
+        new_ssm.clone(),
+    )?;
+
+    // send the message to counter-party
+    runtime.log_debug("sending core arb setup to peer");
+    runtime.send_peer(
+        event.endpoints,
+        PeerMsg::CoreArbitratingSetup(core_arbitrating_setup),
+    )?;
+    Ok(Some(new_ssm))
+}
+
 fn try_bob_funded_to_bob_refund_procedure_signature(
     mut event: Event,
     runtime: &mut Runtime,
@@ -1186,7 +1360,7 @@ fn try_bob_funded_to_bob_refund_procedure_signature(
             // Process params, aggregate and watch xmr address
             let (spend, view) = swap_key_manager.aggregate_xmr_spend_view(&remote_params);
             let address = monero::Address::from_viewpair(
-                runtime.syncer_state.network.into(),
+                monero_network(runtime.syncer_state.network),
                 &ViewPair { spend, view },
             );
             let txlabel = TxLabel::AccLock;
@@ -1198,13 +1372,14 @@ fn try_bob_funded_to_bob_refund_procedure_signature(
             );
 
             event.send_sync_service(runtime.syncer_state.monero_syncer(), SyncMsg::Task(task))?;
-            // register a watch task for buy tx.
+            // register a watch task for buy tx, broadcasted by Alice, reported from its entry in
+            // the mempool.
             // registration performed now already to ensure it's present in checkpoint.
             runtime.log_debug("register watch buy tx task");
             let buy_tx = buy_procedure_signature.buy.clone().extract_tx();
             let task = runtime
                 .syncer_state
-                .watch_tx_btc(buy_tx.txid(), TxLabel::Buy);
+                .watch_mempool_entry_btc(buy_tx.txid(), TxLabel::Buy);
             event.send_sync_service(runtime.syncer_state.bitcoin_syncer(), SyncMsg::Task(task))?;
             // Checkpoint BobRefundProcedureSignatures
             let new_ssm =
@@ -1280,6 +1455,17 @@ fn try_bob_refund_procedure_signatures_to_bob_accordant_lock(
                 acc_lock_height_lower_bound,
             })))
         }
+        BusMsg::Sync(SyncMsg::Event(SyncEvent::BroadcastFailure(BroadcastFailure {
+            reason,
+            ..
+        }))) if runtime
+            .syncer_state
+            .failed_broadcasted_txs
+            .contains_key(&TxLabel::Lock) =>
+        {
+            let reason = reason.clone();
+            handle_bob_lock_rejected(event, runtime, swap_key_manager, &reason)
+        }
         _ => handle_bob_swap_interrupt_after_lock(event, runtime, bob_txs),
     }
 }
@@ -1306,7 +1492,9 @@ fn try_bob_accordant_lock_to_bob_accordant_lock_final(
         ))) if runtime
             .temporal_safety
             .final_tx(confirmations, Blockchain::Monero)
-            && runtime.syncer_state.tasks.watched_txs.get(&id) == Some(&TxLabel::AccLock) =>
+            && runtime.syncer_state.tasks.watched_txs.get(&id) == Some(&TxLabel::AccLock)
+            // past the deadline the buy signature is withheld, the swap refunds
+            && !runtime.deadline_reached =>
         {
             runtime.send_peer(
                 event.endpoints,
@@ -1390,12 +1578,10 @@ fn try_bob_buy_seen_to_bob_buy_sweeping(
     task: SweepAddress,
 ) -> Result<Option<SwapStateMachine>, Error> {
     match event.request {
-        BusMsg::Sync(SyncMsg::Event(SyncEvent::TransactionConfirmations(
-            TransactionConfirmations {
-                confirmations: Some(confirmations),
-                ..
-            },
-        ))) if confirmations >= SWEEP_MONERO_THRESHOLD => {
+        // the syncer signals once the accordant lock reached the unlock depth
+        BusMsg::Sync(SyncMsg::Event(SyncEvent::MoneroUnlocked(MoneroUnlocked { id, .. })))
+            if runtime.syncer_state.tasks.watched_txs.get(&id) == Some(&TxLabel::AccLock) =>
+        {
             // safe cast
             let request = SyncMsg::Task(Task::SweepAddress(task));
             runtime.log_info(format!(
@@ -1457,6 +1643,32 @@ fn try_bob_cancel_final_to_swap_end(
             Ok(None)
         }
 
+        BusMsg::Ctl(CtlMsg::ExecuteRefund(_)) => {
+            // the refund was broadcast already, broadcast it again
+            let refund_tx = runtime
+                .syncer_state
+                .broadcasted_txs
+                .get(&TxLabel::Refund)
+                .or_else(|| {
+                    runtime
+                        .syncer_state
+                        .failed_broadcasted_txs
+                        .get(&TxLabel::Refund)
+                })
+                .cloned();
+            match refund_tx {
+                Some(refund_tx) => {
+                    execute_recovery(event, runtime, &refund_tx, TxLabel::Refund)?;
+                }
+                None => {
+                    event.complete_client_ctl(CtlMsg::Failure(Failure {
+                        code: FailureCode::Unknown,
+                        info: "The refund transaction is being broadcast already".to_string(),
+                    }))?;
+                }
+            }
+            Ok(None)
+        }
         _ => Ok(None),
     }
 }
@@ -1487,6 +1699,13 @@ fn try_bob_canceled_to_bob_cancel_final(
             runtime.broadcast(&bob_txs.refund_tx, TxLabel::Refund, event.endpoints)?;
             Ok(Some(SwapStateMachine::BobCancelFinal))
         }
+        BusMsg::Ctl(CtlMsg::ExecuteRefund(_)) => {
+            if execute_recovery(event, runtime, &bob_txs.refund_tx, TxLabel::Refund)? {
+                Ok(Some(SwapStateMachine::BobCancelFinal))
+            } else {
+                Ok(None)
+            }
+        }
         _ => Ok(None),
     }
 }
@@ -1532,7 +1751,8 @@ fn try_alice_reveal_to_alice_core_arbitrating_setup(
     } = alice_reveal;
     match event.request.clone() {
         BusMsg::P2p(PeerMsg::CoreArbitratingSetup(setup)) => {
-            // register a watch task for arb lock, cancel, and refund
+            // register a watch task for arb lock, cancel, and refund, the arb lock broadcasted
+            // by Bob is reported from its entry in the mempool
             for (&tx, tx_label) in [&setup.lock, &setup.cancel, &setup.refund].iter().zip([
                 TxLabel::Lock,
                 TxLabel::Cancel,
@@ -1540,7 +1760,23 @@ fn try_alice_reveal_to_alice_core_arbitrating_setup(
             ]) {
                 runtime.log_debug(format!("Register watch {} tx", tx_label));
                 let txid = tx.clone().extract_tx().txid();
-                let task = runtime.syncer_state.watch_tx_btc(txid, tx_label);
+                let task = if tx_label == TxLabel::Lock {
+                    runtime.syncer_state.watch_mempool_entry_btc(txid, tx_label)
+                } else {
+                    runtime.syncer_state.watch_tx_btc(txid, tx_label)
+                };
+                event.send_sync_service(
+                    runtime.syncer_state.bitcoin_syncer(),
+                    SyncMsg::Task(task),
+                )?;
+            }
+            // register a watch task on the funding outpoints of the arb lock to detect a
+            // conflicting spend of the funding
+            let lock = setup.lock.clone().extract_tx();
+            for input in lock.input.iter() {
+                let task = runtime
+                    .syncer_state
+                    .watch_outpoint_spend(input.previous_output, lock.txid());
                 event.send_sync_service(
                     runtime.syncer_state.bitcoin_syncer(),
                     SyncMsg::Task(task),
@@ -1624,8 +1860,10 @@ fn try_alice_core_arbitrating_setup_to_alice_arbitrating_lock_final(
         {
             let (spend, view) = swap_key_manager.aggregate_xmr_spend_view(&remote_params);
             let viewpair = ViewPair { spend, view };
-            let address =
-                monero::Address::from_viewpair(runtime.syncer_state.network.into(), &viewpair);
+            let address = monero::Address::from_viewpair(
+                monero_network(runtime.syncer_state.network),
+                &viewpair,
+            );
             let swap_id = runtime.swap_id();
             let amount = runtime.deal.parameters.accordant_amount;
             let funding_info = MoneroFundingInfo {
@@ -1658,6 +1896,22 @@ fn try_alice_core_arbitrating_setup_to_alice_arbitrating_lock_final(
                 },
             )))
         }
+        // the funding of the arbitrating lock got spent by another transaction, the lock can
+        // never confirm and Alice has nothing locked yet
+        BusMsg::Sync(SyncMsg::Event(SyncEvent::OutpointSpent(OutpointSpent {
+            id,
+            outpoint,
+            spending_txid,
+        }))) if runtime.syncer_state.is_watched_outpoint_spend(&id) => {
+            let msg = format!(
+                "Funding output {} spent by conflicting transaction {}, the lock transaction cannot confirm. Failing the swap.",
+                outpoint,
+                spending_txid.tx_hash()
+            );
+            runtime.log_error(&msg);
+            runtime.report_progress_message_log_fail(event.endpoints, msg);
+            Ok(Some(SwapStateMachine::SwapEnd(Outcome::FailureAbort)))
+        }
         _ => handle_alice_swap_interrupt_after_lock(
             event,
             runtime,
@@ -1688,7 +1942,9 @@ fn try_alice_arbitrating_lock_final_to_alice_accordant_lock(
     } = alice_arbitrating_lock_final;
     match event.request {
         BusMsg::Sync(SyncMsg::Event(SyncEvent::Empty(id)))
-            if runtime.syncer_state.tasks.watched_addrs.get(&id) == Some(&TxLabel::AccLock) =>
+            if runtime.syncer_state.tasks.watched_addrs.get(&id) == Some(&TxLabel::AccLock)
+                // past the deadline the Monero is no longer locked, the swap refunds
+                && !runtime.deadline_reached =>
         {
             runtime.log_info(format!(
                 "Send {} to {}",
@@ -2104,6 +2360,19 @@ fn try_alice_canceled_to_alice_refund_or_alice_punish(
                 _ => Ok(None),
             }
         }
+        BusMsg::Ctl(CtlMsg::ExecutePunish(_)) => {
+            if execute_recovery(event, runtime, &alice_txs.punish_tx, TxLabel::Punish)? {
+                Ok(Some(SwapStateMachine::AliceCanceled(AliceCanceled {
+                    remote_params,
+                    adaptor_refund,
+                    swap_key_manager,
+                    alice_txs,
+                    acc_lock_height_lower_bound,
+                })))
+            } else {
+                Ok(None)
+            }
+        }
         _ => Ok(None),
     }
 }
@@ -2114,12 +2383,10 @@ fn try_alice_refund_to_alice_refund_sweeping(
     sweep_address: SweepAddress,
 ) -> Result<Option<SwapStateMachine>, Error> {
     match event.request {
-        BusMsg::Sync(SyncMsg::Event(SyncEvent::TransactionConfirmations(
-            TransactionConfirmations {
-                confirmations: Some(confirmations),
-                ..
-            },
-        ))) if confirmations >= SWEEP_MONERO_THRESHOLD => {
+        // the syncer signals once the accordant lock reached the unlock depth
+        BusMsg::Sync(SyncMsg::Event(SyncEvent::MoneroUnlocked(MoneroUnlocked { id, .. })))
+            if runtime.syncer_state.tasks.watched_txs.get(&id) == Some(&TxLabel::AccLock) =>
+        {
             runtime.log_info(format!(
                 "Monero are spendable now (height {}), sweeping ephemeral swap_key_manager",
                 runtime.syncer_state.monero_height.label(),
@@ -2185,7 +2452,7 @@ fn try_bob_buy_sweeping_to_swap_end(
 }
 
 fn attempt_transition_to_bob_reveal(
-    event: Event,
+    mut event: Event,
     runtime: &mut Runtime,
     remote_commit: CommitAliceParameters,
     mut swap_key_manager: BobSwapKeyManager,
@@ -2194,7 +2461,19 @@ fn attempt_transition_to_bob_reveal(
         BusMsg::P2p(PeerMsg::Reveal(Reveal::Alice(parameters))) => {
             runtime.log_info("Handling reveal with swap_key_manager");
             let (bob_reveal, remote_params) =
-                swap_key_manager.handle_alice_reveals(runtime, parameters, remote_commit)?;
+                match swap_key_manager.handle_alice_reveals(runtime, parameters, remote_commit) {
+                    Ok(reveal) => reveal,
+                    Err(err) => {
+                        // the reveal does not match the commitment or its proof is invalid, the
+                        // counterparty violates the protocol
+                        runtime.log_error(format!(
+                            "Invalid reveal from Alice: {} Failing the swap.",
+                            err
+                        ));
+                        sweep_bob_funding(&mut event, runtime, swap_key_manager)?;
+                        return Ok(Some(SwapStateMachine::BobAbortAwaitingBitcoinSweep));
+                    }
+                };
 
             // The swap_key_manager only returns reveal if we are Bob Maker
             if let Some(bob_reveal) = bob_reveal {
@@ -2224,7 +2503,18 @@ fn attempt_transition_to_alice_reveal(
         BusMsg::P2p(PeerMsg::Reveal(Reveal::Bob(parameters))) => {
             runtime.log_info("Handling reveal with swap_key_manager");
             let (alice_reveal, remote_params) =
-                swap_key_manager.handle_bob_reveals(runtime, parameters, remote_commit)?;
+                match swap_key_manager.handle_bob_reveals(runtime, parameters, remote_commit) {
+                    Ok(reveal) => reveal,
+                    Err(err) => {
+                        // the reveal does not match the commitment or its proof is invalid, the
+                        // counterparty violates the protocol
+                        runtime.log_error(format!(
+                            "Invalid reveal from Bob: {} Failing the swap.",
+                            err
+                        ));
+                        return Ok(Some(SwapStateMachine::SwapEnd(Outcome::FailureAbort)));
+                    }
+                };
 
             // The swap_key_manager only returns reveal if we are Alice Maker
             if let Some(alice_reveal) = alice_reveal {
@@ -2353,6 +2643,71 @@ fn handle_alice_swap_interrupt_after_lock(
     }
 }
 
+/// Check the timelock path transaction can be broadcast on the operator's request: the cancel
+/// transaction must be final and, for the punish transaction, the punish timelock expired
+fn recovery_precondition(runtime: &Runtime, tx_label: TxLabel) -> Result<(), String> {
+    match runtime.syncer_state.get_confs(TxLabel::Cancel) {
+        Some(confs) if !runtime.temporal_safety.final_tx(confs, Blockchain::Bitcoin) => {
+            Err(format!(
+                "the cancel transaction is not final yet, it has {} confirmations",
+                confs
+            ))
+        }
+        Some(confs)
+            if tx_label == TxLabel::Punish && !runtime.temporal_safety.valid_punish(confs) =>
+        {
+            Err(format!(
+                "the punish timelock did not expire yet, {} blocks remaining",
+                runtime
+                    .temporal_safety
+                    .blocks_until_punish_after_cancel(confs)
+            ))
+        }
+        Some(_) => Ok(()),
+        None => Err("the cancel transaction is not confirmed yet".to_string()),
+    }
+}
+
+/// Broadcast a timelock path transaction requested by the operator if valid, replying with its
+/// txid or the reason it cannot be broadcast yet. Returns whether it is broadcast.
+fn execute_recovery(
+    mut event: Event,
+    runtime: &mut Runtime,
+    tx: &bitcoin::Transaction,
+    tx_label: TxLabel,
+) -> Result<bool, Error> {
+    if let Err(reason) = recovery_precondition(runtime, tx_label) {
+        let msg = format!("Cannot broadcast the {} transaction: {}", tx_label, reason);
+        runtime.log_warn(&msg);
+        event.complete_client_ctl(CtlMsg::Failure(Failure {
+            code: FailureCode::Unknown,
+            info: msg,
+        }))?;
+        return Ok(false);
+    }
+    let txid = tx.txid();
+    if !runtime
+        .syncer_state
+        .tasks
+        .watched_txs
+        .values()
+        .any(|label| *label == tx_label)
+    {
+        let task = runtime.syncer_state.watch_tx_btc(txid, tx_label);
+        event.send_sync_service(runtime.syncer_state.bitcoin_syncer(), SyncMsg::Task(task))?;
+    }
+    runtime.log_info(format!(
+        "Broadcasting the {} transaction on request",
+        tx_label
+    ));
+    runtime.broadcast(tx, tx_label, event.endpoints)?;
+    event.complete_client_info(InfoMsg::String(format!(
+        "Broadcasting the {} transaction {}",
+        tx_label, txid
+    )))?;
+    Ok(true)
+}
+
 fn handle_abort_swap(
     event: Event,
     runtime: &mut Runtime,
@@ -2378,8 +2733,39 @@ fn handle_abort_impossible(
 fn handle_bob_abort_swap(
     mut event: Event,
     runtime: &mut Runtime,
-    mut swap_key_manager: BobSwapKeyManager,
+    swap_key_manager: BobSwapKeyManager,
 ) -> Result<Option<SwapStateMachine>, Error> {
+    sweep_bob_funding(&mut event, runtime, swap_key_manager)?;
+    event.complete_client_info(InfoMsg::String(
+        "Aborting swap, checking if funds can be sweeped.".to_string(),
+    ))?;
+    Ok(Some(SwapStateMachine::BobAbortAwaitingBitcoinSweep))
+}
+
+fn handle_bob_lock_rejected(
+    mut event: Event,
+    runtime: &mut Runtime,
+    swap_key_manager: BobSwapKeyManager,
+    reason: &str,
+) -> Result<Option<SwapStateMachine>, Error> {
+    // the lock will never confirm, do not retry its broadcast
+    runtime
+        .syncer_state
+        .failed_broadcasted_txs
+        .remove(&TxLabel::Lock);
+    runtime.log_error(format!(
+        "Lock transaction rejected by the bitcoin backend: {}. Failing the swap.",
+        reason
+    ));
+    sweep_bob_funding(&mut event, runtime, swap_key_manager)?;
+    Ok(Some(SwapStateMachine::BobAbortAwaitingBitcoinSweep))
+}
+
+fn sweep_bob_funding(
+    event: &mut Event,
+    runtime: &mut Runtime,
+    mut swap_key_manager: BobSwapKeyManager,
+) -> Result<(), Error> {
     let funding_address = swap_key_manager
         .funding_address()
         .expect("Am Bob, so have funding address");
@@ -2391,10 +2777,7 @@ fn handle_bob_abort_swap(
     ));
     let task = runtime.syncer_state.sweep_btc(sweep_btc, false);
     event.send_sync_service(runtime.syncer_state.bitcoin_syncer(), SyncMsg::Task(task))?;
-    event.complete_client_info(InfoMsg::String(
-        "Aborting swap, checking if funds can be sweeped.".to_string(),
-    ))?;
-    Ok(Some(SwapStateMachine::BobAbortAwaitingBitcoinSweep))
+    Ok(())
 }
 
 fn watch_cancel_address(
@@ -5,12 +5,14 @@
 // https://opensource.org/licenses/MIT.
 
 use crate::{
-    bus::ServiceBus,
+    bus::{ctl::MoneroDeposit, ServiceBus},
     service::{Endpoints, LogStyle, SwapDetails, SwapLogging},
     syncerd::{
-        Abort, AddressAddendum, BroadcastTransaction, BtcAddressAddendum, GetTx, SweepAddress,
-        SweepAddressAddendum, SweepBitcoinAddress, SweepMoneroAddress, TaskTarget,
-        TransactionBroadcasted, TxFilter, Txid, WatchAddress, WatchEstimateFee, WatchHeight,
+        Abort, AddressAddendum, Balance, BroadcastFailure, BroadcastTransaction,
+        BtcAddressAddendum, Event, GetBalance, GetTx, SweepAddress, SweepAddressAddendum,
+        SweepBitcoinAddress, SweepMoneroAddress, SweepSuccess, TaskAborted, TaskTarget,
+        TransactionBroadcasted, TransactionRetrieved, TxFilter, Txid, TxidOrOutpoint, WatchAddress,
+        WatchEstimateFee, WatchHeight, WatchMempoolEntry, WatchMoneroAddress, WatchOutpointSpend,
         WatchTransaction, XmrAddressAddendum,
     },
     Error,
@@ -22,7 +24,7 @@ use farcaster_core::{
     swap::SwapId,
     transaction::TxLabel,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::{
     bus::sync::SyncMsg,
@@ -38,7 +40,10 @@ pub struct SyncerTasks {
     pub watched_addrs: HashMap<TaskId, TxLabel>,
     pub retrieving_txs: HashMap<TaskId, TxLabel>,
     pub broadcasting_txs: HashMap<TaskId, TxLabel>,
+    pub rebroadcasting_txs: HashMap<TaskId, TxLabel>,
     pub sweeping_addr: Option<TaskId>,
+    pub watched_deposit: Option<TaskId>,
+    pub funding_balance: Option<TaskId>,
     pub txids: HashMap<TxLabel, bitcoin::Txid>,
     pub tasks: HashMap<TaskId, Task>,
 }
@@ -58,6 +63,10 @@ pub struct SyncerState {
     pub bitcoin_height: u64,
     pub monero_height: u64,
     pub confirmation_bound: u32,
+    // Confirmations after which the swap considers a transaction final, the syncers poll
+    // eagerly while a watched transaction is about to reach them
+    pub bitcoin_finality: u32,
+    pub monero_finality: u32,
     pub last_tx_event: HashMap<TxLabel, SyncMsg>,
     pub network: farcaster_core::blockchain::Network,
     pub bitcoin_syncer: ServiceId,
@@ -67,6 +76,17 @@ pub struct SyncerState {
     pub awaiting_funding: bool,
     pub broadcasted_txs: HashMap<TxLabel, bitcoin::Transaction>,
     pub failed_broadcasted_txs: HashMap<TxLabel, bitcoin::Transaction>,
+    // Number of blocks between two re-broadcasts of the broadcasted transactions not final yet,
+    // zero disables the re-broadcasts
+    pub rebroadcast_interval: u32,
+    pub last_rebroadcast_height: u64,
+    // Broadcasted transactions rejected on re-broadcast, e.g. double spent, no longer
+    // re-broadcasted until a new version is broadcasted under their label
+    pub rejected_txs: HashSet<TxLabel>,
+    // Number of confirmations before a received Monero output can be spent
+    pub monero_unlock_depth: u32,
+    // Height the Monero deposit subaddress of the swap is watched from
+    pub monero_deposit_from_height: Option<u64>,
 }
 
 impl SwapLogging for SyncerState {
@@ -133,8 +153,64 @@ impl SyncerState {
         } else {
             self.log_warn("block height did not increment, maybe syncer sends multiple events");
         }
+        if blockchain == Blockchain::Bitcoin {
+            self.rebroadcast_txs(endpoints);
+        }
+    }
+
+    /// Re-submit the broadcasted transactions not final yet once every rebroadcast interval, in
+    /// case they were dropped from the mempool by a reorg or an eviction. Only the latest version
+    /// of each transaction is re-broadcasted, so fee bumps replace the transactions re-submitted.
+    fn rebroadcast_txs(&mut self, endpoints: &mut Endpoints) {
+        if self.rebroadcast_interval == 0
+            || self.bitcoin_height
+                < self.last_rebroadcast_height + u64::from(self.rebroadcast_interval)
+        {
+            return;
+        }
+        self.last_rebroadcast_height = self.bitcoin_height;
+        for (label, tx) in self.broadcasted_txs.clone() {
+            if self.tasks.final_txs.get(&label) == Some(&true)
+                || self.rejected_txs.contains(&label)
+                || self.broadcasted_tx_pending(&label)
+            {
+                continue;
+            }
+            self.log_debug(format!("Re-broadcasting {} transaction", label));
+            let id = self.tasks.new_taskid();
+            let task = Task::BroadcastTransaction(BroadcastTransaction {
+                id,
+                tx: bitcoin::consensus::serialize(&tx),
+                broadcast_after_height: None,
+            });
+            self.tasks.tasks.insert(id, task.clone());
+            self.tasks.rebroadcasting_txs.insert(id, label);
+            if let Err(err) = endpoints.send_to(
+                ServiceBus::Sync,
+                ServiceId::Swap(self.swap_id),
+                self.bitcoin_syncer(),
+                BusMsg::Sync(SyncMsg::Task(task)),
+            ) {
+                self.log_error(format!(
+                    "Failed to send task for re-broadcasting {} transaction: {}",
+                    label, err
+                ));
+            }
+        }
+    }
+
+    /// Whether a broadcast or re-broadcast of the transaction is underway
+    fn broadcasted_tx_pending(&self, tx_label: &TxLabel) -> bool {
+        self.tasks.broadcasting_txs.values().any(|l| l == tx_label)
+            || self
+                .tasks
+                .rebroadcasting_txs
+                .values()
+                .any(|l| l == tx_label)
     }
     pub fn abort_task(&mut self, id: TaskId) -> Task {
+        // an aborted task is not issued again on a syncer failover
+        self.tasks.tasks.remove(&id);
         Task::Abort(Abort {
             task_target: TaskTarget::TaskId(id),
             respond: false,
@@ -176,10 +252,61 @@ impl SyncerState {
             lifetime: self.task_lifetime(Blockchain::Bitcoin),
             hash: txid.into(),
             confirmation_bound: self.confirmation_bound,
+            finality: self.bitcoin_finality,
         });
         self.tasks.tasks.insert(id, task.clone());
         task
     }
+    /// Watch a transaction broadcasted by the counterparty for its entry in the mempool, its
+    /// confirmations are then reported as for [`SyncerState::watch_tx_btc`]
+    pub fn watch_mempool_entry_btc(&mut self, txid: bitcoin::Txid, tx_label: TxLabel) -> Task {
+        if self.is_watched_tx(&tx_label) {
+            self.log_warn(format!(
+                "Already watching for tx with label {} - notifications will be repeated",
+                tx_label.label()
+            ));
+        }
+        let id = self.tasks.new_taskid();
+        self.tasks.watched_txs.insert(id, tx_label);
+        self.tasks.txids.insert(tx_label, txid);
+        self.log_info(format!(
+            "Watching {} transaction ({}) from its entry in the mempool",
+            tx_label.label(),
+            txid.tx_hash()
+        ));
+        let task = Task::WatchMempoolEntry(WatchMempoolEntry {
+            id,
+            lifetime: self.task_lifetime(Blockchain::Bitcoin),
+            txid_or_outpoint: TxidOrOutpoint::Txid(txid),
+            confirmation_bound: self.confirmation_bound,
+            finality: self.bitcoin_finality,
+        });
+        self.tasks.tasks.insert(id, task.clone());
+        task
+    }
+    pub fn watch_outpoint_spend(
+        &mut self,
+        outpoint: bitcoin::OutPoint,
+        expected_spending_txid: bitcoin::Txid,
+    ) -> Task {
+        let id = self.tasks.new_taskid();
+        self.log_debug(format!(
+            "Watching outpoint {} for a spend conflicting with {}",
+            outpoint,
+            expected_spending_txid.tx_hash()
+        ));
+        let task = Task::WatchOutpointSpend(WatchOutpointSpend {
+            id,
+            lifetime: self.task_lifetime(Blockchain::Bitcoin),
+            outpoint,
+            expected_spending_txid: Some(expected_spending_txid),
+        });
+        self.tasks.tasks.insert(id, task.clone());
+        task
+    }
+    pub fn is_watched_outpoint_spend(&self, id: &TaskId) -> bool {
+        matches!(self.tasks.tasks.get(id), Some(Task::WatchOutpointSpend(_)))
+    }
     pub fn is_watched_tx(&self, tx_label: &TxLabel) -> bool {
         self.tasks.watched_txs.values().any(|tx| tx == tx_label)
     }
@@ -204,6 +331,7 @@ impl SyncerState {
             lifetime: self.task_lifetime(Blockchain::Monero),
             hash,
             confirmation_bound: self.confirmation_bound,
+            finality: self.monero_finality,
         });
         self.tasks.tasks.insert(id, task.clone());
         task
@@ -215,6 +343,18 @@ impl SyncerState {
         self.tasks.tasks.insert(id, task.clone());
         task
     }
+    /// Query the confirmed and unconfirmed balance of the funding address, answered once by a
+    /// balance event
+    pub fn funding_balance_btc(&mut self, address: bitcoin::Address) -> Task {
+        let id = self.tasks.new_taskid();
+        let task = Task::GetBalance(GetBalance {
+            id,
+            descriptor: AddressAddendum::Bitcoin(BtcAddressAddendum { address }),
+        });
+        self.tasks.funding_balance = Some(id);
+        self.tasks.tasks.insert(id, task.clone());
+        task
+    }
     pub fn watch_addr_btc(&mut self, address: bitcoin::Address, tx_label: TxLabel) -> Task {
         if self.is_watched_addr(&tx_label) {
             self.log_warn(format!(
@@ -303,6 +443,27 @@ impl SyncerState {
         task
     }
 
+    /// Watches the subaddress of the node Monero wallet dedicated to the swap for incoming funds
+    pub fn watch_monero_deposit(&mut self, deposit: &MoneroDeposit, from_height: u64) -> Task {
+        let id = self.tasks.new_taskid();
+        self.tasks.watched_deposit = Some(id);
+        self.monero_deposit_from_height = Some(from_height);
+        self.log_info(format!(
+            "Watching the Monero deposit subaddress {} from height {}",
+            deposit.address, from_height
+        ));
+        let task = Task::WatchMoneroAddress(WatchMoneroAddress {
+            id,
+            lifetime: self.task_lifetime(Blockchain::Monero),
+            address: deposit.watch_address(),
+            view_key: deposit.view_key,
+            min_amount: monero::Amount::from_pico(0),
+            from_height,
+        });
+        self.tasks.tasks.insert(id, task.clone());
+        task
+    }
+
     pub fn watch_height(
         &mut self,
         endpoints: &mut Endpoints,
@@ -370,6 +531,16 @@ impl SyncerState {
         task
     }
     pub fn transaction_broadcasted(&mut self, event: &TransactionBroadcasted) {
+        if let Some(txlabel) = self.tasks.rebroadcasting_txs.remove(&event.id) {
+            self.tasks.tasks.remove(&event.id);
+            if let Some(ref err) = event.error {
+                self.log_debug(format!(
+                    "Error re-broadcasting {} transaction: {}, retrying on the next interval",
+                    txlabel, err
+                ));
+            }
+            return;
+        }
         if let Some(txlabel) = self.tasks.broadcasting_txs.remove(&event.id) {
             self.tasks.tasks.remove(&event.id);
             if let Some(ref err) = event.error {
@@ -392,10 +563,43 @@ impl SyncerState {
                         return;
                     }
                 };
+                self.rejected_txs.remove(&txlabel);
                 self.broadcasted_txs.insert(txlabel, tx);
             }
         }
     }
+    pub fn broadcast_failed(&mut self, event: &BroadcastFailure) {
+        if let Some(txlabel) = self.tasks.rebroadcasting_txs.remove(&event.id) {
+            self.tasks.tasks.remove(&event.id);
+            // a rejected previous version is expected once a new version replaced it
+            let current = self
+                .broadcasted_txs
+                .get(&txlabel)
+                .map(|tx| Txid::from(tx.txid()));
+            if current == Some(event.txid) {
+                self.log_warn(format!(
+                    "The {} transaction {} was rejected on re-broadcast: {}. Not re-broadcasting it anymore.",
+                    txlabel, event.txid, event.reason
+                ));
+                self.rejected_txs.insert(txlabel);
+            }
+            return;
+        }
+        if let Some(txlabel) = self.tasks.broadcasting_txs.remove(&event.id) {
+            self.log_error(format!(
+                "The {} transaction {} was rejected: {}",
+                txlabel, event.txid, event.reason
+            ));
+            if let Some(Task::BroadcastTransaction(task)) = self.tasks.tasks.remove(&event.id) {
+                if let Ok(tx) =
+                    bitcoin::Transaction::consensus_decode(std::io::Cursor::new(task.tx))
+                {
+                    self.failed_broadcasted_txs.insert(txlabel, tx);
+                    self.log_warn("Retrying broadcast on the next block height increase.");
+                }
+            }
+        }
+    }
     pub fn pending_broadcast_txs(&self) -> Vec<(bitcoin::Transaction, TxLabel)> {
         self.tasks
             .broadcasting_txs
@@ -513,4 +717,299 @@ impl SyncerState {
     pub fn get_confs(&self, label: TxLabel) -> Option<u32> {
         self.confirmations.get(&label).copied().flatten()
     }
+
+    /// Blockchain of the syncer a recorded task was sent to, none for the height watches which
+    /// are sent to both syncers
+    fn task_blockchain(&self, id: &TaskId, task: &Task) -> Option<Blockchain> {
+        match task {
+            Task::WatchHeight(_) => None,
+            Task::WatchAddress(WatchAddress {
+                addendum: AddressAddendum::Monero(_),
+                ..
+            })
+            | Task::SweepAddress(SweepAddress {
+                addendum: SweepAddressAddendum::Monero(_),
+                ..
+            }) => Some(Blockchain::Monero),
+            Task::WatchTransaction(_)
+                if self.tasks.watched_txs.get(id) == Some(&TxLabel::AccLock) =>
+            {
+                Some(Blockchain::Monero)
+            }
+            _ => Some(Blockchain::Bitcoin),
+        }
+    }
+
+    /// Forget the one-shot tasks completed by the event, they are not issued again on a syncer
+    /// failover
+    pub fn forget_completed_task(&mut self, event: &Event) {
+        match event {
+            Event::TransactionRetrieved(TransactionRetrieved { id, tx: Some(_) })
+            | Event::SweepSuccess(SweepSuccess { id, .. })
+            | Event::Balance(Balance { id, .. }) => {
+                self.tasks.tasks.remove(id);
+            }
+            Event::TaskAborted(TaskAborted { id, .. }) => {
+                for id in id {
+                    self.tasks.tasks.remove(id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Move the live tasks of a blockchain to a new syncer: the tasks are aborted on the previous
+    /// syncer, if it still answers, and issued again to the new one along with a new height
+    /// watch. The events of the tasks are then expected from the new syncer only. Switching to
+    /// the current syncer issues its live tasks to it again.
+    pub fn switch_syncer(
+        &mut self,
+        new_syncer: ServiceId,
+        endpoints: &mut Endpoints,
+    ) -> Result<(), Error> {
+        let (blockchain, old_syncer, tasks) = self.move_tasks(new_syncer.clone())?;
+        let identity = ServiceId::Swap(self.swap_id);
+        if let Err(err) = endpoints.send_to(
+            ServiceBus::Sync,
+            identity.clone(),
+            old_syncer.clone(),
+            BusMsg::Sync(SyncMsg::Task(Task::Abort(Abort {
+                task_target: TaskTarget::AllTasks,
+                respond: false,
+            }))),
+        ) {
+            self.log_debug(format!(
+                "Failed to abort the tasks on {}: {}",
+                old_syncer, err
+            ));
+        }
+        if old_syncer == new_syncer {
+            self.log_info(format!(
+                "Issuing {} {} tasks to {} again",
+                tasks.len(),
+                blockchain,
+                new_syncer
+            ));
+        } else {
+            self.log_info(format!(
+                "Moving {} {} tasks from {} to {}",
+                tasks.len(),
+                blockchain,
+                old_syncer,
+                new_syncer
+            ));
+        }
+        for task in tasks {
+            endpoints.send_to(
+                ServiceBus::Sync,
+                identity.clone(),
+                new_syncer.clone(),
+                BusMsg::Sync(SyncMsg::Task(task)),
+            )?;
+        }
+        self.watch_height(endpoints, blockchain)
+    }
+
+    /// Replace the syncer of the blockchain of the new syncer, returning the blockchain, the
+    /// previous syncer and the live tasks to issue to the new one in the order they were
+    /// issued. The tasks expired on the previous syncer are forgotten, the lifetimes of the
+    /// others are extended to the lifetime of a task issued at the current height.
+    fn move_tasks(
+        &mut self,
+        new_syncer: ServiceId,
+    ) -> Result<(Blockchain, ServiceId, Vec<Task>), Error> {
+        let blockchain = match new_syncer {
+            ServiceId::Syncer(blockchain, ..) => blockchain,
+            _ => {
+                return Err(Error::Farcaster(format!(
+                    "Cannot switch to {}, not a syncer",
+                    new_syncer
+                )))
+            }
+        };
+        let old_syncer = match blockchain {
+            Blockchain::Bitcoin => std::mem::replace(&mut self.bitcoin_syncer, new_syncer),
+            Blockchain::Monero => std::mem::replace(&mut self.monero_syncer, new_syncer),
+        };
+        let height = self.height(blockchain);
+        let lifetime = self.task_lifetime(blockchain);
+        let mut ids: Vec<TaskId> = self
+            .tasks
+            .tasks
+            .iter()
+            .filter(|(id, task)| self.task_blockchain(id, task) == Some(blockchain))
+            .map(|(id, _)| *id)
+            .collect();
+        ids.sort_by_key(|id| id.0);
+        let mut tasks = vec![];
+        for id in ids {
+            let task = match self.tasks.tasks.get_mut(&id) {
+                Some(task) => task,
+                None => continue,
+            };
+            let live = match task.lifetime_mut() {
+                Some(task_lifetime) if *task_lifetime < height => false,
+                Some(task_lifetime) => {
+                    *task_lifetime = (*task_lifetime).max(lifetime);
+                    true
+                }
+                None => true,
+            };
+            if live {
+                tasks.push(task.clone());
+            } else {
+                self.tasks.tasks.remove(&id);
+            }
+        }
+        Ok((blockchain, old_syncer, tasks))
+    }
+}
+
+#[cfg(test)]
+fn test_syncer_state() -> SyncerState {
+    use crate::SyncerInstance;
+    use farcaster_core::{blockchain::Network, Uuid};
+
+    SyncerState {
+        swap_id: Uuid::new().into(),
+        local_swap_role: SwapRole::Alice,
+        local_trade_role: TradeRole::Maker,
+        tasks: SyncerTasks {
+            counter: 0,
+            watched_addrs: none!(),
+            watched_txs: none!(),
+            retrieving_txs: none!(),
+            sweeping_addr: none!(),
+            watched_deposit: none!(),
+            funding_balance: none!(),
+            broadcasting_txs: none!(),
+            rebroadcasting_txs: none!(),
+            txids: none!(),
+            final_txs: none!(),
+            tasks: none!(),
+        },
+        bitcoin_height: 0,
+        monero_height: 0,
+        confirmation_bound: 50000,
+        bitcoin_finality: 1,
+        monero_finality: 1,
+        last_tx_event: none!(),
+        network: Network::Local,
+        bitcoin_syncer: ServiceId::Syncer(
+            Blockchain::Bitcoin,
+            Network::Local,
+            SyncerInstance::DEFAULT,
+        ),
+        monero_syncer: ServiceId::Syncer(
+            Blockchain::Monero,
+            Network::Local,
+            SyncerInstance::DEFAULT,
+        ),
+        xmr_addr_addendum: None,
+        confirmations: none!(),
+        awaiting_funding: false,
+        broadcasted_txs: none!(),
+        failed_broadcasted_txs: none!(),
+        rebroadcast_interval: 0,
+        last_rebroadcast_height: 0,
+        rejected_txs: none!(),
+        monero_deposit_from_height: None,
+        monero_unlock_depth: 10,
+    }
+}
+
+#[test]
+fn conflicting_spend_of_watched_outpoint() {
+    use bitcoin::hashes::Hash;
+
+    let mut state = test_syncer_state();
+    let lock_txid = bitcoin::Txid::from_slice(&[1; 32]).unwrap();
+    let outpoint = bitcoin::OutPoint::new(bitcoin::Txid::from_slice(&[2; 32]).unwrap(), 0);
+    let spend_task = state.watch_outpoint_spend(outpoint, lock_txid);
+    let tx_task = state.watch_tx_btc(lock_txid, TxLabel::Lock);
+    assert!(state.is_watched_outpoint_spend(&spend_task.id().unwrap()));
+    // a transaction watch is not an outpoint spend watch
+    assert!(!state.is_watched_outpoint_spend(&tx_task.id().unwrap()));
+    // unknown tasks, e.g. from before a restore, are ignored
+    assert!(!state.is_watched_outpoint_spend(&TaskId(42)));
+
+    // a transaction watched from its mempool entry is reported under its label
+    let buy_txid = bitcoin::Txid::from_slice(&[3; 32]).unwrap();
+    let entry_task = state.watch_mempool_entry_btc(buy_txid, TxLabel::Buy);
+    assert_eq!(
+        state.tasks.watched_txs.get(&entry_task.id().unwrap()),
+        Some(&TxLabel::Buy)
+    );
+    assert!(matches!(
+        entry_task,
+        Task::WatchMempoolEntry(WatchMempoolEntry {
+            txid_or_outpoint: TxidOrOutpoint::Txid(txid),
+            finality: 1,
+            ..
+        }) if txid == buy_txid
+    ));
+}
+
+#[test]
+fn failover_reissues_the_live_tasks_to_the_new_syncer_only() {
+    use crate::SyncerInstance;
+    use bitcoin::hashes::Hash;
+    use farcaster_core::blockchain::Network;
+
+    let mut state = test_syncer_state();
+    let txid = |byte| bitcoin::Txid::from_slice(&[byte; 32]).unwrap();
+    state.bitcoin_height = 100;
+    state.monero_height = 1000;
+    // expires at height 600
+    let expired_task = state.watch_tx_btc(txid(1), TxLabel::Lock);
+    state.bitcoin_height = 400;
+    let cancel_task = state.watch_tx_btc(txid(2), TxLabel::Cancel);
+    let pending_task = state.retrieve_tx_btc(txid(3).into(), TxLabel::Buy);
+    let retrieved_task = state.retrieve_tx_btc(txid(4).into(), TxLabel::Refund);
+    let acc_lock_task = state.watch_tx_xmr(monero::Hash::new(b"acc lock").into(), TxLabel::AccLock);
+    state.forget_completed_task(&Event::TransactionRetrieved(TransactionRetrieved {
+        id: retrieved_task.id().unwrap(),
+        tx: Some(bitcoin::Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![],
+            output: vec![],
+        }),
+    }));
+    state.bitcoin_height = 700;
+
+    let old_syncer = state.bitcoin_syncer();
+    let monero_syncer = state.monero_syncer();
+    let fallback = ServiceId::Syncer(
+        Blockchain::Bitcoin,
+        Network::Local,
+        SyncerInstance(Some("fallback".to_string())),
+    );
+    let (blockchain, previous, tasks) = state.move_tasks(fallback.clone()).unwrap();
+    assert_eq!(Blockchain::Bitcoin, blockchain);
+    assert_eq!(old_syncer, previous);
+    assert_eq!(fallback, state.bitcoin_syncer());
+    // the Monero tasks stay on the Monero syncer
+    assert_eq!(monero_syncer, state.monero_syncer());
+    assert!(state.tasks.tasks.contains_key(&acc_lock_task.id().unwrap()));
+
+    // the watch issued at height 400 is extended to the lifetime of a watch issued now, the
+    // expired watch and the answered retrieval are not issued again
+    let mut extended_task = cancel_task;
+    *extended_task.lifetime_mut().unwrap() = 1200;
+    assert_eq!(vec![extended_task.clone(), pending_task], tasks);
+    assert_eq!(
+        Some(&extended_task),
+        state.tasks.tasks.get(&extended_task.id().unwrap())
+    );
+    assert!(!state.tasks.tasks.contains_key(&expired_task.id().unwrap()));
+    // the watched transaction keeps its label under the same task id on the new syncer
+    assert_eq!(
+        Some(&TxLabel::Cancel),
+        state.tasks.watched_txs.get(&extended_task.id().unwrap())
+    );
+
+    let not_a_syncer = ServiceId::Swap(state.swap_id);
+    assert!(state.move_tasks(not_a_syncer).is_err());
+    assert_eq!(fallback, state.bitcoin_syncer());
 }
@@ -14,9 +14,6 @@ pub type BlockHeight = u32;
 /// Represent a block length or a block number
 pub type BlockSpan = u32;
 
-/// The minimum number of block confirmations required before sweeping
-pub const SWEEP_MONERO_THRESHOLD: u32 = 10;
-
 /// List of parameters used to determined if a transaction should be considered final or not and if
 /// it is safe to broadcast a transaction given the timelocks and confirmations of other
 /// transactions.
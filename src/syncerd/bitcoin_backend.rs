@@ -0,0 +1,946 @@
+// Copyright 2020-2022 Farcaster Devs & LNP/BP Standards Association
+//
+// Use of this source code is governed by an MIT-style
+// license that can be found in the LICENSE file or at
+// https://opensource.org/licenses/MIT.
+
+use crate::error::{Error, SyncerError};
+use crate::syncerd::syncer_state::{AddressTx, OutpointStatus};
+use crate::syncerd::{BtcAddressAddendum, FeeHistogramBucket, TxFilter};
+use bitcoin::{BlockHash, BlockHeader};
+use bitcoincore_rpc::json::{GetTransactionResultDetailCategory, ListTransactionResult};
+use bitcoincore_rpc::{jsonrpc, Auth, RpcApi};
+use electrum_client::{Client, ConfigBuilder, ElectrumApi, GetHistoryRes, Socks5Config};
+use std::collections::{HashMap, HashSet};
+
+/// Label used when importing watched addresses into the Bitcoin Core wallet.
+const WATCH_ONLY_LABEL_PREFIX: &str = "farcaster";
+
+/// Confirmation target of the fee estimate used to approximate the mempool fee histogram.
+const HISTOGRAM_APPROXIMATION_TARGET: usize = 2;
+
+/// Number of wallet entries listed per watched address by the Bitcoin Core backend.
+const LIST_TRANSACTIONS_COUNT: usize = 1000;
+
+/// Number of recent blocks used to measure the average block interval.
+const BLOCK_INTERVAL_SAMPLE: u64 = 24;
+
+/// Chain data access required by the Bitcoin syncer. Every backend must report the same
+/// information so the events emitted by the syncer do not depend on the backend in use.
+pub trait BitcoinBackend: Send {
+    /// Return the history of the address matching the filter
+    fn watch_address(
+        &mut self,
+        address: &BtcAddressAddendum,
+        filter: &TxFilter,
+    ) -> Result<Vec<AddressTx>, Error>;
+
+    /// Return the histories of the addresses matching their filters, in the order of the
+    /// addresses, each address failing on its own. Backends able to do so query all the
+    /// addresses with a single call.
+    fn watch_addresses(
+        &mut self,
+        addresses: &[(BtcAddressAddendum, TxFilter)],
+    ) -> Vec<Result<Vec<AddressTx>, Error>> {
+        addresses
+            .iter()
+            .map(|(address, filter)| self.watch_address(address, filter))
+            .collect()
+    }
+
+    /// Retrieve a transaction from the mempool or the chain
+    fn get_tx(&self, txid: &bitcoin::Txid) -> Result<bitcoin::Transaction, Error>;
+
+    /// Broadcast a serialized transaction
+    fn broadcast(&self, tx: &[u8]) -> Result<bitcoin::Txid, Error>;
+
+    /// Estimate the fee rate, in BTC/kvB, for a high and a low priority confirmation target
+    fn estimate_fee(&self, near_target: usize, far_target: usize) -> Result<FeeByPriority, Error>;
+
+    /// Return the confirmations of the transactions creating and spending the outpoint
+    fn outpoint_status(&mut self, outpoint: &bitcoin::OutPoint) -> Result<OutpointStatus, Error>;
+
+    /// Return the transaction spending the outpoint with its confirmations, none if the
+    /// outpoint is unspent or unknown
+    fn outpoint_spend(
+        &mut self,
+        outpoint: &bitcoin::OutPoint,
+    ) -> Result<Option<(bitcoin::Txid, u32)>, Error> {
+        let status = self.outpoint_status(outpoint)?;
+        Ok(status
+            .spending_txid
+            .map(|txid| (txid, status.spent_confirmations.unwrap_or(0))))
+    }
+
+    /// Return the transaction spending the outpoint, none if the outpoint is unspent or unknown
+    fn outpoint_spender(
+        &mut self,
+        outpoint: &bitcoin::OutPoint,
+    ) -> Result<Option<bitcoin::Txid>, Error> {
+        Ok(self.outpoint_spend(outpoint)?.map(|(txid, _)| txid))
+    }
+
+    /// Return the height and hash of the current chain tip
+    fn tip(&self) -> Result<(u64, BlockHash), Error>;
+
+    /// Return the timestamp of the block at the given height
+    fn block_time(&self, height: u64) -> Result<u32, Error>;
+
+    /// Return the header of the block at the given height with its transactions, none if the
+    /// backend does not serve full blocks
+    fn block_at(
+        &self,
+        height: u64,
+    ) -> Result<(BlockHeader, Option<Vec<bitcoin::Transaction>>), Error>;
+
+    /// Return the confirmed and unconfirmed sums, in satoshis, of the unspent outputs of the
+    /// address
+    fn balance(&mut self, address: &bitcoin::Address) -> Result<(u64, u64), Error>;
+
+    /// Return the average interval, in seconds, between the recent blocks up to the given tip
+    /// height, measured from their timestamps
+    fn average_block_interval(&self, tip_height: u64) -> Result<u64, Error> {
+        let sample = BLOCK_INTERVAL_SAMPLE.min(tip_height);
+        if sample == 0 {
+            return Err(Error::Farcaster(
+                "not enough blocks to measure the block interval".to_string(),
+            ));
+        }
+        let first = self.block_time(tip_height - sample)?;
+        let last = self.block_time(tip_height)?;
+        Ok((last.saturating_sub(first) as u64) / sample)
+    }
+
+    /// Return the indexes of the outputs of the transaction paying to the wallet of the backend
+    fn wallet_outputs(&self, _tx: &bitcoin::Transaction) -> Result<Vec<usize>, Error> {
+        Err(Error::Farcaster(
+            "no wallet available with this backend".to_string(),
+        ))
+    }
+
+    /// Sign the inputs of the transaction with the keys of the wallet of the backend
+    fn sign_with_wallet(&self, _tx: &bitcoin::Transaction) -> Result<bitcoin::Transaction, Error> {
+        Err(Error::Farcaster(
+            "no wallet available with this backend to sign transactions".to_string(),
+        ))
+    }
+
+    /// Return the mempool fee histogram, sorted by decreasing fee rate. Backends unable to
+    /// report it approximate the histogram with a single bucket at the high priority fee rate.
+    fn fee_histogram(&self) -> Result<Vec<FeeHistogramBucket>, Error> {
+        Ok(approximate_fee_histogram(self.estimate_fee(
+            HISTOGRAM_APPROXIMATION_TARGET,
+            HISTOGRAM_APPROXIMATION_TARGET,
+        )?))
+    }
+}
+
+/// Single bucket fee histogram at the high priority fee rate, the mempool size is unknown.
+fn approximate_fee_histogram(fee: FeeByPriority) -> Vec<FeeHistogramBucket> {
+    vec![FeeHistogramBucket {
+        sats_per_kvbyte: (fee.high_fee * 1.0e8).round() as u64,
+        vsize: 0,
+    }]
+}
+
+/// Backend selection and connection parameters, the syncer connects a new backend for each of
+/// its polling loops.
+#[derive(Clone, Debug)]
+pub enum BitcoinBackendConfig {
+    Electrum {
+        server: String,
+        proxy_address: Option<String>,
+    },
+    BitcoinRpc {
+        url: String,
+        auth: Auth,
+    },
+}
+
+impl BitcoinBackendConfig {
+    pub fn connect(&self) -> Result<Box<dyn BitcoinBackend>, Error> {
+        let connection_failed =
+            |err: &dyn std::fmt::Display| SyncerError::BackendConnectionFailed {
+                backend: self.to_string(),
+                reason: err.to_string(),
+            };
+        match self {
+            BitcoinBackendConfig::Electrum {
+                server,
+                proxy_address,
+            } => Ok(Box::new(
+                create_electrum_client(server, proxy_address.clone())
+                    .map_err(|err| connection_failed(&err))?,
+            )),
+            BitcoinBackendConfig::BitcoinRpc { url, auth } => Ok(Box::new(
+                BitcoinRpc::new(url, auth.clone()).map_err(|err| connection_failed(&err))?,
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for BitcoinBackendConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BitcoinBackendConfig::Electrum { server, .. } => write!(f, "electrum {}", server),
+            BitcoinBackendConfig::BitcoinRpc { url, .. } => write!(f, "bitcoin rpc {}", url),
+        }
+    }
+}
+
+pub fn create_electrum_client(
+    electrum_server: &str,
+    proxy_address: Option<String>,
+) -> Result<Client, electrum_client::Error> {
+    let config = ConfigBuilder::new().retry(0);
+
+    if let Some(proxy_address) = proxy_address {
+        let proxy = Socks5Config::new(proxy_address);
+        Client::from_config(electrum_server, config.socks5(Some(proxy)).unwrap().build())
+    } else {
+        Client::from_config(electrum_server, config.build())
+    }
+}
+
+/// Result of querying a backend to get a low priority and high priority fee rate.
+pub struct FeeByPriority {
+    pub low_fee: f64,
+    pub high_fee: f64,
+}
+
+/// Extend electrum client capabilities and query fee for low and high priority.
+pub trait GenericEstimateFee {
+    /// Query electrum for estimate fee for low and high priority or fall back on node's relay fee.
+    fn estimate_priority_fee(
+        &self,
+        near_target: usize,
+        far_target: usize,
+    ) -> Result<FeeByPriority, electrum_client::Error>;
+}
+
+impl GenericEstimateFee for Client {
+    fn estimate_priority_fee(
+        &self,
+        near_target: usize,
+        far_target: usize,
+    ) -> Result<FeeByPriority, electrum_client::Error> {
+        let low_fee;
+        let mut high_fee = ElectrumApi::estimate_fee(self, near_target)?;
+        if high_fee == -1.0 {
+            // None returned internally between node and electrum, fallback on relay_fee
+            high_fee = self.relay_fee()?;
+            low_fee = high_fee;
+        } else {
+            // Shortcut in case we want only 1 fee and near == far
+            if far_target != near_target {
+                low_fee = ElectrumApi::estimate_fee(self, far_target)?;
+            } else {
+                low_fee = high_fee
+            }
+        }
+        Ok(FeeByPriority { low_fee, high_fee })
+    }
+}
+
+pub fn query_addr_history(
+    client: &Client,
+    address: &BtcAddressAddendum,
+    filter: &TxFilter,
+) -> Result<Vec<AddressTx>, Error> {
+    // now that we have established _something_ has changed get the full transaction
+    // history of the address
+    let tx_hist = client.script_get_history(&address.address.script_pubkey())?;
+    history_txs(client, address, filter, tx_hist)
+}
+
+/// Query the histories of the addresses with a single batched call, returned in the order of the
+/// addresses. An address failing does not fail the others: if the batch fails as a whole the
+/// addresses are queried one by one.
+pub fn query_addrs_history(
+    client: &Client,
+    addresses: &[(BtcAddressAddendum, TxFilter)],
+) -> Vec<Result<Vec<AddressTx>, Error>> {
+    let script_pubkeys: Vec<bitcoin::Script> = addresses
+        .iter()
+        .map(|(address, _)| address.address.script_pubkey())
+        .collect();
+    match client.batch_script_get_history(script_pubkeys.iter()) {
+        Ok(tx_hists) if tx_hists.len() == addresses.len() => addresses
+            .iter()
+            .zip(tx_hists)
+            .map(|((address, filter), tx_hist)| history_txs(client, address, filter, tx_hist))
+            .collect(),
+        batch => {
+            if let Err(err) = batch {
+                debug!(
+                    "Batched address history query failed, querying one by one: {}",
+                    err
+                );
+            }
+            addresses
+                .iter()
+                .map(|(address, filter)| query_addr_history(client, address, filter))
+                .collect()
+        }
+    }
+}
+
+/// Retrieve the transactions of the history of an address matching the filter
+fn history_txs(
+    client: &Client,
+    address: &BtcAddressAddendum,
+    filter: &TxFilter,
+    tx_hist: Vec<GetHistoryRes>,
+) -> Result<Vec<AddressTx>, Error> {
+    let script_pubkey = address.address.script_pubkey();
+    trace!("history: {:?}", tx_hist);
+
+    let mut addr_txs = vec![];
+    for hist in tx_hist {
+        let txid = hist.tx_hash;
+        let tx = client.transaction_get(&txid)?;
+        let mut output_found = false;
+        let mut input_found = false;
+        let mut in_amount: u64 = 0;
+        let mut out_amount: u64 = 0;
+        for output in tx.output.iter() {
+            if output.script_pubkey == script_pubkey {
+                output_found = true;
+                in_amount += output.value;
+            } else {
+                // since we're filtering for a pubkey's history, if the tx's
+                // output's pubkey is _not_ the pubkey we're filtering for, we can infer
+                // from the fact that the tx is related to the pubkey that the tx
+                // must be spending _from_ the pubkey
+                out_amount += output.value;
+            }
+        }
+        for input in tx.input.iter() {
+            let prev_tx = match client.transaction_get(&input.previous_output.txid) {
+                Ok(tx) => tx,
+                Err(_) => {
+                    trace!(
+                        "Input transaction not found, this is probably a coinbase tx, skipping."
+                    );
+                    break;
+                }
+            };
+            for output in prev_tx.output.iter() {
+                if output.script_pubkey == script_pubkey {
+                    input_found = true;
+                }
+            }
+        }
+
+        let amount = match filter_amount(filter, output_found, input_found, in_amount, out_amount) {
+            Some(amount) => amount,
+            None => {
+                debug!(
+                    "Ignoring transaction {} in handle address notification, continuing",
+                    txid
+                );
+                continue;
+            }
+        };
+        addr_txs.push(AddressTx {
+            amount,
+            tx_id: txid.into(),
+            tx: bitcoin::consensus::serialize(&tx),
+            incoming: output_found && !input_found,
+        })
+    }
+    Ok(addr_txs)
+}
+
+/// Select the amount reported for a transaction of an address history, returns none if the
+/// transaction does not pass the filter
+fn filter_amount(
+    filter: &TxFilter,
+    output_found: bool,
+    input_found: bool,
+    in_amount: u64,
+    out_amount: u64,
+) -> Option<u64> {
+    match filter {
+        TxFilter::Incoming if output_found => Some(in_amount),
+        TxFilter::Outgoing if input_found => Some(out_amount),
+        TxFilter::All if output_found => Some(in_amount),
+        TxFilter::All if input_found => Some(out_amount),
+        _ => None,
+    }
+}
+
+impl BitcoinBackend for Client {
+    fn watch_address(
+        &mut self,
+        address: &BtcAddressAddendum,
+        filter: &TxFilter,
+    ) -> Result<Vec<AddressTx>, Error> {
+        query_addr_history(self, address, filter)
+    }
+
+    fn watch_addresses(
+        &mut self,
+        addresses: &[(BtcAddressAddendum, TxFilter)],
+    ) -> Vec<Result<Vec<AddressTx>, Error>> {
+        query_addrs_history(self, addresses)
+    }
+
+    fn get_tx(&self, txid: &bitcoin::Txid) -> Result<bitcoin::Transaction, Error> {
+        Ok(self.transaction_get(txid)?)
+    }
+
+    fn broadcast(&self, tx: &[u8]) -> Result<bitcoin::Txid, Error> {
+        self.transaction_broadcast_raw(tx)
+            .map_err(|err| SyncerError::rpc("blockchain.transaction.broadcast", err).into())
+    }
+
+    fn outpoint_status(&mut self, outpoint: &bitcoin::OutPoint) -> Result<OutpointStatus, Error> {
+        // the creating and spending transactions are part of the history of the output's script
+        let prev_tx = match self.transaction_get(&outpoint.txid) {
+            Ok(tx) => tx,
+            Err(_) => return Ok(OutpointStatus::default()),
+        };
+        let script_pubkey = match prev_tx.output.get(outpoint.vout as usize) {
+            Some(output) => output.script_pubkey.clone(),
+            None => return Ok(OutpointStatus::default()),
+        };
+        let (tip, _) = self.tip()?;
+        // electrum reports mempool transactions with a height of zero or less
+        let confirmations = |height: i32| {
+            if height > 0 {
+                (tip + 1).saturating_sub(height as u64) as u32
+            } else {
+                0
+            }
+        };
+        let mut status = OutpointStatus::default();
+        for hist in self.script_get_history(&script_pubkey)? {
+            if hist.tx_hash == outpoint.txid {
+                status.created_confirmations = Some(confirmations(hist.height));
+                continue;
+            }
+            let tx = self.transaction_get(&hist.tx_hash)?;
+            if tx
+                .input
+                .iter()
+                .any(|input| input.previous_output == *outpoint)
+            {
+                status.spending_txid = Some(hist.tx_hash);
+                status.spent_confirmations = Some(confirmations(hist.height));
+            }
+        }
+        Ok(status)
+    }
+
+    fn estimate_fee(&self, near_target: usize, far_target: usize) -> Result<FeeByPriority, Error> {
+        Ok(self.estimate_priority_fee(near_target, far_target)?)
+    }
+
+    fn tip(&self) -> Result<(u64, BlockHash), Error> {
+        let header = self
+            .block_headers_subscribe()
+            .map_err(|err| SyncerError::rpc("blockchain.headers.subscribe", err))?;
+        Ok((header.height as u64, header.header.block_hash()))
+    }
+
+    fn block_time(&self, height: u64) -> Result<u32, Error> {
+        Ok(self.block_header(height as usize)?.time)
+    }
+
+    fn block_at(
+        &self,
+        height: u64,
+    ) -> Result<(BlockHeader, Option<Vec<bitcoin::Transaction>>), Error> {
+        // electrum servers only serve block headers
+        Ok((self.block_header(height as usize)?, None))
+    }
+
+    fn balance(&mut self, address: &bitcoin::Address) -> Result<(u64, u64), Error> {
+        // outputs in the mempool are listed at height zero
+        let (confirmed, unconfirmed): (Vec<_>, Vec<_>) = self
+            .script_list_unspent(&address.script_pubkey())?
+            .into_iter()
+            .partition(|unspent| unspent.height > 0);
+        Ok((
+            confirmed.iter().map(|unspent| unspent.value).sum(),
+            unconfirmed.iter().map(|unspent| unspent.value).sum(),
+        ))
+    }
+
+    fn fee_histogram(&self) -> Result<Vec<FeeHistogramBucket>, Error> {
+        // the histogram is a list of [fee rate in sat/vB, vsize] pairs
+        let buckets: Option<Vec<FeeHistogramBucket>> = self
+            .raw_call("mempool.get_fee_histogram", vec![])
+            .ok()
+            .and_then(|histogram| {
+                histogram
+                    .as_array()?
+                    .iter()
+                    .map(|bucket| {
+                        Some(FeeHistogramBucket {
+                            sats_per_kvbyte: (bucket.get(0)?.as_f64()? * 1000.0).round() as u64,
+                            vsize: bucket.get(1)?.as_u64()?,
+                        })
+                    })
+                    .collect()
+            });
+        match buckets {
+            Some(buckets) if !buckets.is_empty() => Ok(buckets),
+            _ => Ok(approximate_fee_histogram(BitcoinBackend::estimate_fee(
+                self,
+                HISTOGRAM_APPROXIMATION_TARGET,
+                HISTOGRAM_APPROXIMATION_TARGET,
+            )?)),
+        }
+    }
+}
+
+/// Bitcoin Core node accessed through its RPC interface. Watched addresses are imported as
+/// watch-only addresses in the node's loaded wallet.
+pub struct BitcoinRpc {
+    client: bitcoincore_rpc::Client,
+    imported: HashSet<bitcoin::Address>,
+    // spending transactions found for the watched outpoints
+    spenders: HashMap<bitcoin::OutPoint, bitcoin::Txid>,
+    // last tip at which the watched outpoints were still unspent in the chain, the blocks up to
+    // it do not need to be searched for their spender
+    unspent_at: HashMap<bitcoin::OutPoint, (u64, BlockHash)>,
+    // false once the node proved too old to look mempool spenders up by outpoint
+    spending_prevout_rpc: bool,
+    // inputs of the mempool transactions already fetched, used when the node cannot look mempool
+    // spenders up by outpoint
+    mempool_txs: HashMap<bitcoin::Txid, Vec<bitcoin::OutPoint>>,
+    mempool_spends: HashMap<bitcoin::OutPoint, bitcoin::Txid>,
+}
+
+/// Error code of the calls to a method unknown to the node
+const RPC_METHOD_NOT_FOUND: i32 = -32601;
+
+/// Status of a transaction as reported by the node
+pub struct TxStatus {
+    pub tx: bitcoin::Transaction,
+    pub block_hash: Option<BlockHash>,
+    pub confirmations: u32,
+}
+
+impl BitcoinRpc {
+    pub fn new(url: &str, auth: Auth) -> Result<Self, Error> {
+        debug!("creating BitcoinRpc client");
+        let client = bitcoincore_rpc::Client::new(url, auth)?;
+        Ok(Self {
+            client,
+            imported: none!(),
+            spenders: none!(),
+            unspent_at: none!(),
+            spending_prevout_rpc: true,
+            mempool_txs: none!(),
+            mempool_spends: none!(),
+        })
+    }
+
+    /// Check the node maintains a synced transaction index, without it the node only knows
+    /// about the confirmed transactions of its wallet
+    pub fn require_txindex(&self) -> Result<(), Error> {
+        let info: serde_json::Value = match self.client.call("getindexinfo", &[]) {
+            Ok(info) => info,
+            // nodes answering with an error are too old to maintain the index info
+            Err(bitcoincore_rpc::Error::JsonRpc(jsonrpc::error::Error::Rpc(err))) => {
+                return Err(SyncerError::TxIndexUnavailable(err.message).into())
+            }
+            Err(err) => return Err(SyncerError::rpc("getindexinfo", err).into()),
+        };
+        match info.get("txindex") {
+            Some(txindex)
+                if txindex.get("synced").and_then(|synced| synced.as_bool()) == Some(true) =>
+            {
+                Ok(())
+            }
+            Some(_) => Err(SyncerError::TxIndexUnavailable(
+                "the index is still syncing".to_string(),
+            )
+            .into()),
+            None => {
+                Err(SyncerError::TxIndexUnavailable("the index is disabled".to_string()).into())
+            }
+        }
+    }
+
+    /// Return the status of the transaction, none if the node does not know it
+    pub fn tx_status(&self, txid: &bitcoin::Txid) -> Result<Option<TxStatus>, Error> {
+        match self.client.get_raw_transaction_info(txid, None) {
+            Ok(info) => Ok(Some(TxStatus {
+                tx: info.transaction()?,
+                block_hash: info.blockhash,
+                confirmations: info.confirmations.unwrap_or(0),
+            })),
+            Err(err) => {
+                trace!("transaction {} not found: {}", txid, err);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Import the address in the wallet as watch-only if not imported yet, returns the label of
+    /// its wallet entries
+    fn import_address(&mut self, address: &BtcAddressAddendum) -> Result<String, Error> {
+        let label = format!("{}-{}", WATCH_ONLY_LABEL_PREFIX, address.address);
+        if !self.imported.contains(&address.address) {
+            debug!("importing watch-only address: {}", address.address);
+            // addresses are freshly derived for each swap, rescanning the chain is not needed
+            self.client
+                .import_address(&address.address, Some(&label), Some(false))?;
+            self.imported.insert(address.address.clone());
+        }
+        Ok(label)
+    }
+
+    /// The transactions of the wallet entries of an address matching the filter
+    fn address_txs(
+        &self,
+        entries: Vec<ListTransactionResult>,
+        filter: &TxFilter,
+    ) -> Result<Vec<AddressTx>, Error> {
+        // group the wallet entries per transaction, a transaction may pay to and spend from
+        // the address
+        let mut grouped: HashMap<bitcoin::Txid, (u64, u64, bool, bool)> = HashMap::new();
+        for entry in entries {
+            let (in_amount, out_amount, output_found, input_found) =
+                grouped.entry(entry.info.txid).or_default();
+            match entry.detail.category {
+                GetTransactionResultDetailCategory::Send => {
+                    *input_found = true;
+                    *out_amount += entry.detail.amount.as_sat().unsigned_abs();
+                }
+                GetTransactionResultDetailCategory::Receive
+                | GetTransactionResultDetailCategory::Generate
+                | GetTransactionResultDetailCategory::Immature => {
+                    *output_found = true;
+                    *in_amount += entry.detail.amount.as_sat().unsigned_abs();
+                }
+                GetTransactionResultDetailCategory::Orphan => {}
+            }
+        }
+
+        let mut addr_txs = vec![];
+        for (txid, (in_amount, out_amount, output_found, input_found)) in grouped {
+            let amount =
+                match filter_amount(filter, output_found, input_found, in_amount, out_amount) {
+                    Some(amount) => amount,
+                    None => continue,
+                };
+            let tx = self.get_tx(&txid)?;
+            addr_txs.push(AddressTx {
+                amount,
+                tx_id: txid.into(),
+                tx: bitcoin::consensus::serialize(&tx),
+                incoming: output_found && !input_found,
+            });
+        }
+        Ok(addr_txs)
+    }
+
+    /// Return the transaction spending the outpoint with its confirmations. The confirmations
+    /// of the transaction creating the outpoint are only looked up if not given and the
+    /// spender must be searched.
+    fn spend(
+        &mut self,
+        outpoint: &bitcoin::OutPoint,
+        created_confirmations: Option<u32>,
+    ) -> Result<Option<(bitcoin::Txid, u32)>, Error> {
+        // the node does not index spending transactions, the outpoint is known spent if it
+        // is missing from the utxo set while the transaction creating it is known
+        if let Some(txout) = self
+            .client
+            .get_tx_out(&outpoint.txid, outpoint.vout, Some(true))?
+        {
+            self.spenders.remove(outpoint);
+            // remember the tip the outpoint is unspent at, once per tip, so its spender is
+            // searched in the blocks mined after it only
+            let recorded = self.unspent_at.get(outpoint).map(|(_, hash)| *hash);
+            if txout.confirmations > 0 && recorded != Some(txout.bestblock) {
+                let height = self.client.get_block_header_info(&txout.bestblock)?.height;
+                self.unspent_at
+                    .insert(*outpoint, (height as u64, txout.bestblock));
+            }
+            return Ok(None);
+        }
+        // reuse the spender found previously as long as the node still knows it
+        if let Some(txid) = self.spenders.get(outpoint).cloned() {
+            if let Some(spent) = self.tx_status(&txid)? {
+                return Ok(Some((txid, spent.confirmations)));
+            }
+        }
+        let created_confirmations = match created_confirmations {
+            Some(confirmations) => confirmations,
+            None => match self.tx_status(&outpoint.txid)? {
+                Some(created) => created.confirmations,
+                None => return Ok(None),
+            },
+        };
+        let spend = self.find_spender(outpoint, created_confirmations)?;
+        match spend {
+            Some((txid, _)) => self.spenders.insert(*outpoint, txid),
+            None => self.spenders.remove(outpoint),
+        };
+        Ok(spend)
+    }
+
+    /// Search the transaction spending the outpoint, returned with its confirmations. Outpoints
+    /// still in the chain utxo set are only spent in the mempool, the others are searched in
+    /// the blocks mined since they were last seen unspent.
+    fn find_spender(
+        &mut self,
+        outpoint: &bitcoin::OutPoint,
+        created_confirmations: u32,
+    ) -> Result<Option<(bitcoin::Txid, u32)>, Error> {
+        // read the tip first, the outpoint is unspent up to it at least if found in the utxo set
+        let (tip, tip_hash) = self.tip()?;
+        let chain_unspent = created_confirmations > 0
+            && self
+                .client
+                .get_tx_out(&outpoint.txid, outpoint.vout, Some(false))?
+                .is_some();
+        if chain_unspent {
+            self.unspent_at.insert(*outpoint, (tip, tip_hash));
+        }
+        if created_confirmations == 0 || chain_unspent {
+            return Ok(self.mempool_spender(outpoint)?.map(|txid| (txid, 0)));
+        }
+        let created_height = (tip + 1).saturating_sub(created_confirmations as u64);
+        // blocks reorganized away since the outpoint was last seen unspent are searched again
+        let from_height = match self.unspent_at.get(outpoint) {
+            Some((height, hash)) if self.client.get_block_hash(*height).ok() == Some(*hash) => {
+                height + 1
+            }
+            _ => created_height,
+        };
+        for height in (from_height.max(created_height)..=tip).rev() {
+            let block = self
+                .client
+                .get_block(&self.client.get_block_hash(height)?)?;
+            if let Some(tx) = block.txdata.iter().find(|tx| {
+                tx.input
+                    .iter()
+                    .any(|input| input.previous_output == *outpoint)
+            }) {
+                return Ok(Some((tx.txid(), (tip + 1 - height) as u32)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Return the mempool transaction spending the outpoint. Nodes older than Bitcoin Core 24
+    /// cannot look it up, the inputs of the mempool transactions are then indexed as they enter
+    /// the mempool.
+    fn mempool_spender(
+        &mut self,
+        outpoint: &bitcoin::OutPoint,
+    ) -> Result<Option<bitcoin::Txid>, Error> {
+        if self.spending_prevout_rpc {
+            let prevout = serde_json::json!([{ "txid": outpoint.txid, "vout": outpoint.vout }]);
+            match self
+                .client
+                .call::<serde_json::Value>("gettxspendingprevout", &[prevout])
+            {
+                Ok(res) => {
+                    return Ok(res
+                        .get(0)
+                        .and_then(|spent| spent.get("spendingtxid"))
+                        .and_then(|txid| txid.as_str())
+                        .and_then(|txid| txid.parse().ok()))
+                }
+                Err(bitcoincore_rpc::Error::JsonRpc(jsonrpc::error::Error::Rpc(err)))
+                    if err.code == RPC_METHOD_NOT_FOUND =>
+                {
+                    debug!("gettxspendingprevout unavailable, indexing the mempool spends");
+                    self.spending_prevout_rpc = false;
+                }
+                Err(err) => return Err(SyncerError::rpc("gettxspendingprevout", err).into()),
+            }
+        }
+        let mempool: HashSet<bitcoin::Txid> = self.client.get_raw_mempool()?.into_iter().collect();
+        let mempool_spends = &mut self.mempool_spends;
+        self.mempool_txs.retain(|txid, inputs| {
+            let in_mempool = mempool.contains(txid);
+            if !in_mempool {
+                for input in inputs.iter() {
+                    mempool_spends.remove(input);
+                }
+            }
+            in_mempool
+        });
+        for txid in mempool {
+            if self.mempool_txs.contains_key(&txid) {
+                continue;
+            }
+            // the transaction may have left the mempool in the meantime
+            if let Ok(tx) = self.client.get_raw_transaction(&txid, None) {
+                let inputs: Vec<bitcoin::OutPoint> =
+                    tx.input.iter().map(|input| input.previous_output).collect();
+                for input in inputs.iter() {
+                    self.mempool_spends.insert(*input, txid);
+                }
+                self.mempool_txs.insert(txid, inputs);
+            }
+        }
+        Ok(self.mempool_spends.get(outpoint).cloned())
+    }
+}
+
+impl BitcoinBackend for BitcoinRpc {
+    fn watch_address(
+        &mut self,
+        address: &BtcAddressAddendum,
+        filter: &TxFilter,
+    ) -> Result<Vec<AddressTx>, Error> {
+        let label = self.import_address(address)?;
+        let entries = self.client.list_transactions(
+            Some(&label),
+            Some(LIST_TRANSACTIONS_COUNT),
+            None,
+            Some(true),
+        )?;
+        self.address_txs(entries, filter)
+    }
+
+    fn get_tx(&self, txid: &bitcoin::Txid) -> Result<bitcoin::Transaction, Error> {
+        Ok(self.client.get_raw_transaction(txid, None)?)
+    }
+
+    fn broadcast(&self, tx: &[u8]) -> Result<bitcoin::Txid, Error> {
+        self.client
+            .send_raw_transaction(tx)
+            .map_err(|err| SyncerError::rpc("sendrawtransaction", err).into())
+    }
+
+    fn outpoint_status(&mut self, outpoint: &bitcoin::OutPoint) -> Result<OutpointStatus, Error> {
+        let created = match self.tx_status(&outpoint.txid)? {
+            Some(created) if created.tx.output.len() > outpoint.vout as usize => created,
+            _ => return Ok(OutpointStatus::default()),
+        };
+        let mut status = OutpointStatus {
+            created_confirmations: Some(created.confirmations),
+            ..Default::default()
+        };
+        if let Some((txid, confirmations)) = self.spend(outpoint, Some(created.confirmations))? {
+            status.spending_txid = Some(txid);
+            status.spent_confirmations = Some(confirmations);
+        }
+        Ok(status)
+    }
+
+    fn outpoint_spend(
+        &mut self,
+        outpoint: &bitcoin::OutPoint,
+    ) -> Result<Option<(bitcoin::Txid, u32)>, Error> {
+        self.spend(outpoint, None)
+    }
+
+    fn estimate_fee(&self, near_target: usize, far_target: usize) -> Result<FeeByPriority, Error> {
+        let estimate_smart_fee = |target: usize| {
+            self.client
+                .estimate_smart_fee(target as u16, None)
+                .map_err(|err| SyncerError::rpc("estimatesmartfee", err))
+        };
+        let high_fee = estimate_smart_fee(near_target)?.fee_rate;
+        let low_fee = if far_target != near_target {
+            estimate_smart_fee(far_target)?.fee_rate
+        } else {
+            high_fee
+        };
+        match (high_fee, low_fee) {
+            (Some(high_fee), Some(low_fee)) => Ok(FeeByPriority {
+                low_fee: low_fee.as_btc(),
+                high_fee: high_fee.as_btc(),
+            }),
+            _ => {
+                // the node does not have enough data to estimate, fallback on relay_fee
+                let relay_fee = self.client.get_network_info()?.relay_fee.as_btc();
+                Ok(FeeByPriority {
+                    low_fee: relay_fee,
+                    high_fee: relay_fee,
+                })
+            }
+        }
+    }
+
+    fn tip(&self) -> Result<(u64, BlockHash), Error> {
+        let height = self
+            .client
+            .get_block_count()
+            .map_err(|err| SyncerError::rpc("getblockcount", err))?;
+        let block_hash = self
+            .client
+            .get_block_hash(height)
+            .map_err(|err| SyncerError::rpc("getblockhash", err))?;
+        Ok((height, block_hash))
+    }
+
+    fn block_time(&self, height: u64) -> Result<u32, Error> {
+        let block_hash = self.client.get_block_hash(height)?;
+        Ok(self.client.get_block_header(&block_hash)?.time)
+    }
+
+    fn block_at(
+        &self,
+        height: u64,
+    ) -> Result<(BlockHeader, Option<Vec<bitcoin::Transaction>>), Error> {
+        let block_hash = self.client.get_block_hash(height)?;
+        let block = self.client.get_block(&block_hash)?;
+        Ok((block.header, Some(block.txdata)))
+    }
+
+    fn balance(&mut self, address: &bitcoin::Address) -> Result<(u64, u64), Error> {
+        // the utxo set scan covers every confirmed output of the address, imported or not
+        let scan = self.client.scan_tx_out_set_blocking(&[
+            bitcoincore_rpc::json::ScanTxOutRequest::Single(format!("addr({})", address)),
+        ])?;
+        // the mempool is only known through the wallet, for the outputs received since the
+        // address is imported: the swaps import their addresses when watching them
+        self.import_address(&BtcAddressAddendum {
+            address: address.clone(),
+        })?;
+        let unconfirmed = self
+            .client
+            .list_unspent(Some(0), Some(0), Some(&[address]), Some(true), None)?
+            .iter()
+            .map(|unspent| unspent.amount.as_sat())
+            .sum();
+        Ok((scan.total_amount.as_sat(), unconfirmed))
+    }
+
+    fn wallet_outputs(&self, tx: &bitcoin::Transaction) -> Result<Vec<usize>, Error> {
+        let network = match self.client.get_blockchain_info()?.chain.as_str() {
+            "main" => bitcoin::Network::Bitcoin,
+            "test" => bitcoin::Network::Testnet,
+            "signet" => bitcoin::Network::Signet,
+            _ => bitcoin::Network::Regtest,
+        };
+        let mut outputs = vec![];
+        for (vout, output) in tx.output.iter().enumerate() {
+            let address = match bitcoin::Address::from_script(&output.script_pubkey, network) {
+                Some(address) => address,
+                None => continue,
+            };
+            if self.client.get_address_info(&address)?.is_mine == Some(true) {
+                outputs.push(vout);
+            }
+        }
+        Ok(outputs)
+    }
+
+    fn sign_with_wallet(&self, tx: &bitcoin::Transaction) -> Result<bitcoin::Transaction, Error> {
+        let signed = self
+            .client
+            .sign_raw_transaction_with_wallet(tx, None, None)?;
+        if !signed.complete {
+            return Err(Error::Farcaster(format!(
+                "the wallet could not sign all the inputs of {}: {:?}",
+                tx.txid(),
+                signed.errors
+            )));
+        }
+        Ok(signed.transaction()?)
+    }
+}
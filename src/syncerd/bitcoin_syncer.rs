@@ -8,29 +8,40 @@ use crate::bus::info::Address;
 use crate::bus::sync::{BridgeEvent, SyncMsg};
 use crate::bus::{AddressSecretKey, BusMsg};
 use crate::error::SyncerError;
-use crate::syncerd::opts::Opts;
+use crate::syncerd::bitcoin_backend::{
+    create_electrum_client, query_addrs_history, BitcoinBackend, BitcoinBackendConfig, BitcoinRpc,
+    FeeByPriority, GenericEstimateFee,
+};
+use crate::syncerd::opts::{adaptive_poll, Opts, DEFAULT_POLL_INTERVAL_MS};
+use crate::syncerd::polling::AdaptivePoll;
+use crate::syncerd::retry::RetryPolicy;
 use crate::syncerd::runtime::SyncerdTask;
 use crate::syncerd::runtime::Synclet;
-use crate::syncerd::syncer_state::{AddressTx, BalanceServiceIdPair, TransactionServiceIdPair};
+use crate::syncerd::syncer_state::{
+    balance_event, AddressTx, BalanceServiceIdPair, BalanceTask, InternalId,
+    TransactionServiceIdPair,
+};
 use crate::syncerd::syncer_state::{GetTxServiceIdPair, SyncerState};
-use crate::syncerd::types::{AddressAddendum, SweepAddressAddendum, Task};
+use crate::syncerd::types::{validate_sweep_fee_rate, AddressAddendum, SweepAddressAddendum, Task};
 use crate::syncerd::BtcAddressAddendum;
 use crate::syncerd::Event;
 use crate::syncerd::FeeEstimations;
+use crate::syncerd::GetMempoolFeeHistogram;
 use crate::syncerd::GetTx;
 use crate::syncerd::Health;
 use crate::syncerd::TaskTarget;
 use crate::syncerd::TransactionBroadcasted;
 use crate::syncerd::TransactionRetrieved;
-use crate::syncerd::{AddressBalance, BroadcastTransaction};
+use crate::syncerd::TxidOrOutpoint;
+use crate::syncerd::{AddressBalance, BroadcastFailure, BroadcastTransaction};
 use crate::{error::Error, syncerd::syncer_state::create_set};
 use crate::{LogStyle, ServiceId};
+use bitcoin::consensus::Decodable;
 use bitcoin::hashes::hex::ToHex;
 use bitcoin::BlockHash;
 use bitcoin::Script;
-use electrum_client::{
-    Client, ConfigBuilder, ElectrumApi, HeaderNotification, Hex32Bytes, Socks5Config,
-};
+use bitcoincore_rpc::Auth;
+use electrum_client::{Client, ElectrumApi, HeaderNotification, Hex32Bytes};
 use farcaster_core::bitcoin::segwitv0::signature_hash;
 use farcaster_core::bitcoin::transaction::TxInRef;
 use farcaster_core::blockchain::{Blockchain, Network};
@@ -40,18 +51,26 @@ use internet2::zeromq::ZmqSocketType;
 use internet2::SendRecvMessage;
 use internet2::TypedEnum;
 use std::collections::{HashMap, HashSet};
-use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc::Receiver as TokioReceiver;
 use tokio::sync::mpsc::Sender as TokioSender;
 use tokio::sync::Mutex;
 
-use super::HealthCheck;
+use super::{
+    BlockData, BumpFee, FeeBumped, GetBackendStatus, GetBalance, GetBlockByHeight, HealthCheck,
+    SetPollIntervals,
+};
 use super::{TxFilter, Txid};
 
 const RETRY_TIMEOUT: u64 = 5;
 const PING_WAIT: u8 = 2;
+/// Minimum fee rate, in satoshis per kvB, a replacement transaction pays on top of the fee of
+/// the replaced one, the default incremental relay fee of BIP125
+const INCREMENTAL_RELAY_FEE: u64 = 1000;
+/// Smallest output value relayed by the nodes
+const DUST_LIMIT: u64 = 546;
 
 pub struct ElectrumRpc {
     client: Client,
@@ -73,20 +92,6 @@ pub struct AddressNotif {
     txs: Vec<AddressTx>,
 }
 
-fn create_electrum_client(
-    electrum_server: &str,
-    proxy_address: Option<String>,
-) -> Result<Client, electrum_client::Error> {
-    let config = ConfigBuilder::new().retry(0);
-
-    if let Some(proxy_address) = proxy_address {
-        let proxy = Socks5Config::new(proxy_address);
-        Client::from_config(electrum_server, config.socks5(Some(proxy)).unwrap().build())
-    } else {
-        Client::from_config(electrum_server, config.build())
-    }
-}
-
 impl ElectrumRpc {
     fn new(
         electrum_server: &str,
@@ -115,18 +120,38 @@ impl ElectrumRpc {
         Ok(())
     }
 
-    pub fn script_subscribe(
+    /// Subscribe to the addresses not subscribed yet and query the histories of all the given
+    /// addresses with a single batched call. Only the addresses both subscribed and queried are
+    /// notified, the others are logged and left to be retried.
+    pub fn subscribe_addresses(
         &mut self,
-        address_addendum: BtcAddressAddendum,
-        filter: TxFilter,
-    ) -> Result<AddressNotif, Error> {
-        debug!("attempting subscribing to: {}", address_addendum.address);
-
-        if !self.addresses.contains_key(&address_addendum) {
+        addresses: &[(BtcAddressAddendum, TxFilter)],
+    ) -> Vec<AddressNotif> {
+        let mut subscribed = vec![];
+        for (address_addendum, filter) in addresses {
+            if self.addresses.contains_key(address_addendum) {
+                subscribed.push((address_addendum.clone(), filter.clone()));
+                continue;
+            }
             debug!("subscribing to: {}", address_addendum.address);
-            let script_status = self
+            let script_status = match self
                 .client
-                .script_subscribe(&address_addendum.address.script_pubkey())?;
+                .script_subscribe(&address_addendum.address.script_pubkey())
+            {
+                Ok(script_status) => script_status,
+                // do nothing if we are already subscribed
+                Err(electrum_client::Error::AlreadySubscribed(_)) => {
+                    subscribed.push((address_addendum.clone(), filter.clone()));
+                    continue;
+                }
+                Err(err) => {
+                    error!(
+                        "error subscribing to address {}: {}",
+                        address_addendum.address, err
+                    );
+                    continue;
+                }
+            };
             self.addresses
                 .insert(address_addendum.clone(), (script_status, filter.clone()));
             debug!(
@@ -134,14 +159,31 @@ impl ElectrumRpc {
                 &address_addendum.address,
                 &script_status.map(|h| hex::encode(h.to_vec()))
             );
+            subscribed.push((address_addendum.clone(), filter.clone()));
         }
-        let txs = query_addr_history(&mut self.client, &address_addendum, &filter)?;
-        logging(&txs, &address_addendum);
-        let notif = AddressNotif {
-            address: address_addendum,
-            txs,
-        };
-        Ok(notif)
+        self.address_notifs(&subscribed)
+    }
+
+    /// Query the histories of the addresses with a single batched call, an address whose
+    /// history fails is logged and left out
+    fn address_notifs(&self, addresses: &[(BtcAddressAddendum, TxFilter)]) -> Vec<AddressNotif> {
+        addresses
+            .iter()
+            .zip(query_addrs_history(&self.client, addresses))
+            .filter_map(|((address, _), history)| match history {
+                Ok(txs) => Some(AddressNotif {
+                    address: address.clone(),
+                    txs,
+                }),
+                Err(err) => {
+                    debug!(
+                        "Error querying address history of {}: {}",
+                        address.address, err
+                    );
+                    None
+                }
+            })
+            .collect()
     }
 
     pub fn new_block_check(&mut self) -> Result<Vec<Block>, Error> {
@@ -159,9 +201,10 @@ impl ElectrumRpc {
         Ok(blocks)
     }
 
-    /// check if a subscribed address received a new transaction
+    /// check if a subscribed address received a new transaction, the histories of the changed
+    /// addresses are queried with a single batched call
     pub fn address_change_check(&mut self) -> Vec<AddressNotif> {
-        let mut notifs: Vec<AddressNotif> = vec![];
+        let mut changed: Vec<(BtcAddressAddendum, TxFilter)> = vec![];
         for (address, (previous_status, filter)) in self.addresses.clone().into_iter() {
             // get pending notifications for this address/script_pubkey
             let script_pubkey = &address.address.script_pubkey();
@@ -184,27 +227,19 @@ impl ElectrumRpc {
                             hex::encode(script_status.to_vec())
                         );
                     }
-                    match query_addr_history(&mut self.client, &address, &filter) {
-                        Ok(txs) => {
-                            debug!("creating AddressNotif");
-                            logging(&txs, &address);
-                            let new_notif = AddressNotif {
-                                address: address.clone(),
-                                txs,
-                            };
-                            debug!("creating address notifications");
-                            notifs.push(new_notif);
-                        }
-                        Err(err) => {
-                            debug!("Error querying address history: {}", err);
-                        }
+                    if !changed.iter().any(|(changed, _)| *changed == address) {
+                        changed.push((address.clone(), filter.clone()));
                     }
                 } else {
                     debug!("state did not change for given address");
                 }
             }
         }
-        notifs
+        if changed.is_empty() {
+            return vec![];
+        }
+        debug!("creating address notifications");
+        self.address_notifs(&changed)
     }
 
     async fn query_transactions(&self, state: Arc<Mutex<SyncerState>>, unseen: bool) {
@@ -357,99 +392,6 @@ impl ElectrumRpc {
     }
 }
 
-fn query_addr_history(
-    client: &mut Client,
-    address: &BtcAddressAddendum,
-    filter: &TxFilter,
-) -> Result<Vec<AddressTx>, Error> {
-    // now that we have established _something_ has changed get the full transaction
-    // history of the address
-    let script_pubkey = address.address.script_pubkey();
-    let tx_hist = client.script_get_history(&script_pubkey)?;
-    trace!("history: {:?}", tx_hist);
-
-    let mut addr_txs = vec![];
-    for hist in tx_hist {
-        let txid = hist.tx_hash;
-        let tx = client.transaction_get(&txid)?;
-        let mut output_found = false;
-        let mut input_found = false;
-        let mut in_amount: u64 = 0;
-        let mut out_amount: u64 = 0;
-        for output in tx.output.iter() {
-            if output.script_pubkey == script_pubkey {
-                output_found = true;
-                in_amount += output.value;
-            } else {
-                // since we're filtering for a pubkey's history, if the tx's
-                // output's pubkey is _not_ the pubkey we're filtering for, we can infer
-                // from the fact that the tx is related to the pubkey that the tx
-                // must be spending _from_ the pubkey
-                out_amount += output.value;
-            }
-        }
-        for input in tx.input.iter() {
-            let prev_tx = match client.transaction_get(&input.previous_output.txid) {
-                Ok(tx) => tx,
-                Err(_) => {
-                    trace!(
-                        "Input transaction not found, this is probably a coinbase tx, skipping."
-                    );
-                    break;
-                }
-            };
-            for output in prev_tx.output.iter() {
-                if output.script_pubkey == script_pubkey {
-                    input_found = true;
-                }
-            }
-        }
-
-        let amount = match filter {
-            TxFilter::Incoming => {
-                if output_found {
-                    in_amount
-                } else {
-                    debug!(
-                        "Ignoring outgoing transaction {} in handle address notification, continuing", txid
-                    );
-                    continue;
-                }
-            }
-            TxFilter::Outgoing => {
-                if input_found {
-                    out_amount
-                } else {
-                    debug!(
-                        "Ignoring incoming transaction {} in handle address notification, continuing", txid
-                    );
-                    continue;
-                }
-            }
-            TxFilter::All => {
-                if output_found {
-                    in_amount
-                } else if input_found {
-                    out_amount
-                } else {
-                    debug!(
-                        "Ignoring transaction {} in handle address notifcation, continuing",
-                        txid
-                    );
-                    continue;
-                }
-            }
-        };
-        addr_txs.push(AddressTx {
-            amount,
-            tx_id: txid.into(),
-            tx: bitcoin::consensus::serialize(&tx),
-            incoming: output_found && !input_found,
-        })
-    }
-    Ok(addr_txs)
-}
-
 /// Returns the script code used for spending a P2WPKH output if this script is a script pubkey
 /// for a P2WPKH output. The `scriptCode` is described in [BIP143].
 ///
@@ -469,6 +411,7 @@ fn sweep_address(
     source_secret_key: bitcoin::secp256k1::SecretKey,
     source_address: bitcoin::Address,
     dest_address: bitcoin::Address,
+    fee_sat_per_kvb: Option<u64>,
     client: &Client,
     network: bitcoin::Network,
 ) -> Result<Vec<Txid>, Error> {
@@ -522,14 +465,20 @@ fn sweep_address(
         }],
     };
 
-    // TODO (maybe): make blocks_until_confirmation or fee_btc_per_kvb configurable by user (see FeeStrategy)
-    let blocks_until_confirmation = 2;
-    let fee_sat_per_kvb = (client
-        // because near == far (target) low and high fee are equal
-        .estimate_priority_fee(blocks_until_confirmation, blocks_until_confirmation)?
-        .high_fee
-        * 1.0e8)
-        .ceil() as u64;
+    let fee_sat_per_kvb = match fee_sat_per_kvb {
+        Some(fee_sat_per_kvb) => {
+            validate_sweep_fee_rate(fee_sat_per_kvb).map_err(Error::Farcaster)?
+        }
+        None => {
+            let blocks_until_confirmation = 2;
+            (client
+                // because near == far (target) low and high fee are equal
+                .estimate_priority_fee(blocks_until_confirmation, blocks_until_confirmation)?
+                .high_fee
+                * 1.0e8)
+                .ceil() as u64
+        }
+    };
     let fee = p2wpkh_signed_tx_fee(fee_sat_per_kvb, unsigned_tx.vsize(), unspent_txs.len());
 
     // 546 is the dust limit for a p2pkh output. This covers both cases for when
@@ -600,8 +549,8 @@ async fn run_syncerd_bridge_event_sender(
 }
 
 async fn run_syncerd_task_receiver(
-    electrum_server: String,
-    proxy_address: Option<String>,
+    backend: BitcoinBackendConfig,
+    retry: RetryPolicy,
     receive_task_channel: Receiver<SyncerdTask>,
     state: Arc<Mutex<SyncerState>>,
     transaction_broadcast_tx: TokioSender<(BroadcastTransaction, ServiceId)>,
@@ -624,7 +573,13 @@ async fn run_syncerd_task_receiver(
                         }
                         Task::GetAddressBalance(task) => {
                             balance_get_tx
-                                .send((task, syncerd_task.source))
+                                .send((BalanceTask::Address(task), syncerd_task.source))
+                                .await
+                                .expect("failed on balance_get sender");
+                        }
+                        Task::GetBalance(task) => {
+                            balance_get_tx
+                                .send((BalanceTask::Descriptor(task), syncerd_task.source))
                                 .await
                                 .expect("failed on balance_get sender");
                         }
@@ -634,6 +589,15 @@ async fn run_syncerd_task_receiver(
                             drop(state_guard);
                         }
                         Task::SweepAddress(task) => match task.addendum.clone() {
+                            SweepAddressAddendum::Bitcoin(_)
+                                if matches!(backend, BitcoinBackendConfig::BitcoinRpc { .. }) =>
+                            {
+                                error!("Aborting sweep address task - not available with a bitcoin rpc backend");
+                                let mut state_guard = state.lock().await;
+                                state_guard
+                                    .abort(TaskTarget::TaskId(task.id), syncerd_task.source, true)
+                                    .await;
+                            }
                             SweepAddressAddendum::Bitcoin(sweep) => {
                                 let addr = sweep.source_address;
                                 debug!("Sweeping address: {}", addr.addr());
@@ -694,17 +658,81 @@ async fn run_syncerd_task_receiver(
                                 drop(state_guard);
                             }
                         },
+                        Task::WatchAddresses(task) => {
+                            // the runtime splits the batches into WatchAddress tasks, the synclet
+                            // polls all the watched addresses together
+                            error!("Unexpected watch addresses batch {:?} in the synclet", task);
+                        }
+                        Task::WatchMoneroAddress(task) => {
+                            error!(
+                                "Aborting watch monero address task - not available for Bitcoin"
+                            );
+                            let mut state_guard = state.lock().await;
+                            state_guard
+                                .abort(TaskTarget::TaskId(task.id), syncerd_task.source, true)
+                                .await;
+                            drop(state_guard);
+                        }
                         Task::WatchHeight(task) => {
                             let mut state_guard = state.lock().await;
                             state_guard.watch_height(task, syncerd_task.source).await;
                             drop(state_guard);
                         }
+                        Task::WatchEstimatedRefundTime(task) => {
+                            let mut state_guard = state.lock().await;
+                            state_guard
+                                .watch_estimated_refund_time(task, syncerd_task.source)
+                                .await;
+                            drop(state_guard);
+                        }
                         Task::WatchTransaction(task) => {
                             debug!("received new watch tx task for txid: {}", task.hash);
                             let mut state_guard = state.lock().await;
                             state_guard.watch_transaction(task, syncerd_task.source);
                             drop(state_guard);
                         }
+                        Task::WatchOutpoint(task) => {
+                            debug!("received new watch outpoint task for: {}", task.outpoint);
+                            let mut state_guard = state.lock().await;
+                            state_guard.watch_outpoint(task, syncerd_task.source);
+                            drop(state_guard);
+                        }
+                        Task::WatchOutpointSpend(task) => {
+                            debug!(
+                                "received new watch outpoint spend task for: {}",
+                                task.outpoint
+                            );
+                            let mut state_guard = state.lock().await;
+                            state_guard.watch_outpoint_spend(task, syncerd_task.source);
+                            drop(state_guard);
+                        }
+                        Task::WatchMempoolEntry(task) => {
+                            debug!(
+                                "received new watch mempool entry task for: {}",
+                                task.txid_or_outpoint
+                            );
+                            let mut state_guard = state.lock().await;
+                            state_guard.watch_mempool_entry(task, syncerd_task.source);
+                            drop(state_guard);
+                        }
+                        Task::SetPollIntervals(SetPollIntervals {
+                            poll_interval,
+                            max_poll_interval,
+                        }) => match adaptive_poll(
+                            poll_interval.unwrap_or(DEFAULT_POLL_INTERVAL_MS),
+                            max_poll_interval,
+                        ) {
+                            Ok(poll) => {
+                                info!(
+                                    "Polling the backend every {:?} to {:?}",
+                                    poll.min_interval, poll.max_interval
+                                );
+                                let mut state_guard = state.lock().await;
+                                state_guard.set_poll_bounds(poll);
+                                drop(state_guard);
+                            }
+                            Err(err) => error!("Ignoring the new poll intervals: {}", err),
+                        },
                         Task::Terminate => {
                             debug!("terminating async syncer runtime");
                             terminate_tx
@@ -712,17 +740,153 @@ async fn run_syncerd_task_receiver(
                                 .await
                                 .expect("terminating, don't care if we panic");
                         }
+                        Task::GetMempoolFeeHistogram(GetMempoolFeeHistogram { id }) => {
+                            debug!("retrieving the mempool fee histogram");
+                            // retried in its own task, the backoff must not hold up the task loop
+                            let backend = backend.clone();
+                            let state = Arc::clone(&state);
+                            let source = syncerd_task.source;
+                            tokio::spawn(async move {
+                                let buckets = retry
+                                    .retry("mempool fee histogram retrieval", || {
+                                        backend.connect().and_then(|client| client.fee_histogram())
+                                    })
+                                    .await
+                                    .map_err(|err| {
+                                        error!(
+                                            "failed to retrieve the mempool fee histogram: {}",
+                                            err
+                                        );
+                                        err.to_string()
+                                    });
+                                let mut state_guard = state.lock().await;
+                                state_guard.fee_histogram_result(id, buckets, source).await;
+                                drop(state_guard);
+                            });
+                        }
+                        Task::BumpFee(BumpFee { id, txid, .. })
+                            if matches!(backend, BitcoinBackendConfig::Electrum { .. }) =>
+                        {
+                            error!("Aborting bump fee task - not available with an electrum backend, the replacement must be signed by the bitcoin rpc wallet");
+                            let mut state_guard = state.lock().await;
+                            state_guard
+                                .fee_bump_result(
+                                    id,
+                                    Err(format!(
+                                        "cannot bump the fee of {}: fee bumping requires a bitcoin rpc backend",
+                                        txid
+                                    )),
+                                    syncerd_task.source,
+                                )
+                                .await;
+                            drop(state_guard);
+                        }
+                        Task::BumpFee(BumpFee {
+                            id,
+                            txid,
+                            new_fee_rate,
+                        }) => {
+                            debug!("bumping the fee of {} to {} sat/kvB", txid, new_fee_rate);
+                            // the backend round trips must not hold up the task loop
+                            let backend = backend.clone();
+                            let state = Arc::clone(&state);
+                            let source = syncerd_task.source;
+                            tokio::spawn(async move {
+                                let fee_bump = tokio::task::spawn_blocking(move || {
+                                    backend.connect().and_then(|client| {
+                                        bump_fee(client.as_ref(), &txid, new_fee_rate)
+                                    })
+                                })
+                                .await
+                                .unwrap_or_else(|err| Err(Error::Farcaster(err.to_string())))
+                                .map(|replacement_txid| FeeBumped {
+                                    id,
+                                    txid,
+                                    replacement_txid,
+                                })
+                                .map_err(|err| {
+                                    error!("failed to bump the fee of {}: {}", txid, err);
+                                    err.to_string()
+                                });
+                                let mut state_guard = state.lock().await;
+                                state_guard.fee_bump_result(id, fee_bump, source).await;
+                                drop(state_guard);
+                            });
+                        }
+                        Task::GetBackendStatus(GetBackendStatus { id }) => {
+                            let tip_height = backend
+                                .connect()
+                                .and_then(|client| client.tip())
+                                .map(|(height, _)| height)
+                                .map_err(|err| err.to_string());
+                            let mut state_guard = state.lock().await;
+                            state_guard
+                                .backend_status(id, tip_height, syncerd_task.source)
+                                .await;
+                            drop(state_guard);
+                        }
+                        Task::GetBlockByHeight(GetBlockByHeight {
+                            id,
+                            height,
+                            include_txs,
+                        }) => {
+                            debug!("retrieving the block at height {}", height);
+                            let block = backend
+                                .connect()
+                                .and_then(|client| client.block_at(height))
+                                .map_err(|err| err.to_string())
+                                .and_then(|(header, txs)| {
+                                    let txs_data = match (include_txs, &txs) {
+                                        (false, _) => None,
+                                        (true, Some(txs)) => Some(
+                                            txs.iter()
+                                                .map(bitcoin::consensus::encode::serialize)
+                                                .collect(),
+                                        ),
+                                        (true, None) => {
+                                            return Err(format!(
+                                            "block transactions not available with the {} backend",
+                                            backend
+                                        ))
+                                        }
+                                    };
+                                    // block hashes are displayed in reverse byte order
+                                    let mut hash = header.block_hash()[..].to_vec();
+                                    hash.reverse();
+                                    Ok(BlockData {
+                                        id,
+                                        height,
+                                        hash,
+                                        header: bitcoin::consensus::encode::serialize(&header),
+                                        tx_count: txs.map(|txs| txs.len() as u64),
+                                        txs: txs_data,
+                                    })
+                                });
+                            if let Err(err) = &block {
+                                error!(
+                                    "failed to retrieve the block at height {}: {}",
+                                    height, err
+                                );
+                            }
+                            let mut state_guard = state.lock().await;
+                            state_guard
+                                .block_data_result(id, block, syncerd_task.source)
+                                .await;
+                            drop(state_guard);
+                        }
                         Task::HealthCheck(HealthCheck { id }) => {
                             debug!("performing health check");
-                            let health =
-                                match ElectrumRpc::new(&electrum_server, proxy_address.clone())
-                                    .and_then(|client| {
-                                        client.client.ping()?;
-                                        Ok(())
-                                    }) {
-                                    Err(err) => Health::FaultyElectrum(err.to_string()),
-                                    Ok(_) => Health::Healthy,
-                                };
+                            let health = match backend.connect().and_then(|client| client.tip()) {
+                                Err(err) => match backend {
+                                    BitcoinBackendConfig::Electrum { .. } => {
+                                        Health::FaultyElectrum(err.to_string())
+                                    }
+                                    BitcoinBackendConfig::BitcoinRpc { .. } => {
+                                        Health::FaultyBitcoinRpc(err.to_string())
+                                    }
+                                },
+                                Ok(_) => Health::Healthy,
+                            };
                             let mut state_guard = state.lock().await;
                             state_guard
                                 .health_result(id, health, syncerd_task.source)
@@ -748,6 +912,7 @@ fn address_polling(
     state: Arc<Mutex<SyncerState>>,
     electrum_server: String,
     proxy_address: Option<String>,
+    mut poll: AdaptivePoll,
 ) -> tokio::task::JoinHandle<()> {
     tokio::task::spawn(async move {
         loop {
@@ -772,40 +937,41 @@ fn address_polling(
                     break;
                 }
                 let state_guard = state.lock().await;
-                let addresses = state_guard.addresses.clone();
+                let unsubscribed: Vec<(InternalId, BtcAddressAddendum, TxFilter)> = state_guard
+                    .addresses
+                    .iter()
+                    .filter(|(_, address)| !address.subscribed)
+                    .filter_map(|(id, address)| match &address.task.addendum {
+                        AddressAddendum::Bitcoin(address_addendum) => {
+                            Some((*id, address_addendum.clone(), address.task.filter.clone()))
+                        }
+                        _ => None,
+                    })
+                    .collect();
                 drop(state_guard);
-                for (id, address) in addresses.clone() {
-                    if let AddressAddendum::Bitcoin(address_addendum) = address.task.addendum {
-                        if !address.subscribed {
-                            match rpc
-                                .script_subscribe(address_addendum.clone(), address.task.filter)
-                            {
-                                Ok(notif) => {
-                                    logging(&notif.txs, &address_addendum);
-                                    let tx_set = create_set(notif.txs);
-                                    let mut state_guard = state.lock().await;
-                                    if let Some(address) = state_guard.addresses.get_mut(&id) {
-                                        address.subscribed = true;
-                                    }
-                                    state_guard
-                                        .change_address(
-                                            AddressAddendum::Bitcoin(address_addendum.clone()),
-                                            tx_set,
-                                        )
-                                        .await;
-                                    drop(state_guard);
-                                }
-                                // do nothing if we are already subscribed
-                                Err(Error::Syncer(SyncerError::Electrum(
-                                    electrum_client::Error::AlreadySubscribed(_),
-                                ))) => {}
-                                Err(e) => {
-                                    error!("error in bitcoin address polling: {}", e);
-                                    break;
-                                }
+                if !unsubscribed.is_empty() {
+                    let addresses = dedup_addresses(
+                        unsubscribed
+                            .iter()
+                            .map(|(_, address, filter)| (address.clone(), filter.clone())),
+                    );
+                    let notifs = rpc.subscribe_addresses(&addresses);
+                    let mut state_guard = state.lock().await;
+                    // the addresses failing to subscribe or query are retried on the next poll
+                    for (id, address, _) in unsubscribed.iter() {
+                        if notifs.iter().any(|notif| notif.address == *address) {
+                            if let Some(address) = state_guard.addresses.get_mut(id) {
+                                address.subscribed = true;
                             }
                         }
                     }
+                    for AddressNotif { address, txs } in notifs {
+                        logging(&txs, &address);
+                        state_guard
+                            .change_address(AddressAddendum::Bitcoin(address), create_set(txs))
+                            .await;
+                    }
+                    drop(state_guard);
                 }
                 let mut addrs_notifs = rpc.address_change_check();
                 if !addrs_notifs.is_empty() {
@@ -818,7 +984,8 @@ fn address_polling(
                     }
                     drop(state_guard);
                 }
-                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                let interval = state.lock().await.next_poll(&mut poll);
+                tokio::time::sleep(interval).await;
             }
 
             // we need to un-subscribe all addresses first if we are creating a new client
@@ -829,11 +996,57 @@ fn address_polling(
     })
 }
 
+async fn send_pending_broadcasts(
+    state: Arc<Mutex<SyncerState>>,
+    transaction_broadcast_tx: &TokioSender<(BroadcastTransaction, ServiceId)>,
+) {
+    let state_guard = state.lock().await;
+    let height = state_guard.block_height();
+    let pending_broadcasts: HashSet<(BroadcastTransaction, ServiceId)> = state_guard
+        .pending_broadcasts
+        .iter()
+        .filter(|(task, _)| {
+            if let Some(after_height) = task.broadcast_after_height {
+                after_height < height
+            } else {
+                false
+            }
+        })
+        .cloned()
+        .collect();
+    drop(state_guard);
+    for pending in pending_broadcasts {
+        // Do not re-try sending pending broadcasts
+        if let Err(err) = transaction_broadcast_tx.send(pending.clone()).await {
+            error!("error sending through transaction_broadcast_tx {}", err);
+        }
+        let mut state_guard = state.lock().await;
+        state_guard.pending_broadcasts.remove(&pending);
+        drop(state_guard);
+    }
+}
+
+/// Update the average block interval measured from the timestamps of the blocks preceding the
+/// tip, the syncer state keeps its previous value on failure
+async fn update_block_interval(
+    state: &Arc<Mutex<SyncerState>>,
+    block_interval: Result<u64, Error>,
+) {
+    match block_interval {
+        Ok(block_interval) => {
+            trace!("average block interval: {}s", block_interval);
+            state.lock().await.set_block_interval(block_interval);
+        }
+        Err(err) => debug!("failed to measure the average block interval: {}", err),
+    }
+}
+
 fn height_polling(
     state: Arc<Mutex<SyncerState>>,
     electrum_server: String,
     proxy_address: Option<String>,
     transaction_broadcast_tx: TokioSender<(BroadcastTransaction, ServiceId)>,
+    mut poll: AdaptivePoll,
 ) -> tokio::task::JoinHandle<()> {
     tokio::task::spawn(async move {
         // outer loop ensures the polling restarts if there is an error
@@ -851,6 +1064,8 @@ fn height_polling(
                 }
             };
 
+            let block_interval = rpc.client.average_block_interval(rpc.height);
+            update_block_interval(&state, block_interval).await;
             let mut state_guard = state.lock().await;
             state_guard
                 .change_height(rpc.height, rpc.block_hash.to_vec())
@@ -873,6 +1088,10 @@ fn height_polling(
                         break;
                     }
                 };
+                if let Some(tip) = blocks.last() {
+                    let block_interval = rpc.client.average_block_interval(tip.height);
+                    update_block_interval(&state, block_interval).await;
+                }
                 let mut state_guard = state.lock().await;
                 let mut block_change = false;
                 for block_notif in blocks.drain(..) {
@@ -884,35 +1103,12 @@ fn height_polling(
 
                 // if the blocks changed, check pending broadcasts and query transactions
                 if block_change {
-                    let state_guard = state.lock().await;
-                    let height = state_guard.block_height();
-                    let pending_broadcasts: HashSet<(BroadcastTransaction, ServiceId)> =
-                        state_guard
-                            .pending_broadcasts
-                            .iter()
-                            .filter(|(task, _)| {
-                                if let Some(after_height) = task.broadcast_after_height {
-                                    after_height < height
-                                } else {
-                                    false
-                                }
-                            })
-                            .cloned()
-                            .collect();
-                    drop(state_guard);
-                    for pending in pending_broadcasts {
-                        // Do not re-try sending pending broadcasts
-                        if let Err(err) = transaction_broadcast_tx.send(pending.clone()).await {
-                            error!("error sending through transaction_broadcast_tx {}", err);
-                        }
-                        let mut state_guard = state.lock().await;
-                        state_guard.pending_broadcasts.remove(&pending);
-                        drop(state_guard);
-                    }
+                    send_pending_broadcasts(Arc::clone(&state), &transaction_broadcast_tx).await;
                     rpc.query_transactions(Arc::clone(&state), false).await;
                 }
 
-                tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                let interval = state.lock().await.next_poll(&mut poll);
+                tokio::time::sleep(interval).await;
             }
             // wait a bit before retrying the connection
             tokio::time::sleep(std::time::Duration::from_secs(RETRY_TIMEOUT)).await;
@@ -948,6 +1144,134 @@ fn unseen_transaction_polling(
     })
 }
 
+/// Polls a Bitcoin Core node for the chain tip, the watched addresses and the watched
+/// transactions. The node does not push notifications, so everything is queried on every round.
+fn bitcoin_rpc_polling(
+    state: Arc<Mutex<SyncerState>>,
+    url: String,
+    auth: Auth,
+    transaction_broadcast_tx: TokioSender<(BroadcastTransaction, ServiceId)>,
+    mut poll: AdaptivePoll,
+) -> tokio::task::JoinHandle<()> {
+    tokio::task::spawn(async move {
+        // outer loop ensures the polling restarts if there is an error
+        loop {
+            let mut rpc = match BitcoinRpc::new(&url, auth.clone())
+                .and_then(|client| client.require_txindex().map(|_| client))
+            {
+                Ok(client) => client,
+                Err(err) => {
+                    error!("failed to spawn bitcoin rpc client {}: {}", &url, err);
+                    // wait a bit before retrying the connection
+                    tokio::time::sleep(std::time::Duration::from_secs(RETRY_TIMEOUT)).await;
+                    continue;
+                }
+            };
+
+            // inner loop actually polls
+            loop {
+                let (height, block_hash) = match rpc.tip() {
+                    Ok(tip) => tip,
+                    Err(err) => {
+                        error!("error polling bitcoin block height: {}", err);
+                        // break this loop and retry, since the rpc client is probably broken
+                        break;
+                    }
+                };
+                if state.lock().await.block_height() != height {
+                    let block_interval = rpc.average_block_interval(height);
+                    update_block_interval(&state, block_interval).await;
+                }
+                let mut state_guard = state.lock().await;
+                let block_change = state_guard.change_height(height, block_hash.to_vec()).await;
+                let addresses = state_guard.addresses.clone();
+                let txids: Vec<Txid> = state_guard
+                    .transactions
+                    .values()
+                    .map(|watched_tx| watched_tx.task.hash)
+                    .collect();
+                drop(state_guard);
+
+                if block_change {
+                    send_pending_broadcasts(Arc::clone(&state), &transaction_broadcast_tx).await;
+                }
+
+                let addresses = dedup_addresses(addresses.into_values().filter_map(|address| {
+                    match address.task.addendum {
+                        AddressAddendum::Bitcoin(address_addendum) => {
+                            Some((address_addendum, address.task.filter))
+                        }
+                        _ => None,
+                    }
+                }));
+                if !addresses.is_empty() {
+                    let histories = rpc.watch_addresses(&addresses);
+                    let mut state_guard = state.lock().await;
+                    for ((address_addendum, _), history) in addresses.into_iter().zip(histories) {
+                        match history {
+                            Ok(txs) => {
+                                logging(&txs, &address_addendum);
+                                state_guard
+                                    .change_address(
+                                        AddressAddendum::Bitcoin(address_addendum),
+                                        create_set(txs),
+                                    )
+                                    .await;
+                            }
+                            Err(err) => error!(
+                                "error in bitcoin address polling of {}: {}",
+                                address_addendum.address, err
+                            ),
+                        }
+                    }
+                    drop(state_guard);
+                }
+
+                for tx_id in txids {
+                    let tx_id = match tx_id {
+                        Txid::Bitcoin(tx_id) => tx_id,
+                        Txid::Monero(tx_id) => {
+                            error!(
+                                "This is Monero txid, but expected a Bitcoin txid: {}",
+                                tx_id
+                            );
+                            continue;
+                        }
+                    };
+                    let status = rpc.tx_status(&tx_id).unwrap_or_else(|err| {
+                        debug!("error getting transaction {}: {}", tx_id, err);
+                        None
+                    });
+                    let mut state_guard = state.lock().await;
+                    match status {
+                        Some(status) => {
+                            state_guard
+                                .change_transaction(
+                                    tx_id.into(),
+                                    status.block_hash.map(|hash| hash.to_vec()),
+                                    Some(status.confirmations),
+                                    bitcoin::consensus::serialize(&status.tx),
+                                )
+                                .await;
+                        }
+                        None => {
+                            state_guard
+                                .change_transaction(tx_id.into(), None, None, vec![])
+                                .await;
+                        }
+                    }
+                    drop(state_guard);
+                }
+
+                let interval = state.lock().await.next_poll(&mut poll);
+                tokio::time::sleep(interval).await;
+            }
+            // wait a bit before retrying the connection
+            tokio::time::sleep(std::time::Duration::from_secs(RETRY_TIMEOUT)).await;
+        }
+    })
+}
+
 #[derive(Default)]
 pub struct BitcoinSyncer {}
 
@@ -958,105 +1282,105 @@ impl BitcoinSyncer {
 }
 
 fn transaction_broadcasting(
-    electrum_server: String,
-    proxy_address: Option<String>,
+    backend: BitcoinBackendConfig,
+    retry: RetryPolicy,
     mut transaction_broadcast_rx: TokioReceiver<(BroadcastTransaction, ServiceId)>,
     tx_event: TokioSender<BridgeEvent>,
 ) -> tokio::task::JoinHandle<()> {
     tokio::task::spawn(async move {
         while let Some((broadcast_transaction, source)) = transaction_broadcast_rx.recv().await {
-            debug!("creating transaction broadcast electrum client");
-            match create_electrum_client(&electrum_server, proxy_address.clone()).and_then(
-                |broadcast_client| {
-                    broadcast_client.transaction_broadcast_raw(&broadcast_transaction.tx.clone())
-                },
-            ) {
-                Ok(txid) => {
-                    tx_event
-                        .send(BridgeEvent {
-                            event: Event::TransactionBroadcasted(TransactionBroadcasted {
-                                id: broadcast_transaction.id,
-                                tx: broadcast_transaction.tx,
-                                error: None,
-                            }),
-                            source,
-                        })
-                        .await
-                        .expect("error sending transaction broadcast event");
-                    debug!("Successfully broadcasted: {}", txid.bright_yellow_italic());
-                }
-                Err(e) => {
-                    tx_event
-                        .send(BridgeEvent {
-                            event: Event::TransactionBroadcasted(TransactionBroadcasted {
-                                id: broadcast_transaction.id,
-                                tx: broadcast_transaction.tx,
-                                error: Some(format!("failed to broadcast tx: {}", e.err())),
-                            }),
-                            source,
+            let backend = backend.clone();
+            let tx_event = tx_event.clone();
+            // handled in its own task, retrying a request must not hold up the next ones
+            tokio::spawn(async move {
+                debug!("creating transaction broadcast {} client", backend);
+                // only the connection is retried, a rejected transaction is not a transient failure
+                let broadcast_client = match retry
+                    .retry("transaction broadcast connection", || backend.connect())
+                    .await
+                {
+                    Ok(broadcast_client) => broadcast_client,
+                    Err(e) => {
+                        tx_event
+                            .send(BridgeEvent {
+                                event: Event::TransactionBroadcasted(TransactionBroadcasted {
+                                    id: broadcast_transaction.id,
+                                    tx: broadcast_transaction.tx,
+                                    error: Some(format!("failed to broadcast tx: {}", e.err())),
+                                }),
+                                source,
+                            })
+                            .await
+                            .expect("error sending transaction broadcast event");
+                        error!("failed to broadcast tx: {}", e.err());
+                        return;
+                    }
+                };
+                let event = match broadcast_client.broadcast(&broadcast_transaction.tx) {
+                    Ok(txid) => {
+                        debug!("Successfully broadcasted: {}", txid.bright_yellow_italic());
+                        Event::TransactionBroadcasted(TransactionBroadcasted {
+                            id: broadcast_transaction.id,
+                            tx: broadcast_transaction.tx,
+                            error: None,
                         })
-                        .await
-                        .expect("error sending transaction broadcast event");
-                    error!("failed to broadcast tx: {}", e.err());
-                }
-            }
+                    }
+                    Err(e) => {
+                        let reason = e.to_string();
+                        match bitcoin::Transaction::consensus_decode(std::io::Cursor::new(
+                            &broadcast_transaction.tx,
+                        )) {
+                            // the backend may reject a transaction it already knows, e.g. when
+                            // re-broadcasting a restored swap's transaction
+                            Ok(tx) if broadcast_client.get_tx(&tx.txid()).is_ok() => {
+                                debug!("Transaction {} already known by the backend", tx.txid());
+                                Event::TransactionBroadcasted(TransactionBroadcasted {
+                                    id: broadcast_transaction.id,
+                                    tx: broadcast_transaction.tx,
+                                    error: None,
+                                })
+                            }
+                            Ok(tx) => {
+                                error!("transaction {} rejected: {}", tx.txid(), reason.err());
+                                Event::BroadcastFailure(BroadcastFailure {
+                                    id: broadcast_transaction.id,
+                                    txid: tx.txid().into(),
+                                    reason,
+                                })
+                            }
+                            Err(_) => {
+                                error!("failed to broadcast tx: {}", reason.err());
+                                Event::TransactionBroadcasted(TransactionBroadcasted {
+                                    id: broadcast_transaction.id,
+                                    tx: broadcast_transaction.tx,
+                                    error: Some(format!("failed to broadcast tx: {}", reason)),
+                                })
+                            }
+                        }
+                    }
+                };
+                tx_event
+                    .send(BridgeEvent { event, source })
+                    .await
+                    .expect("error sending transaction broadcast event");
+            });
         }
     })
 }
 
-/// Result of querying electrum to get a low priority and high priority fee rate.
-struct FeeByPriority {
-    low_fee: f64,
-    high_fee: f64,
-}
-
-/// Extend electrum client capabilities and query fee for low and high priority.
-trait GenericEstimateFee {
-    /// Query electrum for estimate fee for low and high priority or fall back on node's relay fee.
-    fn estimate_priority_fee(
-        &self,
-        near_target: usize,
-        far_target: usize,
-    ) -> Result<FeeByPriority, electrum_client::Error>;
-}
-
-impl GenericEstimateFee for Client {
-    fn estimate_priority_fee(
-        &self,
-        near_target: usize,
-        far_target: usize,
-    ) -> Result<FeeByPriority, electrum_client::Error> {
-        let low_fee;
-        let mut high_fee = self.estimate_fee(near_target)?;
-        if high_fee == -1.0 {
-            // None returned internally between node and electrum, fallback on relay_fee
-            high_fee = self.relay_fee()?;
-            low_fee = high_fee;
-        } else {
-            // Shortcut in case we want only 1 fee and near == far
-            if far_target != near_target {
-                low_fee = self.estimate_fee(far_target)?;
-            } else {
-                low_fee = high_fee
-            }
-        }
-        Ok(FeeByPriority { low_fee, high_fee })
-    }
-}
-
 fn estimate_fee_polling(
-    electrum_server: String,
-    proxy_address: Option<String>,
+    backend: BitcoinBackendConfig,
     state: Arc<Mutex<SyncerState>>,
+    fee_stale_ratio: f64,
 ) -> tokio::task::JoinHandle<()> {
     tokio::task::spawn(async move {
         let high_priority_target = 2;
         let low_priority_target = 6;
         loop {
-            debug!("creating fee polling electrum client");
-            if let Ok(client) = create_electrum_client(&electrum_server, proxy_address.clone()) {
+            debug!("creating fee polling {} client", backend);
+            if let Ok(client) = backend.connect() {
                 loop {
-                    match client.estimate_priority_fee(high_priority_target, low_priority_target) {
+                    match client.estimate_fee(high_priority_target, low_priority_target) {
                         Ok(FeeByPriority { low_fee, high_fee }) => {
                             let mut state_guard = state.lock().await;
                             state_guard
@@ -1065,7 +1389,33 @@ fn estimate_fee_polling(
                                     low_priority_sats_per_kvbyte: (low_fee * 1.0e8).ceil() as u64,
                                 })
                                 .await;
+                            let unconfirmed_txs = state_guard.unconfirmed_transactions();
                             drop(state_guard);
+                            // compare the watched transactions stuck in the mempool against the
+                            // new estimation
+                            for (id, tx, recommended) in unconfirmed_txs {
+                                match transaction_fee_rate(client.as_ref(), &tx) {
+                                    Ok(tx_fee_rate) => {
+                                        let mut state_guard = state.lock().await;
+                                        state_guard
+                                            .transaction_fee_checked(
+                                                &id,
+                                                tx_fee_rate,
+                                                recommended,
+                                                fee_stale_ratio,
+                                            )
+                                            .await;
+                                        drop(state_guard);
+                                    }
+                                    Err(err) => {
+                                        debug!(
+                                            "Failed to compute the fee rate of {}: {}",
+                                            tx.txid(),
+                                            err
+                                        );
+                                    }
+                                }
+                            }
                         }
                         Err(err) => {
                             error!("Failed to retrieve fee estimation: {}", err);
@@ -1080,6 +1430,220 @@ fn estimate_fee_polling(
     })
 }
 
+/// Fee rate paid by a transaction, in satoshis per kvB, from the values of the outputs it spends
+fn transaction_fee_rate(
+    client: &dyn BitcoinBackend,
+    tx: &bitcoin::Transaction,
+) -> Result<u64, Error> {
+    let in_amount = input_amount(client, tx)?;
+    let out_amount: u64 = tx.output.iter().map(|output| output.value).sum();
+    let fee = in_amount.saturating_sub(out_amount);
+    Ok(fee * 1000 / (tx.vsize() as u64).max(1))
+}
+
+/// Sum of the values of the outputs spent by a transaction
+fn input_amount(client: &dyn BitcoinBackend, tx: &bitcoin::Transaction) -> Result<u64, Error> {
+    let mut in_amount = 0;
+    for input in tx.input.iter() {
+        let previous_tx = client.get_tx(&input.previous_output.txid)?;
+        let previous_output = previous_tx
+            .output
+            .get(input.previous_output.vout as usize)
+            .ok_or_else(|| {
+                Error::Farcaster(format!(
+                    "Output {} spent by {} not found",
+                    input.previous_output,
+                    tx.txid()
+                ))
+            })?;
+        in_amount += previous_output.value;
+    }
+    Ok(in_amount)
+}
+
+/// Replace the transaction with one paying the new fee rate, in satoshis per kvB, taken from the
+/// largest output of the wallet of the backend, and broadcast it
+fn bump_fee(
+    client: &dyn BitcoinBackend,
+    txid: &bitcoin::Txid,
+    new_fee_rate: u64,
+) -> Result<bitcoin::Txid, Error> {
+    let tx = client.get_tx(txid)?;
+    let in_amount = input_amount(client, &tx)?;
+    let change = client
+        .wallet_outputs(&tx)?
+        .into_iter()
+        .max_by_key(|vout| tx.output[*vout].value);
+    let replacement =
+        replacement_transaction(&tx, in_amount, change, new_fee_rate).map_err(Error::Farcaster)?;
+    let signed = client.sign_with_wallet(&replacement)?;
+    client.broadcast(&bitcoin::consensus::serialize(&signed))
+}
+
+/// Unsigned replacement of the transaction paying the new fee rate, in satoshis per kvB, with the
+/// same inputs and outputs, the additional fee being taken from the change output. Fails if the
+/// replacement would break the BIP125 rules.
+fn replacement_transaction(
+    tx: &bitcoin::Transaction,
+    in_amount: u64,
+    change: Option<usize>,
+    new_fee_rate: u64,
+) -> Result<bitcoin::Transaction, String> {
+    // BIP125 rule 1: at least one input opts in to the replacement
+    if !tx.input.iter().any(|input| input.sequence < 0xffff_fffe) {
+        return Err(format!(
+            "{} does not signal BIP125 replaceability",
+            tx.txid()
+        ));
+    }
+    // the replacement spends the same inputs, its signatures have the same size
+    let vsize = tx.vsize() as u64;
+    let out_amount: u64 = tx.output.iter().map(|output| output.value).sum();
+    let fee = in_amount.saturating_sub(out_amount);
+    let new_fee = (new_fee_rate * vsize + 999) / 1000;
+    // BIP125 rules 3 and 4: pay the fee of the replaced transaction and the relay of the
+    // replacement
+    let min_fee = fee + (INCREMENTAL_RELAY_FEE * vsize + 999) / 1000;
+    if new_fee < min_fee {
+        return Err(format!(
+            "fee rate of {} sat/kvB too low to replace {}, the replacement must pay at least {} sat",
+            new_fee_rate,
+            tx.txid(),
+            min_fee
+        ));
+    }
+    let change = change.ok_or_else(|| {
+        format!(
+            "{} has no change output to take the additional fee from",
+            tx.txid()
+        )
+    })?;
+    let mut replacement = tx.clone();
+    let change_output = &mut replacement.output[change];
+    change_output.value = change_output
+        .value
+        .checked_sub(new_fee - fee)
+        .filter(|value| *value >= DUST_LIMIT)
+        .ok_or_else(|| {
+            format!(
+                "change output of {} too small to pay {} sat of additional fee",
+                tx.txid(),
+                new_fee - fee
+            )
+        })?;
+    for input in replacement.input.iter_mut() {
+        input.script_sig = bitcoin::Script::default();
+        input.witness = bitcoin::Witness::new();
+    }
+    Ok(replacement)
+}
+
+fn outpoint_polling(
+    backend: BitcoinBackendConfig,
+    state: Arc<Mutex<SyncerState>>,
+    mut poll: AdaptivePoll,
+) -> tokio::task::JoinHandle<()> {
+    tokio::task::spawn(async move {
+        loop {
+            debug!("creating outpoint polling {} client", backend);
+            if let Ok(mut client) = backend.connect() {
+                let mut last_tip = None;
+                'polling: loop {
+                    let state_guard = state.lock().await;
+                    let outpoints = state_guard.outpoints.clone();
+                    let outpoint_watches = state_guard.outpoint_watches.clone();
+                    let mempool_entries = state_guard.mempool_entries.clone();
+                    let tip = (
+                        state_guard.block_height(),
+                        state_guard.block_hash().to_vec(),
+                    );
+                    drop(state_guard);
+                    // confirmed creations and spends only change with the chain tip, re-evaluate
+                    // them on every tip change to follow reorgs
+                    let tip_changed = last_tip.as_ref() != Some(&tip);
+                    for (id, outpoint_watch) in outpoint_watches.iter() {
+                        let status = &outpoint_watch.status;
+                        let confirmed = status.created_confirmations.unwrap_or(0) > 0
+                            && status.spent_confirmations.unwrap_or(0) > 0;
+                        if confirmed && !tip_changed {
+                            continue;
+                        }
+                        match client.outpoint_status(&outpoint_watch.task.outpoint) {
+                            Ok(status) => {
+                                let mut state_guard = state.lock().await;
+                                state_guard.change_outpoint_status(id, status).await;
+                                drop(state_guard);
+                            }
+                            Err(err) => {
+                                error!(
+                                    "Failed to query the status of outpoint {}: {}",
+                                    outpoint_watch.task.outpoint, err
+                                );
+                                break 'polling;
+                            }
+                        }
+                    }
+                    last_tip = Some(tip);
+                    for (id, watched_outpoint) in outpoints.iter() {
+                        match client.outpoint_spender(&watched_outpoint.task.outpoint) {
+                            Ok(spending_txid) => {
+                                let mut state_guard = state.lock().await;
+                                state_guard.change_outpoint_spend(id, spending_txid).await;
+                                drop(state_guard);
+                            }
+                            Err(err) => {
+                                error!(
+                                    "Failed to query the spender of outpoint {}: {}",
+                                    watched_outpoint.task.outpoint, err
+                                );
+                                break 'polling;
+                            }
+                        }
+                    }
+                    for (id, mempool_entry) in mempool_entries.iter() {
+                        let entry = match mempool_entry.txid_or_outpoint {
+                            // the confirmations of the transaction creating an outpoint are the
+                            // ones of the transaction, every transaction has a first output
+                            TxidOrOutpoint::Txid(txid) => client
+                                .outpoint_status(&bitcoin::OutPoint { txid, vout: 0 })
+                                .map(|status| (Some(txid), status.created_confirmations)),
+                            // only the spender matters, the creation of the outpoint is not
+                            // looked up unless the spender must be searched in the blocks
+                            TxidOrOutpoint::Outpoint(outpoint) => {
+                                client.outpoint_spend(&outpoint).map(|spend| match spend {
+                                    Some((txid, confirmations)) => {
+                                        (Some(txid), Some(confirmations))
+                                    }
+                                    None => (None, None),
+                                })
+                            }
+                        };
+                        match entry {
+                            Ok((txid, confirmations)) => {
+                                let mut state_guard = state.lock().await;
+                                state_guard
+                                    .change_mempool_entry(id, txid, confirmations)
+                                    .await;
+                                drop(state_guard);
+                            }
+                            Err(err) => {
+                                error!(
+                                    "Failed to query the mempool entry of {}: {}",
+                                    mempool_entry.txid_or_outpoint, err
+                                );
+                                break 'polling;
+                            }
+                        }
+                    }
+                    let interval = state.lock().await.next_poll(&mut poll);
+                    tokio::time::sleep(interval).await;
+                }
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(RETRY_TIMEOUT)).await;
+        }
+    })
+}
+
 fn sweep_polling(
     state: Arc<Mutex<SyncerState>>,
     electrum_server: String,
@@ -1109,6 +1673,7 @@ fn sweep_polling(
                                     addendum.source_secret_key,
                                     addendum.source_address,
                                     addendum.destination_address,
+                                    addendum.fee_sat_per_kvb,
                                     &client,
                                     network,
                                 )
@@ -1140,130 +1705,189 @@ fn sweep_polling(
 }
 
 fn transaction_fetcher(
-    electrum_server: String,
-    proxy_address: Option<String>,
+    backend: BitcoinBackendConfig,
+    retry: RetryPolicy,
     mut transaction_get_rx: TokioReceiver<GetTxServiceIdPair>,
     tx_event: TokioSender<BridgeEvent>,
 ) -> tokio::task::JoinHandle<()> {
     tokio::task::spawn(async move {
         while let Some((get_transaction, source)) = transaction_get_rx.recv().await {
-            let tx_id = match get_transaction.hash {
-                Txid::Bitcoin(tx_id) => tx_id,
-                Txid::Monero(tx_id) => {
-                    error!(
-                        "This is a Monero txid, but expected a Bitcoin txid: {}",
-                        tx_id
-                    );
-                    continue;
-                }
-            };
-            debug!("creating transaction fetcher electrum client");
-            match create_electrum_client(&electrum_server, proxy_address.clone())
-                .and_then(|transaction_client| transaction_client.transaction_get(&tx_id))
-            {
-                Ok(tx) => {
-                    tx_event
-                        .send(BridgeEvent {
-                            event: Event::TransactionRetrieved(TransactionRetrieved {
-                                id: get_transaction.id,
-                                tx: Some(tx),
-                            }),
-                            source,
-                        })
-                        .await
-                        .expect("error sending transaction retrieved event");
-                    debug!("successfully retrieved tx: {}", get_transaction.hash);
-                }
-                Err(e) => {
-                    tx_event
-                        .send(BridgeEvent {
-                            event: Event::TransactionRetrieved(TransactionRetrieved {
-                                id: get_transaction.id,
-                                tx: None,
-                            }),
-                            source,
-                        })
-                        .await
-                        .expect("error sending transaction retrieved event");
-                    debug!("Error while retrieving tx {}: {}", get_transaction.hash, e);
+            let backend = backend.clone();
+            let tx_event = tx_event.clone();
+            // handled in its own task, retrying a request must not hold up the next ones
+            tokio::spawn(async move {
+                let tx_id = match get_transaction.hash {
+                    Txid::Bitcoin(tx_id) => tx_id,
+                    Txid::Monero(tx_id) => {
+                        error!(
+                            "This is a Monero txid, but expected a Bitcoin txid: {}",
+                            tx_id
+                        );
+                        return;
+                    }
+                };
+                debug!("creating transaction fetcher {} client", backend);
+                // only the connection is retried, an unknown transaction is a valid answer
+                match retry
+                    .retry("transaction fetcher connection", || backend.connect())
+                    .await
+                    .and_then(|transaction_client| transaction_client.get_tx(&tx_id))
+                {
+                    Ok(tx) => {
+                        tx_event
+                            .send(BridgeEvent {
+                                event: Event::TransactionRetrieved(TransactionRetrieved {
+                                    id: get_transaction.id,
+                                    tx: Some(tx),
+                                }),
+                                source,
+                            })
+                            .await
+                            .expect("error sending transaction retrieved event");
+                        debug!("successfully retrieved tx: {}", get_transaction.hash);
+                    }
+                    Err(e) => {
+                        tx_event
+                            .send(BridgeEvent {
+                                event: Event::TransactionRetrieved(TransactionRetrieved {
+                                    id: get_transaction.id,
+                                    tx: None,
+                                }),
+                                source,
+                            })
+                            .await
+                            .expect("error sending transaction retrieved event");
+                        debug!("Error while retrieving tx {}: {}", get_transaction.hash, e);
+                    }
                 }
-            }
+            });
         }
     })
 }
 
 fn balance_fetcher(
-    electrum_server: String,
-    proxy_address: Option<String>,
+    backend: BitcoinBackendConfig,
+    retry: RetryPolicy,
     mut balance_get_rx: TokioReceiver<BalanceServiceIdPair>,
     tx_event: TokioSender<BridgeEvent>,
 ) -> tokio::task::JoinHandle<()> {
     tokio::task::spawn(async move {
-        while let Some((get_balance, source)) = balance_get_rx.recv().await {
-            let address = match get_balance.address_secret_key {
-                AddressSecretKey::Monero { address, .. } => {
-                    tx_event
-                        .send(BridgeEvent {
-                            event: Event::AddressBalance(AddressBalance {
-                                address: Address::Monero(address),
-                                id: get_balance.id,
-                                balance: 0,
-                                err: Some(
-                                    "Sent monero address balance to bitcoin syncer".to_string(),
-                                ),
-                            }),
-                            source,
-                        })
-                        .await
-                        .expect("error sending address balance event");
-                    warn!("Received monero address balance task in bitcoin syncer");
-                    continue;
-                }
-                AddressSecretKey::Bitcoin { address, .. } => address,
-            };
+        while let Some((balance_task, source)) = balance_get_rx.recv().await {
+            let backend = backend.clone();
+            let tx_event = tx_event.clone();
+            // handled in its own task, retrying a request must not hold up the next ones
+            tokio::spawn(async move {
+                let get_balance = match balance_task {
+                    BalanceTask::Address(get_balance) => get_balance,
+                    BalanceTask::Descriptor(GetBalance { id, descriptor }) => {
+                        let balance = match descriptor {
+                            AddressAddendum::Bitcoin(BtcAddressAddendum { address }) => retry
+                                .retry("balance retrieval", || {
+                                    backend
+                                        .connect()
+                                        .and_then(|mut client| client.balance(&address))
+                                })
+                                .await
+                                .map_err(|err| err.to_string()),
+                            AddressAddendum::Monero(_) => {
+                                warn!("Received monero balance task in bitcoin syncer");
+                                Err("Sent monero balance task to bitcoin syncer".to_string())
+                            }
+                        };
+                        if let Err(err) = &balance {
+                            debug!("failed to retrieve balance: {}", err);
+                        }
+                        tx_event
+                            .send(BridgeEvent {
+                                event: balance_event(id, balance),
+                                source,
+                            })
+                            .await
+                            .expect("error sending balance event");
+                        return;
+                    }
+                };
+                let address = match get_balance.address_secret_key {
+                    AddressSecretKey::Monero { address, .. } => {
+                        tx_event
+                            .send(BridgeEvent {
+                                event: Event::AddressBalance(AddressBalance {
+                                    address: Address::Monero(address),
+                                    id: get_balance.id,
+                                    balance: 0,
+                                    err: Some(
+                                        "Sent monero address balance to bitcoin syncer".to_string(),
+                                    ),
+                                }),
+                                source,
+                            })
+                            .await
+                            .expect("error sending address balance event");
+                        warn!("Received monero address balance task in bitcoin syncer");
+                        return;
+                    }
+                    AddressSecretKey::Bitcoin { address, .. } => address,
+                };
 
-            debug!("creating balance fetcher electrum client");
+                let balance = match &backend {
+                    BitcoinBackendConfig::Electrum {
+                        server,
+                        proxy_address,
+                    } => {
+                        debug!("creating balance fetcher electrum client");
+                        retry
+                            .retry("address balance retrieval", || {
+                                create_electrum_client(server, proxy_address.clone())
+                                    .and_then(|transaction_client| {
+                                        transaction_client
+                                            .script_get_balance(&address.script_pubkey())
+                                    })
+                                    .map_err(Error::from)
+                            })
+                            .await
+                    }
+                    BitcoinBackendConfig::BitcoinRpc { .. } => Err(Error::Farcaster(
+                        "address balance is not available with a bitcoin rpc backend".to_string(),
+                    )),
+                };
 
-            match create_electrum_client(&electrum_server, proxy_address.clone()).and_then(
-                |transaction_client| {
-                    transaction_client.script_get_balance(&address.script_pubkey())
-                },
-            ) {
-                Ok(balance) => {
-                    tx_event
-                        .send(BridgeEvent {
-                            event: Event::AddressBalance(AddressBalance {
-                                id: get_balance.id,
-                                address: Address::Bitcoin(address.clone()),
-                                balance: balance.unconfirmed.unsigned_abs(),
-                                err: None,
-                            }),
-                            source,
-                        })
-                        .await
-                        .expect("error sending address balance event");
-                    debug!(
-                        "successfully retrieved balance: {} for address {}.",
-                        balance.unconfirmed.unsigned_abs(),
-                        address
-                    );
-                }
-                Err(e) => {
-                    tx_event
-                        .send(BridgeEvent {
-                            event: Event::AddressBalance(AddressBalance {
-                                id: get_balance.id,
-                                address: Address::Bitcoin(address.clone()),
-                                balance: 0,
-                                err: Some(e.to_string()),
-                            }),
-                            source,
-                        })
-                        .await
-                        .expect("error sending address balance event");
-                    debug!("failed to retrieve balance for address {}: {}", address, e);
+                match balance {
+                    Ok(balance) => {
+                        tx_event
+                            .send(BridgeEvent {
+                                event: Event::AddressBalance(AddressBalance {
+                                    id: get_balance.id,
+                                    address: Address::Bitcoin(address.clone()),
+                                    balance: balance.unconfirmed.unsigned_abs(),
+                                    err: None,
+                                }),
+                                source,
+                            })
+                            .await
+                            .expect("error sending address balance event");
+                        debug!(
+                            "successfully retrieved balance: {} for address {}.",
+                            balance.unconfirmed.unsigned_abs(),
+                            address
+                        );
+                    }
+                    Err(e) => {
+                        tx_event
+                            .send(BridgeEvent {
+                                event: Event::AddressBalance(AddressBalance {
+                                    id: get_balance.id,
+                                    address: Address::Bitcoin(address.clone()),
+                                    balance: 0,
+                                    err: Some(e.to_string()),
+                                }),
+                                source,
+                            })
+                            .await
+                            .expect("error sending address balance event");
+                        debug!("failed to retrieve balance for address {}: {}", address, e);
+                    }
                 }
-            }
+            });
         }
     })
 }
@@ -1284,6 +1908,7 @@ impl Synclet for BitcoinSyncer {
         receive_task_channel: Receiver<SyncerdTask>,
         tx: zmq::Socket,
         syncer_address: Vec<u8>,
+        terminated: Sender<()>,
         opts: &Opts,
         network: Network,
     ) -> Result<(), Error> {
@@ -1291,131 +1916,207 @@ impl Synclet for BitcoinSyncer {
         let proxy_address = opts.shared.tor_proxy.map(|address| address.to_string());
         debug!("bitcoin synclet using proxy: {:?}", proxy_address);
 
-        if let Some(electrum_server) = &opts.electrum_server {
-            let electrum_server = electrum_server.clone();
-            std::thread::spawn(move || {
-                use tokio::runtime::Builder;
-                trace!("building tokio syncer runtime");
-                let rt = Builder::new_multi_thread()
-                    .worker_threads(2)
-                    .enable_all()
-                    .build()
-                    .expect("failed to build tokio runtime");
-                trace!("completed tokio syncer runtime");
-                rt.block_on(async {
-                    let (event_tx, event_rx): (
-                        TokioSender<BridgeEvent>,
-                        TokioReceiver<BridgeEvent>,
-                    ) = tokio::sync::mpsc::channel(200);
-                    let (transaction_broadcast_tx, transaction_broadcast_rx): (
-                        TokioSender<TransactionServiceIdPair>,
-                        TokioReceiver<TransactionServiceIdPair>,
-                    ) = tokio::sync::mpsc::channel(200);
-                    let (transaction_get_tx, transaction_get_rx): (
-                        TokioSender<GetTxServiceIdPair>,
-                        TokioReceiver<GetTxServiceIdPair>,
-                    ) = tokio::sync::mpsc::channel(200);
-                    let (balance_get_tx, balance_get_rx): (
-                        TokioSender<BalanceServiceIdPair>,
-                        TokioReceiver<BalanceServiceIdPair>,
-                    ) = tokio::sync::mpsc::channel(200);
-                    let (terminate_tx, terminate_rx): (TokioSender<()>, TokioReceiver<()>) =
-                        tokio::sync::mpsc::channel(1);
-                    let state = Arc::new(Mutex::new(SyncerState::new(
-                        event_tx.clone(),
-                        Blockchain::Bitcoin,
-                    )));
-
-                    run_syncerd_task_receiver(
-                        electrum_server.clone(),
-                        proxy_address.clone(),
-                        receive_task_channel,
-                        Arc::clone(&state),
-                        transaction_broadcast_tx.clone(),
-                        transaction_get_tx,
-                        balance_get_tx,
-                        terminate_tx,
-                    )
-                    .await;
-                    run_syncerd_bridge_event_sender(tx, event_rx, syncer_address).await;
-
-                    let address_handle = address_polling(
-                        Arc::clone(&state),
-                        electrum_server.clone(),
-                        proxy_address.clone(),
-                    );
-
-                    let height_handle = height_polling(
-                        Arc::clone(&state),
-                        electrum_server.clone(),
-                        proxy_address.clone(),
-                        transaction_broadcast_tx,
-                    );
-
-                    let unseen_transaction_handle = unseen_transaction_polling(
-                        Arc::clone(&state),
-                        electrum_server.clone(),
-                        proxy_address.clone(),
-                    );
-
-                    let transaction_broadcast_handle = transaction_broadcasting(
-                        electrum_server.clone(),
-                        proxy_address.clone(),
-                        transaction_broadcast_rx,
-                        event_tx.clone(),
+        let backend = if let Some(url) = &opts.bitcoin_rpc {
+            let auth = match (
+                &opts.bitcoin_cookie_path,
+                &opts.bitcoin_rpc_user,
+                &opts.bitcoin_rpc_pass,
+            ) {
+                (Some(cookie), _, _) => Auth::CookieFile(cookie.into()),
+                (None, Some(user), Some(pass)) => Auth::UserPass(user.clone(), pass.clone()),
+                _ => {
+                    error!("Missing --bitcoin-cookie-path or --bitcoin-rpc-user and --bitcoin-rpc-pass arguments");
+                    return Err(SyncerError::InvalidConfig.into());
+                }
+            };
+            BitcoinBackendConfig::BitcoinRpc {
+                url: url.clone(),
+                auth,
+            }
+        } else if let Some(electrum_server) = &opts.electrum_server {
+            BitcoinBackendConfig::Electrum {
+                server: electrum_server.clone(),
+                proxy_address: proxy_address.clone(),
+            }
+        } else {
+            error!("Missing --electrum-server or --bitcoin-rpc argument");
+            return Err(SyncerError::InvalidConfig.into());
+        };
+        info!("bitcoin synclet using {}", backend);
+        if let BitcoinBackendConfig::BitcoinRpc { url, auth } = &backend {
+            // transactions are looked up by txid, refuse to run on a node without the index
+            // rather than missing the confirmed transactions of the swaps
+            match BitcoinRpc::new(url, auth.clone())?.require_txindex() {
+                Err(Error::Syncer(SyncerError::BackendRpc { method, message })) => {
+                    warn!(
+                        "Could not check the transaction index of {}, the {} call failed: {}",
+                        backend, method, message
                     );
+                }
+                res => res?,
+            }
+        }
+        let poll = opts.adaptive_poll()?;
+        let retry = opts.retry_policy()?;
+        let fee_stale_ratio = opts.fee_stale_ratio()?;
+
+        std::thread::spawn(move || {
+            use tokio::runtime::Builder;
+            trace!("building tokio syncer runtime");
+            let rt = Builder::new_multi_thread()
+                .worker_threads(2)
+                .enable_all()
+                .build()
+                .expect("failed to build tokio runtime");
+            trace!("completed tokio syncer runtime");
+            rt.block_on(async {
+                let (event_tx, event_rx): (TokioSender<BridgeEvent>, TokioReceiver<BridgeEvent>) =
+                    tokio::sync::mpsc::channel(200);
+                let (transaction_broadcast_tx, transaction_broadcast_rx): (
+                    TokioSender<TransactionServiceIdPair>,
+                    TokioReceiver<TransactionServiceIdPair>,
+                ) = tokio::sync::mpsc::channel(200);
+                let (transaction_get_tx, transaction_get_rx): (
+                    TokioSender<GetTxServiceIdPair>,
+                    TokioReceiver<GetTxServiceIdPair>,
+                ) = tokio::sync::mpsc::channel(200);
+                let (balance_get_tx, balance_get_rx): (
+                    TokioSender<BalanceServiceIdPair>,
+                    TokioReceiver<BalanceServiceIdPair>,
+                ) = tokio::sync::mpsc::channel(200);
+                let (terminate_tx, terminate_rx): (TokioSender<()>, TokioReceiver<()>) =
+                    tokio::sync::mpsc::channel(1);
+                let state = Arc::new(Mutex::new(SyncerState::new(
+                    event_tx.clone(),
+                    Blockchain::Bitcoin,
+                )));
+
+                run_syncerd_task_receiver(
+                    backend.clone(),
+                    retry,
+                    receive_task_channel,
+                    Arc::clone(&state),
+                    transaction_broadcast_tx.clone(),
+                    transaction_get_tx,
+                    balance_get_tx,
+                    terminate_tx,
+                )
+                .await;
+                run_syncerd_bridge_event_sender(tx, event_rx, syncer_address).await;
+
+                let transaction_broadcast_handle = transaction_broadcasting(
+                    backend.clone(),
+                    retry,
+                    transaction_broadcast_rx,
+                    event_tx.clone(),
+                );
+
+                let transaction_get_handle = transaction_fetcher(
+                    backend.clone(),
+                    retry,
+                    transaction_get_rx,
+                    event_tx.clone(),
+                );
+
+                let balance_get_handle =
+                    balance_fetcher(backend.clone(), retry, balance_get_rx, event_tx.clone());
+
+                let estimate_fee_handle =
+                    estimate_fee_polling(backend.clone(), Arc::clone(&state), fee_stale_ratio);
+
+                let outpoint_handle = outpoint_polling(backend.clone(), Arc::clone(&state), poll);
+
+                let terminate_handle = terminate_polling(terminate_rx);
+
+                let res = match backend {
+                    BitcoinBackendConfig::Electrum {
+                        server: electrum_server,
+                        proxy_address,
+                    } => {
+                        let address_handle = address_polling(
+                            Arc::clone(&state),
+                            electrum_server.clone(),
+                            proxy_address.clone(),
+                            poll,
+                        );
 
-                    let transaction_get_handle = transaction_fetcher(
-                        electrum_server.clone(),
-                        proxy_address.clone(),
-                        transaction_get_rx,
-                        event_tx.clone(),
-                    );
+                        let height_handle = height_polling(
+                            Arc::clone(&state),
+                            electrum_server.clone(),
+                            proxy_address.clone(),
+                            transaction_broadcast_tx,
+                            poll,
+                        );
 
-                    let balance_get_handle = balance_fetcher(
-                        electrum_server.clone(),
-                        proxy_address.clone(),
-                        balance_get_rx,
-                        event_tx.clone(),
-                    );
+                        let unseen_transaction_handle = unseen_transaction_polling(
+                            Arc::clone(&state),
+                            electrum_server.clone(),
+                            proxy_address.clone(),
+                        );
 
-                    let estimate_fee_handle = estimate_fee_polling(
-                        electrum_server.clone(),
-                        proxy_address.clone(),
-                        Arc::clone(&state),
-                    );
+                        let sweep_handle = sweep_polling(
+                            Arc::clone(&state),
+                            electrum_server,
+                            proxy_address,
+                            btc_network,
+                        );
 
-                    let sweep_handle = sweep_polling(
-                        Arc::clone(&state),
-                        electrum_server.clone(),
-                        proxy_address.clone(),
-                        btc_network,
-                    );
+                        tokio::try_join!(
+                            address_handle,
+                            height_handle,
+                            unseen_transaction_handle,
+                            transaction_broadcast_handle,
+                            transaction_get_handle,
+                            balance_get_handle,
+                            estimate_fee_handle,
+                            outpoint_handle,
+                            sweep_handle,
+                            terminate_handle,
+                        )
+                        .map(|_| ())
+                    }
+                    BitcoinBackendConfig::BitcoinRpc { url, auth } => {
+                        let rpc_handle = bitcoin_rpc_polling(
+                            Arc::clone(&state),
+                            url,
+                            auth,
+                            transaction_broadcast_tx,
+                            poll,
+                        );
 
-                    let terminate_handle = terminate_polling(terminate_rx);
-
-                    let res = tokio::try_join!(
-                        address_handle,
-                        height_handle,
-                        unseen_transaction_handle,
-                        transaction_broadcast_handle,
-                        transaction_get_handle,
-                        balance_get_handle,
-                        estimate_fee_handle,
-                        sweep_handle,
-                        terminate_handle,
-                    );
-                    debug!("exiting bitcoin synclet run routine with: {:?}", res);
-                });
-                debug!("shutting down runtime");
-                rt.shutdown_timeout(Duration::from_millis(100));
+                        tokio::try_join!(
+                            rpc_handle,
+                            transaction_broadcast_handle,
+                            transaction_get_handle,
+                            balance_get_handle,
+                            estimate_fee_handle,
+                            outpoint_handle,
+                            terminate_handle,
+                        )
+                        .map(|_| ())
+                    }
+                };
+                debug!("exiting bitcoin synclet run routine with: {:?}", res);
             });
-            Ok(())
-        } else {
-            error!("Missing --electrum-server argument");
-            Err(SyncerError::InvalidConfig.into())
+            debug!("shutting down runtime");
+            rt.shutdown_timeout(Duration::from_millis(100));
+            let _ = terminated.send(());
+        });
+        Ok(())
+    }
+}
+
+/// The watched addresses with their filters, each queried once however many tasks watch it
+fn dedup_addresses(
+    addresses: impl Iterator<Item = (BtcAddressAddendum, TxFilter)>,
+) -> Vec<(BtcAddressAddendum, TxFilter)> {
+    let mut deduped = vec![];
+    for address in addresses {
+        if !deduped.contains(&address) {
+            deduped.push(address);
         }
     }
+    deduped
 }
 
 fn logging(txs: &[AddressTx], address: &BtcAddressAddendum) {
@@ -1446,3 +2147,74 @@ pub fn p2wpkh_signed_tx_fee(
     // after multiplication we can safely convert
     fee.ceil() as u64
 }
+
+#[cfg(test)]
+fn replaceable_transaction(sequence: u32, outputs: &[u64]) -> bitcoin::Transaction {
+    bitcoin::Transaction {
+        version: 2,
+        lock_time: 0,
+        input: vec![bitcoin::TxIn {
+            previous_output: bitcoin::OutPoint::default(),
+            script_sig: Script::from(vec![0x51]),
+            sequence,
+            witness: bitcoin::Witness::from_vec(vec![vec![0x01; 72], vec![0x02; 33]]),
+        }],
+        output: outputs
+            .iter()
+            .map(|value| bitcoin::TxOut {
+                value: *value,
+                script_pubkey: Script::new(),
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn replacement_transaction_pays_the_new_fee_from_the_change() {
+    let tx = replaceable_transaction(0xffff_fffd, &[50_000, 100_000]);
+    let fee = 1_000;
+    let in_amount = 150_000 + fee;
+    let vsize = tx.vsize() as u64;
+
+    let replacement = replacement_transaction(&tx, in_amount, Some(1), 30_000).unwrap();
+    let new_fee = (30_000 * vsize + 999) / 1000;
+    assert_eq!(replacement.output[0], tx.output[0]);
+    assert_eq!(replacement.output[1].value, 100_000 - (new_fee - fee));
+    // BIP125 rules 3 and 4: the replacement pays for itself on top of the replaced fee
+    let out_amount: u64 = replacement.output.iter().map(|output| output.value).sum();
+    assert!(in_amount - out_amount >= fee + (INCREMENTAL_RELAY_FEE * vsize + 999) / 1000);
+    // same inputs, left unsigned for the wallet
+    assert_eq!(replacement.input.len(), 1);
+    assert_eq!(
+        replacement.input[0].previous_output,
+        tx.input[0].previous_output
+    );
+    assert_eq!(replacement.input[0].sequence, 0xffff_fffd);
+    assert!(replacement.input[0].script_sig.is_empty());
+    assert!(replacement.input[0].witness.is_empty());
+}
+
+#[test]
+fn replacement_transaction_enforces_bip125() {
+    let fee = 1_000;
+    let in_amount = 150_000 + fee;
+
+    // rule 1: no input signals replaceability
+    let final_tx = replaceable_transaction(0xffff_fffe, &[50_000, 100_000]);
+    assert!(replacement_transaction(&final_tx, in_amount, Some(1), 30_000).is_err());
+
+    let tx = replaceable_transaction(0xffff_fffd, &[50_000, 100_000]);
+    let vsize = tx.vsize() as u64;
+    // rules 3 and 4: paying the replaced fee without the incremental relay fee is refused
+    let same_fee_rate = fee * 1000 / vsize;
+    assert!(replacement_transaction(&tx, in_amount, Some(1), same_fee_rate).is_err());
+    // the smallest replacement paying the incremental relay fee is accepted
+    let min_fee = fee + (INCREMENTAL_RELAY_FEE * vsize + 999) / 1000;
+    let min_fee_rate = min_fee * 1000 / vsize + 1;
+    assert!(replacement_transaction(&tx, in_amount, Some(1), min_fee_rate).is_ok());
+
+    // the additional fee has to come from a change output above the dust limit
+    assert!(replacement_transaction(&tx, in_amount, None, 30_000).is_err());
+    let small_change = replaceable_transaction(0xffff_fffd, &[148_000, 2_000]);
+    assert!(replacement_transaction(&small_change, in_amount, Some(1), 30_000).is_err());
+}
@@ -0,0 +1,368 @@
+// Copyright 2020-2022 Farcaster Devs & LNP/BP Standards Association
+//
+// Use of this source code is governed by an MIT-style
+// license that can be found in the LICENSE file or at
+// https://opensource.org/licenses/MIT.
+
+//! Deterministic synclet replaying scripted events in response to tasks, used by tests to drive
+//! swaps without blockchain backends. The scenario is read from the YAML file passed to syncerd
+//! with `--mock-scenario` and maps task kinds to the events replayed for them, e.g.:
+//!
+//! ```yaml
+//! steps:
+//!   - task: WatchHeight
+//!     events:
+//!       - height_changed: { height: 100 }
+//!   - task: WatchEstimateFee
+//!     events:
+//!       - fee_estimation: { sat_per_kvb: 1000 }
+//!   - task: WatchTransaction
+//!     repeat: true
+//!     events:
+//!       - transaction_confirmations: { confirmations: 1 }
+//!       - delay: { millis: 500 }
+//!       # the transaction is reorged out
+//!       - transaction_confirmations: { confirmations: null }
+//!   - task: BroadcastTransaction
+//!     events:
+//!       - transaction_broadcasted: { error: "txn-mempool-conflict" }
+//! ```
+//!
+//! Replayed events carry the id of the task they respond to. Tasks not matching any step are
+//! left unanswered, as a backend without news would.
+
+use std::path::Path;
+use std::sync::mpsc::{Receiver, Sender};
+use std::thread::sleep;
+use std::time::Duration;
+
+use farcaster_core::blockchain::Network;
+use internet2::session::LocalSession;
+use internet2::zeromq::ZmqSocketType;
+use internet2::SendRecvMessage;
+use internet2::TypedEnum;
+use serde::Deserialize;
+
+use crate::bus::sync::{BridgeEvent, SyncMsg};
+use crate::bus::BusMsg;
+use crate::error::Error;
+use crate::syncerd::opts::Opts;
+use crate::syncerd::runtime::{SyncerdTask, Synclet};
+use crate::syncerd::types::{
+    Event, FeeEstimation, FeeEstimations, HeightChanged, MoneroUnlocked, Task, TaskAborted, TaskId,
+    TaskTarget, TransactionBroadcasted, TransactionConfirmations, Txid, WatchTransaction,
+};
+
+/// Task to event mapping replayed by the [`MockSyncer`]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(crate = "serde_crate")]
+pub struct Scenario {
+    pub steps: Vec<ScenarioStep>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(crate = "serde_crate")]
+pub struct ScenarioStep {
+    /// Kind of the task the step responds to, e.g. `WatchTransaction`
+    pub task: String,
+    /// Replay the step for every matching task instead of only the first one
+    #[serde(default)]
+    pub repeat: bool,
+    pub events: Vec<ScenarioEvent>,
+}
+
+/// An event to replay, completed with the id and data of the task it responds to
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(crate = "serde_crate", rename_all = "snake_case")]
+pub enum ScenarioEvent {
+    HeightChanged {
+        height: u64,
+    },
+    TransactionConfirmations {
+        confirmations: Option<u32>,
+    },
+    TransactionBroadcasted {
+        error: Option<String>,
+    },
+    /// The watched Monero transaction reached the unlock depth
+    MoneroUnlocked,
+    TaskAborted {
+        error: Option<String>,
+    },
+    /// Bitcoin fee rate estimated for both priorities
+    FeeEstimation {
+        sat_per_kvb: u64,
+    },
+    Empty,
+    /// Wait before replaying the next event, e.g. to trigger timeouts
+    Delay {
+        millis: u64,
+    },
+}
+
+impl Scenario {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let scenario = std::fs::read_to_string(path).map_err(|err| {
+            Error::Farcaster(format!(
+                "Failed to read the mock scenario {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+        serde_yaml::from_str(&scenario).map_err(|err| {
+            Error::Farcaster(format!(
+                "Failed to parse the mock scenario {}: {}",
+                path.display(),
+                err
+            ))
+        })
+    }
+
+    /// Take the events replayed for the task, consuming the matching step unless it repeats
+    pub fn events_for(&mut self, task: &Task) -> Option<Vec<ScenarioEvent>> {
+        let kind = task_kind(task);
+        let pos = self.steps.iter().position(|step| step.task == kind)?;
+        if self.steps[pos].repeat {
+            Some(self.steps[pos].events.clone())
+        } else {
+            Some(self.steps.remove(pos).events)
+        }
+    }
+}
+
+impl ScenarioEvent {
+    /// The event responding to the task, none for delays
+    fn to_event(&self, task: &Task) -> Option<Event> {
+        let id = task.id().unwrap_or(TaskId(0));
+        let event = match self {
+            ScenarioEvent::HeightChanged { height } => Event::HeightChanged(HeightChanged {
+                id,
+                block: vec![],
+                height: *height,
+            }),
+            ScenarioEvent::TransactionConfirmations { confirmations } => {
+                Event::TransactionConfirmations(TransactionConfirmations {
+                    id,
+                    block: vec![],
+                    confirmations: *confirmations,
+                    tx: vec![],
+                })
+            }
+            ScenarioEvent::TransactionBroadcasted { error } => {
+                let tx = match task {
+                    Task::BroadcastTransaction(broadcast) => broadcast.tx.clone(),
+                    _ => vec![],
+                };
+                Event::TransactionBroadcasted(TransactionBroadcasted {
+                    id,
+                    tx,
+                    error: error.clone(),
+                })
+            }
+            ScenarioEvent::MoneroUnlocked => {
+                let txid = match task {
+                    Task::WatchTransaction(WatchTransaction {
+                        hash: Txid::Monero(hash),
+                        ..
+                    }) => *hash,
+                    _ => monero::Hash::null(),
+                };
+                Event::MoneroUnlocked(MoneroUnlocked { id, txid })
+            }
+            ScenarioEvent::TaskAborted { error } => {
+                let id = match task {
+                    Task::Abort(abort) => match abort.task_target {
+                        TaskTarget::TaskId(id) => vec![id],
+                        TaskTarget::AllTasks => vec![],
+                    },
+                    _ => vec![id],
+                };
+                Event::TaskAborted(TaskAborted {
+                    id,
+                    error: error.clone(),
+                })
+            }
+            ScenarioEvent::FeeEstimation { sat_per_kvb } => Event::FeeEstimation(FeeEstimation {
+                id,
+                fee_estimations: FeeEstimations::BitcoinFeeEstimation {
+                    high_priority_sats_per_kvbyte: *sat_per_kvb,
+                    low_priority_sats_per_kvbyte: *sat_per_kvb,
+                },
+            }),
+            ScenarioEvent::Empty => Event::Empty(id),
+            ScenarioEvent::Delay { .. } => return None,
+        };
+        Some(event)
+    }
+}
+
+/// Synclet replaying a [`Scenario`] instead of querying a blockchain backend
+pub struct MockSyncer {
+    scenario: Option<Scenario>,
+}
+
+impl MockSyncer {
+    pub fn with(scenario: Scenario) -> Self {
+        Self {
+            scenario: Some(scenario),
+        }
+    }
+}
+
+impl Synclet for MockSyncer {
+    fn run(
+        &mut self,
+        receive_task_channel: Receiver<SyncerdTask>,
+        tx: zmq::Socket,
+        syncer_address: Vec<u8>,
+        terminated: Sender<()>,
+        _opts: &Opts,
+        _network: Network,
+    ) -> Result<(), Error> {
+        let mut scenario = self.scenario.take().unwrap_or_default();
+        std::thread::spawn(move || {
+            let mut session = LocalSession::with_zmq_socket(ZmqSocketType::Push, tx);
+            while let Ok(syncerd_task) = receive_task_channel.recv() {
+                if syncerd_task.task == Task::Terminate {
+                    debug!("terminating mock syncer");
+                    break;
+                }
+                let events = match scenario.events_for(&syncerd_task.task) {
+                    Some(events) => events,
+                    None => {
+                        debug!("no scripted events for task {}", syncerd_task.task);
+                        continue;
+                    }
+                };
+                for scripted in events {
+                    if let ScenarioEvent::Delay { millis } = scripted {
+                        sleep(Duration::from_millis(millis));
+                    }
+                    if let Some(event) = scripted.to_event(&syncerd_task.task) {
+                        let request = BusMsg::Sync(SyncMsg::BridgeEvent(BridgeEvent {
+                            event,
+                            source: syncerd_task.source.clone(),
+                        }));
+                        trace!("replaying over syncerd bridge: {}", request);
+                        session
+                            .send_routed_message(
+                                &syncer_address,
+                                &syncer_address,
+                                &syncer_address,
+                                &request.serialize(),
+                            )
+                            .expect("failed to send from mock syncer to syncerd bridge");
+                    }
+                }
+            }
+            let _ = terminated.send(());
+        });
+        Ok(())
+    }
+}
+
+fn task_kind(task: &Task) -> &'static str {
+    match task {
+        Task::Abort(_) => "Abort",
+        Task::WatchHeight(_) => "WatchHeight",
+        Task::WatchAddress(_) => "WatchAddress",
+        Task::WatchAddresses(_) => "WatchAddresses",
+        Task::WatchTransaction(_) => "WatchTransaction",
+        Task::WatchOutpointSpend(_) => "WatchOutpointSpend",
+        Task::WatchOutpoint(_) => "WatchOutpoint",
+        Task::WatchMempoolEntry(_) => "WatchMempoolEntry",
+        Task::WatchMoneroAddress(_) => "WatchMoneroAddress",
+        Task::BroadcastTransaction(_) => "BroadcastTransaction",
+        Task::SweepAddress(_) => "SweepAddress",
+        Task::GetTx(_) => "GetTx",
+        Task::GetAddressBalance(_) => "GetAddressBalance",
+        Task::WatchEstimateFee(_) => "WatchEstimateFee",
+        Task::HealthCheck(_) => "HealthCheck",
+        Task::GetMempoolFeeHistogram(_) => "GetMempoolFeeHistogram",
+        Task::WatchEstimatedRefundTime(_) => "WatchEstimatedRefundTime",
+        Task::GetBackendStatus(_) => "GetBackendStatus",
+        Task::GetBlockByHeight(_) => "GetBlockByHeight",
+        Task::GetBalance(_) => "GetBalance",
+        Task::BumpFee(_) => "BumpFee",
+        Task::SetPollIntervals(_) => "SetPollIntervals",
+        Task::Terminate => "Terminate",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syncerd::types::{WatchEstimateFee, WatchHeight};
+
+    #[test]
+    fn mock_scenario_replays_steps() {
+        let mut scenario: Scenario = serde_yaml::from_str(
+            r#"
+steps:
+  - task: WatchHeight
+    events:
+      - height_changed: { height: 100 }
+      - delay: { millis: 1 }
+  - task: WatchHeight
+    repeat: true
+    events:
+      - empty
+"#,
+        )
+        .unwrap();
+        let task = Task::WatchHeight(WatchHeight {
+            id: TaskId(3),
+            lifetime: 1,
+        });
+
+        let events = scenario.events_for(&task).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(
+            events[0].to_event(&task),
+            Some(Event::HeightChanged(HeightChanged {
+                id: TaskId(3),
+                block: vec![],
+                height: 100,
+            }))
+        );
+        assert_eq!(events[1].to_event(&task), None);
+
+        // the first step is consumed, the repeated one keeps answering
+        for _ in 0..2 {
+            let events = scenario.events_for(&task).unwrap();
+            assert_eq!(events[0].to_event(&task), Some(Event::Empty(TaskId(3))));
+        }
+        assert_eq!(scenario.steps.len(), 1);
+    }
+
+    #[test]
+    fn mock_scenario_estimates_fee() {
+        let mut scenario: Scenario = serde_yaml::from_str(
+            r#"
+steps:
+  - task: WatchEstimateFee
+    events:
+      - fee_estimation: { sat_per_kvb: 1000 }
+"#,
+        )
+        .unwrap();
+        let task = Task::WatchEstimateFee(WatchEstimateFee {
+            id: TaskId(4),
+            lifetime: 1,
+        });
+
+        let events = scenario.events_for(&task).unwrap();
+        assert_eq!(
+            events[0].to_event(&task),
+            Some(Event::FeeEstimation(FeeEstimation {
+                id: TaskId(4),
+                fee_estimations: FeeEstimations::BitcoinFeeEstimation {
+                    high_priority_sats_per_kvbyte: 1000,
+                    low_priority_sats_per_kvbyte: 1000,
+                },
+            }))
+        );
+        assert!(scenario.events_for(&task).is_none());
+    }
+}
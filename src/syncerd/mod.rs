@@ -4,8 +4,13 @@
 // license that can be found in the LICENSE file or at
 // https://opensource.org/licenses/MIT.
 
+pub mod bitcoin_backend;
 pub mod bitcoin_syncer;
+#[cfg(feature = "shell")]
+pub mod mock_syncer;
 pub mod monero_syncer;
+pub mod polling;
+pub mod retry;
 pub mod syncer_state;
 pub mod types;
 
@@ -8,19 +8,26 @@ use crate::bus::info::Address;
 use crate::bus::sync::{BridgeEvent, SyncMsg};
 use crate::bus::{AddressSecretKey, BusMsg};
 use crate::error::{Error, SyncerError};
+use crate::monero_network;
 use crate::service::LogStyle;
-use crate::syncerd::opts::Opts;
+use crate::syncerd::opts::{adaptive_poll, Opts, DEFAULT_POLL_INTERVAL_MS};
+use crate::syncerd::polling::AdaptivePoll;
+use crate::syncerd::retry::RetryPolicy;
 use crate::syncerd::runtime::SyncerdTask;
 use crate::syncerd::runtime::Synclet;
 use crate::syncerd::syncer_state::create_set;
 use crate::syncerd::syncer_state::AddressTx;
+use crate::syncerd::syncer_state::MoneroReceivedTx;
 use crate::syncerd::syncer_state::SyncerState;
 use crate::syncerd::types::{AddressAddendum, SweepAddressAddendum, Task};
+use crate::syncerd::GetMempoolFeeHistogram;
 use crate::syncerd::TaskTarget;
 use crate::syncerd::TransactionBroadcasted;
+use crate::syncerd::WatchMoneroAddress;
 use crate::syncerd::XmrAddressAddendum;
 use crate::syncerd::{AddressBalance, TxFilter};
 use crate::syncerd::{Event, Health};
+use crate::syncerd::{WatchMempoolEntry, WatchOutpoint, WatchOutpointSpend};
 use farcaster_core::blockchain::{Blockchain, Network};
 use internet2::session::LocalSession;
 use internet2::zeromq::ZmqSocketType;
@@ -28,19 +35,24 @@ use internet2::SendRecvMessage;
 use internet2::TypedEnum;
 use monero::PrivateKey;
 use monero_rpc::{
-    GenerateFromKeysArgs, GetBlockHeaderSelector, GetTransfersCategory, GetTransfersSelector,
-    PrivateKeyType,
+    BalanceData, GenerateFromKeysArgs, GetBlockHeaderSelector, GetTransfersCategory,
+    GetTransfersSelector, PrivateKeyType,
 };
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
-use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::mpsc::{Receiver, Sender, TryRecvError};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc::Receiver as TokioReceiver;
 use tokio::sync::mpsc::Sender as TokioSender;
 use tokio::sync::Mutex;
 
-use super::{syncer_state::BalanceServiceIdPair, HealthCheck, Txid};
+use super::{
+    syncer_state::{balance_event, BalanceServiceIdPair, BalanceTask},
+    BlockData, BumpFee, GetBackendStatus, GetBalance, GetBlockByHeight, HealthCheck,
+    SetPollIntervals, TaskId, Txid,
+};
 
 #[derive(Debug, Clone)]
 pub struct MoneroRpc {
@@ -89,16 +101,51 @@ impl MoneroRpc {
     }
 
     async fn get_height(&mut self) -> Result<u64, Error> {
-        let count: u64 = self.daemon_json_rpc.get_block_count().await?.into();
+        let count: u64 = self
+            .daemon_json_rpc
+            .get_block_count()
+            .await
+            .map_err(|err| SyncerError::rpc("get_block_count", err))?
+            .into();
         Ok(count - 1)
     }
 
     async fn get_block_hash(&mut self, height: u64) -> Result<Vec<u8>, Error> {
         let selector = GetBlockHeaderSelector::Height(height);
-        let header = self.daemon_json_rpc.get_block_header(selector).await?;
+        let header = self
+            .daemon_json_rpc
+            .get_block_header(selector)
+            .await
+            .map_err(|err| SyncerError::rpc("get_block_header", err))?;
         Ok(header.hash.0.to_vec())
     }
 
+    /// Return the block at the given height with its consensus serialized header, the daemon
+    /// rpc does not serve the block transactions
+    async fn get_block_data(&mut self, id: TaskId, height: u64) -> Result<BlockData, Error> {
+        let selector = GetBlockHeaderSelector::Height(height);
+        let header = self
+            .daemon_json_rpc
+            .get_block_header(selector)
+            .await
+            .map_err(|err| SyncerError::rpc("get_block_header", err))?;
+        let block_header = monero::BlockHeader {
+            major_version: monero::VarInt(header.major_version.into()),
+            minor_version: monero::VarInt(header.minor_version.into()),
+            timestamp: monero::VarInt(header.timestamp.timestamp() as u64),
+            prev_id: header.prev_hash.0,
+            nonce: header.nonce,
+        };
+        Ok(BlockData {
+            id,
+            height,
+            hash: header.hash.0.to_vec(),
+            header: monero::consensus::encode::serialize(&block_header),
+            tx_count: Some(header.num_txes),
+            txs: None,
+        })
+    }
+
     async fn get_transactions(
         &mut self,
         monero_txids: Vec<monero::Hash>,
@@ -306,6 +353,106 @@ impl MoneroRpc {
 
         Ok(AddressNotif { txs: address_txs })
     }
+
+    /// Scan for transactions paying to the watched address with a view-only
+    /// wallet. Returns the height scanned up to and the received transactions.
+    async fn check_received(
+        &mut self,
+        task: WatchMoneroAddress,
+        scanned_height: u64,
+        wallet_mutex: Arc<Mutex<monero_rpc::WalletClient>>,
+    ) -> Result<(u64, Vec<MoneroReceivedTx>), Error> {
+        let WatchMoneroAddress {
+            address,
+            view_key,
+            from_height,
+            ..
+        } = task;
+        let wallet_filename = format!("view:{}", address);
+        let password = s!(" ");
+
+        let wallet = wallet_mutex.lock().await;
+        trace!("taking check received lock");
+
+        if let Err(err) = wallet
+            .open_wallet(wallet_filename.clone(), Some(password.clone()))
+            .await
+        {
+            debug!("wallet doesn't exist, generating a new wallet: {}", err);
+            wallet
+                .generate_from_keys(GenerateFromKeysArgs {
+                    restore_height: Some(from_height),
+                    filename: wallet_filename.clone(),
+                    address,
+                    spendkey: None,
+                    viewkey: view_key,
+                    password: password.clone(),
+                    autosave_current: Some(true),
+                })
+                .await?;
+            wallet.open_wallet(wallet_filename, Some(password)).await?;
+        }
+
+        // The wallet is persisted by the wallet rpc, so after a restart scanning
+        // resumes from where the wallet left off, or from the last scanned height
+        // if that is further ahead.
+        wallet.refresh(Some(scanned_height)).await?;
+        let height = self.get_height().await?;
+
+        let mut category_selector: HashMap<GetTransfersCategory, bool> = HashMap::new();
+        category_selector.insert(GetTransfersCategory::In, true);
+        category_selector.insert(GetTransfersCategory::Pool, true);
+        let selector = GetTransfersSelector {
+            category_selector,
+            subaddr_indices: None,
+            account_index: None,
+            block_height_filter: Some(monero_rpc::BlockHeightFilter {
+                min_height: Some(from_height),
+                max_height: None,
+            }),
+        };
+        let mut transfers = wallet.get_transfers(selector).await?;
+        trace!("releasing check received lock");
+        drop(wallet);
+
+        // A single transaction may carry several outputs to the same address,
+        // which can be reported as separate transfers. Sum them up per txid so
+        // the minimum amount is checked against the total received.
+        let mut received: HashMap<monero::Hash, MoneroReceivedTx> = HashMap::new();
+        for (_, mut txs) in transfers.drain() {
+            for tx in txs.drain(..) {
+                if tx.unlock_time > 0 {
+                    warn!(
+                        "Address {} had transaction {} with an unlock time {}. Locked transactions are not supported. Skipping.",
+                        address, tx.txid, tx.unlock_time
+                    );
+                    continue;
+                }
+                let tx_id = monero::Hash::from_slice(&tx.txid.0);
+                let confirmations = tx.confirmations.map(|confs| confs as u32);
+                received
+                    .entry(tx_id)
+                    .and_modify(|received_tx| received_tx.amount += tx.amount.as_pico())
+                    .or_insert(MoneroReceivedTx {
+                        tx_id,
+                        amount: tx.amount.as_pico(),
+                        confirmations,
+                    });
+            }
+        }
+
+        Ok((height, received.into_values().collect()))
+    }
+}
+
+/// Outcome of an attempt to sweep a Monero address
+enum SweepResult {
+    /// The sweep transactions were relayed
+    Swept(Vec<Txid>),
+    /// The funds were received but cannot be spent yet
+    Locked { confirmations_remaining: u32 },
+    /// There is nothing to sweep yet
+    Empty,
 }
 
 async fn sweep_address(
@@ -317,7 +464,8 @@ async fn sweep_address(
     wallet_mutex: Arc<Mutex<monero_rpc::WalletClient>>,
     restore_height: Option<u64>,
     wallet_dir_path: Option<PathBuf>,
-) -> Result<Vec<Txid>, Error> {
+    unlock_depth: u32,
+) -> Result<SweepResult, Error> {
     let keypair = monero::KeyPair { view, spend };
     let password = s!(" ");
     let source_address = monero::Address::from_keypair(*network, &keypair);
@@ -421,14 +569,45 @@ async fn sweep_address(
         } else {
             info!("Completed operations on Monero wallets with address {}. These wallets can now be safely deleted", source_address.addr());
         }
-        Ok(tx_ids)
+        if tx_ids.is_empty() {
+            Ok(SweepResult::Empty)
+        } else {
+            Ok(SweepResult::Swept(tx_ids))
+        }
+    } else if balance.balance >= minimum_balance {
+        // the funds arrived, find out how long until the youngest output unlocks
+        let mut category_selector: HashMap<GetTransfersCategory, bool> = HashMap::new();
+        category_selector.insert(GetTransfersCategory::In, true);
+        category_selector.insert(GetTransfersCategory::Pool, true);
+        let selector = GetTransfersSelector {
+            category_selector,
+            subaddr_indices: None,
+            account_index: None,
+            block_height_filter: None,
+        };
+        let confirmations_remaining = wallet
+            .get_transfers(selector)
+            .await?
+            .values()
+            .flatten()
+            .map(|transfer| unlock_depth.saturating_sub(transfer.confirmations.unwrap_or(0) as u32))
+            .max()
+            .unwrap_or(0);
+        debug!(
+            "retrying sweep, balance not unlocked yet. Unlocked balance {}. Total balance {}. Expected balance {}. Confirmations remaining {}.",
+            balance.unlocked_balance, balance.balance, minimum_balance, confirmations_remaining
+        );
+        trace!("releasing sweep wallet lock");
+        Ok(SweepResult::Locked {
+            confirmations_remaining,
+        })
     } else {
         debug!(
-            "retrying sweep, balance not unlocked yet. Unlocked balance {}. Total balance {}. Expected balance {}.",
+            "retrying sweep, balance not received yet. Unlocked balance {}. Total balance {}. Expected balance {}.",
             balance.unlocked_balance, balance.balance, minimum_balance
         );
         trace!("releasing sweep wallet lock");
-        Ok(vec![])
+        Ok(SweepResult::Empty)
     }
 }
 
@@ -443,11 +622,13 @@ impl MoneroSyncer {
 
 async fn run_syncerd_task_receiver(
     syncer_servers: MoneroSyncerServers,
+    retry: RetryPolicy,
     receive_task_channel: Receiver<SyncerdTask>,
     state: Arc<Mutex<SyncerState>>,
     balance_get_tx: TokioSender<BalanceServiceIdPair>,
     tx_event: TokioSender<BridgeEvent>,
     proxy_address: Option<String>,
+    terminate_tx: TokioSender<()>,
 ) {
     tokio::spawn(async move {
         loop {
@@ -461,13 +642,44 @@ async fn run_syncerd_task_receiver(
                         }
                         Task::GetAddressBalance(task) => {
                             balance_get_tx
-                                .send((task, syncerd_task.source))
+                                .send((BalanceTask::Address(task), syncerd_task.source))
+                                .await
+                                .expect("failed on balance_get sender");
+                        }
+                        Task::GetBalance(task) => {
+                            balance_get_tx
+                                .send((BalanceTask::Descriptor(task), syncerd_task.source))
                                 .await
                                 .expect("failed on balance_get sender");
                         }
                         Task::WatchEstimateFee(_) => {
                             error!("estimate fee not implemented for monero syncer");
                         }
+                        Task::GetMempoolFeeHistogram(GetMempoolFeeHistogram { id }) => {
+                            error!("mempool fee histogram not available for Monero");
+                            let mut state_guard = state.lock().await;
+                            state_guard.unsupported_task(id, syncerd_task.source).await;
+                        }
+                        Task::BumpFee(BumpFee { id, .. }) => {
+                            error!("fee bump not available for Monero");
+                            let mut state_guard = state.lock().await;
+                            state_guard.unsupported_task(id, syncerd_task.source).await;
+                        }
+                        Task::WatchOutpoint(WatchOutpoint { id, .. }) => {
+                            error!("outpoint watch not available for Monero");
+                            let mut state_guard = state.lock().await;
+                            state_guard.unsupported_task(id, syncerd_task.source).await;
+                        }
+                        Task::WatchOutpointSpend(WatchOutpointSpend { id, .. }) => {
+                            error!("outpoint spend watch not available for Monero");
+                            let mut state_guard = state.lock().await;
+                            state_guard.unsupported_task(id, syncerd_task.source).await;
+                        }
+                        Task::WatchMempoolEntry(WatchMempoolEntry { id, .. }) => {
+                            error!("mempool entry watch not available for Monero");
+                            let mut state_guard = state.lock().await;
+                            state_guard.unsupported_task(id, syncerd_task.source).await;
+                        }
                         Task::SweepAddress(task) => match task.addendum.clone() {
                             SweepAddressAddendum::Monero(sweep) => {
                                 let addr = sweep.destination_address;
@@ -514,17 +726,97 @@ async fn run_syncerd_task_receiver(
                                     .await;
                             }
                         },
+                        Task::WatchAddresses(task) => {
+                            // the runtime splits the batches into WatchAddress tasks, the synclet
+                            // polls all the watched addresses together
+                            error!("Unexpected watch addresses batch {:?} in the synclet", task);
+                        }
+                        Task::WatchMoneroAddress(task) => {
+                            debug!("received new watch monero address task: {}", task);
+                            let mut state_guard = state.lock().await;
+                            state_guard.watch_monero_address(task, syncerd_task.source);
+                        }
                         Task::WatchHeight(task) => {
                             let mut state_guard = state.lock().await;
                             state_guard.watch_height(task, syncerd_task.source).await;
                         }
+                        Task::WatchEstimatedRefundTime(task) => {
+                            let mut state_guard = state.lock().await;
+                            state_guard
+                                .watch_estimated_refund_time(task, syncerd_task.source)
+                                .await;
+                        }
                         Task::WatchTransaction(task) => {
                             debug!("received new watch tx task: {}", task.hash);
                             let mut state_guard = state.lock().await;
                             state_guard.watch_transaction(task, syncerd_task.source);
                         }
+                        Task::SetPollIntervals(SetPollIntervals {
+                            poll_interval,
+                            max_poll_interval,
+                        }) => match adaptive_poll(
+                            poll_interval.unwrap_or(DEFAULT_POLL_INTERVAL_MS),
+                            max_poll_interval,
+                        ) {
+                            Ok(poll) => {
+                                info!(
+                                    "Polling the backend every {:?} to {:?}",
+                                    poll.min_interval, poll.max_interval
+                                );
+                                let mut state_guard = state.lock().await;
+                                state_guard.set_poll_bounds(poll);
+                            }
+                            Err(err) => error!("Ignoring the new poll intervals: {}", err),
+                        },
                         Task::Terminate => {
-                            debug!("unimplemented");
+                            debug!("terminating async syncer runtime");
+                            terminate_tx
+                                .send(())
+                                .await
+                                .expect("terminating, don't care if we panic");
+                        }
+                        Task::GetBackendStatus(GetBackendStatus { id }) => {
+                            let tip_height = create_rpc_client(
+                                syncer_servers.monero_daemon.clone(),
+                                proxy_address.clone(),
+                            )
+                            .daemon()
+                            .get_block_count()
+                            .await
+                            .map(|count| u64::from(count).saturating_sub(1))
+                            .map_err(|err| err.to_string());
+                            let mut state_guard = state.lock().await;
+                            state_guard
+                                .backend_status(id, tip_height, syncerd_task.source)
+                                .await;
+                        }
+                        Task::GetBlockByHeight(GetBlockByHeight {
+                            id,
+                            height,
+                            include_txs,
+                        }) => {
+                            debug!("retrieving the block at height {}", height);
+                            let block = if include_txs {
+                                Err("block transactions not available for Monero".to_string())
+                            } else {
+                                MoneroRpc::new(
+                                    syncer_servers.monero_daemon.clone(),
+                                    proxy_address.clone(),
+                                )
+                                .get_block_data(id, height)
+                                .await
+                                .map_err(|err| err.to_string())
+                            };
+                            if let Err(err) = &block {
+                                error!(
+                                    "failed to retrieve the block at height {}: {}",
+                                    height, err
+                                );
+                            }
+                            let mut state_guard = state.lock().await;
+                            state_guard
+                                .block_data_result(id, block, syncerd_task.source)
+                                .await;
                         }
                         Task::HealthCheck(HealthCheck { id }) => {
                             debug!("performing health check");
@@ -599,6 +891,7 @@ fn address_polling(
     syncer_servers: MoneroSyncerServers,
     wallet_mutex: Arc<Mutex<monero_rpc::WalletClient>>,
     proxy_address: Option<String>,
+    mut poll: AdaptivePoll,
 ) -> tokio::task::JoinHandle<()> {
     tokio::task::spawn(async move {
         let mut rpc = MoneroRpc::new(syncer_servers.monero_daemon, proxy_address);
@@ -689,7 +982,48 @@ fn address_polling(
                         .await;
                 }
             }
-            tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
+            let interval = state.lock().await.next_poll(&mut poll);
+            tokio::time::sleep(interval).await;
+        }
+    })
+}
+
+fn received_polling(
+    state: Arc<Mutex<SyncerState>>,
+    syncer_servers: MoneroSyncerServers,
+    wallet_mutex: Arc<Mutex<monero_rpc::WalletClient>>,
+    proxy_address: Option<String>,
+    mut poll: AdaptivePoll,
+) -> tokio::task::JoinHandle<()> {
+    tokio::task::spawn(async move {
+        let mut rpc = MoneroRpc::new(syncer_servers.monero_daemon, proxy_address);
+        loop {
+            let state_guard = state.lock().await;
+            let mut monero_addresses = state_guard.monero_addresses.clone();
+            drop(state_guard);
+            for (id, watched_address) in monero_addresses.drain() {
+                match rpc
+                    .check_received(
+                        watched_address.task,
+                        watched_address.scanned_height,
+                        Arc::clone(&wallet_mutex),
+                    )
+                    .await
+                {
+                    Ok((scanned_height, received)) => {
+                        let mut state_guard = state.lock().await;
+                        state_guard
+                            .change_monero_address(id, scanned_height, received)
+                            .await;
+                        drop(state_guard);
+                    }
+                    Err(err) => {
+                        error!("error polling received transactions: {}", err);
+                    }
+                }
+            }
+            let interval = state.lock().await.next_poll(&mut poll);
+            tokio::time::sleep(interval).await;
         }
     })
 }
@@ -698,6 +1032,7 @@ fn height_polling(
     state: Arc<Mutex<SyncerState>>,
     syncer_servers: MoneroSyncerServers,
     proxy_address: Option<String>,
+    mut poll: AdaptivePoll,
 ) -> tokio::task::JoinHandle<()> {
     tokio::task::spawn(async move {
         let mut rpc = MoneroRpc::new(syncer_servers.monero_daemon, proxy_address);
@@ -751,7 +1086,8 @@ fn height_polling(
                     }
                 }
             }
-            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            let interval = state.lock().await.next_poll(&mut poll);
+            tokio::time::sleep(interval).await;
         }
     })
 }
@@ -766,11 +1102,12 @@ fn sweep_polling(
         loop {
             let state_guard = state.lock().await;
             let sweep_addresses = state_guard.sweep_addresses.clone();
+            let unlock_depth = state_guard.unlock_depth();
             drop(state_guard);
             for (id, sweep_address_task) in sweep_addresses.iter() {
                 if let SweepAddressAddendum::Monero(addendum) = sweep_address_task.addendum.clone()
                 {
-                    let sweep_address_txs = sweep_address(
+                    let sweep_result = sweep_address(
                         addendum.destination_address,
                         addendum.source_view_key,
                         addendum.source_spend_key,
@@ -779,6 +1116,7 @@ fn sweep_polling(
                         Arc::clone(&wallet),
                         addendum.from_height,
                         wallet_dir_path.clone(),
+                        unlock_depth,
                     )
                     .await
                     .unwrap_or_else(|err| {
@@ -786,13 +1124,18 @@ fn sweep_polling(
                             "error polling sweep address {}, retrying: {}",
                             err, sweep_address_task.retry
                         );
-                        vec![]
+                        SweepResult::Empty
                     });
                     let mut state_guard = state.lock().await;
-                    if !sweep_address_txs.is_empty() {
-                        state_guard.success_sweep(id, sweep_address_txs).await;
-                    } else if !sweep_address_task.retry {
-                        state_guard.fail_sweep(id).await;
+                    match sweep_result {
+                        SweepResult::Swept(txids) => state_guard.success_sweep(id, txids).await,
+                        SweepResult::Locked {
+                            confirmations_remaining,
+                        } => state_guard.sweep_locked(id, confirmations_remaining).await,
+                        SweepResult::Empty if !sweep_address_task.retry => {
+                            state_guard.fail_sweep(id).await
+                        }
+                        SweepResult::Empty => {}
                     }
                     drop(state_guard);
                 }
@@ -806,6 +1149,7 @@ fn unseen_transaction_polling(
     state: Arc<Mutex<SyncerState>>,
     syncer_servers: MoneroSyncerServers,
     proxy_address: Option<String>,
+    mut poll: AdaptivePoll,
 ) -> tokio::task::JoinHandle<()> {
     tokio::task::spawn(async move {
         let mut rpc = MoneroRpc::new(syncer_servers.monero_daemon, proxy_address);
@@ -848,7 +1192,8 @@ fn unseen_transaction_polling(
                         .await;
                 }
             }
-            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            let interval = state.lock().await.next_poll(&mut poll);
+            tokio::time::sleep(interval).await;
         }
     })
 }
@@ -881,7 +1226,7 @@ async fn fetch_balance(
     address: monero::Address,
     viewkey: PrivateKey,
     creation_height: u64,
-) -> Result<monero::Amount, Error> {
+) -> Result<BalanceData, Error> {
     let wallet_filename = format!("balance:{}", address);
     let password = s!(" ");
     debug!("creating balance fetcher wallet client");
@@ -925,81 +1270,135 @@ async fn fetch_balance(
         }
     }
 
-    Ok(balance.balance)
+    Ok(balance)
 }
 
 fn balance_fetcher(
     wallet_mutex: Arc<Mutex<monero_rpc::WalletClient>>,
     wallet_dir_path: Option<PathBuf>,
+    retry: RetryPolicy,
     mut balance_get_rx: TokioReceiver<BalanceServiceIdPair>,
     tx_event: TokioSender<BridgeEvent>,
 ) -> tokio::task::JoinHandle<()> {
     tokio::task::spawn(async move {
-        while let Some((get_balance, source)) = balance_get_rx.recv().await {
-            match get_balance.address_secret_key {
-                AddressSecretKey::Monero {
-                    address,
-                    secret_key_info,
-                } => {
-                    match fetch_balance(
-                        Arc::clone(&wallet_mutex),
-                        wallet_dir_path.clone(),
-                        address,
-                        secret_key_info.view,
-                        secret_key_info.creation_height,
-                    )
-                    .await
-                    {
-                        Ok(balance) => {
-                            tx_event
-                                .send(BridgeEvent {
-                                    event: Event::AddressBalance(AddressBalance {
-                                        id: get_balance.id,
-                                        address: Address::Monero(address),
-                                        balance: balance.as_pico(),
-                                        err: None,
-                                    }),
-                                    source,
+        while let Some((balance_task, source)) = balance_get_rx.recv().await {
+            let wallet_mutex = Arc::clone(&wallet_mutex);
+            let wallet_dir_path = wallet_dir_path.clone();
+            let tx_event = tx_event.clone();
+            // handled in its own task, retrying a request must not hold up the next ones
+            tokio::spawn(async move {
+                let get_balance = match balance_task {
+                    BalanceTask::Address(get_balance) => get_balance,
+                    BalanceTask::Descriptor(GetBalance { id, descriptor }) => {
+                        let balance = match descriptor {
+                            AddressAddendum::Monero(XmrAddressAddendum {
+                                address,
+                                view_key,
+                                from_height,
+                            }) => retry
+                                .retry_async("balance retrieval", || {
+                                    fetch_balance(
+                                        Arc::clone(&wallet_mutex),
+                                        wallet_dir_path.clone(),
+                                        address,
+                                        view_key,
+                                        from_height,
+                                    )
                                 })
                                 .await
-                                .expect("error sending address balance event");
-                        }
-                        Err(e) => {
-                            tx_event
-                                .send(BridgeEvent {
-                                    event: Event::AddressBalance(AddressBalance {
-                                        id: get_balance.id,
-                                        address: Address::Monero(address),
-                                        balance: 0,
-                                        err: Some(e.to_string()),
-                                    }),
-                                    source,
+                                .map(|balance| {
+                                    // outputs are spendable once unlocked, the locked ones are
+                                    // reported as unconfirmed
+                                    let unlocked = balance.unlocked_balance.as_pico();
+                                    (unlocked, balance.balance.as_pico().saturating_sub(unlocked))
                                 })
-                                .await
-                                .expect("error sending address balance event");
-                            debug!("failed to retrieve address balance: {}", e);
+                                .map_err(|err| err.to_string()),
+                            AddressAddendum::Bitcoin(_) => {
+                                warn!("Received bitcoin balance task in monero syncer");
+                                Err("Sent bitcoin balance task to monero syncer".to_string())
+                            }
+                        };
+                        if let Err(err) = &balance {
+                            debug!("failed to retrieve balance: {}", err);
                         }
+                        tx_event
+                            .send(BridgeEvent {
+                                event: balance_event(id, balance),
+                                source,
+                            })
+                            .await
+                            .expect("error sending balance event");
+                        return;
                     }
-                }
-                AddressSecretKey::Bitcoin { address, .. } => {
-                    tx_event
-                        .send(BridgeEvent {
-                            event: Event::AddressBalance(AddressBalance {
-                                address: Address::Bitcoin(address),
-                                id: get_balance.id,
-                                balance: 0,
-                                err: Some(
-                                    "Sent monero address balance to bitcoin syncer".to_string(),
-                                ),
-                            }),
-                            source,
-                        })
-                        .await
-                        .expect("error sending address balance event");
-                    warn!("Received monero address balance task in bitcoin syncer");
-                    continue;
-                }
-            };
+                };
+                match get_balance.address_secret_key {
+                    AddressSecretKey::Monero {
+                        address,
+                        secret_key_info,
+                    } => {
+                        let balance = retry
+                            .retry_async("address balance retrieval", || {
+                                fetch_balance(
+                                    Arc::clone(&wallet_mutex),
+                                    wallet_dir_path.clone(),
+                                    address,
+                                    secret_key_info.view,
+                                    secret_key_info.creation_height,
+                                )
+                            })
+                            .await;
+                        match balance {
+                            Ok(balance) => {
+                                tx_event
+                                    .send(BridgeEvent {
+                                        event: Event::AddressBalance(AddressBalance {
+                                            id: get_balance.id,
+                                            address: Address::Monero(address),
+                                            balance: balance.balance.as_pico(),
+                                            err: None,
+                                        }),
+                                        source,
+                                    })
+                                    .await
+                                    .expect("error sending address balance event");
+                            }
+                            Err(e) => {
+                                tx_event
+                                    .send(BridgeEvent {
+                                        event: Event::AddressBalance(AddressBalance {
+                                            id: get_balance.id,
+                                            address: Address::Monero(address),
+                                            balance: 0,
+                                            err: Some(e.to_string()),
+                                        }),
+                                        source,
+                                    })
+                                    .await
+                                    .expect("error sending address balance event");
+                                debug!("failed to retrieve address balance: {}", e);
+                            }
+                        }
+                    }
+                    AddressSecretKey::Bitcoin { address, .. } => {
+                        tx_event
+                            .send(BridgeEvent {
+                                event: Event::AddressBalance(AddressBalance {
+                                    address: Address::Bitcoin(address),
+                                    id: get_balance.id,
+                                    balance: 0,
+                                    err: Some(
+                                        "Sent monero address balance to bitcoin syncer".to_string(),
+                                    ),
+                                }),
+                                source,
+                            })
+                            .await
+                            .expect("error sending address balance event");
+                        warn!("Received monero address balance task in bitcoin syncer");
+                        return;
+                    }
+                };
+            });
         }
     })
 }
@@ -1017,16 +1416,27 @@ pub struct MoneroSyncerServers {
     pub monero_lws: Option<String>,
 }
 
+fn terminate_polling(
+    mut rx_terminate: TokioReceiver<()>,
+) -> tokio::task::JoinHandle<Result<(), Error>> {
+    tokio::task::spawn(async move {
+        let _ = rx_terminate.recv().await;
+        debug!("received terminate thread");
+        panic!("terminate this thread");
+    })
+}
+
 impl Synclet for MoneroSyncer {
     fn run(
         &mut self,
         receive_task_channel: Receiver<SyncerdTask>,
         tx: zmq::Socket,
         syncer_address: Vec<u8>,
+        terminated: Sender<()>,
         opts: &Opts,
         network: Network,
     ) -> Result<(), Error> {
-        let network = network.into();
+        let network = monero_network(network);
         if let Some(daemon) = &opts.monero_daemon {
             if let Some(rpc_wallet) = &opts.monero_rpc_wallet {
                 let syncer_servers = MoneroSyncerServers {
@@ -1039,6 +1449,9 @@ impl Synclet for MoneroSyncer {
 
                 let proxy_address = opts.shared.tor_proxy.map(|address| address.to_string());
                 debug!("monero synclet using proxy: {:?}", proxy_address);
+                let poll = opts.adaptive_poll()?;
+                let retry = opts.retry_policy()?;
+                let unlock_depth = opts.monero_unlock_depth;
 
                 let _handle = std::thread::spawn(move || {
                     use tokio::runtime::Builder;
@@ -1064,18 +1477,21 @@ impl Synclet for MoneroSyncer {
                             TokioSender<BridgeEvent>,
                             TokioReceiver<BridgeEvent>,
                         ) = tokio::sync::mpsc::channel(120);
-                        let state = Arc::new(Mutex::new(SyncerState::new(
-                            event_tx.clone(),
-                            Blockchain::Monero,
-                        )));
+                        let mut state = SyncerState::new(event_tx.clone(), Blockchain::Monero);
+                        state.set_unlock_depth(unlock_depth);
+                        let state = Arc::new(Mutex::new(state));
+                        let (terminate_tx, terminate_rx): (TokioSender<()>, TokioReceiver<()>) =
+                            tokio::sync::mpsc::channel(1);
 
                         run_syncerd_task_receiver(
                             syncer_servers.clone(),
+                            retry,
                             receive_task_channel,
                             Arc::clone(&state),
                             balance_get_tx,
                             event_tx.clone(),
                             proxy_address.clone(),
+                            terminate_tx,
                         )
                         .await;
                         run_syncerd_bridge_event_sender(tx, event_rx, syncer_address).await;
@@ -1085,6 +1501,15 @@ impl Synclet for MoneroSyncer {
                             syncer_servers.clone(),
                             Arc::clone(&wallet_mutex),
                             proxy_address.clone(),
+                            poll,
+                        );
+
+                        let received_handle = received_polling(
+                            Arc::clone(&state),
+                            syncer_servers.clone(),
+                            Arc::clone(&wallet_mutex),
+                            proxy_address.clone(),
+                            poll,
                         );
 
                         // transaction polling is done in the same loop
@@ -1092,12 +1517,14 @@ impl Synclet for MoneroSyncer {
                             Arc::clone(&state),
                             syncer_servers.clone(),
                             proxy_address.clone(),
+                            poll,
                         );
 
                         let unseen_transaction_handle = unseen_transaction_polling(
                             Arc::clone(&state),
                             syncer_servers.clone(),
                             proxy_address.clone(),
+                            poll,
                         );
 
                         let sweep_handle = sweep_polling(
@@ -1110,19 +1537,27 @@ impl Synclet for MoneroSyncer {
                         let balance_handle = balance_fetcher(
                             Arc::clone(&wallet_mutex),
                             wallet_dir,
+                            retry,
                             balance_get_rx,
                             event_tx,
                         );
 
+                        let terminate_handle = terminate_polling(terminate_rx);
+
                         let res = tokio::try_join!(
                             address_handle,
+                            received_handle,
                             height_handle,
                             unseen_transaction_handle,
                             sweep_handle,
                             balance_handle,
+                            terminate_handle,
                         );
                         debug!("exiting monero synclet run routine with: {:?}", res);
                     });
+                    debug!("shutting down runtime");
+                    rt.shutdown_timeout(Duration::from_millis(100));
+                    let _ = terminated.send(());
                 });
                 Ok(())
             } else {
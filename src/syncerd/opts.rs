@@ -4,8 +4,25 @@
 // license that can be found in the LICENSE file or at
 // https://opensource.org/licenses/MIT.
 
+use crate::opts::{BridgeSocket, BusTransport};
+use crate::syncerd::polling::AdaptivePoll;
+use crate::syncerd::retry::RetryPolicy;
+use crate::Error;
 use farcaster_core::blockchain::{Blockchain, Network};
 use std::str::FromStr;
+use std::time::Duration;
+
+/// Poll intervals below this value are likely to get the syncer rate-limited by public backends
+const LOW_POLL_INTERVAL_MS: u64 = 100;
+
+/// Default interval between two polls while a watched item is about to change state
+pub const DEFAULT_POLL_INTERVAL_MS: u64 = 1000;
+
+/// Default upper bound of the poll interval while no watched item is about to change state
+const DEFAULT_MAX_POLL_INTERVAL_MS: u64 = 10000;
+
+/// Environment variable passing the Bitcoin Core RPC password to the syncer
+pub const BITCOIN_RPC_PASS_ENV: &str = "FARCASTER_BITCOIN_RPC_PASS";
 
 /// Syncer blockchain management daemon; part of Farcaster Node
 ///
@@ -34,10 +51,34 @@ pub struct Opts {
     )]
     pub network: Network,
 
+    /// Name of the syncer instance in the syncers configuration, the default syncer of the
+    /// network if absent
+    #[clap(long)]
+    pub instance: Option<String>,
+
     /// Electrum server to use for Bitcoin syncers
     #[clap(long)]
     pub electrum_server: Option<String>,
 
+    /// Bitcoin Core node RPC to use for Bitcoin syncers, takes precedence over the Electrum
+    /// server when set. The node must run with `-txindex`, the syncer refuses to start
+    /// otherwise
+    #[clap(long)]
+    pub bitcoin_rpc: Option<String>,
+
+    /// Path to the cookie file to connect to the Bitcoin Core node
+    #[clap(long)]
+    pub bitcoin_cookie_path: Option<String>,
+
+    /// RPC user to connect to the Bitcoin Core node
+    #[clap(long)]
+    pub bitcoin_rpc_user: Option<String>,
+
+    /// RPC password to connect to the Bitcoin Core node, preferably passed in the environment
+    /// so it is not visible in the process list
+    #[clap(long, env = "FARCASTER_BITCOIN_RPC_PASS", hide_env_values = true)]
+    pub bitcoin_rpc_pass: Option<String>,
+
     /// Monero daemon to use for Monero syncers
     #[clap(long)]
     pub monero_daemon: Option<String>,
@@ -53,10 +94,143 @@ pub struct Opts {
     /// Wallet directory use by the monero-wallet-rpc
     #[clap(long)]
     pub monero_wallet_dir_path: Option<String>,
+
+    /// Replay the scripted events of the scenario file instead of querying a blockchain
+    /// backend, for tests only
+    #[clap(long)]
+    pub mock_scenario: Option<String>,
+
+    /// Interval, in milliseconds, between two polls of the blockchain backend while a watched
+    /// item is about to change state, the shortest interval of the adaptive polling
+    #[clap(long, default_value_t = DEFAULT_POLL_INTERVAL_MS)]
+    pub poll_interval: u64,
+
+    /// Upper bound, in milliseconds, of the interval between two polls of the blockchain backend
+    /// while no watched item is about to change state, default to 10000 or to the poll interval
+    /// if greater. Set to the poll interval to poll at a fixed interval.
+    #[clap(long)]
+    pub max_poll_interval: Option<u64>,
+
+    /// Number of confirmations before a received Monero output can be spent, for networks not
+    /// following the standard unlock depth
+    #[clap(long, default_value = "10")]
+    pub monero_unlock_depth: u32,
+
+    /// Number of attempts of a failing backend call before reporting the failure
+    #[clap(long, default_value = "4")]
+    pub retry_max_attempts: u32,
+
+    /// Delay, in milliseconds, before retrying a failed backend call, doubled after every
+    /// failed attempt
+    #[clap(long, default_value = "500")]
+    pub retry_backoff: u64,
+
+    /// Upper bound, in milliseconds, of the delay between two attempts of a backend call
+    #[clap(long, default_value = "10000")]
+    pub retry_max_backoff: u64,
+
+    /// Ratio of the estimated fee rate below which a watched unconfirmed Bitcoin transaction is
+    /// reported as likely stuck
+    #[clap(long, default_value = "0.5")]
+    pub fee_stale_ratio: f64,
+
+    /// Transport of the bridge between the syncer runtime and its synclet, `inproc`, `ipc`
+    /// or `tcp`
+    #[clap(long, default_value = "inproc")]
+    pub bridge_transport: BusTransport,
+
+    /// ZMQ socket types of the bridge between the syncer runtime and its synclet, `pair` or
+    /// `push`; `push` reconnects the synclet over the ipc and tcp transports
+    #[clap(long, default_value = "pair")]
+    pub bridge_socket: BridgeSocket,
+}
+
+/// Return the validated bounds of the adaptive interval between two polls of the backend, given
+/// in milliseconds as `--poll-interval` and `--max-poll-interval`
+pub fn adaptive_poll(
+    poll_interval: u64,
+    max_poll_interval: Option<u64>,
+) -> Result<AdaptivePoll, Error> {
+    if poll_interval == 0 {
+        return Err(Error::Farcaster(
+            "--poll-interval must be greater than zero".to_string(),
+        ));
+    }
+    if poll_interval < LOW_POLL_INTERVAL_MS {
+        warn!(
+            "Polling the backend every {}ms, the backend may rate-limit the syncer",
+            poll_interval
+        );
+    }
+    let max_interval =
+        max_poll_interval.unwrap_or_else(|| DEFAULT_MAX_POLL_INTERVAL_MS.max(poll_interval));
+    if max_interval < poll_interval {
+        return Err(Error::Farcaster(
+            "--max-poll-interval must not be lower than --poll-interval".to_string(),
+        ));
+    }
+    Ok(AdaptivePoll::new(
+        Duration::from_millis(poll_interval),
+        Duration::from_millis(max_interval),
+    ))
 }
 
 impl Opts {
     pub fn process(&mut self) {
         self.shared.process();
     }
+
+    /// Return the validated bounds of the adaptive interval between two polls of the backend
+    pub fn adaptive_poll(&self) -> Result<AdaptivePoll, Error> {
+        adaptive_poll(self.poll_interval, self.max_poll_interval)
+    }
+
+    /// Return the endpoint the bridge between the syncer runtime and its synclet binds to. A tcp
+    /// bridge binds to an ephemeral port of the loopback interface, the synclet connects to the
+    /// endpoint actually bound.
+    pub fn bridge_endpoint(&self) -> String {
+        match self.bridge_transport {
+            BusTransport::Inproc => "inproc://syncerdbridge".to_string(),
+            BusTransport::Ipc => format!(
+                "ipc://{}/syncerd-bridge-{}-{}{}",
+                self.shared.data_dir.to_string_lossy(),
+                self.blockchain,
+                self.network,
+                self.instance
+                    .as_ref()
+                    .map_or(String::new(), |name| format!("-{}", name))
+            ),
+            BusTransport::Tcp => "tcp://127.0.0.1:*".to_string(),
+        }
+    }
+
+    /// Return the validated retry policy of the backend calls
+    pub fn retry_policy(&self) -> Result<RetryPolicy, Error> {
+        if self.retry_max_attempts == 0 {
+            return Err(Error::Farcaster(
+                "--retry-max-attempts must be greater than zero".to_string(),
+            ));
+        }
+        if self.retry_max_backoff < self.retry_backoff {
+            return Err(Error::Farcaster(
+                "--retry-max-backoff must not be lower than --retry-backoff".to_string(),
+            ));
+        }
+        Ok(RetryPolicy {
+            max_attempts: self.retry_max_attempts,
+            initial_backoff: Duration::from_millis(self.retry_backoff),
+            max_backoff: Duration::from_millis(self.retry_max_backoff),
+        })
+    }
+
+    /// Return the validated ratio of the estimated fee rate below which a transaction is stale
+    pub fn fee_stale_ratio(&self) -> Result<f64, Error> {
+        if self.fee_stale_ratio > 0.0 && self.fee_stale_ratio <= 1.0 {
+            Ok(self.fee_stale_ratio)
+        } else {
+            Err(Error::Farcaster(
+                "--fee-stale-ratio must be greater than zero and at most one".to_string(),
+            ))
+        }
+    }
 }
@@ -0,0 +1,100 @@
+// Copyright 2020-2022 Farcaster Devs & LNP/BP Standards Association
+//
+// Use of this source code is governed by an MIT-style
+// license that can be found in the LICENSE file or at
+// https://opensource.org/licenses/MIT.
+
+use std::time::Duration;
+
+/// Whether a watched item is about to change state, deciding how soon the synclets poll their
+/// backend again, see [`SyncerState::poll_activity`](super::syncer_state::SyncerState::poll_activity)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PollActivity {
+    /// No watched item is expected to change state soon, or nothing is watched
+    Idle,
+    /// A watched item is expected to change state soon, e.g. a watched transaction is in the
+    /// mempool or one confirmation away from its required depth
+    Imminent,
+}
+
+/// Interval between two polls of a backend, adapted to the activity of the watched items within
+/// configurable bounds.
+///
+/// The heuristic favors latency when it matters and saves backend calls otherwise:
+/// - while a state change is imminent the backend is polled at the minimum interval, so the
+///   event is emitted as soon as the backend reports it;
+/// - once idle the interval grows by half on every poll up to the maximum interval. The growth
+///   is gradual so a burst of activity, e.g. the transactions of a swap following each other, is
+///   still followed closely, while a long quiet period costs few backend calls;
+/// - the interval falls back to the minimum as soon as a state change is imminent again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AdaptivePoll {
+    /// Interval used while a state change is imminent
+    pub min_interval: Duration,
+    /// Upper bound of the interval while idle
+    pub max_interval: Duration,
+    interval: Duration,
+}
+
+impl AdaptivePoll {
+    pub fn new(min_interval: Duration, max_interval: Duration) -> Self {
+        AdaptivePoll {
+            min_interval,
+            max_interval,
+            interval: min_interval,
+        }
+    }
+
+    /// Replace the bounds of the interval, e.g. when the configuration is reloaded, the current
+    /// interval is brought within the new bounds
+    pub fn set_bounds(&mut self, min_interval: Duration, max_interval: Duration) {
+        self.min_interval = min_interval;
+        self.max_interval = max_interval;
+        self.interval = self.interval.max(min_interval).min(max_interval);
+    }
+
+    /// Interval to wait before the next poll given the current activity of the watched items
+    pub fn next(&mut self, activity: PollActivity) -> Duration {
+        self.interval = match activity {
+            PollActivity::Imminent => self.min_interval,
+            PollActivity::Idle => (self.interval + self.interval / 2).min(self.max_interval),
+        };
+        self.interval
+    }
+}
+
+#[test]
+fn adaptive_poll_backs_off_when_idle_and_tightens_near_events() {
+    let mut poll = AdaptivePoll::new(Duration::from_millis(1000), Duration::from_millis(3000));
+    assert_eq!(poll.next(PollActivity::Idle), Duration::from_millis(1500));
+    assert_eq!(poll.next(PollActivity::Idle), Duration::from_millis(2250));
+    assert_eq!(poll.next(PollActivity::Idle), Duration::from_millis(3000));
+    assert_eq!(poll.next(PollActivity::Idle), Duration::from_millis(3000));
+    assert_eq!(
+        poll.next(PollActivity::Imminent),
+        Duration::from_millis(1000)
+    );
+    assert_eq!(poll.next(PollActivity::Idle), Duration::from_millis(1500));
+
+    // equal bounds poll at a fixed interval
+    let mut poll = AdaptivePoll::new(Duration::from_millis(1000), Duration::from_millis(1000));
+    assert_eq!(poll.next(PollActivity::Idle), Duration::from_millis(1000));
+    assert_eq!(
+        poll.next(PollActivity::Imminent),
+        Duration::from_millis(1000)
+    );
+}
+
+#[test]
+fn adaptive_poll_follows_new_bounds() {
+    let mut poll = AdaptivePoll::new(Duration::from_millis(1000), Duration::from_millis(3000));
+    assert_eq!(poll.next(PollActivity::Idle), Duration::from_millis(1500));
+    poll.set_bounds(Duration::from_millis(200), Duration::from_millis(1000));
+    assert_eq!(poll.next(PollActivity::Idle), Duration::from_millis(1000));
+    assert_eq!(
+        poll.next(PollActivity::Imminent),
+        Duration::from_millis(200)
+    );
+    poll.set_bounds(Duration::from_millis(500), Duration::from_millis(5000));
+    assert_eq!(poll.next(PollActivity::Idle), Duration::from_millis(750));
+}
@@ -0,0 +1,127 @@
+// Copyright 2020-2022 Farcaster Devs & LNP/BP Standards Association
+//
+// Use of this source code is governed by an MIT-style
+// license that can be found in the LICENSE file or at
+// https://opensource.org/licenses/MIT.
+
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
+/// Retry policy of the syncers' one-shot backend calls. Failed calls are retried with an
+/// exponential backoff, so brief backend restarts or rate limits do not surface as task
+/// failures, until the maximum number of attempts is reached. The backoff sleeps the calling
+/// task, the syncers retry each request in a task of its own to keep serving the others.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Number of attempts before giving up, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled after every failed attempt
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay between two attempts
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Delay before the next attempt once the given attempt, starting at one, failed
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+
+    /// Run the blocking backend call until it succeeds or the attempts are exhausted, returning
+    /// the last error. The first failure is reported as a warning, the backend connectivity is
+    /// likely degraded.
+    pub async fn retry<T, E, F>(&self, what: &str, mut call: F) -> Result<T, E>
+    where
+        E: Display,
+        F: FnMut() -> Result<T, E>,
+    {
+        self.retry_async(what, || std::future::ready(call())).await
+    }
+
+    /// Run the asynchronous backend call until it succeeds or the attempts are exhausted,
+    /// returning the last error
+    pub async fn retry_async<T, E, F, Fut>(&self, what: &str, mut call: F) -> Result<T, E>
+    where
+        E: Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match call().await {
+                Ok(res) => {
+                    if attempt > 1 {
+                        info!("{} succeeded after {} attempts", what, attempt);
+                    }
+                    return Ok(res);
+                }
+                Err(err) if attempt >= self.max_attempts => return Err(err),
+                Err(err) => {
+                    let backoff = self.backoff(attempt);
+                    if attempt == 1 {
+                        warn!(
+                            "{} failed, backend connectivity may be degraded, retrying in {:?}: {}",
+                            what, backoff, err
+                        );
+                    } else {
+                        debug!(
+                            "{} failed ({}/{}), retrying in {:?}: {}",
+                            what, attempt, self.max_attempts, backoff, err
+                        );
+                    }
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn retry_backoff_is_exponential_and_bounded() {
+    let policy = RetryPolicy {
+        max_attempts: 10,
+        initial_backoff: Duration::from_millis(500),
+        max_backoff: Duration::from_secs(3),
+    };
+    assert_eq!(policy.backoff(1), Duration::from_millis(500));
+    assert_eq!(policy.backoff(2), Duration::from_millis(1000));
+    assert_eq!(policy.backoff(3), Duration::from_millis(2000));
+    assert_eq!(policy.backoff(4), Duration::from_secs(3));
+    assert_eq!(policy.backoff(40), Duration::from_secs(3));
+}
+
+#[tokio::test]
+async fn retry_stops_after_max_attempts() {
+    let policy = RetryPolicy {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(2),
+    };
+    let mut attempts = 0;
+    let res: Result<(), String> = policy
+        .retry("failing call", || {
+            attempts += 1;
+            Err("unreachable backend".to_string())
+        })
+        .await;
+    assert_eq!(res, Err("unreachable backend".to_string()));
+    assert_eq!(attempts, 3);
+
+    let mut attempts = 0;
+    let res = policy
+        .retry("flaky call", || {
+            attempts += 1;
+            if attempts < 2 {
+                Err("unreachable backend".to_string())
+            } else {
+                Ok(attempts)
+            }
+        })
+        .await;
+    assert_eq!(res, Ok(2));
+}
@@ -6,17 +6,18 @@
 
 use crate::bus::{
     ctl::CtlMsg,
-    info::{InfoMsg, SyncerInfo},
-    sync::SyncMsg,
+    info::{InfoMsg, SyncerHealth, SyncerInfo},
+    sync::{BridgeEvent, SyncMsg},
     BusMsg, ServiceBus,
 };
 use crate::service::Endpoints;
 use crate::syncerd::bitcoin_syncer::BitcoinSyncer;
+use crate::syncerd::mock_syncer::{MockSyncer, Scenario};
 use crate::syncerd::monero_syncer::MoneroSyncer;
 use crate::syncerd::opts::Opts;
 use crate::syncerd::*;
 use crate::CtlServer;
-use crate::{Error, LogStyle, Service, ServiceConfig, ServiceId};
+use crate::{Error, LogStyle, Service, ServiceConfig, ServiceId, SyncerInstance};
 
 use std::collections::HashSet;
 use std::sync::mpsc::Receiver;
@@ -28,12 +29,20 @@ use microservices::esb::{self, Handler};
 use microservices::ZMQ_CONTEXT;
 use strict_encoding::{StrictDecode, StrictEncode};
 
+/// Maximum time a terminating syncer waits for in-flight broadcasts before exiting
+const TERMINATE_DRAIN_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Maximum time a terminating syncer waits for its synclet to stop before exiting
+const TERMINATE_ACK_TIMEOUT: Duration = Duration::from_secs(10);
+
 pub trait Synclet {
+    /// Start the synclet, `terminated` is signaled once it stopped after a terminate task
     fn run(
         &mut self,
         rx: Receiver<SyncerdTask>,
         tx: zmq::Socket,
         syncer_address: Vec<u8>,
+        terminated: Sender<()>,
         opts: &Opts,
         network: Network,
     ) -> Result<(), Error>;
@@ -55,29 +64,55 @@ pub fn run(config: ServiceConfig, opts: Opts) -> Result<(), Error> {
     let blockchain = opts.blockchain;
     let network = opts.network;
 
-    info!("Creating new {} ({}) syncer", &blockchain, &network);
+    let instance = SyncerInstance(opts.instance.clone());
+
+    info!(
+        "Creating new {} ({}) syncer{}",
+        &blockchain, &network, &instance
+    );
+    // fail early on invalid poll intervals or retry policy, synclets read them once running
+    opts.adaptive_poll()?;
+    opts.retry_policy()?;
     let (tx, rx): (Sender<SyncerdTask>, Receiver<SyncerdTask>) = std::sync::mpsc::channel();
+    let (terminated_tx, terminated_rx): (Sender<()>, Receiver<()>) = std::sync::mpsc::channel();
 
-    let tx_event = ZMQ_CONTEXT.socket(zmq::PAIR)?;
-    let rx_event = ZMQ_CONTEXT.socket(zmq::PAIR)?;
-    rx_event.bind("inproc://syncerdbridge")?;
-    tx_event.connect("inproc://syncerdbridge")?;
+    let (rx_type, tx_type) = opts.bridge_socket.socket_types();
+    let tx_event = ZMQ_CONTEXT.socket(tx_type)?;
+    let rx_event = ZMQ_CONTEXT.socket(rx_type)?;
+    rx_event.bind(&opts.bridge_endpoint())?;
+    // the endpoint bound resolves the ephemeral port of a tcp bridge
+    let bridge_endpoint = rx_event
+        .get_last_endpoint()?
+        .map_err(|_| Error::Farcaster("Invalid syncer bridge endpoint".to_string()))?;
+    debug!("Syncer bridge bound to {}", bridge_endpoint);
+    tx_event.connect(&bridge_endpoint)?;
 
-    let syncer: Box<dyn Synclet> = match blockchain {
-        Blockchain::Monero => Box::new(MoneroSyncer::new()),
-        Blockchain::Bitcoin => Box::new(BitcoinSyncer::new()),
+    let syncer: Box<dyn Synclet> = match (&opts.mock_scenario, blockchain) {
+        (Some(scenario), _) => {
+            warn!("Replaying the mock scenario {}", scenario);
+            Box::new(MockSyncer::with(Scenario::load(scenario)?))
+        }
+        (None, Blockchain::Monero) => Box::new(MoneroSyncer::new()),
+        (None, Blockchain::Bitcoin) => Box::new(BitcoinSyncer::new()),
     };
 
     let mut runtime = Runtime {
-        identity: ServiceId::Syncer(blockchain, network),
+        identity: ServiceId::Syncer(blockchain, network, instance),
         started: SystemTime::now(),
         tasks: none!(),
         syncer,
         tx,
+        terminated: terminated_rx,
+        terminating: None,
     };
-    runtime
-        .syncer
-        .run(rx, tx_event, runtime.identity().into(), &opts, network)?;
+    runtime.syncer.run(
+        rx,
+        tx_event,
+        runtime.identity().into(),
+        terminated_tx,
+        &opts,
+        network,
+    )?;
     let mut service = Service::service(config, runtime)?;
     service.add_bridge_service_bus(rx_event)?;
     service.run_loop()?;
@@ -90,6 +125,10 @@ pub struct Runtime {
     started: SystemTime,
     tasks: HashSet<SyncerdTask>,
     tx: Sender<SyncerdTask>,
+    /// Signaled by the synclet once it stopped
+    terminated: Receiver<()>,
+    /// Set on terminate, new tasks are refused while in-flight broadcasts drain
+    terminating: Option<SystemTime>,
 }
 
 impl CtlServer for Runtime {}
@@ -134,7 +173,7 @@ impl esb::Handler<ServiceBus> for Runtime {
 impl Runtime {
     fn handle_ctl(
         &mut self,
-        _endpoints: &mut Endpoints,
+        endpoints: &mut Endpoints,
         source: ServiceId,
         request: CtlMsg,
     ) -> Result<(), Error> {
@@ -148,10 +187,71 @@ impl Runtime {
                 );
             }
 
+            (CtlMsg::Heartbeat, ServiceId::Farcasterd) => {
+                endpoints.send_to(
+                    ServiceBus::Ctl,
+                    self.identity(),
+                    ServiceId::Farcasterd,
+                    BusMsg::Ctl(CtlMsg::Heartbeat),
+                )?;
+                // bound the wait of a terminating syncer on broadcasts never answered
+                if let Some(terminating) = self.terminating {
+                    if terminating.elapsed().unwrap_or_default() > TERMINATE_DRAIN_TIMEOUT {
+                        warn!(
+                            "Terminating {} with {} broadcast(s) still in flight",
+                            self.identity(),
+                            self.pending_broadcasts()
+                        );
+                        self.terminate();
+                    }
+                }
+            }
+
             (CtlMsg::Terminate, ServiceId::Farcasterd) => {
-                // terminate all runtimes
                 info!("Received terminate on {}", self.identity());
-                std::process::exit(0);
+                self.terminating = Some(SystemTime::now());
+                let pending = self.pending_broadcasts();
+                if pending > 0 {
+                    info!(
+                        "Waiting for {} broadcast(s) to complete before terminating",
+                        pending
+                    );
+                } else {
+                    self.terminate();
+                }
+            }
+
+            (CtlMsg::TerminateSwap(swap_id), ServiceId::Farcasterd) => {
+                let swap_service = ServiceId::Swap(*swap_id);
+                info!(
+                    "Dropping the tasks of {} on {}",
+                    swap_service.bright_blue_italic(),
+                    self.identity()
+                );
+                // abort on behalf of the swap, the synclet only aborts tasks of the same source
+                self.handle_sync(
+                    endpoints,
+                    swap_service,
+                    SyncMsg::Task(Task::Abort(Abort {
+                        task_target: TaskTarget::AllTasks,
+                        respond: false,
+                    })),
+                )?;
+            }
+
+            (CtlMsg::SetPollIntervals(intervals), ServiceId::Farcasterd) => {
+                // the synclet applies the new bounds to its polling loops
+                let task = SyncerdTask {
+                    task: Task::SetPollIntervals(intervals.clone()),
+                    source: source.clone(),
+                };
+                if let Err(e) = self.tx.send(task) {
+                    error!("Failed to send task with error: {}", e.to_string());
+                }
+            }
+
+            (CtlMsg::SetLogFilter(filter), ServiceId::Farcasterd) => {
+                crate::logger::set_filter(filter.as_deref());
             }
 
             (req, source) => {
@@ -202,6 +302,17 @@ impl Runtime {
                 )?;
             }
 
+            InfoMsg::GetSyncerHealth => {
+                // query the backend through the synclet, the status is answered on the bridge
+                let task = SyncerdTask {
+                    task: Task::GetBackendStatus(GetBackendStatus { id: TaskId(0) }),
+                    source,
+                };
+                if let Err(e) = self.tx.send(task) {
+                    error!("Failed to send task with error: {}", e.to_string());
+                }
+            }
+
             req => {
                 warn!("Ignoring request: {}", req.err());
             }
@@ -212,14 +323,74 @@ impl Runtime {
 
     fn handle_sync(
         &mut self,
-        _endpoints: &mut Endpoints,
+        endpoints: &mut Endpoints,
         source: ServiceId,
         request: SyncMsg,
     ) -> Result<(), Error> {
         match request {
+            SyncMsg::Task(task) if self.terminating.is_some() => {
+                warn!("Terminating, refusing task {} from {}", task, source);
+            }
+
+            SyncMsg::Task(task)
+                if task.key().is_some()
+                    && self.tasks.contains(&SyncerdTask {
+                        task: task.clone(),
+                        source: source.clone(),
+                    }) =>
+            {
+                debug!("Watch task {} from {} is already running", task, source);
+            }
+
+            SyncMsg::Task(Task::WatchAddresses(WatchAddresses { addresses })) => {
+                // each address of the batch is tracked and aborted as a watch task of its own,
+                // the synclet polls all the watched addresses together
+                for task in addresses {
+                    self.handle_sync(
+                        endpoints,
+                        source.clone(),
+                        SyncMsg::Task(Task::WatchAddress(task)),
+                    )?;
+                }
+            }
+
             SyncMsg::Task(task) => {
+                if let Some(key) = task.key() {
+                    // a watch re-issued by the source, e.g. on resumption, supersedes the running
+                    // one instead of duplicating its events
+                    let superseded: Vec<TaskId> = self
+                        .tasks
+                        .iter()
+                        .filter(|t| t.source == source && t.task.key().as_ref() == Some(&key))
+                        .filter_map(|t| t.task.id())
+                        .collect();
+                    for id in superseded {
+                        debug!(
+                            "Task {} from {} supersedes the running task {}",
+                            task, source, id
+                        );
+                        self.handle_sync(
+                            endpoints,
+                            source.clone(),
+                            SyncMsg::Task(Task::Abort(Abort {
+                                task_target: TaskTarget::TaskId(id),
+                                respond: false,
+                            })),
+                        )?;
+                    }
+                }
                 let t = SyncerdTask { task, source };
-                self.tasks.insert(t.clone());
+                if let Task::Abort(Abort { task_target, .. }) = &t.task {
+                    // forget the aborted tasks of the source, the synclet stops watching them
+                    self.tasks.retain(|task| match task_target {
+                        TaskTarget::AllTasks => task.source != t.source,
+                        TaskTarget::TaskId(id) => {
+                            task.source != t.source || task.task.id() != Some(*id)
+                        }
+                    });
+                } else {
+                    self.tasks.insert(t.clone());
+                }
                 match self.tx.send(t) {
                     Ok(()) => trace!("Task successfully sent to syncer runtime"),
                     Err(e) => error!("Failed to send task with error: {}", e.to_string()),
@@ -234,6 +405,55 @@ impl Runtime {
         Ok(())
     }
 
+    /// Whether the event answers a task its source still has running. Events of the tasks of
+    /// terminated swaps or of aborted tasks are stale, the source forgot them. Abort
+    /// confirmations are always forwarded, the source is waiting for them.
+    fn is_live_task_event(&self, bridge_event: &BridgeEvent) -> bool {
+        if matches!(bridge_event.event, Event::TaskAborted(_)) {
+            return true;
+        }
+        let task_ids = bridge_event.event.task_ids();
+        self.tasks.iter().any(|t| {
+            t.source == bridge_event.source
+                && t.task.id().map_or(false, |id| task_ids.contains(&id))
+        })
+    }
+
+    /// Broadcasts sent to the backend without a result yet, broadcasts delayed until a
+    /// future height are not waited for
+    fn pending_broadcasts(&self) -> usize {
+        self.tasks
+            .iter()
+            .filter(|t| {
+                matches!(
+                    &t.task,
+                    Task::BroadcastTransaction(BroadcastTransaction {
+                        broadcast_after_height: None,
+                        ..
+                    })
+                )
+            })
+            .count()
+    }
+
+    fn terminate(&mut self) {
+        info!("Terminating {}", self.identity());
+        // let the synclet stop its backend connections before exiting
+        if let Err(err) = self.tx.send(SyncerdTask {
+            task: Task::Terminate,
+            source: self.identity(),
+        }) {
+            debug!("Synclet already stopped: {}", err);
+        } else if let Err(err) = self.terminated.recv_timeout(TERMINATE_ACK_TIMEOUT) {
+            warn!(
+                "Synclet of {} did not acknowledge termination: {}",
+                self.identity(),
+                err
+            );
+        }
+        std::process::exit(0);
+    }
+
     fn handle_bridge(
         &mut self,
         endpoints: &mut Endpoints,
@@ -242,13 +462,63 @@ impl Runtime {
     ) -> Result<(), Error> {
         debug!("Syncerd BRIDGE RPC request: {}", request);
         match request {
+            SyncMsg::BridgeEvent(BridgeEvent {
+                event: Event::BackendStatus(status),
+                source,
+            }) => {
+                let lag = status
+                    .tip_height
+                    .map(|tip_height| tip_height.saturating_sub(status.synced_height));
+                self.send_client_info(
+                    endpoints,
+                    source,
+                    InfoMsg::SyncerHealth(SyncerHealth {
+                        syncer: self.identity().to_string(),
+                        connected: status.tip_height.is_some(),
+                        synced_height: status.synced_height,
+                        tip_height: status.tip_height,
+                        lag,
+                        error: status.error,
+                    }),
+                )?;
+            }
+
+            SyncMsg::BridgeEvent(syncerd_bridge_event)
+                if !self.is_live_task_event(&syncerd_bridge_event) =>
+            {
+                debug!(
+                    "Dropping event for task(s) {:?}, {} has no such live task anymore",
+                    syncerd_bridge_event.event.task_ids(),
+                    syncerd_bridge_event.source
+                );
+            }
+
             SyncMsg::BridgeEvent(syncerd_bridge_event) => {
+                trace!(
+                    "Forwarding event for task(s) {:?} to {}",
+                    syncerd_bridge_event.event.task_ids(),
+                    syncerd_bridge_event.source
+                );
+                // a broadcast is completed once its result is known, either way
+                if let Event::TransactionBroadcasted(TransactionBroadcasted { id, .. })
+                | Event::BroadcastFailure(BroadcastFailure { id, .. }) =
+                    syncerd_bridge_event.event
+                {
+                    let source = &syncerd_bridge_event.source;
+                    self.tasks.retain(|t| {
+                        !(t.source == *source
+                            && matches!(&t.task, Task::BroadcastTransaction(task) if task.id == id))
+                    });
+                }
                 endpoints.send_to(
                     ServiceBus::Sync,
                     self.identity(),
                     syncerd_bridge_event.source,
                     BusMsg::Sync(SyncMsg::Event(syncerd_bridge_event.event)),
                 )?;
+                if self.terminating.is_some() && self.pending_broadcasts() == 0 {
+                    self.terminate();
+                }
             }
 
             _ => {
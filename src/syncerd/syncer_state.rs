@@ -5,21 +5,38 @@
 // https://opensource.org/licenses/MIT.
 
 use crate::bus::sync::BridgeEvent;
+use crate::error::{Error, SyncerError};
+use crate::syncerd::polling::{AdaptivePoll, PollActivity};
 use crate::syncerd::{TaskId, TaskTarget};
-use crate::Error;
 use crate::ServiceId;
 use farcaster_core::blockchain::Blockchain;
 use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 use tokio::sync::mpsc::Sender as TokioSender;
 
 use crate::service::LogStyle;
 use crate::syncerd::*;
 use hex;
 
-pub type BalanceServiceIdPair = (GetAddressBalance, ServiceId);
+pub type BalanceServiceIdPair = (BalanceTask, ServiceId);
 pub type TransactionServiceIdPair = (BroadcastTransaction, ServiceId);
 pub type GetTxServiceIdPair = (GetTx, ServiceId);
 
+/// Balance tasks handled by the balance fetcher of the syncers, off the task receiver since
+/// scanning a Monero wallet may take a while
+#[derive(Debug, Clone)]
+pub enum BalanceTask {
+    Address(GetAddressBalance),
+    Descriptor(GetBalance),
+}
+
+/// Target block interval of Bitcoin, in seconds, used until an average is measured
+pub const BITCOIN_BLOCK_INTERVAL: u64 = 600;
+/// Target block interval of Monero, in seconds
+pub const MONERO_BLOCK_INTERVAL: u64 = 120;
+/// Number of confirmations before a received Monero output can be spent
+pub const MONERO_UNLOCK_DEPTH: u32 = 10;
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Hash, Display)]
 #[display(Debug)]
 pub struct InternalId(u32);
@@ -47,11 +64,26 @@ pub struct SyncerState {
     tasks_sources: HashMap<InternalId, ServiceId>,
     watch_height: HashMap<InternalId, WatchHeight>,
     watch_fee_estimation: HashMap<InternalId, WatchEstimateFee>,
+    watch_refund_time: HashMap<InternalId, WatchEstimatedRefundTime>,
+    // average number of seconds between two blocks
+    block_interval: u64,
+    // number of confirmations before a received Monero output can be spent
+    unlock_depth: u32,
+    // bounds of the poll interval set at runtime, replacing those the polling loops started with
+    poll_bounds: Option<AdaptivePoll>,
     lifetimes: HashMap<u64, HashSet<InternalId>>,
     pub addresses: HashMap<InternalId, AddressTransactions>,
+    pub monero_addresses: HashMap<InternalId, WatchedMoneroAddress>,
     pub transactions: HashMap<InternalId, WatchedTransaction>,
     pub unseen_transactions: HashSet<InternalId>,
+    pub outpoints: HashMap<InternalId, WatchedOutpoint>,
+    pub outpoint_watches: HashMap<InternalId, OutpointWatch>,
+    pub mempool_entries: HashMap<InternalId, WatchMempoolEntry>,
     pub sweep_addresses: HashMap<InternalId, SweepAddress>,
+    // last reported number of confirmations before the funds to sweep are spendable
+    locked_sweeps: HashMap<InternalId, u32>,
+    // last recommended fee rate a watched transaction with a stale fee was reported against
+    stale_fees: HashMap<InternalId, u64>,
     tx_event: TokioSender<BridgeEvent>,
     task_count: TaskCounter,
     pub subscribed_addresses: HashSet<AddressAddendum>,
@@ -65,6 +97,29 @@ pub struct WatchedTransaction {
     pub transaction_confirmations: TransactionConfirmations,
 }
 
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct WatchedOutpoint {
+    pub task: WatchOutpointSpend,
+    /// The last seen transaction spending the outpoint
+    pub spending_txid: Option<bitcoin::Txid>,
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct OutpointWatch {
+    pub task: WatchOutpoint,
+    /// The last reported status of the outpoint
+    pub status: OutpointStatus,
+}
+
+/// Status of a bitcoin outpoint as reported by a backend. Confirmations are zero in the mempool
+/// and none if the transaction is neither in the mempool nor in the chain.
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub struct OutpointStatus {
+    pub created_confirmations: Option<u32>,
+    pub spending_txid: Option<bitcoin::Txid>,
+    pub spent_confirmations: Option<u32>,
+}
+
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct AddressTransactions {
     pub task: WatchAddress,
@@ -73,6 +128,24 @@ pub struct AddressTransactions {
     pub initial_check_done: bool,
 }
 
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct WatchedMoneroAddress {
+    pub task: WatchMoneroAddress,
+    /// The height up to which the address has been scanned so far.
+    pub scanned_height: u64,
+    // received transactions with the last reported number of confirmations
+    known_txs: HashMap<monero::Hash, Option<u32>>,
+}
+
+/// An incoming transaction for a view-key watched Monero address, with the
+/// amounts of all the outputs to the address summed up.
+#[derive(Debug, Clone, PartialEq, Hash, Eq)]
+pub struct MoneroReceivedTx {
+    pub tx_id: monero::Hash,
+    pub amount: u64,
+    pub confirmations: Option<u32>,
+}
+
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
 pub struct AddressTx {
     pub amount: u64,
@@ -93,11 +166,24 @@ impl SyncerState {
             tasks_sources: HashMap::new(),
             watch_height: HashMap::new(),
             watch_fee_estimation: HashMap::new(),
+            watch_refund_time: HashMap::new(),
+            block_interval: match blockchain {
+                Blockchain::Bitcoin => BITCOIN_BLOCK_INTERVAL,
+                Blockchain::Monero => MONERO_BLOCK_INTERVAL,
+            },
+            unlock_depth: MONERO_UNLOCK_DEPTH,
+            poll_bounds: None,
             lifetimes: HashMap::new(),
             addresses: HashMap::new(),
+            monero_addresses: HashMap::new(),
             transactions: HashMap::new(),
             unseen_transactions: HashSet::new(),
+            outpoints: HashMap::new(),
+            outpoint_watches: HashMap::new(),
+            mempool_entries: HashMap::new(),
             sweep_addresses: HashMap::new(),
+            locked_sweeps: HashMap::new(),
+            stale_fees: HashMap::new(),
             tx_event,
             task_count: TaskCounter(0),
             blockchain,
@@ -111,6 +197,79 @@ impl SyncerState {
         self.block_height
     }
 
+    pub fn block_hash(&self) -> &[u8] {
+        &self.block_hash
+    }
+
+    /// Set the average block interval, in seconds, used to estimate the time left before
+    /// reaching a target height
+    pub fn set_block_interval(&mut self, block_interval: u64) {
+        if block_interval > 0 {
+            self.block_interval = block_interval;
+        }
+    }
+
+    pub fn unlock_depth(&self) -> u32 {
+        self.unlock_depth
+    }
+
+    /// Set the number of confirmations before a received Monero output can be spent, for
+    /// networks not following the standard unlock depth
+    pub fn set_unlock_depth(&mut self, unlock_depth: u32) {
+        if unlock_depth > 0 {
+            self.unlock_depth = unlock_depth;
+        }
+    }
+
+    /// Set the bounds of the poll interval of the polling loops, e.g. when the configuration is
+    /// reloaded
+    pub fn set_poll_bounds(&mut self, poll: AdaptivePoll) {
+        self.poll_bounds = Some(poll);
+    }
+
+    /// Interval a polling loop waits before its next poll, within the latest bounds set
+    pub fn next_poll(&self, poll: &mut AdaptivePoll) -> Duration {
+        if let Some(bounds) = self.poll_bounds {
+            poll.set_bounds(bounds.min_interval, bounds.max_interval);
+        }
+        poll.next(self.poll_activity())
+    }
+
+    /// Whether a watched item is about to change state: a watched transaction or outpoint is in
+    /// the mempool or one confirmation away from its required depth, the finality of a
+    /// transaction or the confirmation bound of an outpoint, or a transaction is waiting to be
+    /// broadcast. Transactions and mempool entries not seen yet and addresses without activity
+    /// are idle, they may stay so for a long time.
+    pub fn poll_activity(&self) -> PollActivity {
+        let near_bound = |confirmations: Option<u32>, bound: u32| {
+            confirmations.map_or(false, |confs| {
+                confs == 0 || (confs + 1 >= bound && confs < bound)
+            })
+        };
+        let imminent = !self.pending_broadcasts.is_empty()
+            || self.transactions.values().any(|watched_tx| {
+                near_bound(
+                    watched_tx.transaction_confirmations.confirmations,
+                    watched_tx
+                        .task
+                        .finality
+                        .min(watched_tx.task.confirmation_bound),
+                )
+            })
+            || self.outpoint_watches.values().any(|watch| {
+                watch.status.created_confirmations == Some(0)
+                    || near_bound(
+                        watch.status.spent_confirmations,
+                        watch.task.confirmation_bound,
+                    )
+            });
+        if imminent {
+            PollActivity::Imminent
+        } else {
+            PollActivity::Idle
+        }
+    }
+
     pub async fn abort(
         &mut self,
         task_task_id_or_all_tasks: TaskTarget,
@@ -151,6 +310,33 @@ impl SyncerState {
                 .collect(),
         );
 
+        // check view-key watched monero addresses tasks
+        let ids: Vec<(InternalId, TaskId)> = self
+            .monero_addresses
+            .iter()
+            .filter_map(|(id, watched_address)| {
+                if task_id.is_none() || watched_address.task.id == task_id.unwrap() {
+                    Some((*id, watched_address.task.id))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        aborted_ids.append(
+            &mut ids
+                .iter()
+                .filter_map(|(internal_id, found_task_id)| {
+                    if let Some(source_id) = self.tasks_sources.get(internal_id) {
+                        if *source_id == source {
+                            self.remove_monero_address(internal_id);
+                            return Some(*found_task_id);
+                        }
+                    }
+                    None
+                })
+                .collect(),
+        );
+
         // check transactions tasks
         let ids: Vec<(InternalId, TaskId)> = self
             .transactions
@@ -178,6 +364,87 @@ impl SyncerState {
                 .collect(),
         );
 
+        // check outpoint spend tasks
+        let ids: Vec<(InternalId, TaskId)> = self
+            .outpoints
+            .iter()
+            .filter_map(|(id, watched_outpoint)| {
+                if task_id.is_none() || watched_outpoint.task.id == task_id.unwrap() {
+                    Some((*id, watched_outpoint.task.id))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        aborted_ids.append(
+            &mut ids
+                .iter()
+                .filter_map(|(internal_id, found_task_id)| {
+                    if let Some(source_id) = self.tasks_sources.get(internal_id) {
+                        if *source_id == source {
+                            self.remove_outpoint(internal_id);
+                            return Some(*found_task_id);
+                        }
+                    }
+                    None
+                })
+                .collect(),
+        );
+
+        // check outpoint tasks
+        let ids: Vec<(InternalId, TaskId)> = self
+            .outpoint_watches
+            .iter()
+            .filter_map(|(id, outpoint_watch)| {
+                if task_id.is_none() || outpoint_watch.task.id == task_id.unwrap() {
+                    Some((*id, outpoint_watch.task.id))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        aborted_ids.append(
+            &mut ids
+                .iter()
+                .filter_map(|(internal_id, found_task_id)| {
+                    if let Some(source_id) = self.tasks_sources.get(internal_id) {
+                        if *source_id == source {
+                            self.remove_outpoint_watch(internal_id);
+                            return Some(*found_task_id);
+                        }
+                    }
+                    None
+                })
+                .collect(),
+        );
+
+        // check mempool entry tasks
+        let ids: Vec<(InternalId, TaskId)> = self
+            .mempool_entries
+            .iter()
+            .filter_map(|(id, mempool_entry)| {
+                if task_id.is_none() || mempool_entry.id == task_id.unwrap() {
+                    Some((*id, mempool_entry.id))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        aborted_ids.append(
+            &mut ids
+                .iter()
+                .filter_map(|(internal_id, found_task_id)| {
+                    if let Some(source_id) = self.tasks_sources.get(internal_id) {
+                        if *source_id == source {
+                            self.remove_mempool_entry(internal_id);
+                            return Some(*found_task_id);
+                        }
+                    }
+                    None
+                })
+                .collect(),
+        );
+
         // check height tasks
         let ids: Vec<(InternalId, TaskId)> = self
             .watch_height
@@ -205,6 +472,34 @@ impl SyncerState {
                 .collect(),
         );
 
+        // check estimated refund time tasks
+        let ids: Vec<(InternalId, TaskId)> = self
+            .watch_refund_time
+            .iter()
+            .filter_map(|(id, watch_refund_time)| {
+                if task_id.is_none() || watch_refund_time.id == task_id.unwrap() {
+                    Some((*id, watch_refund_time.id))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        aborted_ids.append(
+            &mut ids
+                .iter()
+                .filter_map(|(internal_id, found_task_id)| {
+                    if let Some(source_id) = self.tasks_sources.get(internal_id) {
+                        if *source_id == source {
+                            self.watch_refund_time.remove(internal_id);
+                            self.tasks_sources.remove(internal_id);
+                            return Some(*found_task_id);
+                        }
+                    }
+                    None
+                })
+                .collect(),
+        );
+
         // check sweep address tasks
         let ids: Vec<(InternalId, TaskId)> = self
             .sweep_addresses
@@ -239,8 +534,8 @@ impl SyncerState {
                     Event::TaskAborted(TaskAborted {
                         id: vec![],
                         error: Some(format!(
-                            "abort failed, task from source {} not found",
-                            source
+                            "abort failed, {}",
+                            SyncerError::TaskNotFound(source.clone())
                         )),
                     }),
                     source.clone(),
@@ -295,6 +590,45 @@ impl SyncerState {
         }
     }
 
+    pub async fn watch_estimated_refund_time(
+        &mut self,
+        task: WatchEstimatedRefundTime,
+        source: ServiceId,
+    ) {
+        // the target is already reached, report it and drop the task
+        if self.block_height != 0 && self.block_height >= task.target_height {
+            send_event(
+                &self.tx_event,
+                &mut vec![(self.estimated_time_remaining(&task), source)],
+            )
+            .await;
+            return;
+        }
+        // increment the count to use it as a unique internal id
+        self.task_count.increment();
+        self.watch_refund_time
+            .insert(self.task_count.into(), task.clone());
+        self.tasks_sources
+            .insert(self.task_count.into(), source.clone());
+
+        if self.block_height != 0 {
+            send_event(
+                &self.tx_event,
+                &mut vec![(self.estimated_time_remaining(&task), source)],
+            )
+            .await;
+        }
+    }
+
+    fn estimated_time_remaining(&self, task: &WatchEstimatedRefundTime) -> Event {
+        let blocks = task.target_height.saturating_sub(self.block_height);
+        Event::EstimatedTimeRemaining(EstimatedTimeRemaining {
+            id: task.id,
+            seconds: blocks * self.block_interval,
+            blocks,
+        })
+    }
+
     pub fn watch_address(&mut self, task: WatchAddress, source: ServiceId) {
         // increment the count to use it as a unique internal id
         self.task_count.increment();
@@ -312,6 +646,24 @@ impl SyncerState {
         self.addresses.insert(self.task_count.into(), address_txs);
     }
 
+    pub fn watch_monero_address(&mut self, task: WatchMoneroAddress, source: ServiceId) {
+        // increment the count to use it as a unique internal id
+        self.task_count.increment();
+        if let Err(e) = self.add_lifetime(task.lifetime, self.task_count.into()) {
+            error!("{}", e);
+            return;
+        };
+        self.tasks_sources.insert(self.task_count.into(), source);
+        self.monero_addresses.insert(
+            self.task_count.into(),
+            WatchedMoneroAddress {
+                scanned_height: task.from_height,
+                task,
+                known_txs: none!(),
+            },
+        );
+    }
+
     pub fn address_subscribed(&mut self, id: InternalId) {
         let address = self.addresses.get_mut(&id);
         if let Some(address) = address {
@@ -350,6 +702,54 @@ impl SyncerState {
         self.unseen_transactions.insert(self.task_count.into());
     }
 
+    pub fn watch_outpoint_spend(&mut self, task: WatchOutpointSpend, source: ServiceId) {
+        // increment the count to use it as a unique internal id
+        self.task_count.increment();
+
+        if let Err(e) = self.add_lifetime(task.lifetime, self.task_count.into()) {
+            error!("{}", e);
+            return;
+        };
+        self.tasks_sources.insert(self.task_count.into(), source);
+        self.outpoints.insert(
+            self.task_count.into(),
+            WatchedOutpoint {
+                task,
+                spending_txid: None,
+            },
+        );
+    }
+
+    pub fn watch_outpoint(&mut self, task: WatchOutpoint, source: ServiceId) {
+        // increment the count to use it as a unique internal id
+        self.task_count.increment();
+
+        if let Err(e) = self.add_lifetime(task.lifetime, self.task_count.into()) {
+            error!("{}", e);
+            return;
+        };
+        self.tasks_sources.insert(self.task_count.into(), source);
+        self.outpoint_watches.insert(
+            self.task_count.into(),
+            OutpointWatch {
+                task,
+                status: OutpointStatus::default(),
+            },
+        );
+    }
+
+    pub fn watch_mempool_entry(&mut self, task: WatchMempoolEntry, source: ServiceId) {
+        // increment the count to use it as a unique internal id
+        self.task_count.increment();
+
+        if let Err(e) = self.add_lifetime(task.lifetime, self.task_count.into()) {
+            error!("{}", e);
+            return;
+        };
+        self.tasks_sources.insert(self.task_count.into(), source);
+        self.mempool_entries.insert(self.task_count.into(), task);
+    }
+
     pub async fn estimate_fee(&mut self, task: WatchEstimateFee, source: ServiceId) {
         // increment the count to use it as a unique internal id
         self.task_count.increment();
@@ -406,6 +806,24 @@ impl SyncerState {
                 )
                 .await;
             }
+
+            // Emit the estimated time remaining, dropping the tasks that reached their target
+            let mut events = vec![];
+            let mut reached = vec![];
+            for (id, task) in self.watch_refund_time.iter() {
+                events.push((
+                    self.estimated_time_remaining(task),
+                    self.tasks_sources.get(id).unwrap().clone(),
+                ));
+                if self.block_height >= task.target_height {
+                    reached.push(*id);
+                }
+            }
+            for id in reached {
+                self.watch_refund_time.remove(&id);
+                self.tasks_sources.remove(&id);
+            }
+            send_event(&self.tx_event, &mut events).await;
             true
         } else {
             false
@@ -535,6 +953,62 @@ impl SyncerState {
         send_event(&self.tx_event, &mut events).await;
     }
 
+    /// Record the result of scanning a view-key watched Monero address up to
+    /// `scanned_height`. A `MoneroReceived` event is emitted for every
+    /// transaction paying at least the task's minimum amount that was not seen
+    /// before, or whose number of confirmations changed.
+    pub async fn change_monero_address(
+        &mut self,
+        id: InternalId,
+        scanned_height: u64,
+        received: Vec<MoneroReceivedTx>,
+    ) {
+        self.drop_lifetimes();
+        let mut events: Vec<(Event, ServiceId)> = Vec::new();
+        if let Some(watched_address) = self.monero_addresses.get_mut(&id) {
+            if scanned_height > watched_address.scanned_height {
+                watched_address.scanned_height = scanned_height;
+            }
+            let source = self
+                .tasks_sources
+                .get(&id)
+                .cloned()
+                .expect("task source missing");
+            let min_amount = watched_address.task.min_amount.as_pico();
+            for tx in received.into_iter().filter(|tx| tx.amount >= min_amount) {
+                let known_confirmations = watched_address.known_txs.get(&tx.tx_id).cloned();
+                if known_confirmations == Some(tx.confirmations) {
+                    continue;
+                }
+                debug!("new received tx seen: {}", tx.tx_id);
+                watched_address.known_txs.insert(tx.tx_id, tx.confirmations);
+                events.push((
+                    Event::MoneroReceived(MoneroReceived {
+                        id: watched_address.task.id,
+                        txid: tx.tx_id,
+                        amount: tx.amount,
+                        confirmations: tx.confirmations,
+                    }),
+                    source.clone(),
+                ));
+                if unlocked(
+                    known_confirmations.flatten(),
+                    tx.confirmations,
+                    self.unlock_depth,
+                ) {
+                    events.push((
+                        Event::MoneroUnlocked(MoneroUnlocked {
+                            id: watched_address.task.id,
+                            txid: tx.tx_id,
+                        }),
+                        source.clone(),
+                    ));
+                }
+            }
+        }
+        send_event(&self.tx_event, &mut events).await;
+    }
+
     pub async fn change_transaction(
         &mut self,
         tx_id: Txid,
@@ -544,6 +1018,11 @@ impl SyncerState {
     ) {
         self.drop_lifetimes();
         let mut events: Vec<(Event, ServiceId)> = Vec::new();
+        // only Monero outputs are locked once confirmed
+        let unlock_depth = match self.blockchain {
+            Blockchain::Monero => Some(self.unlock_depth),
+            Blockchain::Bitcoin => None,
+        };
         inner(
             &mut self.transactions,
             &mut self.unseen_transactions,
@@ -553,6 +1032,7 @@ impl SyncerState {
             block_hash,
             confirmations,
             tx,
+            unlock_depth,
         );
 
         #[allow(clippy::too_many_arguments)]
@@ -565,6 +1045,7 @@ impl SyncerState {
             block_hash: Option<Vec<u8>>,
             confirmations: Option<u32>,
             tx: Vec<u8>,
+            unlock_depth: Option<u32>,
         ) {
             let block = match block_hash {
                 Some(bh) => bh,
@@ -601,6 +1082,21 @@ impl SyncerState {
                             Event::TransactionConfirmations(tx_confs.clone()),
                             tasks_sources.get(id).unwrap().clone(),
                         ));
+                        if let (Some(unlock_depth), Txid::Monero(txid)) = (unlock_depth, tx_id) {
+                            if unlocked(
+                                watched_tx.transaction_confirmations.confirmations,
+                                confirmations,
+                                unlock_depth,
+                            ) {
+                                events.push((
+                                    Event::MoneroUnlocked(MoneroUnlocked {
+                                        id: watched_tx.task.id,
+                                        txid,
+                                    }),
+                                    tasks_sources.get(id).unwrap().clone(),
+                                ));
+                            }
+                        }
                         tx_confs
                     } else {
                         watched_tx.transaction_confirmations.clone()
@@ -624,20 +1120,150 @@ impl SyncerState {
         send_event(&self.tx_event, &mut events).await;
     }
 
-    pub async fn success_sweep(&mut self, id: &InternalId, txids: Vec<Txid>) {
-        if let Some(sweep_address) = self.sweep_addresses.get(id) {
-            send_event(
-                &self.tx_event,
-                &mut vec![(
-                    Event::SweepSuccess(SweepSuccess {
-                        id: sweep_address.id,
-                        txids,
-                    }),
-                    self.tasks_sources
-                        .get(id)
-                        .cloned()
-                        .expect("task source missing"),
-                )],
+    /// Update the transaction spending a watched outpoint, emitting an event if the outpoint
+    /// is now spent by a transaction other than the expected one
+    pub async fn change_outpoint_spend(
+        &mut self,
+        id: &InternalId,
+        spending_txid: Option<bitcoin::Txid>,
+    ) {
+        self.drop_lifetimes();
+        let watched_outpoint = match self.outpoints.get_mut(id) {
+            Some(watched_outpoint) => watched_outpoint,
+            None => return,
+        };
+        if watched_outpoint.spending_txid == spending_txid {
+            return;
+        }
+        watched_outpoint.spending_txid = spending_txid;
+        if let Some(spending_txid) = spending_txid {
+            if Some(spending_txid) != watched_outpoint.task.expected_spending_txid {
+                let event = Event::OutpointSpent(OutpointSpent {
+                    id: watched_outpoint.task.id,
+                    outpoint: watched_outpoint.task.outpoint,
+                    spending_txid,
+                });
+                let source = self
+                    .tasks_sources
+                    .get(id)
+                    .cloned()
+                    .expect("task source missing");
+                send_event(&self.tx_event, &mut vec![(event, source)]).await;
+            }
+        }
+    }
+
+    /// Update the transaction of a watched mempool entry with its confirmations, none if it is
+    /// not seen yet. On its first sighting the transaction is watched in place of the entry,
+    /// under the same internal id so the lifetime and the source of the task carry over: a
+    /// [`SeenInMempool`] event is emitted if it is in the mempool, then its confirmations are
+    /// reported as for a [`WatchTransaction`] task.
+    pub async fn change_mempool_entry(
+        &mut self,
+        id: &InternalId,
+        txid: Option<bitcoin::Txid>,
+        confirmations: Option<u32>,
+    ) {
+        self.drop_lifetimes();
+        let (txid, confirmations) = match (txid, confirmations) {
+            (Some(txid), Some(confirmations)) => (txid, confirmations),
+            _ => return,
+        };
+        let task = match self.mempool_entries.remove(id) {
+            Some(task) => task,
+            None => return,
+        };
+        if confirmations == 0 {
+            let source = self
+                .tasks_sources
+                .get(id)
+                .cloned()
+                .expect("task source missing");
+            let event = Event::SeenInMempool(SeenInMempool { id: task.id, txid });
+            send_event(&self.tx_event, &mut vec![(event, source)]).await;
+        }
+        self.transactions.insert(
+            *id,
+            WatchedTransaction {
+                task: WatchTransaction {
+                    id: task.id,
+                    lifetime: task.lifetime,
+                    hash: txid.into(),
+                    confirmation_bound: task.confirmation_bound,
+                    finality: task.finality,
+                },
+                transaction_confirmations: TransactionConfirmations {
+                    id: task.id,
+                    block: none!(),
+                    confirmations: None,
+                    tx: vec![],
+                },
+            },
+        );
+        self.unseen_transactions.insert(*id);
+    }
+
+    /// Update the status of a watched outpoint, emitting an event for each side of its lifecycle
+    /// that changed. The status is re-evaluated by the syncer as the chain tip moves, a reorg
+    /// reverting the creation or the spend is reported like any other change.
+    pub async fn change_outpoint_status(&mut self, id: &InternalId, status: OutpointStatus) {
+        self.drop_lifetimes();
+        let outpoint_watch = match self.outpoint_watches.get_mut(id) {
+            Some(outpoint_watch) => outpoint_watch,
+            None => return,
+        };
+        let source = self
+            .tasks_sources
+            .get(id)
+            .cloned()
+            .expect("task source missing");
+        let mut events = vec![];
+        if status.created_confirmations != outpoint_watch.status.created_confirmations {
+            events.push((
+                Event::OutpointCreationConfirmations(OutpointCreationConfirmations {
+                    id: outpoint_watch.task.id,
+                    outpoint: outpoint_watch.task.outpoint,
+                    confirmations: status.created_confirmations,
+                }),
+                source.clone(),
+            ));
+        }
+        if status.spending_txid != outpoint_watch.status.spending_txid
+            || status.spent_confirmations != outpoint_watch.status.spent_confirmations
+        {
+            events.push((
+                Event::OutpointSpendConfirmations(OutpointSpendConfirmations {
+                    id: outpoint_watch.task.id,
+                    outpoint: outpoint_watch.task.outpoint,
+                    spending_txid: status.spending_txid,
+                    confirmations: status.spent_confirmations,
+                }),
+                source,
+            ));
+        }
+        // prune the task once the spend has reached its confirmation bound
+        if status.spent_confirmations >= Some(outpoint_watch.task.confirmation_bound) {
+            self.remove_outpoint_watch(id);
+        } else {
+            outpoint_watch.status = status;
+        }
+        send_event(&self.tx_event, &mut events).await;
+    }
+
+    pub async fn success_sweep(&mut self, id: &InternalId, txids: Vec<Txid>) {
+        if let Some(sweep_address) = self.sweep_addresses.get(id) {
+            send_event(
+                &self.tx_event,
+                &mut vec![(
+                    Event::SweepSuccess(SweepSuccess {
+                        id: sweep_address.id,
+                        txids,
+                    }),
+                    self.tasks_sources
+                        .get(id)
+                        .cloned()
+                        .expect("task source missing"),
+                )],
             )
             .await;
             if let Some(ids) = self.lifetimes.get_mut(&sweep_address.lifetime) {
@@ -647,10 +1273,40 @@ impl SyncerState {
                 }
             }
             self.sweep_addresses.remove(id);
+            self.locked_sweeps.remove(id);
             self.tasks_sources.remove(id);
         }
     }
 
+    pub async fn sweep_locked(&mut self, id: &InternalId, confirmations_remaining: u32) {
+        if let Some(sweep_address) = self.sweep_addresses.get(id) {
+            // only report when the number of remaining confirmations changed
+            if self.locked_sweeps.insert(*id, confirmations_remaining)
+                == Some(confirmations_remaining)
+            {
+                return;
+            }
+            send_event(
+                &self.tx_event,
+                &mut vec![(
+                    Event::SweepLocked(SweepLocked {
+                        id: sweep_address.id,
+                        confirmations_remaining,
+                    }),
+                    self.tasks_sources
+                        .get(id)
+                        .cloned()
+                        .expect("task source missing"),
+                )],
+            )
+            .await;
+            // a one-shot sweep ends here, the requester has to try again once unlocked
+            if !sweep_address.retry {
+                self.remove_sweep_address(id);
+            }
+        }
+    }
+
     pub async fn fee_estimated(&mut self, fee_estimations: FeeEstimations) {
         // Emit fee estimation events
         if self.fee_estimation.as_ref() != Some(&fee_estimations) {
@@ -673,6 +1329,77 @@ impl SyncerState {
         self.drop_lifetimes();
     }
 
+    /// Watched Bitcoin transactions seen unconfirmed, with the fee rate of the current
+    /// estimation, in satoshis per kvB, they are expected to pay. None until a fee estimation is
+    /// available.
+    pub fn unconfirmed_transactions(&self) -> Vec<(InternalId, bitcoin::Transaction, u64)> {
+        let recommended = match self.fee_estimation {
+            Some(FeeEstimations::BitcoinFeeEstimation {
+                high_priority_sats_per_kvbyte,
+                ..
+            }) => high_priority_sats_per_kvbyte,
+            None => return vec![],
+        };
+        self.transactions
+            .iter()
+            .filter(|(_, watched_tx)| watched_tx.transaction_confirmations.confirmations == Some(0))
+            .filter_map(|(id, watched_tx)| {
+                let tx: Vec<u8> = watched_tx
+                    .transaction_confirmations
+                    .tx
+                    .iter()
+                    .flatten()
+                    .copied()
+                    .collect();
+                bitcoin::consensus::deserialize(&tx)
+                    .ok()
+                    .map(|tx| (*id, tx, recommended))
+            })
+            .collect()
+    }
+
+    /// Report a watched transaction paying a fee rate below the stale ratio of the recommended
+    /// one, once per recommended fee rate
+    pub async fn transaction_fee_checked(
+        &mut self,
+        id: &InternalId,
+        tx_fee_rate: u64,
+        recommended: u64,
+        stale_ratio: f64,
+    ) {
+        let watched_tx = match self.transactions.get(id) {
+            Some(watched_tx) => watched_tx,
+            None => return,
+        };
+        if !fee_is_stale(tx_fee_rate, recommended, stale_ratio) {
+            self.stale_fees.remove(id);
+            return;
+        }
+        if self.stale_fees.insert(*id, recommended) == Some(recommended) {
+            return;
+        }
+        let txid = match watched_tx.task.hash {
+            Txid::Bitcoin(txid) => txid,
+            Txid::Monero(_) => return,
+        };
+        send_event(
+            &self.tx_event,
+            &mut vec![(
+                Event::TransactionFeeStale(TransactionFeeStale {
+                    id: watched_tx.task.id,
+                    txid,
+                    tx_fee_rate,
+                    recommended,
+                }),
+                self.tasks_sources
+                    .get(id)
+                    .cloned()
+                    .expect("task source missing"),
+            )],
+        )
+        .await;
+    }
+
     pub async fn fail_sweep(&mut self, id: &InternalId) {
         if let Some(sweep_address) = self.sweep_addresses.get(id) {
             send_event(
@@ -699,6 +1426,7 @@ impl SyncerState {
                 }
             }
             self.sweep_addresses.remove(id);
+            self.locked_sweeps.remove(id);
             self.tasks_sources.remove(id);
         }
     }
@@ -732,11 +1460,17 @@ impl SyncerState {
         if let Some(tasks) = self.lifetimes.remove(&lifetime) {
             for task in &tasks {
                 self.addresses.remove(task);
+                self.monero_addresses.remove(task);
                 self.transactions.remove(task);
                 self.unseen_transactions.remove(task);
+                self.stale_fees.remove(task);
+                self.outpoints.remove(task);
+                self.outpoint_watches.remove(task);
+                self.mempool_entries.remove(task);
                 self.watch_height.remove(task);
                 self.watch_fee_estimation.remove(task);
                 self.sweep_addresses.remove(task);
+                self.locked_sweeps.remove(task);
                 self.tasks_sources.remove(task);
             }
         } else {
@@ -755,6 +1489,46 @@ impl SyncerState {
         }
         self.transactions.remove(id);
         self.unseen_transactions.remove(id);
+        self.stale_fees.remove(id);
+        self.tasks_sources.remove(id);
+    }
+
+    fn remove_outpoint(&mut self, id: &InternalId) {
+        if let Some(watched_outpoint) = self.outpoints.get(id) {
+            if let Some(ids) = self.lifetimes.get_mut(&watched_outpoint.task.lifetime) {
+                ids.remove(id);
+                if ids.is_empty() {
+                    self.lifetimes.remove(&watched_outpoint.task.lifetime);
+                }
+            }
+        }
+        self.outpoints.remove(id);
+        self.tasks_sources.remove(id);
+    }
+
+    fn remove_outpoint_watch(&mut self, id: &InternalId) {
+        if let Some(outpoint_watch) = self.outpoint_watches.get(id) {
+            if let Some(ids) = self.lifetimes.get_mut(&outpoint_watch.task.lifetime) {
+                ids.remove(id);
+                if ids.is_empty() {
+                    self.lifetimes.remove(&outpoint_watch.task.lifetime);
+                }
+            }
+        }
+        self.outpoint_watches.remove(id);
+        self.tasks_sources.remove(id);
+    }
+
+    fn remove_mempool_entry(&mut self, id: &InternalId) {
+        if let Some(mempool_entry) = self.mempool_entries.get(id) {
+            if let Some(ids) = self.lifetimes.get_mut(&mempool_entry.lifetime) {
+                ids.remove(id);
+                if ids.is_empty() {
+                    self.lifetimes.remove(&mempool_entry.lifetime);
+                }
+            }
+        }
+        self.mempool_entries.remove(id);
         self.tasks_sources.remove(id);
     }
 
@@ -771,6 +1545,19 @@ impl SyncerState {
         self.tasks_sources.remove(id);
     }
 
+    fn remove_monero_address(&mut self, id: &InternalId) {
+        if let Some(watched_address) = self.monero_addresses.get(id) {
+            if let Some(ids) = self.lifetimes.get_mut(&watched_address.task.lifetime) {
+                ids.remove(id);
+                if ids.is_empty() {
+                    self.lifetimes.remove(&watched_address.task.lifetime);
+                }
+            }
+        }
+        self.monero_addresses.remove(id);
+        self.tasks_sources.remove(id);
+    }
+
     fn remove_height(&mut self, id: &InternalId) {
         if let Some(watch_height) = self.watch_height.get(id) {
             if let Some(ids) = self.lifetimes.get_mut(&watch_height.lifetime) {
@@ -794,6 +1581,7 @@ impl SyncerState {
             }
         }
         self.sweep_addresses.remove(id);
+        self.locked_sweeps.remove(id);
         self.tasks_sources.remove(id);
     }
 
@@ -804,6 +1592,114 @@ impl SyncerState {
         )
         .await;
     }
+
+    pub async fn fee_histogram_result(
+        &mut self,
+        id: TaskId,
+        buckets: Result<Vec<FeeHistogramBucket>, String>,
+        source: ServiceId,
+    ) {
+        let event = match buckets {
+            Ok(buckets) => Event::FeeHistogram(FeeHistogram { id, buckets }),
+            Err(error) => Event::TaskAborted(TaskAborted {
+                id: vec![id],
+                error: Some(error),
+            }),
+        };
+        send_event(&self.tx_event, &mut vec![(event, source)]).await;
+    }
+
+    pub async fn backend_status(
+        &mut self,
+        id: TaskId,
+        tip_height: Result<u64, String>,
+        source: ServiceId,
+    ) {
+        let (tip_height, error) = match tip_height {
+            Ok(tip_height) => (Some(tip_height), None),
+            Err(error) => (None, Some(error)),
+        };
+        send_event(
+            &self.tx_event,
+            &mut vec![(
+                Event::BackendStatus(BackendStatus {
+                    id,
+                    synced_height: self.block_height,
+                    tip_height,
+                    error,
+                }),
+                source,
+            )],
+        )
+        .await;
+    }
+
+    pub async fn fee_bump_result(
+        &mut self,
+        id: TaskId,
+        fee_bump: Result<FeeBumped, String>,
+        source: ServiceId,
+    ) {
+        let event = match fee_bump {
+            Ok(fee_bumped) => Event::FeeBumped(fee_bumped),
+            Err(error) => Event::TaskAborted(TaskAborted {
+                id: vec![id],
+                error: Some(error),
+            }),
+        };
+        send_event(&self.tx_event, &mut vec![(event, source)]).await;
+    }
+
+    pub async fn block_data_result(
+        &mut self,
+        id: TaskId,
+        block: Result<BlockData, String>,
+        source: ServiceId,
+    ) {
+        let event = match block {
+            Ok(block) => Event::BlockData(block),
+            Err(error) => Event::TaskAborted(TaskAborted {
+                id: vec![id],
+                error: Some(error),
+            }),
+        };
+        send_event(&self.tx_event, &mut vec![(event, source)]).await;
+    }
+
+    pub async fn unsupported_task(&mut self, id: TaskId, source: ServiceId) {
+        send_event(&self.tx_event, &mut vec![(Event::Unsupported(id), source)]).await;
+    }
+}
+
+/// Whether a transaction paying the fee rate is likely stuck, i.e. its fee rate is below the
+/// given ratio of the recommended one
+pub fn fee_is_stale(tx_fee_rate: u64, recommended: u64, stale_ratio: f64) -> bool {
+    (tx_fee_rate as f64) < recommended as f64 * stale_ratio
+}
+
+/// Event answering a [`GetBalance`] task from the confirmed and unconfirmed balance, an abort if
+/// the balance could not be retrieved
+pub fn balance_event(id: TaskId, balance: Result<(u64, u64), String>) -> Event {
+    match balance {
+        Ok((confirmed, unconfirmed)) => Event::Balance(Balance {
+            id,
+            confirmed,
+            unconfirmed,
+        }),
+        Err(error) => Event::TaskAborted(TaskAborted {
+            id: vec![id],
+            error: Some(error),
+        }),
+    }
+}
+
+/// Whether a Monero output got unlocked, i.e. its confirmations reached the unlock depth
+fn unlocked(
+    previous_confirmations: Option<u32>,
+    confirmations: Option<u32>,
+    unlock_depth: u32,
+) -> bool {
+    previous_confirmations.unwrap_or(0) < unlock_depth && confirmations.unwrap_or(0) >= unlock_depth
 }
 
 pub async fn send_event(tx_event: &TokioSender<BridgeEvent>, events: &mut Vec<(Event, ServiceId)>) {
@@ -817,6 +1713,7 @@ pub async fn send_event(tx_event: &TokioSender<BridgeEvent>, events: &mut Vec<(E
 
 #[tokio::test]
 async fn syncer_state_transaction() {
+    use crate::SyncerInstance;
     use farcaster_core::blockchain::Network;
 
     use tokio::sync::mpsc::Receiver as TokioReceiver;
@@ -829,18 +1726,24 @@ async fn syncer_state_transaction() {
         lifetime: 1,
         hash: monero::Hash::new(vec![0]).into(),
         confirmation_bound: 4,
+        finality: 4,
     };
     let transaction_task_two = WatchTransaction {
         id: TaskId(0),
         lifetime: 3,
         hash: monero::Hash::new(vec![1]).into(),
         confirmation_bound: 4,
+        finality: 4,
     };
     let height_task = WatchHeight {
         id: TaskId(0),
         lifetime: 4,
     };
-    let source1 = ServiceId::Syncer(Blockchain::Bitcoin, Network::Mainnet);
+    let source1 = ServiceId::Syncer(
+        Blockchain::Bitcoin,
+        Network::Mainnet,
+        SyncerInstance::DEFAULT,
+    );
 
     state.watch_transaction(transaction_task_one.clone(), source1.clone());
     state
@@ -921,7 +1824,11 @@ async fn syncer_state_transaction() {
     assert_eq!(state.unseen_transactions.len(), 2);
     assert!(event_rx.try_recv().is_ok());
 
-    let source2 = ServiceId::Syncer(Blockchain::Monero, Network::Mainnet);
+    let source2 = ServiceId::Syncer(
+        Blockchain::Monero,
+        Network::Mainnet,
+        SyncerInstance::DEFAULT,
+    );
     state.watch_transaction(transaction_task_two.clone(), source2.clone());
     state
         .abort(TaskTarget::TaskId(TaskId(0)), source2.clone(), true)
@@ -940,8 +1847,305 @@ async fn syncer_state_transaction() {
     assert!(event_rx.try_recv().is_err());
 }
 
+#[tokio::test]
+async fn syncer_state_outpoint_spend() {
+    use crate::SyncerInstance;
+    use bitcoin::hashes::Hash;
+    use farcaster_core::blockchain::Network;
+
+    use tokio::sync::mpsc::Receiver as TokioReceiver;
+    let (event_tx, mut event_rx): (TokioSender<BridgeEvent>, TokioReceiver<BridgeEvent>) =
+        tokio::sync::mpsc::channel(120);
+    let mut state = SyncerState::new(event_tx.clone(), Blockchain::Bitcoin);
+
+    let expected_txid = bitcoin::Txid::from_slice(&[1; 32]).unwrap();
+    let conflicting_txid = bitcoin::Txid::from_slice(&[2; 32]).unwrap();
+    let outpoint_task = WatchOutpointSpend {
+        id: TaskId(0),
+        lifetime: 1,
+        outpoint: bitcoin::OutPoint {
+            txid: bitcoin::Txid::from_slice(&[0; 32]).unwrap(),
+            vout: 0,
+        },
+        expected_spending_txid: Some(expected_txid),
+    };
+    let source1 = ServiceId::Syncer(
+        Blockchain::Bitcoin,
+        Network::Mainnet,
+        SyncerInstance::DEFAULT,
+    );
+
+    state.watch_outpoint_spend(outpoint_task.clone(), source1.clone());
+    state
+        .abort(TaskTarget::TaskId(TaskId(0)), source1.clone(), true)
+        .await;
+    assert!(event_rx.try_recv().is_ok());
+    assert_eq!(state.outpoints.len(), 0);
+    assert_eq!(state.lifetimes.len(), 0);
+    assert_eq!(state.tasks_sources.len(), 0);
+
+    state.watch_outpoint_spend(outpoint_task.clone(), source1.clone());
+    assert_eq!(state.outpoints.len(), 1);
+    assert_eq!(state.lifetimes.len(), 1);
+    assert_eq!(state.tasks_sources.len(), 1);
+    let id = *state.outpoints.keys().next().unwrap();
+
+    // unspent, nothing to report
+    state.change_outpoint_spend(&id, None).await;
+    assert!(event_rx.try_recv().is_err());
+
+    // spent by the expected transaction, nothing to report
+    state.change_outpoint_spend(&id, Some(expected_txid)).await;
+    assert!(event_rx.try_recv().is_err());
+
+    // spent by a conflicting transaction, reported once
+    state
+        .change_outpoint_spend(&id, Some(conflicting_txid))
+        .await;
+    let event = event_rx.try_recv().unwrap();
+    assert_eq!(
+        event.event,
+        Event::OutpointSpent(OutpointSpent {
+            id: TaskId(0),
+            outpoint: outpoint_task.outpoint,
+            spending_txid: conflicting_txid,
+        })
+    );
+    state
+        .change_outpoint_spend(&id, Some(conflicting_txid))
+        .await;
+    assert!(event_rx.try_recv().is_err());
+
+    // the task is dropped once its lifetime expired
+    state.change_height(2, vec![0]).await;
+    assert_eq!(state.outpoints.len(), 0);
+    assert_eq!(state.lifetimes.len(), 0);
+    assert_eq!(state.tasks_sources.len(), 0);
+}
+
+#[tokio::test]
+async fn syncer_state_mempool_entry() {
+    use crate::SyncerInstance;
+    use bitcoin::hashes::Hash;
+    use farcaster_core::blockchain::Network;
+
+    use tokio::sync::mpsc::Receiver as TokioReceiver;
+    let (event_tx, mut event_rx): (TokioSender<BridgeEvent>, TokioReceiver<BridgeEvent>) =
+        tokio::sync::mpsc::channel(120);
+    let mut state = SyncerState::new(event_tx.clone(), Blockchain::Bitcoin);
+
+    let spending_txid = bitcoin::Txid::from_slice(&[1; 32]).unwrap();
+    let mempool_task = WatchMempoolEntry {
+        id: TaskId(0),
+        lifetime: 1,
+        txid_or_outpoint: TxidOrOutpoint::Outpoint(bitcoin::OutPoint {
+            txid: bitcoin::Txid::from_slice(&[0; 32]).unwrap(),
+            vout: 0,
+        }),
+        confirmation_bound: 2,
+        finality: 2,
+    };
+    let source1 = ServiceId::Syncer(
+        Blockchain::Bitcoin,
+        Network::Mainnet,
+        SyncerInstance::DEFAULT,
+    );
+
+    state.watch_mempool_entry(mempool_task.clone(), source1.clone());
+    state
+        .abort(TaskTarget::TaskId(TaskId(0)), source1.clone(), true)
+        .await;
+    assert!(event_rx.try_recv().is_ok());
+    assert_eq!(state.mempool_entries.len(), 0);
+    assert_eq!(state.lifetimes.len(), 0);
+    assert_eq!(state.tasks_sources.len(), 0);
+
+    state.watch_mempool_entry(mempool_task.clone(), source1.clone());
+    // an entry waiting for its transaction does not keep the polling at its fastest
+    assert_eq!(state.poll_activity(), PollActivity::Idle);
+    let id = *state.mempool_entries.keys().next().unwrap();
+
+    // not seen yet, nothing to report
+    state.change_mempool_entry(&id, None, None).await;
+    assert!(event_rx.try_recv().is_err());
+    assert_eq!(state.mempool_entries.len(), 1);
+
+    // seen in the mempool, reported once and watched as a transaction from then on
+    state
+        .change_mempool_entry(&id, Some(spending_txid), Some(0))
+        .await;
+    let event = event_rx.try_recv().unwrap();
+    assert_eq!(
+        event.event,
+        Event::SeenInMempool(SeenInMempool {
+            id: TaskId(0),
+            txid: spending_txid,
+        })
+    );
+    state
+        .change_mempool_entry(&id, Some(spending_txid), Some(0))
+        .await;
+    assert!(event_rx.try_recv().is_err());
+    assert_eq!(state.mempool_entries.len(), 0);
+    assert_eq!(state.transactions[&id].task.hash, spending_txid.into());
+    assert!(state.unseen_transactions.contains(&id));
+
+    // the confirmations follow as for a watched transaction
+    state
+        .change_transaction(spending_txid.into(), None, Some(0), vec![])
+        .await;
+    let event = event_rx.try_recv().unwrap();
+    assert!(matches!(
+        event.event,
+        Event::TransactionConfirmations(TransactionConfirmations {
+            id: TaskId(0),
+            confirmations: Some(0),
+            ..
+        })
+    ));
+
+    // a transaction confirmed before being seen in the mempool is not reported as such
+    let mined_task = WatchMempoolEntry {
+        id: TaskId(1),
+        txid_or_outpoint: TxidOrOutpoint::Txid(spending_txid),
+        ..mempool_task
+    };
+    state.watch_mempool_entry(mined_task, source1.clone());
+    let id = *state.mempool_entries.keys().next().unwrap();
+    state
+        .change_mempool_entry(&id, Some(spending_txid), Some(1))
+        .await;
+    assert!(event_rx.try_recv().is_err());
+    assert_eq!(state.mempool_entries.len(), 0);
+    assert_eq!(state.transactions.len(), 2);
+
+    // the tasks are dropped once their lifetime expired
+    state.change_height(2, vec![0]).await;
+    assert_eq!(state.transactions.len(), 0);
+    assert_eq!(state.lifetimes.len(), 0);
+    assert_eq!(state.tasks_sources.len(), 0);
+}
+
+#[tokio::test]
+async fn syncer_state_outpoint() {
+    use crate::SyncerInstance;
+    use bitcoin::hashes::Hash;
+    use farcaster_core::blockchain::Network;
+
+    use tokio::sync::mpsc::Receiver as TokioReceiver;
+    let (event_tx, mut event_rx): (TokioSender<BridgeEvent>, TokioReceiver<BridgeEvent>) =
+        tokio::sync::mpsc::channel(120);
+    let mut state = SyncerState::new(event_tx.clone(), Blockchain::Bitcoin);
+
+    let spending_txid = bitcoin::Txid::from_slice(&[1; 32]).unwrap();
+    let outpoint_task = WatchOutpoint {
+        id: TaskId(0),
+        lifetime: 10,
+        outpoint: bitcoin::OutPoint {
+            txid: bitcoin::Txid::from_slice(&[0; 32]).unwrap(),
+            vout: 0,
+        },
+        confirmation_bound: 2,
+    };
+    let source1 = ServiceId::Syncer(
+        Blockchain::Bitcoin,
+        Network::Mainnet,
+        SyncerInstance::DEFAULT,
+    );
+
+    state.watch_outpoint(outpoint_task.clone(), source1.clone());
+    state
+        .abort(TaskTarget::TaskId(TaskId(0)), source1.clone(), true)
+        .await;
+    assert!(event_rx.try_recv().is_ok());
+    assert_eq!(state.outpoint_watches.len(), 0);
+    assert_eq!(state.lifetimes.len(), 0);
+    assert_eq!(state.tasks_sources.len(), 0);
+
+    state.watch_outpoint(outpoint_task.clone(), source1.clone());
+    let id = *state.outpoint_watches.keys().next().unwrap();
+
+    // unseen, nothing to report
+    state
+        .change_outpoint_status(&id, OutpointStatus::default())
+        .await;
+    assert!(event_rx.try_recv().is_err());
+
+    // created in the mempool
+    let mut status = OutpointStatus {
+        created_confirmations: Some(0),
+        ..Default::default()
+    };
+    state.change_outpoint_status(&id, status.clone()).await;
+    assert_eq!(
+        event_rx.try_recv().unwrap().event,
+        Event::OutpointCreationConfirmations(OutpointCreationConfirmations {
+            id: TaskId(0),
+            outpoint: outpoint_task.outpoint,
+            confirmations: Some(0),
+        })
+    );
+    state.change_outpoint_status(&id, status.clone()).await;
+    assert!(event_rx.try_recv().is_err());
+
+    // confirmed and spent in the mempool
+    status = OutpointStatus {
+        created_confirmations: Some(1),
+        spending_txid: Some(spending_txid),
+        spent_confirmations: Some(0),
+    };
+    state.change_outpoint_status(&id, status.clone()).await;
+    assert!(matches!(
+        event_rx.try_recv().unwrap().event,
+        Event::OutpointCreationConfirmations(OutpointCreationConfirmations {
+            confirmations: Some(1),
+            ..
+        })
+    ));
+    assert_eq!(
+        event_rx.try_recv().unwrap().event,
+        Event::OutpointSpendConfirmations(OutpointSpendConfirmations {
+            id: TaskId(0),
+            outpoint: outpoint_task.outpoint,
+            spending_txid: Some(spending_txid),
+            confirmations: Some(0),
+        })
+    );
+
+    // a reorg reverts the spend
+    status = OutpointStatus {
+        created_confirmations: Some(1),
+        ..Default::default()
+    };
+    state.change_outpoint_status(&id, status.clone()).await;
+    assert_eq!(
+        event_rx.try_recv().unwrap().event,
+        Event::OutpointSpendConfirmations(OutpointSpendConfirmations {
+            id: TaskId(0),
+            outpoint: outpoint_task.outpoint,
+            spending_txid: None,
+            confirmations: None,
+        })
+    );
+    assert!(event_rx.try_recv().is_err());
+
+    // the task is dropped once the spend reached the confirmation bound
+    status = OutpointStatus {
+        created_confirmations: Some(3),
+        spending_txid: Some(spending_txid),
+        spent_confirmations: Some(2),
+    };
+    state.change_outpoint_status(&id, status).await;
+    assert!(event_rx.try_recv().is_ok());
+    assert!(event_rx.try_recv().is_ok());
+    assert_eq!(state.outpoint_watches.len(), 0);
+    assert_eq!(state.lifetimes.len(), 0);
+    assert_eq!(state.tasks_sources.len(), 0);
+}
+
 #[tokio::test]
 async fn syncer_state_addresses() {
+    use crate::SyncerInstance;
     use farcaster_core::blockchain::Network;
     use std::str::FromStr;
     use tokio::sync::mpsc::Receiver as TokioReceiver;
@@ -965,7 +2169,11 @@ async fn syncer_state_addresses() {
         include_tx: false,
         filter: TxFilter::All,
     };
-    let source1 = ServiceId::Syncer(Blockchain::Bitcoin, Network::Mainnet);
+    let source1 = ServiceId::Syncer(
+        Blockchain::Bitcoin,
+        Network::Mainnet,
+        SyncerInstance::DEFAULT,
+    );
 
     state.watch_address(address_task.clone(), source1.clone());
     state
@@ -1076,7 +2284,11 @@ async fn syncer_state_addresses() {
     assert!(event_rx.try_recv().is_ok());
     assert!(event_rx.try_recv().is_ok());
 
-    let source2 = ServiceId::Syncer(Blockchain::Monero, Network::Testnet);
+    let source2 = ServiceId::Syncer(
+        Blockchain::Monero,
+        Network::Testnet,
+        SyncerInstance::DEFAULT,
+    );
     state.watch_address(address_task_two.clone(), source2.clone());
     state
         .abort(TaskTarget::TaskId(TaskId(0)), source2.clone(), true)
@@ -1109,8 +2321,80 @@ async fn syncer_state_addresses() {
     assert!(event_rx.try_recv().is_ok());
 }
 
+#[tokio::test]
+async fn syncer_state_address_batch_abort() {
+    use crate::SyncerInstance;
+    use farcaster_core::blockchain::Network;
+    use std::str::FromStr;
+    use tokio::sync::mpsc::Receiver as TokioReceiver;
+
+    let (event_tx, mut event_rx): (TokioSender<BridgeEvent>, TokioReceiver<BridgeEvent>) =
+        tokio::sync::mpsc::channel(120);
+    let mut state = SyncerState::new(event_tx.clone(), Blockchain::Bitcoin);
+    let addendum_one = AddressAddendum::Bitcoin(BtcAddressAddendum {
+        address: bitcoin::Address::from_str("32BkaQeAVcd65Vn7pjEziohf5bCiryNQov").unwrap(),
+    });
+    let addendum_two = AddressAddendum::Bitcoin(BtcAddressAddendum {
+        address: bitcoin::Address::from_str("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2").unwrap(),
+    });
+    let batch = WatchAddresses {
+        addresses: vec![
+            WatchAddress {
+                id: TaskId(0),
+                lifetime: 1,
+                addendum: addendum_one.clone(),
+                include_tx: false,
+                filter: TxFilter::All,
+            },
+            WatchAddress {
+                id: TaskId(1),
+                lifetime: 1,
+                addendum: addendum_two.clone(),
+                include_tx: false,
+                filter: TxFilter::All,
+            },
+        ],
+    };
+    let source1 = ServiceId::Syncer(
+        Blockchain::Bitcoin,
+        Network::Mainnet,
+        SyncerInstance::DEFAULT,
+    );
+    for task in batch.addresses {
+        state.watch_address(task, source1.clone());
+    }
+    assert_eq!(state.addresses.len(), 2);
+
+    // aborting an address of the batch leaves the other one watched
+    state
+        .abort(TaskTarget::TaskId(TaskId(0)), source1.clone(), true)
+        .await;
+    assert!(event_rx.try_recv().is_ok());
+    assert_eq!(state.addresses.len(), 1);
+    assert_eq!(state.tasks_sources.len(), 1);
+
+    let address_tx = AddressTx {
+        amount: 1,
+        tx_id: monero::Hash::new(vec![0]).into(),
+        tx: vec![0],
+        incoming: true,
+    };
+    state
+        .change_address(addendum_one, create_set(vec![address_tx.clone()]))
+        .await;
+    assert!(event_rx.try_recv().is_err());
+    state
+        .change_address(addendum_two, create_set(vec![address_tx]))
+        .await;
+    match event_rx.try_recv().map(|bridge_event| bridge_event.event) {
+        Ok(Event::AddressTransaction(AddressTransaction { id, .. })) => assert_eq!(id, TaskId(1)),
+        event => panic!("expected an address transaction, got {:?}", event),
+    }
+}
+
 #[tokio::test]
 async fn syncer_state_sweep_addresses() {
+    use crate::SyncerInstance;
     use farcaster_core::blockchain::Network;
     use std::str::FromStr;
     use tokio::sync::mpsc::Receiver as TokioReceiver;
@@ -1139,7 +2423,11 @@ async fn syncer_state_sweep_addresses() {
             from_height: None,
         }),
     };
-    let source1 = ServiceId::Syncer(Blockchain::Monero, Network::Mainnet);
+    let source1 = ServiceId::Syncer(
+        Blockchain::Monero,
+        Network::Mainnet,
+        SyncerInstance::DEFAULT,
+    );
 
     state.sweep_address(sweep_task.clone(), source1.clone());
     assert_eq!(state.lifetimes.len(), 1);
@@ -1157,17 +2445,26 @@ async fn syncer_state_sweep_addresses() {
     assert_eq!(state.lifetimes.len(), 1);
     assert_eq!(state.tasks_sources.len(), 1);
     assert_eq!(state.sweep_addresses.len(), 1);
+    state.sweep_locked(&InternalId(2), 4).await;
+    assert!(event_rx.try_recv().is_ok());
+    // the same number of remaining confirmations is not reported twice
+    state.sweep_locked(&InternalId(2), 4).await;
+    assert!(event_rx.try_recv().is_err());
+    state.sweep_locked(&InternalId(2), 3).await;
+    assert!(event_rx.try_recv().is_ok());
     state
         .success_sweep(&InternalId(2), vec![monero::Hash::new(vec![0]).into()])
         .await;
     assert_eq!(state.lifetimes.len(), 0);
     assert_eq!(state.tasks_sources.len(), 0);
     assert_eq!(state.sweep_addresses.len(), 0);
+    assert_eq!(state.locked_sweeps.len(), 0);
     assert!(event_rx.try_recv().is_ok());
 }
 
 #[tokio::test]
 async fn syncer_state_height() {
+    use crate::SyncerInstance;
     use farcaster_core::blockchain::Network;
     use tokio::sync::mpsc::Receiver as TokioReceiver;
 
@@ -1182,7 +2479,11 @@ async fn syncer_state_height() {
         id: TaskId(0),
         lifetime: 3,
     };
-    let source1 = ServiceId::Syncer(Blockchain::Bitcoin, Network::Mainnet);
+    let source1 = ServiceId::Syncer(
+        Blockchain::Bitcoin,
+        Network::Mainnet,
+        SyncerInstance::DEFAULT,
+    );
 
     state
         .watch_height(height_task.clone(), source1.clone())
@@ -1224,7 +2525,11 @@ async fn syncer_state_height() {
     assert_eq!(state.watch_height.len(), 1);
     assert!(event_rx.try_recv().is_err());
 
-    let source2 = ServiceId::Syncer(Blockchain::Monero, Network::Mainnet);
+    let source2 = ServiceId::Syncer(
+        Blockchain::Monero,
+        Network::Mainnet,
+        SyncerInstance::DEFAULT,
+    );
     state
         .watch_height(another_height_task.clone(), source2.clone())
         .await;
@@ -1243,3 +2548,255 @@ async fn syncer_state_height() {
     assert_eq!(state.watch_height.len(), 0);
     assert!(event_rx.try_recv().is_err());
 }
+
+#[tokio::test]
+async fn syncer_state_estimated_refund_time() {
+    use crate::SyncerInstance;
+    use farcaster_core::blockchain::Network;
+    use tokio::sync::mpsc::Receiver as TokioReceiver;
+
+    let (event_tx, mut event_rx): (TokioSender<BridgeEvent>, TokioReceiver<BridgeEvent>) =
+        tokio::sync::mpsc::channel(120);
+    let mut state = SyncerState::new(event_tx.clone(), Blockchain::Bitcoin);
+    let source1 = ServiceId::Syncer(
+        Blockchain::Bitcoin,
+        Network::Mainnet,
+        SyncerInstance::DEFAULT,
+    );
+    let refund_task = WatchEstimatedRefundTime {
+        id: TaskId(0),
+        target_height: 10,
+    };
+
+    state.change_height(5, vec![5]).await;
+    state.set_block_interval(300);
+    state
+        .watch_estimated_refund_time(refund_task.clone(), source1.clone())
+        .await;
+    assert_eq!(state.watch_refund_time.len(), 1);
+    assert_eq!(
+        event_rx.try_recv().unwrap().event,
+        Event::EstimatedTimeRemaining(EstimatedTimeRemaining {
+            id: TaskId(0),
+            seconds: 1500,
+            blocks: 5,
+        })
+    );
+
+    state.change_height(10, vec![10]).await;
+    assert_eq!(state.watch_refund_time.len(), 0);
+    assert_eq!(state.tasks_sources.len(), 0);
+    assert_eq!(
+        event_rx.try_recv().unwrap().event,
+        Event::EstimatedTimeRemaining(EstimatedTimeRemaining {
+            id: TaskId(0),
+            seconds: 0,
+            blocks: 0,
+        })
+    );
+    assert!(event_rx.try_recv().is_err());
+
+    // the target is already reached, zero is reported immediately
+    state
+        .watch_estimated_refund_time(refund_task.clone(), source1.clone())
+        .await;
+    assert_eq!(state.watch_refund_time.len(), 0);
+    assert_eq!(state.tasks_sources.len(), 0);
+    assert!(event_rx.try_recv().is_ok());
+    assert!(event_rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn syncer_state_monero_addresses() {
+    use crate::SyncerInstance;
+    use farcaster_core::blockchain::Network;
+    use std::str::FromStr;
+    use tokio::sync::mpsc::Receiver as TokioReceiver;
+
+    let (event_tx, mut event_rx): (TokioSender<BridgeEvent>, TokioReceiver<BridgeEvent>) =
+        tokio::sync::mpsc::channel(120);
+    let mut state = SyncerState::new(event_tx.clone(), Blockchain::Monero);
+    let address_task = WatchMoneroAddress {
+        id: TaskId(0),
+        lifetime: 5,
+        address: monero::Address::from_str(
+            "51qzspbPiQ9Z9Wq3hR8HRhPmVcE3URCK8b8A9ypHHzyvhigWTefCapoG1MXVZQQi7B5t4DpJYrHZyaFjHSb5QqLe8YEaBpo"
+        )
+        .unwrap(),
+        view_key: monero::PrivateKey::from_str(
+            "77916d0cd56ed1920aef6ca56d8a41bac915b68e4c46a589e0956e27a7b77404",
+        )
+        .unwrap(),
+        min_amount: monero::Amount::from_pico(10),
+        from_height: 1,
+    };
+    let source1 = ServiceId::Syncer(
+        Blockchain::Monero,
+        Network::Mainnet,
+        SyncerInstance::DEFAULT,
+    );
+
+    state.watch_monero_address(address_task.clone(), source1.clone());
+    assert_eq!(state.lifetimes.len(), 1);
+    assert_eq!(state.tasks_sources.len(), 1);
+    assert_eq!(state.monero_addresses.len(), 1);
+
+    // a transaction below the minimum amount is ignored
+    let small_tx = MoneroReceivedTx {
+        tx_id: monero::Hash::new(vec![0]),
+        amount: 5,
+        confirmations: Some(0),
+    };
+    state
+        .change_monero_address(InternalId(1), 2, vec![small_tx])
+        .await;
+    assert!(event_rx.try_recv().is_err());
+    assert_eq!(state.monero_addresses[&InternalId(1)].scanned_height, 2);
+
+    // a transaction paying at least the minimum amount is reported once
+    let received_tx = MoneroReceivedTx {
+        tx_id: monero::Hash::new(vec![1]),
+        amount: 12,
+        confirmations: Some(0),
+    };
+    state
+        .change_monero_address(InternalId(1), 2, vec![received_tx.clone()])
+        .await;
+    assert!(event_rx.try_recv().is_ok());
+    state
+        .change_monero_address(InternalId(1), 2, vec![received_tx.clone()])
+        .await;
+    assert!(event_rx.try_recv().is_err());
+
+    // a change in confirmations is reported again
+    state
+        .change_monero_address(
+            InternalId(1),
+            3,
+            vec![MoneroReceivedTx {
+                confirmations: Some(1),
+                ..received_tx
+            }],
+        )
+        .await;
+    assert!(event_rx.try_recv().is_ok());
+    assert_eq!(state.monero_addresses[&InternalId(1)].scanned_height, 3);
+
+    // reaching the unlock depth is reported along the confirmations, once
+    state.set_unlock_depth(2);
+    state
+        .change_monero_address(
+            InternalId(1),
+            3,
+            vec![MoneroReceivedTx {
+                confirmations: Some(2),
+                ..received_tx.clone()
+            }],
+        )
+        .await;
+    assert!(event_rx.try_recv().is_ok());
+    assert_eq!(
+        event_rx.try_recv().unwrap().event,
+        Event::MoneroUnlocked(MoneroUnlocked {
+            id: TaskId(0),
+            txid: received_tx.tx_id,
+        })
+    );
+    state
+        .change_monero_address(
+            InternalId(1),
+            3,
+            vec![MoneroReceivedTx {
+                confirmations: Some(3),
+                ..received_tx.clone()
+            }],
+        )
+        .await;
+    assert!(event_rx.try_recv().is_ok());
+    assert!(event_rx.try_recv().is_err());
+
+    state
+        .abort(TaskTarget::TaskId(TaskId(0)), source1.clone(), true)
+        .await;
+    assert_eq!(state.lifetimes.len(), 0);
+    assert_eq!(state.tasks_sources.len(), 0);
+    assert_eq!(state.monero_addresses.len(), 0);
+    assert!(event_rx.try_recv().is_ok());
+}
+
+#[test]
+fn fee_is_stale_below_ratio_of_recommended() {
+    assert!(fee_is_stale(4_000, 10_000, 0.5));
+    assert!(!fee_is_stale(5_000, 10_000, 0.5));
+    assert!(!fee_is_stale(12_000, 10_000, 1.0));
+    assert!(fee_is_stale(9_999, 10_000, 1.0));
+}
+
+#[tokio::test]
+async fn syncer_state_poll_activity() {
+    use crate::SyncerInstance;
+    use farcaster_core::blockchain::Network;
+
+    let (event_tx, _event_rx) = tokio::sync::mpsc::channel(120);
+    let mut state = SyncerState::new(event_tx, Blockchain::Bitcoin);
+    assert_eq!(state.poll_activity(), PollActivity::Idle);
+
+    let hash: Txid = monero::Hash::new(vec![0]).into();
+    let transaction_task = WatchTransaction {
+        id: TaskId(0),
+        lifetime: 10,
+        hash: hash,
+        // a swap keeps following its transactions long after they are final
+        confirmation_bound: 50000,
+        finality: 4,
+    };
+    let source1 = ServiceId::Syncer(
+        Blockchain::Bitcoin,
+        Network::Mainnet,
+        SyncerInstance::DEFAULT,
+    );
+    state.watch_transaction(transaction_task, source1);
+    // not seen yet
+    assert_eq!(state.poll_activity(), PollActivity::Idle);
+
+    // in the mempool
+    state.change_transaction(hash, None, Some(0), vec![]).await;
+    assert_eq!(state.poll_activity(), PollActivity::Imminent);
+
+    state
+        .change_transaction(hash, Some(vec![1]), Some(1), vec![])
+        .await;
+    assert_eq!(state.poll_activity(), PollActivity::Idle);
+
+    // one confirmation away from finality
+    state
+        .change_transaction(hash, Some(vec![1]), Some(3), vec![])
+        .await;
+    assert_eq!(state.poll_activity(), PollActivity::Imminent);
+
+    // final, only followed until the confirmation bound
+    state
+        .change_transaction(hash, Some(vec![1]), Some(5), vec![])
+        .await;
+    assert_eq!(state.poll_activity(), PollActivity::Idle);
+}
+
+#[test]
+fn balance_event_aborts_on_failure() {
+    let id = TaskId(1);
+    assert_eq!(
+        balance_event(id, Ok((10, 5))),
+        Event::Balance(Balance {
+            id,
+            confirmed: 10,
+            unconfirmed: 5,
+        })
+    );
+    assert_eq!(
+        balance_event(id, Err("unreachable".to_string())),
+        Event::TaskAborted(TaskAborted {
+            id: vec![id],
+            error: Some("unreachable".to_string()),
+        })
+    );
+}
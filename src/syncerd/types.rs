@@ -120,11 +120,31 @@ pub struct SweepMoneroAddress {
     derive(Serialize, Deserialize),
     serde(crate = "serde_crate")
 )]
-#[display("Source address: {source_address}, destination_address: {destination_address}")]
+#[display("Source address: {source_address}, destination_address: {destination_address}, fee: {fee_sat_per_kvb:?}")]
 pub struct SweepBitcoinAddress {
     pub source_secret_key: bitcoin::secp256k1::SecretKey,
     pub source_address: bitcoin::Address,
     pub destination_address: bitcoin::Address,
+    /// Fee rate in satoshi per kvB used for the sweep transaction, estimated by the syncer if
+    /// not set
+    pub fee_sat_per_kvb: Option<u64>,
+}
+
+/// Lowest fee rate of a sweep transaction, in satoshi per kvB, the default minimum relay fee
+pub const MIN_SWEEP_FEE_SAT_PER_KVB: u64 = 1_000;
+/// Highest fee rate of a sweep transaction, in satoshi per kvB, a rate above is likely a typo
+pub const MAX_SWEEP_FEE_SAT_PER_KVB: u64 = 1_000_000;
+
+/// Check a sweep fee rate lies between the minimum relay fee and an implausibly high rate
+pub fn validate_sweep_fee_rate(fee_sat_per_kvb: u64) -> Result<u64, String> {
+    if (MIN_SWEEP_FEE_SAT_PER_KVB..=MAX_SWEEP_FEE_SAT_PER_KVB).contains(&fee_sat_per_kvb) {
+        Ok(fee_sat_per_kvb)
+    } else {
+        Err(format!(
+            "the fee rate of {} satoshi/kvB is outside of the accepted range of {} to {} satoshi/kvB",
+            fee_sat_per_kvb, MIN_SWEEP_FEE_SAT_PER_KVB, MAX_SWEEP_FEE_SAT_PER_KVB
+        ))
+    }
 }
 
 #[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
@@ -177,6 +197,43 @@ pub struct WatchAddress {
     pub filter: TxFilter,
 }
 
+/// Watch several addresses with a single task. Each address is a [`WatchAddress`] task of its
+/// own: its events carry its id and aborting it leaves the other addresses of the batch watched.
+/// The histories of all the watched addresses are queried together on each poll.
+#[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display(Debug)]
+pub struct WatchAddresses {
+    pub addresses: Vec<WatchAddress>,
+}
+
+/// Watch a Monero address for incoming funds using only its view key. No spend
+/// key is imported, the syncer only scans for outputs belonging to the address.
+#[cfg_attr(feature = "serde", serde_as)]
+#[derive(Clone, Debug, Display, Eq, PartialEq, Hash, StrictEncode, StrictDecode)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display("WatchMoneroAddress(address: {address}, min_amount: {min_amount}, from_height: {from_height}, id: {id}, lifetime: {lifetime})")]
+pub struct WatchMoneroAddress {
+    pub id: TaskId,
+    pub lifetime: u64,
+    pub address: monero::Address,
+    #[serde_as(as = "DisplayFromStr")]
+    pub view_key: monero::PrivateKey,
+    /// Transactions paying less than this amount to the address are ignored.
+    #[serde(with = "monero::util::amount::serde::as_xmr")]
+    pub min_amount: monero::Amount,
+    /// The blockchain height where to start scanning (not inclusive).
+    pub from_height: u64,
+}
+
 #[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
 #[cfg_attr(
     feature = "serde",
@@ -196,12 +253,89 @@ pub enum TxFilter {
     derive(Serialize, Deserialize),
     serde(crate = "serde_crate")
 )]
-#[display("WatchTransaction(id: {id}, lifetime: {lifetime}, hash: {hash}, confirmation_bound: {confirmation_bound})")]
+#[display("WatchTransaction(id: {id}, lifetime: {lifetime}, hash: {hash}, confirmation_bound: {confirmation_bound}, finality: {finality})")]
 pub struct WatchTransaction {
     pub id: TaskId,
     pub lifetime: u64,
     pub hash: Txid,
     pub confirmation_bound: u32,
+    /// Confirmations after which the source considers the transaction final, the syncer polls
+    /// eagerly while the transaction is about to reach them
+    pub finality: u32,
+}
+
+/// Watch a bitcoin outpoint for spends. An [`OutpointSpent`] event is emitted once the outpoint
+/// is spent by a transaction other than the expected spending transaction, or by any
+/// transaction if none is expected, e.g. to detect a double-spend of the funding output.
+#[derive(Clone, Display, Debug, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display("WatchOutpointSpend(id: {id}, lifetime: {lifetime}, outpoint: {outpoint}, expected_spending_txid: {expected_spending_txid:?})")]
+pub struct WatchOutpointSpend {
+    pub id: TaskId,
+    pub lifetime: u64,
+    pub outpoint: bitcoin::OutPoint,
+    pub expected_spending_txid: Option<bitcoin::Txid>,
+}
+
+/// Watch the lifecycle of a bitcoin outpoint. An [`OutpointCreationConfirmations`] event is
+/// emitted whenever the confirmations of the transaction creating the outpoint change, and an
+/// [`OutpointSpendConfirmations`] event whenever the transaction spending it or its
+/// confirmations change, including when a reorg reverts them. The task is dropped once the
+/// spending transaction reaches the confirmation bound.
+#[derive(Clone, Display, Debug, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display("WatchOutpoint(id: {id}, lifetime: {lifetime}, outpoint: {outpoint}, confirmation_bound: {confirmation_bound})")]
+pub struct WatchOutpoint {
+    pub id: TaskId,
+    pub lifetime: u64,
+    pub outpoint: bitcoin::OutPoint,
+    pub confirmation_bound: u32,
+}
+
+/// Transaction watched by a [`WatchMempoolEntry`] task: a known transaction, or whichever
+/// transaction spends an outpoint
+#[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub enum TxidOrOutpoint {
+    #[display("txid: {0}")]
+    Txid(bitcoin::Txid),
+    #[display("outpoint: {0}")]
+    Outpoint(bitcoin::OutPoint),
+}
+
+/// Watch a bitcoin transaction, or the transaction spending an outpoint, for its entry in the
+/// mempool. A [`SeenInMempool`] event is emitted on the first sighting of the transaction in the
+/// mempool, the transaction is then watched as by a [`WatchTransaction`] task with the same id
+/// and [`TransactionConfirmations`] events follow up to the confirmation bound. A transaction
+/// confirmed before being seen in the mempool emits no [`SeenInMempool`] event. The entry is
+/// polled as any other watch until the transaction shows up, a sighting may therefore lag up to
+/// the maximum poll interval.
+#[derive(Clone, Display, Debug, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display("WatchMempoolEntry(id: {id}, lifetime: {lifetime}, {txid_or_outpoint}, confirmation_bound: {confirmation_bound}, finality: {finality})")]
+pub struct WatchMempoolEntry {
+    pub id: TaskId,
+    pub lifetime: u64,
+    pub txid_or_outpoint: TxidOrOutpoint,
+    pub confirmation_bound: u32,
+    /// Finality of the transaction once seen, as of [`WatchTransaction`]
+    pub finality: u32,
 }
 
 #[derive(Clone, Debug, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
@@ -279,6 +413,111 @@ pub struct GetAddressBalance {
     pub address_secret_key: AddressSecretKey,
 }
 
+#[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display(Debug)]
+pub struct GetMempoolFeeHistogram {
+    pub id: TaskId,
+}
+
+/// Query the backend chain tip to report the syncer connectivity and lag. Issued by the syncer
+/// runtime itself to answer health requests.
+#[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display(Debug)]
+pub struct GetBackendStatus {
+    pub id: TaskId,
+}
+
+/// Periodically estimate the wall-clock time left until the chain reaches the target height,
+/// e.g. the height at which a timelocked refund becomes available. The task is dropped once the
+/// target height is reached.
+#[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display("WatchEstimatedRefundTime(id: {id}, target_height: {target_height})")]
+pub struct WatchEstimatedRefundTime {
+    pub id: TaskId,
+    pub target_height: u64,
+}
+
+/// Retrieve the block at the given height, e.g. to audit what the syncer saw during a disputed
+/// swap. The transactions are only returned on request to keep the event size bounded. Answered
+/// with a [`BlockData`] event.
+#[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display("GetBlockByHeight(id: {id}, height: {height}, include_txs: {include_txs})")]
+pub struct GetBlockByHeight {
+    pub id: TaskId,
+    pub height: u64,
+    pub include_txs: bool,
+}
+
+/// Retrieve the confirmed and unconfirmed balance of the outputs matching the descriptor, the
+/// unspent outputs of the address on Bitcoin, the outputs found with the view key since the given
+/// height on Monero. Answered with a [`Balance`] event.
+#[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display("GetBalance(id: {id}, descriptor: {descriptor})")]
+pub struct GetBalance {
+    pub id: TaskId,
+    pub descriptor: AddressAddendum,
+}
+
+/// Replace an unconfirmed transaction signaling BIP125 replaceability with one paying the new fee
+/// rate, in satoshis per kvB, taken from its change output. The inputs are signed by the wallet of
+/// the backend, only bitcoin rpc backends have one. Answered with a [`FeeBumped`] event, or a
+/// [`TaskAborted`] event if the fee cannot be bumped.
+#[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display("BumpFee(id: {id}, txid: {txid}, new_fee_rate: {new_fee_rate})")]
+pub struct BumpFee {
+    pub id: TaskId,
+    pub txid: bitcoin::Txid,
+    pub new_fee_rate: u64,
+}
+
+/// Set the bounds, in milliseconds, of the adaptive interval between two polls of the backend,
+/// as the `--poll-interval` and `--max-poll-interval` arguments of the syncer do, the defaults
+/// applying to the bounds not set. Sent by farcasterd to the running syncers when the
+/// configuration is reloaded.
+#[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(crate = "serde_crate")
+)]
+#[display(
+    "SetPollIntervals(poll_interval: {poll_interval:?}, max_poll_interval: {max_poll_interval:?})"
+)]
+pub struct SetPollIntervals {
+    pub poll_interval: Option<u64>,
+    pub max_poll_interval: Option<u64>,
+}
+
 /// Tasks created by the daemon and handle by syncers to process a blockchain
 /// and generate [`Event`] back to the syncer.
 #[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
@@ -295,8 +534,18 @@ pub enum Task {
     #[display("{0}")]
     WatchAddress(WatchAddress),
     #[display("{0}")]
+    WatchAddresses(WatchAddresses),
+    #[display("{0}")]
     WatchTransaction(WatchTransaction),
     #[display("{0}")]
+    WatchOutpointSpend(WatchOutpointSpend),
+    #[display("{0}")]
+    WatchOutpoint(WatchOutpoint),
+    #[display("{0}")]
+    WatchMempoolEntry(WatchMempoolEntry),
+    #[display("{0}")]
+    WatchMoneroAddress(WatchMoneroAddress),
+    #[display("{0}")]
     BroadcastTransaction(BroadcastTransaction),
     #[display("{0}")]
     SweepAddress(SweepAddress),
@@ -308,10 +557,101 @@ pub enum Task {
     WatchEstimateFee(WatchEstimateFee),
     #[display("{0}")]
     HealthCheck(HealthCheck),
+    #[display("{0}")]
+    GetMempoolFeeHistogram(GetMempoolFeeHistogram),
+    #[display("{0}")]
+    WatchEstimatedRefundTime(WatchEstimatedRefundTime),
+    #[display("{0}")]
+    GetBackendStatus(GetBackendStatus),
+    #[display("{0}")]
+    GetBlockByHeight(GetBlockByHeight),
+    #[display("{0}")]
+    GetBalance(GetBalance),
+    #[display("{0}")]
+    BumpFee(BumpFee),
+    #[display("{0}")]
+    SetPollIntervals(SetPollIntervals),
     #[display("Terminate")]
     Terminate,
 }
 
+impl Task {
+    /// The id of the task, none for aborts and termination which target other tasks, for
+    /// address batches whose addresses have their own ids, and for the settings of the syncer
+    pub fn id(&self) -> Option<TaskId> {
+        match self {
+            Task::WatchHeight(task) => Some(task.id),
+            Task::WatchAddress(task) => Some(task.id),
+            Task::WatchTransaction(task) => Some(task.id),
+            Task::WatchOutpointSpend(task) => Some(task.id),
+            Task::WatchOutpoint(task) => Some(task.id),
+            Task::WatchMempoolEntry(task) => Some(task.id),
+            Task::WatchMoneroAddress(task) => Some(task.id),
+            Task::BroadcastTransaction(task) => Some(task.id),
+            Task::SweepAddress(task) => Some(task.id),
+            Task::GetTx(task) => Some(task.id),
+            Task::GetAddressBalance(task) => Some(task.id),
+            Task::WatchEstimateFee(task) => Some(task.id),
+            Task::HealthCheck(task) => Some(task.id),
+            Task::GetMempoolFeeHistogram(task) => Some(task.id),
+            Task::WatchEstimatedRefundTime(task) => Some(task.id),
+            Task::GetBackendStatus(task) => Some(task.id),
+            Task::GetBlockByHeight(task) => Some(task.id),
+            Task::GetBalance(task) => Some(task.id),
+            Task::BumpFee(task) => Some(task.id),
+            Task::Abort(_)
+            | Task::WatchAddresses(_)
+            | Task::SetPollIntervals(_)
+            | Task::Terminate => None,
+        }
+    }
+
+    /// The height up to which the syncer keeps the task, none for the tasks without a lifetime
+    pub fn lifetime_mut(&mut self) -> Option<&mut u64> {
+        match self {
+            Task::WatchHeight(WatchHeight { lifetime, .. })
+            | Task::WatchAddress(WatchAddress { lifetime, .. })
+            | Task::WatchTransaction(WatchTransaction { lifetime, .. })
+            | Task::WatchOutpointSpend(WatchOutpointSpend { lifetime, .. })
+            | Task::WatchOutpoint(WatchOutpoint { lifetime, .. })
+            | Task::WatchMempoolEntry(WatchMempoolEntry { lifetime, .. })
+            | Task::WatchMoneroAddress(WatchMoneroAddress { lifetime, .. })
+            | Task::WatchEstimateFee(WatchEstimateFee { lifetime, .. })
+            | Task::SweepAddress(SweepAddress { lifetime, .. }) => Some(lifetime),
+            _ => None,
+        }
+    }
+
+    /// The semantic key of a watch task, none for one-shot tasks. Watch tasks differing only by
+    /// their id and lifetime have the same key: they watch the same thing and emit the same
+    /// events.
+    pub fn key(&self) -> Option<TaskKey> {
+        let mut task = self.clone();
+        match &mut task {
+            Task::WatchHeight(WatchHeight { id, lifetime })
+            | Task::WatchAddress(WatchAddress { id, lifetime, .. })
+            | Task::WatchTransaction(WatchTransaction { id, lifetime, .. })
+            | Task::WatchOutpointSpend(WatchOutpointSpend { id, lifetime, .. })
+            | Task::WatchOutpoint(WatchOutpoint { id, lifetime, .. })
+            | Task::WatchMempoolEntry(WatchMempoolEntry { id, lifetime, .. })
+            | Task::WatchMoneroAddress(WatchMoneroAddress { id, lifetime, .. })
+            | Task::WatchEstimateFee(WatchEstimateFee { id, lifetime }) => {
+                *id = TaskId(0);
+                *lifetime = 0;
+            }
+            Task::WatchEstimatedRefundTime(WatchEstimatedRefundTime { id, .. }) => {
+                *id = TaskId(0);
+            }
+            _ => return None,
+        }
+        Some(TaskKey(task))
+    }
+}
+
+/// Semantic key of a watch task, see [`Task::key`]
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TaskKey(Task);
+
 #[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
 #[display(Debug)]
 pub struct TaskAborted {
@@ -410,6 +750,108 @@ impl fmt::Display for TransactionConfirmations {
     }
 }
 
+#[derive(Clone, Debug, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+pub struct OutpointSpent {
+    pub id: TaskId,
+    pub outpoint: bitcoin::OutPoint,
+    pub spending_txid: bitcoin::Txid,
+}
+
+impl fmt::Display for OutpointSpent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "OutpointSpent(id: {}, outpoint: {}, spending_txid: {})",
+            self.id, self.outpoint, self.spending_txid,
+        )
+    }
+}
+
+#[derive(Clone, Debug, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+pub struct OutpointCreationConfirmations {
+    pub id: TaskId,
+    pub outpoint: bitcoin::OutPoint,
+    /// Confirmations of the transaction creating the outpoint, zero in the mempool and none if
+    /// neither in the mempool nor in the chain
+    pub confirmations: Option<u32>,
+}
+
+impl fmt::Display for OutpointCreationConfirmations {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "OutpointCreationConfirmations(id: {}, outpoint: {}, confirmations: {:?})",
+            self.id, self.outpoint, self.confirmations,
+        )
+    }
+}
+
+#[derive(Clone, Debug, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+pub struct OutpointSpendConfirmations {
+    pub id: TaskId,
+    pub outpoint: bitcoin::OutPoint,
+    /// The transaction spending the outpoint, none if unspent
+    pub spending_txid: Option<bitcoin::Txid>,
+    /// Confirmations of the spending transaction, zero in the mempool
+    pub confirmations: Option<u32>,
+}
+
+impl fmt::Display for OutpointSpendConfirmations {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "OutpointSpendConfirmations(id: {}, outpoint: {}, spending_txid: {:?}, confirmations: {:?})",
+            self.id, self.outpoint, self.spending_txid, self.confirmations,
+        )
+    }
+}
+
+#[derive(Clone, Debug, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+pub struct SeenInMempool {
+    pub id: TaskId,
+    pub txid: bitcoin::Txid,
+}
+
+impl fmt::Display for SeenInMempool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SeenInMempool(id: {}, txid: {})", self.id, self.txid)
+    }
+}
+
+#[derive(Clone, Debug, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+pub struct MoneroUnlocked {
+    pub id: TaskId,
+    pub txid: monero::Hash,
+}
+
+impl fmt::Display for MoneroUnlocked {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MoneroUnlocked(id: {}, txid: {})", self.id, self.txid)
+    }
+}
+
+#[derive(Clone, Debug, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+pub struct MoneroReceived {
+    pub id: TaskId,
+    pub txid: monero::Hash,
+    /// Sum of all the outputs of the transaction paying to the watched address.
+    pub amount: u64,
+    pub confirmations: Option<u32>,
+}
+
+impl fmt::Display for MoneroReceived {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "MoneroReceived(id: {}, txid: {}, amount: {}, confirmations: {:?})",
+            self.id,
+            self.txid,
+            monero::Amount::from_pico(self.amount),
+            self.confirmations,
+        )
+    }
+}
+
 #[derive(Clone, Debug, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
 pub struct TransactionBroadcasted {
     pub id: TaskId,
@@ -431,6 +873,23 @@ impl fmt::Display for TransactionBroadcasted {
     }
 }
 
+#[derive(Clone, Debug, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+pub struct BroadcastFailure {
+    pub id: TaskId,
+    pub txid: Txid,
+    pub reason: String,
+}
+
+impl fmt::Display for BroadcastFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "BroadcastFailure(id: {}, tx_id: {}, reason: {})",
+            self.id, self.txid, self.reason,
+        )
+    }
+}
+
 #[derive(Clone, Debug, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
 pub struct SweepSuccess {
     pub id: TaskId,
@@ -452,6 +911,100 @@ impl fmt::Display for SweepSuccess {
     }
 }
 
+#[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[display("SweepLocked(id: {id}, confirmations_remaining: {confirmations_remaining})")]
+pub struct SweepLocked {
+    pub id: TaskId,
+    pub confirmations_remaining: u32,
+}
+
+#[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[display(Debug)]
+pub struct BackendStatus {
+    pub id: TaskId,
+    /// Height the syncer is synced to
+    pub synced_height: u64,
+    /// Height of the backend chain tip, none if the backend is unreachable
+    pub tip_height: Option<u64>,
+    /// Reason the backend is unreachable
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Debug, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+pub struct BlockData {
+    pub id: TaskId,
+    pub height: u64,
+    /// Hash of the block, in the byte order block explorers display it
+    pub hash: Vec<u8>,
+    /// Consensus serialized block header
+    pub header: Vec<u8>,
+    /// Number of transactions in the block, none if the backend does not report it
+    pub tx_count: Option<u64>,
+    /// Consensus serialized transactions of the block, only if requested
+    pub txs: Option<Vec<Vec<u8>>>,
+}
+
+impl fmt::Display for BlockData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "BlockData(id: {}, height: {}, hash: {}, tx_count: {}, txs: {})",
+            self.id,
+            self.height,
+            hex::encode(&self.hash),
+            self.tx_count
+                .map(|count| count.to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+            self.txs
+                .as_ref()
+                .map(|txs| txs.len().to_string())
+                .unwrap_or_else(|| "not included".to_string()),
+        )
+    }
+}
+
+#[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[display("Balance(id: {id}, confirmed: {confirmed}, unconfirmed: {unconfirmed})")]
+pub struct Balance {
+    pub id: TaskId,
+    /// Sum of the confirmed outputs, in the smallest unit of the chain. Only the unlocked
+    /// outputs on Monero.
+    pub confirmed: u64,
+    /// Sum of the outputs not confirmed yet, or still locked on Monero
+    pub unconfirmed: u64,
+}
+
+#[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[display("FeeBumped(id: {id}, txid: {txid}, replacement_txid: {replacement_txid})")]
+pub struct FeeBumped {
+    pub id: TaskId,
+    pub txid: bitcoin::Txid,
+    pub replacement_txid: bitcoin::Txid,
+}
+
+/// A watched unconfirmed transaction pays a fee rate too low compared to the current estimation
+/// and is likely to stay stuck in the mempool. Fee rates are in satoshis per kvB.
+#[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[display(
+    "TransactionFeeStale(id: {id}, txid: {txid}, tx_fee_rate: {tx_fee_rate}, recommended: {recommended})"
+)]
+pub struct TransactionFeeStale {
+    pub id: TaskId,
+    pub txid: bitcoin::Txid,
+    pub tx_fee_rate: u64,
+    pub recommended: u64,
+}
+
+#[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[display("EstimatedTimeRemaining(id: {id}, seconds: {seconds}, blocks: {blocks})")]
+pub struct EstimatedTimeRemaining {
+    pub id: TaskId,
+    /// Estimated time left, based on the average block interval
+    pub seconds: u64,
+    /// Blocks left before reaching the target height
+    pub blocks: u64,
+}
+
 #[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
 #[display(Debug)]
 pub struct TransactionRetrieved {
@@ -476,6 +1029,24 @@ pub enum FeeEstimations {
     },
 }
 
+/// A fee rate bucket of the mempool: the virtual size of the transactions paying at least the
+/// fee rate and less than the fee rate of the previous bucket.
+#[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[display(Debug)]
+// the sats per kvB is because we need u64 for Eq, PartialEq and Hash
+pub struct FeeHistogramBucket {
+    pub sats_per_kvbyte: u64,
+    pub vsize: u64,
+}
+
+#[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
+#[display(Debug)]
+pub struct FeeHistogram {
+    pub id: TaskId,
+    /// Buckets sorted by decreasing fee rate
+    pub buckets: Vec<FeeHistogramBucket>,
+}
+
 #[derive(Clone, Debug, Display, StrictEncode, StrictDecode, Eq, PartialEq, Hash)]
 #[display(Debug)]
 pub struct HealthResult {
@@ -493,6 +1064,7 @@ pub struct HealthResult {
 pub enum Health {
     Healthy,
     FaultyElectrum(String),
+    FaultyBitcoinRpc(String),
     FaultyMoneroDaemon(String),
     FaultyMoneroRpcWallet(String),
     ConfigUnavailable(String),
@@ -522,6 +1094,27 @@ pub enum Event {
     TransactionConfirmations(TransactionConfirmations),
     #[display("{0}")]
     TransactionBroadcasted(TransactionBroadcasted),
+    /// Notify the daemon a watched outpoint has been spent by a transaction other than the
+    /// expected one.
+    #[display("{0}")]
+    OutpointSpent(OutpointSpent),
+    /// Notify the daemon the confirmations of the transaction creating a watched outpoint
+    /// changed.
+    #[display("{0}")]
+    OutpointCreationConfirmations(OutpointCreationConfirmations),
+    /// Notify the daemon the transaction spending a watched outpoint, or its confirmations,
+    /// changed.
+    #[display("{0}")]
+    OutpointSpendConfirmations(OutpointSpendConfirmations),
+    /// Notify the daemon the transaction watched by a [`WatchMempoolEntry`] task entered the
+    /// mempool. The transaction is not confirmed: it may still be evicted or replaced, only the
+    /// [`TransactionConfirmations`] events following it report its confirmation.
+    #[display("{0}")]
+    SeenInMempool(SeenInMempool),
+    /// Notify the daemon the backend rejected a transaction to broadcast, with the reject
+    /// reason.
+    #[display("{0}")]
+    BroadcastFailure(BroadcastFailure),
     #[display("{0}")]
     SweepSuccess(SweepSuccess),
     #[display("{0}")]
@@ -538,6 +1131,80 @@ pub enum Event {
     Empty(TaskId),
     #[display("{0}")]
     HealthResult(HealthResult),
+    /// Notify the daemon of the mempool fee histogram.
+    #[display("{0}")]
+    FeeHistogram(FeeHistogram),
+    /// Notify the daemon the task with a certain id is not supported by the syncer.
+    #[display("Unsupported({0})")]
+    Unsupported(TaskId),
     #[display("{0}")]
     AddressBalance(AddressBalance),
+    /// Notify the daemon a view-key watched Monero address received funds, or
+    /// that the confirmations of such a transaction changed.
+    #[display("{0}")]
+    MoneroReceived(MoneroReceived),
+    /// Notify the daemon a watched Monero transaction, or a transaction received by a
+    /// view-key watched address, reached the unlock depth and its outputs are spendable.
+    #[display("{0}")]
+    MoneroUnlocked(MoneroUnlocked),
+    /// Notify the daemon the funds of a sweep task are not spendable yet, with the number of
+    /// confirmations left before they unlock.
+    #[display("{0}")]
+    SweepLocked(SweepLocked),
+    /// Notify the daemon of the estimated time left before reaching the target height of a
+    /// [`WatchEstimatedRefundTime`] task. Zero once the target is reached.
+    #[display("{0}")]
+    EstimatedTimeRemaining(EstimatedTimeRemaining),
+    /// Notify the syncer runtime of the backend connectivity and chain tip.
+    #[display("{0}")]
+    BackendStatus(BackendStatus),
+    /// Notify the daemon of the block at the height requested by a [`GetBlockByHeight`] task.
+    #[display("{0}")]
+    BlockData(BlockData),
+    /// Notify the daemon of the balance requested by a [`GetBalance`] task.
+    #[display("{0}")]
+    Balance(Balance),
+    /// Notify the daemon a transaction watched by a [`WatchTransaction`] task pays a fee rate
+    /// likely too low to confirm.
+    #[display("{0}")]
+    TransactionFeeStale(TransactionFeeStale),
+    /// Notify the daemon the replacement transaction of a [`BumpFee`] task has been broadcast.
+    #[display("{0}")]
+    FeeBumped(FeeBumped),
+}
+
+impl Event {
+    /// The ids of the tasks the event responds to, several for an abort of all tasks
+    pub fn task_ids(&self) -> Vec<TaskId> {
+        let id = match self {
+            Event::HeightChanged(event) => event.id,
+            Event::AddressTransaction(event) => event.id,
+            Event::TransactionConfirmations(event) => event.id,
+            Event::TransactionBroadcasted(event) => event.id,
+            Event::OutpointSpent(event) => event.id,
+            Event::OutpointCreationConfirmations(event) => event.id,
+            Event::OutpointSpendConfirmations(event) => event.id,
+            Event::SeenInMempool(event) => event.id,
+            Event::BroadcastFailure(event) => event.id,
+            Event::SweepSuccess(event) => event.id,
+            Event::TaskAborted(event) => return event.id.clone(),
+            Event::TransactionRetrieved(event) => event.id,
+            Event::FeeEstimation(event) => event.id,
+            Event::Empty(id) => *id,
+            Event::HealthResult(event) => event.id,
+            Event::FeeHistogram(event) => event.id,
+            Event::Unsupported(id) => *id,
+            Event::AddressBalance(event) => event.id,
+            Event::MoneroReceived(event) => event.id,
+            Event::MoneroUnlocked(event) => event.id,
+            Event::SweepLocked(event) => event.id,
+            Event::EstimatedTimeRemaining(event) => event.id,
+            Event::BackendStatus(event) => event.id,
+            Event::BlockData(event) => event.id,
+            Event::Balance(event) => event.id,
+            Event::TransactionFeeStale(event) => event.id,
+            Event::FeeBumped(event) => event.id,
+        };
+        vec![id]
+    }
 }
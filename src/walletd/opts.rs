@@ -6,16 +6,27 @@
 
 use crate::opts::FARCASTER_KEY_FILE;
 use clap::ValueHint;
+use std::convert::TryInto;
 use std::path::PathBuf;
-use std::{fs, io::Read};
+use std::{fs, io, io::Read};
 
 use crate::opts::TokenString;
+use bitcoin::hashes::{sha256, sha256d, Hash, HashEngine};
 use bitcoin::secp256k1::{
     rand::{rngs::ThreadRng, thread_rng},
     PublicKey, Secp256k1, SecretKey,
 };
+use farcaster_core::crypto::{ArbitratingKeyId, GenerateKey, ProveCrossGroupDleq, Sign};
+use farcaster_core::swap::btcxmr::KeyManager;
+use farcaster_core::swap::SwapId;
 use strict_encoding::{StrictDecode, StrictEncode};
 
+/// Swap index the key manager self-check derives its keys at, never reached by the wallet counter
+const KEY_MANAGER_CHECK_INDEX: u32 = u32::MAX;
+
+/// Account of the node Monero wallet holding the per-swap subaddresses
+const MONERO_SUBADDRESS_ACCOUNT: u32 = 0;
+
 /// Walletd daemon; part of Farcaster Node
 #[derive(Parser, Clone, PartialEq, Eq, Debug)]
 #[clap(name = "walletd", bin_name = "walletd", author, version)]
@@ -28,6 +39,21 @@ pub struct Opts {
     #[clap(flatten)]
     pub wallet_token: TokenString,
 
+    /// Restore the wallet seed from its mnemonic when creating the key file
+    ///
+    /// Ignored if the key file already holds the same seed, refused if it holds
+    /// another one. Prefer the environment variable to keep the mnemonic out of
+    /// the process list.
+    #[clap(long, env = "FARCASTER_RESTORE_MNEMONIC", hide_env_values = true)]
+    pub restore_mnemonic: Option<String>,
+
+    /// Wallet account the swap keys are derived in
+    ///
+    /// Nodes sharing a wallet seed must use distinct accounts for their keys
+    /// not to collide. Account 0 derives the keys of nodes predating accounts.
+    #[clap(long, default_value = "0")]
+    pub account: u32,
+
     /// These params can be read also from the configuration file, not just
     /// command-line args or environment variables
     #[clap(flatten)]
@@ -68,7 +94,7 @@ impl Counter {
 }
 
 /// Hold secret keys and seeds
-#[derive(StrictEncode, StrictDecode, Clone, PartialEq, Eq, Debug)]
+#[derive(StrictEncode, Clone, PartialEq, Eq, Debug)]
 pub struct NodeSecrets {
     /// local key file
     pub key_file: String,
@@ -78,28 +104,67 @@ pub struct NodeSecrets {
     pub wallet_seed: [u8; 32],
     /// wallet last derivation index
     pub wallet_counter: Counter,
+    /// last derivation index of the rotated node key
+    pub node_key_counter: Counter,
+}
+
+impl StrictDecode for NodeSecrets {
+    fn strict_decode<D: io::Read>(mut d: D) -> Result<Self, strict_encoding::Error> {
+        let key_file = StrictDecode::strict_decode(&mut d)?;
+        let peerd_secret_key = StrictDecode::strict_decode(&mut d)?;
+        let wallet_seed = StrictDecode::strict_decode(&mut d)?;
+        let wallet_counter = StrictDecode::strict_decode(&mut d)?;
+        // key files created before node key rotation end before the counter, any other
+        // remainder must be a valid counter
+        let mut rest = vec![];
+        d.read_to_end(&mut rest)?;
+        let node_key_counter = if rest.is_empty() {
+            Counter(0)
+        } else {
+            Counter::strict_deserialize(&rest)?
+        };
+        Ok(Self {
+            key_file,
+            peerd_secret_key,
+            wallet_seed,
+            wallet_counter,
+            node_key_counter,
+        })
+    }
 }
 
 impl NodeSecrets {
-    pub fn new(key_file: String) -> Self {
+    /// Load the node secrets from the key file, or create it with the restored wallet seed if
+    /// any, a new one otherwise
+    pub fn new(key_file: String, restored_seed: Option<[u8; 32]>) -> Self {
         if PathBuf::from(key_file.clone()).exists() {
-            NodeSecrets::strict_decode(fs::File::open(key_file.clone()).unwrap_or_else(|_| {
-                panic!(
-                    "Unable to open key file {}; please check that the user \
+            let node_secrets =
+                NodeSecrets::strict_decode(fs::File::open(key_file.clone()).unwrap_or_else(|_| {
+                    panic!(
+                        "Unable to open key file {}; please check that the user \
                     running the deamon has necessary permissions",
+                        key_file
+                    )
+                }))
+                .expect("Unable to read node code file format");
+            if matches!(restored_seed, Some(seed) if seed != node_secrets.wallet_seed) {
+                panic!(
+                    "Key file {} holds another wallet seed than the restored one; move it \
+                    away to restore the seed",
                     key_file
-                )
-            }))
-            .expect("Unable to read node code file format")
+                );
+            }
+            node_secrets
         } else {
             let mut rng = thread_rng();
             let peer_private_key = SecretKey::new(&mut rng);
-            let wallet_seed = Self::create_seed(&mut rng);
+            let wallet_seed = restored_seed.unwrap_or_else(|| Self::create_seed(&mut rng));
             let node_secrets = Self {
                 key_file: key_file.clone(),
                 peerd_secret_key: peer_private_key,
                 wallet_seed,
                 wallet_counter: Counter(0),
+                node_key_counter: Counter(0),
             };
 
             let key_file_handle = fs::File::create(&key_file).unwrap_or_else(|_| {
@@ -139,20 +204,177 @@ impl NodeSecrets {
 
     pub fn increment_wallet_counter(&mut self) -> u32 {
         self.wallet_counter.increment();
+        self.save("Unable to save incremented wallet counter");
+        self.wallet_counter.0
+    }
+
+    /// Replace the peerd secret key with a fresh one derived from the wallet seed at the next
+    /// node key index. The wallet seed and counter are untouched, so swap key derivations are not
+    /// affected. Returns the new node id.
+    pub fn rotate_node_key(&mut self) -> PublicKey {
+        let index = self.node_key_counter.increment();
+        let mut engine = sha256::Hash::engine();
+        engine.input(&self.wallet_seed);
+        engine.input(b"farcaster-node-key");
+        engine.input(&index.to_le_bytes());
+        self.peerd_secret_key = SecretKey::from_slice(&sha256::Hash::from_engine(engine)[..])
+            .expect("sha256 output is a valid secret key with overwhelming probability");
+        self.save("Unable to save rotated node key");
+        self.node_id()
+    }
+
+    fn save(&self, msg: &str) {
         let key_file_handle = fs::File::create(&self.key_file).unwrap_or_else(|_| {
             panic!(
                 "Unable to create key file '{}'; please check that path exists",
                 self.key_file
             )
         });
-        self.strict_encode(key_file_handle)
-            .expect("Unable to save incremented wallet counter");
-        self.wallet_counter.0
+        self.strict_encode(key_file_handle).expect(msg);
     }
 
     pub fn wallet_seed(&self) -> [u8; 32] {
         self.wallet_seed
     }
+
+    /// Seed the swap keys of an account are derived from. Account 0 uses the wallet seed itself,
+    /// keeping the keys derived before accounts were introduced.
+    pub fn account_seed(&self, account: u32) -> [u8; 32] {
+        if account == 0 {
+            return self.wallet_seed;
+        }
+        let mut engine = sha256::Hash::engine();
+        engine.input(&self.wallet_seed);
+        engine.input(b"farcaster-wallet-account");
+        engine.input(&account.to_le_bytes());
+        sha256::Hash::from_engine(engine).into_inner()
+    }
+
+    /// Keys of the node Monero wallet derived from the wallet seed, the view key is derived from
+    /// the spend key as Monero wallets do
+    pub fn monero_wallet_keys(&self) -> monero::KeyPair {
+        let mut seed = self.wallet_seed.to_vec();
+        seed.extend_from_slice(b"farcaster-monero-wallet");
+        let spend = monero::cryptonote::hash::Hash::hash_to_scalar(&seed);
+        let view = monero::cryptonote::hash::Hash::hash_to_scalar(spend.as_bytes());
+        monero::KeyPair { view, spend }
+    }
+
+    /// Subaddress index of a swap in the node Monero wallet. It only depends on the swap id, so a
+    /// swap restored from a checkpoint or a wallet restored from the seed finds its subaddress
+    /// again. Index 0 is the wallet main address and never used.
+    pub fn monero_subaddress_index(swap_id: SwapId) -> u32 {
+        let mut encoded = vec![];
+        swap_id
+            .strict_encode(&mut encoded)
+            .expect("swap id encodes in memory");
+        let hash = monero::cryptonote::hash::keccak_256(&encoded);
+        match u32::from_le_bytes([hash[0], hash[1], hash[2], hash[3]]) {
+            0 => 1,
+            index => index,
+        }
+    }
+
+    /// Subaddress of the node Monero wallet dedicated to a swap
+    pub fn monero_subaddress(&self, network: monero::Network, swap_id: SwapId) -> monero::Address {
+        let keys = self.monero_wallet_keys();
+        let index = Self::monero_subaddress_index(swap_id);
+        let mut data = b"SubAddr\0".to_vec();
+        data.extend_from_slice(keys.view.as_bytes());
+        data.extend_from_slice(&MONERO_SUBADDRESS_ACCOUNT.to_le_bytes());
+        data.extend_from_slice(&index.to_le_bytes());
+        let m = monero::cryptonote::hash::Hash::hash_to_scalar(&data);
+        let spend = monero::PublicKey::from_private_key(&keys.spend)
+            + monero::PublicKey::from_private_key(&m);
+        let view = keys.view * &spend;
+        monero::Address::subaddress(network, spend, view)
+    }
+
+    /// BIP39 mnemonic encoding the wallet seed
+    pub fn seed_mnemonic(&self) -> String {
+        bip39::Mnemonic::from_entropy(&self.wallet_seed)
+            .expect("a 32 bytes seed is valid BIP39 entropy")
+            .to_string()
+    }
+
+    /// Decode a wallet seed from its BIP39 mnemonic
+    pub fn seed_from_mnemonic(mnemonic: &str) -> Result<[u8; 32], String> {
+        let entropy = bip39::Mnemonic::parse_normalized(mnemonic)
+            .map_err(|err| format!("Invalid seed mnemonic: {}", err))?
+            .to_entropy();
+        entropy
+            .as_slice()
+            .try_into()
+            .map_err(|_| format!("Invalid seed mnemonic of {} bytes", entropy.len()))
+    }
+
+    /// Derive the swap keys of an account at a reserved swap index and check their consistency:
+    /// the derivation is deterministic, the public keys match the secret keys, signatures verify
+    /// and the cross-group DLEQ proof holds. Returns the first failed check.
+    pub fn verify_key_manager(&self, account: u32) -> Result<(), String> {
+        let seed = self.account_seed(account);
+        let mut key_manager = KeyManager::new(seed, KEY_MANAGER_CHECK_INDEX)
+            .map_err(|err| format!("Failed to create the key manager: {}", err))?;
+        let mut twin_key_manager = KeyManager::new(seed, KEY_MANAGER_CHECK_INDEX)
+            .map_err(|err| format!("Failed to create the key manager: {}", err))?;
+        let secp = Secp256k1::new();
+
+        for key_id in [
+            ArbitratingKeyId::Lock,
+            ArbitratingKeyId::Buy,
+            ArbitratingKeyId::Cancel,
+            ArbitratingKeyId::Refund,
+            ArbitratingKeyId::Punish,
+        ] {
+            let secret_key = key_manager
+                .get_or_derive_bitcoin_key(key_id)
+                .map_err(|err| format!("Failed to derive the {:?} key: {}", key_id, err))?;
+            let public_key = key_manager
+                .get_pubkey(key_id)
+                .map_err(|err| format!("Failed to derive the {:?} public key: {}", key_id, err))?;
+            if PublicKey::from_secret_key(&secp, &secret_key) != public_key {
+                return Err(format!(
+                    "The {:?} public key does not match its secret key",
+                    key_id
+                ));
+            }
+            match twin_key_manager.get_pubkey(key_id) {
+                Ok(twin_public_key) if twin_public_key == public_key => {}
+                _ => {
+                    return Err(format!(
+                        "The {:?} key derivation is not deterministic",
+                        key_id
+                    ))
+                }
+            }
+        }
+
+        let msg = sha256d::Hash::hash(b"farcaster-key-manager-check");
+        let lock_public_key = key_manager
+            .get_pubkey(ArbitratingKeyId::Lock)
+            .map_err(|err| format!("Failed to derive the Lock public key: {}", err))?;
+        let sig = key_manager
+            .sign(ArbitratingKeyId::Lock, msg)
+            .map_err(|err| format!("Failed to sign with the Lock key: {}", err))?;
+        key_manager
+            .verify_signature(&lock_public_key, msg, &sig)
+            .map_err(|err| format!("The Lock key signature does not verify: {}", err))?;
+
+        let spend_secret_key = key_manager
+            .get_or_derive_monero_spend_key()
+            .map_err(|err| format!("Failed to derive the Monero spend key: {}", err))?;
+        let (spend, adaptor, proof) = key_manager
+            .generate_proof()
+            .map_err(|err| format!("Failed to generate the DLEQ proof: {}", err))?;
+        if monero::PublicKey::from_private_key(&spend_secret_key) != spend {
+            return Err("The Monero spend public key does not match its secret key".to_string());
+        }
+        key_manager
+            .verify_proof(&spend, &adaptor, proof)
+            .map_err(|err| format!("The DLEQ proof does not verify: {}", err))?;
+
+        Ok(())
+    }
 }
 
 impl KeyOpts {
@@ -160,3 +382,105 @@ impl KeyOpts {
         shared.process_dir(&mut self.key_file);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn account_seed_keeps_account_zero() {
+        let node_secrets = NodeSecrets {
+            key_file: "".to_string(),
+            peerd_secret_key: SecretKey::from_slice(&[1u8; 32]).unwrap(),
+            wallet_seed: [2u8; 32],
+            wallet_counter: Counter(0),
+            node_key_counter: Counter(0),
+        };
+        assert_eq!(node_secrets.account_seed(0), node_secrets.wallet_seed);
+        assert_ne!(node_secrets.account_seed(1), node_secrets.wallet_seed);
+    }
+
+    #[test]
+    fn monero_subaddress_is_deterministic_per_swap() {
+        let node_secrets = NodeSecrets {
+            key_file: "".to_string(),
+            peerd_secret_key: SecretKey::from_slice(&[1u8; 32]).unwrap(),
+            wallet_seed: [2u8; 32],
+            wallet_counter: Counter(0),
+            node_key_counter: Counter(0),
+        };
+        let swap_id: SwapId = farcaster_core::Uuid::new().into();
+        let address = node_secrets.monero_subaddress(monero::Network::Stagenet, swap_id);
+        assert_eq!(address.addr_type, monero::AddressType::SubAddress);
+        assert_eq!(
+            address,
+            node_secrets.monero_subaddress(monero::Network::Stagenet, swap_id)
+        );
+        assert_ne!(NodeSecrets::monero_subaddress_index(swap_id), 0);
+        assert_ne!(
+            address,
+            node_secrets.monero_subaddress(
+                monero::Network::Stagenet,
+                farcaster_core::Uuid::new().into()
+            )
+        );
+    }
+
+    #[test]
+    fn seed_mnemonic_round_trips() {
+        let node_secrets = NodeSecrets {
+            key_file: "".to_string(),
+            peerd_secret_key: SecretKey::from_slice(&[1u8; 32]).unwrap(),
+            wallet_seed: NodeSecrets::create_seed(&mut thread_rng()),
+            wallet_counter: Counter(0),
+            node_key_counter: Counter(0),
+        };
+        let mnemonic = node_secrets.seed_mnemonic();
+        assert_eq!(mnemonic.split_whitespace().count(), 24);
+        assert_eq!(
+            NodeSecrets::seed_from_mnemonic(&mnemonic),
+            Ok(node_secrets.wallet_seed)
+        );
+        // a 12 words mnemonic encodes a seed too short
+        assert!(NodeSecrets::seed_from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon about"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn node_secrets_decode_without_node_key_counter() {
+        let node_secrets = NodeSecrets {
+            key_file: "".to_string(),
+            peerd_secret_key: SecretKey::from_slice(&[1u8; 32]).unwrap(),
+            wallet_seed: [2u8; 32],
+            wallet_counter: Counter(3),
+            node_key_counter: Counter(4),
+        };
+        let encoded = node_secrets.strict_serialize().unwrap();
+        assert_eq!(
+            NodeSecrets::strict_deserialize(&encoded).unwrap(),
+            node_secrets
+        );
+        // key files predating node key rotation end before the counter
+        let legacy = NodeSecrets::strict_deserialize(&encoded[..encoded.len() - 4]).unwrap();
+        assert_eq!(legacy.node_key_counter, Counter(0));
+        assert_eq!(legacy.wallet_counter, Counter(3));
+        // a truncated counter is an error, not a missing one
+        assert!(NodeSecrets::strict_deserialize(&encoded[..encoded.len() - 2]).is_err());
+    }
+
+    #[test]
+    fn key_manager_verifies() {
+        let node_secrets = NodeSecrets {
+            key_file: "".to_string(),
+            peerd_secret_key: SecretKey::from_slice(&[1u8; 32]).unwrap(),
+            wallet_seed: NodeSecrets::create_seed(&mut thread_rng()),
+            wallet_counter: Counter(0),
+            node_key_counter: Counter(0),
+        };
+        assert_eq!(node_secrets.verify_key_manager(0), Ok(()));
+        assert_eq!(node_secrets.verify_key_manager(1), Ok(()));
+    }
+}
@@ -5,8 +5,9 @@
 // https://opensource.org/licenses/MIT.
 
 use crate::bus::{
-    ctl::{CtlMsg, GetKeys, Keys, SwapKeys, Token, WrappedKeyManager},
-    BusMsg, ServiceBus,
+    ctl::{CtlMsg, GetKeys, Keys, MoneroDeposit, SwapKeys, Token, WrappedKeyManager},
+    p2p::{buy_key_commitment, derive_swap_id},
+    BusMsg, Failure, FailureCode, OptionDetails, ServiceBus,
 };
 
 use crate::service::Endpoints;
@@ -20,11 +21,13 @@ pub fn run(
     config: ServiceConfig,
     wallet_token: Token,
     node_secrets: NodeSecrets,
+    account: u32,
 ) -> Result<(), Error> {
     let runtime = Runtime {
         identity: ServiceId::Wallet,
         wallet_token,
         node_secrets,
+        account,
     };
 
     Service::run(config, runtime, false)
@@ -34,6 +37,8 @@ pub struct Runtime {
     identity: ServiceId,
     wallet_token: Token,
     node_secrets: NodeSecrets,
+    /// Wallet account the swap keys are derived in
+    account: u32,
 }
 
 impl CtlServer for Runtime {}
@@ -81,15 +86,49 @@ impl Runtime {
                 debug!("Received Hello from {}", source);
             }
 
+            CtlMsg::Heartbeat if source == ServiceId::Farcasterd => {
+                endpoints.send_to(
+                    ServiceBus::Ctl,
+                    self.identity(),
+                    ServiceId::Farcasterd,
+                    BusMsg::Ctl(CtlMsg::Heartbeat),
+                )?;
+            }
+
+            CtlMsg::Terminate if source == ServiceId::Farcasterd => {
+                info!("Terminating {}", self.identity());
+                std::process::exit(0);
+            }
+
+            CtlMsg::SetLogFilter(filter) if source == ServiceId::Farcasterd => {
+                crate::logger::set_filter(filter.as_deref());
+            }
+
             CtlMsg::CreateSwapKeys(deal, wallet_token) => {
                 if wallet_token != self.wallet_token {
                     return Err(Error::InvalidToken);
                 }
                 let wallet_index = self.node_secrets.increment_wallet_counter();
-                let key_manager = KeyManager::new(self.node_secrets.wallet_seed, wallet_index)?;
+                let key_manager =
+                    KeyManager::new(self.node_secrets.account_seed(self.account), wallet_index)?;
+                // the id the swap takes when these keys are the taker's, fresh for every set of
+                // keys whichever the role
+                let deposit_id = derive_swap_id(&deal, &buy_key_commitment(&key_manager)?);
+                let monero_deposit = MoneroDeposit {
+                    address: self
+                        .node_secrets
+                        .monero_subaddress(deal.parameters.network.into(), deposit_id),
+                    view_key: self.node_secrets.monero_wallet_keys().view,
+                };
+                debug!(
+                    "{} | Derived the Monero subaddress {}",
+                    deal.id(),
+                    monero_deposit.address
+                );
                 let swap_keys = SwapKeys {
                     key_manager: WrappedKeyManager(key_manager),
                     deal,
+                    monero_deposit,
                 };
                 endpoints.send_to(
                     ServiceBus::Ctl,
@@ -115,6 +154,60 @@ impl Runtime {
                 )?;
             }
 
+            CtlMsg::RotateNodeKeys(wallet_token) => {
+                if wallet_token.as_ref() != Some(&self.wallet_token) {
+                    return Err(Error::InvalidToken);
+                }
+                let node_id = self.node_secrets.rotate_node_key();
+                info!("Rotated node keys, new node id is {}", node_id);
+                endpoints.send_to(
+                    ServiceBus::Ctl,
+                    ServiceId::Wallet,
+                    ServiceId::Farcasterd,
+                    BusMsg::Ctl(CtlMsg::Keys(Keys(
+                        self.node_secrets.peerd_secret_key,
+                        node_id,
+                    ))),
+                )?;
+            }
+
+            CtlMsg::ExportSeed(wallet_token) => {
+                if wallet_token.as_ref() != Some(&self.wallet_token) {
+                    return Err(Error::InvalidToken);
+                }
+                self.send_client_ctl(
+                    endpoints,
+                    source,
+                    CtlMsg::SeedMnemonic(self.node_secrets.seed_mnemonic()),
+                )?;
+            }
+
+            CtlMsg::VerifyKeyManager(wallet_token) => {
+                if wallet_token.as_ref() != Some(&self.wallet_token) {
+                    return Err(Error::InvalidToken);
+                }
+                let resp = match self.node_secrets.verify_key_manager(self.account) {
+                    Ok(()) => {
+                        info!("Verified the key manager of account {}", self.account);
+                        CtlMsg::Success(OptionDetails::with(format!(
+                            "Key manager of account {} verified",
+                            self.account
+                        )))
+                    }
+                    Err(info) => {
+                        error!(
+                            "Key manager of account {} failed verification: {}",
+                            self.account, info
+                        );
+                        CtlMsg::Failure(Failure {
+                            code: FailureCode::Unknown,
+                            info,
+                        })
+                    }
+                };
+                self.send_client_ctl(endpoints, source, resp)?;
+            }
+
             req => {
                 error!(
                     "BusMsg {} is not supported by the CTL interface",
@@ -13,7 +13,7 @@ use farcaster_node::syncerd::types::{
 };
 use farcaster_node::syncerd::{runtime::Synclet, TaskId, TaskTarget};
 use farcaster_node::syncerd::{GetAddressBalance, SweepBitcoinAddress, TxFilter};
-use farcaster_node::ServiceId;
+use farcaster_node::{ServiceId, SyncerInstance};
 use microservices::ZMQ_CONTEXT;
 use ntest::timeout;
 use std::sync::mpsc::Receiver;
@@ -30,7 +30,8 @@ extern crate log;
 
 mod utils;
 
-const SOURCE1: ServiceId = ServiceId::Syncer(Blockchain::Bitcoin, Network::Local);
+const SOURCE1: ServiceId =
+    ServiceId::Syncer(Blockchain::Bitcoin, Network::Local, SyncerInstance::DEFAULT);
 
 /*
 These tests need to run serially, otherwise we cannot verify events based on the
@@ -429,6 +430,7 @@ fn bitcoin_syncer_transaction_test() {
             lifetime: blocks + 5,
             hash: txid_1.into(),
             confirmation_bound: 0,
+            finality: 0,
         }),
         source: SOURCE1.clone(),
     })
@@ -445,6 +447,7 @@ fn bitcoin_syncer_transaction_test() {
             lifetime: blocks + 5,
             hash: txid_1.into(),
             confirmation_bound: 2,
+            finality: 2,
         }),
         source: SOURCE1.clone(),
     })
@@ -487,6 +490,7 @@ fn bitcoin_syncer_transaction_test() {
             lifetime: blocks + 5,
             hash: address_txid.into(),
             confirmation_bound: 2,
+            finality: 2,
         }),
         source: SOURCE1.clone(),
     })
@@ -523,6 +527,7 @@ fn bitcoin_syncer_transaction_test() {
             lifetime: blocks + 5,
             hash: txid_2.into(),
             confirmation_bound: 2,
+            finality: 2,
         }),
         source: SOURCE1.clone(),
     })
@@ -533,6 +538,7 @@ fn bitcoin_syncer_transaction_test() {
             lifetime: blocks + 5,
             hash: txid_3.into(),
             confirmation_bound: 2,
+            finality: 2,
         }),
         source: SOURCE1.clone(),
     })
@@ -588,6 +594,7 @@ fn bitcoin_syncer_transaction_test() {
             lifetime: blocks + 5,
             hash: txid.into(),
             confirmation_bound: 2,
+            finality: 2,
         }),
         source: SOURCE1.clone(),
     })
@@ -646,6 +653,7 @@ fn bitcoin_syncer_abort_test() {
             lifetime: blocks + 10,
             hash: bitcoin::Txid::from_slice(&[0; 32]).unwrap().into(),
             confirmation_bound: 2,
+            finality: 2,
         }),
         source: SOURCE1.clone(),
     };
@@ -694,6 +702,7 @@ fn bitcoin_syncer_abort_test() {
             lifetime: blocks + 10,
             hash: bitcoin::Txid::from_slice(&[0; 32]).unwrap().into(),
             confirmation_bound: 2,
+            finality: 2,
         }),
         source: SOURCE1.clone(),
     };
@@ -709,6 +718,7 @@ fn bitcoin_syncer_abort_test() {
             lifetime: blocks + 10,
             hash: bitcoin::Txid::from_slice(&[0; 32]).unwrap().into(),
             confirmation_bound: 2,
+            finality: 2,
         }),
         source: SOURCE1.clone(),
     };
@@ -916,6 +926,7 @@ fn bitcoin_syncer_sweep_address_test() {
                 source_secret_key,
                 source_address: sweep_source_address.clone(),
                 destination_address: sweep_destination_address_1.clone(),
+                fee_sat_per_kvb: None,
             }),
         }),
         source: SOURCE1.clone(),
@@ -977,6 +988,8 @@ fn bitcoin_syncer_sweep_address_test() {
                 source_secret_key,
                 source_address: sweep_source_address,
                 destination_address: sweep_destination_address_2.clone(),
+                // sweep with a fixed fee rate instead of the estimation
+                fee_sat_per_kvb: Some(2000),
             }),
         }),
         source: SOURCE1.clone(),
@@ -1076,7 +1089,14 @@ fn create_bitcoin_syncer(socket_name: &str) -> (std::sync::mpsc::Sender<SyncerdT
     ]));
 
     syncer
-        .run(rx, tx_event, SOURCE1.clone().into(), &opts, Network::Local)
+        .run(
+            rx,
+            tx_event,
+            SOURCE1.clone().into(),
+            std::sync::mpsc::channel().0,
+            &opts,
+            Network::Local,
+        )
         .expect("Invalid Bitcoin syncer!");
     (tx, rx_event)
 }
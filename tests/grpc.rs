@@ -142,10 +142,12 @@ async fn grpc_server_functional_test() {
         monero_address: xmr_address.to_string(),
     };
     let request = tonic::Request::new(take_request.clone());
-    let response = farcaster_client_2.take(request).await;
-    assert_eq!(response.unwrap().into_inner().id, 5);
+    let response = farcaster_client_2.take(request).await.unwrap().into_inner();
+    assert_eq!(response.id, 5);
 
     let swap_id = retry_until_swap_id(&mut farcaster_client_2).await;
+    // the swap id returned when taking the deal is the id of the running swap
+    assert_eq!(response.swap_id, swap_id);
 
     // Test progress
     retry_until_progress(&mut farcaster_client_2, swap_id.clone()).await;
@@ -13,7 +13,7 @@ use farcaster_node::syncerd::{
     XmrAddressAddendum,
 };
 use farcaster_node::syncerd::{GetAddressBalance, TxFilter};
-use farcaster_node::ServiceId;
+use farcaster_node::{ServiceId, SyncerInstance};
 use microservices::ZMQ_CONTEXT;
 use monero_rpc::GetBlockHeaderSelector;
 use ntest::timeout;
@@ -32,8 +32,10 @@ extern crate log;
 
 mod utils;
 
-const SOURCE1: ServiceId = ServiceId::Syncer(Blockchain::Bitcoin, Network::Local);
-const SOURCE2: ServiceId = ServiceId::Syncer(Blockchain::Monero, Network::Local);
+const SOURCE1: ServiceId =
+    ServiceId::Syncer(Blockchain::Bitcoin, Network::Local, SyncerInstance::DEFAULT);
+const SOURCE2: ServiceId =
+    ServiceId::Syncer(Blockchain::Monero, Network::Local, SyncerInstance::DEFAULT);
 
 /*
 We test for the following scenarios in the block height tests:
@@ -612,6 +614,7 @@ async fn monero_syncer_transaction_test() {
             lifetime: blocks + 5,
             hash: txid_1,
             confirmation_bound: 2,
+            finality: 2,
         }),
         source: SOURCE1.clone(),
     })
@@ -647,6 +650,7 @@ async fn monero_syncer_transaction_test() {
             lifetime: block_height + 5,
             hash: tx_id2,
             confirmation_bound: 2,
+            finality: 2,
         }),
         source: SOURCE1.clone(),
     })
@@ -673,6 +677,7 @@ async fn monero_syncer_transaction_test() {
             lifetime: blocks + 5,
             hash: txid_2,
             confirmation_bound: 2,
+            finality: 2,
         }),
         source: SOURCE1.clone(),
     })
@@ -683,6 +688,7 @@ async fn monero_syncer_transaction_test() {
             lifetime: blocks + 5,
             hash: txid_3,
             confirmation_bound: 2,
+            finality: 2,
         }),
         source: SOURCE1.clone(),
     })
@@ -725,6 +731,7 @@ async fn monero_syncer_transaction_test() {
             lifetime: blocks + 5,
             hash: transaction.tx_hash.0.into(),
             confirmation_bound: 2,
+            finality: 2,
         }),
         source: SOURCE1.clone(),
     })
@@ -765,6 +772,24 @@ We test for the following scenarios in the abort tests:
 
 - Submit two WatchTransaction tasks, abort them both and receive both their aborted id's.
 */
+#[tokio::test]
+#[timeout(300000)]
+#[ignore]
+async fn monero_syncer_terminate_test() {
+    setup_logging();
+    let (tx, _rx_event, terminated) = create_monero_syncer_with_ack("terminate", false);
+
+    let task = SyncerdTask {
+        task: Task::Terminate,
+        source: SOURCE2.clone(),
+    };
+    tx.send(task).unwrap();
+    // the synclet acknowledges once its runtime stopped
+    terminated
+        .recv_timeout(std::time::Duration::from_secs(10))
+        .expect("monero synclet did not acknowledge termination");
+}
+
 #[tokio::test]
 #[timeout(300000)]
 #[ignore]
@@ -787,6 +812,7 @@ async fn monero_syncer_abort_test() {
             lifetime: blocks + 2,
             hash: zero_txid_hash,
             confirmation_bound: 2,
+            finality: 2,
         }),
         source: SOURCE2.clone(),
     };
@@ -833,6 +859,7 @@ async fn monero_syncer_abort_test() {
             lifetime: blocks + 10,
             hash: zero_txid_hash,
             confirmation_bound: 2,
+            finality: 2,
         }),
         source: SOURCE2.clone(),
     };
@@ -846,6 +873,7 @@ async fn monero_syncer_abort_test() {
             lifetime: blocks + 10,
             hash: zero_txid_hash,
             confirmation_bound: 2,
+            finality: 2,
         }),
         source: SOURCE2.clone(),
     };
@@ -959,12 +987,25 @@ fn create_monero_syncer(
     socket_name: &str,
     lws: bool,
 ) -> (std::sync::mpsc::Sender<SyncerdTask>, zmq::Socket) {
+    let (tx, rx_event, _) = create_monero_syncer_with_ack(socket_name, lws);
+    (tx, rx_event)
+}
+
+fn create_monero_syncer_with_ack(
+    socket_name: &str,
+    lws: bool,
+) -> (
+    std::sync::mpsc::Sender<SyncerdTask>,
+    zmq::Socket,
+    Receiver<()>,
+) {
     use rand::prelude::*;
     let mut rng = rand::thread_rng();
     let id: u64 = rng.gen();
     let addr = format!("inproc://testmonerobridge-{}-{}", socket_name, id);
 
     let (tx, rx): (Sender<SyncerdTask>, Receiver<SyncerdTask>) = std::sync::mpsc::channel();
+    let (terminated_tx, terminated_rx): (Sender<()>, Receiver<()>) = std::sync::mpsc::channel();
     let tx_event = ZMQ_CONTEXT.socket(zmq::PAIR).unwrap();
     let rx_event = ZMQ_CONTEXT.socket(zmq::PAIR).unwrap();
     tx_event.connect(&addr).unwrap();
@@ -994,9 +1035,16 @@ fn create_monero_syncer(
     );
 
     syncer
-        .run(rx, tx_event, SOURCE2.clone().into(), &opts, Network::Local)
+        .run(
+            rx,
+            tx_event,
+            SOURCE2.clone().into(),
+            terminated_tx,
+            &opts,
+            Network::Local,
+        )
         .expect("Invalid Monero syncer!");
-    (tx, rx_event)
+    (tx, rx_event, terminated_rx)
 }
 
 async fn new_address(wallet: &monero_rpc::WalletClient) -> (monero::Address, monero::PrivateKey) {
@@ -64,6 +64,51 @@ async fn swap_bob_maker_normal() {
     kill_all();
 }
 
+#[tokio::test]
+#[timeout(600000)]
+#[ignore]
+async fn swap_bob_maker_mock_syncers_reach_funding() {
+    setup_logging();
+    let (_, data_dir_maker, _, data_dir_taker) = launch_farcasterd_mock_pair();
+
+    let maker_info_args = info_args(data_dir_maker.clone());
+    let (stdout, _stderr) = run("../swap-cli", maker_info_args.clone()).unwrap();
+    let previous_swap_ids: HashSet<SwapId> =
+        cli_output_to_node_info(stdout).swaps.drain(..).collect();
+
+    let btc_addr = reusable_btc_address().to_string();
+    let xmr_addr = reusable_xmr_address().to_string();
+    let cli_make_args = make_deal_args(
+        data_dir_maker.clone(),
+        "Bob".to_string(),
+        btc_addr.clone(),
+        bitcoin::Amount::from_str("1 BTC").unwrap(),
+        xmr_addr.clone(),
+        monero::Amount::from_str_with_denomination("1 XMR").unwrap(),
+    );
+    run("../swap-cli", cli_make_args).unwrap();
+    let deals = retry_until_deal(maker_info_args.clone()).await;
+
+    let cli_take_args =
+        take_deal_args(data_dir_taker.clone(), btc_addr, xmr_addr, deals[0].clone());
+    run("../swap-cli", cli_take_args).unwrap();
+    let swap_id = retry_until_swap_id(maker_info_args, previous_swap_ids).await;
+
+    // the scripted heights and fee estimations are all the swapds need to set up the swap
+    retry_until_state_transition(
+        progress_args(data_dir_taker, swap_id),
+        "Taker Maker Commit".to_string(),
+    )
+    .await;
+    retry_until_bitcoin_funding_address(
+        swap_id,
+        needs_funding_args(data_dir_maker, "bitcoin".to_string()),
+    )
+    .await;
+
+    kill_all();
+}
+
 #[tokio::test]
 #[timeout(600000)]
 #[ignore]
@@ -2161,6 +2206,20 @@ async fn make_and_take_deal(
 
     let swap_id = retry_until_swap_id(taker_info_args.clone(), previous_swap_ids).await;
 
+    // the swap setup is only complete once both swapds exchanged their commitments, the taker
+    // receiving the maker commit implies the maker swapd is running
+    info!("waiting for the taker and maker commit stage");
+    retry_until_state_transition(
+        progress_args(data_dir_taker, swap_id),
+        "Taker Maker Commit".to_string(),
+    )
+    .await;
+    retry_until_state_transition(
+        progress_args(data_dir_maker, swap_id),
+        "Init Maker".to_string(),
+    )
+    .await;
+
     (xmr_address_wallet_name, btc_address, swap_id)
 }
 
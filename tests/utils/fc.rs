@@ -65,6 +65,33 @@ pub fn launch_farcasterd_taker() -> (process::Child, Vec<String>) {
     (farcasterd_taker, data_dir_taker)
 }
 
+/// Launch a maker and a taker whose syncers replay `tests/cfg/mock_scenario.yml` instead of
+/// querying the blockchain backends
+pub fn launch_farcasterd_mock_pair() -> (process::Child, Vec<String>, process::Child, Vec<String>) {
+    let data_dir_maker = vec!["-d".to_string(), "tests/fc1".to_string()];
+    let data_dir_taker = vec!["-d".to_string(), "tests/fc2".to_string()];
+
+    let farcasterd_maker_args = farcasterd_args(
+        data_dir_maker.clone(),
+        vec!["--config", "tests/cfg/fc1.mock.toml"],
+        vec![],
+    );
+    let farcasterd_taker_args = farcasterd_args(
+        data_dir_taker.clone(),
+        vec!["--config", "tests/cfg/fc2.mock.toml"],
+        vec![],
+    );
+
+    let farcasterd_maker = launch("../farcasterd", farcasterd_maker_args).unwrap();
+    let farcasterd_taker = launch("../farcasterd", farcasterd_taker_args).unwrap();
+    (
+        farcasterd_maker,
+        data_dir_maker,
+        farcasterd_taker,
+        data_dir_taker,
+    )
+}
+
 fn farcasterd_args(data_dir: Vec<String>, server_args: Vec<&str>, extra: Vec<&str>) -> Vec<String> {
     data_dir
         .into_iter()